@@ -1,27 +1,642 @@
-use quick_xml::events::{BytesDecl, BytesText};
+use std::borrow::Cow;
+use std::ops::Range;
+use std::str::from_utf8;
+use std::sync::Arc;
 
-use crate::node::Document;
+use quick_xml::events::{BytesCData, BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::Reader;
 
-pub(crate) struct DocumentBuilder {
-    _document: Document,
+use crate::chars::{is_whitespace, is_xml_char};
+use crate::dtd::{self, AttributeType, DefaultDecl, EntityValue};
+use crate::namespace::{declaration, split_qname, NamespaceScope};
+use crate::node::{
+    Attribute, CData, Comment, DocType, Document, Element, Node, NodeId, ProcessingInstruction,
+    QName, Text,
+};
+use crate::position::{LineIndex, Location};
+use crate::resolver::Resolver;
+use crate::{Error, ErrorKind, ParseOptions, Result};
+
+pub(crate) struct DocumentBuilder<'a> {
+    input: &'a str,
+    /// Present when locations are tracked.
+    lines: Option<LineIndex<'a>>,
+    source: Option<Arc<str>>,
+    /// The input span of the event being processed.
+    span: Range<usize>,
+    document: Document,
+    open_elements: Vec<NodeId>,
+    namespaces: NamespaceScope,
+    has_root: bool,
+    options: &'a ParseOptions,
+    /// Fetches the external DTD subset and external entities, when they
+    /// are to be read.
+    resolver: Option<&'a dyn Resolver>,
+    /// The general entities being expanded, innermost last.
+    entities: Vec<String>,
+    /// How many bytes of replacement text have been expanded so far.
+    expanded: usize,
+}
+
+/// What a general entity or character reference stands for.
+enum Replacement {
+    Char(char),
+    /// The replacement text of an internal entity, or the text of an
+    /// external one.
+    Text(String),
+    /// An external entity that is not read.
+    Skipped,
 }
 
-impl DocumentBuilder {
-    pub fn new() -> Self {
+impl<'a> DocumentBuilder<'a> {
+    pub fn new(
+        input: &'a str,
+        options: &'a ParseOptions,
+        resolver: Option<&'a dyn Resolver>,
+    ) -> Self {
         DocumentBuilder {
-            _document: Default::default(),
+            input,
+            lines: options.locations.then(|| LineIndex::new(input)),
+            source: options.source_uri.as_deref().map(Arc::from),
+            span: 0..0,
+            document: Default::default(),
+            open_elements: Vec::new(),
+            namespaces: NamespaceScope::new(),
+            has_root: false,
+            options,
+            resolver,
+            entities: Vec::new(),
+            expanded: 0,
+        }
+    }
+
+    pub fn build(self) -> Result<Document> {
+        if let Some(&open) = self.open_elements.last() {
+            let name = match &self.document.nodes[&open] {
+                Node::Element(element) => element.name.to_string(),
+                _ => String::new(),
+            };
+            return Err(ErrorKind::UnclosedElement(name).into());
+        }
+
+        if !self.has_root {
+            return Err(ErrorKind::MissingRootElement.into());
+        }
+
+        Ok(self.document)
+    }
+
+    /// Records the input span of the next event.
+    pub fn set_span(&mut self, span: Range<usize>) {
+        self.span = span;
+    }
+
+    /// Records the XML declaration, which may only open the document.
+    pub fn set_decl(&mut self, event: &BytesDecl) -> Result<()> {
+        if !self.input[..self.span.start]
+            .trim_start_matches('\u{feff}')
+            .is_empty()
+        {
+            return Err(ErrorKind::Syntax(
+                "the XML declaration must be at the start of the document".to_owned(),
+            )
+            .into());
+        }
+        self.document.decl = event.into();
+        Ok(())
+    }
+
+    /// Reads the content of the `<!DOCTYPE>` in the current span, which
+    /// the caller has delimited. It may only come once, before the root
+    /// element.
+    pub fn set_doctype(&mut self) -> Result<()> {
+        if self.has_root {
+            return Err(ErrorKind::Syntax(
+                "the document type declaration must come before the root element".to_owned(),
+            )
+            .into());
+        }
+        if self.document.dtd.is_some() {
+            return Err(ErrorKind::Syntax(
+                "a document has at most one document type declaration".to_owned(),
+            )
+            .into());
+        }
+        let content = &self.input[self.span.start + "<!DOCTYPE".len()..self.span.end - 1];
+        let text = content.trim_start_matches(is_whitespace);
+        let offset = self.span.end - 1 - text.len();
+        self.document.doc_type = DocType(text.to_owned());
+        let text = &self.document.doc_type.0;
+        let base = self.source.as_deref();
+        let resolver = self.resolver.filter(|_| self.options.external_dtd);
+        let dtd = dtd::parse_doctype(text, offset, self.input, base, resolver)?;
+        self.document.dtd = Some(dtd);
+        Ok(())
+    }
+
+    pub fn start_element(&mut self, event: &BytesStart) -> Result<()> {
+        let id = self.element(event)?;
+        self.open_elements.push(id);
+        Ok(())
+    }
+
+    pub fn empty_element(&mut self, event: &BytesStart) -> Result<()> {
+        self.element(event)?;
+        self.namespaces.pop();
+        Ok(())
+    }
+
+    pub fn end_element(&mut self, _event: &BytesEnd) -> Result<()> {
+        // quick-xml already checks that end names match their start tags.
+        let id = self.open_elements.pop();
+        if let Some(lines) = &self.lines {
+            let end = lines.position(self.span.end);
+            if let Some(Node::Element(element)) = id.and_then(|id| self.document.nodes.get_mut(&id))
+            {
+                if let Some(location) = &mut element.location {
+                    location.end = end;
+                }
+            }
+        }
+        self.namespaces.pop();
+        Ok(())
+    }
+
+    pub fn text(&mut self, event: &BytesText) -> Result<()> {
+        let raw = from_utf8(event)?;
+        self.check_chars(raw, 0)?;
+        if let Some(offset) = raw.find("]]>") {
+            let e = ErrorKind::Syntax("`]]>` in character data".to_owned()).into();
+            return Err(self.at_reference(e, offset));
+        }
+        if self.open_elements.is_empty() {
+            if raw.chars().all(is_whitespace) {
+                return Ok(());
+            }
+            return Err(ErrorKind::ContentOutsideRoot.into());
+        }
+
+        let mut data = String::new();
+        let mut rest = raw;
+        while let Some(start) = rest.find('&') {
+            data.push_str(&self.line_ends(&rest[..start]));
+            let offset = raw.len() - rest.len() + start;
+            let (reference, length) =
+                split_reference(&rest[start..]).map_err(|e| self.at_reference(e, offset))?;
+            let replacement = self
+                .replacement(reference, false)
+                .map_err(|e| self.at_reference(e, offset))?;
+            match replacement {
+                Replacement::Char(c) => data.push(c),
+                Replacement::Text(text) => {
+                    self.push_text(std::mem::take(&mut data));
+                    self.include(reference, &text)
+                        .map_err(|e| self.at_reference(e, offset))?;
+                }
+                Replacement::Skipped => {}
+            }
+            rest = &rest[start + length..];
+        }
+        data.push_str(&self.line_ends(rest));
+        self.push_text(data);
+        Ok(())
+    }
+
+    /// Normalizes the line ends of text read from the document. Entity
+    /// replacement text was normalized when it was read, so a `\r` in it
+    /// comes from a character reference and is kept.
+    fn line_ends<'t>(&self, text: &'t str) -> Cow<'t, str> {
+        match self.entities.is_empty() {
+            true => normalize_newlines(text),
+            false => Cow::Borrowed(text),
+        }
+    }
+
+    /// Adds character data to the open element, joining it to a text node
+    /// just before it.
+    fn push_text(&mut self, data: String) {
+        if data.is_empty() {
+            return;
+        }
+        let parent = self.current_parent();
+        let location = self.location();
+        let previous = parent
+            .and_then(|parent| self.document.nodes.get(&parent))
+            .and_then(|parent| parent.children().last().copied());
+        if let Some(Node::Text(text)) = previous.and_then(|id| self.document.nodes.get_mut(&id)) {
+            text.data.push_str(&data);
+            if let (Some(location), Some(end)) = (&mut text.location, location) {
+                location.end = end.end;
+            }
+            return;
         }
+        self.push(Node::Text(Text {
+            parent,
+            location,
+            data,
+        }));
     }
 
-    pub fn build(self) -> Document {
-        Document::default()
+    /// Positions an error at the reference `offset` bytes into the text
+    /// being read, unless the text is replacement text, whose errors are
+    /// positioned at the outermost reference.
+    fn at_reference(&self, e: Error, offset: usize) -> Error {
+        match self.entities.is_empty() {
+            true => e.at(self.input, self.span.start + offset),
+            false => e,
+        }
     }
 
-    pub fn set_decl(&mut self, event: &BytesDecl) {
-        self._document.decl = event.into();
+    /// Fails at the first character of `text` that XML does not allow;
+    /// `text` starts `offset` bytes into the event being read.
+    fn check_chars(&self, text: &str, offset: usize) -> Result<()> {
+        match text.char_indices().find(|&(_, c)| !is_xml_char(c)) {
+            Some((index, c)) => {
+                let e = ErrorKind::InvalidCharacter(c).into();
+                Err(self.at_reference(e, offset + index))
+            }
+            None => Ok(()),
+        }
     }
 
-    pub fn set_doctype(&mut self, event: &BytesText) {
-        self._document.doc_type = event.into();
+    /// What the reference `&reference;` stands for. Internal entities may
+    /// not refer to themselves, however indirectly, nor nest or expand
+    /// beyond the limits of the options. Attribute values may not refer to
+    /// external entities.
+    fn replacement(&mut self, reference: &str, in_attribute: bool) -> Result<Replacement> {
+        if let Some(code) = reference.strip_prefix('#') {
+            return char_reference(code).map(Replacement::Char);
+        }
+        match reference {
+            "lt" => return Ok(Replacement::Char('<')),
+            "gt" => return Ok(Replacement::Char('>')),
+            "amp" => return Ok(Replacement::Char('&')),
+            "apos" => return Ok(Replacement::Char('\'')),
+            "quot" => return Ok(Replacement::Char('"')),
+            _ => {}
+        }
+        let Some(entity) = self
+            .document
+            .dtd
+            .as_ref()
+            .and_then(|dtd| dtd.entity(reference))
+        else {
+            return Err(ErrorKind::UndeclaredEntity(reference.to_owned()).into());
+        };
+        if self.entities.iter().any(|name| name == reference) {
+            return Err(ErrorKind::RecursiveEntity(reference.to_owned()).into());
+        }
+        let limits = self.options.entity_limits;
+        if self.entities.len() >= limits.max_depth {
+            return Err(ErrorKind::EntityLimitExceeded(format!(
+                "entity references nest more than {} deep",
+                limits.max_depth
+            ))
+            .into());
+        }
+        let text = match &entity.value {
+            EntityValue::Internal(text) => text.clone(),
+            EntityValue::External {
+                notation: Some(_), ..
+            } => {
+                return Err(ErrorKind::Syntax(format!(
+                    "`&{};` refers to an unparsed entity",
+                    reference
+                ))
+                .into());
+            }
+            EntityValue::External { .. } if in_attribute => {
+                return Err(ErrorKind::Syntax(format!(
+                    "attribute value refers to external entity `&{};`",
+                    reference
+                ))
+                .into());
+            }
+            EntityValue::External {
+                public_id,
+                system_id,
+                ..
+            } => {
+                let Some(resolver) = self.resolver.filter(|_| self.options.external_entities)
+                else {
+                    return Ok(Replacement::Skipped);
+                };
+                let base = entity.base.as_deref().or(self.source.as_deref());
+                let resource = resolver.resolve_entity(base, public_id.as_deref(), system_id)?;
+                normalize_newlines(resource.text()?).into_owned()
+            }
+        };
+        self.expanded += text.len();
+        if self.expanded > limits.max_expansion {
+            return Err(ErrorKind::EntityLimitExceeded(format!(
+                "entities expand to more than {} bytes",
+                limits.max_expansion
+            ))
+            .into());
+        }
+        Ok(Replacement::Text(text))
     }
+
+    /// Reads the replacement text of the entity `name` as content of the
+    /// open element. The elements it starts must end within it.
+    fn include(&mut self, name: &str, text: &str) -> Result<()> {
+        self.entities.push(name.to_owned());
+        let depth = self.open_elements.len();
+        let mut reader = Reader::from_str(text);
+        let mut first = true;
+        loop {
+            match reader.read_event()? {
+                Event::Eof => break,
+                // The text declaration of an external entity.
+                Event::Decl(_) if first => {}
+                Event::Decl(_) | Event::DocType(_) => {
+                    return Err(ErrorKind::Syntax(format!(
+                        "entity `{}` contains a declaration",
+                        name
+                    ))
+                    .into());
+                }
+                Event::Start(e) => self.start_element(&e)?,
+                Event::Empty(e) => self.empty_element(&e)?,
+                Event::End(e) => self.end_element(&e)?,
+                Event::Text(e) => self.text(&e)?,
+                Event::CData(e) => self.cdata(&e)?,
+                Event::Comment(e) => self.comment(&e)?,
+                Event::PI(e) => self.processing_instruction(&e)?,
+            }
+            first = false;
+        }
+        if self.open_elements.len() != depth {
+            return Err(
+                ErrorKind::Syntax(format!("entity `{}` leaves an element open", name)).into(),
+            );
+        }
+        self.entities.pop();
+        Ok(())
+    }
+
+    /// The value of the attribute `name` of the element `element`,
+    /// normalized as XML 1.0 prescribes: references are replaced, white
+    /// space becomes spaces and, for attributes declared with a type
+    /// other than CDATA, runs of spaces collapse and the ends are trimmed.
+    fn attribute_value(&mut self, element: &str, name: &str, raw: &str) -> Result<String> {
+        let mut value = String::new();
+        self.append_attribute_text(raw, &mut value)?;
+        let tokenized = self
+            .document
+            .dtd
+            .as_ref()
+            .and_then(|dtd| dtd.attribute(element, name))
+            .is_some_and(|attribute| attribute.kind != AttributeType::CData);
+        if tokenized {
+            value = value
+                .split(' ')
+                .filter(|token| !token.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+        Ok(value)
+    }
+
+    fn append_attribute_text(&mut self, raw: &str, value: &mut String) -> Result<()> {
+        let mut rest = raw;
+        while let Some(start) = rest.find(['&', '<', '\r', '\n', '\t']) {
+            value.push_str(&rest[..start]);
+            rest = &rest[start..];
+            if rest.starts_with('<') {
+                return Err(ErrorKind::Syntax("`<` in an attribute value".to_owned()).into());
+            }
+            if !rest.starts_with('&') {
+                // A line end, `\r\n` included, or a tab.
+                value.push(' ');
+                let length = match rest.starts_with("\r\n") {
+                    true => 2,
+                    false => 1,
+                };
+                rest = &rest[length..];
+                continue;
+            }
+            let (reference, length) = split_reference(rest)?;
+            match self.replacement(reference, true)? {
+                Replacement::Char(c) => value.push(c),
+                Replacement::Text(text) => {
+                    self.entities.push(reference.to_owned());
+                    self.append_attribute_text(&text, value)?;
+                    self.entities.pop();
+                }
+                Replacement::Skipped => {}
+            }
+            rest = &rest[length..];
+        }
+        value.push_str(rest);
+        Ok(())
+    }
+
+    pub fn cdata(&mut self, event: &BytesCData) -> Result<()> {
+        if self.open_elements.is_empty() {
+            return Err(ErrorKind::ContentOutsideRoot.into());
+        }
+
+        let parent = self.current_parent();
+        let data = from_utf8(event)?;
+        self.check_chars(data, "<![CDATA[".len())?;
+        let data = self.line_ends(data).into_owned();
+        let location = self.location();
+        self.push(Node::CData(CData {
+            parent,
+            location,
+            data,
+        }));
+        Ok(())
+    }
+
+    pub fn comment(&mut self, event: &BytesText) -> Result<()> {
+        let parent = self.current_parent();
+        let data = from_utf8(event)?;
+        self.check_chars(data, "<!--".len())?;
+        let data = self.line_ends(data).into_owned();
+        let location = self.location();
+        self.push(Node::Comment(Comment {
+            parent,
+            location,
+            data,
+        }));
+        Ok(())
+    }
+
+    pub fn processing_instruction(&mut self, event: &BytesText) -> Result<()> {
+        let parent = self.current_parent();
+        let data = from_utf8(event)?;
+        self.check_chars(data, "<?".len())?;
+        let data = self.line_ends(data).into_owned();
+        let location = self.location();
+        self.push(Node::ProcessingInstruction(ProcessingInstruction {
+            parent,
+            location,
+            data,
+        }));
+        Ok(())
+    }
+
+    fn element(&mut self, event: &BytesStart) -> Result<NodeId> {
+        let parent = self.current_parent();
+        if parent.is_none() {
+            if self.has_root {
+                return Err(ErrorKind::MultipleRootElements.into());
+            }
+            self.has_root = true;
+        }
+
+        let qname = event.name();
+        let element_name = from_utf8(qname.as_ref())?;
+        let mut values = Vec::new();
+        for attribute in event.attributes() {
+            let attribute = attribute.map_err(quick_xml::Error::from)?;
+            let name = from_utf8(attribute.key.as_ref())?.to_owned();
+            let value = self.attribute_value(element_name, &name, from_utf8(&attribute.value)?)?;
+            values.push((name, value));
+        }
+        // Attributes the DTD gives a default value to and the tag omits.
+        let defaults: Vec<_> = self
+            .document
+            .dtd
+            .iter()
+            .filter_map(|dtd| dtd.attributes.get(element_name))
+            .flatten()
+            .filter(|attribute| values.iter().all(|(name, _)| *name != attribute.name))
+            .filter_map(|attribute| match &attribute.default {
+                DefaultDecl::Fixed(value) | DefaultDecl::Default(value) => {
+                    Some((attribute.name.clone(), value.clone()))
+                }
+                DefaultDecl::Required | DefaultDecl::Implied => None,
+            })
+            .collect();
+        for (name, raw) in defaults {
+            let value = self.attribute_value(element_name, &name, &raw)?;
+            values.push((name, value));
+        }
+
+        let mut raw_attributes = Vec::new();
+        let mut namespaces = Vec::new();
+        for (name, value) in values {
+            match declaration(&name, &value)? {
+                Some(namespace) => namespaces.push(namespace),
+                None => raw_attributes.push((name, value)),
+            }
+        }
+        self.namespaces.push(namespaces.clone());
+
+        let name = self.resolve(from_utf8(event.name().as_ref())?, true)?;
+        let mut attributes: Vec<Attribute> = Vec::with_capacity(raw_attributes.len());
+        for (name, value) in raw_attributes {
+            let name = self.resolve(&name, false)?;
+            if attributes
+                .iter()
+                .any(|other| other.name.is(name.namespace.as_deref(), &name.local_name))
+            {
+                return Err(ErrorKind::DuplicateAttribute(name.to_string()).into());
+            }
+            attributes.push(Attribute { name, value });
+        }
+
+        let location = self.location();
+        let id = self.push(Node::Element(Element {
+            parent,
+            location,
+            name,
+            namespaces,
+            attributes,
+            children: Vec::new(),
+        }));
+        if parent.is_none() {
+            self.document.root = id;
+        }
+        Ok(id)
+    }
+
+    /// Resolves a lexical QName against the namespaces in scope. Unprefixed
+    /// attribute names never take the default namespace.
+    fn resolve(&self, name: &str, is_element: bool) -> Result<QName> {
+        let (prefix, local_name) = split_qname(name)?;
+        let namespace = match (prefix, is_element) {
+            (None, false) => None,
+            _ => self.namespaces.resolve(prefix)?,
+        };
+
+        Ok(QName {
+            prefix: prefix.map(str::to_owned),
+            local_name: local_name.to_owned(),
+            namespace,
+        })
+    }
+
+    /// The location of the current event, when locations are tracked.
+    fn location(&self) -> Option<Location> {
+        self.lines.as_ref().map(|lines| Location {
+            source: self.source.clone(),
+            start: lines.position(self.span.start),
+            end: lines.position(self.span.end),
+        })
+    }
+
+    fn current_parent(&self) -> Option<NodeId> {
+        self.open_elements.last().copied()
+    }
+
+    /// Inserts `node` and links it into its parent's children, or into the
+    /// document's top-level nodes when it has no parent.
+    fn push(&mut self, node: Node) -> NodeId {
+        let parent = node.parent();
+        let id = self.document.add_node(node);
+
+        let index = match parent {
+            Some(parent) => match self.document.nodes.get_mut(&parent) {
+                Some(Node::Element(element)) => {
+                    element.children.push(id);
+                    element.children.len() - 1
+                }
+                _ => return id,
+            },
+            None => {
+                self.document.children.push(id);
+                self.document.children.len() - 1
+            }
+        };
+        self.document.positions.insert(id, index);
+
+        id
+    }
+}
+
+/// Translates `\r\n` and a lone `\r` to `\n`, as XML 1.0 §2.11 asks of a
+/// parser before it reads the text any further.
+fn normalize_newlines(text: &str) -> Cow<'_, str> {
+    match text.contains('\r') {
+        true => Cow::Owned(text.replace("\r\n", "\n").replace('\r', "\n")),
+        false => Cow::Borrowed(text),
+    }
+}
+
+/// Splits the reference at the start of `text`, `&name;` or `&#code;`,
+/// into what is between `&` and `;` and its length.
+fn split_reference(text: &str) -> Result<(&str, usize)> {
+    match text.find(';') {
+        Some(end) if end > 1 => Ok((&text[1..end], end + 1)),
+        _ => Err(ErrorKind::Syntax("`&` does not start a reference".to_owned()).into()),
+    }
+}
+
+/// The character of the reference `&#code;`.
+fn char_reference(code: &str) -> Result<char> {
+    let value = match code.strip_prefix('x') {
+        Some(hex) if hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            u32::from_str_radix(hex, 16).ok()
+        }
+        _ if code.chars().all(|c| c.is_ascii_digit()) => code.parse().ok(),
+        _ => None,
+    };
+    value
+        .and_then(char::from_u32)
+        .filter(|&c| is_xml_char(c))
+        .ok_or_else(|| ErrorKind::InvalidCharacterReference(format!("&#{};", code)).into())
 }
@@ -0,0 +1,70 @@
+//! Character classes from the XML 1.0 (fifth edition) grammar.
+
+/// `S`: space, tab, carriage return or line feed.
+pub fn is_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\r' | '\n')
+}
+
+/// `Char`: any character allowed in an XML document.
+pub fn is_xml_char(c: char) -> bool {
+    matches!(c,
+        '\t' | '\n' | '\r'
+        | '\u{20}'..='\u{D7FF}'
+        | '\u{E000}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{10FFFF}')
+}
+
+/// `NameStartChar`.
+pub fn is_name_start_char(c: char) -> bool {
+    matches!(c,
+        ':' | 'A'..='Z' | '_' | 'a'..='z'
+        | '\u{C0}'..='\u{D6}'
+        | '\u{D8}'..='\u{F6}'
+        | '\u{F8}'..='\u{2FF}'
+        | '\u{370}'..='\u{37D}'
+        | '\u{37F}'..='\u{1FFF}'
+        | '\u{200C}'..='\u{200D}'
+        | '\u{2070}'..='\u{218F}'
+        | '\u{2C00}'..='\u{2FEF}'
+        | '\u{3001}'..='\u{D7FF}'
+        | '\u{F900}'..='\u{FDCF}'
+        | '\u{FDF0}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{EFFFF}')
+}
+
+/// `NameChar`.
+pub fn is_name_char(c: char) -> bool {
+    is_name_start_char(c)
+        || matches!(c,
+            '-' | '.' | '0'..='9'
+            | '\u{B7}'
+            | '\u{300}'..='\u{36F}'
+            | '\u{203F}'..='\u{2040}')
+}
+
+/// `NCName` start character: a `NameStartChar` other than `:`.
+pub fn is_ncname_start_char(c: char) -> bool {
+    c != ':' && is_name_start_char(c)
+}
+
+/// `NCName` character: a `NameChar` other than `:`.
+pub fn is_ncname_char(c: char) -> bool {
+    c != ':' && is_name_char(c)
+}
+
+/// Returns `true` when `name` matches the `Name` production.
+pub fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(is_name_start_char) && chars.all(is_name_char)
+}
+
+/// Returns `true` when `token` matches the `Nmtoken` production.
+pub fn is_nmtoken(token: &str) -> bool {
+    !token.is_empty() && token.chars().all(is_name_char)
+}
+
+/// Returns `true` when `name` matches the `NCName` production.
+pub fn is_ncname(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(is_ncname_start_char) && chars.all(is_ncname_char)
+}
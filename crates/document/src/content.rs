@@ -0,0 +1,271 @@
+//! Content models of elements, matched one child at a time by taking
+//! derivatives. DTD and XML Schema validation both compile their models to
+//! these terms; what a leaf stands for, an element name or a particle, is
+//! up to them.
+
+use std::collections::{BTreeSet, HashMap};
+
+/// The index of an interned term.
+pub type Id = usize;
+
+pub const EMPTY: Id = 0;
+pub const NOT_ALLOWED: Id = 1;
+
+/// A content model as the derivative algorithm sees it. Terms are
+/// interned, so equal models share an id and derivatives can be cached by
+/// id. Choices are kept as chains of distinct alternatives in id order and
+/// groups are nested to the right, so that models differing only in how
+/// they are bracketed share an id too, and a model has finitely many
+/// derivatives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Term {
+    Empty,
+    NotAllowed,
+    /// What a child element may match, by an index the caller assigns.
+    Leaf(usize),
+    Choice(Id, Id),
+    Group(Id, Id),
+    /// The particles of an `all` group, in any order.
+    Interleave(Id, Id),
+    /// A term with the least and greatest number of times it may still
+    /// occur, `None` for unbounded.
+    Repeat(Id, u32, Option<u32>),
+}
+
+/// The terms of a set of content models, which share their subterms and
+/// derivatives.
+#[derive(Debug, Clone)]
+pub struct Terms {
+    terms: Vec<Term>,
+    nullable: Vec<bool>,
+    ids: HashMap<Term, Id>,
+    derivatives: HashMap<(Id, usize), Id>,
+    firsts: HashMap<Id, BTreeSet<usize>>,
+}
+
+impl Default for Terms {
+    fn default() -> Self {
+        Terms::new()
+    }
+}
+
+impl Terms {
+    pub fn new() -> Terms {
+        let mut terms = Terms {
+            terms: Vec::new(),
+            nullable: Vec::new(),
+            ids: HashMap::new(),
+            derivatives: HashMap::new(),
+            firsts: HashMap::new(),
+        };
+        terms.intern(Term::Empty);
+        terms.intern(Term::NotAllowed);
+        terms
+    }
+
+    /// Whether the model matches the empty sequence of elements.
+    pub fn nullable(&self, id: Id) -> bool {
+        self.nullable[id]
+    }
+
+    fn intern(&mut self, term: Term) -> Id {
+        if let Some(&id) = self.ids.get(&term) {
+            return id;
+        }
+        let nullable = match term {
+            Term::Empty => true,
+            Term::Choice(a, b) => self.nullable[a] || self.nullable[b],
+            Term::Group(a, b) | Term::Interleave(a, b) => self.nullable[a] && self.nullable[b],
+            Term::Repeat(p, min, _) => min == 0 || self.nullable[p],
+            _ => false,
+        };
+        let id = self.terms.len();
+        self.terms.push(term);
+        self.nullable.push(nullable);
+        self.ids.insert(term, id);
+        id
+    }
+
+    pub fn leaf(&mut self, leaf: usize) -> Id {
+        self.intern(Term::Leaf(leaf))
+    }
+
+    pub fn choice(&mut self, a: Id, b: Id) -> Id {
+        let mut alternatives = BTreeSet::new();
+        for mut p in [a, b] {
+            while let Term::Choice(first, rest) = self.terms[p] {
+                alternatives.insert(first);
+                p = rest;
+            }
+            alternatives.insert(p);
+        }
+        alternatives.remove(&NOT_ALLOWED);
+        let mut alternatives = alternatives.into_iter().rev();
+        let Some(last) = alternatives.next() else {
+            return NOT_ALLOWED;
+        };
+        alternatives.fold(last, |rest, p| self.intern(Term::Choice(p, rest)))
+    }
+
+    pub fn group(&mut self, a: Id, b: Id) -> Id {
+        match (a, b) {
+            (NOT_ALLOWED, _) | (_, NOT_ALLOWED) => NOT_ALLOWED,
+            (EMPTY, p) | (p, EMPTY) => p,
+            (a, b) => match self.terms[a] {
+                Term::Group(first, rest) => {
+                    let rest = self.group(rest, b);
+                    self.group(first, rest)
+                }
+                _ => self.intern(Term::Group(a, b)),
+            },
+        }
+    }
+
+    pub fn interleave(&mut self, a: Id, b: Id) -> Id {
+        match (a, b) {
+            (NOT_ALLOWED, _) | (_, NOT_ALLOWED) => NOT_ALLOWED,
+            (EMPTY, p) | (p, EMPTY) => p,
+            (a, b) => self.intern(Term::Interleave(a.min(b), a.max(b))),
+        }
+    }
+
+    pub fn repeat(&mut self, p: Id, min: u32, max: Option<u32>) -> Id {
+        match (p, min, max) {
+            (_, _, Some(0)) | (EMPTY, _, _) => EMPTY,
+            (NOT_ALLOWED, 0, _) => EMPTY,
+            (NOT_ALLOWED, _, _) => NOT_ALLOWED,
+            (p, 1, Some(1)) => p,
+            (p, min, max) => self.intern(Term::Repeat(p, min, max)),
+        }
+    }
+
+    /// The model that matches what may follow an element attributed to
+    /// `leaf`.
+    pub fn derive(&mut self, p: Id, leaf: usize) -> Id {
+        if let Some(&derivative) = self.derivatives.get(&(p, leaf)) {
+            return derivative;
+        }
+        let derivative = match self.terms[p] {
+            Term::Empty | Term::NotAllowed => NOT_ALLOWED,
+            Term::Leaf(other) if other == leaf => EMPTY,
+            Term::Leaf(_) => NOT_ALLOWED,
+            Term::Choice(a, b) => {
+                let a = self.derive(a, leaf);
+                let b = self.derive(b, leaf);
+                self.choice(a, b)
+            }
+            Term::Group(a, b) => {
+                let first = self.derive(a, leaf);
+                let first = self.group(first, b);
+                match self.nullable(a) {
+                    true => {
+                        let second = self.derive(b, leaf);
+                        self.choice(first, second)
+                    }
+                    false => first,
+                }
+            }
+            Term::Interleave(a, b) => {
+                let first = self.derive(a, leaf);
+                let first = self.interleave(first, b);
+                let second = self.derive(b, leaf);
+                let second = self.interleave(a, second);
+                self.choice(first, second)
+            }
+            Term::Repeat(q, min, max) => {
+                let first = self.derive(q, leaf);
+                let rest = self.repeat(q, min.saturating_sub(1), max.map(|max| max - 1));
+                self.group(first, rest)
+            }
+        };
+        self.derivatives.insert((p, leaf), derivative);
+        derivative
+    }
+
+    /// The leaves an element may be attributed to next.
+    pub fn first(&mut self, p: Id) -> BTreeSet<usize> {
+        if let Some(leaves) = self.firsts.get(&p) {
+            return leaves.clone();
+        }
+        let leaves = match self.terms[p] {
+            Term::Empty | Term::NotAllowed => BTreeSet::new(),
+            Term::Leaf(leaf) => BTreeSet::from([leaf]),
+            Term::Choice(a, b) | Term::Interleave(a, b) => {
+                let mut leaves = self.first(a);
+                leaves.extend(self.first(b));
+                leaves
+            }
+            Term::Group(a, b) => {
+                let mut leaves = self.first(a);
+                if self.nullable(a) {
+                    leaves.extend(self.first(b));
+                }
+                leaves
+            }
+            Term::Repeat(q, _, _) => self.first(q),
+        };
+        self.firsts.insert(p, leaves.clone());
+        leaves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::content::{Terms, EMPTY, NOT_ALLOWED};
+
+    #[test]
+    fn counts_occurrences() {
+        let mut terms = Terms::new();
+        let a = terms.leaf(0);
+        let b = terms.leaf(1);
+        let a = terms.repeat(a, 2, Some(3));
+        let model = terms.group(a, b);
+
+        let mut p = model;
+        for _ in 0..2 {
+            assert_eq!(terms.first(p).into_iter().collect::<Vec<_>>(), [0]);
+            p = terms.derive(p, 0);
+        }
+        assert_eq!(terms.first(p).into_iter().collect::<Vec<_>>(), [0, 1]);
+        p = terms.derive(p, 0);
+        assert_eq!(terms.derive(p, 0), NOT_ALLOWED);
+        assert_eq!(terms.derive(p, 1), EMPTY);
+    }
+
+    #[test]
+    fn interleaves_all_groups() {
+        let mut terms = Terms::new();
+        let a = terms.leaf(0);
+        let b = terms.leaf(1);
+        let b = terms.repeat(b, 0, Some(1));
+        let model = terms.interleave(a, b);
+
+        assert!(!terms.nullable(model));
+        let p = terms.derive(model, 1);
+        assert_eq!(terms.derive(p, 1), NOT_ALLOWED);
+        let p = terms.derive(p, 0);
+        assert!(terms.nullable(p));
+        let p = terms.derive(model, 0);
+        assert!(terms.nullable(p));
+    }
+
+    #[test]
+    fn normalizes_choices_and_groups() {
+        let mut terms = Terms::new();
+        let [a, b, c] = [0, 1, 2].map(|leaf| terms.leaf(leaf));
+
+        let ab = terms.choice(a, b);
+        let left = terms.choice(ab, c);
+        let bc = terms.choice(c, b);
+        let right = terms.choice(bc, a);
+        assert_eq!(left, right);
+        assert_eq!(terms.choice(left, b), left);
+        assert_eq!(terms.choice(left, NOT_ALLOWED), left);
+
+        let ab = terms.group(a, b);
+        let left = terms.group(ab, c);
+        let bc = terms.group(b, c);
+        assert_eq!(terms.group(a, bc), left);
+        assert_ne!(terms.group(c, ab), left);
+    }
+}
@@ -0,0 +1,441 @@
+use crate::node::{
+    CData, Comment, Document, Element, Node, NodeId, ProcessingInstruction, QName, Text,
+};
+use crate::{ErrorKind, Result};
+
+impl Document {
+    /// Creates a document whose only node is an empty document element.
+    pub fn new(root: QName) -> Document {
+        let mut document = Document::default();
+        let id = document.create_element(root);
+        document.root = id;
+        document.children.push(id);
+        document.positions.insert(id, 0);
+        document
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(&id)
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(&id)
+    }
+
+    pub fn element(&self, id: NodeId) -> Option<&Element> {
+        self.nodes.get(&id).and_then(Node::as_element)
+    }
+
+    pub fn element_mut(&mut self, id: NodeId) -> Option<&mut Element> {
+        match self.nodes.get_mut(&id) {
+            Some(Node::Element(element)) => Some(element),
+            _ => None,
+        }
+    }
+
+    /// Inserts `node` under a fresh id without linking it into the tree.
+    pub fn add_node(&mut self, node: Node) -> NodeId {
+        let next = self.nodes.last_key_value().map_or(0, |(id, _)| id + 1);
+        let id = self.next_id.max(next);
+        self.next_id = id + 1;
+        self.nodes.insert(id, node);
+        id
+    }
+
+    /// Creates a detached element without attributes or children.
+    pub fn create_element(&mut self, name: QName) -> NodeId {
+        self.add_node(Node::Element(Element {
+            parent: None,
+            location: None,
+            name,
+            namespaces: Vec::new(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+        }))
+    }
+
+    pub fn create_text(&mut self, data: &str) -> NodeId {
+        self.add_node(Node::Text(Text {
+            parent: None,
+            location: None,
+            data: data.to_owned(),
+        }))
+    }
+
+    pub fn create_cdata(&mut self, data: &str) -> NodeId {
+        self.add_node(Node::CData(CData {
+            parent: None,
+            location: None,
+            data: data.to_owned(),
+        }))
+    }
+
+    pub fn create_comment(&mut self, data: &str) -> NodeId {
+        self.add_node(Node::Comment(Comment {
+            parent: None,
+            location: None,
+            data: data.to_owned(),
+        }))
+    }
+
+    pub fn create_processing_instruction(&mut self, data: &str) -> NodeId {
+        self.add_node(Node::ProcessingInstruction(ProcessingInstruction {
+            parent: None,
+            location: None,
+            data: data.to_owned(),
+        }))
+    }
+
+    /// Appends `child` as the last child of `parent`, moving it from wherever
+    /// it was attached before.
+    pub fn append_child(&mut self, parent: NodeId, child: NodeId) -> Result<()> {
+        self.check_insertion(parent, child)?;
+        self.detach(child)?;
+        let children = self.children_mut(parent)?;
+        children.push(child);
+        let index = children.len() - 1;
+        self.positions.insert(child, index);
+        self.set_parent(child, Some(parent))
+    }
+
+    /// Inserts `child` into `parent` just before its child `reference`,
+    /// moving it from wherever it was attached before.
+    pub fn insert_before(
+        &mut self,
+        parent: NodeId,
+        child: NodeId,
+        reference: NodeId,
+    ) -> Result<()> {
+        self.check_insertion(parent, child)?;
+        if child == reference {
+            return Ok(());
+        }
+        if !self.children_of(parent)?.contains(&reference) {
+            return Err(
+                ErrorKind::HierarchyRequest("reference node is not a child of the parent").into(),
+            );
+        }
+
+        self.detach(child)?;
+        let children = self.children_mut(parent)?;
+        let index = children
+            .iter()
+            .position(|&id| id == reference)
+            .unwrap_or(children.len());
+        children.insert(index, child);
+        self.reindex(Some(parent));
+        self.set_parent(child, Some(parent))
+    }
+
+    /// Adds a comment or processing instruction at the top level of the
+    /// document, before `reference` or at the end.
+    pub fn insert_top_level(&mut self, child: NodeId, reference: Option<NodeId>) -> Result<()> {
+        match self.node(child) {
+            Some(Node::Comment(_)) | Some(Node::ProcessingInstruction(_)) => {}
+            Some(_) => {
+                return Err(ErrorKind::HierarchyRequest(
+                    "only comments and processing instructions can be top-level siblings",
+                )
+                .into())
+            }
+            None => return Err(ErrorKind::UnknownNode(child).into()),
+        }
+        if let Some(reference) = reference {
+            if !self.children.contains(&reference) {
+                return Err(
+                    ErrorKind::HierarchyRequest("reference node is not a top-level node").into(),
+                );
+            }
+        }
+
+        self.detach(child)?;
+        let index = reference
+            .and_then(|reference| self.children.iter().position(|&id| id == reference))
+            .unwrap_or(self.children.len());
+        self.children.insert(index, child);
+        self.reindex(None);
+        Ok(())
+    }
+
+    /// Makes the detached or attached element `id` the document element,
+    /// taking the place of the current one, which is left detached.
+    pub fn set_root_element(&mut self, id: NodeId) -> Result<()> {
+        if self.element(id).is_none() {
+            return Err(
+                ErrorKind::HierarchyRequest("the document element must be an element").into(),
+            );
+        }
+        if id == self.root && self.children.contains(&id) {
+            return Ok(());
+        }
+
+        let old_root = self.root;
+        let old_index = self.children.iter().position(|&child| child == old_root);
+        if let Some(index) = old_index {
+            self.children[index] = id;
+        }
+
+        // Unlink from the former parent only after taking the old root's
+        // place, so promoting one of its descendants keeps that position.
+        let parent = self.node(id).and_then(Node::parent);
+        if let Some(parent) = parent {
+            self.children_mut(parent)?.retain(|&child| child != id);
+            self.reindex(Some(parent));
+            self.set_parent(id, None)?;
+        }
+        if old_index.is_none() {
+            self.children.push(id);
+        }
+        self.reindex(None);
+
+        self.root = id;
+        Ok(())
+    }
+
+    /// Unlinks `id` from its parent. The node and its subtree stay in the
+    /// document and can be inserted again. The document element cannot be
+    /// detached; replace it with [`Document::set_root_element`] instead.
+    pub fn detach(&mut self, id: NodeId) -> Result<()> {
+        let node = self.node(id).ok_or(ErrorKind::UnknownNode(id))?;
+        match node.parent() {
+            Some(parent) => {
+                self.children_mut(parent)?.retain(|&child| child != id);
+                self.reindex(Some(parent));
+                self.set_parent(id, None)
+            }
+            None if id == self.root && self.children.contains(&id) => {
+                Err(ErrorKind::HierarchyRequest("the document element cannot be detached").into())
+            }
+            None => {
+                self.children.retain(|&child| child != id);
+                self.reindex(None);
+                Ok(())
+            }
+        }
+    }
+
+    /// Detaches `id` and deletes it together with all of its descendants.
+    pub fn remove(&mut self, id: NodeId) -> Result<()> {
+        self.detach(id)?;
+
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            self.positions.remove(&id);
+            if let Some(node) = self.nodes.remove(&id) {
+                pending.extend_from_slice(node.children());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copies `id` and all of its descendants under fresh ids. The copy is
+    /// detached.
+    pub fn clone_subtree(&mut self, id: NodeId) -> Result<NodeId> {
+        self.clone_node(id, None)
+    }
+
+    fn clone_node(&mut self, id: NodeId, parent: Option<NodeId>) -> Result<NodeId> {
+        let mut node = self.node(id).ok_or(ErrorKind::UnknownNode(id))?.clone();
+        node.set_parent(parent);
+        let children = match &mut node {
+            Node::Element(element) => std::mem::take(&mut element.children),
+            _ => Vec::new(),
+        };
+
+        let copy = self.add_node(node);
+        for child in children {
+            let child = self.clone_node(child, Some(copy))?;
+            let children = self.children_mut(copy)?;
+            children.push(child);
+            let index = children.len() - 1;
+            self.positions.insert(child, index);
+        }
+
+        Ok(copy)
+    }
+
+    /// Checks that `child` may become a child of `parent` without creating a
+    /// cycle.
+    fn check_insertion(&self, parent: NodeId, child: NodeId) -> Result<()> {
+        if self.element(parent).is_none() {
+            return match self.node(parent) {
+                Some(_) => {
+                    Err(ErrorKind::HierarchyRequest("only elements can have children").into())
+                }
+                None => Err(ErrorKind::UnknownNode(parent).into()),
+            };
+        }
+        if !self.nodes.contains_key(&child) {
+            return Err(ErrorKind::UnknownNode(child).into());
+        }
+
+        let mut current = Some(parent);
+        while let Some(id) = current {
+            if id == child {
+                return Err(ErrorKind::HierarchyRequest(
+                    "a node cannot be inserted into its own subtree",
+                )
+                .into());
+            }
+            current = self.node(id).and_then(Node::parent);
+        }
+
+        if child == self.root && self.children.contains(&child) {
+            return Err(ErrorKind::HierarchyRequest("the document element cannot be moved").into());
+        }
+
+        Ok(())
+    }
+
+    fn children_of(&self, parent: NodeId) -> Result<&Vec<NodeId>> {
+        self.element(parent)
+            .map(|element| &element.children)
+            .ok_or_else(|| ErrorKind::UnknownNode(parent).into())
+    }
+
+    fn children_mut(&mut self, parent: NodeId) -> Result<&mut Vec<NodeId>> {
+        self.element_mut(parent)
+            .map(|element| &mut element.children)
+            .ok_or_else(|| ErrorKind::UnknownNode(parent).into())
+    }
+
+    /// Records the index of every node in the children of `parent`, or in
+    /// the top-level list.
+    fn reindex(&mut self, parent: Option<NodeId>) {
+        let children = match parent {
+            Some(parent) => self.nodes.get(&parent).map_or(&[][..], Node::children),
+            None => &self.children,
+        };
+        for (index, &id) in children.iter().enumerate() {
+            self.positions.insert(id, index);
+        }
+    }
+
+    fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) -> Result<()> {
+        self.node_mut(id)
+            .ok_or(ErrorKind::UnknownNode(id))?
+            .set_parent(parent);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::node::{Document, Node, QName};
+    use crate::writer::WriteOptions;
+    use crate::{Error, ErrorKind};
+
+    fn xml(document: &Document) -> String {
+        document.serialize(&WriteOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn builds_and_edits_trees() -> Result<(), Error> {
+        let mut document = Document::new(QName::local("root"));
+        let root = document.root;
+        let a = document.create_element(QName::local("a"));
+        let b = document.create_element(QName::local("b"));
+        let text = document.create_text("hi");
+
+        document.append_child(root, a)?;
+        document.append_child(root, b)?;
+        document.append_child(a, text)?;
+        assert_eq!(xml(&document), "<root><a>hi</a><b/></root>");
+
+        // Moving keeps links consistent.
+        document.insert_before(root, b, a)?;
+        document.append_child(b, text)?;
+        assert_eq!(xml(&document), "<root><b>hi</b><a/></root>");
+        assert_eq!(document.node(text).and_then(Node::parent), Some(b));
+
+        let copy = document.clone_subtree(b)?;
+        document.append_child(a, copy)?;
+        assert_eq!(xml(&document), "<root><b>hi</b><a><b>hi</b></a></root>");
+
+        document.remove(b)?;
+        assert!(document.node(b).is_none());
+        assert!(document.node(text).is_none());
+        assert_eq!(xml(&document), "<root><a><b>hi</b></a></root>");
+
+        // Ids of removed nodes are never handed out again.
+        let c = document.create_comment(" c ");
+        assert!(c > text);
+        document.insert_top_level(c, Some(root))?;
+        assert_eq!(xml(&document), "<!-- c -->\n<root><a><b>hi</b></a></root>");
+
+        // Equality ignores how many nodes were created and removed.
+        let mut scratch = Document::new(QName::local("root"));
+        let unused = scratch.create_element(QName::local("unused"));
+        scratch.remove(unused)?;
+        assert_eq!(scratch, Document::new(QName::local("root")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_invalid_edits() -> Result<(), Error> {
+        let mut document = Document::parse("<root><a><b/></a></root>")?;
+        let root = document.root;
+        let a = document.node(root).unwrap().children()[0];
+        let b = document.node(a).unwrap().children()[0];
+
+        assert!(matches!(
+            document.append_child(b, a),
+            Err(Error {
+                kind: ErrorKind::HierarchyRequest(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            document.append_child(a, a),
+            Err(Error {
+                kind: ErrorKind::HierarchyRequest(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            document.append_child(a, root),
+            Err(Error {
+                kind: ErrorKind::HierarchyRequest(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            document.detach(root),
+            Err(Error {
+                kind: ErrorKind::HierarchyRequest(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            document.append_child(a, 99),
+            Err(Error {
+                kind: ErrorKind::UnknownNode(99),
+                ..
+            })
+        ));
+
+        let text = document.create_text("t");
+        assert!(matches!(
+            document.append_child(text, a),
+            Err(Error {
+                kind: ErrorKind::HierarchyRequest(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            document.insert_top_level(text, None),
+            Err(Error {
+                kind: ErrorKind::HierarchyRequest(_),
+                ..
+            })
+        ));
+
+        document.set_root_element(b)?;
+        assert_eq!(document.root, b);
+        assert_eq!(xml(&document), "<b/>");
+
+        Ok(())
+    }
+}
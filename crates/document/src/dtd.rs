@@ -0,0 +1,164 @@
+//! The declarations of a document type definition, read from the internal
+//! subset of a `<!DOCTYPE>` and, when asked for, its external subset.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+pub(crate) use parser::{doctype_length, parse_doctype};
+
+mod parser;
+
+/// A document type declaration: the name it gives the document element,
+/// its external identifier and the declarations of its subsets.
+///
+/// Where a name is declared more than once, the first declaration is
+/// kept, as XML prescribes for entities and attributes. The internal
+/// subset is read before the external one, so its declarations win.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dtd {
+    pub name: String,
+    pub public_id: Option<String>,
+    pub system_id: Option<String>,
+    /// Whether the external subset was read.
+    pub external_subset_read: bool,
+    /// Whether a reference to an external parameter entity, or the
+    /// external subset, was left unread, so declarations may be missing.
+    pub incomplete: bool,
+    pub elements: BTreeMap<String, ElementDecl>,
+    /// Elements declared more than once, which no valid document allows.
+    pub redeclared_elements: BTreeSet<String>,
+    /// Attribute-list declarations, by element name.
+    pub attributes: BTreeMap<String, Vec<AttributeDecl>>,
+    /// General entities, by name.
+    pub entities: BTreeMap<String, EntityDecl>,
+    pub parameter_entities: BTreeMap<String, EntityDecl>,
+    pub notations: BTreeMap<String, NotationDecl>,
+}
+
+impl Dtd {
+    pub fn element(&self, name: &str) -> Option<&ElementDecl> {
+        self.elements.get(name)
+    }
+
+    /// The declaration of the attribute `name` of the element `element`.
+    pub fn attribute(&self, element: &str, name: &str) -> Option<&AttributeDecl> {
+        self.attributes
+            .get(element)?
+            .iter()
+            .find(|attribute| attribute.name == name)
+    }
+
+    pub fn entity(&self, name: &str) -> Option<&EntityDecl> {
+        self.entities.get(name)
+    }
+}
+
+/// `<!ELEMENT>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElementDecl {
+    pub name: String,
+    pub content: ContentSpec,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentSpec {
+    Empty,
+    Any,
+    /// Text mixed with the elements named, in any order and number.
+    Mixed(Vec<String>),
+    /// Elements only, as the content model allows.
+    Children(ContentParticle),
+}
+
+/// A name or group of a content model, with how often it may occur.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentParticle {
+    pub term: ContentTerm,
+    pub occurrence: Occurrence,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentTerm {
+    Name(String),
+    /// `(a, b)`; a group of one particle is a sequence.
+    Sequence(Vec<ContentParticle>),
+    /// `(a | b)`
+    Choice(Vec<ContentParticle>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Occurrence {
+    Once,
+    /// `?`
+    Optional,
+    /// `*`
+    ZeroOrMore,
+    /// `+`
+    OneOrMore,
+}
+
+/// An attribute definition of an `<!ATTLIST>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeDecl {
+    pub name: String,
+    pub kind: AttributeType,
+    pub default: DefaultDecl,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttributeType {
+    CData,
+    Id,
+    IdRef,
+    IdRefs,
+    Entity,
+    Entities,
+    NmToken,
+    NmTokens,
+    /// `NOTATION (a | b)`
+    Notation(Vec<String>),
+    /// `(a | b)`
+    Enumeration(Vec<String>),
+}
+
+/// Whether an attribute must be given and the value it has if not. Values
+/// are as written, before references are replaced and the value is
+/// normalized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DefaultDecl {
+    Required,
+    Implied,
+    Fixed(String),
+    Default(String),
+}
+
+/// `<!ENTITY>`, for general and parameter entities alike.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityDecl {
+    pub name: String,
+    pub value: EntityValue,
+    /// The URI of the entity the declaration appears in, against which an
+    /// external entity's system identifier is resolved.
+    pub base: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntityValue {
+    /// The replacement text: the literal with character references and
+    /// parameter entity references replaced. General entity references
+    /// are left as they are.
+    Internal(String),
+    External {
+        public_id: Option<String>,
+        system_id: String,
+        /// The notation of an unparsed entity, from `NDATA`.
+        notation: Option<String>,
+    },
+}
+
+/// `<!NOTATION>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotationDecl {
+    pub name: String,
+    pub public_id: Option<String>,
+    pub system_id: Option<String>,
+}
@@ -0,0 +1,1113 @@
+use std::rc::Rc;
+
+use crate::chars::{is_name_char, is_name_start_char, is_whitespace, is_xml_char};
+use crate::dtd::{
+    AttributeDecl, AttributeType, ContentParticle, ContentSpec, ContentTerm, DefaultDecl, Dtd,
+    ElementDecl, EntityDecl, EntityValue, NotationDecl, Occurrence,
+};
+use crate::resolver::Resolver;
+use crate::{Error, ErrorKind, Result};
+
+/// Reads the content of a `<!DOCTYPE>`, which starts at byte `offset` of
+/// `document`. With a resolver, the external subset and external parameter
+/// entities are read too, relative to `base`, the URI of the document.
+pub(crate) fn parse_doctype(
+    text: &str,
+    offset: usize,
+    document: &str,
+    base: Option<&str>,
+    resolver: Option<&dyn Resolver>,
+) -> Result<Dtd> {
+    let mut parser = Parser {
+        dtd: Dtd::default(),
+        sources: vec![Source {
+            text: Rc::from(text),
+            position: 0,
+            entity: None,
+            uri: None,
+            offset: Some(offset),
+            external: false,
+        }],
+        document,
+        base,
+        resolver,
+        includes: 0,
+    };
+    parser.doctype()?;
+
+    let (public_id, system_id) = (parser.dtd.public_id.clone(), parser.dtd.system_id.clone());
+    if let Some(system_id) = system_id {
+        match resolver {
+            Some(resolver) => {
+                let resource = resolver
+                    .resolve_entity(base, public_id.as_deref(), &system_id)
+                    .map_err(|e| parser.error(&format!("cannot read `{}`: {}", system_id, e)))?;
+                let text = resource.text()?;
+                parser.sources = vec![Source {
+                    text: Rc::from(strip_text_declaration(text)),
+                    position: 0,
+                    entity: None,
+                    uri: Some(resource.uri.clone()),
+                    offset: None,
+                    external: true,
+                }];
+                parser.declarations(false)?;
+                parser.dtd.external_subset_read = true;
+            }
+            None => parser.dtd.incomplete = true,
+        }
+    }
+    Ok(parser.dtd)
+}
+
+/// Text the parser reads from: the document, an external entity or the
+/// replacement text of a parameter entity.
+struct Source {
+    text: Rc<str>,
+    position: usize,
+    /// The parameter entity this is the replacement text of.
+    entity: Option<String>,
+    /// The URI of the external entity this was read from.
+    uri: Option<String>,
+    /// Where the text starts in the document, if it is part of it.
+    offset: Option<usize>,
+    /// Whether this is, or was referenced from, the external subset or an
+    /// external parameter entity, where references may appear inside
+    /// declarations.
+    external: bool,
+}
+
+impl Source {
+    fn rest(&self) -> &str {
+        &self.text[self.position..]
+    }
+}
+
+struct Parser<'a> {
+    dtd: Dtd,
+    /// The sources being read, the innermost last.
+    sources: Vec<Source>,
+    document: &'a str,
+    base: Option<&'a str>,
+    resolver: Option<&'a dyn Resolver>,
+    /// How many `INCLUDE` sections are open.
+    includes: usize,
+}
+
+impl Parser<'_> {
+    fn source(&self) -> &Source {
+        self.sources.last().expect("a source")
+    }
+
+    fn rest(&self) -> &str {
+        self.source().rest()
+    }
+
+    fn starts_with(&self, prefix: &str) -> bool {
+        self.rest().starts_with(prefix)
+    }
+
+    fn advance(&mut self, length: usize) {
+        self.sources.last_mut().expect("a source").position += length;
+    }
+
+    fn error(&self, message: &str) -> Error {
+        let source = self.source();
+        let message = match (source.offset, &source.uri, &source.entity) {
+            (None, Some(uri), _) => format!("{} in `{}`", message, uri),
+            (None, None, Some(entity)) => {
+                format!("{} in parameter entity `%{};`", message, entity)
+            }
+            _ => message.to_owned(),
+        };
+        let error = Error::from(ErrorKind::Syntax(message));
+        match source.offset {
+            Some(offset) => error.at(self.document, offset + source.position),
+            None => error.at(&source.text, source.position),
+        }
+    }
+
+    /// Whether parameter entity references may appear inside declarations
+    /// here.
+    fn in_external(&self) -> bool {
+        self.source().external
+    }
+
+    fn expect(&mut self, token: &str) -> Result<()> {
+        if !self.starts_with(token) {
+            return Err(self.error(&format!("expected `{}`", token)));
+        }
+        self.advance(token.len());
+        Ok(())
+    }
+
+    /// Drops the sources that have been read to the end, other than the
+    /// outermost one. Gives whether any were dropped.
+    fn pop_finished(&mut self) -> bool {
+        let mut popped = false;
+        while self.sources.len() > 1 && self.rest().is_empty() {
+            self.sources.pop();
+            popped = true;
+        }
+        popped
+    }
+
+    /// Skips whitespace and, where `references` allows, parameter entity
+    /// references, whose replacement text is read in their place. Gives
+    /// whether anything was skipped; the edges of replacement text count as
+    /// whitespace.
+    fn skip_space(&mut self, references: bool) -> Result<bool> {
+        let mut skipped = false;
+        loop {
+            if self.pop_finished() {
+                skipped = true;
+            }
+            let rest = self.rest();
+            let trimmed = rest.trim_start_matches(is_whitespace);
+            if trimmed.len() < rest.len() {
+                let length = rest.len() - trimmed.len();
+                self.advance(length);
+                skipped = true;
+                continue;
+            }
+            let mut chars = rest.chars();
+            if references
+                && chars.next() == Some('%')
+                && chars.next().is_some_and(is_name_start_char)
+            {
+                self.parameter_reference()?;
+                skipped = true;
+                continue;
+            }
+            return Ok(skipped);
+        }
+    }
+
+    /// Skips whitespace inside a declaration, which must be there.
+    fn require_space(&mut self) -> Result<()> {
+        let references = self.in_external();
+        match self.skip_space(references)? {
+            true => Ok(()),
+            false => Err(self.error("expected whitespace")),
+        }
+    }
+
+    fn space(&mut self) -> Result<bool> {
+        let references = self.in_external();
+        self.skip_space(references)
+    }
+
+    /// Reads `%name;` and starts reading the entity's replacement text.
+    fn parameter_reference(&mut self) -> Result<()> {
+        self.advance(1);
+        let name = self.name()?;
+        self.expect(";")?;
+        let Some(declaration) = self.dtd.parameter_entities.get(&name).cloned() else {
+            if self.dtd.incomplete || self.dtd.system_id.is_some() {
+                return Ok(());
+            }
+            return Err(self.error(&format!("undeclared parameter entity `%{};`", name)));
+        };
+        if self
+            .sources
+            .iter()
+            .any(|source| source.entity.as_deref() == Some(name.as_str()))
+        {
+            return Err(self.error(&format!("parameter entity `%{};` refers to itself", name)));
+        }
+        let source = match declaration.value {
+            EntityValue::Internal(text) => Source {
+                text: Rc::from(text),
+                position: 0,
+                entity: Some(name),
+                uri: None,
+                offset: None,
+                external: self.in_external(),
+            },
+            EntityValue::External {
+                public_id,
+                system_id,
+                ..
+            } => {
+                let Some(resolver) = self.resolver else {
+                    self.dtd.incomplete = true;
+                    return Ok(());
+                };
+                let resource = resolver
+                    .resolve_entity(
+                        declaration.base.as_deref(),
+                        public_id.as_deref(),
+                        &system_id,
+                    )
+                    .map_err(|e| self.error(&format!("cannot read `{}`: {}", system_id, e)))?;
+                let text = resource.text()?;
+                Source {
+                    text: Rc::from(strip_text_declaration(text)),
+                    position: 0,
+                    entity: Some(name),
+                    uri: Some(resource.uri.clone()),
+                    offset: None,
+                    external: true,
+                }
+            }
+        };
+        self.sources.push(source);
+        Ok(())
+    }
+
+    /// The rest of `<!DOCTYPE`: the name, external identifier and internal
+    /// subset.
+    fn doctype(&mut self) -> Result<()> {
+        self.dtd.name = self.name()?;
+        if self.skip_space(false)? && (self.starts_with("SYSTEM") || self.starts_with("PUBLIC")) {
+            let (public_id, system_id) = self.external_id(true)?;
+            self.dtd.public_id = public_id;
+            self.dtd.system_id = system_id;
+            self.skip_space(false)?;
+        }
+        if self.starts_with("[") {
+            self.advance(1);
+            self.declarations(true)?;
+            self.expect("]")?;
+            self.skip_space(false)?;
+        }
+        if !self.rest().is_empty() {
+            return Err(self.error("unexpected content in the document type declaration"));
+        }
+        Ok(())
+    }
+
+    /// Reads markup declarations up to the `]` that closes the internal
+    /// subset, or to the end of the external subset.
+    fn declarations(&mut self, internal: bool) -> Result<()> {
+        loop {
+            self.skip_space(true)?;
+            if self.sources.len() == 1 {
+                if internal && self.starts_with("]") {
+                    break;
+                }
+                if !internal && self.rest().is_empty() {
+                    break;
+                }
+            }
+            if self.includes > 0 && self.starts_with("]]>") {
+                self.advance(3);
+                self.includes -= 1;
+                continue;
+            }
+            if self.rest().is_empty() {
+                return Err(self.error("unexpected end of the document type declaration"));
+            }
+            self.declaration()?;
+        }
+        if self.includes > 0 {
+            return Err(self.error("unterminated conditional section"));
+        }
+        Ok(())
+    }
+
+    fn declaration(&mut self) -> Result<()> {
+        if self.starts_with("<!--") {
+            return self.skip_past("-->", "comment");
+        }
+        if self.starts_with("<?") {
+            return self.skip_past("?>", "processing instruction");
+        }
+        if self.starts_with("<![") {
+            return self.conditional_section();
+        }
+        if self.starts_with("<!ELEMENT") {
+            self.advance("<!ELEMENT".len());
+            return self.element_declaration();
+        }
+        if self.starts_with("<!ATTLIST") {
+            self.advance("<!ATTLIST".len());
+            return self.attribute_list_declaration();
+        }
+        if self.starts_with("<!ENTITY") {
+            self.advance("<!ENTITY".len());
+            return self.entity_declaration();
+        }
+        if self.starts_with("<!NOTATION") {
+            self.advance("<!NOTATION".len());
+            return self.notation_declaration();
+        }
+        Err(self.error("expected a markup declaration"))
+    }
+
+    fn skip_past(&mut self, end: &str, construct: &str) -> Result<()> {
+        match self.rest().find(end) {
+            Some(index) => {
+                self.advance(index + end.len());
+                Ok(())
+            }
+            None => Err(self.error(&format!("unterminated {}", construct))),
+        }
+    }
+
+    /// `<![INCLUDE[ … ]]>` or `<![IGNORE[ … ]]>`, which the external
+    /// subset alone may contain.
+    fn conditional_section(&mut self) -> Result<()> {
+        if !self.in_external() {
+            return Err(self.error("conditional sections are only allowed in the external subset"));
+        }
+        self.advance(3);
+        self.skip_space(true)?;
+        let keyword = self.name()?;
+        self.skip_space(true)?;
+        self.expect("[")?;
+        match keyword.as_str() {
+            "INCLUDE" => {
+                self.includes += 1;
+                Ok(())
+            }
+            "IGNORE" => {
+                let mut depth = 1;
+                while depth > 0 {
+                    let rest = self.rest();
+                    let open = rest.find("<![");
+                    let close = rest.find("]]>");
+                    match (open, close) {
+                        (Some(open), Some(close)) if open < close => {
+                            self.advance(open + 3);
+                            depth += 1;
+                        }
+                        (_, Some(close)) => {
+                            self.advance(close + 3);
+                            depth -= 1;
+                        }
+                        (_, None) => return Err(self.error("unterminated conditional section")),
+                    }
+                }
+                Ok(())
+            }
+            other => Err(self.error(&format!(
+                "expected `INCLUDE` or `IGNORE`, found `{}`",
+                other
+            ))),
+        }
+    }
+
+    fn element_declaration(&mut self) -> Result<()> {
+        self.require_space()?;
+        let name = self.name()?;
+        self.require_space()?;
+        let content = if self.starts_with("(") {
+            self.advance(1);
+            self.space()?;
+            match self.starts_with("#PCDATA") {
+                true => self.mixed()?,
+                false => ContentSpec::Children(self.group()?),
+            }
+        } else {
+            match self.name()?.as_str() {
+                "EMPTY" => ContentSpec::Empty,
+                "ANY" => ContentSpec::Any,
+                other => {
+                    return Err(self.error(&format!("unknown content specification `{}`", other)))
+                }
+            }
+        };
+        self.space()?;
+        self.expect(">")?;
+        match self.dtd.elements.contains_key(&name) {
+            true => {
+                self.dtd.redeclared_elements.insert(name);
+            }
+            false => {
+                self.dtd
+                    .elements
+                    .insert(name.clone(), ElementDecl { name, content });
+            }
+        }
+        Ok(())
+    }
+
+    /// The rest of `(#PCDATA | a | b)*`.
+    fn mixed(&mut self) -> Result<ContentSpec> {
+        self.advance("#PCDATA".len());
+        let mut names = Vec::new();
+        loop {
+            self.space()?;
+            if self.starts_with("|") {
+                self.advance(1);
+                self.space()?;
+                names.push(self.name()?);
+            } else if self.starts_with(")") {
+                self.advance(1);
+                if self.starts_with("*") {
+                    self.advance(1);
+                } else if !names.is_empty() {
+                    return Err(self.error("mixed content that names elements must end in `)*`"));
+                }
+                return Ok(ContentSpec::Mixed(names));
+            } else {
+                return Err(self.error("expected `|` or `)`"));
+            }
+        }
+    }
+
+    /// The rest of a choice or sequence whose `(` has been read.
+    fn group(&mut self) -> Result<ContentParticle> {
+        let mut particles = vec![self.particle()?];
+        let mut separator = None;
+        loop {
+            self.space()?;
+            let next = self.rest().chars().next();
+            match next {
+                Some(')') => {
+                    self.advance(1);
+                    break;
+                }
+                Some(c @ ('|' | ',')) => {
+                    if separator.is_some_and(|separator| separator != c) {
+                        return Err(self.error("a group mixes `|` and `,`"));
+                    }
+                    separator = Some(c);
+                    self.advance(1);
+                    self.space()?;
+                    particles.push(self.particle()?);
+                }
+                _ => return Err(self.error("expected `|`, `,` or `)`")),
+            }
+        }
+        let term = match separator {
+            Some('|') => ContentTerm::Choice(particles),
+            _ => ContentTerm::Sequence(particles),
+        };
+        Ok(ContentParticle {
+            term,
+            occurrence: self.occurrence(),
+        })
+    }
+
+    fn particle(&mut self) -> Result<ContentParticle> {
+        if self.starts_with("(") {
+            self.advance(1);
+            self.space()?;
+            return self.group();
+        }
+        let name = self.name()?;
+        Ok(ContentParticle {
+            term: ContentTerm::Name(name),
+            occurrence: self.occurrence(),
+        })
+    }
+
+    fn occurrence(&mut self) -> Occurrence {
+        let occurrence = match self.rest().chars().next() {
+            Some('?') => Occurrence::Optional,
+            Some('*') => Occurrence::ZeroOrMore,
+            Some('+') => Occurrence::OneOrMore,
+            _ => return Occurrence::Once,
+        };
+        self.advance(1);
+        occurrence
+    }
+
+    fn attribute_list_declaration(&mut self) -> Result<()> {
+        self.require_space()?;
+        let element = self.name()?;
+        loop {
+            let spaced = self.space()?;
+            if self.starts_with(">") {
+                self.advance(1);
+                return Ok(());
+            }
+            if !spaced {
+                return Err(self.error("expected whitespace"));
+            }
+            let name = self.name()?;
+            self.require_space()?;
+            let kind = self.attribute_type()?;
+            self.require_space()?;
+            let default = self.default_declaration()?;
+            let attributes = self.dtd.attributes.entry(element.clone()).or_default();
+            if !attributes.iter().any(|attribute| attribute.name == name) {
+                attributes.push(AttributeDecl {
+                    name,
+                    kind,
+                    default,
+                });
+            }
+        }
+    }
+
+    fn attribute_type(&mut self) -> Result<AttributeType> {
+        if self.starts_with("(") {
+            return Ok(AttributeType::Enumeration(self.enumeration(true)?));
+        }
+        Ok(match self.name()?.as_str() {
+            "CDATA" => AttributeType::CData,
+            "ID" => AttributeType::Id,
+            "IDREF" => AttributeType::IdRef,
+            "IDREFS" => AttributeType::IdRefs,
+            "ENTITY" => AttributeType::Entity,
+            "ENTITIES" => AttributeType::Entities,
+            "NMTOKEN" => AttributeType::NmToken,
+            "NMTOKENS" => AttributeType::NmTokens,
+            "NOTATION" => {
+                self.require_space()?;
+                AttributeType::Notation(self.enumeration(false)?)
+            }
+            other => return Err(self.error(&format!("unknown attribute type `{}`", other))),
+        })
+    }
+
+    /// `(a | b)`, of name tokens or of names.
+    fn enumeration(&mut self, tokens: bool) -> Result<Vec<String>> {
+        self.expect("(")?;
+        let mut values = Vec::new();
+        loop {
+            self.space()?;
+            values.push(match tokens {
+                true => self.name_token()?,
+                false => self.name()?,
+            });
+            self.space()?;
+            if self.starts_with(")") {
+                self.advance(1);
+                return Ok(values);
+            }
+            self.expect("|")?;
+        }
+    }
+
+    fn default_declaration(&mut self) -> Result<DefaultDecl> {
+        if self.starts_with("#REQUIRED") {
+            self.advance("#REQUIRED".len());
+            return Ok(DefaultDecl::Required);
+        }
+        if self.starts_with("#IMPLIED") {
+            self.advance("#IMPLIED".len());
+            return Ok(DefaultDecl::Implied);
+        }
+        if self.starts_with("#FIXED") {
+            self.advance("#FIXED".len());
+            self.require_space()?;
+            return Ok(DefaultDecl::Fixed(self.attribute_value()?));
+        }
+        Ok(DefaultDecl::Default(self.attribute_value()?))
+    }
+
+    fn attribute_value(&mut self) -> Result<String> {
+        let value = self.literal()?;
+        if value.contains('<') {
+            return Err(self.error("`<` in an attribute value"));
+        }
+        Ok(value)
+    }
+
+    fn entity_declaration(&mut self) -> Result<()> {
+        self.require_space()?;
+        let parameter = self.starts_with("%");
+        if parameter {
+            self.advance(1);
+            if !self.skip_space(false)? {
+                return Err(self.error("expected whitespace after `%`"));
+            }
+        }
+        let name = self.name()?;
+        self.require_space()?;
+        let value = if self.starts_with("\"") || self.starts_with("'") {
+            EntityValue::Internal(self.entity_value()?)
+        } else {
+            let (public_id, system_id) = self.external_id(true)?;
+            let mut notation = None;
+            if self.space()? && self.starts_with("NDATA") {
+                if parameter {
+                    return Err(self.error("parameter entities cannot be unparsed"));
+                }
+                self.advance("NDATA".len());
+                self.require_space()?;
+                notation = Some(self.name()?);
+            }
+            EntityValue::External {
+                public_id,
+                system_id: system_id.unwrap_or_default(),
+                notation,
+            }
+        };
+        self.space()?;
+        self.expect(">")?;
+
+        let base = self
+            .sources
+            .iter()
+            .rev()
+            .find_map(|source| source.uri.clone())
+            .or_else(|| self.base.map(str::to_owned));
+        let entities = match parameter {
+            true => &mut self.dtd.parameter_entities,
+            false => &mut self.dtd.entities,
+        };
+        entities
+            .entry(name.clone())
+            .or_insert(EntityDecl { name, value, base });
+        Ok(())
+    }
+
+    fn notation_declaration(&mut self) -> Result<()> {
+        self.require_space()?;
+        let name = self.name()?;
+        self.require_space()?;
+        let (public_id, system_id) = self.external_id(false)?;
+        self.space()?;
+        self.expect(">")?;
+        self.dtd
+            .notations
+            .entry(name.clone())
+            .or_insert(NotationDecl {
+                name,
+                public_id,
+                system_id,
+            });
+        Ok(())
+    }
+
+    /// The replacement text of an entity value literal: character
+    /// references and, outside the internal subset, parameter entity
+    /// references are replaced.
+    fn entity_value(&mut self) -> Result<String> {
+        let quote = self.rest().chars().next().expect("a quote");
+        self.advance(1);
+        let depth = self.sources.len();
+        let mut value = String::new();
+        loop {
+            if self.sources.len() > depth && self.rest().is_empty() {
+                self.sources.pop();
+                continue;
+            }
+            let Some(c) = self.rest().chars().next() else {
+                return Err(self.error("unterminated entity value"));
+            };
+            match c {
+                c if c == quote && self.sources.len() == depth => {
+                    self.advance(1);
+                    return Ok(value);
+                }
+                '%' => {
+                    if !self.in_external() {
+                        return Err(self.error(
+                            "parameter entity references cannot appear in entity values in the internal subset",
+                        ));
+                    }
+                    self.parameter_reference()?;
+                }
+                '&' if self.starts_with("&#") => value.push(self.character_reference()?),
+                // Line ends in the literal count as one `\n`; a `&#13;`
+                // above is kept.
+                '\r' => {
+                    value.push('\n');
+                    let length = if self.starts_with("\r\n") { 2 } else { 1 };
+                    self.advance(length);
+                }
+                '&' => {
+                    self.advance(1);
+                    let name = self.name()?;
+                    self.expect(";")?;
+                    value.push('&');
+                    value.push_str(&name);
+                    value.push(';');
+                }
+                c => {
+                    value.push(c);
+                    self.advance(c.len_utf8());
+                }
+            }
+        }
+    }
+
+    fn character_reference(&mut self) -> Result<char> {
+        let rest = self.rest();
+        let end = rest.find(';').unwrap_or(rest.len());
+        let reference = &rest[..end];
+        let code = match reference.strip_prefix("&#x") {
+            Some(hex) => u32::from_str_radix(hex, 16).ok(),
+            None => reference[2..].parse().ok(),
+        };
+        match code.and_then(char::from_u32).filter(|&c| is_xml_char(c)) {
+            Some(c) if end < rest.len() => {
+                self.advance(end + 1);
+                Ok(c)
+            }
+            _ => Err(self.error(&format!("invalid character reference `{};`", reference))),
+        }
+    }
+
+    /// `SYSTEM "uri"` or `PUBLIC "id" "uri"`; notations may leave out the
+    /// system literal after a public identifier.
+    fn external_id(&mut self, system_required: bool) -> Result<(Option<String>, Option<String>)> {
+        match self.name()?.as_str() {
+            "SYSTEM" => {
+                self.require_space()?;
+                Ok((None, Some(self.literal()?)))
+            }
+            "PUBLIC" => {
+                self.require_space()?;
+                let public_id = self.literal()?;
+                if let Some(c) = public_id.chars().find(|&c| !is_pubid_char(c)) {
+                    return Err(self.error(&format!("`{}` in a public identifier", c)));
+                }
+                let public_id = public_id.split_whitespace().collect::<Vec<_>>().join(" ");
+                if system_required {
+                    self.require_space()?;
+                    return Ok((Some(public_id), Some(self.literal()?)));
+                }
+                if self.space()? && (self.starts_with("\"") || self.starts_with("'")) {
+                    return Ok((Some(public_id), Some(self.literal()?)));
+                }
+                Ok((Some(public_id), None))
+            }
+            other => Err(self.error(&format!("expected `SYSTEM` or `PUBLIC`, found `{}`", other))),
+        }
+    }
+
+    /// A quoted string, read as it is.
+    fn literal(&mut self) -> Result<String> {
+        let rest = self.rest();
+        let Some(quote) = rest.chars().next().filter(|&c| c == '"' || c == '\'') else {
+            return Err(self.error("expected a quoted literal"));
+        };
+        match rest[1..].find(quote) {
+            Some(end) => {
+                let value = rest[1..=end].to_owned();
+                self.advance(end + 2);
+                Ok(value)
+            }
+            None => Err(self.error("unterminated literal")),
+        }
+    }
+
+    fn name(&mut self) -> Result<String> {
+        let rest = self.rest();
+        if !rest.starts_with(is_name_start_char) {
+            return Err(self.error("expected a name"));
+        }
+        let end = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+        let name = rest[..end].to_owned();
+        self.advance(end);
+        Ok(name)
+    }
+
+    fn name_token(&mut self) -> Result<String> {
+        let rest = self.rest();
+        let end = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+        if end == 0 {
+            return Err(self.error("expected a name token"));
+        }
+        let token = rest[..end].to_owned();
+        self.advance(end);
+        Ok(token)
+    }
+}
+
+/// `PubidChar`
+fn is_pubid_char(c: char) -> bool {
+    matches!(c, ' ' | '\r' | '\n' | 'a'..='z' | 'A'..='Z' | '0'..='9')
+        || "-'()+,./:=?;!*#@$_%".contains(c)
+}
+
+/// The length of the `<!DOCTYPE … >` that `xml` starts with, up to and
+/// including its closing `>`. Quoted literals, and the comments and
+/// processing instructions of the internal subset, may contain `>`, so
+/// the end is found by skipping over them. Returns `None` if the
+/// declaration is not closed.
+pub(crate) fn doctype_length(xml: &str) -> Option<usize> {
+    let bytes = xml.as_bytes();
+    let mut position = "<!DOCTYPE".len();
+    let mut in_subset = false;
+    while let Some(&byte) = bytes.get(position) {
+        // Only ASCII bytes are matched, so `position` is at a character
+        // boundary wherever `xml` is sliced.
+        position += match byte {
+            b'"' | b'\'' => xml[position + 1..].find(byte as char)? + 2,
+            b'[' if !in_subset => {
+                in_subset = true;
+                1
+            }
+            b']' if in_subset => {
+                in_subset = false;
+                1
+            }
+            b'>' if !in_subset => return Some(position + 1),
+            b'<' if in_subset && xml[position..].starts_with("<!--") => {
+                xml[position + 4..].find("-->")? + 7
+            }
+            b'<' if in_subset && xml[position..].starts_with("<?") => {
+                xml[position + 2..].find("?>")? + 4
+            }
+            _ => 1,
+        };
+    }
+    None
+}
+
+/// Drops the `<?xml … ?>` text declaration an external entity may start
+/// with.
+fn strip_text_declaration(text: &str) -> &str {
+    let declared = text
+        .strip_prefix("<?xml")
+        .is_some_and(|rest| rest.starts_with(is_whitespace));
+    match text.find("?>") {
+        Some(end) if declared => &text[end + 2..],
+        _ => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dtd::{
+        doctype_length, AttributeType, ContentParticle, ContentSpec, ContentTerm, DefaultDecl,
+        EntityValue, Occurrence,
+    };
+    use crate::node::Document;
+    use crate::resolver::MapResolver;
+    use crate::{Error, ParseOptions};
+
+    fn name(name: &str, occurrence: Occurrence) -> ContentParticle {
+        ContentParticle {
+            term: ContentTerm::Name(name.to_owned()),
+            occurrence,
+        }
+    }
+
+    #[test]
+    fn reads_the_internal_subset() -> Result<(), Error> {
+        let document = Document::parse(
+            r#"<!DOCTYPE book PUBLIC "-//Example//DTD  Book//EN" "book.dtd" [
+  <!-- declarations -->
+  <!ELEMENT book (title, (chapter | appendix)+)>
+  <!ELEMENT title (#PCDATA | em)*>
+  <!ELEMENT em (#PCDATA)>
+  <!ELEMENT chapter EMPTY>
+  <!ELEMENT appendix ANY>
+  <!ELEMENT chapter ANY>
+  <!ATTLIST book
+      id ID #REQUIRED
+      lang NMTOKEN "en"
+      status (draft | final) #IMPLIED
+      format NOTATION (pdf) #FIXED 'pdf'>
+  <!ATTLIST book id CDATA #IMPLIED>
+  <!ENTITY % declarations "<!ENTITY defined 'in a parameter entity'>">
+  <!ENTITY copy "&#169; &author;">
+  <!ENTITY copy "ignored">
+  <!ENTITY cover SYSTEM "cover.png" NDATA png>
+  <!NOTATION png PUBLIC "-//Example//NOTATION PNG//EN">
+  <!NOTATION pdf SYSTEM "application/pdf">
+  %declarations;
+  <?processing instruction?>
+]>
+<book id="b1"/>"#,
+        )?;
+        let dtd = document.dtd.expect("a DTD");
+
+        assert_eq!(dtd.name, "book");
+        assert_eq!(dtd.public_id.as_deref(), Some("-//Example//DTD Book//EN"));
+        assert_eq!(dtd.system_id.as_deref(), Some("book.dtd"));
+        assert!(!dtd.external_subset_read);
+        assert_eq!(
+            dtd.element("book").map(|element| &element.content),
+            Some(&ContentSpec::Children(ContentParticle {
+                term: ContentTerm::Sequence(vec![
+                    name("title", Occurrence::Once),
+                    ContentParticle {
+                        term: ContentTerm::Choice(vec![
+                            name("chapter", Occurrence::Once),
+                            name("appendix", Occurrence::Once),
+                        ]),
+                        occurrence: Occurrence::OneOrMore,
+                    },
+                ]),
+                occurrence: Occurrence::Once,
+            }))
+        );
+        assert_eq!(
+            dtd.element("title").map(|element| &element.content),
+            Some(&ContentSpec::Mixed(vec!["em".to_owned()]))
+        );
+        assert_eq!(
+            dtd.element("em").map(|element| &element.content),
+            Some(&ContentSpec::Mixed(Vec::new()))
+        );
+        assert_eq!(
+            dtd.element("chapter").map(|element| &element.content),
+            Some(&ContentSpec::Empty)
+        );
+        assert_eq!(
+            dtd.redeclared_elements.iter().collect::<Vec<_>>(),
+            ["chapter"]
+        );
+
+        let attributes = &dtd.attributes["book"];
+        assert_eq!(attributes.len(), 4);
+        assert_eq!(attributes[0].kind, AttributeType::Id);
+        assert_eq!(attributes[0].default, DefaultDecl::Required);
+        assert_eq!(attributes[1].default, DefaultDecl::Default("en".to_owned()));
+        assert_eq!(
+            attributes[2].kind,
+            AttributeType::Enumeration(vec!["draft".to_owned(), "final".to_owned()])
+        );
+        assert_eq!(
+            dtd.attribute("book", "format")
+                .map(|a| (&a.kind, &a.default)),
+            Some((
+                &AttributeType::Notation(vec!["pdf".to_owned()]),
+                &DefaultDecl::Fixed("pdf".to_owned())
+            ))
+        );
+
+        assert_eq!(
+            dtd.entity("copy").map(|entity| &entity.value),
+            Some(&EntityValue::Internal("© &author;".to_owned()))
+        );
+        assert_eq!(
+            dtd.entity("cover").map(|entity| &entity.value),
+            Some(&EntityValue::External {
+                public_id: None,
+                system_id: "cover.png".to_owned(),
+                notation: Some("png".to_owned()),
+            })
+        );
+        assert_eq!(
+            dtd.entity("defined").map(|entity| &entity.value),
+            Some(&EntityValue::Internal("in a parameter entity".to_owned()))
+        );
+        assert_eq!(dtd.notations["png"].system_id, None);
+        assert_eq!(
+            dtd.notations["pdf"].system_id.as_deref(),
+            Some("application/pdf")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reads_the_external_subset_with_a_resolver() -> Result<(), Error> {
+        let resolver: MapResolver = [
+            (
+                "http://example.org/dtd/doc.dtd",
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<!ENTITY % common SYSTEM "common.ent">
+%common;
+<!ENTITY % draft "INCLUDE">
+<![%draft;[
+  <!ELEMENT doc (%content;)>
+]]>
+<![IGNORE[ <!ELEMENT doc EMPTY> <![INCLUDE[ ]]> ]]>
+<!ATTLIST doc version CDATA "1">"#,
+            ),
+            (
+                "http://example.org/dtd/common.ent",
+                r#"<!ENTITY % list "ul"><!ENTITY % content "p | %list;">
+<!ENTITY title "Title">"#,
+            ),
+            (
+                "http://example.org/doc.xml",
+                r#"<!DOCTYPE doc SYSTEM "dtd/doc.dtd" [<!ATTLIST doc version CDATA "2">]><doc/>"#,
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let options = ParseOptions {
+            external_dtd: true,
+            ..ParseOptions::default()
+        };
+        let document = Document::load(&resolver, "http://example.org/doc.xml", &options)?;
+        let dtd = document.dtd.expect("a DTD");
+
+        assert!(dtd.external_subset_read);
+        assert!(!dtd.incomplete);
+        assert_eq!(
+            dtd.element("doc").map(|element| &element.content),
+            Some(&ContentSpec::Children(ContentParticle {
+                term: ContentTerm::Choice(vec![
+                    name("p", Occurrence::Once),
+                    name("ul", Occurrence::Once),
+                ]),
+                occurrence: Occurrence::Once,
+            }))
+        );
+        assert_eq!(
+            dtd.attribute("doc", "version").map(|a| &a.default),
+            Some(&DefaultDecl::Default("2".to_owned()))
+        );
+        assert_eq!(
+            dtd.entity("title")
+                .and_then(|entity| entity.base.as_deref()),
+            Some("http://example.org/dtd/common.ent")
+        );
+
+        let unread = Document::load(
+            &resolver,
+            "http://example.org/doc.xml",
+            &ParseOptions::default(),
+        )?;
+        let dtd = unread.dtd.expect("a DTD");
+        assert!(dtd.incomplete && !dtd.external_subset_read);
+        assert!(dtd.elements.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn finds_the_end_of_the_doctype() {
+        fn length(xml: &str) -> Option<&str> {
+            doctype_length(xml).map(|length| &xml[..length])
+        }
+
+        assert_eq!(length("<!DOCTYPE a><a/>"), Some("<!DOCTYPE a>"));
+        assert_eq!(
+            length(r#"<!DOCTYPE a SYSTEM "x>" [<!ENTITY e '"]>'>] ><a/>"#),
+            Some(r#"<!DOCTYPE a SYSTEM "x>" [<!ENTITY e '"]>'>] >"#)
+        );
+        assert_eq!(
+            length("<!DOCTYPE a [<!-->]--><?p ]>?>]>é"),
+            Some("<!DOCTYPE a [<!-->]--><?p ]>?>]>")
+        );
+        assert_eq!(length("<!DOCTYPE é [<!-- ]> -->"), None);
+        assert_eq!(length("<!DOCTYPE a [<!ENTITY e \"]>"), None);
+    }
+
+    #[test]
+    fn rejects_malformed_declarations() {
+        let error = |doctype: &str| {
+            let xml = format!("<!DOCTYPE a [\n{}\n]><a/>", doctype);
+            match Document::parse(&xml) {
+                Ok(_) => panic!("`{}` was accepted", doctype),
+                Err(error) => (
+                    error.kind.to_string(),
+                    error
+                        .position
+                        .map(|position| (position.line, position.column)),
+                ),
+            }
+        };
+
+        assert_eq!(
+            error("<!ELEMENT a (b | c, d)>"),
+            (
+                "malformed markup: a group mixes `|` and `,`".to_owned(),
+                Some((2, 19))
+            )
+        );
+        assert_eq!(
+            error("<!ELEMENT a (#PCDATA | b)>").0,
+            "malformed markup: mixed content that names elements must end in `)*`"
+        );
+        assert_eq!(
+            error("<!ENTITY % a \"x\"><!ENTITY b \"%a;\">").0,
+            "malformed markup: parameter entity references cannot appear in entity values in the internal subset"
+        );
+        assert_eq!(
+            error("%undeclared;").0,
+            "malformed markup: undeclared parameter entity `%undeclared;`"
+        );
+        assert_eq!(
+            error("<![INCLUDE[ ]]>").0,
+            "malformed markup: conditional sections are only allowed in the external subset"
+        );
+        assert_eq!(
+            error("<!ELEMENT a (b)*+>").0,
+            "malformed markup: expected `>`"
+        );
+    }
+}
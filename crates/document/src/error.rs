@@ -1,21 +1,230 @@
+use std::fmt::{Display, Formatter};
 use std::str::Utf8Error;
 
+use quick_xml::escape::EscapeError;
+
+use crate::node::NodeId;
+use crate::position::{snippet, Position};
+
 pub type Result<T> = std::result::Result<T, Error>;
 
-pub enum Error {
-    QuickXml(quick_xml::Error),
-    Decode(Utf8Error),
-    Unknown,
+/// An error together with where it happened in the input, when known.
+#[derive(Debug)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub position: Option<Position>,
+    /// The part of the input line around `position`.
+    pub snippet: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    /// Markup that is not well-formed.
+    Syntax(String),
+    /// The input ended in the middle of a construct.
+    UnexpectedEof(String),
+    /// An end tag does not match the open element.
+    MismatchedEndTag {
+        expected: String,
+        found: String,
+    },
+    /// A reference to an entity that has not been declared.
+    UndeclaredEntity(String),
+    /// An entity whose replacement text refers to the entity itself,
+    /// directly or through other entities.
+    RecursiveEntity(String),
+    /// Entity expansion went beyond the limits of the parse options.
+    EntityLimitExceeded(String),
+    /// A character reference to a value that is not a valid character.
+    InvalidCharacterReference(String),
+    /// A character outside the `Char` production of XML 1.0.
+    InvalidCharacter(char),
+    /// The input is not valid in its encoding.
+    Encoding(String),
+    /// The input ended without a document element.
+    MissingRootElement,
+    /// A second element was found after the document element was closed.
+    MultipleRootElements,
+    /// Text or CDATA was found outside of the document element.
+    ContentOutsideRoot,
+    /// The input ended while this element was still open.
+    UnclosedElement(String),
+    /// A name has more than one colon or an empty prefix or local part.
+    InvalidQName(String),
+    /// A prefix is used without a namespace declaration in scope.
+    UndeclaredPrefix(String),
+    /// A declaration rebinds `xml` or `xmlns`, or binds a prefix to their
+    /// reserved namespaces.
+    ReservedNamespace(String),
+    /// A prefixed namespace declaration has an empty value.
+    EmptyNamespace(String),
+    /// Two attributes of one element share the same expanded name.
+    DuplicateAttribute(String),
+    /// A node id does not exist in the document.
+    UnknownNode(NodeId),
+    /// A tree edit would leave the document inconsistent.
+    HierarchyRequest(&'static str),
+    /// The output encoding cannot represent this character in a place where
+    /// character references are not allowed.
+    Unencodable(char),
+    /// A comment or processing instruction whose data would end it early,
+    /// so it cannot be written as markup.
+    Unwritable(&'static str),
+    Io(std::io::Error),
+}
+
+impl Error {
+    /// Attaches the position of byte `offset` in `input`, unless the error
+    /// already carries a position.
+    pub(crate) fn at(mut self, input: &str, offset: usize) -> Self {
+        if self.position.is_none() {
+            let position = Position::locate(input, offset);
+            self.snippet = Some(snippet(input, position));
+            self.position = Some(position);
+        }
+        self
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(position) = &self.position {
+            write!(f, " at {}", position)?;
+        }
+        if let Some(snippet) = &self.snippet {
+            write!(f, "\n  {}", snippet)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::Syntax(message) => write!(f, "malformed markup: {}", message),
+            ErrorKind::UnexpectedEof(context) => {
+                write!(f, "unexpected end of input in {}", context)
+            }
+            ErrorKind::MismatchedEndTag { expected, found } => {
+                write!(
+                    f,
+                    "end tag `{}` does not match start tag `{}`",
+                    found, expected
+                )
+            }
+            ErrorKind::UndeclaredEntity(name) => write!(f, "undeclared entity `&{};`", name),
+            ErrorKind::RecursiveEntity(name) => {
+                write!(f, "entity `&{};` refers to itself", name)
+            }
+            ErrorKind::EntityLimitExceeded(message) => {
+                write!(f, "entity expansion limit exceeded: {}", message)
+            }
+            ErrorKind::InvalidCharacterReference(reference) => {
+                write!(f, "invalid character reference: {}", reference)
+            }
+            ErrorKind::InvalidCharacter(c) => {
+                write!(f, "character U+{:04X} is not allowed", *c as u32)
+            }
+            ErrorKind::Encoding(message) => write!(f, "invalid encoding: {}", message),
+            ErrorKind::MissingRootElement => f.write_str("document has no root element"),
+            ErrorKind::MultipleRootElements => {
+                f.write_str("document has more than one root element")
+            }
+            ErrorKind::ContentOutsideRoot => f.write_str("character data outside the root element"),
+            ErrorKind::UnclosedElement(name) => write!(f, "element `{}` is never closed", name),
+            ErrorKind::InvalidQName(name) => write!(f, "`{}` is not a valid qualified name", name),
+            ErrorKind::UndeclaredPrefix(prefix) => {
+                write!(f, "namespace prefix `{}` is not declared", prefix)
+            }
+            ErrorKind::ReservedNamespace(name) => {
+                write!(f, "`{}` binds a reserved prefix or namespace", name)
+            }
+            ErrorKind::EmptyNamespace(name) => {
+                write!(f, "`{}` binds a prefix to the empty namespace", name)
+            }
+            ErrorKind::DuplicateAttribute(name) => write!(f, "duplicate attribute `{}`", name),
+            ErrorKind::UnknownNode(id) => write!(f, "node {} does not exist", id),
+            ErrorKind::HierarchyRequest(message) => f.write_str(message),
+            ErrorKind::Unencodable(c) => {
+                write!(
+                    f,
+                    "character {:?} cannot be written in the output encoding",
+                    c
+                )
+            }
+            ErrorKind::Unwritable(message) => write!(f, "cannot write node: {}", message),
+            ErrorKind::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error {
+            kind,
+            position: None,
+            snippet: None,
+        }
+    }
 }
 
 impl From<quick_xml::Error> for Error {
     fn from(e: quick_xml::Error) -> Self {
-        Error::QuickXml(e)
+        let kind = match e {
+            quick_xml::Error::Io(e) => ErrorKind::Io(std::io::Error::new(e.kind(), e.to_string())),
+            quick_xml::Error::NonDecodable(e) => ErrorKind::Encoding(match e {
+                Some(e) => e.to_string(),
+                None => "input is not valid UTF-8".to_owned(),
+            }),
+            quick_xml::Error::UnexpectedEof(context) => ErrorKind::UnexpectedEof(context),
+            quick_xml::Error::EndEventMismatch { expected, found } if expected.is_empty() => {
+                ErrorKind::Syntax(format!("end tag `{}` without a start tag", found))
+            }
+            quick_xml::Error::EndEventMismatch { expected, found } => {
+                ErrorKind::MismatchedEndTag { expected, found }
+            }
+            quick_xml::Error::EscapeError(e) => return e.into(),
+            quick_xml::Error::InvalidAttr(e) => ErrorKind::Syntax(e.to_string()),
+            quick_xml::Error::UnknownPrefix(prefix) => {
+                ErrorKind::UndeclaredPrefix(String::from_utf8_lossy(&prefix).into_owned())
+            }
+            e => ErrorKind::Syntax(e.to_string()),
+        };
+        kind.into()
+    }
+}
+
+impl From<EscapeError> for Error {
+    fn from(e: EscapeError) -> Self {
+        let kind = match e {
+            EscapeError::UnrecognizedSymbol(_, name) => ErrorKind::UndeclaredEntity(name),
+            EscapeError::UnterminatedEntity(_) | EscapeError::EntityWithNull(_) => {
+                ErrorKind::Syntax(e.to_string())
+            }
+            e => ErrorKind::InvalidCharacterReference(e.to_string()),
+        };
+        kind.into()
     }
 }
 
 impl From<Utf8Error> for Error {
     fn from(e: Utf8Error) -> Self {
-        Error::Decode(e)
+        ErrorKind::Encoding(e.to_string()).into()
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        ErrorKind::Io(e).into()
     }
 }
@@ -1,64 +1,575 @@
 use quick_xml::events::Event;
 use quick_xml::Reader;
 
-pub use error::{Error, Result};
+pub use error::{Error, ErrorKind, Result};
 
 use crate::builder::DocumentBuilder;
 use crate::node::Document;
+use crate::resolver::Resolver;
 
 mod builder;
+pub mod chars;
+pub mod content;
+pub mod document;
+pub mod dtd;
 mod error;
+pub mod namespace;
+pub mod navigation;
 pub mod node;
-pub mod document;
+pub mod position;
+pub mod resolver;
+pub mod uri;
+pub mod writer;
+
+/// Settings for reading a [`Document`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseOptions {
+    /// Records a [`Location`](position::Location) on every node.
+    pub locations: bool,
+    /// The URI of the input, recorded in node locations and used as the
+    /// base URI of the external DTD subset.
+    pub source_uri: Option<String>,
+    /// Reads the external DTD subset and external parameter entities with
+    /// the resolver given to [`Document::load`] or
+    /// [`Document::parse_with_resolver`].
+    pub external_dtd: bool,
+    /// Includes the external parsed entities that the content refers to,
+    /// fetched with the same resolver. Otherwise references to them are
+    /// left out of the document, as XML allows a processor that does not
+    /// validate to do.
+    pub external_entities: bool,
+    pub entity_limits: EntityLimits,
+}
+
+/// Bounds on the expansion of general entities, against documents such
+/// as the "billion laughs", whose few nested entities expand to
+/// gigabytes of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntityLimits {
+    /// How deeply entity references may nest in replacement text.
+    pub max_depth: usize,
+    /// How many bytes of replacement text may be expanded in all,
+    /// counting every reference.
+    pub max_expansion: usize,
+}
 
-/// TODO
-fn deserialize_to_document(xml: &str) -> Result<Document> {
+impl Default for EntityLimits {
+    fn default() -> Self {
+        EntityLimits {
+            max_depth: 32,
+            max_expansion: 16 * 1024 * 1024,
+        }
+    }
+}
+
+/// Reads every event of `xml` into a [`Document`] node tree, expanding
+/// entity references and normalizing attribute values. `resolver` fetches
+/// the external DTD subset and external entities, if the options ask for
+/// them.
+fn deserialize_to_document(
+    xml: &str,
+    options: &ParseOptions,
+    resolver: Option<&dyn Resolver>,
+) -> Result<Document> {
     let mut reader = Reader::from_str(xml);
-    let mut builder = DocumentBuilder::new();
+    // Where in `xml` the input of `reader` starts.
+    let mut consumed = 0;
+    let mut builder = DocumentBuilder::new(xml, options, resolver);
 
     loop {
-        match reader.read_event() {
-            Err(e) => {
-                return Err(Error::QuickXml(e));
+        let start = consumed + reader.buffer_position();
+        // quick-xml ends a DOCTYPE at the first `>` that balances a `<`,
+        // even inside a literal or comment, so it is delimited here and
+        // reading resumes after it.
+        if xml[start..].starts_with("<!DOCTYPE") {
+            if let Some(length) = dtd::doctype_length(&xml[start..]) {
+                builder.set_span(start..start + length);
+                builder.set_doctype().map_err(|e| e.at(xml, start))?;
+                consumed = start + length;
+                reader = Reader::from_str(&xml[consumed..]);
+                continue;
             }
+        }
 
-            Ok(Event::Eof) => {
-                return Ok(builder.build());
+        let event = match reader.read_event() {
+            Ok(event) => event,
+            Err(e @ quick_xml::Error::EndEventMismatch { .. }) => {
+                return Err(Error::from(e).at(xml, start));
             }
-
-            Ok(Event::Decl(e)) => {
-                builder.set_decl(&e);
-                continue;
+            Err(e) => {
+                return Err(Error::from(e).at(xml, consumed + reader.buffer_position()));
             }
+        };
 
-            Ok(Event::DocType(e)) => {
-                builder.set_doctype(&e);
-                continue;
+        builder.set_span(start..consumed + reader.buffer_position());
+        let result = match event {
+            Event::Eof => {
+                return builder.build().map_err(|e| e.at(xml, xml.len()));
             }
 
-            Ok(Event::Comment(_e)) => {
-                continue;
-            }
+            Event::Decl(e) => builder.set_decl(&e),
+            Event::DocType(_) => builder.set_doctype(),
+            Event::Comment(e) => builder.comment(&e),
+            Event::PI(e) => builder.processing_instruction(&e),
+            Event::Text(e) => builder.text(&e),
+            Event::CData(e) => builder.cdata(&e),
+            Event::Start(e) => builder.start_element(&e),
+            Event::Empty(e) => builder.empty_element(&e),
+            Event::End(e) => builder.end_element(&e),
+        };
 
-            Ok(Event::PI(_e)) => {
-                continue;
-            }
+        result.map_err(|e| e.at(xml, start))?;
+    }
+}
 
-            Ok(Event::Text(_e)) => {
-                continue;
-            }
+#[cfg(test)]
+mod tests {
+    use crate::namespace::XML_NAMESPACE;
+    use crate::node::{Document, Node};
+    use crate::resolver::MapResolver;
+    use crate::writer::WriteOptions;
+    use crate::{EntityLimits, Error, ErrorKind, ParseOptions};
 
-            Ok(Event::CData(_e)) => {
-                continue;
-            }
+    const XS: &str = "http://www.w3.org/2001/XMLSchema";
 
-            Ok(Event::Start(_e)) | Ok(Event::Empty(_e)) => {
-                continue;
-            }
+    #[test]
+    fn builds_node_tree() -> Result<(), Error> {
+        let xml = r#"<?xml version="1.0"?>
+<!-- prolog -->
+<root a="1 &amp; 2"><child>text &lt; more</child><![CDATA[<raw>]]><?pi data?><empty/></root>"#;
+        let document = Document::parse(xml)?;
 
-            Ok(Event::End(_e)) => {
-                continue;
-            }
+        assert_eq!(document.decl.0, r#"xml version="1.0""#);
+        assert_eq!(document.children.len(), 2);
+        assert_eq!(document.children[1], document.root);
+
+        let Node::Element(root) = &document.nodes[&document.root] else {
+            panic!("root is not an element");
+        };
+        assert_eq!(root.name.local_name, "root");
+        assert_eq!(root.attributes[0].value, "1 & 2");
+        assert_eq!(root.children.len(), 4);
+
+        let Node::Element(child) = &document.nodes[&root.children[0]] else {
+            panic!("first child is not an element");
+        };
+        assert_eq!(child.parent, Some(document.root));
+        match &document.nodes[&child.children[0]] {
+            Node::Text(text) => assert_eq!(text.data, "text < more"),
+            _ => panic!("expected text"),
+        }
+        match &document.nodes[&root.children[1]] {
+            Node::CData(cdata) => assert_eq!(cdata.data, "<raw>"),
+            _ => panic!("expected CDATA"),
+        }
+        match &document.nodes[&root.children[2]] {
+            Node::ProcessingInstruction(pi) => assert_eq!(pi.data, "pi data"),
+            _ => panic!("expected processing instruction"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        assert!(matches!(
+            Document::parse(""),
+            Err(Error {
+                kind: ErrorKind::MissingRootElement,
+                ..
+            })
+        ));
+        assert!(matches!(
+            Document::parse("<a/><b/>"),
+            Err(Error {
+                kind: ErrorKind::MultipleRootElements,
+                ..
+            })
+        ));
+        assert!(
+            matches!(Document::parse("<a>"), Err(Error { kind: ErrorKind::UnclosedElement(name), .. }) if name == "a")
+        );
+        assert!(matches!(
+            Document::parse("text<a/>"),
+            Err(Error {
+                kind: ErrorKind::ContentOutsideRoot,
+                ..
+            })
+        ));
+        assert!(Document::parse("<a></b>").is_err());
+
+        assert!(Document::parse("\u{feff}<?xml version='1.0'?><a/>").is_ok());
+        for xml in [
+            "<a/><?xml version='1.0'?>",
+            "<?xml version='1.0'?><?xml version='1.0'?><a/>",
+            " <?xml version='1.0'?><a/>",
+            "<a>]]></a>",
+        ] {
+            assert!(
+                matches!(
+                    Document::parse(xml),
+                    Err(Error {
+                        kind: ErrorKind::Syntax(_),
+                        ..
+                    })
+                ),
+                "{}",
+                xml
+            );
+        }
+        assert!(Document::parse("<a>]]&gt; ]] ></a>").is_ok());
+        for xml in [
+            "<a>\u{1}</a>",
+            "<a><![CDATA[\u{1}]]></a>",
+            "<a><!--\u{fffe}--></a>",
+        ] {
+            assert!(
+                matches!(
+                    Document::parse(xml),
+                    Err(Error {
+                        kind: ErrorKind::InvalidCharacter(_),
+                        ..
+                    })
+                ),
+                "{:?}",
+                xml
+            );
         }
+        let error = Document::parse("<a>\n x\u{1}</a>").unwrap_err();
+        assert_eq!(error.position.map(|p| (p.line, p.column)), Some((2, 3)));
+    }
+
+    #[test]
+    fn resolves_namespaces() -> Result<(), Error> {
+        let xml = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns="urn:default">
+<xs:element name="a" xml:lang="en"/><plain xmlns="" attr="v"/></xs:schema>"#;
+        let document = Document::parse(xml)?;
+
+        let root = document.nodes[&document.root].as_element().unwrap();
+        assert!(root.name.is(Some(XS), "schema"));
+        assert_eq!(root.name.to_string(), "xs:schema");
+        assert_eq!(root.namespaces.len(), 2);
+        assert!(root.attributes.is_empty());
+
+        let element = document.nodes[&root.children[1]].as_element().unwrap();
+        assert_eq!(element.attribute(None, "name"), Some("a"));
+        assert_eq!(element.attribute(Some(XML_NAMESPACE), "lang"), Some("en"));
+        assert_eq!(
+            document.lookup_namespace_uri(root.children[1], None),
+            Some("urn:default".to_owned())
+        );
+
+        let plain = document.nodes[&root.children[2]].as_element().unwrap();
+        assert!(plain.name.is(None, "plain"));
+        assert!(plain.attributes[0].name.is(None, "attr"));
+        assert_eq!(document.lookup_namespace_uri(root.children[2], None), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_namespace_errors() {
+        let parse = Document::parse;
+        assert!(
+            matches!(parse("<p:a/>"), Err(Error { kind: ErrorKind::UndeclaredPrefix(p), .. }) if p == "p")
+        );
+        assert!(matches!(
+            parse(r#"<a xmlns:xml="urn:x"/>"#),
+            Err(Error {
+                kind: ErrorKind::ReservedNamespace(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse(r#"<a xmlns:xmlns="urn:x"/>"#),
+            Err(Error {
+                kind: ErrorKind::ReservedNamespace(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse(r#"<a xmlns:p="http://www.w3.org/2000/xmlns/"/>"#),
+            Err(Error {
+                kind: ErrorKind::ReservedNamespace(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse(r#"<a xmlns:p=""/>"#),
+            Err(Error {
+                kind: ErrorKind::EmptyNamespace(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse("<a:b:c/>"),
+            Err(Error {
+                kind: ErrorKind::InvalidQName(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse(r#"<a xmlns:p="urn:x" xmlns:q="urn:x" p:b="1" q:b="2"/>"#),
+            Err(Error {
+                kind: ErrorKind::DuplicateAttribute(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn reports_error_positions() {
+        let error = Document::parse("<a>\n  <b>x &bogus; y</b>\n</a>").unwrap_err();
+        assert!(matches!(&error.kind, ErrorKind::UndeclaredEntity(name) if name == "bogus"));
+        let position = error.position.unwrap();
+        assert_eq!(
+            (position.line, position.column, position.offset),
+            (2, 8, 11)
+        );
+        assert_eq!(error.snippet.as_deref(), Some("  <b>x &bogus; y</b>"));
+        assert_eq!(
+            error.to_string(),
+            "undeclared entity `&bogus;` at line 2, column 8\n    <b>x &bogus; y</b>"
+        );
+
+        let error = Document::parse("<a>\n<b></c></a>").unwrap_err();
+        assert!(matches!(
+            &error.kind,
+            ErrorKind::MismatchedEndTag { expected, found } if expected == "b" && found == "c"
+        ));
+        assert_eq!(error.position.map(|p| (p.line, p.column)), Some((2, 4)));
+
+        let error = Document::parse("<a>\n<p:b/></a>").unwrap_err();
+        assert_eq!(error.position.map(|p| (p.line, p.column)), Some((2, 1)));
+    }
+
+    #[test]
+    fn delimits_doctype_by_its_markup() -> Result<(), Error> {
+        let document = Document::parse(r#"<!DOCTYPE a [<!ENTITY g ">">]><a>&g;</a>"#)?;
+        let root = document.node(document.root).unwrap();
+        match document.node(root.children()[0]) {
+            Some(Node::Text(text)) => assert_eq!(text.data, ">"),
+            _ => panic!("expected text"),
+        }
+
+        let xml = "<!DOCTYPE a [<!-- a > b --><?pi >?><!ELEMENT a ANY>]>\n<a/>";
+        let document = Document::parse(xml)?;
+        assert_eq!(
+            document.doc_type.0,
+            "a [<!-- a > b --><?pi >?><!ELEMENT a ANY>]"
+        );
+        assert!(document.dtd.unwrap().element("a").is_some());
+
+        let xml = r#"<!DOCTYPE a [<!ATTLIST a b CDATA "x>y" c CDATA 'z]>'>]><a/>"#;
+        let document = Document::parse(xml)?;
+        let root = document.element(document.root).unwrap();
+        assert_eq!(root.attribute(None, "b"), Some("x>y"));
+        assert_eq!(root.attribute(None, "c"), Some("z]>"));
+
+        let options = ParseOptions {
+            locations: true,
+            ..ParseOptions::default()
+        };
+        let document = Document::parse_with("<!DOCTYPE a SYSTEM \"a>b\">\n<a/>", &options)?;
+        assert_eq!(
+            document.dtd.as_ref().unwrap().system_id.as_deref(),
+            Some("a>b")
+        );
+        let location = document.node(document.root).unwrap().location().unwrap();
+        assert_eq!((location.start.line, location.start.column), (2, 1));
+
+        let error = Document::parse("<!DOCTYPE a [\n<!ENTITY g \">\" bad>]><a/>").unwrap_err();
+        assert_eq!(error.position.map(|p| (p.line, p.column)), Some((2, 16)));
+        let error = Document::parse("<!DOCTYPE a [\n<!ENTITY g \">\">]>\n<a></b>").unwrap_err();
+        assert_eq!(error.position.map(|p| (p.line, p.column)), Some((3, 4)));
+        assert!(Document::parse("<!DOCTYPE a [<!-- > ]><a/>").is_err());
+
+        for (xml, message) in [
+            ("<a/><!DOCTYPE b>", "must come before the root element"),
+            ("<a><!DOCTYPE b></a>", "must come before the root element"),
+            (
+                "<!DOCTYPE a><!DOCTYPE a><a/>",
+                "at most one document type declaration",
+            ),
+        ] {
+            let error = Document::parse(xml).unwrap_err();
+            assert!(
+                matches!(&error.kind, ErrorKind::Syntax(text) if text.contains(message)),
+                "{}: {}",
+                xml,
+                error
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn normalizes_line_ends() -> Result<(), Error> {
+        let xml = "<!DOCTYPE a [<!ENTITY e \"1\r\n2\r3&#13;\">]>\r\n<a>x\r\ny\rz&e;<![CDATA[\r\n]]><!--\r--></a>";
+        let document = Document::parse(xml)?;
+        let root = document.node(document.root).unwrap();
+        let data: Vec<_> = root
+            .children()
+            .iter()
+            .map(|&id| match document.node(id) {
+                Some(Node::Text(text)) => text.data.as_str(),
+                Some(Node::CData(cdata)) => cdata.data.as_str(),
+                Some(Node::Comment(comment)) => comment.data.as_str(),
+                _ => panic!("expected character data"),
+            })
+            .collect();
+        assert_eq!(data, ["x\ny\nz1\n2\n3\r", "\n", "\n"]);
+
+        let document = Document::parse("<a>x\r\ny</a>")?;
+        assert_eq!(document.serialize(&WriteOptions::default())?, "<a>x\ny</a>");
+        Ok(())
+    }
+
+    #[test]
+    fn expands_entities() -> Result<(), Error> {
+        let xml = r#"<!DOCTYPE doc [
+    <!ENTITY name "Bob &amp; Alice">
+    <!ENTITY greeting "Hello, <b>&name;</b>&#33;">
+    <!ATTLIST doc
+        ids IDREFS #IMPLIED
+        note CDATA "a&#10;b"
+        xmlns:x CDATA #FIXED "urn:x">
+]>
+<doc ids="  one
+    two " title="&name;&#9;x"><p>&greeting; Bye.</p></doc>"#;
+        let document = Document::parse(xml)?;
+
+        let root = document.nodes[&document.root].as_element().unwrap();
+        assert_eq!(root.attribute(None, "ids"), Some("one two"));
+        assert_eq!(root.attribute(None, "title"), Some("Bob & Alice\tx"));
+        assert_eq!(root.attribute(None, "note"), Some("a\nb"));
+        assert_eq!(
+            document.lookup_namespace_uri(document.root, Some("x")),
+            Some("urn:x".to_owned())
+        );
+
+        let p = document.nodes[&root.children[0]].as_element().unwrap();
+        let texts: Vec<_> = p
+            .children
+            .iter()
+            .map(|child| match &document.nodes[child] {
+                Node::Text(text) => text.data.clone(),
+                Node::Element(element) => format!("<{}>", element.name),
+                _ => String::new(),
+            })
+            .collect();
+        assert_eq!(texts, ["Hello, ", "<b>", "! Bye."]);
+        let b = document.nodes[&p.children[1]].as_element().unwrap();
+        assert!(
+            matches!(&document.nodes[&b.children[0]], Node::Text(text) if text.data == "Bob & Alice")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn includes_external_entities_when_asked() -> Result<(), Error> {
+        let xml = r#"<!DOCTYPE book [<!ENTITY ch SYSTEM "ch.xml">]><book>&ch;</book>"#;
+        let resolver: MapResolver = [(
+            "http://example.org/ch.xml",
+            r#"<?xml version="1.0" encoding="UTF-8"?><chapter/>"#,
+        )]
+        .into_iter()
+        .collect();
+        let mut options = ParseOptions {
+            source_uri: Some("http://example.org/book.xml".to_owned()),
+            ..ParseOptions::default()
+        };
+        let document = Document::parse_with_resolver(xml, &options, &resolver)?;
+        let root = document.nodes[&document.root].as_element().unwrap();
+        assert!(root.children.is_empty());
+
+        options.external_entities = true;
+        let document = Document::parse_with_resolver(xml, &options, &resolver)?;
+        let root = document.nodes[&document.root].as_element().unwrap();
+        let chapter = document.nodes[&root.children[0]].as_element().unwrap();
+        assert_eq!(chapter.name.local_name, "chapter");
+        Ok(())
+    }
+
+    #[test]
+    fn limits_entity_expansion() {
+        let laughs = r#"<!DOCTYPE lolz [
+    <!ENTITY lol "lol">
+    <!ENTITY lol1 "&lol;&lol;&lol;&lol;&lol;&lol;&lol;&lol;&lol;&lol;">
+    <!ENTITY lol2 "&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;">
+    <!ENTITY lol3 "&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;">
+    <!ENTITY lol4 "&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;">
+]>
+<lolz>&lol4;</lolz>"#;
+        let options = ParseOptions {
+            entity_limits: EntityLimits {
+                max_expansion: 10_000,
+                ..EntityLimits::default()
+            },
+            ..ParseOptions::default()
+        };
+        let error = Document::parse_with(laughs, &options).unwrap_err();
+        assert!(matches!(error.kind, ErrorKind::EntityLimitExceeded(_)));
+        assert_eq!(error.position.map(|p| (p.line, p.column)), Some((8, 7)));
+        assert!(Document::parse(laughs).is_ok());
+
+        let recursive = r#"<!DOCTYPE a [<!ENTITY x "<b>&y;</b>"><!ENTITY y "&x;">]><a>&x;</a>"#;
+        assert!(
+            matches!(Document::parse(recursive), Err(Error { kind: ErrorKind::RecursiveEntity(name), .. }) if name == "x")
+        );
+        let attribute = r#"<!DOCTYPE a [<!ENTITY x "&x;">]><a b="&x;"/>"#;
+        assert!(matches!(
+            Document::parse(attribute),
+            Err(Error {
+                kind: ErrorKind::RecursiveEntity(_),
+                ..
+            })
+        ));
+        let unbalanced = r#"<!DOCTYPE a [<!ENTITY x "<b>">]><a>&x;</a>"#;
+        assert!(Document::parse(unbalanced).is_err());
+        assert!(matches!(
+            Document::parse("<a>&#0;</a>"),
+            Err(Error {
+                kind: ErrorKind::InvalidCharacterReference(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn records_node_locations() -> Result<(), Error> {
+        let options = ParseOptions {
+            locations: true,
+            source_uri: Some("file:///config.xml".to_owned()),
+            ..ParseOptions::default()
+        };
+        let document = Document::parse_with("<a>\n  <b x=\"1\">t\u{e9}xt</b>\n</a>", &options)?;
+
+        let root = document.node(document.root).unwrap();
+        let location = root.location().unwrap();
+        assert_eq!(location.source.as_deref(), Some("file:///config.xml"));
+        assert_eq!((location.start.line, location.start.column), (1, 1));
+        assert_eq!((location.end.line, location.end.column), (3, 5));
+        assert_eq!(location.to_string(), "file:///config.xml:1:1");
+
+        let b = root.children()[1];
+        let location = document.node(b).unwrap().location().unwrap();
+        assert_eq!((location.start.line, location.start.column), (2, 3));
+        assert_eq!((location.end.line, location.end.column), (2, 20));
+
+        let text = document
+            .node(document.node(b).unwrap().children()[0])
+            .unwrap();
+        let location = text.location().unwrap();
+        assert_eq!((location.start.offset, location.end.offset), (15, 20));
+        assert_eq!((location.start.column, location.end.column), (12, 16));
+
+        assert!(Document::parse("<a/>")?
+            .node(0)
+            .unwrap()
+            .location()
+            .is_none());
+
+        Ok(())
     }
 }
@@ -0,0 +1,101 @@
+use crate::{ErrorKind, Result};
+
+/// The namespace bound to the `xml` prefix.
+pub const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// The namespace of `xmlns` attributes; it cannot be bound to any prefix.
+pub const XMLNS_NAMESPACE: &str = "http://www.w3.org/2000/xmlns/";
+
+/// A namespace declaration made by an `xmlns` or `xmlns:prefix` attribute.
+///
+/// A `None` prefix is the default namespace; an empty `uri` on the default
+/// namespace undeclares it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Namespace {
+    pub prefix: Option<String>,
+    pub uri: String,
+}
+
+/// Splits a lexical QName into its optional prefix and local part.
+pub(crate) fn split_qname(name: &str) -> Result<(Option<&str>, &str)> {
+    match name.split_once(':') {
+        None => Ok((None, name)),
+        Some((prefix, local_name))
+            if !prefix.is_empty() && !local_name.is_empty() && !local_name.contains(':') =>
+        {
+            Ok((Some(prefix), local_name))
+        }
+        Some(_) => Err(ErrorKind::InvalidQName(name.to_owned()).into()),
+    }
+}
+
+/// Returns the namespace declared by the attribute `name`, or `None` when the
+/// attribute is not a namespace declaration.
+pub(crate) fn declaration(name: &str, value: &str) -> Result<Option<Namespace>> {
+    let prefix = match split_qname(name)? {
+        (None, "xmlns") => None,
+        (Some("xmlns"), prefix) => Some(prefix),
+        _ => return Ok(None),
+    };
+
+    match prefix {
+        Some("xmlns") => return Err(ErrorKind::ReservedNamespace(name.to_owned()).into()),
+        Some("xml") if value != XML_NAMESPACE => {
+            return Err(ErrorKind::ReservedNamespace(name.to_owned()).into())
+        }
+        Some("xml") => {}
+        Some(_) if value.is_empty() => {
+            return Err(ErrorKind::EmptyNamespace(name.to_owned()).into())
+        }
+        _ if value == XML_NAMESPACE || value == XMLNS_NAMESPACE => {
+            return Err(ErrorKind::ReservedNamespace(name.to_owned()).into())
+        }
+        _ => {}
+    }
+
+    Ok(Some(Namespace {
+        prefix: prefix.map(str::to_owned),
+        uri: value.to_owned(),
+    }))
+}
+
+/// The stack of namespace declarations visible while reading a document.
+pub(crate) struct NamespaceScope {
+    scopes: Vec<Vec<Namespace>>,
+}
+
+impl NamespaceScope {
+    pub fn new() -> Self {
+        NamespaceScope { scopes: Vec::new() }
+    }
+
+    pub fn push(&mut self, declarations: Vec<Namespace>) {
+        self.scopes.push(declarations);
+    }
+
+    pub fn pop(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Resolves `prefix` against the declarations in scope. The default
+    /// namespace resolves to `None` when it is undeclared or empty.
+    pub fn resolve(&self, prefix: Option<&str>) -> Result<Option<String>> {
+        if prefix == Some("xml") {
+            return Ok(Some(XML_NAMESPACE.to_owned()));
+        }
+
+        let declared = self
+            .scopes
+            .iter()
+            .rev()
+            .flatten()
+            .find(|namespace| namespace.prefix.as_deref() == prefix);
+
+        match (declared, prefix) {
+            (Some(namespace), _) if namespace.uri.is_empty() => Ok(None),
+            (Some(namespace), _) => Ok(Some(namespace.uri.clone())),
+            (None, None) => Ok(None),
+            (None, Some(prefix)) => Err(ErrorKind::UndeclaredPrefix(prefix.to_owned()).into()),
+        }
+    }
+}
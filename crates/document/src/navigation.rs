@@ -0,0 +1,345 @@
+use std::cmp::Ordering;
+
+use crate::node::{Document, Node, NodeId};
+
+/// An item yielded by the navigation iterators.
+pub type NodeItem<'a> = (NodeId, &'a Node);
+
+impl Document {
+    /// The children of `id`, in document order. The top-level nodes are
+    /// in the `children` field.
+    pub fn child_nodes(&self, id: NodeId) -> Children<'_> {
+        let children = self.node(id).map_or(&[][..], Node::children);
+        Children {
+            document: self,
+            ids: children.iter(),
+        }
+    }
+
+    /// The descendants of `id` in document order (pre-order), excluding `id`.
+    pub fn descendants(&self, id: NodeId) -> Descendants<'_> {
+        let mut stack = self
+            .node(id)
+            .map_or(Vec::new(), |node| node.children().to_vec());
+        stack.reverse();
+        Descendants {
+            document: self,
+            stack,
+        }
+    }
+
+    /// The parent of `id`, its parent, and so on up to the document element.
+    pub fn ancestors(&self, id: NodeId) -> Ancestors<'_> {
+        Ancestors {
+            document: self,
+            current: self.node(id).and_then(Node::parent),
+        }
+    }
+
+    /// The siblings after `id`, nearest first.
+    pub fn following_siblings(&self, id: NodeId) -> Children<'_> {
+        let siblings = self.siblings(id);
+        let index = self.index(id).map_or(siblings.len(), |index| index + 1);
+        Children {
+            document: self,
+            ids: siblings[index..].iter(),
+        }
+    }
+
+    /// The siblings before `id`, nearest first.
+    pub fn preceding_siblings(&self, id: NodeId) -> std::iter::Rev<Children<'_>> {
+        let siblings = self.siblings(id);
+        let index = self.index(id).unwrap_or(0);
+        Children {
+            document: self,
+            ids: siblings[..index].iter(),
+        }
+        .rev()
+    }
+
+    /// Every node after `id` in document order, excluding its descendants.
+    pub fn following(&self, id: NodeId) -> Following<'_> {
+        Following {
+            document: self,
+            next: self.next_skipping_children(id),
+        }
+    }
+
+    /// Every node before `id` in reverse document order, excluding its
+    /// ancestors.
+    pub fn preceding(&self, id: NodeId) -> Preceding<'_> {
+        Preceding {
+            document: self,
+            ancestors: self.ancestors(id).map(|(id, _)| id).collect(),
+            current: id,
+        }
+    }
+
+    /// Compares the position of two nodes in document order. Nodes that
+    /// are not attached to the same tree are ordered by id.
+    pub fn compare_document_order(&self, a: NodeId, b: NodeId) -> Ordering {
+        if a == b {
+            return Ordering::Equal;
+        }
+
+        let path_a = self.path(a);
+        let path_b = self.path(b);
+        match (path_a, path_b) {
+            (Some(path_a), Some(path_b)) => path_a.cmp(&path_b),
+            _ => a.cmp(&b),
+        }
+    }
+
+    /// The position of `id` among its own siblings at every level, from the
+    /// top of the document down. Comparing paths compares document order.
+    fn path(&self, id: NodeId) -> Option<Vec<usize>> {
+        let mut path = Vec::new();
+        let mut current = id;
+        loop {
+            path.push(self.index(current)?);
+            match self.node(current)?.parent() {
+                Some(parent) => current = parent,
+                None => break,
+            }
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// The index of `id` among its siblings, or `None` if it is detached.
+    fn index(&self, id: NodeId) -> Option<usize> {
+        let list = self.siblings(id);
+        match self.positions.get(&id) {
+            Some(&index) if list.get(index) == Some(&id) => Some(index),
+            _ => list.iter().position(|&sibling| sibling == id),
+        }
+    }
+
+    /// The list `id` belongs to if it is attached: its parent's children,
+    /// or the top-level nodes of the document.
+    fn siblings(&self, id: NodeId) -> &[NodeId] {
+        match self.node(id).map(Node::parent) {
+            Some(Some(parent)) => self.node(parent).map_or(&[], Node::children),
+            Some(None) => &self.children,
+            None => &[],
+        }
+    }
+
+    fn next_skipping_children(&self, id: NodeId) -> Option<NodeId> {
+        let mut current = id;
+        loop {
+            if let Some((sibling, _)) = self.following_siblings(current).next() {
+                return Some(sibling);
+            }
+            current = self.node(current)?.parent()?;
+        }
+    }
+}
+
+pub struct Children<'a> {
+    document: &'a Document,
+    ids: std::slice::Iter<'a, NodeId>,
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = NodeItem<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = *self.ids.next()?;
+        Some((id, self.document.node(id)?))
+    }
+}
+
+impl DoubleEndedIterator for Children<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let id = *self.ids.next_back()?;
+        Some((id, self.document.node(id)?))
+    }
+}
+
+pub struct Descendants<'a> {
+    document: &'a Document,
+    stack: Vec<NodeId>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = NodeItem<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.stack.pop()?;
+        let node = self.document.node(id)?;
+        self.stack.extend(node.children().iter().rev());
+        Some((id, node))
+    }
+}
+
+pub struct Ancestors<'a> {
+    document: &'a Document,
+    current: Option<NodeId>,
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = NodeItem<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.current?;
+        let node = self.document.node(id)?;
+        self.current = node.parent();
+        Some((id, node))
+    }
+}
+
+pub struct Following<'a> {
+    document: &'a Document,
+    next: Option<NodeId>,
+}
+
+impl<'a> Iterator for Following<'a> {
+    type Item = NodeItem<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.next?;
+        let node = self.document.node(id)?;
+        self.next = match node.children().first() {
+            Some(&child) => Some(child),
+            None => self.document.next_skipping_children(id),
+        };
+        Some((id, node))
+    }
+}
+
+pub struct Preceding<'a> {
+    document: &'a Document,
+    ancestors: Vec<NodeId>,
+    current: NodeId,
+}
+
+impl<'a> Iterator for Preceding<'a> {
+    type Item = NodeItem<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let previous = self.document.preceding_siblings(self.current).next();
+            match previous {
+                Some((mut id, mut node)) => {
+                    // Step down to the last descendant of the previous sibling.
+                    while let Some(&last) = node.children().last() {
+                        id = last;
+                        node = self.document.node(id)?;
+                    }
+                    self.current = id;
+                    return Some((id, node));
+                }
+                None => {
+                    let parent = self.document.node(self.current)?.parent()?;
+                    self.current = parent;
+                    if !self.ancestors.contains(&parent) {
+                        return Some((parent, self.document.node(parent)?));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use crate::node::{Document, Node, NodeId};
+    use crate::Error;
+
+    fn names<'a>(items: impl Iterator<Item = (NodeId, &'a Node)>) -> Vec<String> {
+        items
+            .map(|(_, node)| match node {
+                Node::Element(element) => element.name.to_string(),
+                Node::Text(text) => text.data.clone(),
+                Node::Comment(comment) => format!("#{}", comment.data),
+                _ => String::new(),
+            })
+            .collect()
+    }
+
+    fn find(document: &Document, name: &str) -> NodeId {
+        document
+            .descendants(document.root)
+            .find(|(_, node)| node.as_element().is_some_and(|e| e.name.local_name == name))
+            .map(|(id, _)| id)
+            .unwrap()
+    }
+
+    #[test]
+    fn walks_every_axis() -> Result<(), Error> {
+        let document =
+            Document::parse("<!--c--><r><a><b/>t<c><d/></c></a><e><f/></e><g/></r><!--z-->")?;
+        let r = document.root;
+        let a = find(&document, "a");
+        let c = find(&document, "c");
+        let e = find(&document, "e");
+
+        assert_eq!(names(document.child_nodes(a)), ["b", "t", "c"]);
+        assert_eq!(
+            names(document.descendants(r)),
+            ["a", "b", "t", "c", "d", "e", "f", "g"]
+        );
+        assert_eq!(
+            names(document.ancestors(find(&document, "d"))),
+            ["c", "a", "r"]
+        );
+        assert_eq!(names(document.following_siblings(a)), ["e", "g"]);
+        assert_eq!(names(document.preceding_siblings(c)), ["t", "b"]);
+        assert_eq!(names(document.following(c)), ["e", "f", "g", "#z"]);
+        assert_eq!(
+            names(document.preceding(e)),
+            ["d", "c", "t", "b", "a", "#c"]
+        );
+        assert_eq!(names(document.following_siblings(r)), ["#z"]);
+
+        assert_eq!(document.compare_document_order(a, e), Ordering::Less);
+        assert_eq!(document.compare_document_order(c, a), Ordering::Greater);
+        assert_eq!(document.compare_document_order(r, a), Ordering::Less);
+
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_sibling_positions_through_edits() -> Result<(), Error> {
+        let mut document = Document::parse("<r><a/><b/><c/></r>")?;
+        let r = document.root;
+        let (a, b, c) = (
+            find(&document, "a"),
+            find(&document, "b"),
+            find(&document, "c"),
+        );
+
+        document.insert_before(r, c, a)?;
+        assert_eq!(names(document.following_siblings(c)), ["a", "b"]);
+        assert_eq!(names(document.preceding_siblings(b)), ["a", "c"]);
+        document.detach(a)?;
+        assert_eq!(names(document.following_siblings(c)), ["b"]);
+        assert_eq!(names(document.following_siblings(a)), Vec::<String>::new());
+        assert_eq!(document.compare_document_order(b, c), Ordering::Greater);
+
+        // Edits made through the fields are found by searching.
+        if let Some(Node::Element(root)) = document.nodes.get_mut(&r) {
+            root.children.reverse();
+        }
+        assert_eq!(names(document.following_siblings(b)), ["c"]);
+        assert_eq!(document.compare_document_order(b, c), Ordering::Less);
+
+        // Wide elements do not make each step a search.
+        let xml = format!("<r>{}</r>", "<a/>".repeat(50_000));
+        let document = Document::parse(&xml)?;
+        let last = *document
+            .node(document.root)
+            .unwrap()
+            .children()
+            .last()
+            .unwrap();
+        assert_eq!(document.preceding(last).count(), 49_999);
+        let first = document.node(document.root).unwrap().children()[0];
+        assert_eq!(document.compare_document_order(first, last), Ordering::Less);
+
+        Ok(())
+    }
+}
@@ -1,8 +1,16 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
 use std::str::from_utf8;
 
-use quick_xml::events::BytesDecl;
+use quick_xml::events::{BytesDecl, BytesText};
 
+use crate::dtd::Dtd;
+use crate::namespace::{Namespace, XML_NAMESPACE};
+use crate::position::Location;
+use crate::resolver::Resolver;
+use crate::{ParseOptions, Result};
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct DocDecl(pub String);
 
 impl Default for DocDecl {
@@ -18,6 +26,7 @@ impl From<&BytesDecl<'_>> for DocDecl {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct DocType(pub String);
 
 impl Default for DocType {
@@ -26,33 +35,125 @@ impl Default for DocType {
     }
 }
 
-impl From<&BytesDecl<'_>> for DocType {
-    fn from(value: &BytesDecl<'_>) -> Self {
+impl From<&BytesText<'_>> for DocType {
+    fn from(value: &BytesText<'_>) -> Self {
         let str = from_utf8(value).unwrap_or("");
         DocType(str.to_owned())
     }
 }
 
+#[derive(Debug, Clone, Default)]
 pub struct Document {
     pub decl: DocDecl,
+    /// The text of the `<!DOCTYPE>`, as written.
     pub doc_type: DocType,
+    /// The declarations of the `<!DOCTYPE>`.
+    pub dtd: Option<Dtd>,
     pub nodes: BTreeMap<NodeId, Node>, // Would HashMap be better?
+    /// The document element.
     pub root: NodeId,
+    /// Top-level nodes in document order: the document element plus any
+    /// comments and processing instructions around it.
+    pub children: Vec<NodeId>,
+    /// The next id handed out by [`Document::add_node`]. Ids are never reused,
+    /// so handles to removed nodes stay invalid.
+    pub(crate) next_id: NodeId,
+    /// The index of each attached node in its parent's children, or in
+    /// `children` at the top level, so navigation need not search for it.
+    /// Edits made through the fields directly can leave it stale; lookups
+    /// check the entry and search when it is.
+    pub(crate) positions: HashMap<NodeId, usize>,
 }
 
-impl Default for Document {
-    fn default() -> Self {
-        Document {
-            decl: Default::default(),
-            doc_type: Default::default(),
-            nodes: Default::default(),
-            root: Default::default(),
+/// Documents are equal when their trees are; how many nodes were created
+/// along the way, and what navigation has cached, does not matter.
+impl PartialEq for Document {
+    fn eq(&self, other: &Self) -> bool {
+        self.decl == other.decl
+            && self.doc_type == other.doc_type
+            && self.dtd == other.dtd
+            && self.nodes == other.nodes
+            && self.root == other.root
+            && self.children == other.children
+    }
+}
+
+impl Document {
+    /// Parses `xml` into a document, building the full node tree.
+    pub fn parse(xml: &str) -> Result<Document> {
+        crate::deserialize_to_document(xml, &ParseOptions::default(), None)
+    }
+
+    /// Parses `xml` with the given options.
+    pub fn parse_with(xml: &str, options: &ParseOptions) -> Result<Document> {
+        crate::deserialize_to_document(xml, options, None)
+    }
+
+    /// Parses `xml` with the given options, fetching the external DTD
+    /// subset with `resolver` if the options ask for it.
+    pub fn parse_with_resolver<R: Resolver>(
+        xml: &str,
+        options: &ParseOptions,
+        resolver: &R,
+    ) -> Result<Document> {
+        crate::deserialize_to_document(xml, options, Some(resolver))
+    }
+
+    /// Fetches `reference` with `resolver` and parses it. The URI it was
+    /// found at becomes the source URI of the options, and the external
+    /// DTD subset is fetched with `resolver` too if the options ask for it.
+    pub fn load<R: Resolver>(
+        resolver: &R,
+        reference: &str,
+        options: &ParseOptions,
+    ) -> Result<Document> {
+        let resource = resolver.resolve(None, reference)?;
+        let options = ParseOptions {
+            source_uri: Some(resource.uri.clone()),
+            ..options.clone()
+        };
+        crate::deserialize_to_document(resource.text()?, &options, Some(resolver))
+    }
+
+    /// Returns every namespace binding in scope on the element `id`, keyed by
+    /// prefix (`None` for the default namespace). Undeclared default
+    /// namespaces are left out.
+    pub fn in_scope_namespaces(&self, id: NodeId) -> BTreeMap<Option<String>, String> {
+        let mut in_scope = BTreeMap::new();
+        in_scope.insert(Some("xml".to_owned()), XML_NAMESPACE.to_owned());
+
+        let mut ancestors = Vec::new();
+        let mut current = Some(id);
+        while let Some(node) = current.and_then(|id| self.nodes.get(&id)) {
+            if let Node::Element(element) = node {
+                ancestors.push(element);
+            }
+            current = node.parent();
+        }
+
+        for element in ancestors.into_iter().rev() {
+            for namespace in &element.namespaces {
+                if namespace.uri.is_empty() {
+                    in_scope.remove(&namespace.prefix);
+                } else {
+                    in_scope.insert(namespace.prefix.clone(), namespace.uri.clone());
+                }
+            }
         }
+
+        in_scope
+    }
+
+    /// Resolves `prefix` to its namespace URI as seen from the node `id`.
+    pub fn lookup_namespace_uri(&self, id: NodeId, prefix: Option<&str>) -> Option<String> {
+        self.in_scope_namespaces(id)
+            .remove(&prefix.map(str::to_owned))
     }
 }
 
 pub type NodeId = usize;
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum Node {
     CData(CData),
     Comment(Comment),
@@ -61,33 +162,139 @@ pub enum Node {
     Text(Text),
 }
 
+impl Node {
+    pub fn parent(&self) -> Option<NodeId> {
+        match self {
+            Node::CData(n) => n.parent,
+            Node::Comment(n) => n.parent,
+            Node::Element(n) => n.parent,
+            Node::ProcessingInstruction(n) => n.parent,
+            Node::Text(n) => n.parent,
+        }
+    }
+
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Node::CData(n) => n.location.as_ref(),
+            Node::Comment(n) => n.location.as_ref(),
+            Node::Element(n) => n.location.as_ref(),
+            Node::ProcessingInstruction(n) => n.location.as_ref(),
+            Node::Text(n) => n.location.as_ref(),
+        }
+    }
+
+    pub(crate) fn set_parent(&mut self, parent: Option<NodeId>) {
+        match self {
+            Node::CData(n) => n.parent = parent,
+            Node::Comment(n) => n.parent = parent,
+            Node::Element(n) => n.parent = parent,
+            Node::ProcessingInstruction(n) => n.parent = parent,
+            Node::Text(n) => n.parent = parent,
+        }
+    }
+
+    /// The children of an element; empty for every other node.
+    pub fn children(&self) -> &[NodeId] {
+        match self {
+            Node::Element(e) => &e.children,
+            _ => &[],
+        }
+    }
+
+    pub fn as_element(&self) -> Option<&Element> {
+        match self {
+            Node::Element(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct CData {
     pub parent: Option<NodeId>,
+    pub location: Option<Location>,
     pub data: String,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Comment {
     pub parent: Option<NodeId>,
+    pub location: Option<Location>,
     pub data: String,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Element {
     pub parent: Option<NodeId>,
+    /// Where the node was read from, when location tracking was enabled.
+    pub location: Option<Location>,
+    pub name: QName,
+    /// Namespace declarations made on this element. They are not repeated in
+    /// `attributes`.
+    pub namespaces: Vec<Namespace>,
     pub attributes: Vec<Attribute>,
     pub children: Vec<NodeId>,
 }
 
+impl Element {
+    /// Returns the value of the attribute with the given expanded name.
+    pub fn attribute(&self, namespace: Option<&str>, local_name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name.is(namespace, local_name))
+            .map(|attribute| attribute.value.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct ProcessingInstruction {
     pub parent: Option<NodeId>,
+    pub location: Option<Location>,
     pub data: String,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Text {
     pub parent: Option<NodeId>,
+    pub location: Option<Location>,
     pub data: String,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Attribute {
-    pub local_name: String,
+    pub name: QName,
     pub value: String,
 }
+
+/// A qualified name together with the namespace its prefix resolved to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QName {
+    pub prefix: Option<String>,
+    pub local_name: String,
+    pub namespace: Option<String>,
+}
+
+impl QName {
+    /// Creates a name without prefix or namespace.
+    pub fn local(local_name: &str) -> Self {
+        QName {
+            prefix: None,
+            local_name: local_name.to_owned(),
+            namespace: None,
+        }
+    }
+
+    /// Returns `true` when this name has the given expanded name.
+    pub fn is(&self, namespace: Option<&str>, local_name: &str) -> bool {
+        self.namespace.as_deref() == namespace && self.local_name == local_name
+    }
+}
+
+impl Display for QName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.prefix {
+            Some(prefix) => write!(f, "{}:{}", prefix, self.local_name),
+            None => f.write_str(&self.local_name),
+        }
+    }
+}
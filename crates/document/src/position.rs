@@ -0,0 +1,100 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+/// A location in the source text. Lines and columns start at 1; columns
+/// count characters, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    /// Finds the line and column of the byte `offset` in `input`. Offsets
+    /// past the end are clamped to the end of the input.
+    pub fn locate(input: &str, offset: usize) -> Position {
+        let mut offset = offset.min(input.len());
+        while !input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        Position {
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Where a node came from: its source and the span of input it was read
+/// from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Location {
+    /// The URI of the input, when the caller supplied one.
+    pub source: Option<Arc<str>>,
+    pub start: Position,
+    /// The position just after the node's last character.
+    pub end: Position,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(source) = &self.source {
+            write!(f, "{}:", source)?;
+        }
+        write!(f, "{}:{}", self.start.line, self.start.column)
+    }
+}
+
+/// Maps byte offsets to positions without rescanning the input each time.
+pub(crate) struct LineIndex<'a> {
+    input: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(input: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(input.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        LineIndex { input, line_starts }
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.input.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = self.input[line_start..offset].chars().count() + 1;
+        Position {
+            offset,
+            line,
+            column,
+        }
+    }
+}
+
+/// The longest snippet attached to an error, in characters.
+const SNIPPET_WIDTH: usize = 60;
+
+/// Returns the part of the line around `position`, for error messages.
+pub(crate) fn snippet(input: &str, position: Position) -> String {
+    let line_start = input[..position.offset]
+        .rfind('\n')
+        .map_or(0, |index| index + 1);
+    let line_end = input[position.offset..]
+        .find('\n')
+        .map_or(input.len(), |index| position.offset + index);
+    let line = input[line_start..line_end].trim_end_matches('\r');
+
+    let skip = (position.column - 1).saturating_sub(SNIPPET_WIDTH / 2);
+    line.chars().skip(skip).take(SNIPPET_WIDTH).collect()
+}
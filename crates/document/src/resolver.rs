@@ -0,0 +1,357 @@
+//! Fetching the resources that documents refer to: schema documents,
+//! included grammars, external DTD subsets and entities.
+//!
+//! Loaders take a [`Resolver`] instead of reading files themselves, so an
+//! application can confine lookups to a directory, serve them from
+//! memory, map them through a catalog or cache them.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::uri;
+
+/// The content of a resource and the URI it was found at, against which
+/// the references it makes are resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resource {
+    pub uri: String,
+    pub bytes: Vec<u8>,
+}
+
+impl Resource {
+    /// The content as UTF-8 text, without a byte order mark.
+    pub fn text(&self) -> io::Result<&str> {
+        let bytes = self
+            .bytes
+            .strip_prefix(b"\xEF\xBB\xBF")
+            .unwrap_or(&self.bytes);
+        std::str::from_utf8(bytes).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("`{}` is not UTF-8: {}", self.uri, e),
+            )
+        })
+    }
+}
+
+/// Fetches the resources that references point to.
+pub trait Resolver {
+    /// Resolves `reference` against `base`, the URI of the resource that
+    /// makes the reference, or `None` for a starting resource, and fetches
+    /// the result.
+    fn resolve(&self, base: Option<&str>, reference: &str) -> io::Result<Resource>;
+
+    /// Fetches the external entity or DTD subset with the given public
+    /// and system identifiers. Resolvers that know nothing of public
+    /// identifiers fetch the system identifier.
+    fn resolve_entity(
+        &self,
+        base: Option<&str>,
+        public_id: Option<&str>,
+        system_id: &str,
+    ) -> io::Result<Resource> {
+        let _ = public_id;
+        self.resolve(base, system_id)
+    }
+}
+
+impl<R: Resolver + ?Sized> Resolver for &R {
+    fn resolve(&self, base: Option<&str>, reference: &str) -> io::Result<Resource> {
+        (**self).resolve(base, reference)
+    }
+
+    fn resolve_entity(
+        &self,
+        base: Option<&str>,
+        public_id: Option<&str>,
+        system_id: &str,
+    ) -> io::Result<Resource> {
+        (**self).resolve_entity(base, public_id, system_id)
+    }
+}
+
+/// The URI `reference` names, relative to `base` if given.
+fn absolute(base: Option<&str>, reference: &str) -> String {
+    match base {
+        Some(base) => uri::resolve(base, reference),
+        None => reference.trim().to_owned(),
+    }
+}
+
+/// Reads file paths and `file:` URIs from the filesystem, optionally only
+/// below one directory. Resources are identified by the `file:` URI of
+/// their canonical path.
+#[derive(Debug, Clone, Default)]
+pub struct FileResolver {
+    root: Option<PathBuf>,
+}
+
+impl FileResolver {
+    pub fn new() -> FileResolver {
+        FileResolver::default()
+    }
+
+    /// A resolver that refuses files outside `root`, including those that
+    /// symbolic links lead out of it.
+    pub fn sandboxed<P: AsRef<Path>>(root: P) -> io::Result<FileResolver> {
+        Ok(FileResolver {
+            root: Some(root.as_ref().canonicalize()?),
+        })
+    }
+}
+
+impl Resolver for FileResolver {
+    fn resolve(&self, base: Option<&str>, reference: &str) -> io::Result<Resource> {
+        let uri = absolute(base, reference);
+        let path = uri::to_file_path(&uri).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("`{}` is not a file", uri),
+            )
+        })?;
+        let path = path.canonicalize()?;
+        if let Some(root) = &self.root {
+            if !path.starts_with(root) {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("`{}` is outside `{}`", path.display(), root.display()),
+                ));
+            }
+        }
+        let bytes = std::fs::read(&path)?;
+        Ok(Resource {
+            uri: uri::from_file_path(&path)?,
+            bytes,
+        })
+    }
+}
+
+/// Serves resources from memory, keyed by absolute URI.
+#[derive(Debug, Clone, Default)]
+pub struct MapResolver {
+    resources: HashMap<String, Vec<u8>>,
+}
+
+impl MapResolver {
+    pub fn new() -> MapResolver {
+        MapResolver::default()
+    }
+
+    /// Makes `content` available under `uri`.
+    pub fn insert(&mut self, uri: impl Into<String>, content: impl Into<Vec<u8>>) {
+        self.resources.insert(uri.into(), content.into());
+    }
+}
+
+impl<U: Into<String>, C: Into<Vec<u8>>> FromIterator<(U, C)> for MapResolver {
+    fn from_iter<I: IntoIterator<Item = (U, C)>>(iter: I) -> Self {
+        let mut resolver = MapResolver::new();
+        for (uri, content) in iter {
+            resolver.insert(uri, content);
+        }
+        resolver
+    }
+}
+
+impl Resolver for MapResolver {
+    fn resolve(&self, base: Option<&str>, reference: &str) -> io::Result<Resource> {
+        let uri = absolute(base, reference);
+        match self.resources.get(&uri) {
+            Some(bytes) => Ok(Resource {
+                uri,
+                bytes: bytes.clone(),
+            }),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("`{}` is not available", uri),
+            )),
+        }
+    }
+}
+
+/// Remembers what another resolver fetched, so each resource is fetched
+/// once. Failures are not remembered.
+#[derive(Debug, Default)]
+pub struct CachingResolver<R> {
+    resolver: R,
+    cache: RefCell<HashMap<Lookup, Resource>>,
+}
+
+/// The arguments of a call to a [`Resolver`]. Entities are looked up
+/// apart from other resources, with their public identifier.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Lookup {
+    base: Option<String>,
+    entity: Option<Option<String>>,
+    reference: String,
+}
+
+impl<R: Resolver> CachingResolver<R> {
+    pub fn new(resolver: R) -> CachingResolver<R> {
+        CachingResolver {
+            resolver,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn cached(
+        &self,
+        key: Lookup,
+        fetch: impl FnOnce() -> io::Result<Resource>,
+    ) -> io::Result<Resource> {
+        if let Some(resource) = self.cache.borrow().get(&key) {
+            return Ok(resource.clone());
+        }
+        let resource = fetch()?;
+        self.cache.borrow_mut().insert(key, resource.clone());
+        Ok(resource)
+    }
+}
+
+impl<R: Resolver> Resolver for CachingResolver<R> {
+    fn resolve(&self, base: Option<&str>, reference: &str) -> io::Result<Resource> {
+        let key = Lookup {
+            base: base.map(str::to_owned),
+            entity: None,
+            reference: reference.to_owned(),
+        };
+        self.cached(key, || self.resolver.resolve(base, reference))
+    }
+
+    fn resolve_entity(
+        &self,
+        base: Option<&str>,
+        public_id: Option<&str>,
+        system_id: &str,
+    ) -> io::Result<Resource> {
+        let key = Lookup {
+            base: base.map(str::to_owned),
+            entity: Some(public_id.map(str::to_owned)),
+            reference: system_id.to_owned(),
+        };
+        self.cached(key, || {
+            self.resolver.resolve_entity(base, public_id, system_id)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::io;
+
+    use crate::node::Document;
+    use crate::resolver::{CachingResolver, FileResolver, MapResolver, Resolver, Resource};
+    use crate::{Error, ParseOptions};
+
+    #[test]
+    fn resolves_relative_references() -> Result<(), Error> {
+        let resolver: MapResolver = [
+            ("http://example.org/a/main.xsd", "main"),
+            ("http://example.org/b/types.xsd", "types"),
+            ("http://example.org/doc.xml", "<doc/>"),
+        ]
+        .into_iter()
+        .collect();
+        let main = resolver.resolve(None, "http://example.org/a/main.xsd")?;
+        let types = resolver.resolve(Some(&main.uri), "../b/types.xsd")?;
+        assert_eq!(types.uri, "http://example.org/b/types.xsd");
+        assert_eq!(types.text()?, "types");
+        let options = ParseOptions {
+            locations: true,
+            source_uri: None,
+            ..ParseOptions::default()
+        };
+        let document = Document::load(&resolver, "http://example.org/doc.xml", &options)?;
+        let location = document
+            .element(document.root)
+            .and_then(|e| e.location.clone());
+        assert_eq!(
+            location.and_then(|location| location.source).as_deref(),
+            Some("http://example.org/doc.xml")
+        );
+        let missing = resolver.resolve(Some(&main.uri), "other.xsd");
+        assert_eq!(
+            missing.map_err(|e| e.kind()).map(|_| ()),
+            Err(io::ErrorKind::NotFound)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_lookups_in_the_sandbox() -> io::Result<()> {
+        let root = std::env::temp_dir().join(format!("resolver-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("inside"))?;
+        std::fs::write(root.join("inside/a.xml"), "<a/>")?;
+        std::fs::write(root.join("b.xml"), "<b/>")?;
+
+        let resolver = FileResolver::sandboxed(root.join("inside"))?;
+        let a = resolver.resolve(None, &root.join("inside/a.xml").display().to_string())?;
+        assert_eq!(a.text()?, "<a/>");
+        let b = resolver.resolve(Some(&a.uri), "../b.xml");
+        assert_eq!(
+            b.map_err(|e| e.kind()).map(|_| ()),
+            Err(io::ErrorKind::PermissionDenied)
+        );
+        assert!(FileResolver::new()
+            .resolve(Some(&a.uri), "../b.xml")
+            .is_ok());
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn identifies_files_by_uri() -> io::Result<()> {
+        let root = std::env::temp_dir().join(format!("resolver-uri-{}", std::process::id()));
+        let directory = root.join("d#1 %20");
+        std::fs::create_dir_all(&directory)?;
+        std::fs::write(directory.join("main.xml"), "<main/>")?;
+        std::fs::write(directory.join("inc.txt"), "inside")?;
+        std::fs::write(root.join("inc.txt"), "outside")?;
+
+        let resolver = FileResolver::new();
+        let main = resolver.resolve(None, &directory.join("main.xml").display().to_string())?;
+        assert!(main.uri.starts_with("file://"));
+        assert!(
+            main.uri.ends_with("/d%231%20%2520/main.xml"),
+            "{}",
+            main.uri
+        );
+        assert_eq!(
+            resolver.resolve(Some(&main.uri), "inc.txt")?.text()?,
+            "inside"
+        );
+        assert_eq!(
+            resolver.resolve(Some(&main.uri), "../inc.txt")?.text()?,
+            "outside"
+        );
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn caches_resources() -> io::Result<()> {
+        struct Counting(Cell<usize>);
+        impl Resolver for Counting {
+            fn resolve(&self, _: Option<&str>, reference: &str) -> io::Result<Resource> {
+                self.0.set(self.0.get() + 1);
+                Ok(Resource {
+                    uri: reference.to_owned(),
+                    bytes: Vec::new(),
+                })
+            }
+        }
+
+        let resolver = CachingResolver::new(Counting(Cell::new(0)));
+        resolver.resolve(None, "a.xml")?;
+        resolver.resolve(None, "a.xml")?;
+        resolver.resolve_entity(None, Some("-//A//EN"), "a.xml")?;
+        resolver.resolve_entity(None, Some("-//A//EN"), "a.xml")?;
+        assert_eq!(resolver.resolver.0.get(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+//! Resolution of URI references (RFC 3986), as needed to follow the
+//! references between documents.
+
+use std::io;
+use std::path::{Component, Path, PathBuf, Prefix};
+
+/// The components of a URI reference.
+struct Parts<'a> {
+    scheme: Option<&'a str>,
+    authority: Option<&'a str>,
+    path: &'a str,
+    query: Option<&'a str>,
+    fragment: Option<&'a str>,
+}
+
+fn parse(uri: &str) -> Parts<'_> {
+    let (rest, fragment) = match uri.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (uri, None),
+    };
+    let (rest, query) = match rest.split_once('?') {
+        Some((rest, query)) => (rest, Some(query)),
+        None => (rest, None),
+    };
+    let scheme_end = rest.find(':').filter(|&end| {
+        let scheme = &rest[..end];
+        // A single letter is a drive, as in `C:/catalog.xml`.
+        scheme.len() > 1
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    });
+    let (scheme, rest) = match scheme_end {
+        Some(end) => (Some(&rest[..end]), &rest[end + 1..]),
+        None => (None, rest),
+    };
+    let (authority, path) = match rest.strip_prefix("//") {
+        Some(rest) => {
+            let end = rest.find('/').unwrap_or(rest.len());
+            (Some(&rest[..end]), &rest[end..])
+        }
+        None => (None, rest),
+    };
+    Parts {
+        scheme,
+        authority,
+        path,
+        query,
+        fragment,
+    }
+}
+
+/// Resolves `reference` against `base`, following section 5.2 of RFC
+/// 3986. A base without a scheme, such as a file path, is resolved
+/// against as a path; leading `..` segments of relative paths are kept.
+pub fn resolve(base: &str, reference: &str) -> String {
+    let reference = parse(reference.trim());
+    let base = parse(base.trim());
+    let (scheme, authority, path, query) = if reference.scheme.is_some() {
+        (
+            reference.scheme,
+            reference.authority,
+            remove_dot_segments(reference.path),
+            reference.query,
+        )
+    } else if reference.authority.is_some() {
+        (
+            base.scheme,
+            reference.authority,
+            remove_dot_segments(reference.path),
+            reference.query,
+        )
+    } else if reference.path.is_empty() {
+        (
+            base.scheme,
+            base.authority,
+            base.path.to_owned(),
+            reference.query.or(base.query),
+        )
+    } else if reference.path.starts_with('/') {
+        (
+            base.scheme,
+            base.authority,
+            remove_dot_segments(reference.path),
+            reference.query,
+        )
+    } else {
+        let merged = match (base.authority, base.path) {
+            (Some(_), "") => format!("/{}", reference.path),
+            (_, path) => match path.rfind('/') {
+                Some(end) => format!("{}{}", &path[..=end], reference.path),
+                None => reference.path.to_owned(),
+            },
+        };
+        (
+            base.scheme,
+            base.authority,
+            remove_dot_segments(&merged),
+            reference.query,
+        )
+    };
+
+    let mut uri = String::new();
+    if let Some(scheme) = scheme {
+        uri.push_str(scheme);
+        uri.push(':');
+    }
+    if let Some(authority) = authority {
+        uri.push_str("//");
+        uri.push_str(authority);
+    }
+    uri.push_str(&path);
+    if let Some(query) = query {
+        uri.push('?');
+        uri.push_str(query);
+    }
+    if let Some(fragment) = reference.fragment {
+        uri.push('#');
+        uri.push_str(fragment);
+    }
+    uri
+}
+
+fn remove_dot_segments(path: &str) -> String {
+    let absolute = path.starts_with('/');
+    let segments: Vec<_> = path.split('/').collect();
+    let last = segments.len() - 1;
+    let mut output: Vec<&str> = Vec::new();
+    for (index, segment) in segments.into_iter().enumerate() {
+        match segment {
+            "." => {}
+            ".." => {
+                if output
+                    .last()
+                    .is_some_and(|last| !last.is_empty() && *last != "..")
+                {
+                    output.pop();
+                } else if !absolute {
+                    output.push("..");
+                }
+            }
+            segment => {
+                output.push(segment);
+                continue;
+            }
+        }
+        // A path ending in a dot segment names a directory.
+        if index == last {
+            output.push("");
+        }
+    }
+    match (absolute, output.as_slice()) {
+        (true, [""]) => "/".to_owned(),
+        _ => output.join("/"),
+    }
+}
+
+/// The `file:` URI of a filesystem path, made absolute against the
+/// current directory, with the characters that have a meaning in URIs
+/// percent-encoded. [`to_file_path`] gives the path back.
+pub fn from_file_path(path: &Path) -> io::Result<String> {
+    let path = std::path::absolute(path)?;
+    let mut authority = String::new();
+    let mut uri_path = String::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => match prefix.kind() {
+                Prefix::Disk(drive) | Prefix::VerbatimDisk(drive) => {
+                    uri_path.push_str(&format!("/{}:", char::from(drive)))
+                }
+                Prefix::UNC(server, share) | Prefix::VerbatimUNC(server, share) => {
+                    authority = encode_segment(&server.to_string_lossy());
+                    uri_path.push('/');
+                    uri_path.push_str(&encode_segment(&share.to_string_lossy()));
+                }
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        format!("`{}` has no file URI", path.display()),
+                    ))
+                }
+            },
+            Component::RootDir => {}
+            component => {
+                uri_path.push('/');
+                uri_path.push_str(&encode_segment(&component.as_os_str().to_string_lossy()));
+            }
+        }
+    }
+    if uri_path.is_empty() {
+        uri_path.push('/');
+    }
+    Ok(format!("file://{}{}", authority, uri_path))
+}
+
+/// Percent-encodes what may not appear as such in a path segment.
+fn encode_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        match byte.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@".contains(&byte) {
+            true => encoded.push(char::from(byte)),
+            false => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// The filesystem path that a `file:` URI, or a reference without a
+/// scheme, names. Other URIs have none. On Windows, a URI with a host
+/// names a share on it.
+pub fn to_file_path(uri: &str) -> Option<PathBuf> {
+    let uri = uri.trim();
+    let parts = parse(uri);
+    let (server, path) = match parts.scheme {
+        None => return Some(PathBuf::from(uri)),
+        Some(scheme) if scheme.eq_ignore_ascii_case("file") => match parts.authority {
+            None | Some("") | Some("localhost") => (None, parts.path),
+            Some(server) if cfg!(windows) => (Some(server), parts.path),
+            Some(_) => return None,
+        },
+        Some(_) => return None,
+    };
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let [first, tail @ ..] = rest {
+        let escaped = match tail {
+            [high, low, ..] if *first == b'%' => std::str::from_utf8(&[*high, *low])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                bytes.push(byte);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(*first);
+                rest = tail;
+            }
+        }
+    }
+    let path = String::from_utf8_lossy(&bytes);
+    Some(match (server, path.as_bytes()) {
+        (Some(server), _) => PathBuf::from(format!(r"\\{}{}", server, path.replace('/', "\\"))),
+        // `/C:/dir` is the drive path `C:/dir`.
+        (None, [b'/', drive, b':', ..]) if cfg!(windows) && drive.is_ascii_alphabetic() => {
+            PathBuf::from(&path[1..])
+        }
+        (None, _) => PathBuf::from(path.into_owned()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::uri::{from_file_path, resolve, to_file_path};
+
+    #[test]
+    fn resolves_references() {
+        let base = "http://a/b/c/d;p?q";
+        let cases = [
+            ("g", "http://a/b/c/g"),
+            ("./g/", "http://a/b/c/g/"),
+            ("/g", "http://a/g"),
+            ("//g", "http://g"),
+            ("?y", "http://a/b/c/d;p?y"),
+            ("g#s", "http://a/b/c/g#s"),
+            ("..", "http://a/b/"),
+            ("../../../g", "http://a/g"),
+            ("mailto:x@y", "mailto:x@y"),
+        ];
+        for (reference, expected) in cases {
+            assert_eq!(resolve(base, reference), expected, "{}", reference);
+        }
+        assert_eq!(
+            resolve("/etc/xml/catalog", "docbook/catalog.xml"),
+            "/etc/xml/docbook/catalog.xml"
+        );
+        assert_eq!(resolve("catalogs/main.xml", "../dtd/a.dtd"), "dtd/a.dtd");
+        assert_eq!(resolve("main.xml", "../dtd/a.dtd"), "../dtd/a.dtd");
+        assert_eq!(
+            resolve("file:///C:/xml/catalog.xml", "a.xsd"),
+            "file:///C:/xml/a.xsd"
+        );
+    }
+
+    #[test]
+    fn maps_file_uris_to_paths() {
+        assert_eq!(
+            to_file_path("file:///etc/xml/my%20catalog"),
+            Some(PathBuf::from("/etc/xml/my catalog"))
+        );
+        assert_eq!(
+            to_file_path("file://localhost/etc/xml/catalog"),
+            Some(PathBuf::from("/etc/xml/catalog"))
+        );
+        assert_eq!(
+            to_file_path("schemas/a.xsd"),
+            Some(PathBuf::from("schemas/a.xsd"))
+        );
+        assert_eq!(to_file_path("http://example.org/a.xsd"), None);
+    }
+
+    #[test]
+    fn maps_paths_to_file_uris() -> std::io::Result<()> {
+        let path = Path::new("/tmp/d#1/q?/a b%.xml");
+        let uri = from_file_path(path)?;
+        if cfg!(unix) {
+            assert_eq!(uri, "file:///tmp/d%231/q%3F/a%20b%25.xml");
+        }
+        assert_eq!(
+            to_file_path(&resolve(&uri, "c.xml")),
+            Some(std::path::absolute("/tmp/d#1/q?/c.xml")?)
+        );
+        assert!(from_file_path(Path::new("relative.xml"))?.starts_with("file:///"));
+        Ok(())
+    }
+}
@@ -0,0 +1,444 @@
+use std::io::Write;
+
+use crate::node::{Document, Element, Node, NodeId};
+use crate::{ErrorKind, Result};
+
+/// Character encodings the writer can produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+    Ascii,
+}
+
+impl Encoding {
+    /// The name written in the `encoding` pseudo-attribute.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le | Encoding::Utf16Be => "UTF-16",
+            Encoding::Latin1 => "ISO-8859-1",
+            Encoding::Ascii => "US-ASCII",
+        }
+    }
+
+    fn can_encode(&self, c: char) -> bool {
+        match self {
+            Encoding::Utf8 | Encoding::Utf16Le | Encoding::Utf16Be => true,
+            Encoding::Latin1 => (c as u32) < 0x100,
+            Encoding::Ascii => c.is_ascii(),
+        }
+    }
+
+    fn encode(&self, text: &str) -> Vec<u8> {
+        match self {
+            Encoding::Utf8 => text.as_bytes().to_vec(),
+            Encoding::Utf16Le => [0xFF, 0xFE]
+                .into_iter()
+                .chain(text.encode_utf16().flat_map(u16::to_le_bytes))
+                .collect(),
+            Encoding::Utf16Be => [0xFE, 0xFF]
+                .into_iter()
+                .chain(text.encode_utf16().flat_map(u16::to_be_bytes))
+                .collect(),
+            // The writer only produces characters the encoding can represent.
+            Encoding::Latin1 | Encoding::Ascii => text.chars().map(|c| c as u8).collect(),
+        }
+    }
+}
+
+/// Controls how a [`Document`] is written back to XML.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteOptions {
+    /// Puts element-only content on separate, indented lines. Elements with
+    /// text or CDATA children are written as they are.
+    pub pretty: bool,
+    /// Number of spaces per nesting level when `pretty` is set.
+    pub indent: usize,
+    /// Writes elements without children as `<a/>` instead of `<a></a>`.
+    pub self_close_empty: bool,
+    pub encoding: Encoding,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions {
+            pretty: false,
+            indent: 2,
+            self_close_empty: true,
+            encoding: Encoding::Utf8,
+        }
+    }
+}
+
+impl Document {
+    /// Serializes the document to XML text. Characters that `options.encoding`
+    /// cannot represent are written as character references.
+    pub fn serialize(&self, options: &WriteOptions) -> Result<String> {
+        let mut writer = XmlWriter {
+            document: self,
+            options,
+            output: String::new(),
+        };
+        writer.write_document()?;
+        Ok(writer.output)
+    }
+
+    /// Serializes the document and writes it to `writer` in
+    /// `options.encoding`.
+    pub fn write<W: Write>(&self, mut writer: W, options: &WriteOptions) -> Result<()> {
+        let text = self.serialize(options)?;
+        writer.write_all(&options.encoding.encode(&text))?;
+        Ok(())
+    }
+}
+
+struct XmlWriter<'a> {
+    document: &'a Document,
+    options: &'a WriteOptions,
+    output: String,
+}
+
+impl XmlWriter<'_> {
+    fn write_document(&mut self) -> Result<()> {
+        self.write_decl();
+
+        if !self.document.doc_type.0.is_empty() {
+            self.separate();
+            self.output.push_str("<!DOCTYPE ");
+            self.push_markup(&self.document.doc_type.0)?;
+            self.output.push('>');
+        }
+
+        let mut children = self.document.children.as_slice();
+        let root = [self.document.root];
+        if children.is_empty() && self.document.nodes.contains_key(&self.document.root) {
+            children = &root;
+        }
+
+        for &child in children {
+            self.separate();
+            self.write_node(child, 0)?;
+        }
+
+        if self.options.pretty {
+            self.output.push('\n');
+        }
+
+        Ok(())
+    }
+
+    fn write_decl(&mut self) {
+        let decl = &self.document.decl.0;
+        let encoding = self.options.encoding;
+
+        if decl.is_empty() {
+            if encoding != Encoding::Utf8 {
+                self.output.push_str(&format!(
+                    "<?xml version=\"1.0\" encoding=\"{}\"?>",
+                    encoding.name()
+                ));
+            }
+            return;
+        }
+
+        let declared = pseudo_attribute(decl, "encoding");
+        let matches = match &declared {
+            Some(name) => name.eq_ignore_ascii_case(encoding.name()),
+            None => encoding == Encoding::Utf8,
+        };
+        if matches {
+            self.output.push_str(&format!("<?{}?>", decl));
+            return;
+        }
+
+        let version = pseudo_attribute(decl, "version").unwrap_or_else(|| "1.0".to_owned());
+        self.output.push_str(&format!(
+            "<?xml version=\"{}\" encoding=\"{}\"",
+            version,
+            encoding.name()
+        ));
+        if let Some(standalone) = pseudo_attribute(decl, "standalone") {
+            self.output
+                .push_str(&format!(" standalone=\"{}\"", standalone));
+        }
+        self.output.push_str("?>");
+    }
+
+    /// Separates top-level constructs with a line break.
+    fn separate(&mut self) {
+        if !self.output.is_empty() {
+            self.output.push('\n');
+        }
+    }
+
+    fn write_node(&mut self, id: NodeId, depth: usize) -> Result<()> {
+        let node = self
+            .document
+            .nodes
+            .get(&id)
+            .ok_or(ErrorKind::UnknownNode(id))?;
+
+        match node {
+            Node::Element(element) => self.write_element(element, depth)?,
+            Node::Text(text) => self.push_escaped(&text.data, false),
+            Node::CData(cdata) => {
+                self.output.push_str("<![CDATA[");
+                let mut sections = cdata.data.split("]]>").peekable();
+                while let Some(section) = sections.next() {
+                    self.push_markup(section)?;
+                    if sections.peek().is_some() {
+                        self.output.push_str("]]]]><![CDATA[>");
+                    }
+                }
+                self.output.push_str("]]>");
+            }
+            Node::Comment(comment) => {
+                if comment.data.contains("--") || comment.data.ends_with('-') {
+                    return Err(
+                        ErrorKind::Unwritable("comment contains `--` or ends with `-`").into(),
+                    );
+                }
+                self.output.push_str("<!--");
+                self.push_markup(&comment.data)?;
+                self.output.push_str("-->");
+            }
+            Node::ProcessingInstruction(pi) => {
+                if pi.data.contains("?>") {
+                    return Err(
+                        ErrorKind::Unwritable("processing instruction contains `?>`").into(),
+                    );
+                }
+                self.output.push_str("<?");
+                self.push_markup(&pi.data)?;
+                self.output.push_str("?>");
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_element(&mut self, element: &Element, depth: usize) -> Result<()> {
+        let name = element.name.to_string();
+        self.output.push('<');
+        self.push_markup(&name)?;
+
+        for namespace in &element.namespaces {
+            match &namespace.prefix {
+                Some(prefix) => {
+                    self.output.push_str(" xmlns:");
+                    self.push_markup(prefix)?;
+                }
+                None => self.output.push_str(" xmlns"),
+            }
+            self.output.push_str("=\"");
+            self.push_escaped(&namespace.uri, true);
+            self.output.push('"');
+        }
+
+        for attribute in &element.attributes {
+            self.output.push(' ');
+            self.push_markup(&attribute.name.to_string())?;
+            self.output.push_str("=\"");
+            self.push_escaped(&attribute.value, true);
+            self.output.push('"');
+        }
+
+        if element.children.is_empty() {
+            if self.options.self_close_empty {
+                self.output.push_str("/>");
+            } else {
+                self.output.push_str("></");
+                self.output.push_str(&name);
+                self.output.push('>');
+            }
+            return Ok(());
+        }
+
+        self.output.push('>');
+
+        let indent = self.options.pretty && self.is_element_only(element);
+        for &child in &element.children {
+            if indent {
+                if matches!(self.document.nodes.get(&child), Some(Node::Text(_))) {
+                    continue;
+                }
+                self.newline(depth + 1);
+            }
+            self.write_node(child, depth + 1)?;
+        }
+        if indent {
+            self.newline(depth);
+        }
+
+        self.output.push_str("</");
+        self.output.push_str(&name);
+        self.output.push('>');
+        Ok(())
+    }
+
+    /// Returns `true` when `element` has no text or CDATA content besides
+    /// whitespace, so pretty-printing cannot change its meaning.
+    fn is_element_only(&self, element: &Element) -> bool {
+        element
+            .children
+            .iter()
+            .all(|child| match self.document.nodes.get(child) {
+                Some(Node::Text(text)) => text.data.chars().all(|c| c.is_ascii_whitespace()),
+                Some(Node::CData(_)) => false,
+                _ => true,
+            })
+    }
+
+    fn newline(&mut self, depth: usize) {
+        self.output.push('\n');
+        self.output
+            .push_str(&" ".repeat(depth * self.options.indent));
+    }
+
+    /// Writes character data, escaping markup and characters the output
+    /// encoding cannot represent.
+    fn push_escaped(&mut self, text: &str, in_attribute: bool) {
+        for c in text.chars() {
+            match c {
+                '&' => self.output.push_str("&amp;"),
+                '<' => self.output.push_str("&lt;"),
+                '>' => self.output.push_str("&gt;"),
+                '"' if in_attribute => self.output.push_str("&quot;"),
+                '\t' | '\n' if in_attribute => self.push_reference(c),
+                '\r' => self.push_reference(c),
+                c if !self.options.encoding.can_encode(c) => self.push_reference(c),
+                c => self.output.push(c),
+            }
+        }
+    }
+
+    fn push_reference(&mut self, c: char) {
+        self.output.push_str(&format!("&#x{:X};", c as u32));
+    }
+
+    /// Writes text where character references are not recognised, such as
+    /// names, comments and CDATA sections.
+    fn push_markup(&mut self, text: &str) -> Result<()> {
+        if let Some(c) = text.chars().find(|&c| !self.options.encoding.can_encode(c)) {
+            return Err(ErrorKind::Unencodable(c).into());
+        }
+        self.output.push_str(text);
+        Ok(())
+    }
+}
+
+/// Reads a pseudo-attribute such as `version` from a raw XML declaration.
+fn pseudo_attribute(decl: &str, name: &str) -> Option<String> {
+    let start = decl.find(name)? + name.len();
+    let rest = decl[start..].trim_start().strip_prefix('=')?.trim_start();
+    let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let value = &rest[1..];
+    let end = value.find(quote)?;
+    Some(value[..end].to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::node::Document;
+    use crate::writer::{Encoding, WriteOptions};
+    use crate::{Error, ErrorKind};
+
+    #[test]
+    fn round_trips_documents() -> Result<(), Error> {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE config>
+<!-- settings -->
+<config xmlns="urn:config" xmlns:x="urn:x" x:id="a &amp; &quot;b&quot;" tab="&#x9;">
+  <entry key="k">1 &lt; 2 &gt; 0</entry>
+  <script><![CDATA[if (a < b && c]]]]><![CDATA[>d) {}]]></script>
+  <?render fast?>
+  <empty/>
+</config>"#;
+        let document = Document::parse(xml)?;
+        let written = document.serialize(&WriteOptions::default())?;
+        assert_eq!(Document::parse(&written)?, document);
+        assert!(
+            written.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE config>")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn pretty_prints_element_only_content() -> Result<(), Error> {
+        let document = Document::parse("<a><b><c>text</c></b><d/></a>")?;
+        let options = WriteOptions {
+            pretty: true,
+            indent: 4,
+            self_close_empty: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            document.serialize(&options)?,
+            "<a>\n    <b>\n        <c>text</c>\n    </b>\n    <d></d>\n</a>\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn encodes_output() -> Result<(), Error> {
+        let document = Document::parse("<a>caf\u{e9} \u{2603}</a>")?;
+        let options = WriteOptions {
+            encoding: Encoding::Latin1,
+            ..Default::default()
+        };
+        let mut bytes = Vec::new();
+        document.write(&mut bytes, &options)?;
+        assert_eq!(
+            bytes,
+            b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\n<a>caf\xe9 &#x2603;</a>"
+        );
+
+        let options = WriteOptions {
+            encoding: Encoding::Ascii,
+            ..Default::default()
+        };
+        let document = Document::parse("<caf\u{e9}/>")?;
+        assert!(matches!(
+            document.serialize(&options),
+            Err(Error {
+                kind: ErrorKind::Unencodable('\u{e9}'),
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn refuses_data_that_would_end_its_markup() {
+        for (comment, pi) in [("a--b", "x ?> y"), ("a-", "?>")] {
+            let mut document = Document::parse("<a/>").unwrap();
+            let node = document.create_comment(comment);
+            document.append_child(document.root, node).unwrap();
+            assert!(matches!(
+                document.serialize(&WriteOptions::default()),
+                Err(Error {
+                    kind: ErrorKind::Unwritable(_),
+                    ..
+                })
+            ));
+
+            let mut document = Document::parse("<a/>").unwrap();
+            let node = document.create_processing_instruction(pi);
+            document.append_child(document.root, node).unwrap();
+            assert!(matches!(
+                document.serialize(&WriteOptions::default()),
+                Err(Error {
+                    kind: ErrorKind::Unwritable(_),
+                    ..
+                })
+            ));
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use document::namespace::XML_NAMESPACE;
+use document::node::{Document, Element, Node};
+
+use crate::error::{Error, Result};
+use crate::uri;
+use crate::CATALOG_NAMESPACE;
+
+/// Which kind of identifier wins when an external identifier has both a
+/// public and a system identifier, set by the `prefer` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Prefer {
+    /// `public` entries apply even when a system identifier is given.
+    #[default]
+    Public,
+    /// `public` entries apply only when no system identifier is given.
+    System,
+}
+
+/// What a catalog entry matches and where it points. Identifiers and
+/// prefixes are normalized; URIs are absolute, resolved against the base
+/// URI in effect for the entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryKind {
+    Public { public_id: String, uri: String },
+    System { system_id: String, uri: String },
+    RewriteSystem { start: String, prefix: String },
+    SystemSuffix { suffix: String, uri: String },
+    DelegatePublic { start: String, catalog: String },
+    DelegateSystem { start: String, catalog: String },
+    Uri { name: String, uri: String },
+    RewriteUri { start: String, prefix: String },
+    UriSuffix { suffix: String, uri: String },
+    DelegateUri { start: String, catalog: String },
+    NextCatalog { catalog: String },
+}
+
+/// A catalog entry, with the `prefer` setting of the `catalog` or `group`
+/// it appears in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub kind: EntryKind,
+    pub prefer: Prefer,
+}
+
+/// The entries of one catalog entry file, in document order. Entries of
+/// `group` elements are flattened into the list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogFile {
+    pub uri: String,
+    pub entries: Vec<Entry>,
+}
+
+impl CatalogFile {
+    /// Reads the catalog entry file `xml`, found at `uri`. Elements and
+    /// attributes from other namespaces are ignored, as the specification
+    /// allows.
+    pub fn parse(uri: &str, xml: &str) -> Result<CatalogFile> {
+        let document = Document::parse(xml).map_err(|source| Error::Xml {
+            uri: uri.to_owned(),
+            source: Box::new(source),
+        })?;
+        let mut file = CatalogFile {
+            uri: uri.to_owned(),
+            entries: Vec::new(),
+        };
+        let root = match document.node(document.root) {
+            Some(Node::Element(root)) if root.name.is(Some(CATALOG_NAMESPACE), "catalog") => root,
+            _ => return Err(file.invalid("the document element is not `catalog`".to_owned())),
+        };
+        let base = base_uri(uri, root);
+        let prefer = file.prefer(root, Prefer::default())?;
+        file.read_entries(&document, root, &base, prefer)?;
+        Ok(file)
+    }
+
+    fn read_entries(
+        &mut self,
+        document: &Document,
+        parent: &Element,
+        base: &str,
+        prefer: Prefer,
+    ) -> Result<()> {
+        for &child in &parent.children {
+            let Some(Node::Element(element)) = document.node(child) else {
+                continue;
+            };
+            if element.name.namespace.as_deref() != Some(CATALOG_NAMESPACE) {
+                continue;
+            }
+            let base = base_uri(base, element);
+            let kind = match element.name.local_name.as_str() {
+                "group" => {
+                    let prefer = self.prefer(element, prefer)?;
+                    self.read_entries(document, element, &base, prefer)?;
+                    continue;
+                }
+                "public" => EntryKind::Public {
+                    public_id: uri::normalize_public_id(self.required(element, "publicId")?),
+                    uri: self.reference(element, "uri", &base)?,
+                },
+                "system" => EntryKind::System {
+                    system_id: uri::normalize(self.required(element, "systemId")?),
+                    uri: self.reference(element, "uri", &base)?,
+                },
+                "rewriteSystem" => EntryKind::RewriteSystem {
+                    start: uri::normalize(self.required(element, "systemIdStartString")?),
+                    prefix: self.reference(element, "rewritePrefix", &base)?,
+                },
+                "systemSuffix" => EntryKind::SystemSuffix {
+                    suffix: uri::normalize(self.required(element, "systemIdSuffix")?),
+                    uri: self.reference(element, "uri", &base)?,
+                },
+                "delegatePublic" => EntryKind::DelegatePublic {
+                    start: uri::normalize_public_id(self.required(element, "publicIdStartString")?),
+                    catalog: self.reference(element, "catalog", &base)?,
+                },
+                "delegateSystem" => EntryKind::DelegateSystem {
+                    start: uri::normalize(self.required(element, "systemIdStartString")?),
+                    catalog: self.reference(element, "catalog", &base)?,
+                },
+                "uri" => EntryKind::Uri {
+                    name: uri::normalize(self.required(element, "name")?),
+                    uri: self.reference(element, "uri", &base)?,
+                },
+                "rewriteURI" => EntryKind::RewriteUri {
+                    start: uri::normalize(self.required(element, "uriStartString")?),
+                    prefix: self.reference(element, "rewritePrefix", &base)?,
+                },
+                "uriSuffix" => EntryKind::UriSuffix {
+                    suffix: uri::normalize(self.required(element, "uriSuffix")?),
+                    uri: self.reference(element, "uri", &base)?,
+                },
+                "delegateURI" => EntryKind::DelegateUri {
+                    start: uri::normalize(self.required(element, "uriStartString")?),
+                    catalog: self.reference(element, "catalog", &base)?,
+                },
+                "nextCatalog" => EntryKind::NextCatalog {
+                    catalog: self.reference(element, "catalog", &base)?,
+                },
+                other => {
+                    return Err(self.invalid(format!("unknown catalog entry `{}`", other)));
+                }
+            };
+            self.entries.push(Entry { kind, prefer });
+        }
+        Ok(())
+    }
+
+    fn required<'a>(&self, element: &'a Element, name: &str) -> Result<&'a str> {
+        element.attribute(None, name).ok_or_else(|| {
+            self.invalid(format!(
+                "`{}` lacks the `{}` attribute",
+                element.name.local_name, name
+            ))
+        })
+    }
+
+    /// The absolute URI in the attribute `name` of `element`.
+    fn reference(&self, element: &Element, name: &str, base: &str) -> Result<String> {
+        let reference = self.required(element, name)?;
+        Ok(uri::normalize(&uri::resolve(base, reference)))
+    }
+
+    fn prefer(&self, element: &Element, inherited: Prefer) -> Result<Prefer> {
+        match element.attribute(None, "prefer") {
+            None => Ok(inherited),
+            Some("public") => Ok(Prefer::Public),
+            Some("system") => Ok(Prefer::System),
+            Some(other) => Err(self.invalid(format!("unknown `prefer` value `{}`", other))),
+        }
+    }
+
+    fn invalid(&self, message: String) -> Error {
+        Error::Invalid {
+            uri: self.uri.clone(),
+            message,
+        }
+    }
+}
+
+/// The base URI in effect on `element`, given that of its parent.
+fn base_uri(base: &str, element: &Element) -> String {
+    match element.attribute(Some(XML_NAMESPACE), "base") {
+        Some(reference) => uri::resolve(base, reference),
+        None => base.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CatalogFile, EntryKind, Error, Prefer};
+
+    #[test]
+    fn reads_entries_through_groups() -> Result<(), anyhow::Error> {
+        let file = CatalogFile::parse(
+            "file:///etc/xml/catalog",
+            r#"<catalog xmlns="urn:oasis:names:tc:entity:xmlns:xml:catalog" prefer="system">
+                <public publicId="-//Example//DTD  Doc//EN" uri="doc.dtd"/>
+                <group prefer="public" xml:base="http://example.org/schemas/">
+                    <system systemId="http://example.org/doc.dtd" uri="local/doc.dtd"/>
+                    <other:entry xmlns:other="urn:other"/>
+                </group>
+                <nextCatalog catalog="../sgml/catalog"/>
+            </catalog>"#,
+        )?;
+        let entries: Vec<_> = file
+            .entries
+            .iter()
+            .map(|entry| (entry.kind.clone(), entry.prefer))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                (
+                    EntryKind::Public {
+                        public_id: "-//Example//DTD Doc//EN".to_owned(),
+                        uri: "file:///etc/xml/doc.dtd".to_owned(),
+                    },
+                    Prefer::System,
+                ),
+                (
+                    EntryKind::System {
+                        system_id: "http://example.org/doc.dtd".to_owned(),
+                        uri: "http://example.org/schemas/local/doc.dtd".to_owned(),
+                    },
+                    Prefer::Public,
+                ),
+                (
+                    EntryKind::NextCatalog {
+                        catalog: "file:///etc/sgml/catalog".to_owned(),
+                    },
+                    Prefer::System,
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_invalid_catalogs() {
+        let missing = CatalogFile::parse(
+            "catalog.xml",
+            r#"<catalog xmlns="urn:oasis:names:tc:entity:xmlns:xml:catalog">
+                <system uri="a.dtd"/>
+            </catalog>"#,
+        );
+        assert!(
+            matches!(&missing, Err(Error::Invalid { message, .. }) if message == "`system` lacks the `systemId` attribute"),
+            "{:?}",
+            missing
+        );
+        let root = CatalogFile::parse("catalog.xml", "<catalog/>");
+        assert!(matches!(root, Err(Error::Invalid { .. })), "{:?}", root);
+    }
+}
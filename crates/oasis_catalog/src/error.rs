@@ -0,0 +1,37 @@
+use std::fmt::{Display, Formatter};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A catalog entry file could not be read.
+    Io { uri: String, source: std::io::Error },
+    /// A catalog entry file is not well-formed XML.
+    Xml {
+        uri: String,
+        source: Box<document::Error>,
+    },
+    /// A catalog entry file is not a catalog, or an entry lacks a required
+    /// attribute.
+    Invalid { uri: String, message: String },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io { uri, source } => write!(f, "cannot read catalog `{}`: {}", uri, source),
+            Error::Xml { uri, source } => write!(f, "malformed catalog `{}`: {}", uri, source),
+            Error::Invalid { uri, message } => write!(f, "invalid catalog `{}`: {}", uri, message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Xml { source, .. } => Some(source.as_ref()),
+            Error::Invalid { .. } => None,
+        }
+    }
+}
@@ -1,14 +1,16 @@
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
-}
+//! OASIS XML Catalogs 1.1: reading catalog entry files and resolving
+//! public identifiers, system identifiers and URIs with them. A
+//! [`CatalogResolver`] puts a catalog in front of any
+//! [`Resolver`](document::resolver::Resolver).
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub use catalog::{CatalogFile, Entry, EntryKind, Prefer};
+pub use error::{Error, Result};
+pub use resolver::{Catalog, CatalogResolver};
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+mod catalog;
+mod error;
+mod resolver;
+pub mod uri;
+
+/// The namespace of catalog entry files.
+pub const CATALOG_NAMESPACE: &str = "urn:oasis:names:tc:entity:xmlns:xml:catalog";
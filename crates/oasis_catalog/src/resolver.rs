@@ -0,0 +1,583 @@
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+use document::resolver::{Resolver, Resource};
+use document::uri::to_file_path;
+
+use crate::catalog::{CatalogFile, EntryKind, Prefer};
+use crate::error::{Error, Result};
+use crate::uri;
+
+/// An ordered list of catalog entry files, together with every catalog
+/// they reach through `nextCatalog` and delegation entries.
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    roots: Vec<String>,
+    files: HashMap<String, CatalogFile>,
+}
+
+/// The outcome of looking an identifier up in one catalog entry file.
+enum Lookup {
+    Found(String),
+    /// The lookup was delegated and the delegated catalogs had no match.
+    /// Resolution stops without trying further catalogs.
+    Failed,
+    NotFound,
+}
+
+impl Catalog {
+    /// Reads the catalog entry files at `uris` from the filesystem.
+    /// `file:` URIs are accepted as well as paths.
+    pub fn load(uris: &[&str]) -> Result<Catalog> {
+        Catalog::load_with(uris, |uri| match to_file_path(uri) {
+            Some(path) => std::fs::read_to_string(path),
+            None => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("`{}` is not a file", uri),
+            )),
+        })
+    }
+
+    /// Reads the catalog entry files at `uris`, and the catalogs they
+    /// refer to, with `read`. The files at `uris` must be readable; other
+    /// catalogs that cannot be read or parsed are ignored, as the
+    /// specification asks.
+    pub fn load_with(uris: &[&str], read: impl Fn(&str) -> io::Result<String>) -> Result<Catalog> {
+        let mut catalog = Catalog::default();
+        for &uri in uris {
+            let xml = read(uri).map_err(|source| Error::Io {
+                uri: uri.to_owned(),
+                source,
+            })?;
+            let file = CatalogFile::parse(uri, &xml)?;
+            catalog.roots.push(uri.to_owned());
+            catalog.add(file, &read);
+        }
+        Ok(catalog)
+    }
+
+    fn add(&mut self, file: CatalogFile, read: &impl Fn(&str) -> io::Result<String>) {
+        if self.files.contains_key(&file.uri) {
+            return;
+        }
+        let referenced: Vec<_> = file
+            .entries
+            .iter()
+            .filter_map(|entry| match &entry.kind {
+                EntryKind::DelegatePublic { catalog, .. }
+                | EntryKind::DelegateSystem { catalog, .. }
+                | EntryKind::DelegateUri { catalog, .. }
+                | EntryKind::NextCatalog { catalog } => Some(catalog.clone()),
+                _ => None,
+            })
+            .collect();
+        self.files.insert(file.uri.clone(), file);
+        for uri in referenced {
+            if self.files.contains_key(&uri) {
+                continue;
+            }
+            let Ok(xml) = read(&uri) else {
+                continue;
+            };
+            if let Ok(file) = CatalogFile::parse(&uri, &xml) {
+                self.add(file, read);
+            }
+        }
+    }
+
+    /// The catalog entry files, in the order they were given.
+    pub fn files(&self) -> impl Iterator<Item = &CatalogFile> {
+        self.roots.iter().filter_map(|uri| self.files.get(uri))
+    }
+
+    /// Maps an external identifier to a URI, following section 7.1 of the
+    /// specification: system identifier entries come first, then public
+    /// identifier entries, then `nextCatalog` entries.
+    pub fn resolve_external(
+        &self,
+        public_id: Option<&str>,
+        system_id: Option<&str>,
+    ) -> Option<String> {
+        let mut public_id = public_id.map(|public_id| match uri::unwrap_urn(public_id) {
+            Some(unwrapped) => unwrapped,
+            None => uri::normalize_public_id(public_id),
+        });
+        let mut system_id = system_id.map(uri::normalize);
+        // A system identifier that is a `urn:publicid:` URN stands for a
+        // public identifier. When both are given and differ, the public
+        // identifier wins.
+        if let Some(unwrapped) = system_id.as_deref().and_then(uri::unwrap_urn) {
+            public_id.get_or_insert(unwrapped);
+            system_id = None;
+        }
+        self.resolve_external_in(&self.roots, public_id.as_deref(), system_id.as_deref())
+    }
+
+    /// Maps a URI reference, such as a schema location or namespace name,
+    /// to another URI, following section 7.2 of the specification.
+    pub fn resolve_uri(&self, uri: &str) -> Option<String> {
+        if let Some(public_id) = uri::unwrap_urn(uri) {
+            return self.resolve_external_in(&self.roots, Some(&public_id), None);
+        }
+        let uri = uri::normalize(uri);
+        let mut visited = HashSet::new();
+        for catalog in &self.roots {
+            match self.lookup_uri(catalog, &uri, &mut visited) {
+                Lookup::Found(uri) => return Some(uri),
+                Lookup::Failed => return None,
+                Lookup::NotFound => {}
+            }
+        }
+        None
+    }
+
+    fn resolve_external_in(
+        &self,
+        catalogs: &[String],
+        public_id: Option<&str>,
+        system_id: Option<&str>,
+    ) -> Option<String> {
+        let mut visited = HashSet::new();
+        for catalog in catalogs {
+            match self.lookup_external(catalog, public_id, system_id, &mut visited) {
+                Lookup::Found(uri) => return Some(uri),
+                Lookup::Failed => return None,
+                Lookup::NotFound => {}
+            }
+        }
+        None
+    }
+
+    fn lookup_external(
+        &self,
+        catalog: &str,
+        public_id: Option<&str>,
+        system_id: Option<&str>,
+        visited: &mut HashSet<String>,
+    ) -> Lookup {
+        if !visited.insert(catalog.to_owned()) {
+            return Lookup::NotFound;
+        }
+        let Some(file) = self.files.get(catalog) else {
+            return Lookup::NotFound;
+        };
+        let entries = || file.entries.iter();
+
+        if let Some(system_id) = system_id {
+            let exact = entries().find_map(|entry| match &entry.kind {
+                EntryKind::System { system_id: id, uri } if id == system_id => Some(uri.clone()),
+                _ => None,
+            });
+            if let Some(uri) = exact {
+                return Lookup::Found(uri);
+            }
+            let rewrite = longest(entries().filter_map(|entry| match &entry.kind {
+                EntryKind::RewriteSystem { start, prefix }
+                    if system_id.starts_with(start.as_str()) =>
+                {
+                    Some((start.len(), prefix))
+                }
+                _ => None,
+            }));
+            if let Some((length, prefix)) = rewrite {
+                return Lookup::Found(format!("{}{}", prefix, &system_id[length..]));
+            }
+            let suffix = longest(entries().filter_map(|entry| match &entry.kind {
+                EntryKind::SystemSuffix { suffix, uri } if system_id.ends_with(suffix.as_str()) => {
+                    Some((suffix.len(), uri))
+                }
+                _ => None,
+            }));
+            if let Some((_, uri)) = suffix {
+                return Lookup::Found(uri.clone());
+            }
+            let delegates = delegates(entries().filter_map(|entry| match &entry.kind {
+                EntryKind::DelegateSystem { start, catalog }
+                    if system_id.starts_with(start.as_str()) =>
+                {
+                    Some((start.len(), catalog))
+                }
+                _ => None,
+            }));
+            if !delegates.is_empty() {
+                return self.delegate(&delegates, None, Some(system_id));
+            }
+        }
+
+        if let Some(public_id) = public_id {
+            // With a system identifier at hand, only entries that prefer
+            // public identifiers apply.
+            let applies = |prefer: Prefer| system_id.is_none() || prefer == Prefer::Public;
+            let exact = entries().find_map(|entry| match &entry.kind {
+                EntryKind::Public { public_id: id, uri }
+                    if id == public_id && applies(entry.prefer) =>
+                {
+                    Some(uri.clone())
+                }
+                _ => None,
+            });
+            if let Some(uri) = exact {
+                return Lookup::Found(uri);
+            }
+            let delegates = delegates(entries().filter_map(|entry| match &entry.kind {
+                EntryKind::DelegatePublic { start, catalog }
+                    if public_id.starts_with(start.as_str()) && applies(entry.prefer) =>
+                {
+                    Some((start.len(), catalog))
+                }
+                _ => None,
+            }));
+            if !delegates.is_empty() {
+                return self.delegate(&delegates, Some(public_id), None);
+            }
+        }
+
+        for entry in entries() {
+            if let EntryKind::NextCatalog { catalog } = &entry.kind {
+                match self.lookup_external(catalog, public_id, system_id, visited) {
+                    Lookup::NotFound => {}
+                    lookup => return lookup,
+                }
+            }
+        }
+        Lookup::NotFound
+    }
+
+    fn delegate(
+        &self,
+        catalogs: &[String],
+        public_id: Option<&str>,
+        system_id: Option<&str>,
+    ) -> Lookup {
+        match self.resolve_external_in(catalogs, public_id, system_id) {
+            Some(uri) => Lookup::Found(uri),
+            None => Lookup::Failed,
+        }
+    }
+
+    fn lookup_uri(&self, catalog: &str, uri: &str, visited: &mut HashSet<String>) -> Lookup {
+        if !visited.insert(catalog.to_owned()) {
+            return Lookup::NotFound;
+        }
+        let Some(file) = self.files.get(catalog) else {
+            return Lookup::NotFound;
+        };
+        let entries = || file.entries.iter();
+
+        let exact = entries().find_map(|entry| match &entry.kind {
+            EntryKind::Uri { name, uri: target } if name == uri => Some(target.clone()),
+            _ => None,
+        });
+        if let Some(target) = exact {
+            return Lookup::Found(target);
+        }
+        let rewrite = longest(entries().filter_map(|entry| match &entry.kind {
+            EntryKind::RewriteUri { start, prefix } if uri.starts_with(start.as_str()) => {
+                Some((start.len(), prefix))
+            }
+            _ => None,
+        }));
+        if let Some((length, prefix)) = rewrite {
+            return Lookup::Found(format!("{}{}", prefix, &uri[length..]));
+        }
+        let suffix = longest(entries().filter_map(|entry| match &entry.kind {
+            EntryKind::UriSuffix {
+                suffix,
+                uri: target,
+            } if uri.ends_with(suffix.as_str()) => Some((suffix.len(), target)),
+            _ => None,
+        }));
+        if let Some((_, target)) = suffix {
+            return Lookup::Found(target.clone());
+        }
+        let delegates = delegates(entries().filter_map(|entry| match &entry.kind {
+            EntryKind::DelegateUri { start, catalog } if uri.starts_with(start.as_str()) => {
+                Some((start.len(), catalog))
+            }
+            _ => None,
+        }));
+        if !delegates.is_empty() {
+            let mut visited = HashSet::new();
+            for catalog in &delegates {
+                if let Lookup::Found(target) = self.lookup_uri(catalog, uri, &mut visited) {
+                    return Lookup::Found(target);
+                }
+            }
+            return Lookup::Failed;
+        }
+
+        for entry in entries() {
+            if let EntryKind::NextCatalog { catalog } = &entry.kind {
+                match self.lookup_uri(catalog, uri, visited) {
+                    Lookup::NotFound => {}
+                    lookup => return lookup,
+                }
+            }
+        }
+        Lookup::NotFound
+    }
+}
+
+/// Maps references through a [`Catalog`] before fetching them with
+/// another resolver. References the catalog does not map are fetched
+/// unchanged.
+#[derive(Debug, Clone)]
+pub struct CatalogResolver<R> {
+    catalog: Catalog,
+    resolver: R,
+}
+
+impl<R: Resolver> CatalogResolver<R> {
+    pub fn new(catalog: Catalog, resolver: R) -> CatalogResolver<R> {
+        CatalogResolver { catalog, resolver }
+    }
+
+    pub fn catalog(&self) -> &Catalog {
+        &self.catalog
+    }
+
+    /// The catalog entry for `reference` as written, or else for the
+    /// absolute URI it names.
+    fn lookup(
+        &self,
+        base: Option<&str>,
+        reference: &str,
+        find: impl Fn(&str) -> Option<String>,
+    ) -> Option<String> {
+        find(reference).or_else(|| {
+            let absolute = uri::resolve(base?, reference);
+            find(&absolute)
+        })
+    }
+}
+
+impl<R: Resolver> Resolver for CatalogResolver<R> {
+    fn resolve(&self, base: Option<&str>, reference: &str) -> io::Result<Resource> {
+        match self.lookup(base, reference, |uri| self.catalog.resolve_uri(uri)) {
+            Some(mapped) => self.resolver.resolve(None, &mapped),
+            None => self.resolver.resolve(base, reference),
+        }
+    }
+
+    fn resolve_entity(
+        &self,
+        base: Option<&str>,
+        public_id: Option<&str>,
+        system_id: &str,
+    ) -> io::Result<Resource> {
+        let find = |system_id: &str| self.catalog.resolve_external(public_id, Some(system_id));
+        match self.lookup(base, system_id, find) {
+            Some(mapped) => self.resolver.resolve(None, &mapped),
+            None => self.resolver.resolve_entity(base, public_id, system_id),
+        }
+    }
+}
+
+/// The candidate with the longest match, the first one on ties, along
+/// with the length of the match.
+fn longest<'a, T>(candidates: impl Iterator<Item = (usize, &'a T)>) -> Option<(usize, &'a T)> {
+    candidates.fold(None, |best, (length, candidate)| match best {
+        Some((best_length, _)) if best_length >= length => best,
+        _ => Some((length, candidate)),
+    })
+}
+
+/// The catalogs of matching delegation entries, longest match first and
+/// each catalog once.
+fn delegates<'a>(candidates: impl Iterator<Item = (usize, &'a String)>) -> Vec<String> {
+    let mut candidates: Vec<_> = candidates.collect();
+    candidates.sort_by(|(a, _), (b, _)| b.cmp(a));
+    let mut catalogs: Vec<String> = Vec::new();
+    for (_, catalog) in candidates {
+        if !catalogs.contains(catalog) {
+            catalogs.push(catalog.clone());
+        }
+    }
+    catalogs
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io;
+
+    use document::resolver::{MapResolver, Resolver};
+
+    use crate::{Catalog, CatalogResolver, Error};
+
+    fn catalog(files: &[(&str, &str)]) -> Result<Catalog, Error> {
+        let files: HashMap<_, _> = files
+            .iter()
+            .map(|(uri, body)| {
+                let xml = format!(
+                    r#"<catalog xmlns="urn:oasis:names:tc:entity:xmlns:xml:catalog">{}</catalog>"#,
+                    body
+                );
+                (uri.to_string(), xml)
+            })
+            .collect();
+        Catalog::load_with(&[files.keys().min().unwrap()], |uri| {
+            files
+                .get(uri)
+                .cloned()
+                .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+        })
+    }
+
+    #[test]
+    fn follows_external_identifier_precedence() -> Result<(), anyhow::Error> {
+        let catalog = catalog(&[(
+            "http://example.org/a/catalog",
+            r#"
+            <public publicId="-//Example//DTD Doc//EN" uri="public.dtd"/>
+            <group prefer="system">
+                <public publicId="-//Example//DTD Other//EN" uri="other.dtd"/>
+            </group>
+            <system systemId="http://example.org/doc.dtd" uri="system.dtd"/>
+            <rewriteSystem systemIdStartString="http://example.org/" rewritePrefix="short/"/>
+            <rewriteSystem systemIdStartString="http://example.org/dtd/" rewritePrefix="long/"/>
+            <systemSuffix systemIdSuffix="/book.dtd" uri="book.dtd"/>
+            "#,
+        )])?;
+        let resolve = |public_id, system_id| catalog.resolve_external(public_id, system_id);
+
+        assert_eq!(
+            resolve(
+                Some("-//Example//DTD Doc//EN"),
+                Some("http://example.org/doc.dtd")
+            )
+            .as_deref(),
+            Some("http://example.org/a/system.dtd")
+        );
+        assert_eq!(
+            resolve(None, Some("http://example.org/dtd/x.dtd")).as_deref(),
+            Some("http://example.org/a/long/x.dtd")
+        );
+        assert_eq!(
+            resolve(None, Some("http://example.org/x.dtd")).as_deref(),
+            Some("http://example.org/a/short/x.dtd")
+        );
+        assert_eq!(
+            resolve(None, Some("http://other.org/book.dtd")).as_deref(),
+            Some("http://example.org/a/book.dtd")
+        );
+        assert_eq!(
+            resolve(
+                Some(" -//Example//DTD  Doc//EN"),
+                Some("http://other.org/doc.dtd")
+            )
+            .as_deref(),
+            Some("http://example.org/a/public.dtd")
+        );
+        assert_eq!(
+            resolve(
+                Some("-//Example//DTD Other//EN"),
+                Some("http://other.org/doc.dtd")
+            ),
+            None
+        );
+        assert_eq!(
+            resolve(Some("-//Example//DTD Other//EN"), None).as_deref(),
+            Some("http://example.org/a/other.dtd")
+        );
+        assert_eq!(
+            resolve(None, Some("urn:publicid:-:Example:DTD+Doc:EN")).as_deref(),
+            Some("http://example.org/a/public.dtd")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn delegates_and_chains_catalogs() -> Result<(), anyhow::Error> {
+        let catalog = catalog(&[
+            (
+                "a.xml",
+                r#"
+                <delegatePublic publicIdStartString="-//Example//" catalog="b.xml"/>
+                <delegateURI uriStartString="http://example.org/" catalog="b.xml"/>
+                <nextCatalog catalog="missing.xml"/>
+                <nextCatalog catalog="c.xml"/>
+                "#,
+            ),
+            (
+                "b.xml",
+                r#"
+                <public publicId="-//Example//DTD Doc//EN" uri="doc.dtd"/>
+                <uri name="http://example.org/schema.xsd" uri="schema.xsd"/>
+                "#,
+            ),
+            (
+                "c.xml",
+                r#"
+                <public publicId="-//Example//DTD Missing//EN" uri="unreachable.dtd"/>
+                <public publicId="-//Other//DTD Doc//EN" uri="other.dtd"/>
+                <rewriteURI uriStartString="http://other.org/" rewritePrefix="cache/"/>
+                <uriSuffix uriSuffix=".rng" uri="any.rng"/>
+                <nextCatalog catalog="a.xml"/>
+                "#,
+            ),
+        ])?;
+
+        assert_eq!(
+            catalog
+                .resolve_external(Some("-//Example//DTD Doc//EN"), None)
+                .as_deref(),
+            Some("doc.dtd")
+        );
+        // Delegation is final, even though `c.xml` has an entry.
+        assert_eq!(
+            catalog.resolve_external(Some("-//Example//DTD Missing//EN"), None),
+            None
+        );
+        assert_eq!(
+            catalog
+                .resolve_external(Some("-//Other//DTD Doc//EN"), None)
+                .as_deref(),
+            Some("other.dtd")
+        );
+        assert_eq!(
+            catalog
+                .resolve_uri("http://example.org/schema.xsd")
+                .as_deref(),
+            Some("schema.xsd")
+        );
+        assert_eq!(
+            catalog.resolve_uri("http://other.org/x/a.xsd").as_deref(),
+            Some("cache/x/a.xsd")
+        );
+        assert_eq!(
+            catalog.resolve_uri("http://third.org/a.rng").as_deref(),
+            Some("any.rng")
+        );
+        assert_eq!(catalog.resolve_uri("http://third.org/a.xsd"), None);
+        assert_eq!(catalog.files().count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn maps_references_before_fetching() -> Result<(), anyhow::Error> {
+        let catalog = catalog(&[(
+            "file:///etc/xml/catalog",
+            r#"
+            <public publicId="-//Example//DTD Doc//EN" uri="dtd/doc.dtd"/>
+            <rewriteURI uriStartString="http://example.org/" rewritePrefix="cache/"/>
+            "#,
+        )])?;
+        let files: MapResolver = [
+            ("file:///etc/xml/dtd/doc.dtd", "doc"),
+            ("file:///etc/xml/cache/a.xsd", "a"),
+            ("http://other.org/b.xsd", "b"),
+        ]
+        .into_iter()
+        .collect();
+        let resolver = CatalogResolver::new(catalog, files);
+
+        let a = resolver.resolve(Some("http://example.org/main.xsd"), "a.xsd")?;
+        assert_eq!(a.uri, "file:///etc/xml/cache/a.xsd");
+        let b = resolver.resolve(Some("http://other.org/main.xsd"), "b.xsd")?;
+        assert_eq!(b.text()?, "b");
+        let doc = resolver.resolve_entity(None, Some("-//Example//DTD Doc//EN"), "doc.dtd")?;
+        assert_eq!(doc.text()?, "doc");
+        Ok(())
+    }
+}
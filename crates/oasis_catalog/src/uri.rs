@@ -0,0 +1,90 @@
+//! The URI handling catalogs need: normalizing system identifiers and
+//! URIs, and unwrapping `urn:publicid:` URNs.
+
+pub use document::uri::resolve;
+
+/// Normalizes a system identifier or URI for comparison, as section 6.3
+/// of the specification asks: characters that URIs cannot contain are
+/// percent-encoded as UTF-8.
+pub fn normalize(uri: &str) -> String {
+    let mut normalized = String::with_capacity(uri.len());
+    for c in uri.chars() {
+        let allowed = c.is_ascii_graphic()
+            && !matches!(c, '"' | '<' | '>' | '\\' | '^' | '`' | '{' | '|' | '}');
+        if allowed {
+            normalized.push(c);
+            continue;
+        }
+        let mut bytes = [0; 4];
+        for byte in c.encode_utf8(&mut bytes).bytes() {
+            normalized.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    normalized
+}
+
+/// Normalizes a public identifier: runs of whitespace become one space,
+/// and leading and trailing whitespace is removed.
+pub fn normalize_public_id(public_id: &str) -> String {
+    public_id.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The public identifier a `urn:publicid:` URN stands for, following
+/// section 6.4 of the specification, or `None` for other URIs.
+pub fn unwrap_urn(uri: &str) -> Option<String> {
+    let prefix = "urn:publicid:";
+    let urn = uri.trim();
+    if urn.len() < prefix.len() || !urn[..prefix.len()].eq_ignore_ascii_case(prefix) {
+        return None;
+    }
+    let mut public_id = String::new();
+    let mut rest = &urn[prefix.len()..];
+    while let Some(c) = rest.chars().next() {
+        let (replacement, length) = match c {
+            '+' => (" ", 1),
+            ':' => ("//", 1),
+            ';' => ("::", 1),
+            '%' => match rest.get(1..3).map(str::to_ascii_uppercase).as_deref() {
+                Some("2B") => ("+", 3),
+                Some("3A") => (":", 3),
+                Some("2F") => ("/", 3),
+                Some("3B") => (";", 3),
+                Some("27") => ("'", 3),
+                Some("3F") => ("?", 3),
+                Some("23") => ("#", 3),
+                Some("25") => ("%", 3),
+                _ => ("%", 1),
+            },
+            c => {
+                public_id.push(c);
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+        };
+        public_id.push_str(replacement);
+        rest = &rest[length..];
+    }
+    Some(public_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::uri::{normalize, unwrap_urn};
+
+    #[test]
+    fn normalizes_and_unwraps() {
+        assert_eq!(
+            normalize("http://a/my doc ü.dtd"),
+            "http://a/my%20doc%20%C3%BC.dtd"
+        );
+        assert_eq!(
+            unwrap_urn("urn:publicid:-:OASIS:DTD+DocBook+XML+V4.1.2:EN").as_deref(),
+            Some("-//OASIS//DTD DocBook XML V4.1.2//EN")
+        );
+        assert_eq!(
+            unwrap_urn("URN:PUBLICID:a;b%2Bc").as_deref(),
+            Some("a::b+c")
+        );
+        assert_eq!(unwrap_urn("urn:isbn:1"), None);
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use document::content::{Id, Terms, EMPTY, NOT_ALLOWED};
+use document::dtd::{ContentParticle, ContentTerm, Occurrence};
+
+/// The content models of element declarations, compiled to terms whose
+/// leaves are element names. A DTD tells children apart by name alone, so
+/// every mention of a name is the same leaf.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NamedTerms {
+    terms: Terms,
+    /// The element names of all models, by leaf.
+    names: Vec<String>,
+    leaves: HashMap<String, usize>,
+}
+
+impl NamedTerms {
+    /// Whether the model matches the empty sequence of elements.
+    pub(crate) fn nullable(&self, p: Id) -> bool {
+        self.terms.nullable(p)
+    }
+
+    /// Compiles a content model of children.
+    pub(crate) fn particle(&mut self, particle: &ContentParticle) -> Id {
+        let term = match &particle.term {
+            ContentTerm::Name(name) => {
+                let next = self.names.len();
+                let leaf = *self.leaves.entry(name.clone()).or_insert(next);
+                if leaf == next {
+                    self.names.push(name.clone());
+                }
+                self.terms.leaf(leaf)
+            }
+            ContentTerm::Sequence(particles) => particles.iter().fold(EMPTY, |p, particle| {
+                let q = self.particle(particle);
+                self.terms.group(p, q)
+            }),
+            ContentTerm::Choice(particles) => particles.iter().fold(NOT_ALLOWED, |p, particle| {
+                let q = self.particle(particle);
+                self.terms.choice(p, q)
+            }),
+        };
+        match particle.occurrence {
+            Occurrence::Once => term,
+            Occurrence::Optional => self.terms.repeat(term, 0, Some(1)),
+            Occurrence::ZeroOrMore => self.terms.repeat(term, 0, None),
+            Occurrence::OneOrMore => self.terms.repeat(term, 1, None),
+        }
+    }
+
+    /// The model that matches what may follow the element `name`.
+    pub(crate) fn derive(&mut self, p: Id, name: &str) -> Id {
+        match self.leaves.get(name) {
+            Some(&leaf) => self.terms.derive(p, leaf),
+            None => NOT_ALLOWED,
+        }
+    }
+
+    /// The names of the elements that may come next, in the order the
+    /// models first name them.
+    pub(crate) fn first(&mut self, p: Id) -> Vec<String> {
+        self.terms
+            .first(p)
+            .into_iter()
+            .map(|leaf| self.names[leaf].clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use document::dtd::{ContentParticle, ContentTerm, Occurrence};
+
+    use document::content::{EMPTY, NOT_ALLOWED};
+
+    use crate::content::NamedTerms;
+
+    fn name(name: &str, occurrence: Occurrence) -> ContentParticle {
+        ContentParticle {
+            term: ContentTerm::Name(name.to_owned()),
+            occurrence,
+        }
+    }
+
+    #[test]
+    fn matches_occurrences() {
+        // (title, (para | list)+, note?)
+        let model = ContentParticle {
+            term: ContentTerm::Sequence(vec![
+                name("title", Occurrence::Once),
+                ContentParticle {
+                    term: ContentTerm::Choice(vec![
+                        name("para", Occurrence::Once),
+                        name("list", Occurrence::Once),
+                    ]),
+                    occurrence: Occurrence::OneOrMore,
+                },
+                name("note", Occurrence::Optional),
+            ]),
+            occurrence: Occurrence::Once,
+        };
+        let mut terms = NamedTerms::default();
+        let model = terms.particle(&model);
+
+        assert_eq!(terms.first(model), ["title"]);
+        let p = terms.derive(model, "title");
+        assert!(!terms.nullable(p));
+        assert_eq!(terms.derive(p, "note"), NOT_ALLOWED);
+        assert_eq!(terms.derive(p, "other"), NOT_ALLOWED);
+        let p = terms.derive(p, "para");
+        let p = terms.derive(p, "list");
+        assert!(terms.nullable(p));
+        assert_eq!(terms.first(p), ["para", "list", "note"]);
+        let p = terms.derive(p, "note");
+        assert_eq!(p, EMPTY);
+    }
+}
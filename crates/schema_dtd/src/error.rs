@@ -0,0 +1,153 @@
+use std::fmt::{Display, Formatter};
+
+use document::position::Location;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Declarations were left unread, so documents cannot be judged
+    /// against the DTD.
+    Incomplete,
+    /// A declaration breaks a validity constraint on the DTD itself.
+    Invalid(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Incomplete => f.write_str("the DTD has declarations that were not read"),
+            Error::Invalid(message) => write!(f, "invalid DTD: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A way in which a document is not valid against its DTD.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub kind: ValidationErrorKind,
+    /// The element at fault, as a path of names such as
+    /// `/book/chapter[2]`.
+    pub path: String,
+    /// Where the element was read from, when locations were kept.
+    pub location: Option<Location>,
+}
+
+/// What was not valid. Names are written as in the document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationErrorKind {
+    /// A document element other than the one the `<!DOCTYPE>` names.
+    WrongRootElement { name: String, expected: String },
+    /// An element that has no `<!ELEMENT>` declaration.
+    UndeclaredElement { name: String },
+    /// An element the content model does not allow where it appears.
+    UnexpectedElement { name: String, expected: Vec<String> },
+    /// An element that ends before its content model is satisfied.
+    IncompleteContent { expected: Vec<String> },
+    /// Character data in element content.
+    UnexpectedText { text: String },
+    /// Content of any kind in an element declared `EMPTY`.
+    NotEmpty,
+    /// An attribute that has no declaration for its element.
+    UnexpectedAttribute { name: String },
+    /// A `#REQUIRED` attribute that is missing.
+    MissingAttribute { name: String },
+    /// A value that is not valid for the type of its attribute.
+    InvalidValue {
+        attribute: String,
+        value: String,
+        reason: String,
+    },
+    /// A value that differs from the `#FIXED` value of its attribute.
+    NotFixedValue {
+        attribute: String,
+        value: String,
+        fixed: String,
+    },
+    /// An ID that an earlier element already has.
+    DuplicateId { id: String },
+    /// An IDREF or IDREFS value naming an ID no element has.
+    UnknownId { attribute: String, id: String },
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(location) = &self.location {
+            write!(f, "{}: ", location)?;
+        }
+        write!(f, "{}: {}", self.path, self.kind)
+    }
+}
+
+impl Display for ValidationErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationErrorKind::WrongRootElement { name, expected } => write!(
+                f,
+                "document element is `{}`, but the DOCTYPE names `{}`",
+                name, expected
+            ),
+            ValidationErrorKind::UndeclaredElement { name } => {
+                write!(f, "no declaration for element `{}`", name)
+            }
+            ValidationErrorKind::UnexpectedElement { name, expected } => {
+                write!(f, "element `{}` not allowed here", name)?;
+                write_expected(f, expected)
+            }
+            ValidationErrorKind::IncompleteContent { expected } => {
+                f.write_str("content is incomplete")?;
+                write_expected(f, expected)
+            }
+            ValidationErrorKind::UnexpectedText { text } => {
+                write!(f, "text \"{}\" not allowed", text)
+            }
+            ValidationErrorKind::NotEmpty => f.write_str("element declared EMPTY has content"),
+            ValidationErrorKind::UnexpectedAttribute { name } => {
+                write!(f, "attribute `{}` not allowed", name)
+            }
+            ValidationErrorKind::MissingAttribute { name } => {
+                write!(f, "required attribute `{}` is missing", name)
+            }
+            ValidationErrorKind::InvalidValue {
+                attribute,
+                value,
+                reason,
+            } => write!(
+                f,
+                "invalid value \"{}\" for attribute `{}`: {}",
+                value, attribute, reason
+            ),
+            ValidationErrorKind::NotFixedValue {
+                attribute,
+                value,
+                fixed,
+            } => write!(
+                f,
+                "value \"{}\" of attribute `{}` differs from the fixed value \"{}\"",
+                value, attribute, fixed
+            ),
+            ValidationErrorKind::DuplicateId { id } => write!(f, "ID `{}` is not unique", id),
+            ValidationErrorKind::UnknownId { attribute, id } => {
+                write!(f, "attribute `{}` refers to unknown ID `{}`", attribute, id)
+            }
+        }
+    }
+}
+
+fn write_expected(f: &mut Formatter<'_>, expected: &[String]) -> std::fmt::Result {
+    if expected.is_empty() {
+        return Ok(());
+    }
+    f.write_str("; expected ")?;
+    for (index, item) in expected.iter().enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "`{}`", item)?;
+    }
+    Ok(())
+}
+
+impl std::error::Error for ValidationError {}
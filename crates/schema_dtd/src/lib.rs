@@ -0,0 +1,10 @@
+//! Validating documents against the document type definition read from
+//! their `<!DOCTYPE>`: content models, attribute types and defaults, and
+//! ID/IDREF integrity, as XML 1.0 defines validity.
+
+pub use error::{Error, Result, ValidationError, ValidationErrorKind};
+pub use validator::Validator;
+
+mod content;
+mod error;
+mod validator;
@@ -0,0 +1,749 @@
+use std::collections::{HashMap, HashSet};
+
+use document::chars::{is_name, is_nmtoken, is_whitespace};
+use document::dtd::{AttributeDecl, AttributeType, ContentSpec, DefaultDecl, Dtd, EntityValue};
+use document::node::{Document, Element, Node, NodeId};
+
+use crate::content::NamedTerms;
+use crate::error::{Error, Result, ValidationError, ValidationErrorKind};
+use document::content::{Id, NOT_ALLOWED};
+
+/// How deeply entity references in a default value may nest.
+const MAX_ENTITY_DEPTH: usize = 16;
+
+/// What an element declaration allows as content.
+#[derive(Debug, Clone)]
+enum Model {
+    Empty,
+    Any,
+    Mixed(Vec<String>),
+    Children(Id),
+}
+
+/// Validates documents against a DTD, as XML 1.0 defines validity.
+///
+/// Namespace declarations are checked against the attribute declarations
+/// that name them, `xmlns` or `xmlns:prefix`, but need none.
+#[derive(Debug, Clone)]
+pub struct Validator {
+    dtd: Dtd,
+    terms: NamedTerms,
+    models: HashMap<String, Model>,
+}
+
+impl Validator {
+    /// Compiles the content models of `dtd`, failing if the DTD breaks a
+    /// validity constraint on its declarations or was not read in full.
+    pub fn new(dtd: &Dtd) -> Result<Validator> {
+        if dtd.incomplete {
+            return Err(Error::Incomplete);
+        }
+        if let Some(name) = dtd.redeclared_elements.iter().next() {
+            return Err(Error::Invalid(format!(
+                "element `{}` is declared more than once",
+                name
+            )));
+        }
+        let mut terms = NamedTerms::default();
+        let mut models = HashMap::new();
+        for (name, declaration) in &dtd.elements {
+            let model = match &declaration.content {
+                ContentSpec::Empty => Model::Empty,
+                ContentSpec::Any => Model::Any,
+                ContentSpec::Mixed(names) => {
+                    let mut seen = HashSet::new();
+                    if let Some(duplicate) = names.iter().find(|name| !seen.insert(*name)) {
+                        return Err(Error::Invalid(format!(
+                            "`{}` appears more than once in the content of `{}`",
+                            duplicate, name
+                        )));
+                    }
+                    Model::Mixed(names.clone())
+                }
+                ContentSpec::Children(particle) => Model::Children(terms.particle(particle)),
+            };
+            models.insert(name.clone(), model);
+        }
+        for (element, attributes) in &dtd.attributes {
+            check_attribute_list(dtd, element, attributes)?;
+        }
+        for entity in dtd.entities.values() {
+            if let EntityValue::External {
+                notation: Some(notation),
+                ..
+            } = &entity.value
+            {
+                if !dtd.notations.contains_key(notation) {
+                    return Err(Error::Invalid(format!(
+                        "entity `{}` has undeclared notation `{}`",
+                        entity.name, notation
+                    )));
+                }
+            }
+        }
+        Ok(Validator {
+            dtd: dtd.clone(),
+            terms,
+            models,
+        })
+    }
+
+    /// Validates `document`, returning every error found.
+    pub fn validate(
+        &mut self,
+        document: &Document,
+    ) -> std::result::Result<(), Vec<ValidationError>> {
+        let mut assessment = Assessment {
+            dtd: &self.dtd,
+            models: &self.models,
+            terms: &mut self.terms,
+            document,
+            ids: HashSet::new(),
+            references: Vec::new(),
+            errors: Vec::new(),
+        };
+        if let Some(Node::Element(element)) = document.node(document.root) {
+            let path = format!("/{}", element.name);
+            let name = element.name.to_string();
+            if name != assessment.dtd.name {
+                let kind = ValidationErrorKind::WrongRootElement {
+                    name,
+                    expected: assessment.dtd.name.clone(),
+                };
+                assessment.error(element, &path, kind);
+            }
+            assessment.element(element, &path);
+        }
+        let Assessment {
+            ids,
+            references,
+            mut errors,
+            ..
+        } = assessment;
+        errors.extend(
+            references
+                .into_iter()
+                .filter(|(id, _)| !ids.contains(id))
+                .map(|(_, error)| error),
+        );
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+}
+
+/// Checks the constraints on the attribute definitions of `element`.
+fn check_attribute_list(dtd: &Dtd, element: &str, attributes: &[AttributeDecl]) -> Result<()> {
+    let count = |kind: fn(&AttributeType) -> bool| {
+        attributes
+            .iter()
+            .filter(|attribute| kind(&attribute.kind))
+            .count()
+    };
+    if count(|kind| *kind == AttributeType::Id) > 1 {
+        return Err(Error::Invalid(format!(
+            "`{}` has more than one ID attribute",
+            element
+        )));
+    }
+    if count(|kind| matches!(kind, AttributeType::Notation(_))) > 1 {
+        return Err(Error::Invalid(format!(
+            "`{}` has more than one NOTATION attribute",
+            element
+        )));
+    }
+    for attribute in attributes {
+        let invalid = |reason: String| {
+            Error::Invalid(format!(
+                "attribute `{}` of `{}`: {}",
+                attribute.name, element, reason
+            ))
+        };
+        match &attribute.kind {
+            AttributeType::Id
+                if matches!(
+                    attribute.default,
+                    DefaultDecl::Fixed(_) | DefaultDecl::Default(_)
+                ) =>
+            {
+                return Err(invalid("an ID attribute may not have a default".to_owned()));
+            }
+            AttributeType::Notation(names) => {
+                if matches!(
+                    dtd.element(element).map(|e| &e.content),
+                    Some(ContentSpec::Empty)
+                ) {
+                    return Err(invalid(
+                        "an EMPTY element may not have a NOTATION attribute".to_owned(),
+                    ));
+                }
+                if let Some(name) = names.iter().find(|n| !dtd.notations.contains_key(*n)) {
+                    return Err(invalid(format!("notation `{}` is not declared", name)));
+                }
+            }
+            _ => {}
+        }
+        if let DefaultDecl::Fixed(value) | DefaultDecl::Default(value) = &attribute.default {
+            let value = replace_references(dtd, value, 0).map_err(invalid)?;
+            let value = normalize(&attribute.kind, &value);
+            if let Some(reason) = lexical_error(&attribute.kind, &value) {
+                return Err(invalid(format!("default \"{}\" is {}", value, reason)));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the character and entity references of an attribute value
+/// written in the DTD, turning white space characters into spaces.
+fn replace_references(dtd: &Dtd, value: &str, depth: usize) -> std::result::Result<String, String> {
+    if depth > MAX_ENTITY_DEPTH {
+        return Err("entity references nest too deeply".to_owned());
+    }
+    let mut replaced = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find(|c| c == '&' || is_whitespace(c)) {
+        replaced.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if !rest.starts_with('&') {
+            replaced.push(' ');
+            rest = &rest[1..];
+            continue;
+        }
+        let end = rest
+            .find(';')
+            .ok_or_else(|| "a reference lacks its `;`".to_owned())?;
+        let reference = &rest[1..end];
+        rest = &rest[end + 1..];
+        if let Some(code) = reference.strip_prefix('#') {
+            let code = match code.strip_prefix('x') {
+                Some(hex) => u32::from_str_radix(hex, 16),
+                None => code.parse(),
+            };
+            let c = code
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| format!("`&{};` is not a character", reference))?;
+            replaced.push(c);
+            continue;
+        }
+        match (reference, dtd.entity(reference).map(|e| &e.value)) {
+            ("lt", _) => replaced.push('<'),
+            ("gt", _) => replaced.push('>'),
+            ("amp", _) => replaced.push('&'),
+            ("apos", _) => replaced.push('\''),
+            ("quot", _) => replaced.push('"'),
+            (_, Some(EntityValue::Internal(text))) => {
+                replaced.push_str(&replace_references(dtd, text, depth + 1)?);
+            }
+            (_, Some(EntityValue::External { .. })) => {
+                return Err(format!("external entity `{}` is referenced", reference));
+            }
+            (_, None) => return Err(format!("entity `{}` is not declared", reference)),
+        }
+    }
+    replaced.push_str(rest);
+    Ok(replaced)
+}
+
+/// The value of an attribute of type `kind`: values other than CDATA are
+/// stripped of leading and trailing spaces, and runs of spaces in them
+/// are collapsed.
+fn normalize(kind: &AttributeType, value: &str) -> String {
+    match kind {
+        AttributeType::CData => value.to_owned(),
+        _ => value
+            .split(is_whitespace)
+            .filter(|token| !token.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+/// Why the normalized `value` does not match the production of `kind`.
+fn lexical_error(kind: &AttributeType, value: &str) -> Option<String> {
+    let tokens = || value.split(' ');
+    let valid = match kind {
+        AttributeType::CData => return None,
+        AttributeType::Id | AttributeType::IdRef | AttributeType::Entity => is_name(value),
+        AttributeType::IdRefs | AttributeType::Entities => tokens().all(is_name),
+        AttributeType::NmToken => is_nmtoken(value),
+        AttributeType::NmTokens => tokens().all(is_nmtoken),
+        AttributeType::Notation(names) | AttributeType::Enumeration(names) => {
+            if names.iter().any(|name| name == value) {
+                return None;
+            }
+            let names: Vec<_> = names.iter().map(|name| format!("`{}`", name)).collect();
+            return Some(format!("not one of {}", names.join(", ")));
+        }
+    };
+    match valid {
+        true => None,
+        false => Some(
+            match kind {
+                AttributeType::IdRefs | AttributeType::Entities => "not a list of names",
+                AttributeType::NmToken => "not a name token",
+                AttributeType::NmTokens => "not a list of name tokens",
+                _ => "not a name",
+            }
+            .to_owned(),
+        ),
+    }
+}
+
+/// The state of one validation of a document.
+struct Assessment<'a> {
+    dtd: &'a Dtd,
+    models: &'a HashMap<String, Model>,
+    terms: &'a mut NamedTerms,
+    document: &'a Document,
+    ids: HashSet<String>,
+    /// The IDs that IDREF and IDREFS attributes refer to, with the error
+    /// to report if no element has them.
+    references: Vec<(String, ValidationError)>,
+    errors: Vec<ValidationError>,
+}
+
+impl Assessment<'_> {
+    fn error(&mut self, element: &Element, path: &str, kind: ValidationErrorKind) {
+        self.errors.push(ValidationError {
+            kind,
+            path: path.to_owned(),
+            location: element.location.clone(),
+        });
+    }
+
+    fn element(&mut self, element: &Element, path: &str) {
+        let document = self.document;
+        let name = element.name.to_string();
+        let paths = child_paths(document, element, path);
+        let Some(model) = self.models.get(&name) else {
+            self.error(
+                element,
+                path,
+                ValidationErrorKind::UndeclaredElement { name },
+            );
+            self.children(&paths);
+            return;
+        };
+        self.attributes(element, &name, path);
+        match model {
+            Model::Empty => {
+                if !element.children.is_empty() {
+                    self.error(element, path, ValidationErrorKind::NotEmpty);
+                }
+            }
+            Model::Any => {}
+            Model::Mixed(names) => {
+                for (child, child_path) in &paths {
+                    let Some(Node::Element(child)) = document.node(*child) else {
+                        continue;
+                    };
+                    let name = child.name.to_string();
+                    if !names.contains(&name) {
+                        let kind = ValidationErrorKind::UnexpectedElement {
+                            name,
+                            expected: names.clone(),
+                        };
+                        self.error(child, child_path, kind);
+                    }
+                }
+            }
+            &Model::Children(model) => self.element_content(element, path, model, &paths),
+        }
+        self.children(&paths);
+    }
+
+    /// Matches the children of `element` against the content model
+    /// `model`. Only white space may come between them.
+    fn element_content(
+        &mut self,
+        element: &Element,
+        path: &str,
+        model: Id,
+        paths: &[(NodeId, String)],
+    ) {
+        let document = self.document;
+        let mut paths = paths.iter();
+        let mut p = model;
+        for &child in &element.children {
+            let text = match document.node(child) {
+                Some(Node::Element(child_element)) => {
+                    let name = child_element.name.to_string();
+                    let child_path = paths.next().map_or(path, |(_, path)| path);
+                    match self.terms.derive(p, &name) {
+                        NOT_ALLOWED => {
+                            let expected = self.terms.first(p);
+                            let kind = ValidationErrorKind::UnexpectedElement { name, expected };
+                            self.error(child_element, child_path, kind);
+                        }
+                        next => p = next,
+                    }
+                    continue;
+                }
+                Some(Node::Text(text)) => &text.data,
+                // A CDATA section is character data even when it holds only
+                // whitespace, so element content never allows it.
+                Some(Node::CData(cdata)) => {
+                    let text = cdata.data.clone();
+                    self.error(element, path, ValidationErrorKind::UnexpectedText { text });
+                    continue;
+                }
+                _ => continue,
+            };
+            if !text.chars().all(is_whitespace) {
+                let text = text.trim().to_owned();
+                self.error(element, path, ValidationErrorKind::UnexpectedText { text });
+            }
+        }
+        if !self.terms.nullable(p) {
+            let expected = self.terms.first(p);
+            self.error(
+                element,
+                path,
+                ValidationErrorKind::IncompleteContent { expected },
+            );
+        }
+    }
+
+    fn children(&mut self, paths: &[(NodeId, String)]) {
+        let document = self.document;
+        for (child, child_path) in paths {
+            if let Some(Node::Element(child)) = document.node(*child) {
+                self.element(child, child_path);
+            }
+        }
+    }
+
+    fn attributes(&mut self, element: &Element, name: &str, path: &str) {
+        let dtd = self.dtd;
+        let declarations = dtd.attributes.get(name).map_or(&[][..], Vec::as_slice);
+        let declaration = |name: &str| declarations.iter().find(|d| d.name == name);
+        for attribute in &element.attributes {
+            let name = attribute.name.to_string();
+            match declaration(&name) {
+                Some(declaration) => self.value(element, path, declaration, &attribute.value),
+                None => self.error(
+                    element,
+                    path,
+                    ValidationErrorKind::UnexpectedAttribute { name },
+                ),
+            }
+        }
+        for namespace in &element.namespaces {
+            let name = match &namespace.prefix {
+                Some(prefix) => format!("xmlns:{}", prefix),
+                None => "xmlns".to_owned(),
+            };
+            if let Some(declaration) = declaration(&name) {
+                self.value(element, path, declaration, &namespace.uri);
+            }
+        }
+        for declaration in declarations {
+            if declaration.default != DefaultDecl::Required {
+                continue;
+            }
+            let name = declaration.name.as_str();
+            let present = element
+                .attributes
+                .iter()
+                .any(|attribute| attribute.name.to_string() == name)
+                || element.namespaces.iter().any(|namespace| {
+                    match (&namespace.prefix, name.strip_prefix("xmlns")) {
+                        (None, Some("")) => true,
+                        (Some(prefix), Some(rest)) => rest.strip_prefix(':') == Some(prefix),
+                        _ => false,
+                    }
+                });
+            if !present {
+                let kind = ValidationErrorKind::MissingAttribute {
+                    name: name.to_owned(),
+                };
+                self.error(element, path, kind);
+            }
+        }
+    }
+
+    fn value(&mut self, element: &Element, path: &str, declaration: &AttributeDecl, value: &str) {
+        let dtd = self.dtd;
+        let attribute = declaration.name.clone();
+        let value = normalize(&declaration.kind, value);
+        if let DefaultDecl::Fixed(fixed) = &declaration.default {
+            let fixed = replace_references(dtd, fixed, 0).unwrap_or_else(|_| fixed.clone());
+            let fixed = normalize(&declaration.kind, &fixed);
+            if value != fixed {
+                let kind = ValidationErrorKind::NotFixedValue {
+                    attribute,
+                    value,
+                    fixed,
+                };
+                self.error(element, path, kind);
+                return;
+            }
+        }
+        let reason = lexical_error(&declaration.kind, &value).or_else(|| match &declaration.kind {
+            AttributeType::Id => {
+                if !self.ids.insert(value.clone()) {
+                    let kind = ValidationErrorKind::DuplicateId { id: value.clone() };
+                    self.error(element, path, kind);
+                }
+                None
+            }
+            AttributeType::IdRef | AttributeType::IdRefs => {
+                for id in value.split(' ') {
+                    let kind = ValidationErrorKind::UnknownId {
+                        attribute: attribute.clone(),
+                        id: id.to_owned(),
+                    };
+                    let error = ValidationError {
+                        kind,
+                        path: path.to_owned(),
+                        location: element.location.clone(),
+                    };
+                    self.references.push((id.to_owned(), error));
+                }
+                None
+            }
+            AttributeType::Entity | AttributeType::Entities => value
+                .split(' ')
+                .find(|name| {
+                    !matches!(
+                        dtd.entity(name).map(|e| &e.value),
+                        Some(EntityValue::External {
+                            notation: Some(_),
+                            ..
+                        })
+                    )
+                })
+                .map(|name| format!("`{}` is not an unparsed entity", name)),
+            _ => None,
+        });
+        if let Some(reason) = reason {
+            let kind = ValidationErrorKind::InvalidValue {
+                attribute,
+                value,
+                reason,
+            };
+            self.error(element, path, kind);
+        }
+    }
+}
+
+/// The element children of `element`, with their paths. Positions are
+/// given only to names that occur more than once.
+fn child_paths(document: &Document, element: &Element, path: &str) -> Vec<(NodeId, String)> {
+    let children: Vec<_> = element
+        .children
+        .iter()
+        .filter_map(|&child| match document.node(child) {
+            Some(Node::Element(child_element)) => Some((child, child_element.name.to_string())),
+            _ => None,
+        })
+        .collect();
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for (_, name) in &children {
+        *counts.entry(name).or_default() += 1;
+    }
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    children
+        .iter()
+        .map(|(child, name)| {
+            let path = match counts[name.as_str()] {
+                1 => format!("{}/{}", path, name),
+                _ => {
+                    let position = positions.entry(name).or_default();
+                    *position += 1;
+                    format!("{}/{}[{}]", path, name, position)
+                }
+            };
+            (*child, path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use document::node::Document;
+
+    use crate::{Error, ValidationErrorKind, Validator};
+
+    const DTD: &str = r#"<!DOCTYPE book [
+        <!NOTATION png SYSTEM "image/png">
+        <!ENTITY cover SYSTEM "cover.png" NDATA png>
+        <!ENTITY version "4.5">
+        <!ELEMENT book (title, chapter+, appendix?)>
+        <!ATTLIST book
+            version CDATA #FIXED "&version;"
+            status (draft | final) "draft"
+            image ENTITY #IMPLIED>
+        <!ELEMENT title (#PCDATA)>
+        <!ELEMENT chapter (title, (para | xref)*)>
+        <!ATTLIST chapter id ID #REQUIRED>
+        <!ELEMENT para (#PCDATA | emphasis)*>
+        <!ELEMENT emphasis (#PCDATA)>
+        <!ELEMENT xref EMPTY>
+        <!ATTLIST xref linkend IDREFS #REQUIRED>
+        <!ELEMENT appendix ANY>
+    ]>"#;
+
+    fn validate(body: &str) -> Result<Vec<ValidationErrorKind>, anyhow::Error> {
+        let document = Document::parse(&format!("{}{}", DTD, body))?;
+        let dtd = document.dtd.as_ref().ok_or(anyhow::anyhow!("no DTD"))?;
+        let errors = match Validator::new(dtd)?.validate(&document) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.into_iter().map(|error| error.kind).collect(),
+        };
+        Ok(errors)
+    }
+
+    #[test]
+    fn accepts_valid_documents() -> Result<(), anyhow::Error> {
+        let errors = validate(
+            r#"<book version="4.5" status=" final " image="cover">
+                <title>Guide</title>
+                <chapter id="intro">
+                    <title>Intro</title>
+                    <para>See <emphasis>below</emphasis>.</para>
+                    <xref linkend="usage  intro"/>
+                </chapter>
+                <chapter id="usage"><title>Usage</title></chapter>
+                <appendix>Anything <para/></appendix>
+            </book>"#,
+        )?;
+        assert_eq!(errors, []);
+        Ok(())
+    }
+
+    #[test]
+    fn reports_content_and_attribute_errors() -> Result<(), anyhow::Error> {
+        let errors = validate(
+            r#"<book version="4.6" status="lost" image="version" lang="en">
+                <title>Guide</title>
+                <chapter id="a">
+                    <title>A</title>
+                    text
+                    <para><title/></para>
+                    <xref linkend="b"> </xref>
+                </chapter>
+                <chapter id="a"><title>B</title></chapter>
+                <chapter><title>C</title><note/></chapter>
+            </book>"#,
+        )?;
+        let expected = [
+            ValidationErrorKind::NotFixedValue {
+                attribute: "version".to_owned(),
+                value: "4.6".to_owned(),
+                fixed: "4.5".to_owned(),
+            },
+            ValidationErrorKind::InvalidValue {
+                attribute: "status".to_owned(),
+                value: "lost".to_owned(),
+                reason: "not one of `draft`, `final`".to_owned(),
+            },
+            ValidationErrorKind::InvalidValue {
+                attribute: "image".to_owned(),
+                value: "version".to_owned(),
+                reason: "`version` is not an unparsed entity".to_owned(),
+            },
+            ValidationErrorKind::UnexpectedAttribute {
+                name: "lang".to_owned(),
+            },
+            ValidationErrorKind::UnexpectedText {
+                text: "text".to_owned(),
+            },
+            ValidationErrorKind::UnexpectedElement {
+                name: "title".to_owned(),
+                expected: vec!["emphasis".to_owned()],
+            },
+            ValidationErrorKind::NotEmpty,
+            ValidationErrorKind::DuplicateId { id: "a".to_owned() },
+            ValidationErrorKind::MissingAttribute {
+                name: "id".to_owned(),
+            },
+            ValidationErrorKind::UnexpectedElement {
+                name: "note".to_owned(),
+                expected: vec!["para".to_owned(), "xref".to_owned()],
+            },
+            ValidationErrorKind::UndeclaredElement {
+                name: "note".to_owned(),
+            },
+            ValidationErrorKind::UnknownId {
+                attribute: "linkend".to_owned(),
+                id: "b".to_owned(),
+            },
+        ];
+        assert_eq!(errors, expected);
+
+        let errors = validate("<title>Guide</title>")?;
+        assert_eq!(
+            errors,
+            [ValidationErrorKind::WrongRootElement {
+                name: "title".to_owned(),
+                expected: "book".to_owned(),
+            }]
+        );
+
+        let errors = validate(
+            r#"<book><![CDATA[ ]]><title>Guide</title>
+                <chapter id="a"><title>A</title></chapter>
+            </book>"#,
+        )?;
+        assert_eq!(
+            errors,
+            [ValidationErrorKind::UnexpectedText {
+                text: " ".to_owned(),
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_invalid_declarations() -> Result<(), anyhow::Error> {
+        let cases = [
+            (
+                "<!ATTLIST a x ID #IMPLIED y ID #IMPLIED>",
+                "`a` has more than one ID attribute",
+            ),
+            (
+                r#"<!ATTLIST a x ID "v">"#,
+                "attribute `x` of `a`: an ID attribute may not have a default",
+            ),
+            (
+                "<!ATTLIST a x NOTATION (gif) #IMPLIED>",
+                "attribute `x` of `a`: notation `gif` is not declared",
+            ),
+            (
+                r#"<!ATTLIST a x NMTOKENS "1 &amp;">"#,
+                "attribute `x` of `a`: default \"1 &\" is not a list of name tokens",
+            ),
+            (
+                "<!ELEMENT a (#PCDATA | b | b)*>",
+                "`b` appears more than once in the content of `a`",
+            ),
+            (
+                "<!ELEMENT a ANY><!ELEMENT a EMPTY>",
+                "element `a` is declared more than once",
+            ),
+            (
+                r#"<!ENTITY e SYSTEM "e.gif" NDATA gif>"#,
+                "entity `e` has undeclared notation `gif`",
+            ),
+        ];
+        for (declarations, message) in cases {
+            let document = Document::parse(&format!("<!DOCTYPE a [{}]><a/>", declarations))?;
+            let dtd = document.dtd.as_ref().ok_or(anyhow::anyhow!("no DTD"))?;
+            assert_eq!(
+                Validator::new(dtd).map(|_| ()),
+                Err(Error::Invalid(message.to_owned())),
+                "{}",
+                declarations
+            );
+        }
+
+        let document = Document::parse(r#"<!DOCTYPE a SYSTEM "a.dtd"><a/>"#)?;
+        let dtd = document.dtd.as_ref().ok_or(anyhow::anyhow!("no DTD"))?;
+        assert_eq!(Validator::new(dtd).map(|_| ()), Err(Error::Incomplete));
+        Ok(())
+    }
+}
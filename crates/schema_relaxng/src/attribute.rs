@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use crate::name_class::NameClass;
+use crate::pattern::{Pattern, RawNamed};
+use crate::{DatatypeLibrary, Namespace, QName};
+
+/// An `attribute` pattern. The name is given either by the `name` attribute
+/// or by a name class as the first child, and the value defaults to `text`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "RawNamed", into = "RawNamed")]
+pub struct Attribute {
+    pub ns: Namespace,
+    pub datatype_library: DatatypeLibrary,
+    pub name: Option<QName>,
+    pub name_class: Option<NameClass>,
+    pub pattern: Option<Box<Pattern>>,
+}
+
+impl TryFrom<RawNamed> for Attribute {
+    type Error = String;
+
+    fn try_from(raw: RawNamed) -> Result<Self, Self::Error> {
+        let mut raw = raw;
+        let (name_class, mut content) = raw.split("attribute")?;
+        if content.len() > 1 {
+            return Err("`attribute` has more than one pattern".to_owned());
+        }
+        Ok(Attribute {
+            ns: raw.ns,
+            datatype_library: raw.datatype_library,
+            name: raw.name,
+            name_class,
+            pattern: content.pop().map(Box::new),
+        })
+    }
+}
+
+impl From<Attribute> for RawNamed {
+    fn from(attribute: Attribute) -> Self {
+        RawNamed::join(
+            attribute.ns,
+            attribute.datatype_library,
+            attribute.name,
+            attribute.name_class,
+            attribute
+                .pattern
+                .map(|pattern| *pattern)
+                .into_iter()
+                .collect(),
+        )
+    }
+}
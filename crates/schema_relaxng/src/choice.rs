@@ -1,15 +1,17 @@
+use serde::{Deserialize, Serialize};
+
 use crate::pattern::Pattern;
 use crate::{DatatypeLibrary, Namespace};
-use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize)]
+/// A `choice` between patterns.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Choice {
-    #[serde(rename = "@ns")]
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
     pub ns: Namespace,
 
-    #[serde(rename = "@datatypeLibrary")]
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
     pub datatype_library: DatatypeLibrary,
 
-    #[serde(rename = "$value")]
+    #[serde(rename = "$value", skip_serializing_if = "Option::is_none")]
     pub content: Option<Vec<Pattern>>,
 }
@@ -0,0 +1,174 @@
+use document::chars::{is_ncname_char, is_ncname_start_char, is_whitespace};
+
+use crate::compact::syntax_error;
+use crate::error::Result;
+
+/// The keywords of the compact syntax, which need a backslash to be used
+/// as identifiers.
+pub(crate) const KEYWORDS: &[&str] = &[
+    "attribute",
+    "default",
+    "datatypes",
+    "div",
+    "element",
+    "empty",
+    "external",
+    "grammar",
+    "include",
+    "inherit",
+    "list",
+    "mixed",
+    "namespace",
+    "notAllowed",
+    "parent",
+    "start",
+    "string",
+    "text",
+    "token",
+];
+
+/// The operators and delimiters, longest first so `|=` wins over `|`.
+const SYMBOLS: &[&str] = &[
+    "|=", "&=", ">>", "{", "}", "(", ")", "[", "]", "=", ",", "&", "|", "?", "*", "+", "-", "~",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    /// An NCName, which may be a keyword.
+    Name(String),
+    /// An NCName written with a leading backslash, never a keyword.
+    Escaped(String),
+    /// A prefixed name, `prefix:local`.
+    CName(String, String),
+    /// A namespace wildcard, `prefix:*`.
+    NsName(String),
+    Literal(String),
+    Symbol(&'static str),
+    Eof,
+}
+
+impl Token {
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Name(name) if name == keyword)
+    }
+
+    pub fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self, Token::Symbol(s) if *s == symbol)
+    }
+}
+
+/// Splits `input` into tokens with their byte offsets. Comments, including
+/// `##` documentation, are skipped.
+pub(crate) fn tokenize(input: &str) -> Result<Vec<(Token, usize)>> {
+    let mut tokens = Vec::new();
+    let mut rest = input;
+    loop {
+        rest = rest.trim_start_matches(is_whitespace);
+        let offset = input.len() - rest.len();
+        let Some(c) = rest.chars().next() else {
+            tokens.push((Token::Eof, offset));
+            return Ok(tokens);
+        };
+
+        if c == '#' {
+            rest = rest.find('\n').map_or("", |index| &rest[index..]);
+            continue;
+        }
+
+        let token = if c == '"' || c == '\'' {
+            let (literal, after) = literal(input, rest)?;
+            rest = after;
+            Token::Literal(literal)
+        } else if c == '\\' {
+            let name = ncname(&rest[1..]);
+            if name.is_empty() {
+                return Err(syntax_error(input, offset, "expected a name after `\\`"));
+            }
+            rest = &rest[1 + name.len()..];
+            Token::Escaped(name.to_owned())
+        } else if is_ncname_start_char(c) {
+            let name = ncname(rest);
+            rest = &rest[name.len()..];
+            if let Some(after) = rest.strip_prefix(":*") {
+                rest = after;
+                Token::NsName(name.to_owned())
+            } else if rest.starts_with(':') && rest[1..].starts_with(is_ncname_start_char) {
+                let local_name = ncname(&rest[1..]);
+                rest = &rest[1 + local_name.len()..];
+                Token::CName(name.to_owned(), local_name.to_owned())
+            } else {
+                Token::Name(name.to_owned())
+            }
+        } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            rest = &rest[symbol.len()..];
+            Token::Symbol(symbol)
+        } else {
+            return Err(syntax_error(input, offset, &format!("unexpected `{}`", c)));
+        };
+        tokens.push((token, offset));
+    }
+}
+
+fn ncname(input: &str) -> &str {
+    let end = input
+        .char_indices()
+        .find(|(index, c)| !(is_ncname_char(*c) && (*index > 0 || is_ncname_start_char(*c))))
+        .map_or(input.len(), |(index, _)| index);
+    &input[..end]
+}
+
+/// Reads a quoted literal at the start of `rest`, in single or tripled
+/// quotes, giving its value and the input after it.
+fn literal<'a>(input: &str, rest: &'a str) -> Result<(String, &'a str)> {
+    let offset = input.len() - rest.len();
+    let quote = &rest[..1];
+    let tripled = quote.repeat(3);
+    let delimiter = if rest.starts_with(&tripled) {
+        tripled.as_str()
+    } else {
+        quote
+    };
+    let body = &rest[delimiter.len()..];
+    let Some(end) = body.find(delimiter) else {
+        return Err(syntax_error(input, offset, "unterminated literal"));
+    };
+    let value = &body[..end];
+    if delimiter.len() == 1 && value.contains('\n') {
+        return Err(syntax_error(input, offset, "newline in a literal"));
+    }
+    Ok((value.to_owned(), &body[end + delimiter.len()..]))
+}
+
+/// Replaces the escapes `\x{hex}`, which may stand for any character and
+/// apply before tokenizing.
+pub(crate) fn unescape(input: &str) -> Result<String> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(index) = rest.find('\\') {
+        output.push_str(&rest[..index]);
+        rest = &rest[index..];
+        let after_x = rest[1..].trim_start_matches('x');
+        let x_count = rest.len() - 1 - after_x.len();
+        let escape = match after_x.strip_prefix('{') {
+            Some(hex) if x_count > 0 => hex.find('}').map(|end| (&hex[..end], &hex[end + 1..])),
+            _ => None,
+        };
+        match escape {
+            Some((hex, after)) => {
+                let offset = input.len() - rest.len();
+                let c = u32::from_str_radix(hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| syntax_error(input, offset, "invalid character escape"))?;
+                output.push(c);
+                rest = after;
+            }
+            None => {
+                output.push('\\');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    Ok(output)
+}
@@ -0,0 +1,214 @@
+//! The compact syntax of RELAX NG, read into and written from the same
+//! model as the XML syntax.
+
+use document::position::Position;
+
+use crate::error::Error;
+
+mod lexer;
+mod parser;
+mod writer;
+
+pub(crate) use parser::parse;
+pub(crate) use writer::write;
+
+/// The datatype library the `xsd` prefix is bound to unless declared
+/// otherwise.
+const XSD_DATATYPES: &str = "http://www.w3.org/2001/XMLSchema-datatypes";
+
+fn syntax_error(input: &str, offset: usize, message: &str) -> Error {
+    Error::Compact {
+        position: Position::locate(input, offset),
+        message: message.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        parse_compact, parse_grammar_file, simplify_grammar, simplify_grammar_file, to_compact,
+        Combine, Error, GrammarContent, IncludeContent, NameClass, Pattern, SimpleNameClass,
+        SimplePattern,
+    };
+
+    #[test]
+    fn round_trips_the_schema_for_relaxng() -> Result<(), anyhow::Error> {
+        let pattern = parse_grammar_file("../../schema/relaxng.rng")?;
+        let written = to_compact(&pattern);
+        let read = parse_compact(&written)?;
+        assert_eq!(
+            simplify_grammar(&read, "")?,
+            simplify_grammar(&pattern, "")?
+        );
+        assert_eq!(to_compact(&read), written);
+        Ok(())
+    }
+
+    #[test]
+    fn reads_declarations_annotations_and_includes() -> Result<(), anyhow::Error> {
+        let data = r#"
+# A comment.
+default namespace doc = "urn:doc"
+namespace a = "http://relaxng.org/ns/compatibility/annotations/1.0"
+datatypes d = "http://www.w3.org/2001/XMLSchema-datatypes"
+
+## Documentation.
+[ a:documentation [ "The root." ] ]
+start |= element doc:root { \list* & attribute xml:lang { token "en" | "fr" ~ "ench" }? }
+
+a:example [ "<root/>" ]
+
+\list =
+  element list >> a:note [ x = "y" ] {
+    attribute * - (local | doc:*) { text }*,
+    d:integer { minInclusive = "1" } - "7"
+  }
+include "other.rnc" inherit = doc {
+  start = notAllowed
+}
+"#;
+        let Pattern::Grammar(grammar) = parse_compact(data)? else {
+            panic!("expected a grammar");
+        };
+        assert_eq!(grammar.ns.as_deref(), Some("urn:doc"));
+        let content = grammar.content.unwrap_or_default();
+        assert_eq!(content.len(), 3);
+        let GrammarContent::Start(start) = &content[0] else {
+            panic!("expected a start");
+        };
+        assert_eq!(start.combine, Some(Combine::Choice));
+        let GrammarContent::Define(define) = &content[1] else {
+            panic!("expected a define");
+        };
+        assert_eq!(define.name, "list");
+        let GrammarContent::Include(include) = &content[2] else {
+            panic!("expected an include");
+        };
+        assert_eq!(include.href, "other.rnc");
+        assert_eq!(include.ns.as_deref(), Some("urn:doc"));
+        assert!(matches!(
+            include.content.as_deref(),
+            Some([IncludeContent::Start(_)])
+        ));
+
+        let Some(Pattern::Element(element)) = define.content.as_ref().map(|c| &c[0]) else {
+            panic!("expected an element");
+        };
+        assert_eq!(element.name.as_deref(), Some("list"));
+        let content = element.content.as_deref().unwrap_or_default();
+        let Pattern::ZeroOrMore(attributes) = &content[0] else {
+            panic!("expected zeroOrMore");
+        };
+        let Some(Pattern::Attribute(attribute)) = attributes.content.as_ref().map(|c| &c[0]) else {
+            panic!("expected an attribute");
+        };
+        let Some(NameClass::AnyName(any_name)) = &attribute.name_class else {
+            panic!("expected anyName");
+        };
+        let except = &any_name.except.as_ref().expect("except").content[0];
+        let NameClass::Choice(choice) = except else {
+            panic!("expected a name class choice");
+        };
+        assert!(matches!(
+            &choice.content[0],
+            NameClass::Name(name) if name.ns.as_deref() == Some("") && name.name == "local"
+        ));
+        assert!(matches!(
+            &choice.content[1],
+            NameClass::NsName(ns_name) if ns_name.ns.as_deref() == Some("urn:doc")
+        ));
+        let Pattern::Data(data) = &content[1] else {
+            panic!("expected data");
+        };
+        assert_eq!(
+            data.datatype_library.as_deref(),
+            Some("http://www.w3.org/2001/XMLSchema-datatypes")
+        );
+        assert_eq!(data.params[0].name, "minInclusive");
+        assert!(data.except.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn includes_compact_grammars() -> Result<(), anyhow::Error> {
+        let directory =
+            std::env::temp_dir().join(format!("relaxng-compact-{} #1", std::process::id()));
+        fs::create_dir_all(&directory)?;
+        fs::write(
+            directory.join("main.rnc"),
+            "default namespace = \"urn:main\"\n\
+             include \"common.rnc\" { item = element item { xsd:int } }\n",
+        )?;
+        fs::write(
+            directory.join("common.rnc"),
+            "start = element items { item* }\nitem = element item { text }\n",
+        )?;
+        let grammar = simplify_grammar_file(directory.join("main.rnc"))?;
+        fs::remove_dir_all(&directory)?;
+
+        let name =
+            |local_name: &str| SimpleNameClass::Name("urn:main".to_owned(), local_name.to_owned());
+        let items = grammar
+            .defines
+            .values()
+            .find(|define| matches!(define, SimplePattern::Element(n, _) if *n == name("items")));
+        assert!(items.is_some());
+        let item = grammar
+            .defines
+            .values()
+            .find(|define| matches!(define, SimplePattern::Element(n, _) if *n == name("item")));
+        assert!(matches!(
+            item,
+            Some(SimplePattern::Element(_, content))
+                if matches!(&**content, SimplePattern::Data { data_type, .. } if data_type == "int")
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn writes_what_it_reads() -> Result<(), anyhow::Error> {
+        let data = r#"namespace x = "urn:x"
+
+start = element \element { parent-free, attribute x:id { string 'a"b' }, (text | empty)+ }
+parent-free = grammar { start = external "e.rnc" inherit = x }
+"#;
+        let pattern = parse_compact(data)?;
+        let written = to_compact(&pattern);
+        assert_eq!(parse_compact(&written)?, pattern);
+        assert!(written.contains("namespace ns1 = \"urn:x\""));
+        assert!(written.contains("external \"e.rnc\" inherit = ns1"));
+        assert!(written.contains(r#"string "a" ~ '"' ~ "b""#));
+        Ok(())
+    }
+
+    #[test]
+    fn reports_where_the_syntax_breaks() {
+        let error = |input: &str| match parse_compact(input) {
+            Err(Error::Compact { position, message }) => (position.line, position.column, message),
+            other => panic!("expected a syntax error, got {:?}", other),
+        };
+        assert_eq!(
+            error("start = a,\n  b | c"),
+            (
+                2,
+                5,
+                "operators cannot be mixed without parentheses".to_owned()
+            )
+        );
+        assert_eq!(
+            error("element foo { text"),
+            (1, 19, "expected `}`".to_owned())
+        );
+        assert_eq!(
+            error("element p:foo { text }"),
+            (1, 9, "undeclared prefix `p`".to_owned())
+        );
+        assert_eq!(
+            error("start = \"abc"),
+            (1, 9, "unterminated literal".to_owned())
+        );
+    }
+}
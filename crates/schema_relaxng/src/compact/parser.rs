@@ -0,0 +1,801 @@
+use std::collections::HashMap;
+
+use document::namespace::XML_NAMESPACE;
+
+use crate::compact::lexer::{tokenize, unescape, Token, KEYWORDS};
+use crate::compact::{syntax_error, XSD_DATATYPES};
+use crate::data::{Data, ExceptPattern, Param};
+use crate::define::Define;
+use crate::div::Div;
+use crate::element::Element;
+use crate::error::{Error, Result};
+use crate::external_ref::ExternalRef;
+use crate::grammar::{Grammar, GrammarContent};
+use crate::include::{Include, IncludeContent};
+use crate::name_class::{AnyName, ExceptNameClass, Name, NameClass, NameClassChoice, NsName};
+use crate::pattern::Pattern;
+use crate::r#ref::{ParentRef, Ref};
+use crate::start::Start;
+use crate::value::Value;
+use crate::{
+    Attribute, Choice, Combine, Group, Interleave, List, Mixed, Namespace, OneOrMore, Optional,
+    ZeroOrMore,
+};
+
+/// Parses a grammar in the compact syntax into the model of the XML
+/// syntax. Annotations and comments are dropped, as the XML parser drops
+/// foreign elements and attributes.
+pub(crate) fn parse(input: &str) -> Result<Pattern> {
+    let input = unescape(input)?;
+    let tokens = tokenize(&input)?;
+    let mut parser = Parser {
+        input: &input,
+        tokens,
+        index: 0,
+        namespaces: HashMap::from([("xml".to_owned(), Some(XML_NAMESPACE.to_owned()))]),
+        default_namespace: None,
+        datatypes: HashMap::from([("xsd".to_owned(), XSD_DATATYPES.to_owned())]),
+    };
+    parser.top_level()
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+    /// The declared prefixes, mapped to `None` when they inherit the
+    /// namespace of the referencing pattern.
+    namespaces: HashMap<String, Namespace>,
+    default_namespace: Namespace,
+    datatypes: HashMap<String, String>,
+}
+
+/// How the particles of a pattern are joined.
+#[derive(Clone, Copy, PartialEq)]
+enum Operator {
+    Group,
+    Choice,
+    Interleave,
+}
+
+impl<'a> Parser<'a> {
+    fn top_level(&mut self) -> Result<Pattern> {
+        self.declarations()?;
+        let mut pattern = if self.at_grammar_content()? {
+            let content = self.grammar_content(true)?;
+            Pattern::Grammar(Grammar {
+                ns: None,
+                datatype_library: None,
+                content: Some(content),
+            })
+        } else {
+            let pattern = self.pattern()?;
+            self.expect_end()?;
+            pattern
+        };
+        if self.default_namespace.is_some() {
+            if let Some(ns) = namespace_mut(&mut pattern) {
+                *ns = self.default_namespace.clone();
+            }
+        }
+        Ok(pattern)
+    }
+
+    fn declarations(&mut self) -> Result<()> {
+        loop {
+            let start = self.index;
+            self.skip_annotations()?;
+            let token = self.peek().clone();
+            if token.is_keyword("namespace") {
+                self.advance();
+                let prefix = self.identifier_or_keyword()?;
+                self.expect_symbol("=")?;
+                let uri = self.namespace_uri()?;
+                self.declare_namespace(prefix, uri)?;
+            } else if token.is_keyword("default") {
+                self.advance();
+                self.expect_keyword("namespace")?;
+                let prefix = match self.peek() {
+                    Token::Symbol("=") => None,
+                    _ => Some(self.identifier_or_keyword()?),
+                };
+                self.expect_symbol("=")?;
+                let uri = self.namespace_uri()?;
+                if let Some(prefix) = prefix {
+                    self.declare_namespace(prefix, uri.clone())?;
+                }
+                self.default_namespace = uri;
+            } else if token.is_keyword("datatypes") {
+                self.advance();
+                let prefix = self.identifier_or_keyword()?;
+                self.expect_symbol("=")?;
+                let uri = self.literal()?;
+                if prefix == "xml" {
+                    return Err(self.error_at(start, "`xml` is not a datatypes prefix"));
+                }
+                self.datatypes.insert(prefix, uri);
+            } else {
+                self.index = start;
+                return Ok(());
+            }
+        }
+    }
+
+    fn namespace_uri(&mut self) -> Result<Namespace> {
+        if self.peek().is_keyword("inherit") {
+            self.advance();
+            Ok(None)
+        } else {
+            Ok(Some(self.literal()?))
+        }
+    }
+
+    fn declare_namespace(&mut self, prefix: String, uri: Namespace) -> Result<()> {
+        if prefix == "xml" && uri.as_deref() != Some(XML_NAMESPACE) {
+            return Err(self.error("`xml` is bound to the XML namespace"));
+        }
+        self.namespaces.insert(prefix, uri);
+        Ok(())
+    }
+
+    /// Whether the input is a grammar body rather than a single pattern.
+    fn at_grammar_content(&mut self) -> Result<bool> {
+        let start = self.index;
+        self.skip_annotations()?;
+        let result = match self.peek() {
+            Token::Eof => true,
+            token if token.is_keyword("start") => true,
+            token if token.is_keyword("div") => true,
+            token if token.is_keyword("include") => true,
+            Token::Escaped(_) => true,
+            Token::Name(name) if !KEYWORDS.contains(&name.as_str()) => true,
+            Token::CName(..) => self.peek_at(1).is_symbol("["),
+            _ => false,
+        };
+        self.index = start;
+        Ok(result)
+    }
+
+    /// Reads components up to the closing brace, or the end of the input
+    /// at the top level.
+    fn grammar_content(&mut self, top_level: bool) -> Result<Vec<GrammarContent>> {
+        let mut content = Vec::new();
+        loop {
+            self.skip_annotations()?;
+            let token = self.peek().clone();
+            match &token {
+                Token::Eof if top_level => return Ok(content),
+                Token::Symbol("}") if !top_level => return Ok(content),
+                _ if token.is_keyword("start") => {
+                    self.advance();
+                    let combine = self.assign_method()?;
+                    content.push(GrammarContent::Start(Start {
+                        ns: None,
+                        datatype_library: None,
+                        combine,
+                        content: Some(into_content(self.pattern()?)),
+                    }));
+                }
+                _ if token.is_keyword("div") => {
+                    self.advance();
+                    self.expect_symbol("{")?;
+                    let div = self.grammar_content(false)?;
+                    self.expect_symbol("}")?;
+                    content.push(GrammarContent::Div(Div {
+                        ns: None,
+                        datatype_library: None,
+                        content: Some(div),
+                    }));
+                }
+                _ if token.is_keyword("include") => {
+                    content.push(GrammarContent::Include(self.include()?));
+                }
+                Token::Name(_) | Token::CName(..) if self.peek_at(1).is_symbol("[") => {
+                    self.skip_annotation_element()?;
+                }
+                _ => content.push(GrammarContent::Define(self.define()?)),
+            }
+        }
+    }
+
+    fn include(&mut self) -> Result<Include> {
+        self.expect_keyword("include")?;
+        let href = self.literal()?;
+        let ns = self.inherit()?;
+        let mut content = None;
+        if self.peek().is_symbol("{") {
+            self.advance();
+            let components = self.grammar_content(false)?;
+            self.expect_symbol("}")?;
+            content = Some(
+                components
+                    .into_iter()
+                    .map(|component| match component {
+                        GrammarContent::Define(define) => Ok(IncludeContent::Define(define)),
+                        GrammarContent::Div(div) => Ok(IncludeContent::Div(div)),
+                        GrammarContent::Start(start) => Ok(IncludeContent::Start(start)),
+                        GrammarContent::Include(_) => {
+                            Err(self.error("`include` cannot override with another `include`"))
+                        }
+                    })
+                    .collect::<Result<_>>()?,
+            );
+        }
+        Ok(Include {
+            ns,
+            datatype_library: None,
+            href,
+            content,
+        })
+    }
+
+    /// Reads `inherit = prefix` after `include` and `external`, giving the
+    /// namespace of the prefix, or the default namespace without one.
+    fn inherit(&mut self) -> Result<Namespace> {
+        if !self.peek().is_keyword("inherit") {
+            return Ok(None);
+        }
+        self.advance();
+        self.expect_symbol("=")?;
+        let offset = self.offset();
+        let prefix = self.identifier_or_keyword()?;
+        self.namespace(&prefix, offset)
+    }
+
+    fn define(&mut self) -> Result<Define> {
+        let name = self.identifier()?;
+        let combine = self.assign_method()?;
+        Ok(Define {
+            ns: None,
+            datatype_library: None,
+            combine,
+            name,
+            content: Some(into_content(self.pattern()?)),
+        })
+    }
+
+    fn assign_method(&mut self) -> Result<Option<Combine>> {
+        let combine = match self.peek() {
+            Token::Symbol("=") => None,
+            Token::Symbol("|=") => Some(Combine::Choice),
+            Token::Symbol("&=") => Some(Combine::Interleave),
+            _ => return Err(self.error("expected `=`, `|=` or `&=`")),
+        };
+        self.advance();
+        Ok(combine)
+    }
+
+    /// Reads particles joined by one kind of operator. Mixing operators
+    /// needs parentheses.
+    fn pattern(&mut self) -> Result<Pattern> {
+        let mut operator = None;
+        let mut particles = vec![self.particle()?];
+        loop {
+            let next = match self.peek() {
+                Token::Symbol(",") => Operator::Group,
+                Token::Symbol("|") => Operator::Choice,
+                Token::Symbol("&") => Operator::Interleave,
+                _ => break,
+            };
+            if operator.is_some_and(|operator| operator != next) {
+                return Err(self.error("operators cannot be mixed without parentheses"));
+            }
+            operator = Some(next);
+            self.advance();
+            particles.push(self.particle()?);
+        }
+        let Some(operator) = operator else {
+            return Ok(particles.remove(0));
+        };
+        let content = Some(particles);
+        Ok(match operator {
+            Operator::Group => Pattern::Group(Group {
+                ns: None,
+                datatype_library: None,
+                content,
+            }),
+            Operator::Choice => Pattern::Choice(Choice {
+                ns: None,
+                datatype_library: None,
+                content,
+            }),
+            Operator::Interleave => Pattern::Interleave(Interleave {
+                ns: None,
+                datatype_library: None,
+                content,
+            }),
+        })
+    }
+
+    fn particle(&mut self) -> Result<Pattern> {
+        let primary = self.primary()?;
+        let particle = match self.peek() {
+            Token::Symbol("?") => Pattern::Optional(Optional {
+                ns: None,
+                datatype_library: None,
+                content: Some(into_content(primary)),
+            }),
+            Token::Symbol("*") => Pattern::ZeroOrMore(ZeroOrMore {
+                ns: None,
+                datatype_library: None,
+                content: Some(into_content(primary)),
+            }),
+            Token::Symbol("+") => Pattern::OneOrMore(OneOrMore {
+                ns: None,
+                datatype_library: None,
+                content: Some(into_content(primary)),
+            }),
+            _ => {
+                self.skip_follow_annotations()?;
+                return Ok(primary);
+            }
+        };
+        self.advance();
+        self.skip_follow_annotations()?;
+        Ok(particle)
+    }
+
+    fn primary(&mut self) -> Result<Pattern> {
+        self.skip_annotations()?;
+        let offset = self.offset();
+        let token = self.peek().clone();
+        Ok(match &token {
+            Token::Symbol("(") => {
+                self.advance();
+                let pattern = self.pattern()?;
+                self.expect_symbol(")")?;
+                pattern
+            }
+            Token::Literal(_) => Pattern::Value(Value {
+                ns: None,
+                datatype_library: None,
+                data_type: None,
+                value: self.literal()?,
+            }),
+            Token::Escaped(name) => {
+                self.advance();
+                reference(name.clone())
+            }
+            Token::CName(..) => self.datatype()?,
+            Token::Name(name) => match name.as_str() {
+                "element" => {
+                    self.advance();
+                    let (name, name_class) = self.named(false)?;
+                    let content = self.braced_pattern()?;
+                    Pattern::Element(Element {
+                        ns: None,
+                        datatype_library: None,
+                        name,
+                        name_class,
+                        content: Some(into_content(content)),
+                    })
+                }
+                "attribute" => {
+                    self.advance();
+                    let (name, name_class) = self.named(true)?;
+                    let pattern = self.braced_pattern()?;
+                    Pattern::Attribute(Attribute {
+                        ns: None,
+                        datatype_library: None,
+                        name,
+                        name_class,
+                        pattern: Some(Box::new(pattern)),
+                    })
+                }
+                "list" => {
+                    self.advance();
+                    Pattern::List(List {
+                        ns: None,
+                        datatype_library: None,
+                        content: Some(into_content(self.braced_pattern()?)),
+                    })
+                }
+                "mixed" => {
+                    self.advance();
+                    Pattern::Mixed(Mixed {
+                        ns: None,
+                        datatype_library: None,
+                        content: Some(into_content(self.braced_pattern()?)),
+                    })
+                }
+                "empty" => {
+                    self.advance();
+                    Pattern::Empty
+                }
+                "text" => {
+                    self.advance();
+                    Pattern::Text
+                }
+                "notAllowed" => {
+                    self.advance();
+                    Pattern::NotAllowed
+                }
+                "string" | "token" => self.datatype()?,
+                "parent" => {
+                    self.advance();
+                    Pattern::ParentRef(ParentRef {
+                        ns: None,
+                        datatype_library: None,
+                        name: self.identifier()?,
+                    })
+                }
+                "external" => {
+                    self.advance();
+                    let href = self.literal()?;
+                    Pattern::ExternalRef(ExternalRef {
+                        ns: self.inherit()?,
+                        datatype_library: None,
+                        href,
+                    })
+                }
+                "grammar" => {
+                    self.advance();
+                    self.expect_symbol("{")?;
+                    let content = self.grammar_content(false)?;
+                    self.expect_symbol("}")?;
+                    Pattern::Grammar(Grammar {
+                        ns: None,
+                        datatype_library: None,
+                        content: Some(content),
+                    })
+                }
+                _ if KEYWORDS.contains(&name.as_str()) => {
+                    return Err(self.error_at_offset(offset, &format!("unexpected `{}`", name)))
+                }
+                _ => {
+                    self.advance();
+                    reference(name.clone())
+                }
+            },
+            _ => return Err(self.error("expected a pattern")),
+        })
+    }
+
+    /// Reads a `value` or `data` pattern that starts with a datatype name.
+    fn datatype(&mut self) -> Result<Pattern> {
+        let offset = self.offset();
+        let (datatype_library, data_type) = match self.advance() {
+            Token::CName(prefix, local_name) => match self.datatypes.get(&prefix) {
+                Some(uri) => (uri.clone(), local_name),
+                None => {
+                    return Err(self.error_at_offset(
+                        offset,
+                        &format!("undeclared datatypes prefix `{}`", prefix),
+                    ))
+                }
+            },
+            Token::Name(name) => (String::new(), name),
+            _ => return Err(self.error_at_offset(offset, "expected a datatype name")),
+        };
+        if let Token::Literal(_) = self.peek() {
+            return Ok(Pattern::Value(Value {
+                ns: None,
+                datatype_library: Some(datatype_library),
+                data_type: Some(data_type),
+                value: self.literal()?,
+            }));
+        }
+
+        let mut params = Vec::new();
+        if self.peek().is_symbol("{") {
+            self.advance();
+            loop {
+                self.skip_annotations()?;
+                if self.peek().is_symbol("}") {
+                    self.advance();
+                    break;
+                }
+                let name = self.identifier_or_keyword()?;
+                self.expect_symbol("=")?;
+                params.push(Param {
+                    ns: None,
+                    datatype_library: None,
+                    name,
+                    value: self.literal()?,
+                });
+            }
+        }
+        let except = if self.peek().is_symbol("-") {
+            self.advance();
+            Some(ExceptPattern {
+                ns: None,
+                datatype_library: None,
+                content: Some(vec![self.primary()?]),
+            })
+        } else {
+            None
+        };
+        Ok(Pattern::Data(Data {
+            ns: None,
+            datatype_library: Some(datatype_library),
+            data_type,
+            params,
+            except,
+        }))
+    }
+
+    fn braced_pattern(&mut self) -> Result<Pattern> {
+        self.expect_symbol("{")?;
+        let pattern = self.pattern()?;
+        self.expect_symbol("}")?;
+        Ok(pattern)
+    }
+
+    /// Reads the name class of an `element` or `attribute`, giving an
+    /// unprefixed name on its own as the `name` attribute would.
+    fn named(&mut self, attribute: bool) -> Result<(Option<String>, Option<NameClass>)> {
+        self.skip_annotations()?;
+        if let Token::Name(name) | Token::Escaped(name) = self.peek().clone() {
+            if !self.peek_at(1).is_symbol("|") {
+                self.advance();
+                self.skip_follow_annotations()?;
+                return Ok((Some(name), None));
+            }
+        }
+        Ok((None, Some(self.name_class(attribute)?)))
+    }
+
+    fn name_class(&mut self, attribute: bool) -> Result<NameClass> {
+        let mut name_classes = vec![self.primary_name_class(attribute)?];
+        while self.peek().is_symbol("|") {
+            self.advance();
+            name_classes.push(self.primary_name_class(attribute)?);
+        }
+        Ok(match name_classes.len() {
+            1 => name_classes.remove(0),
+            _ => NameClass::Choice(NameClassChoice {
+                ns: None,
+                datatype_library: None,
+                content: name_classes,
+            }),
+        })
+    }
+
+    fn primary_name_class(&mut self, attribute: bool) -> Result<NameClass> {
+        self.skip_annotations()?;
+        let offset = self.offset();
+        let name_class = match self.advance() {
+            Token::Symbol("(") => {
+                let name_class = self.name_class(attribute)?;
+                self.expect_symbol(")")?;
+                name_class
+            }
+            Token::Symbol("*") => NameClass::AnyName(AnyName {
+                ns: None,
+                datatype_library: None,
+                except: self.except_name_class(attribute)?,
+            }),
+            Token::NsName(prefix) => NameClass::NsName(NsName {
+                ns: self.namespace(&prefix, offset)?,
+                datatype_library: None,
+                except: self.except_name_class(attribute)?,
+            }),
+            Token::CName(prefix, local_name) => NameClass::Name(Name {
+                ns: self.namespace(&prefix, offset)?,
+                datatype_library: None,
+                name: local_name,
+            }),
+            Token::Name(name) | Token::Escaped(name) => NameClass::Name(Name {
+                // Unprefixed attribute names are in no namespace.
+                ns: attribute.then(String::new),
+                datatype_library: None,
+                name,
+            }),
+            _ => return Err(self.error_at_offset(offset, "expected a name class")),
+        };
+        self.skip_follow_annotations()?;
+        Ok(name_class)
+    }
+
+    fn except_name_class(&mut self, attribute: bool) -> Result<Option<ExceptNameClass>> {
+        if !self.peek().is_symbol("-") {
+            return Ok(None);
+        }
+        self.advance();
+        Ok(Some(ExceptNameClass {
+            ns: None,
+            datatype_library: None,
+            content: vec![self.primary_name_class(attribute)?],
+        }))
+    }
+
+    /// The namespace of a prefix used in a name. A prefix that inherits
+    /// its namespace leaves `ns` unset, which only inherits the right one
+    /// when no default namespace is declared.
+    fn namespace(&self, prefix: &str, offset: usize) -> Result<Namespace> {
+        match self.namespaces.get(prefix) {
+            Some(Some(uri)) => Ok(Some(uri.clone())),
+            Some(None) if self.default_namespace.is_none() => Ok(None),
+            Some(None) => Err(self.error_at_offset(
+                offset,
+                &format!(
+                    "`{}` inherits its namespace besides a default namespace",
+                    prefix
+                ),
+            )),
+            None => Err(self.error_at_offset(offset, &format!("undeclared prefix `{}`", prefix))),
+        }
+    }
+
+    /// Skips annotations in brackets before a component, pattern or name.
+    fn skip_annotations(&mut self) -> Result<()> {
+        while self.peek().is_symbol("[") {
+            self.skip_brackets()?;
+        }
+        Ok(())
+    }
+
+    /// Skips `>> name [ ... ]` annotations after a pattern or name.
+    fn skip_follow_annotations(&mut self) -> Result<()> {
+        while self.peek().is_symbol(">>") {
+            self.advance();
+            self.skip_annotation_element()?;
+        }
+        Ok(())
+    }
+
+    fn skip_annotation_element(&mut self) -> Result<()> {
+        match self.advance() {
+            Token::Name(_) | Token::Escaped(_) | Token::CName(..) => self.skip_brackets(),
+            _ => Err(self.error_at(self.index - 1, "expected an annotation element")),
+        }
+    }
+
+    fn skip_brackets(&mut self) -> Result<()> {
+        self.expect_symbol("[")?;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.advance() {
+                Token::Symbol("[") => depth += 1,
+                Token::Symbol("]") => depth -= 1,
+                Token::Eof => return Err(self.error_at(self.index - 1, "unclosed annotation")),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a literal, joining the pieces of `"a" ~ "b"`.
+    fn literal(&mut self) -> Result<String> {
+        let Token::Literal(mut value) = self.peek().clone() else {
+            return Err(self.error("expected a literal"));
+        };
+        self.advance();
+        while self.peek().is_symbol("~") {
+            self.advance();
+            let Token::Literal(piece) = self.peek().clone() else {
+                return Err(self.error("expected a literal after `~`"));
+            };
+            self.advance();
+            value.push_str(&piece);
+        }
+        Ok(value)
+    }
+
+    /// Reads the name of a definition, which may not be a keyword unless
+    /// escaped.
+    fn identifier(&mut self) -> Result<String> {
+        match self.peek().clone() {
+            Token::Name(name) if !KEYWORDS.contains(&name.as_str()) => {
+                self.advance();
+                Ok(name)
+            }
+            Token::Escaped(name) => {
+                self.advance();
+                Ok(name)
+            }
+            _ => Err(self.error("expected an identifier")),
+        }
+    }
+
+    fn identifier_or_keyword(&mut self) -> Result<String> {
+        match self.peek().clone() {
+            Token::Name(name) | Token::Escaped(name) => {
+                self.advance();
+                Ok(name)
+            }
+            _ => Err(self.error("expected an identifier")),
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<()> {
+        if !self.peek().is_symbol(symbol) {
+            return Err(self.error(&format!("expected `{}`", symbol)));
+        }
+        self.advance();
+        Ok(())
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        if !self.peek().is_keyword(keyword) {
+            return Err(self.error(&format!("expected `{}`", keyword)));
+        }
+        self.advance();
+        Ok(())
+    }
+
+    fn expect_end(&mut self) -> Result<()> {
+        match self.peek() {
+            Token::Eof => Ok(()),
+            _ => Err(self.error("expected the end of the input")),
+        }
+    }
+
+    fn peek(&self) -> &Token {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, ahead: usize) -> &Token {
+        let index = (self.index + ahead).min(self.tokens.len() - 1);
+        &self.tokens[index].0
+    }
+
+    /// Moves past the current token, staying on the end of the input.
+    fn advance(&mut self) -> Token {
+        let token = self.peek().clone();
+        if self.index < self.tokens.len() - 1 {
+            self.index += 1;
+        }
+        token
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens[self.index].1
+    }
+
+    fn error(&self, message: &str) -> Error {
+        self.error_at(self.index, message)
+    }
+
+    fn error_at(&self, index: usize, message: &str) -> Error {
+        self.error_at_offset(self.tokens[index].1, message)
+    }
+
+    fn error_at_offset(&self, offset: usize, message: &str) -> Error {
+        syntax_error(self.input, offset, message)
+    }
+}
+
+fn reference(name: String) -> Pattern {
+    Pattern::Ref(Ref {
+        ns: None,
+        datatype_library: None,
+        name,
+    })
+}
+
+/// The children a pattern stands for inside `element`, `define` and the
+/// like, where a group is implied.
+fn into_content(pattern: Pattern) -> Vec<Pattern> {
+    match pattern {
+        Pattern::Group(Group {
+            ns: None,
+            datatype_library: None,
+            content,
+        }) => content.unwrap_or_default(),
+        pattern => vec![pattern],
+    }
+}
+
+/// The `ns` attribute of a pattern, for patterns that have one.
+fn namespace_mut(pattern: &mut Pattern) -> Option<&mut Namespace> {
+    Some(match pattern {
+        Pattern::Attribute(p) => &mut p.ns,
+        Pattern::Choice(p) => &mut p.ns,
+        Pattern::Data(p) => &mut p.ns,
+        Pattern::Element(p) => &mut p.ns,
+        Pattern::ExternalRef(p) => &mut p.ns,
+        Pattern::Grammar(p) => &mut p.ns,
+        Pattern::Group(p) => &mut p.ns,
+        Pattern::List(p) => &mut p.ns,
+        Pattern::Interleave(p) => &mut p.ns,
+        Pattern::Mixed(p) => &mut p.ns,
+        Pattern::OneOrMore(p) => &mut p.ns,
+        Pattern::Optional(p) => &mut p.ns,
+        Pattern::ParentRef(p) => &mut p.ns,
+        Pattern::Ref(p) => &mut p.ns,
+        Pattern::Value(p) => &mut p.ns,
+        Pattern::ZeroOrMore(p) => &mut p.ns,
+        Pattern::Empty | Pattern::NotAllowed | Pattern::Text => return None,
+    })
+}
@@ -0,0 +1,560 @@
+use document::namespace::XML_NAMESPACE;
+
+use crate::compact::lexer::KEYWORDS;
+use crate::compact::XSD_DATATYPES;
+use crate::data::Data;
+use crate::define::Define;
+use crate::grammar::GrammarContent;
+use crate::include::IncludeContent;
+use crate::name_class::{ExceptNameClass, NameClass};
+use crate::pattern::Pattern;
+use crate::start::Start;
+use crate::{Combine, DatatypeLibrary, Namespace};
+
+/// Lines longer than this are broken at the operators and braces.
+const WIDTH: usize = 72;
+
+/// Writes a pattern in the compact syntax. The `ns` of the outermost
+/// pattern becomes the default namespace; other namespaces and datatype
+/// libraries get prefixes as they are met, and names are written with
+/// them wherever they are not in the default namespace.
+pub(crate) fn write(pattern: &Pattern) -> String {
+    let default_namespace = namespace(pattern).unwrap_or_default();
+    let mut writer = Writer {
+        default_namespace: default_namespace.clone(),
+        namespaces: Vec::new(),
+        datatypes: Vec::new(),
+    };
+    let env = Env {
+        ns: default_namespace,
+        datatype_library: String::new(),
+    };
+    let body = match pattern {
+        Pattern::Grammar(grammar) => {
+            let env = env.with(&grammar.ns, &grammar.datatype_library);
+            writer.components(grammar.content.as_deref().unwrap_or_default(), &env)
+        }
+        pattern => writer.pattern(pattern, &env).0,
+    };
+
+    let mut output = String::new();
+    for (prefix, uri) in &writer.namespaces {
+        if !uri.is_empty() && *uri == writer.default_namespace {
+            output.insert_str(
+                0,
+                &format!("default namespace {} = {}\n", prefix, literal(uri)),
+            );
+        } else {
+            output.push_str(&format!("namespace {} = {}\n", prefix, literal(uri)));
+        }
+    }
+    if !writer.default_namespace.is_empty() && !output.starts_with("default") {
+        output.insert_str(
+            0,
+            &format!(
+                "default namespace = {}\n",
+                literal(&writer.default_namespace)
+            ),
+        );
+    }
+    for (prefix, uri) in &writer.datatypes {
+        output.push_str(&format!("datatypes {} = {}\n", prefix, literal(uri)));
+    }
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    output.push_str(&body);
+    output.push('\n');
+    output
+}
+
+struct Writer {
+    default_namespace: String,
+    /// The prefixes given to namespaces, besides `xml`.
+    namespaces: Vec<(String, String)>,
+    /// The prefixes given to datatype libraries, besides `xsd`.
+    datatypes: Vec<(String, String)>,
+}
+
+/// The namespace and datatype library a pattern inherits.
+#[derive(Clone)]
+struct Env {
+    ns: String,
+    datatype_library: String,
+}
+
+impl Env {
+    fn with(&self, ns: &Namespace, datatype_library: &DatatypeLibrary) -> Env {
+        Env {
+            ns: ns.clone().unwrap_or_else(|| self.ns.clone()),
+            datatype_library: match datatype_library {
+                Some(library) => library.trim().to_owned(),
+                None => self.datatype_library.clone(),
+            },
+        }
+    }
+}
+
+/// How tightly a written pattern binds, to know where it needs
+/// parentheses.
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Primary,
+    /// A pattern with a postfix operator, or a `data` with an except.
+    Postfix,
+    /// Patterns joined by `,`, `|` or `&`.
+    Compound,
+}
+
+impl Writer {
+    fn components(&mut self, content: &[GrammarContent], env: &Env) -> String {
+        content
+            .iter()
+            .map(|component| self.component(component, env))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    fn component(&mut self, component: &GrammarContent, env: &Env) -> String {
+        match component {
+            GrammarContent::Start(start) => self.start(start, env),
+            GrammarContent::Define(define) => self.define(define, env),
+            GrammarContent::Div(div) => {
+                let env = env.with(&div.ns, &div.datatype_library);
+                let content = self.components(div.content.as_deref().unwrap_or_default(), &env);
+                block("div", &content)
+            }
+            GrammarContent::Include(include) => {
+                let env = env.with(&include.ns, &include.datatype_library);
+                let head = format!("include {}{}", literal(&include.href), self.inherit(&env));
+                let Some(content) = &include.content else {
+                    return head;
+                };
+                let content = content
+                    .iter()
+                    .map(|component| match component {
+                        IncludeContent::Start(start) => self.start(start, &env),
+                        IncludeContent::Define(define) => self.define(define, &env),
+                        IncludeContent::Div(div) => {
+                            let env = env.with(&div.ns, &div.datatype_library);
+                            let content = div.content.as_deref().unwrap_or_default();
+                            block("div", &self.components(content, &env))
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                block(&head, &content)
+            }
+        }
+    }
+
+    fn start(&mut self, start: &Start, env: &Env) -> String {
+        let env = env.with(&start.ns, &start.datatype_library);
+        let body = self.body(start.content.as_deref().unwrap_or_default(), &env);
+        assignment("start", start.combine, body)
+    }
+
+    fn define(&mut self, define: &Define, env: &Env) -> String {
+        let env = env.with(&define.ns, &define.datatype_library);
+        let body = self.body(define.content.as_deref().unwrap_or_default(), &env);
+        assignment(&identifier(&define.name), define.combine, body)
+    }
+
+    /// The children of `element`, `define` and the like, which form an
+    /// implicit group.
+    fn body(&mut self, patterns: &[Pattern], env: &Env) -> (String, Kind) {
+        match patterns {
+            [] => ("empty".to_owned(), Kind::Primary),
+            [pattern] => self.pattern(pattern, env),
+            patterns => self.compound(patterns, env, ","),
+        }
+    }
+
+    fn compound(&mut self, patterns: &[Pattern], env: &Env, operator: &str) -> (String, Kind) {
+        match patterns {
+            [] if operator == "|" => ("notAllowed".to_owned(), Kind::Primary),
+            [] => ("empty".to_owned(), Kind::Primary),
+            [pattern] => self.pattern(pattern, env),
+            patterns => {
+                let operands = patterns
+                    .iter()
+                    .map(|pattern| match self.pattern(pattern, env) {
+                        (text, Kind::Compound) => parenthesize(&text),
+                        (text, _) => text,
+                    })
+                    .collect::<Vec<_>>();
+                (join(&operands, operator), Kind::Compound)
+            }
+        }
+    }
+
+    fn pattern(&mut self, pattern: &Pattern, env: &Env) -> (String, Kind) {
+        let text = match pattern {
+            Pattern::Element(element) => {
+                let env = env.with(&element.ns, &element.datatype_library);
+                let name = match (&element.name, &element.name_class) {
+                    (Some(name), _) => self.element_name(&env.ns, name.trim()),
+                    (None, Some(name_class)) => self.name_class(name_class, &env, false).0,
+                    (None, None) => "*".to_owned(),
+                };
+                let (body, _) = self.body(element.content.as_deref().unwrap_or_default(), &env);
+                braced(&format!("element {}", name), &body)
+            }
+            Pattern::Attribute(attribute) => {
+                let env = env.with(&attribute.ns, &attribute.datatype_library);
+                let name = match (&attribute.name, &attribute.name_class) {
+                    // A plain attribute name is never in the inherited namespace.
+                    (Some(name), _) => self
+                        .attribute_name(attribute.ns.as_deref().unwrap_or_default(), name.trim()),
+                    (None, Some(name_class)) => self.name_class(name_class, &env, true).0,
+                    (None, None) => "*".to_owned(),
+                };
+                let body = match &attribute.pattern {
+                    Some(pattern) => self.pattern(pattern, &env).0,
+                    None => "text".to_owned(),
+                };
+                braced(&format!("attribute {}", name), &body)
+            }
+            Pattern::Choice(choice) => {
+                let env = env.with(&choice.ns, &choice.datatype_library);
+                return self.compound(choice.content.as_deref().unwrap_or_default(), &env, "|");
+            }
+            Pattern::Group(group) => {
+                let env = env.with(&group.ns, &group.datatype_library);
+                return self.compound(group.content.as_deref().unwrap_or_default(), &env, ",");
+            }
+            Pattern::Interleave(interleave) => {
+                let env = env.with(&interleave.ns, &interleave.datatype_library);
+                let content = interleave.content.as_deref().unwrap_or_default();
+                return self.compound(content, &env, "&");
+            }
+            Pattern::Optional(optional) => {
+                let env = env.with(&optional.ns, &optional.datatype_library);
+                let body = self.body(optional.content.as_deref().unwrap_or_default(), &env);
+                return (postfix(body, "?"), Kind::Postfix);
+            }
+            Pattern::ZeroOrMore(zero_or_more) => {
+                let env = env.with(&zero_or_more.ns, &zero_or_more.datatype_library);
+                let body = self.body(zero_or_more.content.as_deref().unwrap_or_default(), &env);
+                return (postfix(body, "*"), Kind::Postfix);
+            }
+            Pattern::OneOrMore(one_or_more) => {
+                let env = env.with(&one_or_more.ns, &one_or_more.datatype_library);
+                let body = self.body(one_or_more.content.as_deref().unwrap_or_default(), &env);
+                return (postfix(body, "+"), Kind::Postfix);
+            }
+            Pattern::List(list) => {
+                let env = env.with(&list.ns, &list.datatype_library);
+                let (body, _) = self.body(list.content.as_deref().unwrap_or_default(), &env);
+                braced("list", &body)
+            }
+            Pattern::Mixed(mixed) => {
+                let env = env.with(&mixed.ns, &mixed.datatype_library);
+                let (body, _) = self.body(mixed.content.as_deref().unwrap_or_default(), &env);
+                braced("mixed", &body)
+            }
+            Pattern::Empty => "empty".to_owned(),
+            Pattern::NotAllowed => "notAllowed".to_owned(),
+            Pattern::Text => "text".to_owned(),
+            Pattern::Ref(r) => identifier(&r.name),
+            Pattern::ParentRef(r) => format!("parent {}", identifier(&r.name)),
+            Pattern::Value(value) => {
+                let env = env.with(&value.ns, &value.datatype_library);
+                match &value.data_type {
+                    Some(data_type) => format!(
+                        "{} {}",
+                        self.datatype_name(&env.datatype_library, data_type.trim()),
+                        literal(&value.value)
+                    ),
+                    None => literal(&value.value),
+                }
+            }
+            Pattern::Data(data) => return self.data(data, env),
+            Pattern::ExternalRef(external_ref) => {
+                let env = env.with(&external_ref.ns, &external_ref.datatype_library);
+                format!(
+                    "external {}{}",
+                    literal(&external_ref.href),
+                    self.inherit(&env)
+                )
+            }
+            Pattern::Grammar(grammar) => {
+                let env = env.with(&grammar.ns, &grammar.datatype_library);
+                let content = self.components(grammar.content.as_deref().unwrap_or_default(), &env);
+                block("grammar", &content)
+            }
+        };
+        (text, Kind::Primary)
+    }
+
+    fn data(&mut self, data: &Data, env: &Env) -> (String, Kind) {
+        let env = env.with(&data.ns, &data.datatype_library);
+        let mut text = self.datatype_name(&env.datatype_library, data.data_type.trim());
+        if !data.params.is_empty() {
+            let params = data
+                .params
+                .iter()
+                .map(|param| {
+                    format!(
+                        "{} = {}",
+                        identifier(param.name.trim()),
+                        literal(&param.value)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            text = format!("{} {{ {} }}", text, params);
+        }
+        let Some(except) = &data.except else {
+            return (text, Kind::Primary);
+        };
+        let env = env.with(&except.ns, &except.datatype_library);
+        let except = match self.compound(except.content.as_deref().unwrap_or_default(), &env, "|") {
+            (text, Kind::Primary) => text,
+            (text, _) => parenthesize(&text),
+        };
+        (format!("{} - {}", text, except), Kind::Postfix)
+    }
+
+    fn name_class(&mut self, name_class: &NameClass, env: &Env, attribute: bool) -> (String, Kind) {
+        match name_class {
+            NameClass::Name(name) => {
+                let env = env.with(&name.ns, &name.datatype_library);
+                let text = if attribute {
+                    self.attribute_name(&env.ns, name.name.trim())
+                } else {
+                    self.element_name(&env.ns, name.name.trim())
+                };
+                (text, Kind::Primary)
+            }
+            NameClass::AnyName(any_name) => {
+                let env = env.with(&any_name.ns, &any_name.datatype_library);
+                self.wildcard("*".to_owned(), &any_name.except, &env, attribute)
+            }
+            NameClass::NsName(ns_name) => {
+                let env = env.with(&ns_name.ns, &ns_name.datatype_library);
+                let wildcard = format!("{}:*", self.prefix(&env.ns));
+                self.wildcard(wildcard, &ns_name.except, &env, attribute)
+            }
+            NameClass::Choice(choice) => {
+                let env = env.with(&choice.ns, &choice.datatype_library);
+                let operands = choice
+                    .content
+                    .iter()
+                    .map(|name_class| self.name_class(name_class, &env, attribute).0)
+                    .collect::<Vec<_>>();
+                match operands.len() {
+                    1 => (operands.join(""), Kind::Primary),
+                    _ => (operands.join(" | "), Kind::Compound),
+                }
+            }
+        }
+    }
+
+    fn wildcard(
+        &mut self,
+        wildcard: String,
+        except: &Option<ExceptNameClass>,
+        env: &Env,
+        attribute: bool,
+    ) -> (String, Kind) {
+        let Some(except) = except else {
+            return (wildcard, Kind::Primary);
+        };
+        let env = env.with(&except.ns, &except.datatype_library);
+        let operands = except
+            .content
+            .iter()
+            .map(|name_class| self.name_class(name_class, &env, attribute))
+            .collect::<Vec<_>>();
+        let except = match operands.as_slice() {
+            [(operand, Kind::Primary)] => operand.clone(),
+            operands => parenthesize(
+                &operands
+                    .iter()
+                    .map(|(operand, _)| operand.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+            ),
+        };
+        (format!("{} - {}", wildcard, except), Kind::Postfix)
+    }
+
+    /// Writes `inherit = prefix` for an `include` or `external` that does
+    /// not pass on the default namespace.
+    fn inherit(&mut self, env: &Env) -> String {
+        if env.ns == self.default_namespace {
+            return String::new();
+        }
+        format!(" inherit = {}", self.prefix(&env.ns))
+    }
+
+    /// Element names are unprefixed in the default namespace.
+    fn element_name(&mut self, ns: &str, local_name: &str) -> String {
+        if ns == self.default_namespace {
+            local_name.to_owned()
+        } else {
+            format!("{}:{}", self.prefix(ns), local_name)
+        }
+    }
+
+    /// Attribute names are unprefixed in no namespace.
+    fn attribute_name(&mut self, ns: &str, local_name: &str) -> String {
+        if ns.is_empty() {
+            local_name.to_owned()
+        } else {
+            format!("{}:{}", self.prefix(ns), local_name)
+        }
+    }
+
+    fn prefix(&mut self, uri: &str) -> String {
+        if uri == XML_NAMESPACE {
+            return "xml".to_owned();
+        }
+        if let Some((prefix, _)) = self.namespaces.iter().find(|(_, bound)| bound == uri) {
+            return prefix.clone();
+        }
+        let prefix = match uri {
+            "" => "local".to_owned(),
+            _ => format!("ns{}", self.namespaces.len() + 1),
+        };
+        self.namespaces.push((prefix.clone(), uri.to_owned()));
+        prefix
+    }
+
+    fn datatype_name(&mut self, library: &str, data_type: &str) -> String {
+        if library.is_empty() && (data_type == "string" || data_type == "token") {
+            return data_type.to_owned();
+        }
+        if library == XSD_DATATYPES {
+            return format!("xsd:{}", data_type);
+        }
+        let prefix = match self.datatypes.iter().find(|(_, bound)| bound == library) {
+            Some((prefix, _)) => prefix.clone(),
+            None => {
+                let prefix = format!("dt{}", self.datatypes.len() + 1);
+                self.datatypes.push((prefix.clone(), library.to_owned()));
+                prefix
+            }
+        };
+        format!("{}:{}", prefix, data_type)
+    }
+}
+
+/// The `ns` of the outermost pattern, which the compact syntax spells as
+/// the default namespace.
+fn namespace(pattern: &Pattern) -> Namespace {
+    match pattern {
+        Pattern::Attribute(p) => p.ns.clone(),
+        Pattern::Choice(p) => p.ns.clone(),
+        Pattern::Data(p) => p.ns.clone(),
+        Pattern::Element(p) => p.ns.clone(),
+        Pattern::ExternalRef(p) => p.ns.clone(),
+        Pattern::Grammar(p) => p.ns.clone(),
+        Pattern::Group(p) => p.ns.clone(),
+        Pattern::List(p) => p.ns.clone(),
+        Pattern::Interleave(p) => p.ns.clone(),
+        Pattern::Mixed(p) => p.ns.clone(),
+        Pattern::OneOrMore(p) => p.ns.clone(),
+        Pattern::Optional(p) => p.ns.clone(),
+        Pattern::ParentRef(p) => p.ns.clone(),
+        Pattern::Ref(p) => p.ns.clone(),
+        Pattern::Value(p) => p.ns.clone(),
+        Pattern::ZeroOrMore(p) => p.ns.clone(),
+        Pattern::Empty | Pattern::NotAllowed | Pattern::Text => None,
+    }
+}
+
+fn assignment(name: &str, combine: Option<Combine>, (body, kind): (String, Kind)) -> String {
+    let operator = match combine {
+        None => "=",
+        Some(Combine::Choice) => "|=",
+        Some(Combine::Interleave) => "&=",
+    };
+    if kind == Kind::Compound && body.contains('\n') {
+        format!("{} {}\n{}", name, operator, indent(&body))
+    } else {
+        format!("{} {} {}", name, operator, body)
+    }
+}
+
+fn postfix((body, kind): (String, Kind), operator: &str) -> String {
+    match kind {
+        Kind::Primary => format!("{}{}", body, operator),
+        _ => format!("{}{}", parenthesize(&body), operator),
+    }
+}
+
+fn join(operands: &[String], operator: &str) -> String {
+    let width = operands
+        .iter()
+        .map(|operand| operand.len() + 3)
+        .sum::<usize>();
+    let multiline = width > WIDTH || operands.iter().any(|operand| operand.contains('\n'));
+    let separator = match (operator, multiline) {
+        (",", true) => ",\n".to_owned(),
+        (",", false) => ", ".to_owned(),
+        (_, true) => format!(" {}\n", operator),
+        (_, false) => format!(" {} ", operator),
+    };
+    operands.join(&separator)
+}
+
+fn parenthesize(text: &str) -> String {
+    format!("({})", text.replace('\n', "\n "))
+}
+
+/// Writes `head { body }`, on several lines if the body is long.
+fn braced(head: &str, body: &str) -> String {
+    if body.contains('\n') || head.len() + body.len() + 5 > WIDTH {
+        block(head, body)
+    } else {
+        format!("{} {{ {} }}", head, body)
+    }
+}
+
+fn block(head: &str, body: &str) -> String {
+    if body.is_empty() {
+        format!("{} {{ }}", head)
+    } else {
+        format!("{} {{\n{}\n}}", head, indent(body))
+    }
+}
+
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| match line {
+            "" => String::new(),
+            line => format!("  {}", line),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Escapes keywords used as the names of definitions.
+fn identifier(name: &str) -> String {
+    let name = name.trim();
+    if KEYWORDS.contains(&name) {
+        format!("\\{}", name)
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Quotes a literal. A literal cannot hold its own delimiter, so double
+/// quotes are written as `'"'` pieces joined with `~`, and pieces with line
+/// breaks are tripled-quoted.
+fn literal(value: &str) -> String {
+    value
+        .split('"')
+        .map(|piece| {
+            let piece = piece.replace('\\', "\\x{5C}");
+            if piece.contains(['\n', '\r']) {
+                format!("\"\"\"{}\"\"\"", piece)
+            } else {
+                format!("\"{}\"", piece)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ~ '\"' ~ ")
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use crate::pattern::Pattern;
+use crate::{DatatypeLibrary, NCName, Namespace};
+
+/// A `data` pattern: a value of a datatype, restricted by parameters.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Data {
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
+    pub ns: Namespace,
+
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
+    pub datatype_library: DatatypeLibrary,
+
+    #[serde(rename = "@type")]
+    pub data_type: NCName,
+
+    #[serde(rename = "param", default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<Param>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub except: Option<ExceptPattern>,
+}
+
+/// A datatype parameter of a `data` pattern.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Param {
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
+    pub ns: Namespace,
+
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
+    pub datatype_library: DatatypeLibrary,
+
+    #[serde(rename = "@name")]
+    pub name: NCName,
+
+    #[serde(rename = "$text", default)]
+    pub value: String,
+}
+
+/// The values removed from a `data` pattern.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ExceptPattern {
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
+    pub ns: Namespace,
+
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
+    pub datatype_library: DatatypeLibrary,
+
+    #[serde(rename = "$value", skip_serializing_if = "Option::is_none")]
+    pub content: Option<Vec<Pattern>>,
+}
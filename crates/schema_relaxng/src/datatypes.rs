@@ -0,0 +1,203 @@
+use document::chars::is_whitespace;
+use document::node::{Document, NodeId};
+use xsd_datatypes::{SimpleType, DATATYPE_LIBRARY};
+
+/// The datatypes `data` and `value` patterns refer to, looked up by the
+/// URI of their library and their name.
+pub trait Datatypes {
+    /// Checks that `library` has `data_type` and that it takes `params`,
+    /// describing the problem otherwise.
+    fn check(
+        &self,
+        library: &str,
+        data_type: &str,
+        params: &[(String, String)],
+    ) -> std::result::Result<(), String>;
+
+    /// Whether `value` is a value of the datatype, restricted by `params`.
+    fn allows(
+        &self,
+        library: &str,
+        data_type: &str,
+        params: &[(String, String)],
+        value: &str,
+        context: &Context,
+    ) -> bool;
+
+    /// Whether `value` stands for the same value as `pattern_value`, which
+    /// a `value` pattern gives with `pattern_ns` as default namespace.
+    fn equal(
+        &self,
+        library: &str,
+        data_type: &str,
+        pattern_value: &str,
+        pattern_ns: &str,
+        value: &str,
+        context: &Context,
+    ) -> bool;
+}
+
+/// Where a value appears in the instance, for datatypes whose values
+/// depend on the namespaces in scope.
+#[derive(Debug, Clone, Copy)]
+pub struct Context<'a> {
+    document: &'a Document,
+    element: NodeId,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(document: &'a Document, element: NodeId) -> Context<'a> {
+        Context { document, element }
+    }
+
+    /// Resolves `prefix`, or the default namespace for `None`.
+    pub fn namespace_uri(&self, prefix: Option<&str>) -> Option<String> {
+        self.document.lookup_namespace_uri(self.element, prefix)
+    }
+}
+
+/// The library every grammar has, with the empty URI: `string`, compared
+/// as is, and `token`, compared after collapsing whitespace.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BuiltinDatatypes;
+
+impl Datatypes for BuiltinDatatypes {
+    fn check(
+        &self,
+        library: &str,
+        data_type: &str,
+        params: &[(String, String)],
+    ) -> std::result::Result<(), String> {
+        if !library.is_empty() {
+            return Err(format!("unknown datatype library `{}`", library));
+        }
+        if data_type != "string" && data_type != "token" {
+            return Err(format!("unknown datatype `{}`", data_type));
+        }
+        match params.first() {
+            Some((name, _)) => Err(format!("`{}` takes no parameter `{}`", data_type, name)),
+            None => Ok(()),
+        }
+    }
+
+    fn allows(
+        &self,
+        _library: &str,
+        _data_type: &str,
+        _params: &[(String, String)],
+        _value: &str,
+        _context: &Context,
+    ) -> bool {
+        true
+    }
+
+    fn equal(
+        &self,
+        _library: &str,
+        data_type: &str,
+        pattern_value: &str,
+        _pattern_ns: &str,
+        value: &str,
+        _context: &Context,
+    ) -> bool {
+        match data_type {
+            "token" => tokens(pattern_value).eq(tokens(value)),
+            _ => pattern_value == value,
+        }
+    }
+}
+
+/// The built-in library together with the XML Schema datatypes, under
+/// their standard library URI. Parameters are the facets of XML Schema
+/// other than `enumeration` and `whiteSpace`, which grammars express with
+/// `value` and `choice` instead.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XsdDatatypes;
+
+impl XsdDatatypes {
+    fn simple_type(
+        &self,
+        data_type: &str,
+        params: &[(String, String)],
+    ) -> std::result::Result<SimpleType, String> {
+        if let Some((name, _)) = params
+            .iter()
+            .find(|(name, _)| name == "enumeration" || name == "whiteSpace")
+        {
+            return Err(format!("`{}` takes no parameter `{}`", data_type, name));
+        }
+        SimpleType::from_name(data_type)
+            .and_then(|simple_type| simple_type.restrict(params, &|_: Option<&str>| None))
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl Datatypes for XsdDatatypes {
+    fn check(
+        &self,
+        library: &str,
+        data_type: &str,
+        params: &[(String, String)],
+    ) -> std::result::Result<(), String> {
+        match library {
+            DATATYPE_LIBRARY => self.simple_type(data_type, params).map(|_| ()),
+            _ => BuiltinDatatypes.check(library, data_type, params),
+        }
+    }
+
+    fn allows(
+        &self,
+        library: &str,
+        data_type: &str,
+        params: &[(String, String)],
+        value: &str,
+        context: &Context,
+    ) -> bool {
+        if library != DATATYPE_LIBRARY {
+            return BuiltinDatatypes.allows(library, data_type, params, value, context);
+        }
+        let namespaces = |prefix: Option<&str>| context.namespace_uri(prefix);
+        self.simple_type(data_type, params)
+            .is_ok_and(|simple_type| simple_type.parse(value, &namespaces).is_ok())
+    }
+
+    fn equal(
+        &self,
+        library: &str,
+        data_type: &str,
+        pattern_value: &str,
+        pattern_ns: &str,
+        value: &str,
+        context: &Context,
+    ) -> bool {
+        if library != DATATYPE_LIBRARY {
+            return BuiltinDatatypes.equal(
+                library,
+                data_type,
+                pattern_value,
+                pattern_ns,
+                value,
+                context,
+            );
+        }
+        let Ok(simple_type) = SimpleType::from_name(data_type) else {
+            return false;
+        };
+        // The grammar only keeps the default namespace of a `value`.
+        let pattern_namespaces =
+            |prefix: Option<&str>| prefix.is_none().then(|| pattern_ns.to_owned());
+        let namespaces = |prefix: Option<&str>| context.namespace_uri(prefix);
+        match (
+            simple_type.parse(pattern_value, &pattern_namespaces),
+            simple_type.parse(value, &namespaces),
+        ) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Splits a value at XML whitespace.
+pub(crate) fn tokens(value: &str) -> impl Iterator<Item = &str> {
+    value.split(is_whitespace).filter(|token| !token.is_empty())
+}
@@ -2,20 +2,20 @@ use crate::pattern::Pattern;
 use crate::{Combine, DatatypeLibrary, NCName, Namespace};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Define {
-    #[serde(rename = "@ns")]
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
     pub ns: Namespace,
 
-    #[serde(rename = "@datatypeLibrary")]
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
     pub datatype_library: DatatypeLibrary,
 
-    #[serde(rename = "@combine")]
+    #[serde(rename = "@combine", skip_serializing_if = "Option::is_none")]
     pub combine: Option<Combine>,
 
     #[serde(rename = "@name")]
     pub name: NCName,
 
-    #[serde(rename = "$value")]
+    #[serde(rename = "$value", skip_serializing_if = "Option::is_none")]
     pub content: Option<Vec<Pattern>>,
 }
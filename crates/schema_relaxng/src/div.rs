@@ -1,15 +1,17 @@
-use crate::pattern::Pattern;
-use crate::{DatatypeLibrary, Namespace};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize)]
+use crate::grammar::GrammarContent;
+use crate::{DatatypeLibrary, Namespace};
+
+/// A `div` grouping the content of a grammar.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Div {
-    #[serde(rename = "@ns")]
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
     pub ns: Namespace,
 
-    #[serde(rename = "@datatypeLibrary")]
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
     pub datatype_library: DatatypeLibrary,
 
-    #[serde(rename = "$value")]
-    pub content: Option<Vec<Pattern>>,
+    #[serde(rename = "$value", skip_serializing_if = "Option::is_none")]
+    pub content: Option<Vec<GrammarContent>>,
 }
@@ -1,22 +1,45 @@
 use serde::{Deserialize, Serialize};
 
-use crate::pattern::Pattern;
-use crate::{AnyName, DatatypeLibrary, NCName, Namespace};
+use crate::name_class::NameClass;
+use crate::pattern::{Pattern, RawNamed};
+use crate::{DatatypeLibrary, Namespace, QName};
 
-#[derive(Debug, Deserialize, Serialize)]
+/// An `element` pattern. The name is given either by the `name` attribute
+/// or by a name class as the first child.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "RawNamed", into = "RawNamed")]
 pub struct Element {
-    #[serde(rename = "@ns")]
     pub ns: Namespace,
-
-    #[serde(rename = "@datatypeLibrary")]
     pub datatype_library: DatatypeLibrary,
+    pub name: Option<QName>,
+    pub name_class: Option<NameClass>,
+    pub content: Option<Vec<Pattern>>,
+}
 
-    #[serde(rename = "@name")]
-    pub name: Option<NCName>,
+impl TryFrom<RawNamed> for Element {
+    type Error = String;
 
-    #[serde(rename = "anyName")]
-    pub any_name: Option<AnyName>,
+    fn try_from(raw: RawNamed) -> Result<Self, Self::Error> {
+        let mut raw = raw;
+        let (name_class, content) = raw.split("element")?;
+        Ok(Element {
+            ns: raw.ns,
+            datatype_library: raw.datatype_library,
+            name: raw.name,
+            name_class,
+            content: Some(content),
+        })
+    }
+}
 
-    #[serde(rename = "$value")]
-    pub content: Option<Vec<Pattern>>,
+impl From<Element> for RawNamed {
+    fn from(element: Element) -> Self {
+        RawNamed::join(
+            element.ns,
+            element.datatype_library,
+            element.name,
+            element.name_class,
+            element.content.unwrap_or_default(),
+        )
+    }
 }
@@ -0,0 +1,232 @@
+use std::fmt::{Display, Formatter};
+
+use document::position::{Location, Position};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The document is not well-formed XML.
+    Xml(quick_xml::Error),
+    /// The document element is not in the RELAX NG namespace.
+    NotRelaxNg(String),
+    /// The document does not follow the RELAX NG XML syntax.
+    Syntax(quick_xml::DeError),
+    /// The document does not follow the RELAX NG compact syntax.
+    Compact {
+        position: Position,
+        message: String,
+    },
+    /// A qualified name uses a prefix that is not declared.
+    UndeclaredPrefix(String),
+    /// A `ref` or `parentRef` names a definition that does not exist.
+    UndefinedReference(String),
+    /// A grammar has no `start`.
+    MissingStart,
+    /// The definitions of a name, or the `start` components, disagree on
+    /// their `combine` method.
+    Combine(String),
+    /// An `include` overrides a definition the included grammar lacks.
+    MissingOverride(String),
+    /// A definition refers to itself other than through an `element`, or
+    /// a document includes itself.
+    Recursion(String),
+    /// The grammar breaks a structural rule of the specification.
+    Invalid(String),
+    /// A `data` or `value` pattern uses a datatype the validator lacks.
+    UnsupportedDatatype(String),
+    Io(std::io::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Xml(e) => write!(f, "malformed XML: {}", e),
+            Error::NotRelaxNg(name) => {
+                write!(f, "`{}` is not a RELAX NG element", name)
+            }
+            Error::Syntax(e) => write!(f, "invalid RELAX NG grammar: {}", e),
+            Error::Compact { position, message } => {
+                write!(
+                    f,
+                    "invalid RELAX NG compact syntax at {}: {}",
+                    position, message
+                )
+            }
+            Error::UndeclaredPrefix(prefix) => write!(f, "undeclared prefix `{}`", prefix),
+            Error::UndefinedReference(name) => write!(f, "`{}` is not defined", name),
+            Error::MissingStart => write!(f, "grammar has no start"),
+            Error::Combine(name) => {
+                write!(f, "definitions of `{}` disagree on how to combine", name)
+            }
+            Error::MissingOverride(name) => {
+                write!(
+                    f,
+                    "`{}` is overridden but not defined by the included grammar",
+                    name
+                )
+            }
+            Error::Recursion(name) => write!(f, "`{}` refers to itself", name),
+            Error::Invalid(message) => write!(f, "invalid RELAX NG grammar: {}", message),
+            Error::UnsupportedDatatype(message) => write!(f, "unsupported datatype: {}", message),
+            Error::Io(e) => write!(f, "cannot read grammar: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Xml(e) => Some(e),
+            Error::Syntax(e) => Some(e),
+            Error::Io(e) => Some(e),
+            Error::NotRelaxNg(_)
+            | Error::Compact { .. }
+            | Error::UndeclaredPrefix(_)
+            | Error::UndefinedReference(_)
+            | Error::MissingStart
+            | Error::Combine(_)
+            | Error::MissingOverride(_)
+            | Error::Recursion(_)
+            | Error::Invalid(_)
+            | Error::UnsupportedDatatype(_) => None,
+        }
+    }
+}
+
+impl From<quick_xml::Error> for Error {
+    fn from(e: quick_xml::Error) -> Self {
+        Error::Xml(e)
+    }
+}
+
+impl From<quick_xml::events::attributes::AttrError> for Error {
+    fn from(e: quick_xml::events::attributes::AttrError) -> Self {
+        Error::Xml(e.into())
+    }
+}
+
+impl From<quick_xml::DeError> for Error {
+    fn from(e: quick_xml::DeError) -> Self {
+        Error::Syntax(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// A place where an instance document does not match the grammar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub kind: ValidationErrorKind,
+    /// Where the offending node was read from, when locations were kept.
+    pub location: Option<Location>,
+}
+
+/// What did not match. Element and attribute names are written as in the
+/// instance; what was expected is written in Clark notation, `{ns}local`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationErrorKind {
+    /// An element that is not allowed where it appears.
+    UnexpectedElement { name: String, expected: Vec<String> },
+    /// An attribute the element does not allow.
+    UnexpectedAttribute {
+        element: String,
+        name: String,
+        expected: Vec<String>,
+    },
+    /// An allowed attribute with a value that does not match its pattern.
+    InvalidAttributeValue {
+        element: String,
+        name: String,
+        value: String,
+    },
+    /// Required attributes the element lacks.
+    MissingAttributes {
+        element: String,
+        expected: Vec<String>,
+    },
+    /// Text that is not allowed where it appears, or not a valid value.
+    UnexpectedText {
+        element: String,
+        text: String,
+        expected: Vec<String>,
+    },
+    /// An element that ends before its required content.
+    IncompleteContent {
+        element: String,
+        expected: Vec<String>,
+    },
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(location) = &self.location {
+            write!(f, "{}: ", location)?;
+        }
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl Display for ValidationErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationErrorKind::UnexpectedElement { name, expected } => {
+                write!(f, "element `{}` not allowed here", name)?;
+                write_expected(f, expected)
+            }
+            ValidationErrorKind::UnexpectedAttribute {
+                element,
+                name,
+                expected,
+            } => {
+                write!(f, "attribute `{}` not allowed on `{}`", name, element)?;
+                write_expected(f, expected)
+            }
+            ValidationErrorKind::InvalidAttributeValue {
+                element,
+                name,
+                value,
+            } => write!(
+                f,
+                "invalid value \"{}\" for attribute `{}` of `{}`",
+                value, name, element
+            ),
+            ValidationErrorKind::MissingAttributes { element, expected } => {
+                write!(f, "element `{}` lacks required attributes", element)?;
+                write_expected(f, expected)
+            }
+            ValidationErrorKind::UnexpectedText {
+                element,
+                text,
+                expected,
+            } => {
+                write!(f, "text \"{}\" not allowed in `{}`", text, element)?;
+                write_expected(f, expected)
+            }
+            ValidationErrorKind::IncompleteContent { element, expected } => {
+                write!(f, "element `{}` is incomplete", element)?;
+                write_expected(f, expected)
+            }
+        }
+    }
+}
+
+fn write_expected(f: &mut Formatter<'_>, expected: &[String]) -> std::fmt::Result {
+    if expected.is_empty() {
+        return Ok(());
+    }
+    f.write_str("; expected ")?;
+    for (index, item) in expected.iter().enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "`{}`", item)?;
+    }
+    Ok(())
+}
+
+impl std::error::Error for ValidationError {}
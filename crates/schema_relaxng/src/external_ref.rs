@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{AnyURI, DatatypeLibrary, Namespace};
+
+/// An `externalRef` to the pattern of another grammar document.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ExternalRef {
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
+    pub ns: Namespace,
+
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
+    pub datatype_library: DatatypeLibrary,
+
+    #[serde(rename = "@href")]
+    pub href: AnyURI,
+}
@@ -1,26 +1,28 @@
 use serde::{Deserialize, Serialize};
 
 use crate::define::Define;
+use crate::div::Div;
 use crate::include::Include;
 use crate::start::Start;
 use crate::{DatatypeLibrary, Namespace};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Grammar {
-    #[serde(rename = "@ns")]
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
     pub ns: Namespace,
 
-    #[serde(rename = "@datatypeLibrary")]
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
     pub datatype_library: DatatypeLibrary,
 
-    #[serde(rename = "$value")]
+    #[serde(rename = "$value", skip_serializing_if = "Option::is_none")]
     pub content: Option<Vec<GrammarContent>>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum GrammarContent {
     Define(Define),
+    Div(Div),
     Include(Include),
     Start(Start),
 }
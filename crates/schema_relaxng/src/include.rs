@@ -3,14 +3,26 @@ use serde::{Deserialize, Serialize};
 use crate::define::Define;
 use crate::div::Div;
 use crate::start::Start;
+use crate::{AnyURI, DatatypeLibrary, Namespace};
 
-#[derive(Debug, Deserialize, Serialize)]
+/// An `include` of another grammar document, whose definitions the content
+/// overrides.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Include {
-    #[serde(rename = "$value")]
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
+    pub ns: Namespace,
+
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
+    pub datatype_library: DatatypeLibrary,
+
+    #[serde(rename = "@href")]
+    pub href: AnyURI,
+
+    #[serde(rename = "$value", skip_serializing_if = "Option::is_none")]
     pub content: Option<Vec<IncludeContent>>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum IncludeContent {
     Define(Define),
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+use crate::pattern::Pattern;
+use crate::{DatatypeLibrary, Namespace};
+
+/// An `interleave` of patterns, matched in any order.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Interleave {
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
+    pub ns: Namespace,
+
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
+    pub datatype_library: DatatypeLibrary,
+
+    #[serde(rename = "$value", skip_serializing_if = "Option::is_none")]
+    pub content: Option<Vec<Pattern>>,
+}
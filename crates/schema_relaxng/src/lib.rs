@@ -1,14 +1,66 @@
+use std::fs;
+use std::io::BufRead;
+use std::path::Path;
+
+use document::resolver::{FileResolver, Resolver, Resource};
+use document::uri;
 use serde::{Deserialize, Serialize};
 
-mod choice;
-mod define;
-mod div;
-mod element;
-mod grammar;
-mod include;
-mod pattern;
-mod r#ref;
-mod start;
+pub use attribute::Attribute;
+pub use choice::Choice;
+pub use data::{Data, ExceptPattern, Param};
+pub use datatypes::{BuiltinDatatypes, Context, Datatypes, XsdDatatypes};
+pub use define::Define;
+pub use div::Div;
+pub use element::Element;
+pub use error::{Error, Result, ValidationError, ValidationErrorKind};
+pub use external_ref::ExternalRef;
+pub use grammar::{Grammar, GrammarContent};
+pub use group::Group;
+pub use include::{Include, IncludeContent};
+pub use interleave::Interleave;
+pub use list::List;
+pub use mixed::Mixed;
+pub use name_class::{AnyName, ExceptNameClass, Name, NameClass, NameClassChoice, NsName};
+pub use one_or_more::OneOrMore;
+pub use optional::Optional;
+pub use parser::RELAXNG_NAMESPACE;
+pub use pattern::Pattern;
+pub use r#ref::{ParentRef, Ref};
+pub use simple::{SimpleGrammar, SimpleNameClass, SimplePattern};
+pub use start::Start;
+pub use validator::Validator;
+pub use value::Value;
+pub use zero_or_more::ZeroOrMore;
+
+pub mod attribute;
+pub mod choice;
+mod compact;
+pub mod data;
+pub mod datatypes;
+pub mod define;
+pub mod div;
+pub mod element;
+mod error;
+pub mod external_ref;
+pub mod grammar;
+pub mod group;
+pub mod include;
+pub mod interleave;
+pub mod list;
+pub mod mixed;
+pub mod name_class;
+pub mod one_or_more;
+pub mod optional;
+mod parser;
+pub mod pattern;
+pub mod r#ref;
+pub mod simple;
+mod simplify;
+pub mod start;
+mod validator;
+pub mod value;
+pub mod zero_or_more;
 
 pub type AnyURI = String;
 pub type DatatypeLibrary = Option<AnyURI>;
@@ -16,31 +68,213 @@ pub type Namespace = Option<String>;
 pub type NCName = String;
 pub type QName = String;
 
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(rename = "anyName")]
-pub struct AnyName {}
-
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Combine {
     Choice,
     Interleave,
 }
 
+/// Parses a grammar in the RELAX NG XML syntax. Annotations, which are
+/// elements and qualified attributes outside the RELAX NG namespace, are
+/// dropped.
+pub fn parse_grammar(input: &str) -> Result<Pattern> {
+    let xml = parser::normalize(input)?;
+    Ok(quick_xml::de::from_str(&xml)?)
+}
+
+pub fn parse_grammar_reader<R: BufRead>(mut reader: R) -> Result<Pattern> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    parse_grammar(&input)
+}
+
+/// Reads a grammar file, in the compact syntax if its name ends in `.rnc`
+/// and in the XML syntax otherwise.
+pub fn parse_grammar_file<P: AsRef<Path>>(path: P) -> Result<Pattern> {
+    let input = fs::read_to_string(&path)?;
+    match path.as_ref().extension() {
+        Some(extension) if extension == "rnc" => parse_compact(&input),
+        _ => parse_grammar(&input),
+    }
+}
+
+/// Parses a fetched grammar, in the compact syntax if its URI ends in
+/// `.rnc` and in the XML syntax otherwise.
+pub fn parse_grammar_resource(resource: &Resource) -> Result<Pattern> {
+    let input = resource.text()?;
+    match resource.uri.ends_with(".rnc") {
+        true => parse_compact(input),
+        false => parse_grammar(input),
+    }
+}
+
+/// Parses a grammar in the RELAX NG compact syntax into the same model as
+/// the XML syntax. Annotations and comments are dropped.
+pub fn parse_compact(input: &str) -> Result<Pattern> {
+    compact::parse(input)
+}
+
+pub fn parse_compact_reader<R: BufRead>(mut reader: R) -> Result<Pattern> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    parse_compact(&input)
+}
+
+/// Writes a grammar in the RELAX NG compact syntax, declaring the
+/// namespaces and datatype libraries it uses.
+pub fn to_compact(pattern: &Pattern) -> String {
+    compact::write(pattern)
+}
+
+/// Simplifies a grammar into the simple form of section 4 of the
+/// specification. `base` is the path of the document the grammar was read
+/// from, against which `externalRef` and `include` are resolved.
+pub fn simplify_grammar<P: AsRef<Path>>(pattern: &Pattern, base: P) -> Result<SimpleGrammar> {
+    let base = base.as_ref();
+    let base = match base.as_os_str().is_empty() {
+        true => None,
+        false => Some(base.canonicalize().unwrap_or_else(|_| base.to_path_buf())),
+    };
+    let base = base.map(|base| uri::from_file_path(&base)).transpose()?;
+    simplify::simplify(pattern, base.as_deref(), &FileResolver::new())
+}
+
+/// Simplifies a grammar like [`simplify_grammar`], fetching the documents
+/// that `externalRef` and `include` refer to with `resolver`. `base` is
+/// the URI of the document the grammar was read from.
+pub fn simplify_grammar_with<R: Resolver>(
+    pattern: &Pattern,
+    base: Option<&str>,
+    resolver: &R,
+) -> Result<SimpleGrammar> {
+    simplify::simplify(pattern, base, resolver)
+}
+
+pub fn simplify_grammar_file<P: AsRef<Path>>(path: P) -> Result<SimpleGrammar> {
+    let pattern = parse_grammar_file(&path)?;
+    simplify_grammar(&pattern, path)
+}
+
+/// Fetches the grammar `reference` names with `resolver` and simplifies
+/// it, along with the documents it refers to.
+pub fn load_grammar<R: Resolver>(resolver: &R, reference: &str) -> Result<SimpleGrammar> {
+    let resource = resolver.resolve(None, reference)?;
+    let pattern = parse_grammar_resource(&resource)?;
+    simplify::simplify(&pattern, Some(&resource.uri), resolver)
+}
+
 #[cfg(test)]
 mod tests {
-    use std::fs::read_to_string;
-
     use quick_xml::de::from_str;
+    use quick_xml::se::to_string;
+
+    use crate::{parse_grammar, parse_grammar_file, Error, GrammarContent, NameClass, Pattern};
+
+    #[test]
+    fn parses_the_relax_ng_schema_for_itself() -> Result<(), anyhow::Error> {
+        let structure = parse_grammar_file("../../schema/relaxng.rng")?;
+
+        let Pattern::Grammar(grammar) = &structure else {
+            panic!("expected a grammar");
+        };
+        let content = grammar.content.as_deref().unwrap_or_default();
+        let defines = content
+            .iter()
+            .filter(|item| matches!(item, GrammarContent::Define(_)))
+            .count();
+        assert_eq!(defines, 15);
+
+        let written = to_string(&structure)?;
+        assert_eq!(from_str::<Pattern>(&written)?, structure);
 
-    use crate::pattern::Pattern;
+        Ok(())
+    }
+
+    #[test]
+    fn models_name_classes_and_datatypes() -> Result<(), anyhow::Error> {
+        let data = r#"<element xmlns="http://relaxng.org/ns/structure/1.0" xmlns:b="urn:c">
+  <choice><name ns="urn:a">a</name><nsName ns="urn:b"><except><name>b:c</name></except></nsName></choice>
+  <attribute><anyName><except><nsName/></except></anyName></attribute>
+  <choice>
+    <data type="integer" datatypeLibrary="http://www.w3.org/2001/XMLSchema-datatypes">
+      <param name="minInclusive">1</param>
+      <except><value>7</value></except>
+    </data>
+    <value type="string"></value>
+    <externalRef href="other.rng"/>
+  </choice>
+</element>"#;
+        let structure = parse_grammar(data)?;
+
+        let Pattern::Element(element) = &structure else {
+            panic!("expected an element");
+        };
+        let Some(NameClass::Choice(names)) = &element.name_class else {
+            panic!("expected a name class choice");
+        };
+        assert_eq!(names.content.len(), 2);
+        let NameClass::NsName(ns_name) = &names.content[1] else {
+            panic!("expected a namespace name");
+        };
+        let except = &ns_name.except.as_ref().expect("except").content[0];
+        assert!(matches!(
+            except,
+            NameClass::Name(name) if name.ns.as_deref() == Some("urn:c") && name.name == "c"
+        ));
+
+        let content = element.content.as_deref().unwrap_or_default();
+        let Pattern::Attribute(attribute) = &content[0] else {
+            panic!("expected an attribute");
+        };
+        assert!(matches!(attribute.name_class, Some(NameClass::AnyName(_))));
+        assert!(attribute.pattern.is_none());
+
+        let Pattern::Choice(choice) = &content[1] else {
+            panic!("expected a pattern choice");
+        };
+        let patterns = choice.content.as_deref().unwrap_or_default();
+        let Pattern::Data(data) = &patterns[0] else {
+            panic!("expected data");
+        };
+        assert_eq!(data.params[0].value, "1");
+        assert!(data.except.is_some());
+        assert!(matches!(&patterns[2], Pattern::ExternalRef(r) if r.href == "other.rng"));
+
+        let written = to_string(&structure)?;
+        assert_eq!(from_str::<Pattern>(&written)?, structure);
+
+        Ok(())
+    }
 
     #[test]
-    fn it_works() -> Result<(), anyhow::Error> {
-        let data = read_to_string("resources/relaxng.rng")?;
-        let structure: Pattern = from_str(&data)?;
+    fn drops_annotations_and_prefixes() -> Result<(), anyhow::Error> {
+        let data = r#"<rng:grammar xmlns:rng="http://relaxng.org/ns/structure/1.0"
+    xmlns:a="http://relaxng.org/ns/compatibility/annotations/1.0">
+  <a:documentation>The <rng:empty/> start.</a:documentation>
+  <rng:start a:defaultValue="x"><rng:empty/></rng:start>
+</rng:grammar>"#;
+        let Pattern::Grammar(grammar) = parse_grammar(data)? else {
+            panic!("expected a grammar");
+        };
+        let content = grammar.content.unwrap_or_default();
+        assert_eq!(content.len(), 1);
+        let GrammarContent::Start(start) = &content[0] else {
+            panic!("expected a start");
+        };
+        assert_eq!(start.content, Some(vec![Pattern::Empty]));
 
-        dbg!(structure);
+        assert!(matches!(
+            parse_grammar("<schema/>"),
+            Err(Error::NotRelaxNg(name)) if name == "schema"
+        ));
+        assert!(matches!(
+            parse_grammar(
+                "<element xmlns=\"http://relaxng.org/ns/structure/1.0\"><empty/></element>"
+            ),
+            Err(Error::Syntax(_))
+        ));
+        assert!(matches!(parse_grammar("<grammar"), Err(Error::Xml(_))));
 
         Ok(())
     }
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{DatatypeLibrary, Namespace, QName};
+
+/// The names an `element` or `attribute` pattern matches.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NameClass {
+    Name(Name),
+    AnyName(AnyName),
+    NsName(NsName),
+    Choice(NameClassChoice),
+}
+
+/// A single name, resolved against `ns` when it has no prefix.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Name {
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
+    pub ns: Namespace,
+
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
+    pub datatype_library: DatatypeLibrary,
+
+    #[serde(rename = "$text")]
+    pub name: QName,
+}
+
+/// Any name, except those matched by `except`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct AnyName {
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
+    pub ns: Namespace,
+
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
+    pub datatype_library: DatatypeLibrary,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub except: Option<ExceptNameClass>,
+}
+
+/// Any name in the namespace `ns`, except those matched by `except`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NsName {
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
+    pub ns: Namespace,
+
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
+    pub datatype_library: DatatypeLibrary,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub except: Option<ExceptNameClass>,
+}
+
+/// A `choice` between name classes.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NameClassChoice {
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
+    pub ns: Namespace,
+
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
+    pub datatype_library: DatatypeLibrary,
+
+    #[serde(rename = "$value")]
+    pub content: Vec<NameClass>,
+}
+
+/// The names removed from an `anyName` or `nsName`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ExceptNameClass {
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
+    pub ns: Namespace,
+
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
+    pub datatype_library: DatatypeLibrary,
+
+    #[serde(rename = "$value")]
+    pub content: Vec<NameClass>,
+}
@@ -0,0 +1,149 @@
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::name::{QName, ResolveResult};
+use quick_xml::writer::Writer;
+use quick_xml::NsReader;
+
+use crate::error::{Error, Result};
+
+/// The namespace of RELAX NG grammar elements.
+pub const RELAXNG_NAMESPACE: &str = "http://relaxng.org/ns/structure/1.0";
+
+/// The namespace bound to the `xml` prefix.
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Rewrites a grammar document into the shape the model deserializes:
+/// RELAX NG elements lose their prefix, and foreign elements and
+/// attributes, which are annotations, are dropped. Since namespace
+/// declarations are not kept in the model, prefixed names of `element`,
+/// `attribute` and `name` are resolved here into a `name` with an `ns`
+/// attribute.
+pub(crate) fn normalize(input: &str) -> Result<String> {
+    let mut reader = NsReader::from_str(input);
+    let mut writer = Writer::new(Vec::new());
+    // The depth of the foreign element being skipped, if any.
+    let mut foreign = 0usize;
+    let mut root = true;
+    // A `name` start tag held back until its text has been seen.
+    let mut pending_name: Option<BytesStart> = None;
+
+    loop {
+        let (resolved, event) = reader.read_resolved_event()?;
+        let is_relaxng = matches!(
+            resolved,
+            ResolveResult::Bound(namespace) if namespace.as_ref() == RELAXNG_NAMESPACE.as_bytes()
+        );
+        let is_start = matches!(event, Event::Start(_));
+        if let Some(start) = pending_name.take() {
+            if let Event::Text(text) = &event {
+                if let Some((ns, local_name)) = resolve_qname(&reader, text.unescape()?.trim())? {
+                    let start = with_attribute(&start, "ns", Some(&ns))?;
+                    writer.write_event(Event::Start(start))?;
+                    writer.write_event(Event::Text(BytesText::new(&local_name)))?;
+                    continue;
+                }
+            }
+            writer.write_event(Event::Start(start))?;
+        }
+        match event {
+            Event::Eof => break,
+            Event::Start(_) if foreign > 0 => foreign += 1,
+            Event::End(_) if foreign > 0 => foreign -= 1,
+            _ if foreign > 0 => {}
+            Event::Start(start) | Event::Empty(start) if !is_relaxng => {
+                if root {
+                    let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+                    return Err(Error::NotRelaxNg(name));
+                }
+                if is_start {
+                    foreign = 1;
+                }
+            }
+            Event::Start(start) | Event::Empty(start) => {
+                root = false;
+                let copy = relaxng_start(&start)?;
+                let local_name = start.local_name();
+                let name = match local_name.as_ref() {
+                    b"element" | b"attribute" => match start.try_get_attribute("name")? {
+                        Some(name) => resolve_qname(&reader, name.unescape_value()?.trim())?,
+                        None => None,
+                    },
+                    _ => None,
+                };
+                if let Some((ns, name)) = name {
+                    writer.write_event(Event::Start(with_attribute(&copy, "name", None)?))?;
+                    writer
+                        .create_element("name")
+                        .with_attribute(("ns", ns.as_str()))
+                        .write_text_content(BytesText::new(&name))?;
+                    if !is_start {
+                        writer.write_event(Event::End(copy.to_end()))?;
+                    }
+                } else if local_name.as_ref() == b"name" && is_start {
+                    pending_name = Some(copy);
+                } else if is_start {
+                    writer.write_event(Event::Start(copy))?;
+                } else {
+                    writer.write_event(Event::Empty(copy))?;
+                }
+            }
+            Event::End(end) => {
+                let local_name = String::from_utf8_lossy(end.local_name().into_inner());
+                writer.write_event(Event::End(BytesEnd::new(local_name.into_owned())))?;
+            }
+            event => writer.write_event(event)?,
+        }
+    }
+
+    Ok(String::from_utf8_lossy(&writer.into_inner()).into_owned())
+}
+
+/// Copies a RELAX NG start tag under its local name, keeping namespace
+/// declarations and unqualified attributes.
+fn relaxng_start(start: &BytesStart) -> Result<BytesStart<'static>> {
+    let local_name = String::from_utf8_lossy(start.local_name().into_inner()).into_owned();
+    let mut copy = BytesStart::new(local_name);
+    for attribute in start.attributes() {
+        let attribute = attribute?;
+        if attribute.key.prefix().is_none() || attribute.key.as_namespace_binding().is_some() {
+            copy.push_attribute(attribute);
+        }
+    }
+    Ok(copy)
+}
+
+/// Copies a start tag with the attribute `key` set to `value`, or removed.
+fn with_attribute(
+    start: &BytesStart,
+    key: &str,
+    value: Option<&str>,
+) -> Result<BytesStart<'static>> {
+    let mut copy = BytesStart::new(String::from_utf8_lossy(start.name().as_ref()).into_owned());
+    for attribute in start.attributes() {
+        let attribute = attribute?;
+        if attribute.key.as_ref() != key.as_bytes() {
+            copy.push_attribute(attribute);
+        }
+    }
+    if let Some(value) = value {
+        copy.push_attribute((key, value));
+    }
+    Ok(copy)
+}
+
+/// Splits a prefixed name into its namespace and local name, using the
+/// declarations in scope. Unprefixed names give `None`, as they take the
+/// namespace from the `ns` attribute instead.
+fn resolve_qname(reader: &NsReader<&[u8]>, name: &str) -> Result<Option<(String, String)>> {
+    let Some((prefix, local_name)) = name.split_once(':') else {
+        return Ok(None);
+    };
+    let ns = if prefix == "xml" {
+        XML_NAMESPACE.to_owned()
+    } else {
+        match reader.resolve_element(QName(name.as_bytes())).0 {
+            ResolveResult::Bound(ns) => String::from_utf8_lossy(ns.as_ref()).into_owned(),
+            _ => return Err(Error::UndeclaredPrefix(prefix.to_owned())),
+        }
+    };
+    Ok(Some((ns, local_name.to_owned())))
+}
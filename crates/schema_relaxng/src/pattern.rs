@@ -1,30 +1,244 @@
-use crate::choice::Choice;
-use crate::element::Element;
 use serde::{Deserialize, Serialize};
 
+use crate::attribute::Attribute;
+use crate::choice::Choice;
+use crate::data::Data;
+use crate::element::Element;
+use crate::external_ref::ExternalRef;
 use crate::grammar::Grammar;
-use crate::r#ref::Ref;
+use crate::group::Group;
+use crate::interleave::Interleave;
+use crate::list::List;
+use crate::mixed::Mixed;
+use crate::name_class::{AnyName, Name, NameClass, NameClassChoice, NsName};
+use crate::one_or_more::OneOrMore;
+use crate::optional::Optional;
+use crate::r#ref::{ParentRef, Ref};
+use crate::value::Value;
+use crate::zero_or_more::ZeroOrMore;
+use crate::{DatatypeLibrary, Namespace, QName};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Pattern {
-    Attribute,
+    Attribute(Attribute),
     Choice(Choice),
-    Data,
+    Data(Data),
+    Element(Element),
+    Empty,
+    ExternalRef(ExternalRef),
+    Grammar(Grammar),
+    Group(Group),
+    List(List),
+    Interleave(Interleave),
+    Mixed(Mixed),
+    NotAllowed,
+    OneOrMore(OneOrMore),
+    Optional(Optional),
+    ParentRef(ParentRef),
+    Ref(Ref),
+    Text,
+    Value(Value),
+    ZeroOrMore(ZeroOrMore),
+}
+
+/// The XML form of `element` and `attribute`, whose first child is a name
+/// class unless the `name` attribute is given. A `choice` child may be
+/// either, so it is only told apart by position.
+#[derive(Deserialize, Serialize)]
+pub(crate) struct RawNamed {
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
+    pub ns: Namespace,
+
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
+    pub datatype_library: DatatypeLibrary,
+
+    #[serde(rename = "@name", skip_serializing_if = "Option::is_none")]
+    pub name: Option<QName>,
+
+    #[serde(rename = "$value", default)]
+    content: Vec<Item>,
+}
+
+impl RawNamed {
+    /// Takes the name class and the patterns out of the children.
+    pub fn split(&mut self, element: &str) -> Result<(Option<NameClass>, Vec<Pattern>), String> {
+        let mut items = std::mem::take(&mut self.content).into_iter();
+        let name_class = match self.name {
+            Some(_) => None,
+            None => match items.next() {
+                Some(item) => Some(item.into_name_class()?),
+                None => return Err(format!("`{element}` has neither a name nor a name class")),
+            },
+        };
+        let patterns = items.map(Item::into_pattern).collect::<Result<_, _>>()?;
+        Ok((name_class, patterns))
+    }
+
+    pub fn join(
+        ns: Namespace,
+        datatype_library: DatatypeLibrary,
+        name: Option<QName>,
+        name_class: Option<NameClass>,
+        patterns: Vec<Pattern>,
+    ) -> RawNamed {
+        let content = name_class
+            .map(Item::from)
+            .into_iter()
+            .chain(patterns.into_iter().map(Item::from))
+            .collect();
+        RawNamed {
+            ns,
+            datatype_library,
+            name,
+            content,
+        }
+    }
+}
+
+/// A child of `element` or `attribute`: a pattern or a name class.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum Item {
+    Attribute(Attribute),
+    Choice(ItemChoice),
+    Data(Data),
     Element(Element),
     Empty,
-    ExternalRef,
+    ExternalRef(ExternalRef),
     Grammar(Grammar),
-    Group,
-    List,
-    Interleave,
-    Mixed,
+    Group(Group),
+    List(List),
+    Interleave(Interleave),
+    Mixed(Mixed),
     NotAllowed,
-    OneOrMore,
-    Optional,
-    ParentRef,
+    OneOrMore(OneOrMore),
+    Optional(Optional),
+    ParentRef(ParentRef),
     Ref(Ref),
     Text,
-    Value,
-    ZeroOrMore,
+    Value(Value),
+    ZeroOrMore(ZeroOrMore),
+    Name(Name),
+    AnyName(AnyName),
+    NsName(NsName),
+}
+
+#[derive(Deserialize, Serialize)]
+struct ItemChoice {
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
+    ns: Namespace,
+
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
+    datatype_library: DatatypeLibrary,
+
+    #[serde(rename = "$value", default)]
+    content: Vec<Item>,
+}
+
+impl Item {
+    fn into_pattern(self) -> Result<Pattern, String> {
+        Ok(match self {
+            Item::Attribute(value) => Pattern::Attribute(value),
+            Item::Choice(choice) => Pattern::Choice(Choice {
+                ns: choice.ns,
+                datatype_library: choice.datatype_library,
+                content: Some(
+                    choice
+                        .content
+                        .into_iter()
+                        .map(Item::into_pattern)
+                        .collect::<Result<_, _>>()?,
+                ),
+            }),
+            Item::Data(value) => Pattern::Data(value),
+            Item::Element(value) => Pattern::Element(value),
+            Item::Empty => Pattern::Empty,
+            Item::ExternalRef(value) => Pattern::ExternalRef(value),
+            Item::Grammar(value) => Pattern::Grammar(value),
+            Item::Group(value) => Pattern::Group(value),
+            Item::List(value) => Pattern::List(value),
+            Item::Interleave(value) => Pattern::Interleave(value),
+            Item::Mixed(value) => Pattern::Mixed(value),
+            Item::NotAllowed => Pattern::NotAllowed,
+            Item::OneOrMore(value) => Pattern::OneOrMore(value),
+            Item::Optional(value) => Pattern::Optional(value),
+            Item::ParentRef(value) => Pattern::ParentRef(value),
+            Item::Ref(value) => Pattern::Ref(value),
+            Item::Text => Pattern::Text,
+            Item::Value(value) => Pattern::Value(value),
+            Item::ZeroOrMore(value) => Pattern::ZeroOrMore(value),
+            Item::Name(_) | Item::AnyName(_) | Item::NsName(_) => {
+                return Err("a name class is not allowed as a pattern".to_owned())
+            }
+        })
+    }
+
+    fn into_name_class(self) -> Result<NameClass, String> {
+        Ok(match self {
+            Item::Name(value) => NameClass::Name(value),
+            Item::AnyName(value) => NameClass::AnyName(value),
+            Item::NsName(value) => NameClass::NsName(value),
+            Item::Choice(choice) => NameClass::Choice(NameClassChoice {
+                ns: choice.ns,
+                datatype_library: choice.datatype_library,
+                content: choice
+                    .content
+                    .into_iter()
+                    .map(Item::into_name_class)
+                    .collect::<Result<_, _>>()?,
+            }),
+            _ => return Err("expected a name class".to_owned()),
+        })
+    }
+}
+
+impl From<Pattern> for Item {
+    fn from(pattern: Pattern) -> Self {
+        match pattern {
+            Pattern::Attribute(value) => Item::Attribute(value),
+            Pattern::Choice(choice) => Item::Choice(ItemChoice {
+                ns: choice.ns,
+                datatype_library: choice.datatype_library,
+                content: choice
+                    .content
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(Item::from)
+                    .collect(),
+            }),
+            Pattern::Data(value) => Item::Data(value),
+            Pattern::Element(value) => Item::Element(value),
+            Pattern::Empty => Item::Empty,
+            Pattern::ExternalRef(value) => Item::ExternalRef(value),
+            Pattern::Grammar(value) => Item::Grammar(value),
+            Pattern::Group(value) => Item::Group(value),
+            Pattern::List(value) => Item::List(value),
+            Pattern::Interleave(value) => Item::Interleave(value),
+            Pattern::Mixed(value) => Item::Mixed(value),
+            Pattern::NotAllowed => Item::NotAllowed,
+            Pattern::OneOrMore(value) => Item::OneOrMore(value),
+            Pattern::Optional(value) => Item::Optional(value),
+            Pattern::ParentRef(value) => Item::ParentRef(value),
+            Pattern::Ref(value) => Item::Ref(value),
+            Pattern::Text => Item::Text,
+            Pattern::Value(value) => Item::Value(value),
+            Pattern::ZeroOrMore(value) => Item::ZeroOrMore(value),
+        }
+    }
+}
+
+impl From<NameClass> for Item {
+    fn from(name_class: NameClass) -> Self {
+        match name_class {
+            NameClass::Name(value) => Item::Name(value),
+            NameClass::AnyName(value) => Item::AnyName(value),
+            NameClass::NsName(value) => Item::NsName(value),
+            NameClass::Choice(choice) => Item::Choice(ItemChoice {
+                ns: choice.ns,
+                datatype_library: choice.datatype_library,
+                content: choice.content.into_iter().map(Item::from).collect(),
+            }),
+        }
+    }
 }
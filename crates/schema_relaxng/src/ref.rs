@@ -2,12 +2,26 @@ use serde::{Deserialize, Serialize};
 
 use crate::{DatatypeLibrary, NCName, Namespace};
 
-#[derive(Debug, Deserialize, Serialize)]
+/// A `ref` to a definition of the enclosing grammar.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Ref {
-    #[serde(rename = "@ns")]
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
     pub ns: Namespace,
 
-    #[serde(rename = "@datatypeLibrary")]
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
+    pub datatype_library: DatatypeLibrary,
+
+    #[serde(rename = "@name")]
+    pub name: NCName,
+}
+
+/// A `parentRef` to a definition of the grammar enclosing the current one.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ParentRef {
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
+    pub ns: Namespace,
+
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
     pub datatype_library: DatatypeLibrary,
 
     #[serde(rename = "@name")]
@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use crate::data::{Data, ExceptPattern, Param};
+use crate::define::Define;
+use crate::element::Element;
+use crate::grammar::{Grammar, GrammarContent};
+use crate::name_class::{AnyName, ExceptNameClass, Name, NameClass, NameClassChoice, NsName};
+use crate::pattern::Pattern;
+use crate::r#ref::Ref;
+use crate::start::Start;
+use crate::value::Value;
+use crate::{Attribute, Choice, Group, Interleave, List, OneOrMore};
+
+/// A grammar in the simple form of section 4 of the specification: one
+/// `start`, and definitions that each hold a single `element` pattern.
+/// Elements appear nowhere else, and every `ref` names a definition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimpleGrammar {
+    pub start: SimplePattern,
+    pub defines: BTreeMap<String, SimplePattern>,
+}
+
+/// A pattern of the simple form. Compound patterns are binary, and
+/// `mixed`, `optional` and `zeroOrMore` have been rewritten in terms of the
+/// others.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimplePattern {
+    Empty,
+    NotAllowed,
+    Text,
+    Choice(Box<SimplePattern>, Box<SimplePattern>),
+    Interleave(Box<SimplePattern>, Box<SimplePattern>),
+    Group(Box<SimplePattern>, Box<SimplePattern>),
+    OneOrMore(Box<SimplePattern>),
+    List(Box<SimplePattern>),
+    Data {
+        datatype_library: String,
+        data_type: String,
+        params: Vec<(String, String)>,
+        except: Option<Box<SimplePattern>>,
+    },
+    Value {
+        datatype_library: String,
+        data_type: String,
+        /// The namespace in scope, for datatypes such as `QName`.
+        ns: String,
+        value: String,
+    },
+    Attribute(SimpleNameClass, Box<SimplePattern>),
+    Element(SimpleNameClass, Box<SimplePattern>),
+    Ref(String),
+}
+
+/// A name class of the simple form, with every name split into its
+/// namespace and local name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimpleNameClass {
+    AnyName(Option<Box<SimpleNameClass>>),
+    NsName(String, Option<Box<SimpleNameClass>>),
+    Name(String, String),
+    Choice(Box<SimpleNameClass>, Box<SimpleNameClass>),
+}
+
+impl SimpleGrammar {
+    /// Writes the grammar back into the model of the XML syntax, with the
+    /// `ns` and `datatypeLibrary` attributes spelled out wherever they
+    /// matter.
+    pub fn to_grammar(&self) -> Grammar {
+        let start = GrammarContent::Start(Start {
+            ns: None,
+            datatype_library: None,
+            combine: None,
+            content: Some(vec![self.start.to_pattern()]),
+        });
+        let defines = self.defines.iter().map(|(name, pattern)| {
+            GrammarContent::Define(Define {
+                ns: None,
+                datatype_library: None,
+                combine: None,
+                name: name.clone(),
+                content: Some(vec![pattern.to_pattern()]),
+            })
+        });
+        Grammar {
+            ns: None,
+            datatype_library: None,
+            content: Some(std::iter::once(start).chain(defines).collect()),
+        }
+    }
+}
+
+impl SimplePattern {
+    pub fn to_pattern(&self) -> Pattern {
+        match self {
+            SimplePattern::Empty => Pattern::Empty,
+            SimplePattern::NotAllowed => Pattern::NotAllowed,
+            SimplePattern::Text => Pattern::Text,
+            SimplePattern::Choice(a, b) => Pattern::Choice(Choice {
+                ns: None,
+                datatype_library: None,
+                content: Some(vec![a.to_pattern(), b.to_pattern()]),
+            }),
+            SimplePattern::Interleave(a, b) => Pattern::Interleave(Interleave {
+                ns: None,
+                datatype_library: None,
+                content: Some(vec![a.to_pattern(), b.to_pattern()]),
+            }),
+            SimplePattern::Group(a, b) => Pattern::Group(Group {
+                ns: None,
+                datatype_library: None,
+                content: Some(vec![a.to_pattern(), b.to_pattern()]),
+            }),
+            SimplePattern::OneOrMore(p) => Pattern::OneOrMore(OneOrMore {
+                ns: None,
+                datatype_library: None,
+                content: Some(vec![p.to_pattern()]),
+            }),
+            SimplePattern::List(p) => Pattern::List(List {
+                ns: None,
+                datatype_library: None,
+                content: Some(vec![p.to_pattern()]),
+            }),
+            SimplePattern::Data {
+                datatype_library,
+                data_type,
+                params,
+                except,
+            } => Pattern::Data(Data {
+                ns: None,
+                datatype_library: Some(datatype_library.clone()),
+                data_type: data_type.clone(),
+                params: params
+                    .iter()
+                    .map(|(name, value)| Param {
+                        ns: None,
+                        datatype_library: None,
+                        name: name.clone(),
+                        value: value.clone(),
+                    })
+                    .collect(),
+                except: except.as_ref().map(|p| ExceptPattern {
+                    ns: None,
+                    datatype_library: None,
+                    content: Some(vec![p.to_pattern()]),
+                }),
+            }),
+            SimplePattern::Value {
+                datatype_library,
+                data_type,
+                ns,
+                value,
+            } => Pattern::Value(Value {
+                ns: Some(ns.clone()),
+                datatype_library: Some(datatype_library.clone()),
+                data_type: Some(data_type.clone()),
+                value: value.clone(),
+            }),
+            SimplePattern::Attribute(name_class, p) => Pattern::Attribute(Attribute {
+                ns: None,
+                datatype_library: None,
+                name: None,
+                name_class: Some(name_class.to_name_class()),
+                pattern: Some(Box::new(p.to_pattern())),
+            }),
+            SimplePattern::Element(name_class, p) => Pattern::Element(Element {
+                ns: None,
+                datatype_library: None,
+                name: None,
+                name_class: Some(name_class.to_name_class()),
+                content: Some(vec![p.to_pattern()]),
+            }),
+            SimplePattern::Ref(name) => Pattern::Ref(Ref {
+                ns: None,
+                datatype_library: None,
+                name: name.clone(),
+            }),
+        }
+    }
+}
+
+impl SimpleNameClass {
+    /// Whether the name class matches the name `local_name` in the
+    /// namespace `ns`, the empty string for none.
+    pub fn contains(&self, ns: &str, local_name: &str) -> bool {
+        let excluded = |except: &Option<Box<SimpleNameClass>>| {
+            except
+                .as_ref()
+                .is_some_and(|except| except.contains(ns, local_name))
+        };
+        match self {
+            SimpleNameClass::AnyName(except) => !excluded(except),
+            SimpleNameClass::NsName(uri, except) => uri == ns && !excluded(except),
+            SimpleNameClass::Name(uri, name) => uri == ns && name == local_name,
+            SimpleNameClass::Choice(a, b) => {
+                a.contains(ns, local_name) || b.contains(ns, local_name)
+            }
+        }
+    }
+
+    pub fn to_name_class(&self) -> NameClass {
+        let except = |except: &Option<Box<SimpleNameClass>>| {
+            except.as_ref().map(|name_class| ExceptNameClass {
+                ns: None,
+                datatype_library: None,
+                content: vec![name_class.to_name_class()],
+            })
+        };
+        match self {
+            SimpleNameClass::AnyName(e) => NameClass::AnyName(AnyName {
+                ns: None,
+                datatype_library: None,
+                except: except(e),
+            }),
+            SimpleNameClass::NsName(ns, e) => NameClass::NsName(NsName {
+                ns: Some(ns.clone()),
+                datatype_library: None,
+                except: except(e),
+            }),
+            SimpleNameClass::Name(ns, local_name) => NameClass::Name(Name {
+                ns: Some(ns.clone()),
+                datatype_library: None,
+                name: local_name.clone(),
+            }),
+            SimpleNameClass::Choice(a, b) => NameClass::Choice(NameClassChoice {
+                ns: None,
+                datatype_library: None,
+                content: vec![a.to_name_class(), b.to_name_class()],
+            }),
+        }
+    }
+}
+
+/// Writes names in Clark notation, `{ns}local`, and wildcards as `*`.
+impl Display for SimpleNameClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (wildcard, except) = match self {
+            SimpleNameClass::Name(ns, local_name) if ns.is_empty() => {
+                return f.write_str(local_name)
+            }
+            SimpleNameClass::Name(ns, local_name) => return write!(f, "{{{}}}{}", ns, local_name),
+            SimpleNameClass::Choice(a, b) => return write!(f, "{} | {}", a, b),
+            SimpleNameClass::AnyName(except) => ("*".to_owned(), except),
+            SimpleNameClass::NsName(ns, except) => (format!("{{{}}}*", ns), except),
+        };
+        f.write_str(&wildcard)?;
+        match except {
+            Some(except) => write!(f, " except {}", except),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,968 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use document::resolver::Resolver;
+
+use crate::error::{Error, Result};
+use crate::grammar::GrammarContent;
+use crate::include::IncludeContent;
+use crate::name_class::{ExceptNameClass, NameClass};
+use crate::pattern::Pattern;
+use crate::simple::{SimpleGrammar, SimpleNameClass, SimplePattern};
+use crate::{parse_grammar_resource, Combine, DatatypeLibrary, Namespace};
+
+/// The namespace reserved for namespace declarations, which no attribute
+/// pattern may name.
+const XMLNS_NAMESPACE: &str = "http://www.w3.org/2000/xmlns";
+
+/// Rewrites `pattern`, read from the document at `base`, into a single
+/// grammar of the simple form, following section 4 of the specification.
+/// Annotations are already gone after parsing, as are prefixes of names.
+/// Referenced documents are fetched with `resolver`.
+pub(crate) fn simplify(
+    pattern: &Pattern,
+    base: Option<&str>,
+    resolver: &dyn Resolver,
+) -> Result<SimpleGrammar> {
+    let mut simplifier = Simplifier {
+        resolver,
+        defines: BTreeMap::new(),
+        scopes: Vec::new(),
+        loading: Vec::new(),
+    };
+    let env = Env {
+        ns: String::new(),
+        datatype_library: String::new(),
+        base: base.map(str::to_owned),
+    };
+    if let Some(base) = base {
+        simplifier.loading.push(base.to_owned());
+    }
+    let start = simplifier.pattern(pattern, &env)?;
+    flatten(simplifier.defines, start)
+}
+
+/// What a pattern inherits from its ancestors: the `ns` and
+/// `datatypeLibrary` attributes, and the URI of the document hrefs are
+/// resolved against.
+#[derive(Debug, Clone)]
+struct Env {
+    ns: String,
+    datatype_library: String,
+    base: Option<String>,
+}
+
+impl Env {
+    fn with(&self, ns: &Namespace, datatype_library: &DatatypeLibrary) -> Env {
+        Env {
+            ns: ns.clone().unwrap_or_else(|| self.ns.clone()),
+            datatype_library: match datatype_library {
+                Some(library) => library.trim().to_owned(),
+                None => self.datatype_library.clone(),
+            },
+            base: self.base.clone(),
+        }
+    }
+
+    /// The environment of a document referenced from here, which keeps
+    /// the namespace but not the datatype library.
+    fn document(&self, uri: String) -> Env {
+        Env {
+            ns: self.ns.clone(),
+            datatype_library: String::new(),
+            base: Some(uri),
+        }
+    }
+}
+
+/// A `start` or `define` of a grammar, once divs and includes have been
+/// flattened. The start has no name.
+struct Component {
+    name: Option<String>,
+    combine: Option<Combine>,
+    content: Vec<Pattern>,
+    env: Env,
+}
+
+struct Simplifier<'a> {
+    resolver: &'a dyn Resolver,
+    /// The definitions of every grammar, under names unique across them.
+    defines: BTreeMap<String, SimplePattern>,
+    /// The definitions in scope for each enclosing grammar, mapped to
+    /// their unique names. The last one is the innermost grammar.
+    scopes: Vec<HashMap<String, String>>,
+    /// The URIs of the documents being read, to catch ones that refer to
+    /// themselves.
+    loading: Vec<String>,
+}
+
+impl Simplifier<'_> {
+    fn pattern(&mut self, pattern: &Pattern, env: &Env) -> Result<SimplePattern> {
+        Ok(match pattern {
+            Pattern::Element(element) => {
+                let env = env.with(&element.ns, &element.datatype_library);
+                let name_class = match (&element.name, &element.name_class) {
+                    (Some(name), _) => {
+                        SimpleNameClass::Name(env.ns.clone(), name.trim().to_owned())
+                    }
+                    (None, Some(name_class)) => name_class_of(name_class, &env)?,
+                    (None, None) => return Err(Error::Invalid("`element` has no name".to_owned())),
+                };
+                let content = self.group("element", element.content.as_deref(), &env)?;
+                SimplePattern::Element(name_class, Box::new(content))
+            }
+            Pattern::Attribute(attribute) => {
+                let env = env.with(&attribute.ns, &attribute.datatype_library);
+                let name_class = match (&attribute.name, &attribute.name_class) {
+                    // A plain attribute name is never in the inherited namespace.
+                    (Some(name), _) => SimpleNameClass::Name(
+                        attribute.ns.clone().unwrap_or_default(),
+                        name.trim().to_owned(),
+                    ),
+                    (None, Some(name_class)) => name_class_of(name_class, &env)?,
+                    (None, None) => {
+                        return Err(Error::Invalid("`attribute` has no name".to_owned()))
+                    }
+                };
+                if names_xmlns(&name_class) {
+                    return Err(Error::Invalid(
+                        "an attribute pattern names a namespace declaration".to_owned(),
+                    ));
+                }
+                let content = match &attribute.pattern {
+                    Some(pattern) => self.pattern(pattern, &env)?,
+                    None => SimplePattern::Text,
+                };
+                SimplePattern::Attribute(name_class, Box::new(content))
+            }
+            Pattern::Choice(choice) => {
+                let env = env.with(&choice.ns, &choice.datatype_library);
+                let patterns = self.patterns(choice.content.as_deref(), &env)?;
+                fold("choice", patterns, SimplePattern::Choice)?
+            }
+            Pattern::Group(group) => {
+                let env = env.with(&group.ns, &group.datatype_library);
+                self.group("group", group.content.as_deref(), &env)?
+            }
+            Pattern::Interleave(interleave) => {
+                let env = env.with(&interleave.ns, &interleave.datatype_library);
+                let patterns = self.patterns(interleave.content.as_deref(), &env)?;
+                fold("interleave", patterns, SimplePattern::Interleave)?
+            }
+            Pattern::Optional(optional) => {
+                let env = env.with(&optional.ns, &optional.datatype_library);
+                let content = self.group("optional", optional.content.as_deref(), &env)?;
+                SimplePattern::Choice(Box::new(content), Box::new(SimplePattern::Empty))
+            }
+            Pattern::ZeroOrMore(zero_or_more) => {
+                let env = env.with(&zero_or_more.ns, &zero_or_more.datatype_library);
+                let content = self.group("zeroOrMore", zero_or_more.content.as_deref(), &env)?;
+                SimplePattern::Choice(
+                    Box::new(SimplePattern::OneOrMore(Box::new(content))),
+                    Box::new(SimplePattern::Empty),
+                )
+            }
+            Pattern::OneOrMore(one_or_more) => {
+                let env = env.with(&one_or_more.ns, &one_or_more.datatype_library);
+                let content = self.group("oneOrMore", one_or_more.content.as_deref(), &env)?;
+                SimplePattern::OneOrMore(Box::new(content))
+            }
+            Pattern::List(list) => {
+                let env = env.with(&list.ns, &list.datatype_library);
+                let content = self.group("list", list.content.as_deref(), &env)?;
+                SimplePattern::List(Box::new(content))
+            }
+            Pattern::Mixed(mixed) => {
+                let env = env.with(&mixed.ns, &mixed.datatype_library);
+                let content = self.group("mixed", mixed.content.as_deref(), &env)?;
+                SimplePattern::Interleave(Box::new(content), Box::new(SimplePattern::Text))
+            }
+            Pattern::Empty => SimplePattern::Empty,
+            Pattern::NotAllowed => SimplePattern::NotAllowed,
+            Pattern::Text => SimplePattern::Text,
+            Pattern::Ref(r) => self.reference(&r.name, 1)?,
+            Pattern::ParentRef(r) => self.reference(&r.name, 2)?,
+            Pattern::Value(value) => {
+                let env = env.with(&value.ns, &value.datatype_library);
+                let (datatype_library, data_type) = match &value.data_type {
+                    Some(data_type) => (env.datatype_library.clone(), data_type.trim().to_owned()),
+                    None => (String::new(), "token".to_owned()),
+                };
+                SimplePattern::Value {
+                    datatype_library,
+                    data_type,
+                    ns: env.ns,
+                    value: value.value.clone(),
+                }
+            }
+            Pattern::Data(data) => {
+                let env = env.with(&data.ns, &data.datatype_library);
+                let except = match &data.except {
+                    Some(except) => {
+                        let env = env.with(&except.ns, &except.datatype_library);
+                        let patterns = self.patterns(except.content.as_deref(), &env)?;
+                        Some(Box::new(fold("except", patterns, SimplePattern::Choice)?))
+                    }
+                    None => None,
+                };
+                SimplePattern::Data {
+                    datatype_library: env.datatype_library,
+                    data_type: data.data_type.trim().to_owned(),
+                    params: data
+                        .params
+                        .iter()
+                        .map(|param| (param.name.trim().to_owned(), param.value.clone()))
+                        .collect(),
+                    except,
+                }
+            }
+            Pattern::ExternalRef(external_ref) => {
+                let env = env.with(&external_ref.ns, &external_ref.datatype_library);
+                let (uri, referenced) = self.load(&external_ref.href, &env)?;
+                self.loading.push(uri.clone());
+                let pattern = self.pattern(&referenced, &env.document(uri))?;
+                self.loading.pop();
+                pattern
+            }
+            Pattern::Grammar(grammar) => {
+                let env = env.with(&grammar.ns, &grammar.datatype_library);
+                self.grammar(grammar.content.as_deref().unwrap_or_default(), &env)?
+            }
+        })
+    }
+
+    fn patterns(&mut self, patterns: Option<&[Pattern]>, env: &Env) -> Result<Vec<SimplePattern>> {
+        patterns
+            .unwrap_or_default()
+            .iter()
+            .map(|pattern| self.pattern(pattern, env))
+            .collect()
+    }
+
+    /// The children of `parent` as one pattern, grouped if there are
+    /// several.
+    fn group(
+        &mut self,
+        parent: &str,
+        patterns: Option<&[Pattern]>,
+        env: &Env,
+    ) -> Result<SimplePattern> {
+        let patterns = self.patterns(patterns, env)?;
+        fold(parent, patterns, SimplePattern::Group)
+    }
+
+    /// Looks a definition up in the grammar `depth` levels out.
+    fn reference(&self, name: &str, depth: usize) -> Result<SimplePattern> {
+        let name = name.trim();
+        self.scopes
+            .len()
+            .checked_sub(depth)
+            .and_then(|index| self.scopes[index].get(name))
+            .map(|unique| SimplePattern::Ref(unique.clone()))
+            .ok_or_else(|| Error::UndefinedReference(name.to_owned()))
+    }
+
+    /// Replaces a grammar by its start, moving its definitions under
+    /// unique names into the single grammar of the simple form.
+    fn grammar(&mut self, content: &[GrammarContent], env: &Env) -> Result<SimplePattern> {
+        let mut components = Vec::new();
+        self.components(content, env, &mut components)?;
+
+        let mut starts = Vec::new();
+        let mut defines: BTreeMap<String, Vec<Component>> = BTreeMap::new();
+        for component in components {
+            match &component.name {
+                Some(name) => defines.entry(name.clone()).or_default().push(component),
+                None => starts.push(component),
+            }
+        }
+
+        let scope = defines
+            .keys()
+            .map(|name| (name.clone(), self.reserve(name)))
+            .collect::<HashMap<_, _>>();
+        let unique_names = scope.clone();
+        self.scopes.push(scope);
+        for (name, components) in defines {
+            let pattern = self.combine(&name, components)?;
+            self.defines.insert(unique_names[&name].clone(), pattern);
+        }
+        if starts.is_empty() {
+            return Err(Error::MissingStart);
+        }
+        let start = self.combine("start", starts)?;
+        self.scopes.pop();
+        Ok(start)
+    }
+
+    /// Collects the starts and definitions of a grammar, looking through
+    /// `div` and replacing `include` by the components of the included
+    /// grammar it does not override.
+    fn components(
+        &mut self,
+        content: &[GrammarContent],
+        env: &Env,
+        components: &mut Vec<Component>,
+    ) -> Result<()> {
+        for item in content {
+            match item {
+                GrammarContent::Start(start) => components.push(Component {
+                    name: None,
+                    combine: start.combine,
+                    content: start.content.clone().unwrap_or_default(),
+                    env: env.with(&start.ns, &start.datatype_library),
+                }),
+                GrammarContent::Define(define) => components.push(Component {
+                    name: Some(define.name.trim().to_owned()),
+                    combine: define.combine,
+                    content: define.content.clone().unwrap_or_default(),
+                    env: env.with(&define.ns, &define.datatype_library),
+                }),
+                GrammarContent::Div(div) => {
+                    let env = env.with(&div.ns, &div.datatype_library);
+                    self.components(div.content.as_deref().unwrap_or_default(), &env, components)?;
+                }
+                GrammarContent::Include(include) => {
+                    let env = env.with(&include.ns, &include.datatype_library);
+                    let (uri, included) = self.load(&include.href, &env)?;
+                    let Pattern::Grammar(grammar) = included else {
+                        return Err(Error::Invalid(format!(
+                            "included `{}` is not a grammar",
+                            uri
+                        )));
+                    };
+
+                    let mut included = Vec::new();
+                    let grammar_env = env
+                        .document(uri.clone())
+                        .with(&grammar.ns, &grammar.datatype_library);
+                    self.loading.push(uri);
+                    self.components(
+                        grammar.content.as_deref().unwrap_or_default(),
+                        &grammar_env,
+                        &mut included,
+                    )?;
+                    self.loading.pop();
+
+                    let content = include
+                        .content
+                        .iter()
+                        .flatten()
+                        .map(|item| match item {
+                            IncludeContent::Define(define) => {
+                                GrammarContent::Define(define.clone())
+                            }
+                            IncludeContent::Div(div) => GrammarContent::Div(div.clone()),
+                            IncludeContent::Start(start) => GrammarContent::Start(start.clone()),
+                        })
+                        .collect::<Vec<_>>();
+                    let mut overrides = Vec::new();
+                    self.components(&content, &env, &mut overrides)?;
+
+                    let overridden = overrides
+                        .iter()
+                        .map(|component| component.name.clone())
+                        .collect::<BTreeSet<_>>();
+                    for name in &overridden {
+                        if !included.iter().any(|component| &component.name == name) {
+                            let name = name.clone().unwrap_or_else(|| "start".to_owned());
+                            return Err(Error::MissingOverride(name));
+                        }
+                    }
+                    included.retain(|component| !overridden.contains(&component.name));
+                    components.extend(included);
+                    components.extend(overrides);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Combines the components sharing a name, of which at most one may
+    /// leave out the `combine` attribute.
+    fn combine(&mut self, name: &str, components: Vec<Component>) -> Result<SimplePattern> {
+        let mut methods = components.iter().filter_map(|component| component.combine);
+        let method = methods.next();
+        if methods.any(|other| Some(other) != method)
+            || components
+                .iter()
+                .filter(|component| component.combine.is_none())
+                .count()
+                > 1
+        {
+            return Err(Error::Combine(name.to_owned()));
+        }
+
+        let patterns = components
+            .iter()
+            .map(|component| {
+                let parent = if component.name.is_some() {
+                    "define"
+                } else {
+                    "start"
+                };
+                self.group(parent, Some(&component.content), &component.env)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        match method {
+            Some(Combine::Interleave) => fold(name, patterns, SimplePattern::Interleave),
+            _ => fold(name, patterns, SimplePattern::Choice),
+        }
+    }
+
+    /// Picks a name no other definition has, keeping `name` if possible.
+    fn reserve(&mut self, name: &str) -> String {
+        let unique = unique_name(name, |candidate| self.defines.contains_key(candidate));
+        // Held until the definition is simplified, so nested grammars do
+        // not take the name.
+        self.defines
+            .insert(unique.clone(), SimplePattern::NotAllowed);
+        unique
+    }
+
+    /// Fetches and parses the document `href` refers to, giving its URI.
+    fn load(&self, href: &str, env: &Env) -> Result<(String, Pattern)> {
+        let resource = self.resolver.resolve(env.base.as_deref(), href)?;
+        if self.loading.contains(&resource.uri) {
+            return Err(Error::Recursion(resource.uri));
+        }
+        let pattern = parse_grammar_resource(&resource)?;
+        Ok((resource.uri, pattern))
+    }
+}
+
+/// Turns the definitions of every grammar into the definitions of the
+/// simple form: every element gets a definition of its own, references to
+/// other definitions are expanded, and `notAllowed` and `empty` are
+/// propagated upwards.
+fn flatten(
+    mut defines: BTreeMap<String, SimplePattern>,
+    start: SimplePattern,
+) -> Result<SimpleGrammar> {
+    let names = defines.keys().cloned().collect::<Vec<_>>();
+    for name in names {
+        let Some(slot) = defines.get_mut(&name) else {
+            continue;
+        };
+        let pattern = match std::mem::replace(slot, SimplePattern::NotAllowed) {
+            SimplePattern::Element(name_class, content) => {
+                SimplePattern::Element(name_class, Box::new(hoist(*content, &mut defines)))
+            }
+            pattern => hoist(pattern, &mut defines),
+        };
+        defines.insert(name, pattern);
+    }
+    let start = hoist(start, &mut defines);
+
+    let start = reduce(expand(start, &defines, &mut Vec::new())?);
+    let mut elements = BTreeMap::new();
+    for (name, pattern) in &defines {
+        if let SimplePattern::Element(name_class, content) = pattern {
+            let content = expand((**content).clone(), &defines, &mut Vec::new())?;
+            let element = SimplePattern::Element(name_class.clone(), Box::new(content));
+            elements.insert(name.clone(), reduce(element));
+        }
+    }
+
+    let mut reachable = BTreeSet::new();
+    let mut pending = Vec::new();
+    references(&start, &mut pending);
+    while let Some(name) = pending.pop() {
+        if reachable.insert(name.clone()) {
+            references(&elements[&name], &mut pending);
+        }
+    }
+    elements.retain(|name, _| reachable.contains(name));
+
+    Ok(SimpleGrammar {
+        start,
+        defines: elements,
+    })
+}
+
+/// Moves the elements in `pattern` into definitions of their own,
+/// leaving references in their place.
+fn hoist(pattern: SimplePattern, defines: &mut BTreeMap<String, SimplePattern>) -> SimplePattern {
+    let mut hoist_box = |pattern: Box<SimplePattern>| Box::new(hoist(*pattern, defines));
+    match pattern {
+        SimplePattern::Element(name_class, content) => {
+            let content = hoist_box(content);
+            let base = match &name_class {
+                SimpleNameClass::Name(_, local_name) => local_name.as_str(),
+                _ => "element",
+            };
+            let name = unique_name(base, |candidate| defines.contains_key(candidate));
+            defines.insert(name.clone(), SimplePattern::Element(name_class, content));
+            SimplePattern::Ref(name)
+        }
+        SimplePattern::Choice(a, b) => {
+            let a = hoist_box(a);
+            SimplePattern::Choice(a, hoist_box(b))
+        }
+        SimplePattern::Interleave(a, b) => {
+            let a = hoist_box(a);
+            SimplePattern::Interleave(a, hoist_box(b))
+        }
+        SimplePattern::Group(a, b) => {
+            let a = hoist_box(a);
+            SimplePattern::Group(a, hoist_box(b))
+        }
+        SimplePattern::OneOrMore(p) => SimplePattern::OneOrMore(hoist_box(p)),
+        SimplePattern::List(p) => SimplePattern::List(hoist_box(p)),
+        SimplePattern::Attribute(name_class, p) => {
+            SimplePattern::Attribute(name_class, hoist_box(p))
+        }
+        SimplePattern::Data {
+            datatype_library,
+            data_type,
+            params,
+            except,
+        } => SimplePattern::Data {
+            datatype_library,
+            data_type,
+            params,
+            except: except.map(hoist_box),
+        },
+        pattern => pattern,
+    }
+}
+
+/// Replaces references to definitions other than of an element by their
+/// content. `expanding` holds the definitions being expanded, which must
+/// not be met again.
+fn expand(
+    pattern: SimplePattern,
+    defines: &BTreeMap<String, SimplePattern>,
+    expanding: &mut Vec<String>,
+) -> Result<SimplePattern> {
+    let mut expand_box =
+        |pattern: Box<SimplePattern>| expand(*pattern, defines, expanding).map(Box::new);
+    Ok(match pattern {
+        SimplePattern::Ref(name) => match defines.get(&name) {
+            Some(SimplePattern::Element(..)) => SimplePattern::Ref(name),
+            Some(_) if expanding.contains(&name) => return Err(Error::Recursion(name)),
+            Some(referenced) => {
+                expanding.push(name);
+                let pattern = expand(referenced.clone(), defines, expanding)?;
+                expanding.pop();
+                pattern
+            }
+            None => return Err(Error::UndefinedReference(name)),
+        },
+        SimplePattern::Choice(a, b) => {
+            let a = expand_box(a)?;
+            SimplePattern::Choice(a, expand_box(b)?)
+        }
+        SimplePattern::Interleave(a, b) => {
+            let a = expand_box(a)?;
+            SimplePattern::Interleave(a, expand_box(b)?)
+        }
+        SimplePattern::Group(a, b) => {
+            let a = expand_box(a)?;
+            SimplePattern::Group(a, expand_box(b)?)
+        }
+        SimplePattern::OneOrMore(p) => SimplePattern::OneOrMore(expand_box(p)?),
+        SimplePattern::List(p) => SimplePattern::List(expand_box(p)?),
+        SimplePattern::Attribute(name_class, p) => {
+            SimplePattern::Attribute(name_class, expand_box(p)?)
+        }
+        SimplePattern::Element(name_class, p) => SimplePattern::Element(name_class, expand_box(p)?),
+        SimplePattern::Data {
+            datatype_library,
+            data_type,
+            params,
+            except,
+        } => SimplePattern::Data {
+            datatype_library,
+            data_type,
+            params,
+            except: except.map(expand_box).transpose()?,
+        },
+        pattern => pattern,
+    })
+}
+
+/// Applies the rules of sections 4.20 and 4.21, which remove `notAllowed`
+/// and `empty` wherever they do not change the meaning.
+fn reduce(pattern: SimplePattern) -> SimplePattern {
+    use SimplePattern::*;
+
+    match pattern {
+        Attribute(name_class, p) => match reduce(*p) {
+            NotAllowed => NotAllowed,
+            p => Attribute(name_class, Box::new(p)),
+        },
+        List(p) => match reduce(*p) {
+            NotAllowed => NotAllowed,
+            p => List(Box::new(p)),
+        },
+        OneOrMore(p) => match reduce(*p) {
+            NotAllowed => NotAllowed,
+            Empty => Empty,
+            p => OneOrMore(Box::new(p)),
+        },
+        Group(a, b) => match (reduce(*a), reduce(*b)) {
+            (NotAllowed, _) | (_, NotAllowed) => NotAllowed,
+            (Empty, p) | (p, Empty) => p,
+            (a, b) => Group(Box::new(a), Box::new(b)),
+        },
+        Interleave(a, b) => match (reduce(*a), reduce(*b)) {
+            (NotAllowed, _) | (_, NotAllowed) => NotAllowed,
+            (Empty, p) | (p, Empty) => p,
+            (a, b) => Interleave(Box::new(a), Box::new(b)),
+        },
+        Choice(a, b) => match (reduce(*a), reduce(*b)) {
+            (NotAllowed, p) | (p, NotAllowed) => p,
+            (Empty, Empty) => Empty,
+            (p, Empty) => Choice(Box::new(Empty), Box::new(p)),
+            (a, b) => Choice(Box::new(a), Box::new(b)),
+        },
+        Data {
+            datatype_library,
+            data_type,
+            params,
+            except,
+        } => Data {
+            datatype_library,
+            data_type,
+            params,
+            except: except.map(|p| reduce(*p)).and_then(|p| match p {
+                NotAllowed => None,
+                p => Some(Box::new(p)),
+            }),
+        },
+        Element(name_class, p) => Element(name_class, Box::new(reduce(*p))),
+        p => p,
+    }
+}
+
+fn references(pattern: &SimplePattern, names: &mut Vec<String>) {
+    match pattern {
+        SimplePattern::Ref(name) => names.push(name.clone()),
+        SimplePattern::Choice(a, b)
+        | SimplePattern::Interleave(a, b)
+        | SimplePattern::Group(a, b) => {
+            references(a, names);
+            references(b, names);
+        }
+        SimplePattern::OneOrMore(p)
+        | SimplePattern::List(p)
+        | SimplePattern::Attribute(_, p)
+        | SimplePattern::Element(_, p) => references(p, names),
+        SimplePattern::Data {
+            except: Some(p), ..
+        } => references(p, names),
+        _ => {}
+    }
+}
+
+fn name_class_of(name_class: &NameClass, env: &Env) -> Result<SimpleNameClass> {
+    Ok(match name_class {
+        NameClass::Name(name) => {
+            let env = env.with(&name.ns, &name.datatype_library);
+            SimpleNameClass::Name(env.ns, name.name.trim().to_owned())
+        }
+        NameClass::AnyName(any_name) => {
+            let env = env.with(&any_name.ns, &any_name.datatype_library);
+            let except = except_name_class(&any_name.except, &env)?;
+            if except
+                .as_deref()
+                .is_some_and(|except| has_wildcard(except, false))
+            {
+                return Err(Error::Invalid("`anyName` excludes `anyName`".to_owned()));
+            }
+            SimpleNameClass::AnyName(except)
+        }
+        NameClass::NsName(ns_name) => {
+            let env = env.with(&ns_name.ns, &ns_name.datatype_library);
+            let except = except_name_class(&ns_name.except, &env)?;
+            if except
+                .as_deref()
+                .is_some_and(|except| has_wildcard(except, true))
+            {
+                return Err(Error::Invalid("`nsName` excludes a wildcard".to_owned()));
+            }
+            SimpleNameClass::NsName(env.ns, except)
+        }
+        NameClass::Choice(choice) => {
+            let env = env.with(&choice.ns, &choice.datatype_library);
+            let name_classes = choice
+                .content
+                .iter()
+                .map(|name_class| name_class_of(name_class, &env))
+                .collect::<Result<Vec<_>>>()?;
+            fold("choice", name_classes, SimpleNameClass::Choice)?
+        }
+    })
+}
+
+fn except_name_class(
+    except: &Option<ExceptNameClass>,
+    env: &Env,
+) -> Result<Option<Box<SimpleNameClass>>> {
+    let Some(except) = except else {
+        return Ok(None);
+    };
+    let env = env.with(&except.ns, &except.datatype_library);
+    let name_classes = except
+        .content
+        .iter()
+        .map(|name_class| name_class_of(name_class, &env))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some(Box::new(fold(
+        "except",
+        name_classes,
+        SimpleNameClass::Choice,
+    )?)))
+}
+
+/// Whether a name class contains `anyName`, or also `nsName` if
+/// `ns_name` is set.
+fn has_wildcard(name_class: &SimpleNameClass, ns_name: bool) -> bool {
+    match name_class {
+        SimpleNameClass::AnyName(_) => true,
+        SimpleNameClass::NsName(..) => ns_name,
+        SimpleNameClass::Name(..) => false,
+        SimpleNameClass::Choice(a, b) => has_wildcard(a, ns_name) || has_wildcard(b, ns_name),
+    }
+}
+
+/// Whether a name class names `xmlns` or the namespace of declarations.
+fn names_xmlns(name_class: &SimpleNameClass) -> bool {
+    match name_class {
+        SimpleNameClass::Name(ns, local_name) => {
+            (ns.is_empty() && local_name == "xmlns") || ns == XMLNS_NAMESPACE
+        }
+        SimpleNameClass::NsName(ns, _) => ns == XMLNS_NAMESPACE,
+        SimpleNameClass::Choice(a, b) => names_xmlns(a) || names_xmlns(b),
+        SimpleNameClass::AnyName(_) => false,
+    }
+}
+
+/// Nests a list into binary compounds from the left, as in `((a, b), c)`.
+fn fold<T>(parent: &str, items: Vec<T>, compound: fn(Box<T>, Box<T>) -> T) -> Result<T> {
+    let mut items = items.into_iter();
+    let first = items
+        .next()
+        .ok_or_else(|| Error::Invalid(format!("`{}` is empty", parent)))?;
+    Ok(items.fold(first, |a, b| compound(Box::new(a), Box::new(b))))
+}
+
+fn unique_name(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(name) {
+        return name.to_owned();
+    }
+    (2..)
+        .map(|n| format!("{}.{}", name, n))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use quick_xml::de::from_str;
+    use quick_xml::se::to_string;
+
+    use document::resolver::MapResolver;
+
+    use crate::{
+        load_grammar, parse_grammar, simplify_grammar, simplify_grammar_file, Error, Pattern,
+        SimpleNameClass, SimplePattern,
+    };
+
+    fn name(ns: &str, local_name: &str) -> SimpleNameClass {
+        SimpleNameClass::Name(ns.to_owned(), local_name.to_owned())
+    }
+
+    #[test]
+    fn simplifies_the_schema_for_relaxng() -> Result<(), anyhow::Error> {
+        let simple = simplify_grammar_file("../../schema/relaxng.rng")?;
+
+        assert!(!simple.defines.is_empty());
+        for pattern in simple.defines.values() {
+            assert!(matches!(pattern, SimplePattern::Element(..)));
+        }
+
+        let written = to_string(&Pattern::Grammar(simple.to_grammar()))?;
+        let reread = from_str::<Pattern>(&written)?;
+        assert_eq!(simplify_grammar(&reread, "")?, simple);
+        Ok(())
+    }
+
+    #[test]
+    fn desugars_and_combines() -> Result<(), anyhow::Error> {
+        let data = r#"<grammar xmlns="http://relaxng.org/ns/structure/1.0" xmlns:x="urn:x" ns="urn:d">
+  <start><ref name="doc"/></start>
+  <define name="doc"><element name="x:doc"><ref name="body"/></element></define>
+  <div datatypeLibrary="urn:types">
+    <define name="body" combine="interleave"><optional><attribute name="a"/></optional></define>
+    <define name="body" combine="interleave"><mixed><element name="p"><data type="t"/></element></mixed></define>
+  </div>
+  <define name="unused"><element name="u"><empty/></element></define>
+</grammar>"#;
+        let simple = simplify_grammar(&parse_grammar(data)?, "")?;
+
+        assert_eq!(simple.start, SimplePattern::Ref("doc".to_owned()));
+        assert_eq!(simple.defines.len(), 2);
+        let SimplePattern::Element(doc, content) = &simple.defines["doc"] else {
+            panic!("expected an element");
+        };
+        assert_eq!(doc, &name("urn:x", "doc"));
+
+        let attribute = SimplePattern::Attribute(name("", "a"), Box::new(SimplePattern::Text));
+        let optional = SimplePattern::Choice(Box::new(SimplePattern::Empty), Box::new(attribute));
+        let mixed = SimplePattern::Interleave(
+            Box::new(SimplePattern::Ref("p".to_owned())),
+            Box::new(SimplePattern::Text),
+        );
+        assert_eq!(
+            **content,
+            SimplePattern::Interleave(Box::new(optional), Box::new(mixed))
+        );
+
+        let SimplePattern::Element(p, content) = &simple.defines["p"] else {
+            panic!("expected an element");
+        };
+        assert_eq!(p, &name("urn:d", "p"));
+        assert!(matches!(
+            &**content,
+            SimplePattern::Data { datatype_library, data_type, .. }
+                if datatype_library == "urn:types" && data_type == "t"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn resolves_includes_and_nested_grammars() -> Result<(), anyhow::Error> {
+        let directory = std::env::temp_dir().join("relaxng-simplify-include");
+        fs::create_dir_all(&directory)?;
+        fs::write(
+            directory.join("base.rng"),
+            r#"<grammar xmlns="http://relaxng.org/ns/structure/1.0">
+  <start><element name="base"><ref name="item"/></element></start>
+  <define name="item"><element name="old"><notAllowed/></element></define>
+</grammar>"#,
+        )?;
+        fs::write(
+            directory.join("leaf.rng"),
+            r#"<element name="leaf" xmlns="http://relaxng.org/ns/structure/1.0"><value>x</value></element>"#,
+        )?;
+        let main = directory.join("main.rng");
+        fs::write(
+            &main,
+            r#"<grammar xmlns="http://relaxng.org/ns/structure/1.0">
+  <include href="base.rng">
+    <define name="item">
+      <grammar>
+        <start><choice><parentRef name="leaf"/><notAllowed/></choice></start>
+      </grammar>
+    </define>
+  </include>
+  <define name="leaf"><externalRef href="leaf.rng" ns="urn:leaf"/></define>
+</grammar>"#,
+        )?;
+        let simple = simplify_grammar_file(&main)?;
+
+        assert_eq!(simple.start, SimplePattern::Ref("base".to_owned()));
+        let keys = simple
+            .defines
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["base", "leaf"]);
+        assert_eq!(
+            simple.defines["base"],
+            SimplePattern::Element(
+                name("", "base"),
+                Box::new(SimplePattern::Ref("leaf".to_owned()))
+            )
+        );
+        assert_eq!(
+            simple.defines["leaf"],
+            SimplePattern::Element(
+                name("urn:leaf", "leaf"),
+                Box::new(SimplePattern::Value {
+                    datatype_library: String::new(),
+                    data_type: "token".to_owned(),
+                    ns: "urn:leaf".to_owned(),
+                    value: "x".to_owned(),
+                })
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fetches_documents_with_a_resolver() -> Result<(), anyhow::Error> {
+        let resolver: MapResolver = [
+            (
+                "http://example.org/main.rng",
+                r#"<grammar xmlns="http://relaxng.org/ns/structure/1.0">
+  <start><element name="doc"><externalRef href="parts/item.rnc"/></element></start>
+</grammar>"#,
+            ),
+            (
+                "http://example.org/parts/item.rnc",
+                "element item { external \"../loop.rng\" }",
+            ),
+            (
+                "http://example.org/loop.rng",
+                r#"<externalRef href="parts/item.rnc" xmlns="http://relaxng.org/ns/structure/1.0"/>"#,
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        assert!(matches!(
+            load_grammar(&resolver, "http://example.org/main.rng"),
+            Err(Error::Recursion(uri)) if uri == "http://example.org/parts/item.rnc"
+        ));
+        assert!(matches!(
+            load_grammar(&resolver, "http://example.org/missing.rng"),
+            Err(Error::Io(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_broken_grammars() -> Result<(), anyhow::Error> {
+        let grammar = |body: &str| {
+            let data = format!(
+                r#"<grammar xmlns="http://relaxng.org/ns/structure/1.0">{}</grammar>"#,
+                body
+            );
+            simplify_grammar(&parse_grammar(&data).expect("parses"), "")
+        };
+
+        assert!(matches!(
+            grammar(r#"<start><ref name="a"/></start>"#),
+            Err(Error::UndefinedReference(name)) if name == "a"
+        ));
+        assert!(matches!(
+            grammar(r#"<define name="a"><empty/></define>"#),
+            Err(Error::MissingStart)
+        ));
+        assert!(matches!(
+            grammar(
+                r#"<start><ref name="a"/></start>
+                <define name="a"><empty/></define><define name="a"><text/></define>"#
+            ),
+            Err(Error::Combine(name)) if name == "a"
+        ));
+        assert!(matches!(
+            grammar(
+                r#"<start><ref name="a"/></start>
+                <define name="a"><optional><ref name="a"/></optional></define>"#
+            ),
+            Err(Error::Recursion(name)) if name == "a"
+        ));
+        assert!(matches!(
+            parse_grammar(
+                r#"<element name="p:a" xmlns="http://relaxng.org/ns/structure/1.0"><empty/></element>"#
+            ),
+            Err(Error::UndeclaredPrefix(prefix)) if prefix == "p"
+        ));
+        Ok(())
+    }
+}
@@ -3,17 +3,17 @@ use serde::{Deserialize, Serialize};
 use crate::pattern::Pattern;
 use crate::{Combine, DatatypeLibrary, Namespace};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Start {
-    #[serde(rename = "@ns")]
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
     pub ns: Namespace,
 
-    #[serde(rename = "@datatypeLibrary")]
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
     pub datatype_library: DatatypeLibrary,
 
-    #[serde(rename = "@combine")]
+    #[serde(rename = "@combine", skip_serializing_if = "Option::is_none")]
     pub combine: Option<Combine>,
 
-    #[serde(rename = "$value")]
+    #[serde(rename = "$value", skip_serializing_if = "Option::is_none")]
     pub content: Option<Vec<Pattern>>,
 }
@@ -0,0 +1,1044 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use document::chars::is_whitespace;
+use document::node::{Document, Element, Node, NodeId};
+use document::position::Location;
+
+use crate::datatypes::{tokens, Context, Datatypes, XsdDatatypes};
+use crate::error::{Error, Result, ValidationError, ValidationErrorKind};
+use crate::simple::{SimpleGrammar, SimpleNameClass, SimplePattern};
+
+/// The index of an interned term.
+type Id = usize;
+
+const EMPTY: Id = 0;
+const NOT_ALLOWED: Id = 1;
+const TEXT: Id = 2;
+
+/// A pattern as the derivative algorithm sees it. Terms are interned, so
+/// equal patterns share an id and derivatives can be cached by id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Term {
+    Empty,
+    NotAllowed,
+    Text,
+    Choice(Id, Id),
+    Interleave(Id, Id),
+    Group(Id, Id),
+    OneOrMore(Id),
+    List(Id),
+    /// A datatype, by index, and the patterns it excludes.
+    Data(usize, Option<Id>),
+    Value(usize),
+    /// A name class, by index, and the pattern of the value.
+    Attribute(usize, Id),
+    /// The definition of an element, by index.
+    Element(usize),
+    /// The rest of an element's content, then what follows the element.
+    After(Id, Id),
+}
+
+#[derive(Debug, Clone)]
+struct Terms {
+    terms: Vec<Term>,
+    nullable: Vec<bool>,
+    ids: HashMap<Term, Id>,
+}
+
+impl Terms {
+    fn new() -> Terms {
+        let mut terms = Terms {
+            terms: Vec::new(),
+            nullable: Vec::new(),
+            ids: HashMap::new(),
+        };
+        terms.intern(Term::Empty);
+        terms.intern(Term::NotAllowed);
+        terms.intern(Term::Text);
+        terms
+    }
+
+    fn get(&self, id: Id) -> Term {
+        self.terms[id]
+    }
+
+    /// Whether the pattern matches the empty sequence.
+    fn nullable(&self, id: Id) -> bool {
+        self.nullable[id]
+    }
+
+    fn intern(&mut self, term: Term) -> Id {
+        if let Some(&id) = self.ids.get(&term) {
+            return id;
+        }
+        let nullable = match term {
+            Term::Empty | Term::Text => true,
+            Term::Choice(a, b) => self.nullable[a] || self.nullable[b],
+            Term::Interleave(a, b) | Term::Group(a, b) => self.nullable[a] && self.nullable[b],
+            Term::OneOrMore(p) => self.nullable[p],
+            _ => false,
+        };
+        let id = self.terms.len();
+        self.terms.push(term);
+        self.nullable.push(nullable);
+        self.ids.insert(term, id);
+        id
+    }
+
+    fn choice(&mut self, a: Id, b: Id) -> Id {
+        match (a, b) {
+            (NOT_ALLOWED, p) | (p, NOT_ALLOWED) => p,
+            (a, b) if a == b => a,
+            (a, b) => self.intern(Term::Choice(a.min(b), a.max(b))),
+        }
+    }
+
+    fn group(&mut self, a: Id, b: Id) -> Id {
+        match (a, b) {
+            (NOT_ALLOWED, _) | (_, NOT_ALLOWED) => NOT_ALLOWED,
+            (EMPTY, p) | (p, EMPTY) => p,
+            (a, b) => self.intern(Term::Group(a, b)),
+        }
+    }
+
+    fn interleave(&mut self, a: Id, b: Id) -> Id {
+        match (a, b) {
+            (NOT_ALLOWED, _) | (_, NOT_ALLOWED) => NOT_ALLOWED,
+            (EMPTY, p) | (p, EMPTY) => p,
+            (a, b) => self.intern(Term::Interleave(a, b)),
+        }
+    }
+
+    fn after(&mut self, a: Id, b: Id) -> Id {
+        match (a, b) {
+            (NOT_ALLOWED, _) | (_, NOT_ALLOWED) => NOT_ALLOWED,
+            (a, b) => self.intern(Term::After(a, b)),
+        }
+    }
+
+    fn one_or_more(&mut self, p: Id) -> Id {
+        match p {
+            NOT_ALLOWED | EMPTY => p,
+            p => self.intern(Term::OneOrMore(p)),
+        }
+    }
+
+    /// Applies `f` to what follows the current element in each
+    /// alternative of `p`.
+    fn apply_after(&mut self, p: Id, f: &dyn Fn(&mut Terms, Id) -> Id) -> Id {
+        match self.get(p) {
+            Term::After(a, b) => {
+                let b = f(self, b);
+                self.after(a, b)
+            }
+            Term::Choice(a, b) => {
+                let a = self.apply_after(a, f);
+                let b = self.apply_after(b, f);
+                self.choice(a, b)
+            }
+            _ => NOT_ALLOWED,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DataTerm {
+    library: String,
+    data_type: String,
+    params: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone)]
+struct ValueTerm {
+    library: String,
+    data_type: String,
+    ns: String,
+    value: String,
+}
+
+/// Validates documents against a simplified grammar, using the derivative
+/// algorithm of James Clark's "An algorithm for RELAX NG validation".
+/// Derivatives are cached across documents, so reusing one validator for
+/// many documents gets faster as it goes.
+#[derive(Clone)]
+pub struct Validator {
+    terms: Terms,
+    start: Id,
+    name_classes: Vec<SimpleNameClass>,
+    /// The name class and content of each element definition.
+    elements: Vec<(usize, Id)>,
+    data: Vec<DataTerm>,
+    values: Vec<ValueTerm>,
+    datatypes: Arc<dyn Datatypes + Send + Sync>,
+    start_tag_open: HashMap<(Id, String, String), Id>,
+    start_tag_close: HashMap<Id, Id>,
+}
+
+impl Validator {
+    /// Prepares a grammar that uses the built-in and XML Schema datatypes.
+    pub fn new(grammar: &SimpleGrammar) -> Result<Validator> {
+        Validator::with_datatypes(grammar, XsdDatatypes)
+    }
+
+    pub fn with_datatypes<D>(grammar: &SimpleGrammar, datatypes: D) -> Result<Validator>
+    where
+        D: Datatypes + Send + Sync + 'static,
+    {
+        let mut validator = Validator {
+            terms: Terms::new(),
+            start: NOT_ALLOWED,
+            name_classes: Vec::new(),
+            elements: Vec::new(),
+            data: Vec::new(),
+            values: Vec::new(),
+            datatypes: Arc::new(datatypes),
+            start_tag_open: HashMap::new(),
+            start_tag_close: HashMap::new(),
+        };
+
+        let indices = grammar
+            .defines
+            .keys()
+            .enumerate()
+            .map(|(index, name)| (name.as_str(), index))
+            .collect::<HashMap<_, _>>();
+        let mut contents = Vec::new();
+        for (name, pattern) in &grammar.defines {
+            let SimplePattern::Element(name_class, content) = pattern else {
+                return Err(Error::Invalid(format!(
+                    "`{}` does not define an element",
+                    name
+                )));
+            };
+            validator.name_classes.push(name_class.clone());
+            validator
+                .elements
+                .push((validator.name_classes.len() - 1, NOT_ALLOWED));
+            contents.push(content);
+        }
+        for (index, content) in contents.into_iter().enumerate() {
+            validator.elements[index].1 = validator.compile(content, &indices)?;
+        }
+        validator.start = validator.compile(&grammar.start, &indices)?;
+        Ok(validator)
+    }
+
+    fn compile(&mut self, pattern: &SimplePattern, indices: &HashMap<&str, usize>) -> Result<Id> {
+        Ok(match pattern {
+            SimplePattern::Empty => EMPTY,
+            SimplePattern::NotAllowed => NOT_ALLOWED,
+            SimplePattern::Text => TEXT,
+            SimplePattern::Choice(a, b) => {
+                let (a, b) = (self.compile(a, indices)?, self.compile(b, indices)?);
+                self.terms.choice(a, b)
+            }
+            SimplePattern::Interleave(a, b) => {
+                let (a, b) = (self.compile(a, indices)?, self.compile(b, indices)?);
+                self.terms.interleave(a, b)
+            }
+            SimplePattern::Group(a, b) => {
+                let (a, b) = (self.compile(a, indices)?, self.compile(b, indices)?);
+                self.terms.group(a, b)
+            }
+            SimplePattern::OneOrMore(p) => {
+                let p = self.compile(p, indices)?;
+                self.terms.one_or_more(p)
+            }
+            SimplePattern::List(p) => {
+                let p = self.compile(p, indices)?;
+                self.terms.intern(Term::List(p))
+            }
+            SimplePattern::Data {
+                datatype_library,
+                data_type,
+                params,
+                except,
+            } => {
+                self.datatypes
+                    .check(datatype_library, data_type, params)
+                    .map_err(Error::UnsupportedDatatype)?;
+                let except = match except {
+                    Some(except) => Some(self.compile(except, indices)?),
+                    None => None,
+                };
+                self.data.push(DataTerm {
+                    library: datatype_library.clone(),
+                    data_type: data_type.clone(),
+                    params: params.clone(),
+                });
+                self.terms.intern(Term::Data(self.data.len() - 1, except))
+            }
+            SimplePattern::Value {
+                datatype_library,
+                data_type,
+                ns,
+                value,
+            } => {
+                self.datatypes
+                    .check(datatype_library, data_type, &[])
+                    .map_err(Error::UnsupportedDatatype)?;
+                self.values.push(ValueTerm {
+                    library: datatype_library.clone(),
+                    data_type: data_type.clone(),
+                    ns: ns.clone(),
+                    value: value.clone(),
+                });
+                self.terms.intern(Term::Value(self.values.len() - 1))
+            }
+            SimplePattern::Attribute(name_class, p) => {
+                let p = self.compile(p, indices)?;
+                self.name_classes.push(name_class.clone());
+                self.terms
+                    .intern(Term::Attribute(self.name_classes.len() - 1, p))
+            }
+            SimplePattern::Element(..) => {
+                return Err(Error::Invalid(
+                    "an element outside a definition is not in simple form".to_owned(),
+                ))
+            }
+            SimplePattern::Ref(name) => match indices.get(name.as_str()) {
+                Some(&index) => self.terms.intern(Term::Element(index)),
+                None => return Err(Error::UndefinedReference(name.clone())),
+            },
+        })
+    }
+
+    /// Checks a document, reporting every mismatch. After an error the
+    /// offending node is skipped, so later errors are still found.
+    pub fn validate(
+        &mut self,
+        document: &Document,
+    ) -> std::result::Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let rest = self.element(document, document.root, self.start, &mut errors);
+        if errors.is_empty() && !self.terms.nullable(rest) {
+            if let Some(Node::Element(element)) = document.node(document.root) {
+                errors.push(ValidationError {
+                    kind: ValidationErrorKind::IncompleteContent {
+                        element: element.name.to_string(),
+                        expected: self.expected(rest),
+                    },
+                    location: element.location.clone(),
+                });
+            }
+        }
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+
+    /// Matches the element `id` against `pattern`, giving what may follow
+    /// it. An element that is not allowed leaves `pattern` as it is.
+    fn element(
+        &mut self,
+        document: &Document,
+        id: NodeId,
+        pattern: Id,
+        errors: &mut Vec<ValidationError>,
+    ) -> Id {
+        let Some(Node::Element(element)) = document.node(id) else {
+            return pattern;
+        };
+        let ns = element.name.namespace.as_deref().unwrap_or_default();
+        let name = element.name.to_string();
+        let error = |kind| ValidationError {
+            kind,
+            location: element.location.clone(),
+        };
+        let context = Context::new(document, id);
+
+        let mut p = self.start_tag_open(pattern, ns, &element.name.local_name);
+        if p == NOT_ALLOWED {
+            errors.push(error(ValidationErrorKind::UnexpectedElement {
+                name,
+                expected: self.expected(pattern),
+            }));
+            return pattern;
+        }
+
+        for attribute in &element.attributes {
+            let attribute_ns = attribute.name.namespace.as_deref().unwrap_or_default();
+            let local_name = &attribute.name.local_name;
+            let next = self.attribute(p, attribute_ns, local_name, &attribute.value, &context);
+            if next != NOT_ALLOWED {
+                p = next;
+                continue;
+            }
+            let kind = if self.allows_attribute(p, attribute_ns, local_name) {
+                ValidationErrorKind::InvalidAttributeValue {
+                    element: name.clone(),
+                    name: attribute.name.to_string(),
+                    value: attribute.value.clone(),
+                }
+            } else {
+                ValidationErrorKind::UnexpectedAttribute {
+                    element: name.clone(),
+                    name: attribute.name.to_string(),
+                    expected: self.expected_attributes(p),
+                }
+            };
+            errors.push(error(kind));
+        }
+
+        p = match self.start_tag_close(p, false) {
+            NOT_ALLOWED => {
+                let mut expected = BTreeSet::new();
+                self.required_attributes(p, &mut expected);
+                errors.push(error(ValidationErrorKind::MissingAttributes {
+                    element: name.clone(),
+                    expected: expected.into_iter().collect(),
+                }));
+                self.start_tag_close(p, true)
+            }
+            closed => closed,
+        };
+
+        p = self.children(document, id, element, p, &name, errors);
+
+        match self.end_tag(p, false) {
+            NOT_ALLOWED => {
+                errors.push(error(ValidationErrorKind::IncompleteContent {
+                    element: name,
+                    expected: self.expected(p),
+                }));
+                match self.end_tag(p, true) {
+                    NOT_ALLOWED => pattern,
+                    rest => rest,
+                }
+            }
+            rest => rest,
+        }
+    }
+
+    /// Matches the content of an element. Adjacent text and CDATA form one
+    /// text; when there are child elements, text that is only whitespace
+    /// is ignored.
+    fn children(
+        &mut self,
+        document: &Document,
+        id: NodeId,
+        element: &Element,
+        mut p: Id,
+        name: &str,
+        errors: &mut Vec<ValidationError>,
+    ) -> Id {
+        let context = Context::new(document, id);
+        let mixed = element
+            .children
+            .iter()
+            .any(|child| matches!(document.node(*child), Some(Node::Element(_))));
+        let mut text = String::new();
+        let mut location = None;
+
+        for &child in &element.children {
+            let (data, child_location) = match document.node(child) {
+                Some(Node::Text(node)) => (&node.data, &node.location),
+                Some(Node::CData(node)) => (&node.data, &node.location),
+                Some(Node::Element(_)) => {
+                    p = self.text(p, &text, location.take(), mixed, name, &context, errors);
+                    text.clear();
+                    p = self.element(document, child, p, errors);
+                    continue;
+                }
+                _ => continue,
+            };
+            if location.is_none() {
+                location = child_location.clone();
+            }
+            text.push_str(data);
+        }
+        self.text(p, &text, location, mixed, name, &context, errors)
+    }
+
+    /// Matches a text of the content, giving `p` back unchanged if it does
+    /// not match.
+    #[allow(clippy::too_many_arguments)]
+    fn text(
+        &mut self,
+        p: Id,
+        text: &str,
+        location: Option<Location>,
+        mixed: bool,
+        name: &str,
+        context: &Context,
+        errors: &mut Vec<ValidationError>,
+    ) -> Id {
+        let blank = text.chars().all(is_whitespace);
+        if mixed && blank {
+            return p;
+        }
+        let mut next = self.text_deriv(p, text, context);
+        if blank {
+            next = self.terms.choice(p, next);
+        }
+        if next != NOT_ALLOWED {
+            return next;
+        }
+        errors.push(ValidationError {
+            kind: ValidationErrorKind::UnexpectedText {
+                element: name.to_owned(),
+                text: text.trim().to_owned(),
+                expected: self.expected(p),
+            },
+            location,
+        });
+        p
+    }
+
+    fn start_tag_open(&mut self, p: Id, ns: &str, local_name: &str) -> Id {
+        let key = (p, ns.to_owned(), local_name.to_owned());
+        if let Some(&derivative) = self.start_tag_open.get(&key) {
+            return derivative;
+        }
+        let derivative = match self.terms.get(p) {
+            Term::Choice(a, b) => {
+                let a = self.start_tag_open(a, ns, local_name);
+                let b = self.start_tag_open(b, ns, local_name);
+                self.terms.choice(a, b)
+            }
+            Term::Element(index) => {
+                let (name_class, content) = self.elements[index];
+                match self.name_classes[name_class].contains(ns, local_name) {
+                    true => self.terms.after(content, EMPTY),
+                    false => NOT_ALLOWED,
+                }
+            }
+            Term::Interleave(a, b) => {
+                let da = self.start_tag_open(a, ns, local_name);
+                let da = self
+                    .terms
+                    .apply_after(da, &|terms, x| terms.interleave(x, b));
+                let db = self.start_tag_open(b, ns, local_name);
+                let db = self
+                    .terms
+                    .apply_after(db, &|terms, x| terms.interleave(a, x));
+                self.terms.choice(da, db)
+            }
+            Term::OneOrMore(q) => {
+                let rest = self.terms.choice(p, EMPTY);
+                let dq = self.start_tag_open(q, ns, local_name);
+                self.terms.apply_after(dq, &|terms, x| terms.group(x, rest))
+            }
+            Term::Group(a, b) => {
+                let da = self.start_tag_open(a, ns, local_name);
+                let da = self.terms.apply_after(da, &|terms, x| terms.group(x, b));
+                match self.terms.nullable(a) {
+                    true => {
+                        let db = self.start_tag_open(b, ns, local_name);
+                        self.terms.choice(da, db)
+                    }
+                    false => da,
+                }
+            }
+            Term::After(a, b) => {
+                let da = self.start_tag_open(a, ns, local_name);
+                self.terms.apply_after(da, &|terms, x| terms.after(x, b))
+            }
+            _ => NOT_ALLOWED,
+        };
+        self.start_tag_open.insert(key, derivative);
+        derivative
+    }
+
+    fn attribute(
+        &mut self,
+        p: Id,
+        ns: &str,
+        local_name: &str,
+        value: &str,
+        context: &Context,
+    ) -> Id {
+        match self.terms.get(p) {
+            Term::After(a, b) => {
+                let da = self.attribute(a, ns, local_name, value, context);
+                self.terms.after(da, b)
+            }
+            Term::Choice(a, b) => {
+                let (da, db) = (
+                    self.attribute(a, ns, local_name, value, context),
+                    self.attribute(b, ns, local_name, value, context),
+                );
+                self.terms.choice(da, db)
+            }
+            Term::Group(a, b) => {
+                let (da, db) = (
+                    self.attribute(a, ns, local_name, value, context),
+                    self.attribute(b, ns, local_name, value, context),
+                );
+                let first = self.terms.group(da, b);
+                let second = self.terms.group(a, db);
+                self.terms.choice(first, second)
+            }
+            Term::Interleave(a, b) => {
+                let (da, db) = (
+                    self.attribute(a, ns, local_name, value, context),
+                    self.attribute(b, ns, local_name, value, context),
+                );
+                let first = self.terms.interleave(da, b);
+                let second = self.terms.interleave(a, db);
+                self.terms.choice(first, second)
+            }
+            Term::OneOrMore(q) => {
+                let dq = self.attribute(q, ns, local_name, value, context);
+                let rest = self.terms.choice(p, EMPTY);
+                self.terms.group(dq, rest)
+            }
+            Term::Attribute(name_class, q) => {
+                let matches = self.name_classes[name_class].contains(ns, local_name)
+                    && ((self.terms.nullable(q) && value.chars().all(is_whitespace)) || {
+                        let dq = self.text_deriv(q, value, context);
+                        self.terms.nullable(dq)
+                    });
+                match matches {
+                    true => EMPTY,
+                    false => NOT_ALLOWED,
+                }
+            }
+            _ => NOT_ALLOWED,
+        }
+    }
+
+    /// Ends the attributes of a start tag. Attributes still expected make
+    /// it fail, unless `recover` drops them.
+    fn start_tag_close(&mut self, p: Id, recover: bool) -> Id {
+        if !recover {
+            if let Some(&derivative) = self.start_tag_close.get(&p) {
+                return derivative;
+            }
+        }
+        let derivative = match self.terms.get(p) {
+            Term::After(a, b) => {
+                let da = self.start_tag_close(a, recover);
+                self.terms.after(da, b)
+            }
+            Term::Choice(a, b) => {
+                let (da, db) = (
+                    self.start_tag_close(a, recover),
+                    self.start_tag_close(b, recover),
+                );
+                self.terms.choice(da, db)
+            }
+            Term::Group(a, b) => {
+                let (da, db) = (
+                    self.start_tag_close(a, recover),
+                    self.start_tag_close(b, recover),
+                );
+                self.terms.group(da, db)
+            }
+            Term::Interleave(a, b) => {
+                let (da, db) = (
+                    self.start_tag_close(a, recover),
+                    self.start_tag_close(b, recover),
+                );
+                self.terms.interleave(da, db)
+            }
+            Term::OneOrMore(q) => {
+                let dq = self.start_tag_close(q, recover);
+                self.terms.one_or_more(dq)
+            }
+            Term::Attribute(..) if recover => EMPTY,
+            Term::Attribute(..) => NOT_ALLOWED,
+            _ => p,
+        };
+        if !recover {
+            self.start_tag_close.insert(p, derivative);
+        }
+        derivative
+    }
+
+    fn text_deriv(&mut self, p: Id, text: &str, context: &Context) -> Id {
+        match self.terms.get(p) {
+            Term::Choice(a, b) => {
+                let (da, db) = (
+                    self.text_deriv(a, text, context),
+                    self.text_deriv(b, text, context),
+                );
+                self.terms.choice(da, db)
+            }
+            Term::Interleave(a, b) => {
+                let (da, db) = (
+                    self.text_deriv(a, text, context),
+                    self.text_deriv(b, text, context),
+                );
+                let first = self.terms.interleave(da, b);
+                let second = self.terms.interleave(a, db);
+                self.terms.choice(first, second)
+            }
+            Term::Group(a, b) => {
+                let da = self.text_deriv(a, text, context);
+                let first = self.terms.group(da, b);
+                match self.terms.nullable(a) {
+                    true => {
+                        let db = self.text_deriv(b, text, context);
+                        self.terms.choice(first, db)
+                    }
+                    false => first,
+                }
+            }
+            Term::After(a, b) => {
+                let da = self.text_deriv(a, text, context);
+                self.terms.after(da, b)
+            }
+            Term::OneOrMore(q) => {
+                let dq = self.text_deriv(q, text, context);
+                let rest = self.terms.choice(p, EMPTY);
+                self.terms.group(dq, rest)
+            }
+            Term::Text => TEXT,
+            Term::Value(index) => {
+                let value = &self.values[index];
+                let equal = self.datatypes.equal(
+                    &value.library,
+                    &value.data_type,
+                    &value.value,
+                    &value.ns,
+                    text,
+                    context,
+                );
+                match equal {
+                    true => EMPTY,
+                    false => NOT_ALLOWED,
+                }
+            }
+            Term::Data(index, except) => {
+                let data = &self.data[index];
+                let allowed = self.datatypes.allows(
+                    &data.library,
+                    &data.data_type,
+                    &data.params,
+                    text,
+                    context,
+                ) && except.is_none_or(|except| {
+                    let de = self.text_deriv(except, text, context);
+                    !self.terms.nullable(de)
+                });
+                match allowed {
+                    true => EMPTY,
+                    false => NOT_ALLOWED,
+                }
+            }
+            Term::List(q) => {
+                let mut dq = q;
+                for token in tokens(text) {
+                    dq = self.text_deriv(dq, token, context);
+                }
+                match self.terms.nullable(dq) {
+                    true => EMPTY,
+                    false => NOT_ALLOWED,
+                }
+            }
+            _ => NOT_ALLOWED,
+        }
+    }
+
+    /// Ends an element, giving what may follow it. Content still expected
+    /// makes it fail, unless `recover` drops it.
+    fn end_tag(&mut self, p: Id, recover: bool) -> Id {
+        match self.terms.get(p) {
+            Term::After(a, b) if recover || self.terms.nullable(a) => b,
+            Term::Choice(a, b) => {
+                let (da, db) = (self.end_tag(a, recover), self.end_tag(b, recover));
+                self.terms.choice(da, db)
+            }
+            _ => NOT_ALLOWED,
+        }
+    }
+
+    /// Describes what may come next in the content of `p`.
+    fn expected(&self, p: Id) -> Vec<String> {
+        let mut expected = BTreeSet::new();
+        self.collect_expected(p, &mut expected);
+        expected.into_iter().collect()
+    }
+
+    fn collect_expected(&self, p: Id, expected: &mut BTreeSet<String>) {
+        match self.terms.get(p) {
+            Term::Choice(a, b) | Term::Interleave(a, b) => {
+                self.collect_expected(a, expected);
+                self.collect_expected(b, expected);
+            }
+            Term::Group(a, b) => {
+                self.collect_expected(a, expected);
+                if self.terms.nullable(a) {
+                    self.collect_expected(b, expected);
+                }
+            }
+            Term::OneOrMore(q) | Term::List(q) | Term::After(q, _) => {
+                self.collect_expected(q, expected)
+            }
+            Term::Element(index) => {
+                let name_class = &self.name_classes[self.elements[index].0];
+                expected.insert(name_class.to_string());
+            }
+            Term::Text => {
+                expected.insert("text".to_owned());
+            }
+            Term::Data(index, _) => {
+                expected.insert(self.data[index].data_type.clone());
+            }
+            Term::Value(index) => {
+                expected.insert(format!("\"{}\"", self.values[index].value));
+            }
+            _ => {}
+        }
+    }
+
+    /// Describes the attributes `p` still expects.
+    fn expected_attributes(&self, p: Id) -> Vec<String> {
+        let mut expected = BTreeSet::new();
+        self.visit_attributes(p, &mut |name_class| {
+            expected.insert(name_class.to_string());
+        });
+        expected.into_iter().collect()
+    }
+
+    /// Collects the attributes `p` cannot do without: those outside any
+    /// choice that has an alternative without attributes left.
+    fn required_attributes(&mut self, p: Id, required: &mut BTreeSet<String>) {
+        match self.terms.get(p) {
+            Term::Choice(a, b) => {
+                let closes = |validator: &mut Validator, q| {
+                    validator.start_tag_close(q, false) != NOT_ALLOWED
+                };
+                if !closes(self, a) && !closes(self, b) {
+                    self.required_attributes(a, required);
+                    self.required_attributes(b, required);
+                }
+            }
+            Term::Interleave(a, b) | Term::Group(a, b) => {
+                self.required_attributes(a, required);
+                self.required_attributes(b, required);
+            }
+            Term::OneOrMore(q) | Term::After(q, _) => self.required_attributes(q, required),
+            Term::Attribute(name_class, _) => {
+                required.insert(self.name_classes[name_class].to_string());
+            }
+            _ => {}
+        }
+    }
+
+    fn allows_attribute(&self, p: Id, ns: &str, local_name: &str) -> bool {
+        let mut allowed = false;
+        self.visit_attributes(p, &mut |name_class| {
+            allowed |= name_class.contains(ns, local_name);
+        });
+        allowed
+    }
+
+    fn visit_attributes(&self, p: Id, visit: &mut dyn FnMut(&SimpleNameClass)) {
+        match self.terms.get(p) {
+            Term::Choice(a, b) | Term::Interleave(a, b) | Term::Group(a, b) => {
+                self.visit_attributes(a, visit);
+                self.visit_attributes(b, visit);
+            }
+            Term::OneOrMore(q) | Term::After(q, _) => self.visit_attributes(q, visit),
+            Term::Attribute(name_class, _) => visit(&self.name_classes[name_class]),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use document::node::Document;
+    use document::ParseOptions;
+
+    use crate::{
+        parse_compact, parse_grammar, simplify_grammar, simplify_grammar_file, BuiltinDatatypes,
+        Error, ValidationErrorKind, Validator,
+    };
+
+    const GRAMMAR: &str = r#"<grammar xmlns="http://relaxng.org/ns/structure/1.0" ns="urn:doc">
+  <start>
+    <element name="doc">
+      <attribute name="version"><choice><value>1</value><value>2</value></choice></attribute>
+      <optional><attribute name="lang"/></optional>
+      <interleave>
+        <element name="title"><text/></element>
+        <zeroOrMore><element name="tags"><list><oneOrMore><data type="token"/></oneOrMore></list></element></zeroOrMore>
+      </interleave>
+      <oneOrMore><ref name="para"/></oneOrMore>
+    </element>
+  </start>
+  <define name="para"><element name="p"><mixed><zeroOrMore><element name="b"><text/></element></zeroOrMore></mixed></element></define>
+</grammar>"#;
+
+    fn validator() -> Result<Validator, Error> {
+        Validator::new(&simplify_grammar(&parse_grammar(GRAMMAR)?, "")?)
+    }
+
+    #[test]
+    fn accepts_valid_documents() -> Result<(), anyhow::Error> {
+        let mut validator = validator()?;
+        let documents = [
+            r#"<doc xmlns="urn:doc" version="1"><title>T</title><p>text</p></doc>"#,
+            r#"<doc xmlns="urn:doc" version=" 2 " lang="en">
+  <tags>a b  c</tags>
+  <title/>
+  <tags>d</tags>
+  <p>Some <b>bold</b> text</p>
+  <p/>
+</doc>"#,
+        ];
+        for xml in documents {
+            assert_eq!(
+                validator.validate(&Document::parse(xml)?),
+                Ok(()),
+                "{}",
+                xml
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn reports_what_was_expected() -> Result<(), anyhow::Error> {
+        let mut validator = validator()?;
+        let xml = r#"<doc xmlns="urn:doc" version="3" extra="x">
+  <title>T</title>
+  <q/>
+  <p>fine</p>
+  <p><b>a <b/></b></p>
+</doc>"#;
+        let options = ParseOptions {
+            locations: true,
+            source_uri: None,
+            ..ParseOptions::default()
+        };
+        let errors = validator
+            .validate(&Document::parse_with(xml, &options)?)
+            .unwrap_err();
+        let kinds = errors.iter().map(|e| e.kind.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                ValidationErrorKind::InvalidAttributeValue {
+                    element: "doc".to_owned(),
+                    name: "version".to_owned(),
+                    value: "3".to_owned(),
+                },
+                ValidationErrorKind::UnexpectedAttribute {
+                    element: "doc".to_owned(),
+                    name: "extra".to_owned(),
+                    expected: vec!["lang".to_owned(), "version".to_owned()],
+                },
+                ValidationErrorKind::MissingAttributes {
+                    element: "doc".to_owned(),
+                    expected: vec!["version".to_owned()],
+                },
+                ValidationErrorKind::UnexpectedElement {
+                    name: "q".to_owned(),
+                    expected: vec!["{urn:doc}p".to_owned(), "{urn:doc}tags".to_owned()],
+                },
+                ValidationErrorKind::UnexpectedElement {
+                    name: "b".to_owned(),
+                    expected: vec!["text".to_owned()],
+                },
+            ]
+        );
+        assert_eq!(
+            errors[3].to_string(),
+            "3:3: element `q` not allowed here; expected `{urn:doc}p`, `{urn:doc}tags`"
+        );
+
+        let errors = validator
+            .validate(&Document::parse(
+                r#"<doc xmlns="urn:doc" version="1">x<title/></doc>"#,
+            )?)
+            .unwrap_err();
+        let kinds = errors.iter().map(|e| e.kind.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                ValidationErrorKind::UnexpectedText {
+                    element: "doc".to_owned(),
+                    text: "x".to_owned(),
+                    expected: vec!["{urn:doc}tags".to_owned(), "{urn:doc}title".to_owned()],
+                },
+                ValidationErrorKind::IncompleteContent {
+                    element: "doc".to_owned(),
+                    expected: vec!["{urn:doc}p".to_owned(), "{urn:doc}tags".to_owned()],
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn checks_xml_schema_datatypes() -> Result<(), anyhow::Error> {
+        let grammar = parse_compact(
+            r#"default namespace = "urn:doc"
+start = element doc {
+  attribute size { xsd:int { minInclusive = "1" maxExclusive = "10" } },
+  attribute kind { xsd:QName "item" },
+  list { xsd:date* },
+  element when { xsd:dateTime { explicitTimezone = "required" } }?
+}"#,
+        )?;
+        let mut validator = Validator::new(&simplify_grammar(&grammar, "")?)?;
+        let valid = [
+            r#"<doc xmlns="urn:doc" size=" 9 " kind="item">2000-02-29 1999-12-31</doc>"#,
+            r#"<d:doc xmlns:d="urn:doc" xmlns="urn:doc" size="1" kind="d:item"/>"#,
+        ];
+        for xml in valid {
+            assert_eq!(
+                validator.validate(&Document::parse(xml)?),
+                Ok(()),
+                "{}",
+                xml
+            );
+        }
+        let invalid = [
+            r#"<doc xmlns="urn:doc" size="10" kind="item"/>"#,
+            r#"<doc xmlns="urn:doc" xmlns:d="urn:other" size="1" kind="d:item"/>"#,
+            r#"<doc xmlns="urn:doc" size="1" kind="item">2001-02-29</doc>"#,
+            r#"<doc xmlns="urn:doc" size="1" kind="item"><when>2001-01-01T00:00:00</when></doc>"#,
+        ];
+        for xml in invalid {
+            assert!(
+                validator.validate(&Document::parse(xml)?).is_err(),
+                "{}",
+                xml
+            );
+        }
+
+        let wrong_facet = parse_compact("element doc { xsd:integer { length = \"1\" } }")?;
+        assert!(matches!(
+            Validator::new(&simplify_grammar(&wrong_facet, "")?),
+            Err(Error::UnsupportedDatatype(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn validates_the_schema_for_relaxng_against_itself() -> Result<(), anyhow::Error> {
+        let grammar = simplify_grammar_file("../../schema/relaxng.rng")?;
+        assert!(matches!(
+            Validator::with_datatypes(&grammar, BuiltinDatatypes),
+            Err(Error::UnsupportedDatatype(_))
+        ));
+
+        let mut validator = Validator::new(&grammar)?;
+        let schema = Document::parse(&read_to_string("../../schema/relaxng.rng")?)?;
+        assert_eq!(validator.validate(&schema), Ok(()));
+
+        let broken = Document::parse(
+            r#"<grammar xmlns="http://relaxng.org/ns/structure/1.0"><start><element/></start></grammar>"#,
+        )?;
+        let errors = validator.validate(&broken).unwrap_err();
+        assert!(matches!(
+            &errors[..],
+            [e] if matches!(
+                &e.kind,
+                ValidationErrorKind::IncompleteContent { element, expected }
+                    if element == "element"
+                        && expected.contains(&"{http://relaxng.org/ns/structure/1.0}name".to_owned())
+            )
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{DatatypeLibrary, NCName, Namespace};
+
+/// A `value` pattern: one value of a datatype, `token` by default.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Value {
+    #[serde(rename = "@ns", skip_serializing_if = "Option::is_none")]
+    pub ns: Namespace,
+
+    #[serde(rename = "@datatypeLibrary", skip_serializing_if = "Option::is_none")]
+    pub datatype_library: DatatypeLibrary,
+
+    #[serde(rename = "@type", skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<NCName>,
+
+    #[serde(rename = "$text", default)]
+    pub value: String,
+}
@@ -1,3 +1,88 @@
-pub struct AnyAttrGroup;
-pub struct DefRef;
-pub struct Occurs;
+use crate::complex_types::{All, Element, ExplicitGroup, GroupRef};
+use crate::elements::Any;
+use crate::groups::Particle;
+use crate::simple_types::AllNNI;
+
+/// The `occurs` attribute group: the number of times a particle may
+/// appear, with the default of one for absent attributes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Occurs {
+    pub min: u32,
+    pub max: AllNNI,
+}
+
+impl Occurs {
+    pub fn new(min_occurs: Option<u32>, max_occurs: Option<AllNNI>) -> Occurs {
+        Occurs {
+            min: min_occurs.unwrap_or(1),
+            max: max_occurs.unwrap_or(AllNNI::Bounded(1)),
+        }
+    }
+
+    /// Whether the particle may be left out.
+    pub fn is_optional(&self) -> bool {
+        self.min == 0
+    }
+
+    /// Whether the particle may appear more than once.
+    pub fn is_repeated(&self) -> bool {
+        match self.max {
+            AllNNI::Bounded(max) => max > 1,
+            AllNNI::Unbounded => true,
+        }
+    }
+}
+
+impl Default for Occurs {
+    fn default() -> Self {
+        Occurs::new(None, None)
+    }
+}
+
+/// Implements `occurs()` for the components carrying the `occurs`
+/// attribute group.
+macro_rules! occurs {
+    ($($name:ident),*) => {$(
+        impl $name {
+            pub fn occurs(&self) -> Occurs {
+                Occurs::new(self.min_occurs, self.max_occurs)
+            }
+        }
+    )*};
+}
+
+occurs!(Element, GroupRef, All, ExplicitGroup, Any);
+
+impl Particle {
+    pub fn occurs(&self) -> Occurs {
+        match self {
+            Particle::Element(element) => element.occurs(),
+            Particle::Group(group) => group.occurs(),
+            Particle::Choice(group) | Particle::Sequence(group) => group.occurs(),
+            Particle::Any(any) => any.occurs(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::attribute_groups::Occurs;
+    use crate::complex_types::Element;
+    use crate::groups::Particle;
+    use crate::simple_types::AllNNI;
+
+    #[test]
+    fn applies_occurrence_defaults() {
+        let element = Element {
+            min_occurs: Some(0),
+            ..Element::default()
+        };
+        let occurs = Particle::Element(element).occurs();
+
+        assert_eq!(occurs.max, AllNNI::Bounded(1));
+        assert!(occurs.is_optional());
+        assert!(!occurs.is_repeated());
+        assert!(Occurs::new(None, Some(AllNNI::Unbounded)).is_repeated());
+        assert_eq!(Occurs::default().min, 1);
+    }
+}
@@ -1,36 +1,931 @@
-pub struct All;
-pub struct AltType;
-pub struct Annotated;
-pub struct Assertion;
-pub struct Attribute;
-pub struct AttributeGroup;
-pub struct AttributeGroupRef;
-pub struct ComplexRestrictionType;
-pub struct ComplexType;
-pub struct Element;
-pub struct ExplicitGroup;
-pub struct ExtensionType;
-pub struct Facet;
-pub struct Group;
-pub struct GroupRef;
-pub struct IntFacet;
-pub struct Keybase;
-pub struct LocalComplexType;
-pub struct LocalElement;
-pub struct LocalSimpleType;
-pub struct NamedAttributeGroup;
-pub struct NamedGroup;
-pub struct NoFixedFacet;
-pub struct NumFacet;
-pub struct OpenAttrs;
-pub struct RealGroup;
-pub struct RestrictionType;
-pub struct SimpleExplicitGroup;
-pub struct SimpleExtensionType;
-pub struct SimpleRestrictionType;
-pub struct SimpleType;
-pub struct TopLevelAttribute;
-pub struct TopLevelComplexType;
-pub struct TopLevelElement;
-pub struct TopLevelSimpleType;
-pub struct Wildcard;
+use serde::{Deserialize, Serialize};
+
+use crate::elements::{
+    Annotation, AnyAttribute, ComplexContent, ExplicitTimezone, Field, OpenAttrs, OpenContent,
+    Selector, SimpleContent, WhiteSpace,
+};
+use crate::groups::{
+    AttrDecl, Facet, IdentityConstraint, Particle, RealGroup, SimpleDerivation, TypeDefParticle,
+};
+use crate::simple_types::{AllNNI, DerivationSet, FormChoice, QName, Use, XpathDefaultNamespace};
+
+/// A top-level or local attribute declaration, or a reference to a
+/// top-level one (`xs:attribute`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Attribute {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@name", skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "@ref", skip_serializing_if = "Option::is_none")]
+    pub ref_attr: Option<QName>,
+    #[serde(rename = "@type", skip_serializing_if = "Option::is_none")]
+    pub type_attr: Option<QName>,
+    #[serde(rename = "@use", skip_serializing_if = "Option::is_none")]
+    pub use_attr: Option<Use>,
+    #[serde(rename = "@default", skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    #[serde(rename = "@fixed", skip_serializing_if = "Option::is_none")]
+    pub fixed: Option<String>,
+    #[serde(rename = "@form", skip_serializing_if = "Option::is_none")]
+    pub form: Option<FormChoice>,
+    #[serde(rename = "@targetNamespace", skip_serializing_if = "Option::is_none")]
+    pub target_namespace: Option<String>,
+    #[serde(rename = "@inheritable", skip_serializing_if = "Option::is_none")]
+    pub inheritable: Option<bool>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(
+        rename(serialize = "xs:simpleType", deserialize = "simpleType"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub simple_type: Option<SimpleType>,
+}
+
+/// An `xs:assert` in a complex type, or an `xs:assertion` facet.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Assertion {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@test", skip_serializing_if = "Option::is_none")]
+    pub test: Option<String>,
+    #[serde(
+        rename = "@xpathDefaultNamespace",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub xpath_default_namespace: Option<XpathDefaultNamespace>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+}
+
+/// A top-level (named) or local (anonymous) complex type definition
+/// (`xs:complexType`).
+///
+/// A type has simple content, complex content, or the particle, attributes
+/// and assertions of a restriction of `xs:anyType`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(from = "RawComplexType", into = "RawComplexType")]
+pub struct ComplexType {
+    pub id: Option<String>,
+    pub open_attrs: OpenAttrs,
+    pub name: Option<String>,
+    pub mixed: Option<bool>,
+    pub abstract_attr: Option<bool>,
+    pub final_attr: Option<DerivationSet>,
+    pub block: Option<DerivationSet>,
+    pub default_attributes_apply: Option<bool>,
+    pub annotation: Option<Annotation>,
+    pub simple_content: Option<SimpleContent>,
+    pub complex_content: Option<ComplexContent>,
+    pub open_content: Option<OpenContent>,
+    pub particle: Option<TypeDefParticle>,
+    pub attributes: Vec<AttrDecl>,
+    pub any_attribute: Option<AnyAttribute>,
+    pub asserts: Vec<Assertion>,
+}
+
+/// A restriction in complex content.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(from = "RawDerivation", into = "RawDerivation")]
+pub struct ComplexRestrictionType {
+    pub id: Option<String>,
+    pub open_attrs: OpenAttrs,
+    pub base: QName,
+    pub annotation: Option<Annotation>,
+    pub open_content: Option<OpenContent>,
+    pub particle: Option<TypeDefParticle>,
+    pub attributes: Vec<AttrDecl>,
+    pub any_attribute: Option<AnyAttribute>,
+    pub asserts: Vec<Assertion>,
+}
+
+/// An extension in complex or simple content. Extensions of simple content
+/// have no open content or particle.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(from = "RawDerivation", into = "RawDerivation")]
+pub struct ExtensionType {
+    pub id: Option<String>,
+    pub open_attrs: OpenAttrs,
+    pub base: QName,
+    pub annotation: Option<Annotation>,
+    pub open_content: Option<OpenContent>,
+    pub particle: Option<TypeDefParticle>,
+    pub attributes: Vec<AttrDecl>,
+    pub any_attribute: Option<AnyAttribute>,
+    pub asserts: Vec<Assertion>,
+}
+
+/// A restriction in simple content.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(from = "RawDerivation", into = "RawDerivation")]
+pub struct SimpleRestrictionType {
+    pub id: Option<String>,
+    pub open_attrs: OpenAttrs,
+    pub base: QName,
+    pub annotation: Option<Annotation>,
+    pub simple_type: Option<SimpleType>,
+    pub facets: Vec<Facet>,
+    pub attributes: Vec<AttrDecl>,
+    pub any_attribute: Option<AnyAttribute>,
+    pub asserts: Vec<Assertion>,
+}
+
+// The XML representation of complex type definitions, with the particle,
+// attribute declarations and facets in one list.
+#[derive(Deserialize, Serialize)]
+struct RawComplexType {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    open_attrs: OpenAttrs,
+    #[serde(rename = "@name", skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(rename = "@mixed", skip_serializing_if = "Option::is_none")]
+    mixed: Option<bool>,
+    #[serde(rename = "@abstract", skip_serializing_if = "Option::is_none")]
+    abstract_attr: Option<bool>,
+    #[serde(rename = "@final", skip_serializing_if = "Option::is_none")]
+    final_attr: Option<DerivationSet>,
+    #[serde(rename = "@block", skip_serializing_if = "Option::is_none")]
+    block: Option<DerivationSet>,
+    #[serde(
+        rename = "@defaultAttributesApply",
+        skip_serializing_if = "Option::is_none"
+    )]
+    default_attributes_apply: Option<bool>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    annotation: Option<Annotation>,
+    #[serde(
+        rename(serialize = "xs:simpleContent", deserialize = "simpleContent"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    simple_content: Option<SimpleContent>,
+    #[serde(
+        rename(serialize = "xs:complexContent", deserialize = "complexContent"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    complex_content: Option<ComplexContent>,
+    #[serde(
+        rename(serialize = "xs:openContent", deserialize = "openContent"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    open_content: Option<OpenContent>,
+    #[serde(rename = "$value", default)]
+    content: Vec<TypeContent>,
+    #[serde(
+        rename(serialize = "xs:anyAttribute", deserialize = "anyAttribute"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    any_attribute: Option<AnyAttribute>,
+    #[serde(
+        rename(serialize = "xs:assert", deserialize = "assert"),
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    asserts: Vec<Assertion>,
+}
+
+// The XML representation of derivations in simple and complex content.
+#[derive(Deserialize, Serialize)]
+struct RawDerivation {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    open_attrs: OpenAttrs,
+    #[serde(rename = "@base")]
+    base: QName,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    annotation: Option<Annotation>,
+    #[serde(
+        rename(serialize = "xs:openContent", deserialize = "openContent"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    open_content: Option<OpenContent>,
+    #[serde(
+        rename(serialize = "xs:simpleType", deserialize = "simpleType"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    simple_type: Option<SimpleType>,
+    #[serde(rename = "$value", default)]
+    content: Vec<TypeContent>,
+    #[serde(
+        rename(serialize = "xs:anyAttribute", deserialize = "anyAttribute"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    any_attribute: Option<AnyAttribute>,
+    #[serde(
+        rename(serialize = "xs:assert", deserialize = "assert"),
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    asserts: Vec<Assertion>,
+}
+
+#[derive(Deserialize, Serialize)]
+enum TypeContent {
+    #[serde(rename(serialize = "xs:group", deserialize = "group"))]
+    Group(GroupRef),
+    #[serde(rename(serialize = "xs:all", deserialize = "all"))]
+    All(All),
+    #[serde(rename(serialize = "xs:choice", deserialize = "choice"))]
+    Choice(ExplicitGroup),
+    #[serde(rename(serialize = "xs:sequence", deserialize = "sequence"))]
+    Sequence(ExplicitGroup),
+    #[serde(rename(serialize = "xs:attribute", deserialize = "attribute"))]
+    Attribute(Attribute),
+    #[serde(rename(serialize = "xs:attributeGroup", deserialize = "attributeGroup"))]
+    AttributeGroup(AttributeGroupRef),
+    #[serde(rename(serialize = "xs:minExclusive", deserialize = "minExclusive"))]
+    MinExclusive(ValueFacet),
+    #[serde(rename(serialize = "xs:minInclusive", deserialize = "minInclusive"))]
+    MinInclusive(ValueFacet),
+    #[serde(rename(serialize = "xs:maxExclusive", deserialize = "maxExclusive"))]
+    MaxExclusive(ValueFacet),
+    #[serde(rename(serialize = "xs:maxInclusive", deserialize = "maxInclusive"))]
+    MaxInclusive(ValueFacet),
+    #[serde(rename(serialize = "xs:totalDigits", deserialize = "totalDigits"))]
+    TotalDigits(NumFacet),
+    #[serde(rename(serialize = "xs:fractionDigits", deserialize = "fractionDigits"))]
+    FractionDigits(NumFacet),
+    #[serde(rename(serialize = "xs:length", deserialize = "length"))]
+    Length(NumFacet),
+    #[serde(rename(serialize = "xs:minLength", deserialize = "minLength"))]
+    MinLength(NumFacet),
+    #[serde(rename(serialize = "xs:maxLength", deserialize = "maxLength"))]
+    MaxLength(NumFacet),
+    #[serde(rename(serialize = "xs:enumeration", deserialize = "enumeration"))]
+    Enumeration(NoFixedFacet),
+    #[serde(rename(serialize = "xs:whiteSpace", deserialize = "whiteSpace"))]
+    WhiteSpace(WhiteSpace),
+    #[serde(rename(serialize = "xs:pattern", deserialize = "pattern"))]
+    Pattern(NoFixedFacet),
+    #[serde(rename(serialize = "xs:assertion", deserialize = "assertion"))]
+    Assertion(Assertion),
+    #[serde(rename(serialize = "xs:explicitTimezone", deserialize = "explicitTimezone"))]
+    ExplicitTimezone(ExplicitTimezone),
+}
+
+impl TypeContent {
+    fn from_facet(facet: Facet) -> TypeContent {
+        match facet {
+            Facet::MinExclusive(value) => TypeContent::MinExclusive(value),
+            Facet::MinInclusive(value) => TypeContent::MinInclusive(value),
+            Facet::MaxExclusive(value) => TypeContent::MaxExclusive(value),
+            Facet::MaxInclusive(value) => TypeContent::MaxInclusive(value),
+            Facet::TotalDigits(value) => TypeContent::TotalDigits(value),
+            Facet::FractionDigits(value) => TypeContent::FractionDigits(value),
+            Facet::Length(value) => TypeContent::Length(value),
+            Facet::MinLength(value) => TypeContent::MinLength(value),
+            Facet::MaxLength(value) => TypeContent::MaxLength(value),
+            Facet::Enumeration(value) => TypeContent::Enumeration(value),
+            Facet::WhiteSpace(value) => TypeContent::WhiteSpace(value),
+            Facet::Pattern(value) => TypeContent::Pattern(value),
+            Facet::Assertion(value) => TypeContent::Assertion(value),
+            Facet::ExplicitTimezone(value) => TypeContent::ExplicitTimezone(value),
+        }
+    }
+}
+
+/// The parts of a type definition's children held in `TypeContent`.
+#[derive(Default)]
+struct SplitContent {
+    particle: Option<TypeDefParticle>,
+    attributes: Vec<AttrDecl>,
+    facets: Vec<Facet>,
+}
+
+impl SplitContent {
+    fn new(content: Vec<TypeContent>) -> SplitContent {
+        let mut split = SplitContent::default();
+        for item in content {
+            let facet = match item {
+                TypeContent::Group(value) => {
+                    split.particle = Some(TypeDefParticle::Group(value));
+                    continue;
+                }
+                TypeContent::All(value) => {
+                    split.particle = Some(TypeDefParticle::All(value));
+                    continue;
+                }
+                TypeContent::Choice(value) => {
+                    split.particle = Some(TypeDefParticle::Choice(value));
+                    continue;
+                }
+                TypeContent::Sequence(value) => {
+                    split.particle = Some(TypeDefParticle::Sequence(value));
+                    continue;
+                }
+                TypeContent::Attribute(value) => {
+                    split.attributes.push(AttrDecl::Attribute(value));
+                    continue;
+                }
+                TypeContent::AttributeGroup(value) => {
+                    split.attributes.push(AttrDecl::AttributeGroup(value));
+                    continue;
+                }
+                TypeContent::MinExclusive(value) => Facet::MinExclusive(value),
+                TypeContent::MinInclusive(value) => Facet::MinInclusive(value),
+                TypeContent::MaxExclusive(value) => Facet::MaxExclusive(value),
+                TypeContent::MaxInclusive(value) => Facet::MaxInclusive(value),
+                TypeContent::TotalDigits(value) => Facet::TotalDigits(value),
+                TypeContent::FractionDigits(value) => Facet::FractionDigits(value),
+                TypeContent::Length(value) => Facet::Length(value),
+                TypeContent::MinLength(value) => Facet::MinLength(value),
+                TypeContent::MaxLength(value) => Facet::MaxLength(value),
+                TypeContent::Enumeration(value) => Facet::Enumeration(value),
+                TypeContent::WhiteSpace(value) => Facet::WhiteSpace(value),
+                TypeContent::Pattern(value) => Facet::Pattern(value),
+                TypeContent::Assertion(value) => Facet::Assertion(value),
+                TypeContent::ExplicitTimezone(value) => Facet::ExplicitTimezone(value),
+            };
+            split.facets.push(facet);
+        }
+        split
+    }
+
+    fn join(self) -> Vec<TypeContent> {
+        let particle = self.particle.map(|particle| match particle {
+            TypeDefParticle::Group(value) => TypeContent::Group(value),
+            TypeDefParticle::All(value) => TypeContent::All(value),
+            TypeDefParticle::Choice(value) => TypeContent::Choice(value),
+            TypeDefParticle::Sequence(value) => TypeContent::Sequence(value),
+        });
+        let attributes = self
+            .attributes
+            .into_iter()
+            .map(|attribute| match attribute {
+                AttrDecl::Attribute(value) => TypeContent::Attribute(value),
+                AttrDecl::AttributeGroup(value) => TypeContent::AttributeGroup(value),
+            });
+        let facets = self.facets.into_iter().map(TypeContent::from_facet);
+        particle
+            .into_iter()
+            .chain(facets)
+            .chain(attributes)
+            .collect()
+    }
+}
+
+impl From<RawComplexType> for ComplexType {
+    fn from(raw: RawComplexType) -> Self {
+        let split = SplitContent::new(raw.content);
+        ComplexType {
+            id: raw.id,
+            open_attrs: raw.open_attrs,
+            name: raw.name,
+            mixed: raw.mixed,
+            abstract_attr: raw.abstract_attr,
+            final_attr: raw.final_attr,
+            block: raw.block,
+            default_attributes_apply: raw.default_attributes_apply,
+            annotation: raw.annotation,
+            simple_content: raw.simple_content,
+            complex_content: raw.complex_content,
+            open_content: raw.open_content,
+            particle: split.particle,
+            attributes: split.attributes,
+            any_attribute: raw.any_attribute,
+            asserts: raw.asserts,
+        }
+    }
+}
+
+impl From<ComplexType> for RawComplexType {
+    fn from(value: ComplexType) -> Self {
+        let split = SplitContent {
+            particle: value.particle,
+            attributes: value.attributes,
+            facets: Vec::new(),
+        };
+        RawComplexType {
+            id: value.id,
+            open_attrs: value.open_attrs,
+            name: value.name,
+            mixed: value.mixed,
+            abstract_attr: value.abstract_attr,
+            final_attr: value.final_attr,
+            block: value.block,
+            default_attributes_apply: value.default_attributes_apply,
+            annotation: value.annotation,
+            simple_content: value.simple_content,
+            complex_content: value.complex_content,
+            open_content: value.open_content,
+            content: split.join(),
+            any_attribute: value.any_attribute,
+            asserts: value.asserts,
+        }
+    }
+}
+
+impl From<RawDerivation> for ComplexRestrictionType {
+    fn from(raw: RawDerivation) -> Self {
+        let split = SplitContent::new(raw.content);
+        ComplexRestrictionType {
+            id: raw.id,
+            open_attrs: raw.open_attrs,
+            base: raw.base,
+            annotation: raw.annotation,
+            open_content: raw.open_content,
+            particle: split.particle,
+            attributes: split.attributes,
+            any_attribute: raw.any_attribute,
+            asserts: raw.asserts,
+        }
+    }
+}
+
+impl From<ComplexRestrictionType> for RawDerivation {
+    fn from(value: ComplexRestrictionType) -> Self {
+        let split = SplitContent {
+            particle: value.particle,
+            attributes: value.attributes,
+            facets: Vec::new(),
+        };
+        RawDerivation {
+            id: value.id,
+            open_attrs: value.open_attrs,
+            base: value.base,
+            annotation: value.annotation,
+            open_content: value.open_content,
+            simple_type: None,
+            content: split.join(),
+            any_attribute: value.any_attribute,
+            asserts: value.asserts,
+        }
+    }
+}
+
+impl From<RawDerivation> for ExtensionType {
+    fn from(raw: RawDerivation) -> Self {
+        let split = SplitContent::new(raw.content);
+        ExtensionType {
+            id: raw.id,
+            open_attrs: raw.open_attrs,
+            base: raw.base,
+            annotation: raw.annotation,
+            open_content: raw.open_content,
+            particle: split.particle,
+            attributes: split.attributes,
+            any_attribute: raw.any_attribute,
+            asserts: raw.asserts,
+        }
+    }
+}
+
+impl From<ExtensionType> for RawDerivation {
+    fn from(value: ExtensionType) -> Self {
+        let split = SplitContent {
+            particle: value.particle,
+            attributes: value.attributes,
+            facets: Vec::new(),
+        };
+        RawDerivation {
+            id: value.id,
+            open_attrs: value.open_attrs,
+            base: value.base,
+            annotation: value.annotation,
+            open_content: value.open_content,
+            simple_type: None,
+            content: split.join(),
+            any_attribute: value.any_attribute,
+            asserts: value.asserts,
+        }
+    }
+}
+
+impl From<RawDerivation> for SimpleRestrictionType {
+    fn from(raw: RawDerivation) -> Self {
+        let split = SplitContent::new(raw.content);
+        SimpleRestrictionType {
+            id: raw.id,
+            open_attrs: raw.open_attrs,
+            base: raw.base,
+            annotation: raw.annotation,
+            simple_type: raw.simple_type,
+            facets: split.facets,
+            attributes: split.attributes,
+            any_attribute: raw.any_attribute,
+            asserts: raw.asserts,
+        }
+    }
+}
+
+impl From<SimpleRestrictionType> for RawDerivation {
+    fn from(value: SimpleRestrictionType) -> Self {
+        let split = SplitContent {
+            particle: None,
+            attributes: value.attributes,
+            facets: value.facets,
+        };
+        RawDerivation {
+            id: value.id,
+            open_attrs: value.open_attrs,
+            base: value.base,
+            annotation: value.annotation,
+            open_content: None,
+            simple_type: value.simple_type,
+            content: split.join(),
+            any_attribute: value.any_attribute,
+            asserts: value.asserts,
+        }
+    }
+}
+
+/// A top-level or local element declaration, or a reference to a top-level
+/// one (`xs:element`). The reference form is not allowed at the top level.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Element {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@name", skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "@ref", skip_serializing_if = "Option::is_none")]
+    pub ref_attr: Option<QName>,
+    #[serde(rename = "@minOccurs", skip_serializing_if = "Option::is_none")]
+    pub min_occurs: Option<u32>,
+    #[serde(rename = "@maxOccurs", skip_serializing_if = "Option::is_none")]
+    pub max_occurs: Option<AllNNI>,
+    #[serde(rename = "@type", skip_serializing_if = "Option::is_none")]
+    pub type_attr: Option<QName>,
+    #[serde(rename = "@substitutionGroup", skip_serializing_if = "Option::is_none")]
+    pub substitution_group: Option<QName>,
+    #[serde(rename = "@default", skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    #[serde(rename = "@fixed", skip_serializing_if = "Option::is_none")]
+    pub fixed: Option<String>,
+    #[serde(rename = "@nillable", skip_serializing_if = "Option::is_none")]
+    pub nillable: Option<bool>,
+    #[serde(rename = "@abstract", skip_serializing_if = "Option::is_none")]
+    pub abstract_attr: Option<bool>,
+    #[serde(rename = "@final", skip_serializing_if = "Option::is_none")]
+    pub final_attr: Option<DerivationSet>,
+    #[serde(rename = "@block", skip_serializing_if = "Option::is_none")]
+    pub block: Option<DerivationSet>,
+    #[serde(rename = "@form", skip_serializing_if = "Option::is_none")]
+    pub form: Option<FormChoice>,
+    #[serde(rename = "@targetNamespace", skip_serializing_if = "Option::is_none")]
+    pub target_namespace: Option<String>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(
+        rename(serialize = "xs:simpleType", deserialize = "simpleType"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub simple_type: Option<SimpleType>,
+    #[serde(
+        rename(serialize = "xs:complexType", deserialize = "complexType"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub complex_type: Option<Box<ComplexType>>,
+    #[serde(
+        rename(serialize = "xs:alternative", deserialize = "alternative"),
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub alternative: Vec<AltType>,
+    #[serde(rename = "$value", default)]
+    pub identity_constraint: Vec<IdentityConstraint>,
+}
+
+/// A type alternative (`xs:alternative`) of an element declaration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct AltType {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@test", skip_serializing_if = "Option::is_none")]
+    pub test: Option<String>,
+    #[serde(rename = "@type", skip_serializing_if = "Option::is_none")]
+    pub type_attr: Option<QName>,
+    #[serde(
+        rename = "@xpathDefaultNamespace",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub xpath_default_namespace: Option<XpathDefaultNamespace>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(
+        rename(serialize = "xs:simpleType", deserialize = "simpleType"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub simple_type: Option<SimpleType>,
+    #[serde(
+        rename(serialize = "xs:complexType", deserialize = "complexType"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub complex_type: Option<Box<ComplexType>>,
+}
+
+/// A top-level model group definition (`xs:group`).
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct NamedGroup {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@name")]
+    pub name: String,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(rename = "$value")]
+    pub particle: RealGroup,
+}
+
+/// A reference to a model group definition in a content model.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct GroupRef {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@ref")]
+    pub ref_attr: QName,
+    #[serde(rename = "@minOccurs", skip_serializing_if = "Option::is_none")]
+    pub min_occurs: Option<u32>,
+    #[serde(rename = "@maxOccurs", skip_serializing_if = "Option::is_none")]
+    pub max_occurs: Option<AllNNI>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+}
+
+/// An `xs:all` model group. Only elements, wildcards and group references
+/// may appear in it.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct All {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@minOccurs", skip_serializing_if = "Option::is_none")]
+    pub min_occurs: Option<u32>,
+    #[serde(rename = "@maxOccurs", skip_serializing_if = "Option::is_none")]
+    pub max_occurs: Option<AllNNI>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(rename = "$value", default)]
+    pub particles: Vec<Particle>,
+}
+
+/// An `xs:choice` or `xs:sequence` model group.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct ExplicitGroup {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@minOccurs", skip_serializing_if = "Option::is_none")]
+    pub min_occurs: Option<u32>,
+    #[serde(rename = "@maxOccurs", skip_serializing_if = "Option::is_none")]
+    pub max_occurs: Option<AllNNI>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(rename = "$value", default)]
+    pub particles: Vec<Particle>,
+}
+
+/// A top-level attribute group definition (`xs:attributeGroup`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct NamedAttributeGroup {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@name")]
+    pub name: String,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(rename = "$value", default)]
+    pub attributes: Vec<AttrDecl>,
+    #[serde(
+        rename(serialize = "xs:anyAttribute", deserialize = "anyAttribute"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub any_attribute: Option<AnyAttribute>,
+}
+
+/// A reference to an attribute group definition.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct AttributeGroupRef {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@ref")]
+    pub ref_attr: QName,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+}
+
+/// A `xs:unique` or `xs:key` constraint, or a reference to one.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Keybase {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@name", skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "@ref", skip_serializing_if = "Option::is_none")]
+    pub ref_attr: Option<QName>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(
+        rename(serialize = "xs:selector", deserialize = "selector"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub selector: Option<Selector>,
+    #[serde(
+        rename(serialize = "xs:field", deserialize = "field"),
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub field: Vec<Field>,
+}
+
+/// A top-level (named) or local (anonymous) simple type definition
+/// (`xs:simpleType`).
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct SimpleType {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@name", skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "@final", skip_serializing_if = "Option::is_none")]
+    pub final_attr: Option<DerivationSet>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(rename = "$value")]
+    pub derivation: SimpleDerivation,
+}
+
+/// A bound facet, whose value is in the value space of the restricted
+/// type.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct ValueFacet {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@value")]
+    pub value: String,
+    #[serde(rename = "@fixed", skip_serializing_if = "Option::is_none")]
+    pub fixed: Option<bool>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+}
+
+/// A facet that cannot be fixed: `xs:enumeration` and `xs:pattern`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct NoFixedFacet {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@value")]
+    pub value: String,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+}
+
+/// A facet whose value is a non-negative integer, such as `xs:length`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct NumFacet {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@value")]
+    pub value: u32,
+    #[serde(rename = "@fixed", skip_serializing_if = "Option::is_none")]
+    pub fixed: Option<bool>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+}
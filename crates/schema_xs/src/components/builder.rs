@@ -0,0 +1,1533 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use document::namespace::{Namespace, XML_NAMESPACE};
+use xsd_datatypes::{Builtin, Namespaces, XSD_NAMESPACE};
+
+use crate::complex_types::{
+    Attribute, ComplexType, Element, NamedAttributeGroup, NamedGroup, SimpleType,
+};
+use crate::components::{
+    AttributeDeclaration, AttributeGroupDefinition, AttributeUse, ComplexTypeDefinition,
+    Compositor, ContentType, DerivationMethod, ElementDeclaration, ExpandedName,
+    IdentityConstraintCategory, IdentityConstraintDefinition, ModelGroup, NamespaceConstraint,
+    NotationDeclaration, Particle, SchemaSet, SimpleTypeDefinition, Term, TypeDefinition, TypeRef,
+    ValueConstraint, Wildcard,
+};
+use crate::elements::{AnyAttribute, OpenAttrs, Schema};
+use crate::error::{Error, Result};
+use crate::facets;
+use crate::groups::{
+    self, AttrDecl, ComplexDerivation, IdentityConstraint, RealGroup, SchemaTop,
+    SimpleContentDerivation, SimpleDerivation, TypeDefParticle,
+};
+use crate::simple_types::{
+    AllNNI, DerivationControl, DerivationSet, FormChoice, ProcessContents, Use,
+};
+
+const TYPE: &str = "type definition";
+const ELEMENT: &str = "element declaration";
+const ATTRIBUTE: &str = "attribute declaration";
+const MODEL_GROUP: &str = "model group definition";
+const ATTRIBUTE_GROUP: &str = "attribute group definition";
+const NOTATION: &str = "notation declaration";
+const IDENTITY_CONSTRAINT: &str = "identity constraint";
+
+pub(super) fn build(documents: &[Schema]) -> Result<SchemaSet> {
+    let mut builder = Builder::default();
+    builder.set.type_definitions.extend(builtin_types());
+    for schema in documents {
+        for top in &schema.schema_top {
+            builder.index(Document::new(schema), top)?;
+        }
+    }
+
+    let types: Vec<_> = builder
+        .simple_types
+        .keys()
+        .chain(builder.complex_types.keys())
+        .cloned()
+        .collect();
+    for name in &types {
+        builder.ensure_type(name)?;
+    }
+    for name in builder.elements.keys().cloned().collect::<Vec<_>>() {
+        builder.ensure_element(&name)?;
+    }
+    for name in builder.attributes.keys().cloned().collect::<Vec<_>>() {
+        builder.ensure_attribute(&name)?;
+    }
+    for name in builder.groups.keys().cloned().collect::<Vec<_>>() {
+        builder.ensure_model_group(&name)?;
+    }
+    for name in builder.attribute_groups.keys().cloned().collect::<Vec<_>>() {
+        builder.ensure_attribute_group(&name)?;
+    }
+    builder.check_identity_constraints()?;
+    Ok(builder.set)
+}
+
+/// A schema document, which gives meaning to the names written in it, with
+/// the namespace declarations in scope where a name is written.
+#[derive(Clone, Copy)]
+struct Document<'a> {
+    schema: &'a Schema,
+    scope: Option<&'a Scope<'a>>,
+}
+
+/// The namespace declarations of an element below the document element,
+/// and of the elements around it.
+struct Scope<'a> {
+    namespaces: &'a [Namespace],
+    outer: Document<'a>,
+}
+
+impl<'a> Scope<'a> {
+    /// The document as seen from inside the element.
+    fn document(&'a self) -> Document<'a> {
+        match self.namespaces.is_empty() {
+            true => self.outer,
+            false => Document {
+                schema: self.outer.schema,
+                scope: Some(self),
+            },
+        }
+    }
+}
+
+impl<'a> Document<'a> {
+    fn new(schema: &'a Schema) -> Document<'a> {
+        Document {
+            schema,
+            scope: None,
+        }
+    }
+
+    /// The scope of an element that makes the declarations `namespaces`;
+    /// names in it are resolved with its [`Scope::document`].
+    fn enter<'b>(self, namespaces: &'b [Namespace]) -> Scope<'b>
+    where
+        'a: 'b,
+    {
+        Scope {
+            namespaces,
+            outer: self,
+        }
+    }
+
+    /// The document as seen from the `schema` element.
+    fn top(self) -> Document<'a> {
+        Document::new(self.schema)
+    }
+
+    fn target_namespace(self) -> Option<&'a str> {
+        self.schema.target_namespace.as_deref()
+    }
+
+    /// The name of a top-level component of the document.
+    fn name(self, local_name: &str) -> ExpandedName {
+        ExpandedName::new(self.target_namespace(), local_name)
+    }
+
+    /// The namespace of a local declaration, which is the target namespace
+    /// if the declaration is qualified.
+    fn local_namespace(
+        self,
+        form: Option<FormChoice>,
+        form_default: Option<FormChoice>,
+    ) -> Option<&'a str> {
+        match form.or(form_default) {
+            Some(FormChoice::Qualified) => self.target_namespace(),
+            _ => None,
+        }
+    }
+
+    /// Resolves a QName with the namespace declarations in scope.
+    /// Unprefixed names are in the default namespace.
+    fn resolve(self, qname: &str) -> Result<ExpandedName> {
+        let qname = qname.trim();
+        match qname.split_once(':') {
+            Some((prefix, local_name)) => {
+                let namespace = self
+                    .namespace_uri(Some(prefix))
+                    .ok_or_else(|| Error::UndeclaredPrefix(prefix.to_owned()))?;
+                Ok(ExpandedName::new(Some(&namespace), local_name))
+            }
+            None => Ok(ExpandedName::new(
+                self.namespace_uri(None).as_deref(),
+                qname,
+            )),
+        }
+    }
+
+    /// The declarations in scope, the innermost one for each prefix.
+    fn in_scope(self) -> Vec<Namespace> {
+        let mut scopes = Vec::new();
+        let mut scope = self.scope;
+        while let Some(inner) = scope {
+            scopes.push(inner.namespaces);
+            scope = inner.outer.scope;
+        }
+        let mut namespaces = self.schema.namespaces.clone();
+        for declarations in scopes.into_iter().rev() {
+            for declaration in declarations {
+                namespaces.retain(|namespace| namespace.prefix != declaration.prefix);
+                namespaces.push(declaration.clone());
+            }
+        }
+        namespaces
+    }
+}
+
+impl Namespaces for Document<'_> {
+    fn namespace_uri(&self, prefix: Option<&str>) -> Option<String> {
+        if prefix == Some("xml") {
+            return Some(XML_NAMESPACE.to_owned());
+        }
+        let find = |namespaces: &[Namespace]| {
+            namespaces
+                .iter()
+                .find(|namespace| namespace.prefix.as_deref() == prefix)
+                .map(|namespace| namespace.uri.clone())
+        };
+        let mut scope = self.scope;
+        let uri = loop {
+            match scope {
+                Some(inner) => match find(inner.namespaces) {
+                    Some(uri) => break Some(uri),
+                    None => scope = inner.outer.scope,
+                },
+                None => break find(&self.schema.namespaces),
+            }
+        };
+        uri.filter(|uri| !uri.is_empty())
+    }
+}
+
+/// Attribute uses as written on a complex type or attribute group, before
+/// those of the base type are merged in.
+#[derive(Default)]
+struct Attributes {
+    uses: Vec<AttributeUse>,
+    /// The attributes of the base type that a restriction removes.
+    prohibited: Vec<ExpandedName>,
+    wildcard: Option<Wildcard>,
+}
+
+impl Attributes {
+    fn push(&mut self, attribute_use: AttributeUse) -> Result<()> {
+        let name = &attribute_use.declaration.name;
+        if self.uses.iter().any(|u| &u.declaration.name == name) {
+            return Err(Error::Invalid(format!(
+                "attribute `{}` is declared twice",
+                name
+            )));
+        }
+        self.uses.push(attribute_use);
+        Ok(())
+    }
+}
+
+/// Builds components on demand, so that a component is built after those
+/// it is derived from, whatever the order of the documents.
+#[derive(Default)]
+struct Builder<'a> {
+    simple_types: BTreeMap<ExpandedName, (Document<'a>, &'a SimpleType)>,
+    complex_types: BTreeMap<ExpandedName, (Document<'a>, &'a ComplexType)>,
+    elements: BTreeMap<ExpandedName, (Document<'a>, &'a Element)>,
+    attributes: BTreeMap<ExpandedName, (Document<'a>, &'a Attribute)>,
+    groups: BTreeMap<ExpandedName, (Document<'a>, &'a NamedGroup)>,
+    attribute_groups: BTreeMap<ExpandedName, (Document<'a>, &'a NamedAttributeGroup)>,
+    /// The top-level components being built, to tell when one is made of
+    /// itself.
+    pending: BTreeSet<(&'static str, ExpandedName)>,
+    /// The identity constraints named by `ref`, checked once all are known.
+    constraint_references: Vec<ExpandedName>,
+    set: SchemaSet,
+}
+
+impl<'a> Builder<'a> {
+    fn index(&mut self, document: Document<'a>, top: &'a SchemaTop) -> Result<()> {
+        match top {
+            SchemaTop::SimpleType(simple_type) => {
+                let name = document.name(top_level_name(&simple_type.name, "simpleType")?);
+                if !is_builtin(&name) {
+                    self.check_type_name(&name)?;
+                    self.simple_types.insert(name, (document, simple_type));
+                }
+            }
+            SchemaTop::ComplexType(complex_type) => {
+                let name = document.name(top_level_name(&complex_type.name, "complexType")?);
+                if !is_builtin(&name) {
+                    self.check_type_name(&name)?;
+                    self.complex_types.insert(name, (document, complex_type));
+                }
+            }
+            SchemaTop::Group(group) => insert(
+                &mut self.groups,
+                MODEL_GROUP,
+                document.name(&group.name),
+                (document, group),
+            )?,
+            SchemaTop::AttributeGroup(group) => insert(
+                &mut self.attribute_groups,
+                ATTRIBUTE_GROUP,
+                document.name(&group.name),
+                (document, group),
+            )?,
+            SchemaTop::Element(element) => insert(
+                &mut self.elements,
+                ELEMENT,
+                document.name(top_level_name(&element.name, "element")?),
+                (document, element),
+            )?,
+            SchemaTop::Attribute(attribute) => insert(
+                &mut self.attributes,
+                ATTRIBUTE,
+                document.name(top_level_name(&attribute.name, "attribute")?),
+                (document, attribute),
+            )?,
+            SchemaTop::Notation(notation) => {
+                let name = document.name(&notation.name);
+                let declaration = NotationDeclaration {
+                    name: name.clone(),
+                    public: notation.public.clone(),
+                    system: notation.system.clone(),
+                };
+                insert(
+                    &mut self.set.notation_declarations,
+                    NOTATION,
+                    name,
+                    declaration,
+                )?
+            }
+            SchemaTop::Annotation(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Simple and complex types share a symbol space.
+    fn check_type_name(&self, name: &ExpandedName) -> Result<()> {
+        if self.simple_types.contains_key(name) || self.complex_types.contains_key(name) {
+            return Err(Error::Duplicate {
+                kind: TYPE,
+                name: name.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn enter(&mut self, kind: &'static str, name: &ExpandedName) -> Result<()> {
+        if !self.pending.insert((kind, name.clone())) {
+            return Err(Error::Circular {
+                kind,
+                name: name.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn leave(&mut self, kind: &'static str, name: &ExpandedName) {
+        self.pending.remove(&(kind, name.clone()));
+    }
+
+    fn ensure_type(&mut self, name: &ExpandedName) -> Result<()> {
+        if self.set.type_definitions.contains_key(name) {
+            return Ok(());
+        }
+        self.enter(TYPE, name)?;
+        let definition = if let Some(&(document, simple_type)) = self.simple_types.get(name) {
+            TypeDefinition::Simple(self.simple_type(document, simple_type, Some(name))?)
+        } else if let Some(&(document, complex_type)) = self.complex_types.get(name) {
+            TypeDefinition::Complex(self.complex_type(document, complex_type, Some(name))?)
+        } else {
+            return Err(undefined(TYPE, name));
+        };
+        self.leave(TYPE, name);
+        self.set.type_definitions.insert(name.clone(), definition);
+        Ok(())
+    }
+
+    fn ensure_element(&mut self, name: &ExpandedName) -> Result<()> {
+        if self.set.element_declarations.contains_key(name) {
+            return Ok(());
+        }
+        let &(document, element) = self
+            .elements
+            .get(name)
+            .ok_or_else(|| undefined(ELEMENT, name))?;
+        self.enter(ELEMENT, name)?;
+        let declaration = self.element_declaration(document, element, true)?;
+        self.leave(ELEMENT, name);
+        self.set
+            .element_declarations
+            .insert(name.clone(), declaration);
+        Ok(())
+    }
+
+    fn ensure_attribute(&mut self, name: &ExpandedName) -> Result<()> {
+        if self.set.attribute_declarations.contains_key(name) {
+            return Ok(());
+        }
+        let &(document, attribute) = self
+            .attributes
+            .get(name)
+            .ok_or_else(|| undefined(ATTRIBUTE, name))?;
+        let declaration = self.attribute_declaration(document, attribute, true)?;
+        self.set
+            .attribute_declarations
+            .insert(name.clone(), declaration);
+        Ok(())
+    }
+
+    fn ensure_model_group(&mut self, name: &ExpandedName) -> Result<()> {
+        if self.set.model_group_definitions.contains_key(name) {
+            return Ok(());
+        }
+        let &(document, group) = self
+            .groups
+            .get(name)
+            .ok_or_else(|| undefined(MODEL_GROUP, name))?;
+        self.enter(MODEL_GROUP, name)?;
+        let scope = document.enter(&group.open_attrs.namespaces);
+        let document = scope.document();
+        let model_group = match &group.particle {
+            RealGroup::All(all) => {
+                self.model_group(document, Compositor::All, &all.open_attrs, &all.particles)?
+            }
+            RealGroup::Choice(choice) => self.model_group(
+                document,
+                Compositor::Choice,
+                &choice.open_attrs,
+                &choice.particles,
+            )?,
+            RealGroup::Sequence(sequence) => self.model_group(
+                document,
+                Compositor::Sequence,
+                &sequence.open_attrs,
+                &sequence.particles,
+            )?,
+        };
+        self.leave(MODEL_GROUP, name);
+        self.set
+            .model_group_definitions
+            .insert(name.clone(), model_group);
+        Ok(())
+    }
+
+    fn ensure_attribute_group(&mut self, name: &ExpandedName) -> Result<()> {
+        if self.set.attribute_group_definitions.contains_key(name) {
+            return Ok(());
+        }
+        let &(document, group) = self
+            .attribute_groups
+            .get(name)
+            .ok_or_else(|| undefined(ATTRIBUTE_GROUP, name))?;
+        self.enter(ATTRIBUTE_GROUP, name)?;
+        let scope = document.enter(&group.open_attrs.namespaces);
+        let document = scope.document();
+        let attributes =
+            self.attributes(document, &group.attributes, group.any_attribute.as_ref())?;
+        self.leave(ATTRIBUTE_GROUP, name);
+        let definition = AttributeGroupDefinition {
+            name: name.clone(),
+            attribute_uses: attributes.uses,
+            attribute_wildcard: attributes.wildcard,
+        };
+        self.set
+            .attribute_group_definitions
+            .insert(name.clone(), definition);
+        Ok(())
+    }
+
+    /// A reference to the type `qname` names, which need not be built yet.
+    fn type_ref(&self, document: Document, qname: &str) -> Result<TypeRef> {
+        let name = document.resolve(qname)?;
+        if !self.set.type_definitions.contains_key(&name)
+            && !self.simple_types.contains_key(&name)
+            && !self.complex_types.contains_key(&name)
+        {
+            return Err(undefined(TYPE, &name));
+        }
+        Ok(TypeRef::Named(name))
+    }
+
+    /// A reference to the type `qname` names, built so that types derived
+    /// from it can be.
+    fn base_type(&mut self, document: Document, qname: &str) -> Result<TypeRef> {
+        let name = document.resolve(qname)?;
+        self.ensure_type(&name)?;
+        Ok(TypeRef::Named(name))
+    }
+
+    /// The value space of a built simple type.
+    fn datatype<'b>(&'b self, reference: &'b TypeRef) -> Result<&'b xsd_datatypes::SimpleType> {
+        match self.set.type_definition(reference) {
+            TypeDefinition::Simple(definition) => Ok(&definition.datatype),
+            TypeDefinition::Complex(_) => Err(Error::Invalid(format!(
+                "{} is not a simple type",
+                describe(reference)
+            ))),
+        }
+    }
+
+    /// The simple type named by `qname` or defined by `simple_type`, or
+    /// `xs:anySimpleType` if neither is given.
+    fn simple_type_ref(
+        &mut self,
+        document: Document,
+        qname: Option<&str>,
+        simple_type: Option<&'a SimpleType>,
+    ) -> Result<TypeRef> {
+        match (qname, simple_type) {
+            (Some(qname), None) => {
+                let reference = self.base_type(document, qname)?;
+                self.datatype(&reference)?;
+                Ok(reference)
+            }
+            (None, Some(simple_type)) => Ok(TypeRef::Anonymous(Box::new(TypeDefinition::Simple(
+                self.simple_type(document, simple_type, None)?,
+            )))),
+            (None, None) => Ok(TypeRef::Named(xs("anySimpleType"))),
+            (Some(qname), Some(_)) => Err(Error::Invalid(format!(
+                "`{}` and an anonymous simpleType are given for the same type",
+                qname
+            ))),
+        }
+    }
+
+    fn simple_type(
+        &mut self,
+        document: Document,
+        simple_type: &'a SimpleType,
+        name: Option<&ExpandedName>,
+    ) -> Result<SimpleTypeDefinition> {
+        use DerivationControl::{Extension, List, Restriction, Union};
+
+        let scope = document.enter(&simple_type.open_attrs.namespaces);
+        let document = scope.document();
+        let (base, datatype) = match &simple_type.derivation {
+            SimpleDerivation::Restriction(restriction) => {
+                let scope = document.enter(&restriction.open_attrs.namespaces);
+                let document = scope.document();
+                let base = match (&restriction.base, &restriction.simple_type) {
+                    (Some(_), Some(_)) | (None, None) => {
+                        return Err(Error::Invalid(
+                            "a simple type restriction needs either a base or a simpleType"
+                                .to_owned(),
+                        ))
+                    }
+                    (base, simple_type) => {
+                        self.simple_type_ref(document, base.as_deref(), simple_type.as_deref())?
+                    }
+                };
+                let datatype = restriction.restrict(self.datatype(&base)?, &document)?;
+                (base, datatype)
+            }
+            SimpleDerivation::List(list) => {
+                let scope = document.enter(&list.open_attrs.namespaces);
+                let document = scope.document();
+                if list.item_type.is_none() && list.simple_type.is_none() {
+                    return Err(Error::Invalid(
+                        "a list needs either an itemType or a simpleType".to_owned(),
+                    ));
+                }
+                let item_type = self.simple_type_ref(
+                    document,
+                    list.item_type.as_deref(),
+                    list.simple_type.as_deref(),
+                )?;
+                let datatype = xsd_datatypes::SimpleType::list(self.datatype(&item_type)?.clone());
+                (TypeRef::Named(xs("anySimpleType")), datatype)
+            }
+            SimpleDerivation::Union(union) => {
+                let scope = document.enter(&union.open_attrs.namespaces);
+                let document = scope.document();
+                let mut member_types = Vec::new();
+                for qname in union.member_types.iter().flat_map(|m| m.split_whitespace()) {
+                    let reference = self.simple_type_ref(document, Some(qname), None)?;
+                    member_types.push(self.datatype(&reference)?.clone());
+                }
+                for simple_type in &union.simple_type {
+                    member_types.push(self.simple_type(document, simple_type, None)?.datatype);
+                }
+                if member_types.is_empty() {
+                    return Err(Error::Invalid("a union needs member types".to_owned()));
+                }
+                let datatype = xsd_datatypes::SimpleType::union(member_types);
+                (TypeRef::Named(xs("anySimpleType")), datatype)
+            }
+        };
+        let final_set = simple_type
+            .final_attr
+            .as_ref()
+            .or(document.schema.final_default.as_ref());
+        Ok(SimpleTypeDefinition {
+            name: name.cloned(),
+            base,
+            final_derivations: derivations(final_set, &[Extension, Restriction, List, Union]),
+            datatype,
+        })
+    }
+
+    fn complex_type(
+        &mut self,
+        document: Document,
+        complex_type: &'a ComplexType,
+        name: Option<&ExpandedName>,
+    ) -> Result<ComplexTypeDefinition> {
+        use DerivationControl::{Extension, Restriction};
+
+        let scope = document.enter(&complex_type.open_attrs.namespaces);
+        let document = scope.document();
+        let empty = &[][..];
+        // The declarations on `simpleContent` or `complexContent`, and on
+        // the derivation in it.
+        let (namespaces, base, derivation_method, attribute_decls, any_attribute) =
+            match (&complex_type.simple_content, &complex_type.complex_content) {
+                (Some(content), _) => match &content.derivation {
+                    SimpleContentDerivation::Restriction(restriction) => (
+                        [
+                            &content.open_attrs.namespaces[..],
+                            &restriction.open_attrs.namespaces,
+                        ],
+                        Some(&restriction.base),
+                        DerivationMethod::Restriction,
+                        &restriction.attributes,
+                        restriction.any_attribute.as_ref(),
+                    ),
+                    SimpleContentDerivation::Extension(extension) => (
+                        [
+                            &content.open_attrs.namespaces[..],
+                            &extension.open_attrs.namespaces,
+                        ],
+                        Some(&extension.base),
+                        DerivationMethod::Extension,
+                        &extension.attributes,
+                        extension.any_attribute.as_ref(),
+                    ),
+                },
+                (None, Some(content)) => match &content.derivation {
+                    ComplexDerivation::Restriction(restriction) => (
+                        [
+                            &content.open_attrs.namespaces[..],
+                            &restriction.open_attrs.namespaces,
+                        ],
+                        Some(&restriction.base),
+                        DerivationMethod::Restriction,
+                        &restriction.attributes,
+                        restriction.any_attribute.as_ref(),
+                    ),
+                    ComplexDerivation::Extension(extension) => (
+                        [
+                            &content.open_attrs.namespaces[..],
+                            &extension.open_attrs.namespaces,
+                        ],
+                        Some(&extension.base),
+                        DerivationMethod::Extension,
+                        &extension.attributes,
+                        extension.any_attribute.as_ref(),
+                    ),
+                },
+                (None, None) => (
+                    [empty, empty],
+                    None,
+                    DerivationMethod::Restriction,
+                    &complex_type.attributes,
+                    complex_type.any_attribute.as_ref(),
+                ),
+            };
+        let content_scope = document.enter(namespaces[0]);
+        let derivation_scope = content_scope.document().enter(namespaces[1]);
+        let inner = derivation_scope.document();
+
+        let mut attributes = self.attributes(inner, attribute_decls, any_attribute)?;
+        if let Some(qname) = &document.schema.default_attributes {
+            if complex_type.default_attributes_apply != Some(false) {
+                self.attribute_group(document.top(), qname, &mut attributes)?;
+            }
+        }
+        let base = match base {
+            Some(qname) => self.base_type(inner, qname)?,
+            None => TypeRef::Named(xs("anyType")),
+        };
+        let content_type = self.content_type(inner, complex_type, &base)?;
+        let (attribute_uses, attribute_wildcard) =
+            self.merge_attributes(&base, derivation_method, attributes)?;
+
+        let schema = document.schema;
+        let final_set = complex_type
+            .final_attr
+            .as_ref()
+            .or(schema.final_default.as_ref());
+        let block = complex_type
+            .block
+            .as_ref()
+            .or(schema.block_default.as_ref());
+        Ok(ComplexTypeDefinition {
+            name: name.cloned(),
+            base,
+            derivation_method,
+            is_abstract: complex_type.abstract_attr.unwrap_or(false),
+            final_derivations: derivations(final_set, &[Extension, Restriction]),
+            blocked_derivations: derivations(block, &[Extension, Restriction]),
+            attribute_uses,
+            attribute_wildcard,
+            content_type,
+        })
+    }
+
+    /// The content type of `complex_type`, with `document` seen from inside
+    /// its derivation, if it has one.
+    fn content_type(
+        &mut self,
+        document: Document,
+        complex_type: &'a ComplexType,
+        base: &TypeRef,
+    ) -> Result<ContentType> {
+        if let Some(content) = &complex_type.simple_content {
+            return match &content.derivation {
+                SimpleContentDerivation::Restriction(restriction) => {
+                    let simple_type = restriction
+                        .simple_type
+                        .as_ref()
+                        .map(|simple_type| self.simple_type(document, simple_type, None))
+                        .transpose()?;
+                    let datatype = match (simple_type, self.set.type_definition(base)) {
+                        (Some(definition), _) => definition.datatype,
+                        (None, TypeDefinition::Complex(definition)) => {
+                            match &definition.content_type {
+                                ContentType::Simple(datatype) => datatype.clone(),
+                                _ => return Err(no_simple_content(base)),
+                            }
+                        }
+                        (None, TypeDefinition::Simple(_)) => return Err(no_simple_content(base)),
+                    };
+                    let datatype = facets::restrict(&restriction.facets, &datatype, &document)?;
+                    Ok(ContentType::Simple(datatype))
+                }
+                SimpleContentDerivation::Extension(_) => match self.set.type_definition(base) {
+                    TypeDefinition::Simple(definition) => {
+                        Ok(ContentType::Simple(definition.datatype.clone()))
+                    }
+                    TypeDefinition::Complex(definition) => match &definition.content_type {
+                        ContentType::Simple(datatype) => Ok(ContentType::Simple(datatype.clone())),
+                        _ => Err(no_simple_content(base)),
+                    },
+                },
+            };
+        }
+
+        let (particle, mixed, extension) = match &complex_type.complex_content {
+            Some(content) => {
+                let mixed = content.mixed.or(complex_type.mixed).unwrap_or(false);
+                match &content.derivation {
+                    ComplexDerivation::Restriction(restriction) => {
+                        (restriction.particle.as_ref(), mixed, false)
+                    }
+                    ComplexDerivation::Extension(extension) => {
+                        (extension.particle.as_ref(), mixed, true)
+                    }
+                }
+            }
+            None => (
+                complex_type.particle.as_ref(),
+                complex_type.mixed.unwrap_or(false),
+                false,
+            ),
+        };
+        let particle = particle
+            .map(|particle| self.type_def_particle(document, particle))
+            .transpose()?
+            .filter(|particle| !is_empty(particle));
+        let explicit = match (particle, mixed) {
+            (Some(particle), _) => Some(particle),
+            (None, true) => Some(Particle {
+                min_occurs: 1,
+                max_occurs: AllNNI::Bounded(1),
+                term: Term::ModelGroup(ModelGroup {
+                    compositor: Compositor::Sequence,
+                    particles: Vec::new(),
+                }),
+            }),
+            (None, false) => None,
+        };
+        let content = |particle| match mixed {
+            true => ContentType::Mixed(particle),
+            false => ContentType::ElementOnly(particle),
+        };
+        if !extension {
+            return Ok(explicit.map_or(ContentType::Empty, content));
+        }
+
+        let TypeDefinition::Complex(base_definition) = self.set.type_definition(base) else {
+            return Err(Error::Invalid(format!(
+                "complex content cannot extend the simple type {}",
+                describe(base)
+            )));
+        };
+        match (explicit, &base_definition.content_type) {
+            (None, base_content) => Ok(base_content.clone()),
+            (Some(particle), ContentType::Empty) => Ok(content(particle)),
+            (
+                Some(particle),
+                base_content @ (ContentType::ElementOnly(base_particle)
+                | ContentType::Mixed(base_particle)),
+            ) => {
+                if matches!(base_content, ContentType::Mixed(_)) != mixed {
+                    return Err(Error::Invalid(format!(
+                        "an extension of {} must keep whether its content is mixed",
+                        describe(base)
+                    )));
+                }
+                Ok(content(Particle {
+                    min_occurs: 1,
+                    max_occurs: AllNNI::Bounded(1),
+                    term: Term::ModelGroup(ModelGroup {
+                        compositor: Compositor::Sequence,
+                        particles: vec![base_particle.clone(), particle],
+                    }),
+                }))
+            }
+            (Some(_), ContentType::Simple(_)) => Err(Error::Invalid(format!(
+                "complex content cannot extend {}, which has simple content",
+                describe(base)
+            ))),
+        }
+    }
+
+    /// Merges the attribute uses and wildcard of the base type with those
+    /// of a type derived from it.
+    fn merge_attributes(
+        &self,
+        base: &TypeRef,
+        method: DerivationMethod,
+        attributes: Attributes,
+    ) -> Result<(Vec<AttributeUse>, Option<Wildcard>)> {
+        let (base_uses, base_wildcard) = match self.set.type_definition(base) {
+            TypeDefinition::Complex(definition) => (
+                &definition.attribute_uses[..],
+                definition.attribute_wildcard.as_ref(),
+            ),
+            TypeDefinition::Simple(_) => (&[][..], None),
+        };
+        match method {
+            DerivationMethod::Extension => {
+                let mut merged = Attributes {
+                    uses: base_uses.to_vec(),
+                    ..Attributes::default()
+                };
+                for attribute_use in attributes.uses {
+                    merged.push(attribute_use)?;
+                }
+                let wildcard = match (attributes.wildcard, base_wildcard) {
+                    (Some(wildcard), Some(base_wildcard)) => Some(union(wildcard, base_wildcard)),
+                    (wildcard, base_wildcard) => wildcard.or(base_wildcard.cloned()),
+                };
+                Ok((merged.uses, wildcard))
+            }
+            DerivationMethod::Restriction => {
+                let mut uses = attributes.uses;
+                for base_use in base_uses {
+                    let name = &base_use.declaration.name;
+                    if !uses.iter().any(|u| &u.declaration.name == name)
+                        && !attributes.prohibited.contains(name)
+                    {
+                        uses.push(base_use.clone());
+                    }
+                }
+                Ok((uses, attributes.wildcard))
+            }
+        }
+    }
+
+    fn attributes(
+        &mut self,
+        document: Document,
+        decls: &'a [AttrDecl],
+        any_attribute: Option<&'a AnyAttribute>,
+    ) -> Result<Attributes> {
+        let mut attributes = Attributes::default();
+        for decl in decls {
+            match decl {
+                AttrDecl::Attribute(attribute) => {
+                    let (declaration, value_constraint) = match &attribute.ref_attr {
+                        Some(qname) => {
+                            let scope = document.enter(&attribute.open_attrs.namespaces);
+                            let name = scope.document().resolve(qname)?;
+                            self.ensure_attribute(&name)?;
+                            let declaration = self.set.attribute_declarations[&name].clone();
+                            let value_constraint = value_constraint(
+                                attribute.default.as_deref(),
+                                attribute.fixed.as_deref(),
+                            )?;
+                            (declaration, value_constraint)
+                        }
+                        None => (
+                            self.attribute_declaration(document, attribute, false)?,
+                            None,
+                        ),
+                    };
+                    match attribute.use_attr {
+                        Some(Use::Prohibited) => attributes.prohibited.push(declaration.name),
+                        use_attr => attributes.push(AttributeUse {
+                            required: use_attr == Some(Use::Required),
+                            declaration,
+                            value_constraint,
+                        })?,
+                    }
+                }
+                AttrDecl::AttributeGroup(group) => {
+                    let scope = document.enter(&group.open_attrs.namespaces);
+                    self.attribute_group(scope.document(), &group.ref_attr, &mut attributes)?
+                }
+            }
+        }
+        if let Some(any) = any_attribute {
+            let scope = document.enter(&any.open_attrs.namespaces);
+            let wildcard = self.wildcard(
+                scope.document(),
+                any.namespace.as_deref(),
+                any.not_namespace.as_deref(),
+                any.process_contents,
+                any.not_q_name.as_deref(),
+            )?;
+            attributes.wildcard = Some(match attributes.wildcard.take() {
+                Some(group_wildcard) => intersection(wildcard, &group_wildcard),
+                None => wildcard,
+            });
+        }
+        Ok(attributes)
+    }
+
+    /// Adds the uses and wildcard of the attribute group `qname` names.
+    fn attribute_group(
+        &mut self,
+        document: Document,
+        qname: &str,
+        attributes: &mut Attributes,
+    ) -> Result<()> {
+        let name = document.resolve(qname)?;
+        self.ensure_attribute_group(&name)?;
+        let group = &self.set.attribute_group_definitions[&name];
+        for attribute_use in &group.attribute_uses {
+            attributes.push(attribute_use.clone())?;
+        }
+        if let Some(wildcard) = &group.attribute_wildcard {
+            attributes.wildcard = Some(match attributes.wildcard.take() {
+                Some(other) => intersection(other, wildcard),
+                None => wildcard.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    fn attribute_declaration(
+        &mut self,
+        document: Document,
+        attribute: &'a Attribute,
+        global: bool,
+    ) -> Result<AttributeDeclaration> {
+        let scope = document.enter(&attribute.open_attrs.namespaces);
+        let document = scope.document();
+        let local_name = attribute
+            .name
+            .as_deref()
+            .ok_or_else(|| Error::Invalid("an attribute needs a name or a ref".to_owned()))?;
+        let namespace = match global {
+            true => document.target_namespace(),
+            false => attribute.target_namespace.as_deref().or_else(|| {
+                document.local_namespace(attribute.form, document.schema.attribute_form_default)
+            }),
+        };
+        let type_definition = self.simple_type_ref(
+            document,
+            attribute.type_attr.as_deref(),
+            attribute.simple_type.as_ref(),
+        )?;
+        Ok(AttributeDeclaration {
+            name: ExpandedName::new(namespace, local_name),
+            type_definition,
+            value_constraint: value_constraint(
+                attribute.default.as_deref(),
+                attribute.fixed.as_deref(),
+            )?,
+            inheritable: attribute.inheritable.unwrap_or(false),
+        })
+    }
+
+    fn element_declaration(
+        &mut self,
+        document: Document,
+        element: &'a Element,
+        global: bool,
+    ) -> Result<ElementDeclaration> {
+        use DerivationControl::{Extension, Restriction, Substitution};
+
+        let scope = document.enter(&element.open_attrs.namespaces);
+        let document = scope.document();
+        let schema = document.schema;
+        let local_name = element
+            .name
+            .as_deref()
+            .ok_or_else(|| Error::Invalid("an element needs a name or a ref".to_owned()))?;
+        let namespace = match global {
+            true => document.target_namespace(),
+            false => element
+                .target_namespace
+                .as_deref()
+                .or_else(|| document.local_namespace(element.form, schema.element_form_default)),
+        };
+        let name = ExpandedName::new(namespace, local_name);
+
+        let mut substitution_group_affiliations = Vec::new();
+        if global {
+            for qname in element
+                .substitution_group
+                .iter()
+                .flat_map(|s| s.split_whitespace())
+            {
+                let head = document.resolve(qname)?;
+                if !self.elements.contains_key(&head) {
+                    return Err(undefined(ELEMENT, &head));
+                }
+                substitution_group_affiliations.push(head);
+            }
+        }
+
+        let type_definition = match (
+            &element.type_attr,
+            &element.simple_type,
+            &element.complex_type,
+        ) {
+            (Some(qname), None, None) => self.type_ref(document, qname)?,
+            (None, Some(simple_type), None) => TypeRef::Anonymous(Box::new(
+                TypeDefinition::Simple(self.simple_type(document, simple_type, None)?),
+            )),
+            (None, None, Some(complex_type)) => TypeRef::Anonymous(Box::new(
+                TypeDefinition::Complex(self.complex_type(document, complex_type, None)?),
+            )),
+            (None, None, None) => match substitution_group_affiliations.first() {
+                Some(head) => {
+                    self.ensure_element(head)?;
+                    self.set.element_declarations[head].type_definition.clone()
+                }
+                None => TypeRef::Named(xs("anyType")),
+            },
+            _ => {
+                return Err(Error::Invalid(format!(
+                    "element `{}` is given more than one type",
+                    name
+                )))
+            }
+        };
+
+        let identity_constraints = element
+            .identity_constraint
+            .iter()
+            .map(|constraint| self.identity_constraint(document, constraint))
+            .collect::<Result<_>>()?;
+        let block = element.block.as_ref().or(schema.block_default.as_ref());
+        let final_set = element
+            .final_attr
+            .as_ref()
+            .or(schema.final_default.as_ref());
+        Ok(ElementDeclaration {
+            name,
+            type_definition,
+            nillable: element.nillable.unwrap_or(false),
+            is_abstract: element.abstract_attr.unwrap_or(false),
+            value_constraint: value_constraint(
+                element.default.as_deref(),
+                element.fixed.as_deref(),
+            )?,
+            substitution_group_affiliations,
+            blocked_substitutions: derivations(block, &[Extension, Restriction, Substitution]),
+            substitution_group_exclusions: match global {
+                true => derivations(final_set, &[Extension, Restriction]),
+                false => Vec::new(),
+            },
+            identity_constraints,
+        })
+    }
+
+    /// Adds an identity constraint to the set, giving its name.
+    fn identity_constraint(
+        &mut self,
+        document: Document,
+        constraint: &IdentityConstraint,
+    ) -> Result<ExpandedName> {
+        let (namespaces, name, ref_attr, selector, fields) = match constraint {
+            IdentityConstraint::Unique(keybase) | IdentityConstraint::Key(keybase) => (
+                &keybase.open_attrs.namespaces,
+                &keybase.name,
+                &keybase.ref_attr,
+                &keybase.selector,
+                &keybase.field,
+            ),
+            IdentityConstraint::Keyref(keyref) => (
+                &keyref.open_attrs.namespaces,
+                &keyref.name,
+                &keyref.ref_attr,
+                &keyref.selector,
+                &keyref.field,
+            ),
+        };
+        let scope = document.enter(namespaces);
+        let document = scope.document();
+        if let Some(qname) = ref_attr {
+            let name = document.resolve(qname)?;
+            self.constraint_references.push(name.clone());
+            return Ok(name);
+        }
+        let name = document.name(top_level_name(name, "identity constraint")?);
+        let category = match constraint {
+            IdentityConstraint::Unique(_) => IdentityConstraintCategory::Unique,
+            IdentityConstraint::Key(_) => IdentityConstraintCategory::Key,
+            IdentityConstraint::Keyref(keyref) => {
+                let refer = keyref
+                    .refer
+                    .as_deref()
+                    .ok_or_else(|| Error::Invalid(format!("keyref `{}` has no refer", name)))?;
+                IdentityConstraintCategory::KeyRef(document.resolve(refer)?)
+            }
+        };
+        let selector = selector.as_ref().ok_or_else(|| {
+            Error::Invalid(format!("identity constraint `{}` has no selector", name))
+        })?;
+        let definition = IdentityConstraintDefinition {
+            name: name.clone(),
+            category,
+            selector: selector.xpath.clone(),
+            fields: fields.iter().map(|field| field.xpath.clone()).collect(),
+            namespaces: document.in_scope(),
+        };
+        insert(
+            &mut self.set.identity_constraint_definitions,
+            IDENTITY_CONSTRAINT,
+            name.clone(),
+            definition,
+        )?;
+        Ok(name)
+    }
+
+    /// Checks that constraint references name constraints, and that keyrefs
+    /// refer to keys or unique constraints.
+    fn check_identity_constraints(&self) -> Result<()> {
+        let constraints = &self.set.identity_constraint_definitions;
+        for name in &self.constraint_references {
+            if !constraints.contains_key(name) {
+                return Err(undefined(IDENTITY_CONSTRAINT, name));
+            }
+        }
+        for constraint in constraints.values() {
+            if let IdentityConstraintCategory::KeyRef(refer) = &constraint.category {
+                match constraints.get(refer).map(|c| &c.category) {
+                    None => return Err(undefined(IDENTITY_CONSTRAINT, refer)),
+                    Some(IdentityConstraintCategory::KeyRef(_)) => {
+                        return Err(Error::Invalid(format!(
+                            "keyref `{}` refers to the keyref `{}`",
+                            constraint.name, refer
+                        )))
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn type_def_particle(
+        &mut self,
+        document: Document,
+        particle: &'a TypeDefParticle,
+    ) -> Result<Particle> {
+        match particle {
+            TypeDefParticle::Group(group) => {
+                let scope = document.enter(&group.open_attrs.namespaces);
+                let term = self.group_term(scope.document(), &group.ref_attr)?;
+                occurrence(group.min_occurs, group.max_occurs, term)
+            }
+            TypeDefParticle::All(all) => {
+                let group =
+                    self.model_group(document, Compositor::All, &all.open_attrs, &all.particles)?;
+                occurrence(all.min_occurs, all.max_occurs, Term::ModelGroup(group))
+            }
+            TypeDefParticle::Choice(choice) => {
+                let group = self.model_group(
+                    document,
+                    Compositor::Choice,
+                    &choice.open_attrs,
+                    &choice.particles,
+                )?;
+                occurrence(
+                    choice.min_occurs,
+                    choice.max_occurs,
+                    Term::ModelGroup(group),
+                )
+            }
+            TypeDefParticle::Sequence(sequence) => {
+                let group = self.model_group(
+                    document,
+                    Compositor::Sequence,
+                    &sequence.open_attrs,
+                    &sequence.particles,
+                )?;
+                occurrence(
+                    sequence.min_occurs,
+                    sequence.max_occurs,
+                    Term::ModelGroup(group),
+                )
+            }
+        }
+    }
+
+    fn particle(&mut self, document: Document, particle: &'a groups::Particle) -> Result<Particle> {
+        let occurs = particle.occurs();
+        let term = match particle {
+            groups::Particle::Element(element) => match &element.ref_attr {
+                Some(qname) => {
+                    let scope = document.enter(&element.open_attrs.namespaces);
+                    let name = scope.document().resolve(qname)?;
+                    if !self.elements.contains_key(&name) {
+                        return Err(undefined(ELEMENT, &name));
+                    }
+                    Term::ElementRef(name)
+                }
+                None => Term::Element(Box::new(
+                    self.element_declaration(document, element, false)?,
+                )),
+            },
+            groups::Particle::Group(group) => {
+                let scope = document.enter(&group.open_attrs.namespaces);
+                self.group_term(scope.document(), &group.ref_attr)?
+            }
+            groups::Particle::Choice(choice) => Term::ModelGroup(self.model_group(
+                document,
+                Compositor::Choice,
+                &choice.open_attrs,
+                &choice.particles,
+            )?),
+            groups::Particle::Sequence(sequence) => Term::ModelGroup(self.model_group(
+                document,
+                Compositor::Sequence,
+                &sequence.open_attrs,
+                &sequence.particles,
+            )?),
+            groups::Particle::Any(any) => Term::Wildcard(self.wildcard(
+                document.enter(&any.open_attrs.namespaces).document(),
+                any.namespace.as_deref(),
+                any.not_namespace.as_deref(),
+                any.process_contents,
+                any.not_q_name.as_deref(),
+            )?),
+        };
+        occurrence(Some(occurs.min), Some(occurs.max), term)
+    }
+
+    fn model_group(
+        &mut self,
+        document: Document,
+        compositor: Compositor,
+        open_attrs: &OpenAttrs,
+        particles: &'a [groups::Particle],
+    ) -> Result<ModelGroup> {
+        let scope = document.enter(&open_attrs.namespaces);
+        let document = scope.document();
+        let particles = particles
+            .iter()
+            .map(|particle| self.particle(document, particle))
+            .collect::<Result<_>>()?;
+        Ok(ModelGroup {
+            compositor,
+            particles,
+        })
+    }
+
+    /// The model group of the definition `qname` names.
+    fn group_term(&mut self, document: Document, qname: &str) -> Result<Term> {
+        let name = document.resolve(qname)?;
+        self.ensure_model_group(&name)?;
+        Ok(Term::ModelGroup(
+            self.set.model_group_definitions[&name].clone(),
+        ))
+    }
+
+    fn wildcard(
+        &self,
+        document: Document,
+        namespace: Option<&str>,
+        not_namespace: Option<&str>,
+        process_contents: Option<ProcessContents>,
+        not_q_name: Option<&str>,
+    ) -> Result<Wildcard> {
+        let target_namespace = document.target_namespace().map(str::to_owned);
+        let list = |value: &str| {
+            value
+                .split_whitespace()
+                .map(|token| match token {
+                    "##targetNamespace" => Ok(target_namespace.clone()),
+                    "##local" => Ok(None),
+                    _ if token.starts_with("##") => Err(Error::Invalid(format!(
+                        "`{}` cannot appear in a list of namespaces",
+                        token
+                    ))),
+                    uri => Ok(Some(uri.to_owned())),
+                })
+                .collect::<Result<Vec<_>>>()
+        };
+        let namespace_constraint = match (namespace.map(str::trim), not_namespace) {
+            (Some(_), Some(_)) => {
+                return Err(Error::Invalid(
+                    "a wildcard cannot have both namespace and notNamespace".to_owned(),
+                ))
+            }
+            (None | Some("##any"), None) => NamespaceConstraint::Any,
+            (Some("##other"), None) => match target_namespace.clone() {
+                Some(target_namespace) => {
+                    NamespaceConstraint::Not(vec![Some(target_namespace), None])
+                }
+                None => NamespaceConstraint::Not(vec![None]),
+            },
+            (Some(namespaces), None) => NamespaceConstraint::Enumeration(list(namespaces)?),
+            (None, Some(namespaces)) => NamespaceConstraint::Not(list(namespaces)?),
+        };
+        let disallowed_names = not_q_name
+            .iter()
+            .flat_map(|names| names.split_whitespace())
+            .filter(|name| !matches!(*name, "##defined" | "##definedSibling"))
+            .map(|name| document.resolve(name))
+            .collect::<Result<_>>()?;
+        Ok(Wildcard {
+            namespace_constraint,
+            disallowed_names,
+            process_contents: process_contents.unwrap_or(ProcessContents::Strict),
+        })
+    }
+}
+
+fn insert<T>(
+    map: &mut BTreeMap<ExpandedName, T>,
+    kind: &'static str,
+    name: ExpandedName,
+    value: T,
+) -> Result<()> {
+    if map.contains_key(&name) {
+        return Err(Error::Duplicate {
+            kind,
+            name: name.to_string(),
+        });
+    }
+    map.insert(name, value);
+    Ok(())
+}
+
+fn undefined(kind: &'static str, name: &ExpandedName) -> Error {
+    Error::UndefinedReference {
+        kind,
+        name: name.to_string(),
+    }
+}
+
+fn no_simple_content(base: &TypeRef) -> Error {
+    Error::Invalid(format!(
+        "{} has no simple content to derive from",
+        describe(base)
+    ))
+}
+
+fn describe(reference: &TypeRef) -> String {
+    match reference {
+        TypeRef::Named(name) => format!("`{}`", name),
+        TypeRef::Anonymous(_) => "an anonymous type".to_owned(),
+    }
+}
+
+fn top_level_name<'b>(name: &'b Option<String>, element: &str) -> Result<&'b str> {
+    name.as_deref()
+        .ok_or_else(|| Error::Invalid(format!("a top-level {} needs a name", element)))
+}
+
+fn xs(local_name: &str) -> ExpandedName {
+    ExpandedName::new(Some(XSD_NAMESPACE), local_name)
+}
+
+/// Whether `name` is that of a built-in type, which the schema for
+/// schemas defines again.
+fn is_builtin(name: &ExpandedName) -> bool {
+    name.namespace.as_deref() == Some(XSD_NAMESPACE)
+        && (name.local_name == "anyType" || Builtin::from_name(&name.local_name).is_some())
+}
+
+fn builtin_types() -> Vec<(ExpandedName, TypeDefinition)> {
+    let any = || Wildcard {
+        namespace_constraint: NamespaceConstraint::Any,
+        disallowed_names: Vec::new(),
+        process_contents: ProcessContents::Lax,
+    };
+    let any_type = ComplexTypeDefinition {
+        name: Some(xs("anyType")),
+        base: TypeRef::Named(xs("anyType")),
+        derivation_method: DerivationMethod::Restriction,
+        is_abstract: false,
+        final_derivations: Vec::new(),
+        blocked_derivations: Vec::new(),
+        attribute_uses: Vec::new(),
+        attribute_wildcard: Some(any()),
+        content_type: ContentType::Mixed(Particle {
+            min_occurs: 1,
+            max_occurs: AllNNI::Bounded(1),
+            term: Term::ModelGroup(ModelGroup {
+                compositor: Compositor::Sequence,
+                particles: vec![Particle {
+                    min_occurs: 0,
+                    max_occurs: AllNNI::Unbounded,
+                    term: Term::Wildcard(any()),
+                }],
+            }),
+        }),
+    };
+    let simple_types = Builtin::ALL.iter().map(|&builtin| {
+        let base = builtin.base().map_or("anyType", Builtin::name);
+        let definition = SimpleTypeDefinition {
+            name: Some(xs(builtin.name())),
+            base: TypeRef::Named(xs(base)),
+            final_derivations: Vec::new(),
+            datatype: xsd_datatypes::SimpleType::builtin(builtin),
+        };
+        (xs(builtin.name()), TypeDefinition::Simple(definition))
+    });
+    std::iter::once((xs("anyType"), TypeDefinition::Complex(any_type)))
+        .chain(simple_types)
+        .collect()
+}
+
+fn occurrence(min_occurs: Option<u32>, max_occurs: Option<AllNNI>, term: Term) -> Result<Particle> {
+    let min_occurs = min_occurs.unwrap_or(1);
+    let max_occurs = max_occurs.unwrap_or(AllNNI::Bounded(1));
+    if matches!(max_occurs, AllNNI::Bounded(max) if max < min_occurs) {
+        return Err(Error::Invalid(format!(
+            "minOccurs {} is greater than maxOccurs {}",
+            min_occurs, max_occurs
+        )));
+    }
+    Ok(Particle {
+        min_occurs,
+        max_occurs,
+        term,
+    })
+}
+
+/// Whether a particle leaves the content empty, in which case a complex
+/// type has empty content rather than an element-only one.
+fn is_empty(particle: &Particle) -> bool {
+    match &particle.term {
+        _ if particle.max_occurs == AllNNI::Bounded(0) => true,
+        Term::ModelGroup(group) => {
+            group.particles.is_empty()
+                && (group.compositor != Compositor::Choice || particle.min_occurs == 0)
+        }
+        _ => false,
+    }
+}
+
+fn value_constraint(default: Option<&str>, fixed: Option<&str>) -> Result<Option<ValueConstraint>> {
+    match (default, fixed) {
+        (Some(_), Some(_)) => Err(Error::Invalid(
+            "a declaration cannot have both a default and a fixed value".to_owned(),
+        )),
+        (Some(default), None) => Ok(Some(ValueConstraint::Default(default.to_owned()))),
+        (None, Some(fixed)) => Ok(Some(ValueConstraint::Fixed(fixed.to_owned()))),
+        (None, None) => Ok(None),
+    }
+}
+
+/// The derivations of a `final` or `block` set that apply to a component,
+/// with `#all` standing for all of `applicable`.
+fn derivations(
+    set: Option<&DerivationSet>,
+    applicable: &[DerivationControl],
+) -> Vec<DerivationControl> {
+    match set {
+        None => Vec::new(),
+        Some(DerivationSet::All) => applicable.to_vec(),
+        Some(DerivationSet::List(list)) => list
+            .iter()
+            .copied()
+            .filter(|derivation| applicable.contains(derivation))
+            .collect(),
+    }
+}
+
+/// The wildcard allowing what both wildcards allow, processed as the
+/// first is.
+fn intersection(wildcard: Wildcard, other: &Wildcard) -> Wildcard {
+    use NamespaceConstraint::{Any, Enumeration, Not};
+
+    let namespace_constraint = match (wildcard.namespace_constraint, &other.namespace_constraint) {
+        (constraint, Any) => constraint,
+        (Any, constraint) => constraint.clone(),
+        (Enumeration(a), Enumeration(b)) => {
+            Enumeration(a.into_iter().filter(|n| b.contains(n)).collect())
+        }
+        (Enumeration(a), Not(b)) => Enumeration(a.into_iter().filter(|n| !b.contains(n)).collect()),
+        (Not(a), Enumeration(b)) => {
+            Enumeration(b.iter().filter(|n| !a.contains(n)).cloned().collect())
+        }
+        (Not(mut a), Not(b)) => {
+            a.extend(
+                b.iter()
+                    .filter(|n| !a.contains(n))
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            );
+            Not(a)
+        }
+    };
+    let mut disallowed_names = wildcard.disallowed_names;
+    for name in &other.disallowed_names {
+        if !disallowed_names.contains(name) {
+            disallowed_names.push(name.clone());
+        }
+    }
+    Wildcard {
+        namespace_constraint,
+        disallowed_names,
+        process_contents: wildcard.process_contents,
+    }
+}
+
+/// The wildcard allowing what either wildcard allows, processed as the
+/// first is.
+fn union(wildcard: Wildcard, other: &Wildcard) -> Wildcard {
+    use NamespaceConstraint::{Any, Enumeration, Not};
+
+    let not = |namespaces: Vec<Option<String>>| match namespaces.is_empty() {
+        true => Any,
+        false => Not(namespaces),
+    };
+    let namespace_constraint = match (wildcard.namespace_constraint, &other.namespace_constraint) {
+        (Any, _) | (_, Any) => Any,
+        (Enumeration(mut a), Enumeration(b)) => {
+            a.extend(
+                b.iter()
+                    .filter(|n| !a.contains(n))
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            );
+            Enumeration(a)
+        }
+        (Not(a), Enumeration(b)) => not(a.into_iter().filter(|n| !b.contains(n)).collect()),
+        (Enumeration(a), Not(b)) => not(b.iter().filter(|n| !a.contains(n)).cloned().collect()),
+        (Not(a), Not(b)) => not(a.into_iter().filter(|n| b.contains(n)).collect()),
+    };
+    let disallowed_names = wildcard
+        .disallowed_names
+        .into_iter()
+        .filter(|name| other.disallowed_names.contains(name))
+        .collect();
+    Wildcard {
+        namespace_constraint,
+        disallowed_names,
+        process_contents: wildcard.process_contents,
+    }
+}
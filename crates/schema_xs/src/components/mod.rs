@@ -0,0 +1,672 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use document::namespace::Namespace;
+
+use crate::elements::Schema;
+use crate::error::Result;
+use crate::simple_types::{AllNNI, DerivationControl, ProcessContents};
+
+mod builder;
+
+/// The name of a component: its target namespace and local name.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExpandedName {
+    pub namespace: Option<String>,
+    pub local_name: String,
+}
+
+impl ExpandedName {
+    pub fn new(namespace: Option<&str>, local_name: &str) -> ExpandedName {
+        ExpandedName {
+            namespace: namespace.map(str::to_owned),
+            local_name: local_name.to_owned(),
+        }
+    }
+
+    /// Returns `true` when this is the name with `namespace` and
+    /// `local_name`.
+    pub fn is(&self, namespace: Option<&str>, local_name: &str) -> bool {
+        self.namespace.as_deref() == namespace && self.local_name == local_name
+    }
+}
+
+/// Writes the name in Clark notation, `{namespace}local`.
+impl Display for ExpandedName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.namespace {
+            Some(namespace) => write!(f, "{{{}}}{}", namespace, self.local_name),
+            None => f.write_str(&self.local_name),
+        }
+    }
+}
+
+/// The schema components of a set of schema documents, keyed by name.
+///
+/// Every QName reference between components has been resolved: a
+/// [`TypeRef::Named`] or [`Term::ElementRef`] names a component of the set,
+/// and model group and attribute group references have been replaced by
+/// the groups they name. The built-in types are always present.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaSet {
+    pub type_definitions: BTreeMap<ExpandedName, TypeDefinition>,
+    pub element_declarations: BTreeMap<ExpandedName, ElementDeclaration>,
+    pub attribute_declarations: BTreeMap<ExpandedName, AttributeDeclaration>,
+    pub model_group_definitions: BTreeMap<ExpandedName, ModelGroup>,
+    pub attribute_group_definitions: BTreeMap<ExpandedName, AttributeGroupDefinition>,
+    pub notation_declarations: BTreeMap<ExpandedName, NotationDeclaration>,
+    /// The identity constraints of every element declaration, global or
+    /// local, which share one symbol space.
+    pub identity_constraint_definitions: BTreeMap<ExpandedName, IdentityConstraintDefinition>,
+}
+
+impl SchemaSet {
+    /// Builds the components of `documents`, which may refer to each
+    /// other's components. `include`, `import`, `redefine` and `override`
+    /// are not followed: every document of the set must be given, as
+    /// [`crate::loader::Loader`] gathers them.
+    pub fn new(documents: &[Schema]) -> Result<SchemaSet> {
+        builder::build(documents)
+    }
+
+    /// The type definition `reference` stands for.
+    ///
+    /// # Panics
+    ///
+    /// If the reference names a type the set lacks, which cannot happen
+    /// for references the set was built with.
+    pub fn type_definition<'a>(&'a self, reference: &'a TypeRef) -> &'a TypeDefinition {
+        match reference {
+            TypeRef::Named(name) => &self.type_definitions[name],
+            TypeRef::Anonymous(definition) => definition,
+        }
+    }
+}
+
+/// A type definition named at the top level of a schema, or an anonymous
+/// one in place of the reference.
+#[derive(Debug, Clone)]
+pub enum TypeRef {
+    Named(ExpandedName),
+    Anonymous(Box<TypeDefinition>),
+}
+
+#[derive(Debug, Clone)]
+pub enum TypeDefinition {
+    Simple(SimpleTypeDefinition),
+    Complex(ComplexTypeDefinition),
+}
+
+impl TypeDefinition {
+    pub fn name(&self) -> Option<&ExpandedName> {
+        match self {
+            TypeDefinition::Simple(definition) => definition.name.as_ref(),
+            TypeDefinition::Complex(definition) => definition.name.as_ref(),
+        }
+    }
+
+    /// The type this one derives from. `xs:anyType` is its own base.
+    pub fn base(&self) -> &TypeRef {
+        match self {
+            TypeDefinition::Simple(definition) => &definition.base,
+            TypeDefinition::Complex(definition) => &definition.base,
+        }
+    }
+
+    pub fn final_derivations(&self) -> &[DerivationControl] {
+        match self {
+            TypeDefinition::Simple(definition) => &definition.final_derivations,
+            TypeDefinition::Complex(definition) => &definition.final_derivations,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SimpleTypeDefinition {
+    pub name: Option<ExpandedName>,
+    /// The type restricted, or `xs:anySimpleType` for lists and unions.
+    pub base: TypeRef,
+    pub final_derivations: Vec<DerivationControl>,
+    /// The value space and facets of the type.
+    pub datatype: xsd_datatypes::SimpleType,
+}
+
+#[derive(Debug, Clone)]
+pub struct ComplexTypeDefinition {
+    pub name: Option<ExpandedName>,
+    pub base: TypeRef,
+    pub derivation_method: DerivationMethod,
+    pub is_abstract: bool,
+    pub final_derivations: Vec<DerivationControl>,
+    /// The derivations that may not stand in for the type through
+    /// `xsi:type`.
+    pub blocked_derivations: Vec<DerivationControl>,
+    /// The attribute uses, with those of the base type merged in.
+    pub attribute_uses: Vec<AttributeUse>,
+    pub attribute_wildcard: Option<Wildcard>,
+    pub content_type: ContentType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DerivationMethod {
+    Extension,
+    Restriction,
+}
+
+/// What a complex type allows between the tags of its elements.
+#[derive(Debug, Clone)]
+pub enum ContentType {
+    Empty,
+    /// Character data of the simple type.
+    Simple(xsd_datatypes::SimpleType),
+    ElementOnly(Particle),
+    /// Elements of the particle, with character data between them.
+    Mixed(Particle),
+}
+
+/// A term with the number of times it may occur.
+#[derive(Debug, Clone)]
+pub struct Particle {
+    pub min_occurs: u32,
+    pub max_occurs: AllNNI,
+    pub term: Term,
+}
+
+impl Particle {
+    /// Whether the particle accepts an empty sequence of elements.
+    pub fn is_emptiable(&self) -> bool {
+        self.effective_total_range().0 == 0
+    }
+
+    /// The least and greatest number of elements the particle accepts,
+    /// following section 3.8.6 of the specification.
+    pub fn effective_total_range(&self) -> (u32, AllNNI) {
+        let group = match &self.term {
+            Term::ModelGroup(group) => group,
+            _ => return (self.min_occurs, self.max_occurs),
+        };
+        let ranges: Vec<_> = group
+            .particles
+            .iter()
+            .map(Particle::effective_total_range)
+            .collect();
+        let (min, max) = match group.compositor {
+            Compositor::Sequence | Compositor::All => (
+                ranges
+                    .iter()
+                    .map(|range| range.0)
+                    .fold(0, u32::saturating_add),
+                ranges
+                    .iter()
+                    .map(|range| range.1)
+                    .fold(AllNNI::Bounded(0), |sum, max| match (sum, max) {
+                        (AllNNI::Bounded(a), AllNNI::Bounded(b)) => {
+                            AllNNI::Bounded(a.saturating_add(b))
+                        }
+                        _ => AllNNI::Unbounded,
+                    }),
+            ),
+            Compositor::Choice => (
+                ranges.iter().map(|range| range.0).min().unwrap_or(0),
+                ranges.iter().map(|range| range.1).fold(
+                    AllNNI::Bounded(0),
+                    |greatest, max| match (greatest, max) {
+                        (AllNNI::Bounded(a), AllNNI::Bounded(b)) => AllNNI::Bounded(a.max(b)),
+                        _ => AllNNI::Unbounded,
+                    },
+                ),
+            ),
+        };
+        let max = match (self.max_occurs, max) {
+            (AllNNI::Bounded(0), _) | (_, AllNNI::Bounded(0)) => AllNNI::Bounded(0),
+            (AllNNI::Bounded(a), AllNNI::Bounded(b)) => AllNNI::Bounded(a.saturating_mul(b)),
+            _ => AllNNI::Unbounded,
+        };
+        (self.min_occurs.saturating_mul(min), max)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Term {
+    /// A local element declaration.
+    Element(Box<ElementDeclaration>),
+    /// A reference to a top-level element declaration.
+    ElementRef(ExpandedName),
+    Wildcard(Wildcard),
+    ModelGroup(ModelGroup),
+}
+
+#[derive(Debug, Clone)]
+pub struct ModelGroup {
+    pub compositor: Compositor,
+    pub particles: Vec<Particle>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compositor {
+    All,
+    Choice,
+    Sequence,
+}
+
+#[derive(Debug, Clone)]
+pub struct ElementDeclaration {
+    pub name: ExpandedName,
+    pub type_definition: TypeRef,
+    pub nillable: bool,
+    pub is_abstract: bool,
+    pub value_constraint: Option<ValueConstraint>,
+    /// The heads of the substitution groups the element belongs to.
+    pub substitution_group_affiliations: Vec<ExpandedName>,
+    /// The derivations that may not stand in for the element, through
+    /// `xsi:type` or substitution.
+    pub blocked_substitutions: Vec<DerivationControl>,
+    /// The derivations that may not be members of the element's
+    /// substitution group.
+    pub substitution_group_exclusions: Vec<DerivationControl>,
+    /// The names of the element's identity constraints in
+    /// [`SchemaSet::identity_constraint_definitions`].
+    pub identity_constraints: Vec<ExpandedName>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AttributeDeclaration {
+    pub name: ExpandedName,
+    pub type_definition: TypeRef,
+    pub value_constraint: Option<ValueConstraint>,
+    pub inheritable: bool,
+}
+
+/// An attribute declaration as a complex type uses it.
+#[derive(Debug, Clone)]
+pub struct AttributeUse {
+    pub required: bool,
+    pub declaration: AttributeDeclaration,
+    /// The default or fixed value of the use, which takes precedence over
+    /// that of the declaration.
+    pub value_constraint: Option<ValueConstraint>,
+}
+
+impl AttributeUse {
+    /// The value constraint of the use, or else of the declaration.
+    pub fn effective_value_constraint(&self) -> Option<&ValueConstraint> {
+        self.value_constraint
+            .as_ref()
+            .or(self.declaration.value_constraint.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueConstraint {
+    Default(String),
+    Fixed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct AttributeGroupDefinition {
+    pub name: ExpandedName,
+    pub attribute_uses: Vec<AttributeUse>,
+    pub attribute_wildcard: Option<Wildcard>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Wildcard {
+    pub namespace_constraint: NamespaceConstraint,
+    /// The names of `notQName`, which the wildcard does not match.
+    pub disallowed_names: Vec<ExpandedName>,
+    pub process_contents: ProcessContents,
+}
+
+impl Wildcard {
+    pub fn allows(&self, name: &ExpandedName) -> bool {
+        self.namespace_constraint.allows(name.namespace.as_deref())
+            && !self.disallowed_names.contains(name)
+    }
+}
+
+/// The namespaces a wildcard matches. `None` stands for names in no
+/// namespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NamespaceConstraint {
+    Any,
+    Enumeration(Vec<Option<String>>),
+    Not(Vec<Option<String>>),
+}
+
+impl NamespaceConstraint {
+    pub fn allows(&self, namespace: Option<&str>) -> bool {
+        let listed =
+            |namespaces: &[Option<String>]| namespaces.iter().any(|n| n.as_deref() == namespace);
+        match self {
+            NamespaceConstraint::Any => true,
+            NamespaceConstraint::Enumeration(namespaces) => listed(namespaces),
+            NamespaceConstraint::Not(namespaces) => !listed(namespaces),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NotationDeclaration {
+    pub name: ExpandedName,
+    pub public: Option<String>,
+    pub system: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IdentityConstraintDefinition {
+    pub name: ExpandedName,
+    pub category: IdentityConstraintCategory,
+    pub selector: String,
+    pub fields: Vec<String>,
+    /// The namespace declarations that give meaning to the prefixes of the
+    /// selector and fields.
+    pub namespaces: Vec<Namespace>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdentityConstraintCategory {
+    Unique,
+    Key,
+    /// A reference to the key or unique constraint with this name.
+    KeyRef(ExpandedName),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use xsd_datatypes::XSD_NAMESPACE;
+
+    use crate::components::{
+        ContentType, DerivationMethod, ExpandedName, NamespaceConstraint, SchemaSet, Term,
+        TypeDefinition, TypeRef, ValueConstraint,
+    };
+    use crate::elements::Schema;
+    use crate::error::Error;
+    use crate::simple_types::AllNNI;
+
+    fn build(documents: &[&str]) -> crate::Result<SchemaSet> {
+        let documents: Vec<Schema> = documents
+            .iter()
+            .map(|data| data.parse().expect("a schema document"))
+            .collect();
+        SchemaSet::new(&documents)
+    }
+
+    fn xs(local_name: &str) -> ExpandedName {
+        ExpandedName::new(Some(XSD_NAMESPACE), local_name)
+    }
+
+    #[test]
+    fn builds_the_schema_for_schemas() -> Result<(), anyhow::Error> {
+        let data = read_to_string("../../schema/XMLSchema.xsd")?;
+        let xml = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="http://www.w3.org/XML/1998/namespace">
+  <xs:attribute name="lang" type="xs:language"/>
+</xs:schema>"#;
+
+        let Err(Error::UndefinedReference { kind, name }) = build(&[&data]) else {
+            panic!("expected xml:lang to be undefined");
+        };
+        assert_eq!(kind, "attribute declaration");
+        assert_eq!(name, "{http://www.w3.org/XML/1998/namespace}lang");
+
+        let set = build(&[&data, xml])?;
+        let schema = &set.element_declarations[&xs("schema")];
+        assert_eq!(schema.identity_constraints.len(), 7);
+        let TypeDefinition::Complex(schema_type) = set.type_definition(&schema.type_definition)
+        else {
+            panic!("expected a complex type");
+        };
+        assert!(schema_type
+            .attribute_uses
+            .iter()
+            .any(|u| u.declaration.name.is(None, "targetNamespace")));
+        assert!(matches!(
+            schema_type.content_type,
+            ContentType::ElementOnly(_)
+        ));
+
+        let min_exclusive = &set.element_declarations[&xs("minExclusive")];
+        assert_eq!(min_exclusive.substitution_group_affiliations, [xs("facet")]);
+        let TypeDefinition::Simple(form_choice) = &set.type_definitions[&xs("formChoice")] else {
+            panic!("expected a simple type");
+        };
+        let namespaces = |_: Option<&str>| None;
+        assert!(form_choice.datatype.parse("qualified", &namespaces).is_ok());
+        assert!(form_choice.datatype.parse("both", &namespaces).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn resolves_references_across_documents() -> Result<(), anyhow::Error> {
+        let a = r###"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:a="urn:a" xmlns:b="urn:b" targetNamespace="urn:a" elementFormDefault="qualified">
+  <xs:element name="order" type="a:order"/>
+  <xs:element name="note" type="xs:string"/>
+  <xs:complexType name="order">
+    <xs:complexContent>
+      <xs:extension base="b:base">
+        <xs:sequence>
+          <xs:group ref="a:lines" maxOccurs="3"/>
+          <xs:element ref="a:note" minOccurs="0"/>
+        </xs:sequence>
+        <xs:attributeGroup ref="a:common"/>
+      </xs:extension>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:group name="lines">
+    <xs:choice>
+      <xs:element name="line" type="b:quantity" maxOccurs="unbounded"/>
+      <xs:any namespace="##other" processContents="lax"/>
+    </xs:choice>
+  </xs:group>
+  <xs:attributeGroup name="common">
+    <xs:attribute name="id" type="xs:ID" use="required"/>
+    <xs:anyAttribute namespace="##local"/>
+  </xs:attributeGroup>
+</xs:schema>"###;
+        let b = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:b="urn:b" targetNamespace="urn:b">
+  <xs:complexType name="base">
+    <xs:sequence><xs:element name="date" type="xs:date"/></xs:sequence>
+    <xs:attribute name="version" type="xs:decimal" fixed="1.0"/>
+  </xs:complexType>
+  <xs:simpleType name="quantity">
+    <xs:restriction base="xs:positiveInteger"><xs:maxExclusive value="100"/></xs:restriction>
+  </xs:simpleType>
+</xs:schema>"#;
+        let set = build(&[a, b])?;
+
+        let order = &set.element_declarations[&ExpandedName::new(Some("urn:a"), "order")];
+        let TypeRef::Named(type_name) = &order.type_definition else {
+            panic!("expected a named type");
+        };
+        assert!(type_name.is(Some("urn:a"), "order"));
+        let TypeDefinition::Complex(order_type) = set.type_definition(&order.type_definition)
+        else {
+            panic!("expected a complex type");
+        };
+        assert_eq!(order_type.derivation_method, DerivationMethod::Extension);
+        assert!(matches!(&order_type.base, TypeRef::Named(base) if base.is(Some("urn:b"), "base")));
+        let ContentType::ElementOnly(particle) = &order_type.content_type else {
+            panic!("expected element-only content");
+        };
+        assert_eq!(particle.effective_total_range(), (2, AllNNI::Unbounded));
+        assert!(!particle.is_emptiable());
+
+        let Term::ModelGroup(group) = &particle.term else {
+            panic!("expected a model group");
+        };
+        let Term::ModelGroup(own) = &group.particles[1].term else {
+            panic!("expected the extension's sequence");
+        };
+        assert_eq!(own.particles[0].max_occurs, AllNNI::Bounded(3));
+        let Term::ModelGroup(lines) = &own.particles[0].term else {
+            panic!("expected the lines group");
+        };
+        let Term::Element(line) = &lines.particles[0].term else {
+            panic!("expected a local element");
+        };
+        assert!(line.name.is(Some("urn:a"), "line"));
+        let TypeDefinition::Simple(quantity) = set.type_definition(&line.type_definition) else {
+            panic!("expected a simple type");
+        };
+        let namespaces = |_: Option<&str>| None;
+        assert!(quantity.datatype.parse("99", &namespaces).is_ok());
+        assert!(quantity.datatype.parse("100", &namespaces).is_err());
+        let Term::Wildcard(any) = &lines.particles[1].term else {
+            panic!("expected a wildcard");
+        };
+        assert!(any.allows(&ExpandedName::new(Some("urn:c"), "x")));
+        assert!(!any.allows(&ExpandedName::new(Some("urn:a"), "x")));
+        assert!(!any.allows(&ExpandedName::new(None, "x")));
+        assert!(matches!(own.particles[1].term, Term::ElementRef(_)));
+
+        let [version, id] = &order_type.attribute_uses[..] else {
+            panic!("expected two attribute uses");
+        };
+        assert!(version.declaration.name.is(None, "version"));
+        assert_eq!(
+            version.effective_value_constraint(),
+            Some(&ValueConstraint::Fixed("1.0".to_owned()))
+        );
+        assert!(id.required);
+        let wildcard = order_type.attribute_wildcard.as_ref().expect("a wildcard");
+        assert_eq!(
+            wildcard.namespace_constraint,
+            NamespaceConstraint::Enumeration(vec![None])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn resolves_names_with_nested_declarations() -> Result<(), anyhow::Error> {
+        let xml = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:p="urn:other" targetNamespace="urn:t">
+  <xs:element name="root" xmlns:p="urn:t" type="p:t"/>
+  <xs:complexType name="t" xmlns:q="urn:t" xmlns="urn:t">
+    <xs:sequence xmlns:r="urn:t">
+      <xs:element name="item" type="q:list"/>
+      <xs:element name="other" type="list"/>
+      <xs:element name="plain" type="r:string"/>
+    </xs:sequence>
+  </xs:complexType>
+  <xs:simpleType name="list"><xs:list itemType="xs:int"/></xs:simpleType>
+  <xs:simpleType name="string"><xs:restriction base="xs:string"/></xs:simpleType>
+</xs:schema>"#;
+        // The declarations are written back where they were, with the
+        // names as written, so the written schema means the same.
+        let written = xml.parse::<Schema>()?.to_xml()?;
+        assert!(written.contains(r#"<xs:element xmlns:p="urn:t" name="root" type="p:t"/>"#));
+        for xml in [xml, &written] {
+            let set = build(&[xml])?;
+            let root = &set.element_declarations[&ExpandedName::new(Some("urn:t"), "root")];
+            assert!(
+                matches!(&root.type_definition, TypeRef::Named(name) if name.is(Some("urn:t"), "t"))
+            );
+            let TypeDefinition::Complex(t) = set.type_definition(&root.type_definition) else {
+                panic!("expected a complex type");
+            };
+            let ContentType::ElementOnly(particle) = &t.content_type else {
+                panic!("expected element-only content");
+            };
+            let Term::ModelGroup(sequence) = &particle.term else {
+                panic!("expected a model group");
+            };
+            let types: Vec<_> = sequence
+                .particles
+                .iter()
+                .map(|particle| match &particle.term {
+                    Term::Element(element) => match &element.type_definition {
+                        TypeRef::Named(name) => name.to_string(),
+                        _ => panic!("expected a named type"),
+                    },
+                    _ => panic!("expected a local element"),
+                })
+                .collect();
+            assert_eq!(types, ["{urn:t}list", "{urn:t}list", "{urn:t}string"]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_dangling_and_circular_references() {
+        let schema = |content: &str| {
+            format!(
+                r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:t="urn:t" targetNamespace="urn:t">{}</xs:schema>"#,
+                content
+            )
+        };
+        type Expected = fn(&Error) -> bool;
+        let cases: [(&str, Expected); 7] = [
+            (r#"<xs:element name="e" type="t:missing"/>"#, |e| {
+                matches!(
+                    e,
+                    Error::UndefinedReference {
+                        kind: "type definition",
+                        ..
+                    }
+                )
+            }),
+            (
+                r#"<xs:element name="e" type="u:string"/>"#,
+                |e| matches!(e, Error::UndeclaredPrefix(prefix) if prefix == "u"),
+            ),
+            (
+                r#"<xs:group name="g"><xs:sequence><xs:group ref="t:g"/></xs:sequence></xs:group>"#,
+                |e| {
+                    matches!(
+                        e,
+                        Error::Circular {
+                            kind: "model group definition",
+                            ..
+                        }
+                    )
+                },
+            ),
+            (
+                r#"<xs:simpleType name="s"><xs:restriction base="t:s"/></xs:simpleType>"#,
+                |e| {
+                    matches!(
+                        e,
+                        Error::Circular {
+                            kind: "type definition",
+                            ..
+                        }
+                    )
+                },
+            ),
+            (
+                r#"<xs:element name="e"/><xs:element name="e" type="xs:int"/>"#,
+                |e| {
+                    matches!(
+                        e,
+                        Error::Duplicate {
+                            kind: "element declaration",
+                            ..
+                        }
+                    )
+                },
+            ),
+            (
+                r#"<xs:element name="e"><xs:keyref name="k" refer="t:missing"><xs:selector xpath="a"/><xs:field xpath="@b"/></xs:keyref></xs:element>"#,
+                |e| {
+                    matches!(
+                        e,
+                        Error::UndefinedReference {
+                            kind: "identity constraint",
+                            ..
+                        }
+                    )
+                },
+            ),
+            (
+                r#"<xs:complexType name="c"><xs:sequence><xs:element name="a" minOccurs="2" maxOccurs="1"/></xs:sequence></xs:complexType>"#,
+                |e| matches!(e, Error::Invalid(_)),
+            ),
+        ];
+        for (content, expected) in cases {
+            match build(&[&schema(content)]) {
+                Err(e) => assert!(expected(&e), "{}: {}", content, e),
+                Ok(_) => panic!("{} was accepted", content),
+            }
+        }
+    }
+}
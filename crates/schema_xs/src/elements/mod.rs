@@ -1,47 +1,919 @@
-pub struct All;
-pub struct Annotation;
-pub struct Any;
-pub struct AnyAttribute;
-pub struct Appinfo;
-pub struct Assertion;
-pub struct Attribute;
-pub struct AttributeGroup;
-pub struct Choice;
-pub struct ComplexContent;
-pub struct ComplexType;
-pub struct DefaultOpenContent;
-pub struct Documentation;
-pub struct Element;
-pub struct Enumeration;
-pub struct ExplicitTimezone;
-pub struct Facet;
-pub struct Field;
-pub struct FractionDigits;
-pub struct Group;
-pub struct Import;
-pub struct Include;
-pub struct Key;
-pub struct Keyref;
-pub struct Length;
-pub struct List;
-pub struct MaxExclusive;
-pub struct MaxInclusive;
-pub struct MaxLength;
-pub struct MinExclusive;
-pub struct MinInclusive;
-pub struct MinLength;
-pub struct Notation;
-pub struct OpenContent;
-pub struct Override;
-pub struct Pattern;
-pub struct Redefine;
-pub struct Restriction;
-pub struct Schema;
-pub struct Selector;
-pub struct Sequence;
-pub struct SimpleContent;
-pub struct SimpleType;
-pub struct TotalDigits;
-pub struct Union;
-pub struct Unique;
-pub struct WhiteSpace;
+use std::fmt;
+
+use document::namespace::Namespace;
+use serde::de::{self, Deserializer, MapAccess, Visitor};
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+
+use crate::complex_types::{
+    Attribute, ComplexType, Element, NamedAttributeGroup, NamedGroup, SimpleType,
+};
+use crate::groups::{
+    AnnotationContent, ComplexDerivation, Composition, Facet, Redefinable, SchemaTop,
+    SimpleContentDerivation,
+};
+use crate::simple_types::{
+    AllNNI, DerivationSet, ExplicitTimezoneValue, FormChoice, NamespaceList, OpenContentMode,
+    ProcessContents, Public, QName, QNameList, WhiteSpaceValue, XpathDefaultNamespace,
+};
+
+/// A schema document (`xs:schema`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(from = "RawSchema", into = "RawSchema")]
+pub struct Schema {
+    pub target_namespace: Option<String>,
+    pub version: Option<String>,
+    pub final_default: Option<DerivationSet>,
+    pub block_default: Option<DerivationSet>,
+    pub attribute_form_default: Option<FormChoice>,
+    pub element_form_default: Option<FormChoice>,
+    pub default_attributes: Option<QName>,
+    pub xpath_default_namespace: Option<XpathDefaultNamespace>,
+    pub id: Option<String>,
+    pub xml_lang: Option<String>,
+    /// The namespace declarations on the `schema` element, which give
+    /// meaning to the prefixes of QName-valued attributes.
+    pub namespaces: Vec<Namespace>,
+    /// The foreign attributes of the `schema` element. Its declarations are
+    /// in `namespaces` instead.
+    pub open_attrs: OpenAttrs,
+    pub composition: Vec<Composition>,
+    pub default_open_content: Option<DefaultOpenContent>,
+    pub schema_top: Vec<SchemaTop>,
+}
+
+// The XML representation of `Schema`, with its children in one list.
+#[derive(Deserialize, Serialize)]
+struct RawSchema {
+    #[serde(rename = "@targetNamespace", skip_serializing_if = "Option::is_none")]
+    target_namespace: Option<String>,
+    #[serde(rename = "@version", skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(rename = "@finalDefault", skip_serializing_if = "Option::is_none")]
+    final_default: Option<DerivationSet>,
+    #[serde(rename = "@blockDefault", skip_serializing_if = "Option::is_none")]
+    block_default: Option<DerivationSet>,
+    #[serde(
+        rename = "@attributeFormDefault",
+        skip_serializing_if = "Option::is_none"
+    )]
+    attribute_form_default: Option<FormChoice>,
+    #[serde(
+        rename = "@elementFormDefault",
+        skip_serializing_if = "Option::is_none"
+    )]
+    element_form_default: Option<FormChoice>,
+    #[serde(rename = "@defaultAttributes", skip_serializing_if = "Option::is_none")]
+    default_attributes: Option<QName>,
+    #[serde(
+        rename = "@xpathDefaultNamespace",
+        skip_serializing_if = "Option::is_none"
+    )]
+    xpath_default_namespace: Option<XpathDefaultNamespace>,
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    open_attrs: OpenAttrs,
+    #[serde(
+        rename(serialize = "@xml:lang", deserialize = "@lang"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    xml_lang: Option<String>,
+    #[serde(rename = "$value", default)]
+    content: Vec<SchemaContent>,
+}
+
+#[derive(Deserialize, Serialize)]
+enum SchemaContent {
+    #[serde(rename(serialize = "xs:include", deserialize = "include"))]
+    Include(Include),
+    #[serde(rename(serialize = "xs:import", deserialize = "import"))]
+    Import(Import),
+    #[serde(rename(serialize = "xs:redefine", deserialize = "redefine"))]
+    Redefine(Redefine),
+    #[serde(rename(serialize = "xs:override", deserialize = "override"))]
+    Override(Override),
+    #[serde(rename(serialize = "xs:annotation", deserialize = "annotation"))]
+    Annotation(Annotation),
+    #[serde(rename(
+        serialize = "xs:defaultOpenContent",
+        deserialize = "defaultOpenContent"
+    ))]
+    DefaultOpenContent(DefaultOpenContent),
+    #[serde(rename(serialize = "xs:simpleType", deserialize = "simpleType"))]
+    SimpleType(SimpleType),
+    #[serde(rename(serialize = "xs:complexType", deserialize = "complexType"))]
+    ComplexType(ComplexType),
+    #[serde(rename(serialize = "xs:group", deserialize = "group"))]
+    Group(NamedGroup),
+    #[serde(rename(serialize = "xs:attributeGroup", deserialize = "attributeGroup"))]
+    AttributeGroup(NamedAttributeGroup),
+    #[serde(rename(serialize = "xs:element", deserialize = "element"))]
+    Element(Element),
+    #[serde(rename(serialize = "xs:attribute", deserialize = "attribute"))]
+    Attribute(Attribute),
+    #[serde(rename(serialize = "xs:notation", deserialize = "notation"))]
+    Notation(Notation),
+}
+
+impl From<RawSchema> for Schema {
+    fn from(raw: RawSchema) -> Self {
+        let OpenAttrs {
+            namespaces,
+            attributes,
+        } = raw.open_attrs;
+        let mut schema = Schema {
+            target_namespace: raw.target_namespace,
+            version: raw.version,
+            final_default: raw.final_default,
+            block_default: raw.block_default,
+            attribute_form_default: raw.attribute_form_default,
+            element_form_default: raw.element_form_default,
+            default_attributes: raw.default_attributes,
+            xpath_default_namespace: raw.xpath_default_namespace,
+            id: raw.id,
+            xml_lang: raw.xml_lang,
+            namespaces,
+            open_attrs: OpenAttrs {
+                namespaces: Vec::new(),
+                attributes,
+            },
+            ..Schema::default()
+        };
+
+        for item in raw.content {
+            // Annotations belong to the composition until the first
+            // default open content or top-level component.
+            let in_prolog = schema.default_open_content.is_none() && schema.schema_top.is_empty();
+            match item {
+                SchemaContent::Include(include) => {
+                    schema.composition.push(Composition::Include(include))
+                }
+                SchemaContent::Import(import) => {
+                    schema.composition.push(Composition::Import(import))
+                }
+                SchemaContent::Redefine(redefine) => {
+                    schema.composition.push(Composition::Redefine(redefine))
+                }
+                SchemaContent::Override(r#override) => {
+                    schema.composition.push(Composition::Override(r#override))
+                }
+                SchemaContent::Annotation(annotation) if in_prolog => {
+                    schema.composition.push(Composition::Annotation(annotation))
+                }
+                SchemaContent::Annotation(annotation) => {
+                    schema.schema_top.push(SchemaTop::Annotation(annotation))
+                }
+                SchemaContent::DefaultOpenContent(content) => {
+                    schema.default_open_content = Some(content)
+                }
+                SchemaContent::SimpleType(value) => {
+                    schema.schema_top.push(SchemaTop::SimpleType(value))
+                }
+                SchemaContent::ComplexType(value) => {
+                    schema.schema_top.push(SchemaTop::ComplexType(value))
+                }
+                SchemaContent::Group(value) => schema.schema_top.push(SchemaTop::Group(value)),
+                SchemaContent::AttributeGroup(value) => {
+                    schema.schema_top.push(SchemaTop::AttributeGroup(value))
+                }
+                SchemaContent::Element(value) => schema.schema_top.push(SchemaTop::Element(value)),
+                SchemaContent::Attribute(value) => {
+                    schema.schema_top.push(SchemaTop::Attribute(value))
+                }
+                SchemaContent::Notation(value) => {
+                    schema.schema_top.push(SchemaTop::Notation(value))
+                }
+            }
+        }
+
+        schema
+    }
+}
+
+impl From<Schema> for RawSchema {
+    fn from(schema: Schema) -> Self {
+        let mut content: Vec<SchemaContent> = schema
+            .composition
+            .into_iter()
+            .map(|item| match item {
+                Composition::Include(value) => SchemaContent::Include(value),
+                Composition::Import(value) => SchemaContent::Import(value),
+                Composition::Redefine(value) => SchemaContent::Redefine(value),
+                Composition::Override(value) => SchemaContent::Override(value),
+                Composition::Annotation(value) => SchemaContent::Annotation(value),
+            })
+            .collect();
+        content.extend(
+            schema
+                .default_open_content
+                .map(SchemaContent::DefaultOpenContent),
+        );
+        content.extend(schema.schema_top.into_iter().map(|item| match item {
+            SchemaTop::SimpleType(value) => SchemaContent::SimpleType(value),
+            SchemaTop::ComplexType(value) => SchemaContent::ComplexType(value),
+            SchemaTop::Group(value) => SchemaContent::Group(value),
+            SchemaTop::AttributeGroup(value) => SchemaContent::AttributeGroup(value),
+            SchemaTop::Element(value) => SchemaContent::Element(value),
+            SchemaTop::Attribute(value) => SchemaContent::Attribute(value),
+            SchemaTop::Notation(value) => SchemaContent::Notation(value),
+            SchemaTop::Annotation(value) => SchemaContent::Annotation(value),
+        }));
+
+        RawSchema {
+            target_namespace: schema.target_namespace,
+            version: schema.version,
+            final_default: schema.final_default,
+            block_default: schema.block_default,
+            attribute_form_default: schema.attribute_form_default,
+            element_form_default: schema.element_form_default,
+            default_attributes: schema.default_attributes,
+            xpath_default_namespace: schema.xpath_default_namespace,
+            id: schema.id,
+            open_attrs: OpenAttrs {
+                namespaces: schema.namespaces,
+                attributes: schema.open_attrs.attributes,
+            },
+            xml_lang: schema.xml_lang,
+            content,
+        }
+    }
+}
+
+/// What every schema element may carry besides its own attributes
+/// (`xs:openAttrs`): namespace declarations, and attributes in other
+/// namespaces such as `vc:minVersion`, both as written.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpenAttrs {
+    /// The declarations made on the element, which give meaning to the
+    /// prefixes of QNames in it and its descendants.
+    pub namespaces: Vec<Namespace>,
+    /// The foreign attributes, by qualified name, with their values.
+    pub attributes: Vec<(String, String)>,
+}
+
+impl OpenAttrs {
+    pub fn is_empty(&self) -> bool {
+        self.namespaces.is_empty() && self.attributes.is_empty()
+    }
+}
+
+/// An attribute wildcard (`xs:anyAttribute`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct AnyAttribute {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@namespace", skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<NamespaceList>,
+    #[serde(rename = "@notNamespace", skip_serializing_if = "Option::is_none")]
+    pub not_namespace: Option<NamespaceList>,
+    #[serde(rename = "@processContents", skip_serializing_if = "Option::is_none")]
+    pub process_contents: Option<ProcessContents>,
+    #[serde(rename = "@notQName", skip_serializing_if = "Option::is_none")]
+    pub not_q_name: Option<QNameList>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+}
+
+/// Complex content (`xs:complexContent`) derived from another complex
+/// type.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ComplexContent {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@mixed", skip_serializing_if = "Option::is_none")]
+    pub mixed: Option<bool>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(rename = "$value")]
+    pub derivation: ComplexDerivation,
+}
+
+/// Simple content (`xs:simpleContent`) derived from a simple type or a
+/// complex type with simple content.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct SimpleContent {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(rename = "$value")]
+    pub derivation: SimpleContentDerivation,
+}
+
+/// The open content (`xs:openContent`) of a complex type.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct OpenContent {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@mode", skip_serializing_if = "Option::is_none")]
+    pub mode: Option<OpenContentMode>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(
+        rename(serialize = "xs:any", deserialize = "any"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub any: Option<Any>,
+}
+
+/// The open content applied to every complex type in a schema document
+/// (`xs:defaultOpenContent`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct DefaultOpenContent {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@appliesToEmpty", skip_serializing_if = "Option::is_none")]
+    pub applies_to_empty: Option<bool>,
+    #[serde(rename = "@mode", skip_serializing_if = "Option::is_none")]
+    pub mode: Option<OpenContentMode>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(rename(serialize = "xs:any", deserialize = "any"))]
+    pub any: Any,
+}
+
+/// An element wildcard (`xs:any`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Any {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@minOccurs", skip_serializing_if = "Option::is_none")]
+    pub min_occurs: Option<u32>,
+    #[serde(rename = "@maxOccurs", skip_serializing_if = "Option::is_none")]
+    pub max_occurs: Option<AllNNI>,
+    #[serde(rename = "@namespace", skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<NamespaceList>,
+    #[serde(rename = "@notNamespace", skip_serializing_if = "Option::is_none")]
+    pub not_namespace: Option<NamespaceList>,
+    #[serde(rename = "@processContents", skip_serializing_if = "Option::is_none")]
+    pub process_contents: Option<ProcessContents>,
+    #[serde(rename = "@notQName", skip_serializing_if = "Option::is_none")]
+    pub not_q_name: Option<QNameList>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+}
+
+/// An `xs:include` of a schema document with the same target namespace.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Include {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@schemaLocation")]
+    pub schema_location: String,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+}
+
+/// An `xs:redefine` of the components of another schema document.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Redefine {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@schemaLocation")]
+    pub schema_location: String,
+    #[serde(rename = "$value", default)]
+    pub content: Vec<Redefinable>,
+}
+
+/// An `xs:override` of the components of another schema document.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Override {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@schemaLocation")]
+    pub schema_location: String,
+    #[serde(rename = "$value", default)]
+    pub content: Vec<SchemaTop>,
+}
+
+/// An `xs:import` of the components of another namespace.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Import {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@namespace", skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(rename = "@schemaLocation", skip_serializing_if = "Option::is_none")]
+    pub schema_location: Option<String>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+}
+
+/// The `xs:selector` of an identity constraint.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Selector {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@xpath")]
+    pub xpath: String,
+    #[serde(
+        rename = "@xpathDefaultNamespace",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub xpath_default_namespace: Option<XpathDefaultNamespace>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+}
+
+/// An `xs:field` of an identity constraint.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Field {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@xpath")]
+    pub xpath: String,
+    #[serde(
+        rename = "@xpathDefaultNamespace",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub xpath_default_namespace: Option<XpathDefaultNamespace>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+}
+
+/// A `xs:keyref` constraint, or a reference to one.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Keyref {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@name", skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "@ref", skip_serializing_if = "Option::is_none")]
+    pub ref_attr: Option<QName>,
+    #[serde(rename = "@refer", skip_serializing_if = "Option::is_none")]
+    pub refer: Option<QName>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(
+        rename(serialize = "xs:selector", deserialize = "selector"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub selector: Option<Selector>,
+    #[serde(
+        rename(serialize = "xs:field", deserialize = "field"),
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub field: Vec<Field>,
+}
+
+/// A notation declaration (`xs:notation`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Notation {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@name")]
+    pub name: String,
+    #[serde(rename = "@public", skip_serializing_if = "Option::is_none")]
+    pub public: Option<Public>,
+    #[serde(rename = "@system", skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+}
+
+/// An `xs:annotation` with its application information and documentation.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Annotation {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "$value", default)]
+    pub content: Vec<AnnotationContent>,
+}
+
+/// Application information (`xs:appinfo`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Appinfo {
+    pub source: Option<String>,
+    pub open_attrs: OpenAttrs,
+    pub content: Vec<AnyContent>,
+}
+
+/// Human-readable documentation (`xs:documentation`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Documentation {
+    pub source: Option<String>,
+    pub xml_lang: Option<String>,
+    pub open_attrs: OpenAttrs,
+    pub content: Vec<AnyContent>,
+}
+
+/// An item of the mixed content of `xs:appinfo` and `xs:documentation`.
+/// Element and attribute names are kept without their prefix.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnyContent {
+    Text(String),
+    Element(AnyElement),
+}
+
+/// An element in the content of `xs:appinfo` or `xs:documentation`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnyElement {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub content: Vec<AnyContent>,
+}
+
+/// Reads the attributes and mixed content of an element.
+struct MixedVisitor;
+
+impl<'de> Visitor<'de> for MixedVisitor {
+    type Value = (Vec<(String, String)>, Vec<AnyContent>);
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an element")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut attributes = Vec::new();
+        let mut content = Vec::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if let Some(name) = key.strip_prefix('@') {
+                attributes.push((name.to_owned(), map.next_value()?));
+            } else if key == "$text" {
+                content.push(AnyContent::Text(map.next_value()?));
+            } else {
+                let element = map.next_value_seed(AnyElementSeed(key))?;
+                content.push(AnyContent::Element(element));
+            }
+        }
+        Ok((attributes, content))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok((Vec::new(), vec![AnyContent::Text(value.to_owned())]))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok((Vec::new(), Vec::new()))
+    }
+}
+
+struct AnyElementSeed(String);
+
+impl<'de> de::DeserializeSeed<'de> for AnyElementSeed {
+    type Value = AnyElement;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<AnyElement, D::Error> {
+        let (attributes, content) = deserializer.deserialize_map(MixedVisitor)?;
+        Ok(AnyElement {
+            name: self.0,
+            attributes,
+            content,
+        })
+    }
+}
+
+/// Writes attributes and mixed content as the entries of an element.
+fn serialize_mixed<S: Serializer>(
+    serializer: S,
+    attributes: &[(&str, &str)],
+    content: &[AnyContent],
+) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(None)?;
+    for (name, value) in attributes {
+        map.serialize_entry(&format!("@{name}"), value)?;
+    }
+    for item in content {
+        match item {
+            AnyContent::Text(text) => map.serialize_entry("$text", text)?,
+            AnyContent::Element(element) => map.serialize_entry(&element.name, element)?,
+        }
+    }
+    map.end()
+}
+
+impl Serialize for AnyElement {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let attributes: Vec<_> = self
+            .attributes
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        serialize_mixed(serializer, &attributes, &self.content)
+    }
+}
+
+impl Serialize for Appinfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let open_attrs = self.open_attrs.to_attribute();
+        let source = self.source.iter().map(|source| ("source", source.as_str()));
+        let open = open_attrs
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()));
+        let attributes: Vec<_> = source.chain(open).collect();
+        serialize_mixed(serializer, &attributes, &self.content)
+    }
+}
+
+impl<'de> Deserialize<'de> for Appinfo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (attributes, content) = deserializer.deserialize_map(MixedVisitor)?;
+        let mut appinfo = Appinfo {
+            content,
+            ..Appinfo::default()
+        };
+        for (name, value) in attributes {
+            match name.as_str() {
+                "source" => appinfo.source = Some(value),
+                _ => {
+                    if let Some(open_attrs) = OpenAttrs::from_attribute(&name, &value) {
+                        appinfo.open_attrs = open_attrs;
+                    }
+                }
+            }
+        }
+        Ok(appinfo)
+    }
+}
+
+impl Serialize for Documentation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let open_attrs = self.open_attrs.to_attribute();
+        let source = self.source.iter().map(|source| ("source", source.as_str()));
+        let lang = self.xml_lang.iter().map(|lang| ("xml:lang", lang.as_str()));
+        let open = open_attrs
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()));
+        let attributes: Vec<_> = source.chain(lang).chain(open).collect();
+        serialize_mixed(serializer, &attributes, &self.content)
+    }
+}
+
+impl<'de> Deserialize<'de> for Documentation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (attributes, content) = deserializer.deserialize_map(MixedVisitor)?;
+        let mut documentation = Documentation {
+            content,
+            ..Documentation::default()
+        };
+        for (name, value) in attributes {
+            match name.as_str() {
+                "source" => documentation.source = Some(value),
+                "lang" => documentation.xml_lang = Some(value),
+                _ => {
+                    if let Some(open_attrs) = OpenAttrs::from_attribute(&name, &value) {
+                        documentation.open_attrs = open_attrs;
+                    }
+                }
+            }
+        }
+        Ok(documentation)
+    }
+}
+
+/// A simple type restriction. The `base` attribute and the `simple_type`
+/// child are mutually exclusive, but one of them is required.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Restriction {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@base", skip_serializing_if = "Option::is_none")]
+    pub base: Option<QName>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(
+        rename(serialize = "xs:simpleType", deserialize = "simpleType"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub simple_type: Option<Box<SimpleType>>,
+    #[serde(rename = "$value", default)]
+    pub facets: Vec<Facet>,
+}
+
+/// A list type. The `item_type` attribute and the `simple_type` child are
+/// mutually exclusive, but one of them is required.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct List {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@itemType", skip_serializing_if = "Option::is_none")]
+    pub item_type: Option<QName>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(
+        rename(serialize = "xs:simpleType", deserialize = "simpleType"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub simple_type: Option<Box<SimpleType>>,
+}
+
+/// A union type. `member_types` must be non-empty or there must be at least
+/// one `simple_type` child.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Union {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@memberTypes", skip_serializing_if = "Option::is_none")]
+    pub member_types: Option<String>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+    #[serde(
+        rename(serialize = "xs:simpleType", deserialize = "simpleType"),
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub simple_type: Vec<SimpleType>,
+}
+
+/// The `xs:whiteSpace` facet.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct WhiteSpace {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@value")]
+    pub value: WhiteSpaceValue,
+    #[serde(rename = "@fixed", skip_serializing_if = "Option::is_none")]
+    pub fixed: Option<bool>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+}
+
+/// The `xs:explicitTimezone` facet.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ExplicitTimezone {
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(
+        rename = "@open-attrs",
+        default,
+        skip_serializing_if = "OpenAttrs::is_empty"
+    )]
+    pub open_attrs: OpenAttrs,
+    #[serde(rename = "@value")]
+    pub value: ExplicitTimezoneValue,
+    #[serde(rename = "@fixed", skip_serializing_if = "Option::is_none")]
+    pub fixed: Option<bool>,
+    #[serde(
+        rename(serialize = "xs:annotation", deserialize = "annotation"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation: Option<Annotation>,
+}
@@ -0,0 +1,217 @@
+use std::fmt::{Display, Formatter};
+
+use document::position::Location;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A qualified name uses a prefix that is not declared.
+    UndeclaredPrefix(String),
+    /// A `type`, `base`, `ref` or similar attribute names a component that
+    /// the schema set lacks.
+    UndefinedReference { kind: &'static str, name: String },
+    /// Two top-level components of the same kind share a name.
+    Duplicate { kind: &'static str, name: String },
+    /// A component derives from, or is made of, itself.
+    Circular { kind: &'static str, name: String },
+    /// A component breaks a structural rule of the specification.
+    Invalid(String),
+    /// A simple type cannot be built from its facets.
+    Datatype(xsd_datatypes::Error),
+    /// A schema document could not be read.
+    Io {
+        location: String,
+        source: std::io::Error,
+    },
+    /// A schema document is not well-formed, or not a schema.
+    Syntax {
+        location: String,
+        source: quick_xml::DeError,
+    },
+    /// Two particles of a content model compete for the same element,
+    /// against Unique Particle Attribution. `context` names the type.
+    Ambiguous { context: String, name: String },
+    /// A content model with too many states for Unique Particle Attribution
+    /// to be checked. `context` names the type.
+    TooComplex { context: String },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::UndeclaredPrefix(prefix) => write!(f, "undeclared prefix `{}`", prefix),
+            Error::UndefinedReference { kind, name } => {
+                write!(f, "{} `{}` is not defined", kind, name)
+            }
+            Error::Duplicate { kind, name } => {
+                write!(f, "{} `{}` is defined more than once", kind, name)
+            }
+            Error::Circular { kind, name } => {
+                write!(f, "{} `{}` refers to itself", kind, name)
+            }
+            Error::Invalid(message) => write!(f, "invalid schema: {}", message),
+            Error::Datatype(e) => write!(f, "invalid simple type: {}", e),
+            Error::Io { location, source } => write!(f, "cannot read `{}`: {}", location, source),
+            Error::Syntax { location, source } => {
+                write!(f, "cannot parse `{}`: {}", location, source)
+            }
+            Error::Ambiguous { context, name } => write!(
+                f,
+                "content model of {} is ambiguous: more than one particle matches `{}`",
+                context, name
+            ),
+            Error::TooComplex { context } => write!(
+                f,
+                "content model of {} is too complex to check for ambiguity",
+                context
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Datatype(e) => Some(e),
+            Error::Io { source, .. } => Some(source),
+            Error::Syntax { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<xsd_datatypes::Error> for Error {
+    fn from(e: xsd_datatypes::Error) -> Self {
+        Error::Datatype(e)
+    }
+}
+
+/// A place where an instance document is not valid against the schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub kind: ValidationErrorKind,
+    /// The element at fault, as a path of names written as in the
+    /// instance, such as `/po:order/line[2]`.
+    pub path: String,
+    /// Where the element was read from, when locations were kept.
+    pub location: Option<Location>,
+}
+
+/// What was not valid. Names from the instance are written as in the
+/// instance; what was expected is written in Clark notation, `{ns}local`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationErrorKind {
+    /// An element that needs a declaration and has none.
+    UndeclaredElement { name: String },
+    /// An element the content model does not allow where it appears.
+    UnexpectedElement { name: String, expected: Vec<String> },
+    /// An element that ends before its content model is satisfied.
+    IncompleteContent { expected: Vec<String> },
+    /// Character data in an element whose type allows none.
+    UnexpectedText { text: String },
+    /// An attribute the type neither declares nor matches by wildcard.
+    UnexpectedAttribute { name: String },
+    /// A required attribute that is missing.
+    MissingAttribute { name: String },
+    /// The value of an attribute, or of the element when `attribute` is
+    /// `None`, that is not valid for its type.
+    InvalidValue {
+        attribute: Option<String>,
+        value: String,
+        reason: String,
+    },
+    /// A value that differs from the fixed value of its declaration.
+    NotFixedValue {
+        attribute: Option<String>,
+        value: String,
+        fixed: String,
+    },
+    /// An `xsi:type` that names no type, or a type that may not stand in
+    /// for the declared one.
+    InvalidType { name: String, reason: String },
+    /// An element whose declaration or governing type is abstract.
+    Abstract { name: String },
+    /// An `xsi:nil` the declaration does not allow, or a nilled element
+    /// with content.
+    InvalidNil { reason: String },
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(location) = &self.location {
+            write!(f, "{}: ", location)?;
+        }
+        write!(f, "{}: {}", self.path, self.kind)
+    }
+}
+
+impl Display for ValidationErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationErrorKind::UndeclaredElement { name } => {
+                write!(f, "no declaration for element `{}`", name)
+            }
+            ValidationErrorKind::UnexpectedElement { name, expected } => {
+                write!(f, "element `{}` not allowed here", name)?;
+                write_expected(f, expected)
+            }
+            ValidationErrorKind::IncompleteContent { expected } => {
+                f.write_str("content is incomplete")?;
+                write_expected(f, expected)
+            }
+            ValidationErrorKind::UnexpectedText { text } => {
+                write!(f, "text \"{}\" not allowed", text)
+            }
+            ValidationErrorKind::UnexpectedAttribute { name } => {
+                write!(f, "attribute `{}` not allowed", name)
+            }
+            ValidationErrorKind::MissingAttribute { name } => {
+                write!(f, "required attribute `{}` is missing", name)
+            }
+            ValidationErrorKind::InvalidValue {
+                attribute,
+                value,
+                reason,
+            } => {
+                write!(f, "invalid value \"{}\"", value)?;
+                if let Some(attribute) = attribute {
+                    write!(f, " for attribute `{}`", attribute)?;
+                }
+                write!(f, ": {}", reason)
+            }
+            ValidationErrorKind::NotFixedValue {
+                attribute,
+                value,
+                fixed,
+            } => {
+                write!(f, "value \"{}\"", value)?;
+                if let Some(attribute) = attribute {
+                    write!(f, " of attribute `{}`", attribute)?;
+                }
+                write!(f, " differs from the fixed value \"{}\"", fixed)
+            }
+            ValidationErrorKind::InvalidType { name, reason } => {
+                write!(f, "invalid xsi:type `{}`: {}", name, reason)
+            }
+            ValidationErrorKind::Abstract { name } => write!(f, "`{}` is abstract", name),
+            ValidationErrorKind::InvalidNil { reason } => write!(f, "invalid xsi:nil: {}", reason),
+        }
+    }
+}
+
+fn write_expected(f: &mut Formatter<'_>, expected: &[String]) -> std::fmt::Result {
+    if expected.is_empty() {
+        return Ok(());
+    }
+    f.write_str("; expected ")?;
+    for (index, item) in expected.iter().enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "`{}`", item)?;
+    }
+    Ok(())
+}
+
+impl std::error::Error for ValidationError {}
@@ -0,0 +1,154 @@
+use xsd_datatypes::{Namespaces, Result, SimpleType};
+
+use crate::elements::Restriction;
+use crate::groups::Facet;
+use crate::simple_types::{ExplicitTimezoneValue, WhiteSpaceValue};
+
+impl Facet {
+    /// The local name of the facet element.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Facet::MinExclusive(_) => "minExclusive",
+            Facet::MinInclusive(_) => "minInclusive",
+            Facet::MaxExclusive(_) => "maxExclusive",
+            Facet::MaxInclusive(_) => "maxInclusive",
+            Facet::TotalDigits(_) => "totalDigits",
+            Facet::FractionDigits(_) => "fractionDigits",
+            Facet::Length(_) => "length",
+            Facet::MinLength(_) => "minLength",
+            Facet::MaxLength(_) => "maxLength",
+            Facet::Enumeration(_) => "enumeration",
+            Facet::WhiteSpace(_) => "whiteSpace",
+            Facet::Pattern(_) => "pattern",
+            Facet::Assertion(_) => "assertion",
+            Facet::ExplicitTimezone(_) => "explicitTimezone",
+        }
+    }
+
+    /// The `value` attribute as written, or the `test` of an assertion.
+    pub fn value(&self) -> String {
+        match self {
+            Facet::MinExclusive(facet)
+            | Facet::MinInclusive(facet)
+            | Facet::MaxExclusive(facet)
+            | Facet::MaxInclusive(facet) => facet.value.clone(),
+            Facet::TotalDigits(facet)
+            | Facet::FractionDigits(facet)
+            | Facet::Length(facet)
+            | Facet::MinLength(facet)
+            | Facet::MaxLength(facet) => facet.value.to_string(),
+            Facet::Enumeration(facet) | Facet::Pattern(facet) => facet.value.clone(),
+            Facet::WhiteSpace(facet) => match facet.value {
+                WhiteSpaceValue::Preserve => "preserve",
+                WhiteSpaceValue::Replace => "replace",
+                WhiteSpaceValue::Collapse => "collapse",
+            }
+            .to_owned(),
+            Facet::Assertion(assertion) => assertion.test.clone().unwrap_or_default(),
+            Facet::ExplicitTimezone(facet) => match facet.value {
+                ExplicitTimezoneValue::Optional => "optional",
+                ExplicitTimezoneValue::Required => "required",
+                ExplicitTimezoneValue::Prohibited => "prohibited",
+            }
+            .to_owned(),
+        }
+    }
+}
+
+impl Restriction {
+    /// Applies the facets to `base`, the type the restriction derives
+    /// from. QName values among them are resolved with `namespaces`, the
+    /// declarations in scope on the restriction.
+    pub fn restrict(&self, base: &SimpleType, namespaces: &dyn Namespaces) -> Result<SimpleType> {
+        restrict(&self.facets, base, namespaces)
+    }
+}
+
+/// Applies `facets`, from a simple type or simple content restriction, to
+/// `base`.
+pub(crate) fn restrict(
+    facets: &[Facet],
+    base: &SimpleType,
+    namespaces: &dyn Namespaces,
+) -> Result<SimpleType> {
+    let facets: Vec<_> = facets
+        .iter()
+        .map(|facet| (facet.name().to_owned(), facet.value()))
+        .collect();
+    base.restrict(&facets, namespaces)
+}
+
+#[cfg(test)]
+mod tests {
+    use xsd_datatypes::{Error, SimpleType};
+
+    use crate::elements::Schema;
+    use crate::groups::{SchemaTop, SimpleDerivation};
+
+    #[test]
+    fn checks_values_against_restrictions() -> Result<(), anyhow::Error> {
+        let data = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:t="urn:t" targetNamespace="urn:t">
+  <xs:simpleType name="size">
+    <xs:restriction base="xs:decimal">
+      <xs:minExclusive value="0"/><xs:maxInclusive value="99.9"/><xs:fractionDigits value="1"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="kind">
+    <xs:restriction base="xs:QName">
+      <xs:enumeration value="t:small"/><xs:enumeration value="t:large"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="code">
+    <xs:restriction base="xs:string"><xs:whiteSpace value="collapse"/><xs:length value="2"/></xs:restriction>
+  </xs:simpleType>
+</xs:schema>"#;
+        let schema: Schema = data.parse()?;
+        let namespaces = |prefix: Option<&str>| {
+            schema
+                .namespaces
+                .iter()
+                .find(|namespace| namespace.prefix.as_deref() == prefix)
+                .map(|namespace| namespace.uri.clone())
+        };
+        let types = schema
+            .schema_top
+            .iter()
+            .map(|top| {
+                let SchemaTop::SimpleType(simple_type) = top else {
+                    panic!("expected a simple type");
+                };
+                let SimpleDerivation::Restriction(restriction) = &simple_type.derivation else {
+                    panic!("expected a restriction");
+                };
+                let base = restriction.base.as_deref().unwrap_or_default();
+                let base = SimpleType::from_name(base.trim_start_matches("xs:"))?;
+                restriction.restrict(&base, &namespaces)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let [size, kind, code] = &types[..] else {
+            panic!("expected three types");
+        };
+        assert_eq!(size.parse(" 12.5 ", &namespaces)?.to_string(), "12.5");
+        assert!(matches!(
+            size.parse("0", &namespaces),
+            Err(Error::Facet {
+                facet: "minExclusive",
+                ..
+            })
+        ));
+        assert!(matches!(
+            size.parse("1.25", &namespaces),
+            Err(Error::Facet {
+                facet: "fractionDigits",
+                ..
+            })
+        ));
+        let other = |prefix: Option<&str>| prefix.map(|_| "urn:t".to_owned());
+        assert!(kind.parse("x:large", &other).is_ok());
+        assert!(kind.parse("t:medium", &namespaces).is_err());
+        assert!(code.parse("  ab  ", &namespaces).is_ok());
+        assert!(code.parse("a b", &namespaces).is_err());
+        Ok(())
+    }
+}
@@ -1,13 +1,196 @@
-pub struct AllModel;
-pub struct Assertions;
-pub struct AttrDecls;
-pub struct ComplexTypeModel;
-pub struct Composition;
-pub struct IdentityConstraint;
-pub struct NestedParticle;
-pub struct Particle;
-pub struct Redefinable;
-pub struct SchemaTop;
-pub struct SimpleDerivation;
-pub struct SimpleRestrictionModel;
-pub struct TypeDefParticle;
+use serde::{Deserialize, Serialize};
+
+use crate::complex_types::{
+    All, Assertion, Attribute, AttributeGroupRef, ComplexRestrictionType, ComplexType, Element,
+    ExplicitGroup, ExtensionType, GroupRef, Keybase, NamedAttributeGroup, NamedGroup, NoFixedFacet,
+    NumFacet, SimpleRestrictionType, SimpleType, ValueFacet,
+};
+use crate::elements::{
+    Annotation, Any, Appinfo, Documentation, ExplicitTimezone, Import, Include, Keyref, List,
+    Notation, Override, Redefine, Restriction, Union, WhiteSpace,
+};
+
+/// The `composition` group: the children that bring other schema documents
+/// into a schema.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum Composition {
+    #[serde(rename(serialize = "xs:include", deserialize = "include"))]
+    Include(Include),
+    #[serde(rename(serialize = "xs:import", deserialize = "import"))]
+    Import(Import),
+    #[serde(rename(serialize = "xs:redefine", deserialize = "redefine"))]
+    Redefine(Redefine),
+    #[serde(rename(serialize = "xs:override", deserialize = "override"))]
+    Override(Override),
+    #[serde(rename(serialize = "xs:annotation", deserialize = "annotation"))]
+    Annotation(Annotation),
+}
+
+/// The `schemaTop` group: a top-level component. Annotations between
+/// components are kept in place.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum SchemaTop {
+    #[serde(rename(serialize = "xs:simpleType", deserialize = "simpleType"))]
+    SimpleType(SimpleType),
+    #[serde(rename(serialize = "xs:complexType", deserialize = "complexType"))]
+    ComplexType(ComplexType),
+    #[serde(rename(serialize = "xs:group", deserialize = "group"))]
+    Group(NamedGroup),
+    #[serde(rename(serialize = "xs:attributeGroup", deserialize = "attributeGroup"))]
+    AttributeGroup(NamedAttributeGroup),
+    #[serde(rename(serialize = "xs:element", deserialize = "element"))]
+    Element(Element),
+    #[serde(rename(serialize = "xs:attribute", deserialize = "attribute"))]
+    Attribute(Attribute),
+    #[serde(rename(serialize = "xs:notation", deserialize = "notation"))]
+    Notation(Notation),
+    #[serde(rename(serialize = "xs:annotation", deserialize = "annotation"))]
+    Annotation(Annotation),
+}
+
+/// The `redefinable` group: a component that `xs:redefine` may replace.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum Redefinable {
+    #[serde(rename(serialize = "xs:simpleType", deserialize = "simpleType"))]
+    SimpleType(SimpleType),
+    #[serde(rename(serialize = "xs:complexType", deserialize = "complexType"))]
+    ComplexType(ComplexType),
+    #[serde(rename(serialize = "xs:group", deserialize = "group"))]
+    Group(NamedGroup),
+    #[serde(rename(serialize = "xs:attributeGroup", deserialize = "attributeGroup"))]
+    AttributeGroup(NamedAttributeGroup),
+    #[serde(rename(serialize = "xs:annotation", deserialize = "annotation"))]
+    Annotation(Annotation),
+}
+
+/// The `typeDefParticle` group: the content model of a complex type.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum TypeDefParticle {
+    #[serde(rename(serialize = "xs:group", deserialize = "group"))]
+    Group(GroupRef),
+    #[serde(rename(serialize = "xs:all", deserialize = "all"))]
+    All(All),
+    #[serde(rename(serialize = "xs:choice", deserialize = "choice"))]
+    Choice(ExplicitGroup),
+    #[serde(rename(serialize = "xs:sequence", deserialize = "sequence"))]
+    Sequence(ExplicitGroup),
+}
+
+/// The `nestedParticle` group: a particle inside a model group. `xs:all`
+/// groups use the element, group and any alternatives only.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum Particle {
+    #[serde(rename(serialize = "xs:element", deserialize = "element"))]
+    Element(Element),
+    #[serde(rename(serialize = "xs:group", deserialize = "group"))]
+    Group(GroupRef),
+    #[serde(rename(serialize = "xs:choice", deserialize = "choice"))]
+    Choice(ExplicitGroup),
+    #[serde(rename(serialize = "xs:sequence", deserialize = "sequence"))]
+    Sequence(ExplicitGroup),
+    #[serde(rename(serialize = "xs:any", deserialize = "any"))]
+    Any(Any),
+}
+
+/// An item of the `attrDecls` group: an attribute declaration or attribute
+/// group reference.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum AttrDecl {
+    #[serde(rename(serialize = "xs:attribute", deserialize = "attribute"))]
+    Attribute(Attribute),
+    #[serde(rename(serialize = "xs:attributeGroup", deserialize = "attributeGroup"))]
+    AttributeGroup(AttributeGroupRef),
+}
+
+/// The derivation in complex content.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum ComplexDerivation {
+    #[serde(rename(serialize = "xs:restriction", deserialize = "restriction"))]
+    Restriction(ComplexRestrictionType),
+    #[serde(rename(serialize = "xs:extension", deserialize = "extension"))]
+    Extension(ExtensionType),
+}
+
+/// The derivation in simple content.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum SimpleContentDerivation {
+    #[serde(rename(serialize = "xs:restriction", deserialize = "restriction"))]
+    Restriction(SimpleRestrictionType),
+    #[serde(rename(serialize = "xs:extension", deserialize = "extension"))]
+    Extension(ExtensionType),
+}
+
+/// The model group of a named group.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum RealGroup {
+    #[serde(rename(serialize = "xs:all", deserialize = "all"))]
+    All(All),
+    #[serde(rename(serialize = "xs:choice", deserialize = "choice"))]
+    Choice(ExplicitGroup),
+    #[serde(rename(serialize = "xs:sequence", deserialize = "sequence"))]
+    Sequence(ExplicitGroup),
+}
+
+/// The `identityConstraint` group.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum IdentityConstraint {
+    #[serde(rename(serialize = "xs:unique", deserialize = "unique"))]
+    Unique(Keybase),
+    #[serde(rename(serialize = "xs:key", deserialize = "key"))]
+    Key(Keybase),
+    #[serde(rename(serialize = "xs:keyref", deserialize = "keyref"))]
+    Keyref(Keyref),
+}
+
+/// An item of an annotation.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum AnnotationContent {
+    #[serde(rename(serialize = "xs:appinfo", deserialize = "appinfo"))]
+    Appinfo(Appinfo),
+    #[serde(rename(serialize = "xs:documentation", deserialize = "documentation"))]
+    Documentation(Documentation),
+}
+
+/// The `simpleDerivation` group.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum SimpleDerivation {
+    #[serde(rename(serialize = "xs:restriction", deserialize = "restriction"))]
+    Restriction(Restriction),
+    #[serde(rename(serialize = "xs:list", deserialize = "list"))]
+    List(List),
+    #[serde(rename(serialize = "xs:union", deserialize = "union"))]
+    Union(Union),
+}
+
+/// A constraining facet of a simple type restriction.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum Facet {
+    #[serde(rename(serialize = "xs:minExclusive", deserialize = "minExclusive"))]
+    MinExclusive(ValueFacet),
+    #[serde(rename(serialize = "xs:minInclusive", deserialize = "minInclusive"))]
+    MinInclusive(ValueFacet),
+    #[serde(rename(serialize = "xs:maxExclusive", deserialize = "maxExclusive"))]
+    MaxExclusive(ValueFacet),
+    #[serde(rename(serialize = "xs:maxInclusive", deserialize = "maxInclusive"))]
+    MaxInclusive(ValueFacet),
+    #[serde(rename(serialize = "xs:totalDigits", deserialize = "totalDigits"))]
+    TotalDigits(NumFacet),
+    #[serde(rename(serialize = "xs:fractionDigits", deserialize = "fractionDigits"))]
+    FractionDigits(NumFacet),
+    #[serde(rename(serialize = "xs:length", deserialize = "length"))]
+    Length(NumFacet),
+    #[serde(rename(serialize = "xs:minLength", deserialize = "minLength"))]
+    MinLength(NumFacet),
+    #[serde(rename(serialize = "xs:maxLength", deserialize = "maxLength"))]
+    MaxLength(NumFacet),
+    #[serde(rename(serialize = "xs:enumeration", deserialize = "enumeration"))]
+    Enumeration(NoFixedFacet),
+    #[serde(rename(serialize = "xs:whiteSpace", deserialize = "whiteSpace"))]
+    WhiteSpace(WhiteSpace),
+    #[serde(rename(serialize = "xs:pattern", deserialize = "pattern"))]
+    Pattern(NoFixedFacet),
+    #[serde(rename(serialize = "xs:assertion", deserialize = "assertion"))]
+    Assertion(Assertion),
+    #[serde(rename(serialize = "xs:explicitTimezone", deserialize = "explicitTimezone"))]
+    ExplicitTimezone(ExplicitTimezone),
+}
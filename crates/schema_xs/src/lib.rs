@@ -1,6 +1,17 @@
+// The model mirrors the XML representation, where choices between large
+// components are common; boxing them would only obscure it.
+#![allow(clippy::large_enum_variant)]
+
+pub use error::{Error, Result, ValidationError, ValidationErrorKind};
+
 pub mod attribute_groups;
 pub mod complex_types;
+pub mod components;
 pub mod elements;
+mod error;
+pub mod facets;
 pub mod groups;
+pub mod loader;
 pub mod simple_types;
-mod xml_schema;
+pub mod validator;
+pub mod xml_schema;
@@ -0,0 +1,609 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use document::namespace::Namespace;
+use document::resolver::Resolver;
+
+use crate::complex_types::{NamedAttributeGroup, NamedGroup};
+use crate::components::SchemaSet;
+use crate::elements::{Import, Override, Redefine, Schema};
+use crate::error::{Error, Result};
+use crate::groups::{
+    AttrDecl, ComplexDerivation, Composition, Particle, RealGroup, Redefinable, SchemaTop,
+    SimpleContentDerivation, SimpleDerivation,
+};
+
+/// A schema document as the loader leaves it.
+#[derive(Debug, Clone)]
+pub struct LoadedSchema {
+    /// The URI the document was read from, if it was read by the loader.
+    pub uri: Option<String>,
+    pub schema: Schema,
+}
+
+/// The schema documents that some starting documents lead to, keyed by
+/// target namespace.
+///
+/// Compositions have been applied: chameleon includes have taken the
+/// target namespace of the including document, and the components that
+/// `xs:redefine` and `xs:override` replace have been moved to the
+/// redefining document. The original of a redefined component is kept
+/// under a name with a `#` suffix, which no reference can spell.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaDocuments {
+    pub namespaces: BTreeMap<Option<String>, Vec<LoadedSchema>>,
+}
+
+impl SchemaDocuments {
+    pub fn schemas(&self) -> impl Iterator<Item = &Schema> {
+        self.namespaces
+            .values()
+            .flatten()
+            .map(|loaded| &loaded.schema)
+    }
+
+    /// Builds the components of every document.
+    pub fn build(&self) -> Result<SchemaSet> {
+        let schemas: Vec<Schema> = self.schemas().cloned().collect();
+        SchemaSet::new(&schemas)
+    }
+}
+
+/// Follows the `include`, `import`, `redefine` and `override` elements of
+/// schema documents. A document is read once for each target namespace it
+/// is loaded into, so circular includes and imports end.
+#[derive(Debug)]
+pub struct Loader<R> {
+    resolver: R,
+    documents: SchemaDocuments,
+    /// The URIs and target namespaces of the documents loaded unchanged.
+    loaded: HashSet<(String, Option<String>)>,
+    /// The URIs of the documents being redefined or overridden, to stop
+    /// cycles among them.
+    transforming: Vec<String>,
+    redefinitions: usize,
+}
+
+impl<R: Resolver> Loader<R> {
+    pub fn new(resolver: R) -> Loader<R> {
+        Loader {
+            resolver,
+            documents: SchemaDocuments::default(),
+            loaded: HashSet::new(),
+            transforming: Vec::new(),
+            redefinitions: 0,
+        }
+    }
+
+    /// Loads the document at `location` and the documents it leads to.
+    pub fn load(&mut self, location: &str) -> Result<()> {
+        let (uri, schema) = self.read(None, location)?;
+        if self
+            .loaded
+            .insert((uri.clone(), schema.target_namespace.clone()))
+        {
+            self.add(schema, Some(uri), &[])?;
+        }
+        Ok(())
+    }
+
+    /// Adds a document that was read already, with the URI its locations
+    /// are resolved against, and loads the documents it leads to.
+    pub fn add_schema(&mut self, schema: Schema, uri: Option<&str>) -> Result<()> {
+        if let Some(uri) = uri {
+            self.loaded
+                .insert((uri.to_owned(), schema.target_namespace.clone()));
+        }
+        self.add(schema, uri.map(str::to_owned), &[])?;
+        Ok(())
+    }
+
+    pub fn into_documents(self) -> SchemaDocuments {
+        self.documents
+    }
+
+    fn read(&self, base: Option<&str>, location: &str) -> Result<(String, Schema)> {
+        let io_error = |source| Error::Io {
+            location: location.to_owned(),
+            source,
+        };
+        let resource = self.resolver.resolve(base, location).map_err(io_error)?;
+        let schema = resource
+            .text()
+            .map_err(io_error)?
+            .parse()
+            .map_err(|source| Error::Syntax {
+                location: resource.uri.clone(),
+                source,
+            })?;
+        Ok((resource.uri, schema))
+    }
+
+    /// Applies the compositions of `schema` and keeps it. `overrides` are
+    /// the components of the `xs:override` elements that lead to the
+    /// document; those it defines are dropped, and their names returned.
+    fn add(
+        &mut self,
+        mut schema: Schema,
+        uri: Option<String>,
+        overrides: &[SchemaTop],
+    ) -> Result<BTreeSet<(&'static str, String)>> {
+        let mut overridden = BTreeSet::new();
+        if !overrides.is_empty() {
+            schema.schema_top.retain(|top| {
+                let Some(key) = component_key(top) else {
+                    return true;
+                };
+                let matched = overrides
+                    .iter()
+                    .any(|other| component_key(other) == Some(key.clone()));
+                if matched {
+                    overridden.insert(key);
+                }
+                !matched
+            });
+        }
+
+        for composition in schema.composition.clone() {
+            match composition {
+                Composition::Include(include) if overrides.is_empty() => {
+                    let (included_uri, mut included) =
+                        self.read(uri.as_deref(), &include.schema_location)?;
+                    adopt(&mut included, &schema, &included_uri, "included")?;
+                    if self
+                        .loaded
+                        .insert((included_uri.clone(), included.target_namespace.clone()))
+                    {
+                        self.add(included, Some(included_uri), &[])?;
+                    }
+                }
+                // An include in an overridden document overrides the
+                // included document in the same way.
+                Composition::Include(include) => {
+                    let location = include.schema_location.clone();
+                    let matched =
+                        self.override_document(&schema, uri.as_deref(), &location, overrides)?;
+                    overridden.extend(matched);
+                }
+                Composition::Import(import) => self.import(&schema, uri.as_deref(), &import)?,
+                Composition::Redefine(redefine) => {
+                    self.redefine(&mut schema, uri.as_deref(), &redefine)?
+                }
+                Composition::Override(own) => {
+                    let matched =
+                        self.apply_override(&mut schema, uri.as_deref(), &own, overrides)?;
+                    overridden.extend(matched);
+                }
+                Composition::Annotation(_) => {}
+            }
+        }
+
+        self.documents
+            .namespaces
+            .entry(schema.target_namespace.clone())
+            .or_default()
+            .push(LoadedSchema { uri, schema });
+        Ok(overridden)
+    }
+
+    fn import(&mut self, schema: &Schema, uri: Option<&str>, import: &Import) -> Result<()> {
+        let namespace = import.namespace.as_deref().map(str::trim);
+        if namespace == schema.target_namespace.as_deref() {
+            return Err(Error::Invalid(format!(
+                "a document cannot import its own namespace `{}`",
+                namespace.unwrap_or_default()
+            )));
+        }
+        let Some(location) = &import.schema_location else {
+            return Ok(());
+        };
+        let (imported_uri, imported) = self.read(uri, location)?;
+        if imported.target_namespace.as_deref() != namespace {
+            return Err(Error::Invalid(format!(
+                "imported document `{}` has target namespace `{}`, not `{}`",
+                imported_uri,
+                imported.target_namespace.as_deref().unwrap_or_default(),
+                namespace.unwrap_or_default()
+            )));
+        }
+        if self
+            .loaded
+            .insert((imported_uri.clone(), imported.target_namespace.clone()))
+        {
+            self.add(imported, Some(imported_uri), &[])?;
+        }
+        Ok(())
+    }
+
+    /// Loads the redefined document with the originals of the redefined
+    /// components renamed, and adds the redefinitions to `schema`, where
+    /// references to their own name now reach the originals.
+    fn redefine(
+        &mut self,
+        schema: &mut Schema,
+        uri: Option<&str>,
+        redefine: &Redefine,
+    ) -> Result<()> {
+        let (redefined_uri, mut redefined) = self.read(uri, &redefine.schema_location)?;
+        adopt(&mut redefined, schema, &redefined_uri, "redefined")?;
+        if self.transforming.contains(&redefined_uri) {
+            return Ok(());
+        }
+        for redefinable in &redefine.content {
+            let mut top = match redefinable.clone() {
+                Redefinable::SimpleType(simple_type) => SchemaTop::SimpleType(simple_type),
+                Redefinable::ComplexType(complex_type) => SchemaTop::ComplexType(complex_type),
+                Redefinable::Group(group) => SchemaTop::Group(group),
+                Redefinable::AttributeGroup(group) => SchemaTop::AttributeGroup(group),
+                Redefinable::Annotation(_) => continue,
+            };
+            let Some((kind, name)) = component_key(&top) else {
+                continue;
+            };
+            let Some(original) = redefined.schema_top.iter_mut().find(|other| {
+                component_key(other)
+                    .as_ref()
+                    .is_some_and(|(k, n)| *k == kind && *n == name)
+            }) else {
+                return Err(Error::Invalid(format!(
+                    "`{}` redefines {} `{}`, which it does not define",
+                    redefine.schema_location, kind, name
+                )));
+            };
+            self.redefinitions += 1;
+            let suffix = format!("#{}", self.redefinitions);
+            rename(original, &format!("{}{}", name, suffix));
+            let namespaces = &schema.namespaces;
+            let target_namespace = schema.target_namespace.as_deref();
+            let refers = |qname: &str| refers_to(namespaces, target_namespace, qname, &name);
+            rename_self_references(&mut top, &refers, &suffix);
+            schema.schema_top.push(top);
+        }
+        self.transforming.push(redefined_uri.clone());
+        let result = self.add(redefined, Some(redefined_uri), &[]);
+        self.transforming.pop();
+        result.map(drop)
+    }
+
+    /// Loads the overridden document with the components of `own`, and of
+    /// the overrides that lead to `schema`, in place of its own. Those of
+    /// `own` that replace something move to `schema`; the names of those
+    /// of `outer` that do are returned.
+    fn apply_override(
+        &mut self,
+        schema: &mut Schema,
+        uri: Option<&str>,
+        own: &Override,
+        outer: &[SchemaTop],
+    ) -> Result<BTreeSet<(&'static str, String)>> {
+        let outer_keys: Vec<_> = outer.iter().filter_map(component_key).collect();
+        let own_tops: Vec<_> = own
+            .content
+            .iter()
+            .filter(|top| component_key(top).is_some_and(|key| !outer_keys.contains(&key)))
+            .cloned()
+            .collect();
+        let overrides: Vec<_> = outer.iter().chain(&own_tops).cloned().collect();
+        let matched = self.override_document(schema, uri, &own.schema_location, &overrides)?;
+        for top in own_tops {
+            if component_key(&top).is_some_and(|key| matched.contains(&key)) {
+                schema.schema_top.push(top);
+            }
+        }
+        Ok(matched
+            .into_iter()
+            .filter(|key| outer_keys.contains(key))
+            .collect())
+    }
+
+    fn override_document(
+        &mut self,
+        schema: &Schema,
+        uri: Option<&str>,
+        location: &str,
+        overrides: &[SchemaTop],
+    ) -> Result<BTreeSet<(&'static str, String)>> {
+        let (overridden_uri, mut overridden) = self.read(uri, location)?;
+        adopt(&mut overridden, schema, &overridden_uri, "overridden")?;
+        if self.transforming.contains(&overridden_uri) {
+            return Ok(BTreeSet::new());
+        }
+        self.transforming.push(overridden_uri.clone());
+        let result = self.add(overridden, Some(overridden_uri), overrides);
+        self.transforming.pop();
+        result
+    }
+}
+
+/// Checks that a document brought in by include, redefine or override has
+/// the target namespace of `schema`, or gives it that namespace if it has
+/// none: a chameleon include. Unprefixed references in a chameleon then
+/// name components in its new namespace.
+fn adopt(document: &mut Schema, schema: &Schema, uri: &str, how: &str) -> Result<()> {
+    match (&document.target_namespace, &schema.target_namespace) {
+        (None, Some(namespace)) => {
+            document.target_namespace = Some(namespace.clone());
+            document
+                .namespaces
+                .retain(|declaration| declaration.prefix.is_some() || !declaration.uri.is_empty());
+            if !document
+                .namespaces
+                .iter()
+                .any(|declaration| declaration.prefix.is_none())
+            {
+                document.namespaces.push(Namespace {
+                    prefix: None,
+                    uri: namespace.clone(),
+                });
+            }
+            Ok(())
+        }
+        (own, expected) if own == expected => Ok(()),
+        (own, expected) => Err(Error::Invalid(format!(
+            "{} document `{}` has target namespace `{}`, not `{}`",
+            how,
+            uri,
+            own.as_deref().unwrap_or_default(),
+            expected.as_deref().unwrap_or_default()
+        ))),
+    }
+}
+
+/// The symbol space and name of a top-level component. Simple and complex
+/// types share a symbol space.
+fn component_key(top: &SchemaTop) -> Option<(&'static str, String)> {
+    let (kind, name) = match top {
+        SchemaTop::SimpleType(simple_type) => ("type", simple_type.name.as_deref()?),
+        SchemaTop::ComplexType(complex_type) => ("type", complex_type.name.as_deref()?),
+        SchemaTop::Group(group) => ("group", group.name.as_str()),
+        SchemaTop::AttributeGroup(group) => ("attributeGroup", group.name.as_str()),
+        SchemaTop::Element(element) => ("element", element.name.as_deref()?),
+        SchemaTop::Attribute(attribute) => ("attribute", attribute.name.as_deref()?),
+        SchemaTop::Notation(notation) => ("notation", notation.name.as_str()),
+        SchemaTop::Annotation(_) => return None,
+    };
+    Some((kind, name.trim().to_owned()))
+}
+
+fn rename(top: &mut SchemaTop, name: &str) {
+    match top {
+        SchemaTop::SimpleType(simple_type) => simple_type.name = Some(name.to_owned()),
+        SchemaTop::ComplexType(complex_type) => complex_type.name = Some(name.to_owned()),
+        SchemaTop::Group(group) => group.name = name.to_owned(),
+        SchemaTop::AttributeGroup(group) => group.name = name.to_owned(),
+        _ => {}
+    }
+}
+
+/// Whether `qname`, written in a document with `namespaces`, names the
+/// component `name` of the target namespace.
+fn refers_to(
+    namespaces: &[Namespace],
+    target_namespace: Option<&str>,
+    qname: &str,
+    name: &str,
+) -> bool {
+    let (prefix, local_name) = match qname.trim().split_once(':') {
+        Some((prefix, local_name)) => (Some(prefix), local_name),
+        None => (None, qname.trim()),
+    };
+    let namespace = namespaces
+        .iter()
+        .find(|namespace| namespace.prefix.as_deref() == prefix)
+        .map(|namespace| namespace.uri.as_str())
+        .filter(|uri| !uri.is_empty());
+    local_name == name && namespace == target_namespace
+}
+
+/// Points the references of a redefinition to its own name at the renamed
+/// original: the base of a type, or a group or attribute group reference
+/// in a group.
+fn rename_self_references(top: &mut SchemaTop, refers: &dyn Fn(&str) -> bool, suffix: &str) {
+    let mut rename = |qname: &mut String| {
+        if refers(qname) {
+            *qname = format!("{}{}", qname.trim(), suffix);
+        }
+    };
+    match top {
+        SchemaTop::SimpleType(simple_type) => {
+            if let SimpleDerivation::Restriction(restriction) = &mut simple_type.derivation {
+                if let Some(base) = &mut restriction.base {
+                    rename(base);
+                }
+            }
+        }
+        SchemaTop::ComplexType(complex_type) => {
+            if let Some(content) = &mut complex_type.complex_content {
+                match &mut content.derivation {
+                    ComplexDerivation::Restriction(restriction) => rename(&mut restriction.base),
+                    ComplexDerivation::Extension(extension) => rename(&mut extension.base),
+                }
+            }
+            if let Some(content) = &mut complex_type.simple_content {
+                match &mut content.derivation {
+                    SimpleContentDerivation::Restriction(restriction) => {
+                        rename(&mut restriction.base)
+                    }
+                    SimpleContentDerivation::Extension(extension) => rename(&mut extension.base),
+                }
+            }
+        }
+        SchemaTop::Group(NamedGroup { particle, .. }) => {
+            let particles = match particle {
+                RealGroup::All(all) => &mut all.particles,
+                RealGroup::Choice(group) | RealGroup::Sequence(group) => &mut group.particles,
+            };
+            rename_group_references(particles, &mut rename);
+        }
+        SchemaTop::AttributeGroup(NamedAttributeGroup { attributes, .. }) => {
+            for attribute in attributes {
+                if let AttrDecl::AttributeGroup(reference) = attribute {
+                    rename(&mut reference.ref_attr);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn rename_group_references(particles: &mut [Particle], rename: &mut dyn FnMut(&mut String)) {
+    for particle in particles {
+        match particle {
+            Particle::Group(reference) => rename(&mut reference.ref_attr),
+            Particle::Choice(group) | Particle::Sequence(group) => {
+                rename_group_references(&mut group.particles, rename)
+            }
+            Particle::Element(_) | Particle::Any(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use document::node::Document;
+    use document::resolver::MapResolver;
+
+    use crate::components::{ExpandedName, TypeDefinition};
+    use crate::error::Error;
+    use crate::loader::{Loader, SchemaDocuments};
+    use crate::validator::Validator;
+
+    fn load(files: &[(&str, &str)], location: &str) -> crate::Result<SchemaDocuments> {
+        let resolver: MapResolver = files.iter().copied().collect();
+        let mut loader = Loader::new(resolver);
+        loader.load(location)?;
+        Ok(loader.into_documents())
+    }
+
+    #[test]
+    fn follows_includes_and_imports() -> Result<(), anyhow::Error> {
+        let files = [
+            (
+                "a.xsd",
+                r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:a="urn:a" xmlns:b="urn:b" targetNamespace="urn:a">
+  <xs:include schemaLocation="common.xsd"/>
+  <xs:import namespace="urn:b" schemaLocation="b.xsd"/>
+  <xs:element name="order" type="b:order"/>
+</xs:schema>"#,
+            ),
+            (
+                "common.xsd",
+                r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="code"><xs:restriction base="xs:token"><xs:length value="3"/></xs:restriction></xs:simpleType>
+  <xs:element name="currency" type="code"/>
+</xs:schema>"#,
+            ),
+            (
+                "b.xsd",
+                r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:a="urn:a" targetNamespace="urn:b">
+  <xs:import namespace="urn:a" schemaLocation="a.xsd"/>
+  <xs:complexType name="order">
+    <xs:sequence><xs:element ref="a:currency"/></xs:sequence>
+  </xs:complexType>
+</xs:schema>"#,
+            ),
+        ];
+        let documents = load(&files, "a.xsd")?;
+        let namespaces: Vec<_> = documents
+            .namespaces
+            .iter()
+            .map(|(namespace, loaded)| (namespace.as_deref(), loaded.len()))
+            .collect();
+        assert_eq!(namespaces, [(Some("urn:a"), 2), (Some("urn:b"), 1)]);
+
+        let set = documents.build()?;
+        let currency = &set.element_declarations[&ExpandedName::new(Some("urn:a"), "currency")];
+        assert_eq!(
+            set.type_definition(&currency.type_definition).name(),
+            Some(&ExpandedName::new(Some("urn:a"), "code"))
+        );
+
+        let mut validator = Validator::new(&set)?;
+        let valid = r#"<order xmlns="urn:a"><currency>EUR</currency></order>"#;
+        assert_eq!(validator.validate(&Document::parse(valid)?), Ok(()));
+        let invalid = r#"<order xmlns="urn:a"><currency>EURO</currency></order>"#;
+        assert!(validator.validate(&Document::parse(invalid)?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn applies_redefinitions_and_overrides() -> Result<(), anyhow::Error> {
+        let base = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:p="urn:p" targetNamespace="urn:p" elementFormDefault="qualified">
+  <xs:include schemaLocation="size.xsd"/>
+  <xs:complexType name="person">
+    <xs:sequence><xs:element name="name" type="xs:string"/></xs:sequence>
+  </xs:complexType>
+  <xs:element name="person" type="p:person"/>
+</xs:schema>"#;
+        let size = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:p="urn:p" targetNamespace="urn:p">
+  <xs:simpleType name="size"><xs:restriction base="xs:int"><xs:maxInclusive value="10"/></xs:restriction></xs:simpleType>
+  <xs:element name="size" type="p:size"/>
+</xs:schema>"#;
+        let redefining = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:q="urn:p" targetNamespace="urn:p" elementFormDefault="qualified">
+  <xs:redefine schemaLocation="base.xsd">
+    <xs:complexType name="person">
+      <xs:complexContent>
+        <xs:extension base="q:person">
+          <xs:sequence><xs:element name="age" type="xs:int"/></xs:sequence>
+        </xs:extension>
+      </xs:complexContent>
+    </xs:complexType>
+  </xs:redefine>
+</xs:schema>"#;
+        let overriding = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:p">
+  <xs:override schemaLocation="base.xsd">
+    <xs:simpleType name="size"><xs:restriction base="xs:int"><xs:maxInclusive value="5"/></xs:restriction></xs:simpleType>
+    <xs:element name="unused" type="xs:int"/>
+  </xs:override>
+</xs:schema>"#;
+        let files = [
+            ("base.xsd", base),
+            ("size.xsd", size),
+            ("redefining.xsd", redefining),
+            ("overriding.xsd", overriding),
+        ];
+
+        let set = load(&files, "redefining.xsd")?.build()?;
+        let TypeDefinition::Complex(original) =
+            &set.type_definitions[&ExpandedName::new(Some("urn:p"), "person#1")]
+        else {
+            panic!("expected the original person type");
+        };
+        assert!(original.name.is_some());
+        let mut validator = Validator::new(&set)?;
+        let person = r#"<person xmlns="urn:p"><name>Ann</name><age>7</age></person>"#;
+        assert_eq!(validator.validate(&Document::parse(person)?), Ok(()));
+
+        let set = load(&files, "overriding.xsd")?.build()?;
+        assert!(!set
+            .element_declarations
+            .contains_key(&ExpandedName::new(Some("urn:p"), "unused")));
+        let mut validator = Validator::new(&set)?;
+        let size = |value: &str| format!(r#"<size xmlns="urn:p">{}</size>"#, value);
+        assert_eq!(validator.validate(&Document::parse(&size("5"))?), Ok(()));
+        assert!(validator.validate(&Document::parse(&size("7"))?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_mismatched_namespaces_and_missing_documents() {
+        let files = [
+            (
+                "a.xsd",
+                r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:a"><xs:include schemaLocation="b.xsd"/></xs:schema>"#,
+            ),
+            (
+                "b.xsd",
+                r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:b"/>"#,
+            ),
+            (
+                "c.xsd",
+                r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"><xs:import namespace="urn:x" schemaLocation="x.xsd"/></xs:schema>"#,
+            ),
+        ];
+        assert!(matches!(load(&files, "a.xsd"), Err(Error::Invalid(_))));
+        assert!(matches!(
+            load(&files, "c.xsd"),
+            Err(Error::Io { location, .. }) if location == "x.xsd"
+        ));
+    }
+}
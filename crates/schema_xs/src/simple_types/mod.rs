@@ -1,16 +1,205 @@
-pub struct AllNNI;
-pub struct BasicNamespaceList;
-pub struct BlockSet;
-pub struct DerivationControl;
-pub struct DerivationSet;
-pub struct FormChoice;
-pub struct FullDerivationSet;
-pub struct NamespaceList;
-pub struct Public;
-pub struct QnameList;
-pub struct QnameListA;
-pub struct ReducedDerivationControl;
-pub struct SimpleDerivationSet;
-pub struct SpecialNamespaceList;
-pub struct TypeDerivationControl;
-pub struct XpathDefaultNamespace;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+/// Whether local declarations are qualified with the target namespace.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FormChoice {
+    Qualified,
+    Unqualified,
+}
+
+/// A derivation method, as listed in `final` and `block` attributes.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DerivationControl {
+    Substitution,
+    Extension,
+    Restriction,
+    List,
+    Union,
+}
+
+impl fmt::Display for DerivationControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DerivationControl::Substitution => "substitution",
+            DerivationControl::Extension => "extension",
+            DerivationControl::Restriction => "restriction",
+            DerivationControl::List => "list",
+            DerivationControl::Union => "union",
+        })
+    }
+}
+
+impl FromStr for DerivationControl {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "substitution" => Ok(DerivationControl::Substitution),
+            "extension" => Ok(DerivationControl::Extension),
+            "restriction" => Ok(DerivationControl::Restriction),
+            "list" => Ok(DerivationControl::List),
+            "union" => Ok(DerivationControl::Union),
+            _ => Err(format!("invalid derivation method `{s}`")),
+        }
+    }
+}
+
+/// `#all` or a (possibly empty) list of derivation methods. Used for `final`,
+/// `block`, `finalDefault` and `blockDefault`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DerivationSet {
+    All,
+    List(Vec<DerivationControl>),
+}
+
+impl fmt::Display for DerivationSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DerivationSet::All => f.write_str("#all"),
+            DerivationSet::List(list) => {
+                for (index, control) in list.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(" ")?;
+                    }
+                    write!(f, "{control}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl FromStr for DerivationSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim() == "#all" {
+            return Ok(DerivationSet::All);
+        }
+        s.split_ascii_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(DerivationSet::List)
+    }
+}
+
+/// A non-negative integer or `unbounded`, for `maxOccurs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllNNI {
+    Bounded(u32),
+    Unbounded,
+}
+
+impl fmt::Display for AllNNI {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AllNNI::Bounded(value) => write!(f, "{value}"),
+            AllNNI::Unbounded => f.write_str("unbounded"),
+        }
+    }
+}
+
+impl FromStr for AllNNI {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "unbounded" => Ok(AllNNI::Unbounded),
+            value => value
+                .parse()
+                .map(AllNNI::Bounded)
+                .map_err(|_| format!("invalid occurrence bound `{s}`")),
+        }
+    }
+}
+
+/// Implements serde for a type written as an attribute value through its
+/// `Display` and `FromStr` impls.
+macro_rules! string_serde {
+    ($($name:ident),*) => {$(
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer)?
+                    .parse()
+                    .map_err(de::Error::custom)
+            }
+        }
+    )*};
+}
+
+string_serde!(DerivationSet, AllNNI);
+
+/// The `use` of an attribute declaration.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Use {
+    Prohibited,
+    Optional,
+    Required,
+}
+
+/// How strictly a wildcard validates what it matches.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessContents {
+    Skip,
+    Lax,
+    Strict,
+}
+
+/// The `mode` of open content.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OpenContentMode {
+    None,
+    Interleave,
+    Suffix,
+}
+
+/// The value of the `xs:whiteSpace` facet.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WhiteSpaceValue {
+    Preserve,
+    Replace,
+    Collapse,
+}
+
+/// The value of the `xs:explicitTimezone` facet.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExplicitTimezoneValue {
+    Optional,
+    Required,
+    Prohibited,
+}
+
+/// A qualified name, resolved against the namespace declarations in scope.
+pub type QName = String;
+
+/// `##any`, `##other`, or a whitespace-separated list of namespace names,
+/// `##targetNamespace` and `##local`.
+pub type NamespaceList = String;
+
+/// A whitespace-separated list of qualified names, `##defined` and
+/// `##definedSibling`, excluded from a wildcard.
+pub type QNameList = String;
+
+/// A namespace name, `##defaultNamespace`, `##targetNamespace` or `##local`.
+pub type XpathDefaultNamespace = String;
+
+/// A public identifier.
+pub type Public = String;
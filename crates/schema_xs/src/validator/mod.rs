@@ -0,0 +1,1357 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use document::chars::is_whitespace;
+use document::content::{Id, Terms, EMPTY, NOT_ALLOWED};
+use document::node::{Document, Element, Node, NodeId, QName};
+use xsd_datatypes::{Builtin, Namespaces, SimpleType};
+
+use crate::components::{
+    AttributeUse, Compositor, ContentType, DerivationMethod, ElementDeclaration, ExpandedName,
+    ModelGroup, NamespaceConstraint, Particle, SchemaSet, Term, TypeDefinition, TypeRef,
+    ValueConstraint, Wildcard,
+};
+use crate::error::{Error, Result, ValidationError, ValidationErrorKind};
+use crate::simple_types::{AllNNI, DerivationControl, ProcessContents};
+
+/// The namespace of `xsi:type`, `xsi:nil` and the schema location hints.
+pub const XSI_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+/// How many states of one content model are explored for Unique Particle
+/// Attribution; models with more are rejected as too complex to check.
+const MAX_STATES: usize = 10_000;
+
+#[derive(Debug, Clone)]
+struct ElementDef {
+    name: ExpandedName,
+    type_index: usize,
+    nillable: bool,
+    is_abstract: bool,
+    value_constraint: Option<ValueConstraint>,
+    blocked: Vec<DerivationControl>,
+    /// The members of the element's substitution group that may stand in
+    /// for it.
+    substitutes: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct AttributeDef {
+    name: ExpandedName,
+    required: bool,
+    datatype: SimpleType,
+    value_constraint: Option<ValueConstraint>,
+}
+
+/// A simple or complex type definition. Simple types have no attributes
+/// and simple content.
+#[derive(Debug, Clone)]
+struct TypeDef {
+    name: Option<ExpandedName>,
+    /// The index of the base type; `xs:anyType` is its own base.
+    base: usize,
+    derivation: DerivationControl,
+    is_abstract: bool,
+    blocked: Vec<DerivationControl>,
+    attributes: Vec<AttributeDef>,
+    attribute_wildcard: Option<Wildcard>,
+    content: Content,
+}
+
+#[derive(Debug, Clone)]
+enum Content {
+    Empty,
+    /// Character data of the simple type.
+    Simple(SimpleType),
+    /// Elements matching the model, with character data between them when
+    /// `mixed`.
+    Elements {
+        model: Id,
+        mixed: bool,
+    },
+}
+
+/// What the particle of a content model matches.
+#[derive(Debug, Clone)]
+enum Leaf {
+    Element(usize),
+    Wildcard(Wildcard),
+}
+
+/// The particle an element of the content was attributed to.
+enum Attribution {
+    Element(usize),
+    Wildcard(ProcessContents),
+}
+
+/// The schema components, with types and element declarations numbered
+/// so that content models can refer to them.
+#[derive(Debug, Clone)]
+struct Components {
+    types: Vec<TypeDef>,
+    elements: Vec<ElementDef>,
+    leaves: Vec<Leaf>,
+    named_types: HashMap<ExpandedName, usize>,
+    global_elements: HashMap<ExpandedName, usize>,
+    global_attributes: HashMap<ExpandedName, AttributeDef>,
+}
+
+/// Assesses the schema-validity of documents against a [`SchemaSet`].
+///
+/// Content models are matched with derivatives, as in the RELAX NG
+/// validator, and checked for Unique Particle Attribution when the
+/// validator is built. Default values are checked but not added to the
+/// document.
+#[derive(Debug, Clone)]
+pub struct Validator {
+    components: Components,
+    terms: Terms,
+}
+
+impl Validator {
+    /// Prepares the content models of `set`, which must satisfy Unique
+    /// Particle Attribution.
+    pub fn new(set: &SchemaSet) -> Result<Validator> {
+        let mut compiler = Compiler {
+            set,
+            terms: Terms::new(),
+            types: Vec::new(),
+            elements: Vec::new(),
+            leaves: Vec::new(),
+            named_types: HashMap::new(),
+            global_elements: HashMap::new(),
+        };
+        for name in set.type_definitions.keys() {
+            compiler
+                .named_types
+                .insert(name.clone(), compiler.types.len());
+            compiler.types.push(None);
+        }
+        for name in set.element_declarations.keys() {
+            compiler
+                .global_elements
+                .insert(name.clone(), compiler.elements.len());
+            compiler.elements.push(None);
+        }
+        for (name, definition) in &set.type_definitions {
+            let definition = compiler.type_definition(definition);
+            compiler.types[compiler.named_types[name]] = Some(definition);
+        }
+        for (name, declaration) in &set.element_declarations {
+            let declaration = compiler.element_declaration(declaration);
+            compiler.elements[compiler.global_elements[name]] = Some(declaration);
+        }
+        let global_attributes = set
+            .attribute_declarations
+            .iter()
+            .map(|(name, declaration)| {
+                let definition = AttributeDef {
+                    name: name.clone(),
+                    required: false,
+                    datatype: compiler.datatype(&declaration.type_definition),
+                    value_constraint: declaration.value_constraint.clone(),
+                };
+                (name.clone(), definition)
+            })
+            .collect();
+
+        let mut validator = Validator {
+            components: Components {
+                types: compiler.types.into_iter().flatten().collect(),
+                elements: compiler.elements.into_iter().flatten().collect(),
+                leaves: compiler.leaves,
+                named_types: compiler.named_types,
+                global_elements: compiler.global_elements,
+                global_attributes,
+            },
+            terms: compiler.terms,
+        };
+        validator.components.group_substitutes(set);
+        for index in 0..validator.components.types.len() {
+            if let Content::Elements { model, .. } = validator.components.types[index].content {
+                validator.check_unique_attribution(index, model)?;
+            }
+        }
+        Ok(validator)
+    }
+
+    /// Checks a document, reporting every error. After an error in the
+    /// content of an element, the offending child is skipped, so later
+    /// errors are still found.
+    pub fn validate(
+        &mut self,
+        document: &Document,
+    ) -> std::result::Result<(), Vec<ValidationError>> {
+        let mut assessment = Assessment {
+            components: &self.components,
+            terms: &mut self.terms,
+            document,
+            errors: Vec::new(),
+        };
+        if let Some(Node::Element(element)) = document.node(document.root) {
+            let path = format!("/{}", element.name);
+            let declaration = assessment
+                .components
+                .global_elements
+                .get(&expanded(&element.name))
+                .copied();
+            match (declaration, element.attribute(Some(XSI_NAMESPACE), "type")) {
+                (None, None) => assessment.error(
+                    element,
+                    &path,
+                    ValidationErrorKind::UndeclaredElement {
+                        name: element.name.to_string(),
+                    },
+                ),
+                _ => assessment.element(document.root, declaration, &path),
+            }
+        }
+        match assessment.errors.is_empty() {
+            true => Ok(()),
+            false => Err(assessment.errors),
+        }
+    }
+
+    /// Explores the states of a content model, failing if two particles
+    /// compete for an element in any of them, or if there are more states
+    /// than [`MAX_STATES`].
+    fn check_unique_attribution(&mut self, type_index: usize, model: Id) -> Result<()> {
+        let mut seen = HashSet::from([model]);
+        let mut pending = vec![model];
+        while let Some(p) = pending.pop() {
+            let first: Vec<_> = self.terms.first(p).into_iter().collect();
+            for (index, &a) in first.iter().enumerate() {
+                for &b in &first[index + 1..] {
+                    if let Some(name) = self.components.competing(a, b) {
+                        return Err(Error::Ambiguous {
+                            context: self.components.describe_type(type_index),
+                            name,
+                        });
+                    }
+                }
+            }
+            for leaf in first {
+                let next = self.terms.derive(p, leaf);
+                if seen.contains(&next) {
+                    continue;
+                }
+                if seen.len() == MAX_STATES {
+                    return Err(Error::TooComplex {
+                        context: self.components.describe_type(type_index),
+                    });
+                }
+                seen.insert(next);
+                pending.push(next);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Numbers the components of a schema set and compiles their content
+/// models. Named types and global elements are numbered up front, so that
+/// recursive references resolve.
+struct Compiler<'a> {
+    set: &'a SchemaSet,
+    terms: Terms,
+    types: Vec<Option<TypeDef>>,
+    elements: Vec<Option<ElementDef>>,
+    leaves: Vec<Leaf>,
+    named_types: HashMap<ExpandedName, usize>,
+    global_elements: HashMap<ExpandedName, usize>,
+}
+
+impl Compiler<'_> {
+    fn type_ref(&mut self, reference: &TypeRef) -> usize {
+        match reference {
+            TypeRef::Named(name) => self.named_types[name],
+            TypeRef::Anonymous(definition) => {
+                let definition = self.type_definition(definition);
+                self.types.push(Some(definition));
+                self.types.len() - 1
+            }
+        }
+    }
+
+    fn type_definition(&mut self, definition: &TypeDefinition) -> TypeDef {
+        match definition {
+            TypeDefinition::Simple(simple) => TypeDef {
+                name: simple.name.clone(),
+                base: self.type_ref(&simple.base),
+                derivation: DerivationControl::Restriction,
+                is_abstract: false,
+                blocked: Vec::new(),
+                attributes: Vec::new(),
+                attribute_wildcard: None,
+                content: Content::Simple(simple.datatype.clone()),
+            },
+            TypeDefinition::Complex(complex) => {
+                let content = match &complex.content_type {
+                    ContentType::Empty => Content::Empty,
+                    ContentType::Simple(datatype) => Content::Simple(datatype.clone()),
+                    ContentType::ElementOnly(particle) => Content::Elements {
+                        model: self.particle(particle),
+                        mixed: false,
+                    },
+                    ContentType::Mixed(particle) => Content::Elements {
+                        model: self.particle(particle),
+                        mixed: true,
+                    },
+                };
+                TypeDef {
+                    name: complex.name.clone(),
+                    base: self.type_ref(&complex.base),
+                    derivation: match complex.derivation_method {
+                        DerivationMethod::Extension => DerivationControl::Extension,
+                        DerivationMethod::Restriction => DerivationControl::Restriction,
+                    },
+                    is_abstract: complex.is_abstract,
+                    blocked: complex.blocked_derivations.clone(),
+                    attributes: complex
+                        .attribute_uses
+                        .iter()
+                        .map(|attribute_use| self.attribute_use(attribute_use))
+                        .collect(),
+                    attribute_wildcard: complex.attribute_wildcard.clone(),
+                    content,
+                }
+            }
+        }
+    }
+
+    fn element_declaration(&mut self, declaration: &ElementDeclaration) -> ElementDef {
+        ElementDef {
+            name: declaration.name.clone(),
+            type_index: self.type_ref(&declaration.type_definition),
+            nillable: declaration.nillable,
+            is_abstract: declaration.is_abstract,
+            value_constraint: declaration.value_constraint.clone(),
+            blocked: declaration.blocked_substitutions.clone(),
+            substitutes: Vec::new(),
+        }
+    }
+
+    fn attribute_use(&self, attribute_use: &AttributeUse) -> AttributeDef {
+        AttributeDef {
+            name: attribute_use.declaration.name.clone(),
+            required: attribute_use.required,
+            datatype: self.datatype(&attribute_use.declaration.type_definition),
+            value_constraint: attribute_use.effective_value_constraint().cloned(),
+        }
+    }
+
+    fn datatype(&self, reference: &TypeRef) -> SimpleType {
+        match self.set.type_definition(reference) {
+            TypeDefinition::Simple(definition) => definition.datatype.clone(),
+            TypeDefinition::Complex(_) => SimpleType::builtin(Builtin::AnySimpleType),
+        }
+    }
+
+    fn particle(&mut self, particle: &Particle) -> Id {
+        let term = match &particle.term {
+            Term::Element(declaration) => {
+                let declaration = self.element_declaration(declaration);
+                self.elements.push(Some(declaration));
+                self.leaf(Leaf::Element(self.elements.len() - 1))
+            }
+            Term::ElementRef(name) => self.leaf(Leaf::Element(self.global_elements[name])),
+            Term::Wildcard(wildcard) => self.leaf(Leaf::Wildcard(wildcard.clone())),
+            Term::ModelGroup(group) => self.model_group(group),
+        };
+        let max_occurs = match particle.max_occurs {
+            AllNNI::Bounded(max) => Some(max),
+            AllNNI::Unbounded => None,
+        };
+        self.terms.repeat(term, particle.min_occurs, max_occurs)
+    }
+
+    fn leaf(&mut self, leaf: Leaf) -> Id {
+        self.leaves.push(leaf);
+        self.terms.leaf(self.leaves.len() - 1)
+    }
+
+    fn model_group(&mut self, group: &ModelGroup) -> Id {
+        let particles: Vec<_> = group
+            .particles
+            .iter()
+            .map(|particle| self.particle(particle))
+            .collect();
+        match group.compositor {
+            Compositor::Sequence => particles
+                .into_iter()
+                .rev()
+                .fold(EMPTY, |rest, p| self.terms.group(p, rest)),
+            Compositor::Choice => particles
+                .into_iter()
+                .fold(NOT_ALLOWED, |rest, p| self.terms.choice(p, rest)),
+            Compositor::All => particles
+                .into_iter()
+                .fold(EMPTY, |rest, p| self.terms.interleave(p, rest)),
+        }
+    }
+}
+
+impl Components {
+    /// Fills the substitution groups of the global elements with the
+    /// members their heads do not block.
+    fn group_substitutes(&mut self, set: &SchemaSet) {
+        for (name, declaration) in &set.element_declarations {
+            let member = self.global_elements[name];
+            let mut heads: Vec<_> = declaration.substitution_group_affiliations.clone();
+            let mut seen = HashSet::new();
+            while let Some(head) = heads.pop() {
+                if !seen.insert(head.clone()) {
+                    continue;
+                }
+                if let Some(affiliations) = set
+                    .element_declarations
+                    .get(&head)
+                    .map(|head| &head.substitution_group_affiliations)
+                {
+                    heads.extend(affiliations.iter().cloned());
+                }
+                let head = self.global_elements[&head];
+                if head != member && self.may_substitute(member, head) {
+                    self.elements[head].substitutes.push(member);
+                }
+            }
+        }
+    }
+
+    fn may_substitute(&self, member: usize, head: usize) -> bool {
+        let head = &self.elements[head];
+        if head.blocked.contains(&DerivationControl::Substitution) {
+            return false;
+        }
+        let derivations = self
+            .derivations(self.elements[member].type_index, head.type_index)
+            .unwrap_or_default();
+        let blocked = &self.types[head.type_index].blocked;
+        !derivations
+            .iter()
+            .any(|derivation| head.blocked.contains(derivation) || blocked.contains(derivation))
+    }
+
+    /// The derivation steps from the type `from` up to the type `to`, or
+    /// `None` if `from` does not derive from `to`.
+    fn derivations(&self, mut from: usize, to: usize) -> Option<Vec<DerivationControl>> {
+        let mut derivations = Vec::new();
+        loop {
+            if from == to {
+                return Some(derivations);
+            }
+            let definition = &self.types[from];
+            if definition.base == from {
+                return None;
+            }
+            derivations.push(definition.derivation);
+            from = definition.base;
+        }
+    }
+
+    /// The element declaration of a leaf, or one of its substitutes, that
+    /// an element named `name` matches.
+    fn matching(&self, element: usize, name: &ExpandedName) -> Option<usize> {
+        let declaration = &self.elements[element];
+        if &declaration.name == name {
+            return Some(element);
+        }
+        declaration
+            .substitutes
+            .iter()
+            .copied()
+            .find(|&substitute| &self.elements[substitute].name == name)
+    }
+
+    fn names(&self, element: usize) -> Vec<&ExpandedName> {
+        let declaration = &self.elements[element];
+        std::iter::once(&declaration.name)
+            .chain(
+                declaration
+                    .substitutes
+                    .iter()
+                    .map(|&substitute| &self.elements[substitute].name),
+            )
+            .collect()
+    }
+
+    /// A name both leaves match, when they compete for elements. An
+    /// element declaration takes precedence over a wildcard, so only two
+    /// declarations or two wildcards can compete.
+    fn competing(&self, a: usize, b: usize) -> Option<String> {
+        match (&self.leaves[a], &self.leaves[b]) {
+            (Leaf::Element(a), Leaf::Element(b)) => {
+                let names = self.names(*b);
+                self.names(*a)
+                    .into_iter()
+                    .find(|name| names.contains(name))
+                    .map(ToString::to_string)
+            }
+            (Leaf::Wildcard(a), Leaf::Wildcard(b)) => {
+                match overlaps(&a.namespace_constraint, &b.namespace_constraint) {
+                    true => Some(describe_wildcard(a)),
+                    false => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn describe_type(&self, index: usize) -> String {
+        if let Some(name) = &self.types[index].name {
+            return format!("type `{}`", name);
+        }
+        match self
+            .elements
+            .iter()
+            .find(|element| element.type_index == index)
+        {
+            Some(element) => format!("the type of element `{}`", element.name),
+            None => "an anonymous type".to_owned(),
+        }
+    }
+
+    /// What the leaves match, in Clark notation.
+    fn expected(&self, leaves: &BTreeSet<usize>) -> Vec<String> {
+        let mut expected = BTreeSet::new();
+        for &leaf in leaves {
+            match &self.leaves[leaf] {
+                Leaf::Element(element) => {
+                    expected.extend(self.names(*element).into_iter().map(ToString::to_string))
+                }
+                Leaf::Wildcard(wildcard) => {
+                    expected.insert(describe_wildcard(wildcard));
+                }
+            }
+        }
+        expected.into_iter().collect()
+    }
+}
+
+/// The state of one validation: the errors found so far.
+struct Assessment<'a> {
+    components: &'a Components,
+    terms: &'a mut Terms,
+    document: &'a Document,
+    errors: Vec<ValidationError>,
+}
+
+impl Assessment<'_> {
+    fn error(&mut self, element: &Element, path: &str, kind: ValidationErrorKind) {
+        self.errors.push(ValidationError {
+            kind,
+            path: path.to_owned(),
+            location: element.location.clone(),
+        });
+    }
+
+    /// Assesses the element `id` against its declaration, or only against
+    /// its `xsi:type` when it has none. Without either, it is assessed
+    /// laxly.
+    fn element(&mut self, id: NodeId, declaration: Option<usize>, path: &str) {
+        let document = self.document;
+        let Some(Node::Element(element)) = document.node(id) else {
+            return;
+        };
+        let namespaces = |prefix: Option<&str>| document.lookup_namespace_uri(id, prefix);
+        let components = self.components;
+        let declaration = declaration.map(|index| &components.elements[index]);
+
+        if let Some(declaration) = declaration.filter(|declaration| declaration.is_abstract) {
+            let name = declaration.name.to_string();
+            self.error(element, path, ValidationErrorKind::Abstract { name });
+        }
+        let declared_type = declaration.map(|declaration| declaration.type_index);
+        let type_index = match element.attribute(Some(XSI_NAMESPACE), "type") {
+            Some(value) => match self.xsi_type(value, declaration, &namespaces) {
+                Ok(type_index) => Some(type_index),
+                Err(reason) => {
+                    let name = value.trim().to_owned();
+                    self.error(
+                        element,
+                        path,
+                        ValidationErrorKind::InvalidType { name, reason },
+                    );
+                    declared_type
+                }
+            },
+            None => declared_type,
+        };
+        let Some(type_index) = type_index else {
+            self.lax(id, element, path);
+            return;
+        };
+        let definition = &components.types[type_index];
+        if definition.is_abstract {
+            let name = components.describe_type(type_index);
+            self.error(element, path, ValidationErrorKind::Abstract { name });
+        }
+
+        let nilled = match element.attribute(Some(XSI_NAMESPACE), "nil") {
+            Some(value) => match value.trim() {
+                "true" | "1" => true,
+                "false" | "0" => false,
+                _ => {
+                    let kind = ValidationErrorKind::InvalidValue {
+                        attribute: Some("xsi:nil".to_owned()),
+                        value: value.to_owned(),
+                        reason: "not a boolean".to_owned(),
+                    };
+                    self.error(element, path, kind);
+                    false
+                }
+            },
+            None => false,
+        };
+        if nilled && !declaration.is_some_and(|declaration| declaration.nillable) {
+            let reason = "the element is not nillable".to_owned();
+            self.error(element, path, ValidationErrorKind::InvalidNil { reason });
+        }
+
+        self.attributes(element, definition, path, &namespaces);
+
+        let value_constraint =
+            declaration.and_then(|declaration| declaration.value_constraint.as_ref());
+        if nilled {
+            let has_content = element
+                .children
+                .iter()
+                .any(|&child| match document.node(child) {
+                    Some(Node::Element(_)) => true,
+                    Some(Node::Text(text)) => !text.data.is_empty(),
+                    Some(Node::CData(text)) => !text.data.is_empty(),
+                    _ => false,
+                });
+            if has_content {
+                let reason = "a nilled element must be empty".to_owned();
+                self.error(element, path, ValidationErrorKind::InvalidNil { reason });
+            }
+            if matches!(value_constraint, Some(ValueConstraint::Fixed(_))) {
+                let reason = "the element has a fixed value".to_owned();
+                self.error(element, path, ValidationErrorKind::InvalidNil { reason });
+            }
+            return;
+        }
+        self.content(element, definition, value_constraint, path, &namespaces);
+    }
+
+    /// Resolves an `xsi:type` and checks that it may stand in for the
+    /// declared type.
+    fn xsi_type(
+        &self,
+        value: &str,
+        declaration: Option<&ElementDef>,
+        namespaces: &dyn Namespaces,
+    ) -> std::result::Result<usize, String> {
+        let value = value.trim();
+        let (prefix, local_name) = match value.split_once(':') {
+            Some((prefix, local_name)) => (Some(prefix), local_name),
+            None => (None, value),
+        };
+        let namespace = namespaces.namespace_uri(prefix);
+        if let (Some(prefix), None) = (prefix, &namespace) {
+            return Err(format!("undeclared prefix `{}`", prefix));
+        }
+        let name = ExpandedName::new(namespace.as_deref(), local_name);
+        let Some(&type_index) = self.components.named_types.get(&name) else {
+            return Err(format!("no type `{}` is defined", name));
+        };
+        let Some(declaration) = declaration else {
+            return Ok(type_index);
+        };
+        let declared = &self.components.types[declaration.type_index];
+        let Some(derivations) = self
+            .components
+            .derivations(type_index, declaration.type_index)
+        else {
+            return Err("it does not derive from the declared type".to_owned());
+        };
+        match derivations.iter().find(|derivation| {
+            declaration.blocked.contains(derivation) || declared.blocked.contains(derivation)
+        }) {
+            Some(derivation) => Err(format!("derivation by {} is blocked", derivation)),
+            None => Ok(type_index),
+        }
+    }
+
+    fn attributes(
+        &mut self,
+        element: &Element,
+        definition: &TypeDef,
+        path: &str,
+        namespaces: &dyn Namespaces,
+    ) {
+        let mut present = vec![false; definition.attributes.len()];
+        for attribute in &element.attributes {
+            let name = expanded(&attribute.name);
+            if is_xsi_attribute(&name) {
+                continue;
+            }
+            let display = attribute.name.to_string();
+            let declared = definition
+                .attributes
+                .iter()
+                .position(|declared| declared.name == name);
+            let declaration = match declared {
+                Some(index) => {
+                    present[index] = true;
+                    Some(&definition.attributes[index])
+                }
+                None => match &definition.attribute_wildcard {
+                    Some(wildcard) if wildcard.allows(&name) => {
+                        let global = self.components.global_attributes.get(&name);
+                        match (wildcard.process_contents, global) {
+                            (ProcessContents::Skip, _) => None,
+                            (_, Some(global)) => Some(global),
+                            (ProcessContents::Strict, None) => {
+                                let name = display.clone();
+                                let kind = ValidationErrorKind::UnexpectedAttribute { name };
+                                self.error(element, path, kind);
+                                None
+                            }
+                            (ProcessContents::Lax, None) => None,
+                        }
+                    }
+                    _ => {
+                        let name = display.clone();
+                        let kind = ValidationErrorKind::UnexpectedAttribute { name };
+                        self.error(element, path, kind);
+                        None
+                    }
+                },
+            };
+            if let Some(declaration) = declaration {
+                let constraint = declaration.value_constraint.as_ref();
+                if let Some(kind) = check_value(
+                    &declaration.datatype,
+                    constraint,
+                    &attribute.value,
+                    Some(display),
+                    namespaces,
+                ) {
+                    self.error(element, path, kind);
+                }
+            }
+        }
+        for (declared, present) in definition.attributes.iter().zip(present) {
+            if declared.required && !present {
+                let name = declared.name.to_string();
+                self.error(
+                    element,
+                    path,
+                    ValidationErrorKind::MissingAttribute { name },
+                );
+            }
+        }
+    }
+
+    fn content(
+        &mut self,
+        element: &Element,
+        definition: &TypeDef,
+        value_constraint: Option<&ValueConstraint>,
+        path: &str,
+        namespaces: &dyn Namespaces,
+    ) {
+        let document = self.document;
+        let mut paths = child_paths(document, element, path).into_iter();
+        let mut text = String::new();
+        let mut has_elements = false;
+        let (mut p, mixed) = match definition.content {
+            Content::Elements { model, mixed } => (model, mixed),
+            _ => (NOT_ALLOWED, false),
+        };
+
+        for &child in &element.children {
+            let child_element = match document.node(child) {
+                Some(Node::Text(node)) => {
+                    text.push_str(&node.data);
+                    continue;
+                }
+                Some(Node::CData(node)) => {
+                    text.push_str(&node.data);
+                    continue;
+                }
+                Some(Node::Element(child_element)) => child_element,
+                _ => continue,
+            };
+            has_elements = true;
+            let (_, child_path) = paths.next().unwrap_or((child, String::new()));
+            if !mixed {
+                self.text(element, path, &text, definition);
+            }
+            text.clear();
+            if let Content::Elements { .. } = definition.content {
+                p = self.child(child, child_element, p, &child_path);
+            } else {
+                let kind = ValidationErrorKind::UnexpectedElement {
+                    name: child_element.name.to_string(),
+                    expected: Vec::new(),
+                };
+                self.error(child_element, &child_path, kind);
+            }
+        }
+
+        match &definition.content {
+            Content::Simple(datatype) => {
+                let value = match (text.is_empty(), value_constraint) {
+                    (
+                        true,
+                        Some(ValueConstraint::Default(value) | ValueConstraint::Fixed(value)),
+                    ) => value,
+                    _ => &text,
+                };
+                if let Some(kind) = check_value(datatype, value_constraint, value, None, namespaces)
+                {
+                    self.error(element, path, kind);
+                }
+            }
+            Content::Empty => self.text(element, path, &text, definition),
+            Content::Elements { mixed, .. } => {
+                if !mixed {
+                    self.text(element, path, &text, definition);
+                } else if let Some(ValueConstraint::Fixed(fixed)) = value_constraint {
+                    if !has_elements && !text.is_empty() && &text != fixed {
+                        let kind = ValidationErrorKind::NotFixedValue {
+                            attribute: None,
+                            value: text,
+                            fixed: fixed.clone(),
+                        };
+                        self.error(element, path, kind);
+                    }
+                }
+                if !self.terms.nullable(p) {
+                    let expected = self.components.expected(&self.terms.first(p));
+                    self.error(
+                        element,
+                        path,
+                        ValidationErrorKind::IncompleteContent { expected },
+                    );
+                }
+            }
+        }
+    }
+
+    /// Checks character data where the content type allows none. Only
+    /// whitespace is allowed between elements of element-only content.
+    fn text(&mut self, element: &Element, path: &str, text: &str, definition: &TypeDef) {
+        let allowed = match definition.content {
+            Content::Empty => text.is_empty(),
+            _ => text.chars().all(is_whitespace),
+        };
+        if !allowed {
+            let text = text.to_owned();
+            self.error(element, path, ValidationErrorKind::UnexpectedText { text });
+        }
+    }
+
+    /// Attributes a child element to a particle of the content model in
+    /// state `p` and assesses it, giving the state that follows. A child
+    /// that no particle matches leaves the state as it is.
+    fn child(&mut self, id: NodeId, element: &Element, p: Id, path: &str) -> Id {
+        let components = self.components;
+        let name = expanded(&element.name);
+        let first = self.terms.first(p);
+        let attribution = first
+            .iter()
+            .find_map(|&leaf| match &components.leaves[leaf] {
+                Leaf::Element(declaration) => components
+                    .matching(*declaration, &name)
+                    .map(|declaration| (leaf, Attribution::Element(declaration))),
+                Leaf::Wildcard(_) => None,
+            })
+            .or_else(|| {
+                first
+                    .iter()
+                    .find_map(|&leaf| match &components.leaves[leaf] {
+                        Leaf::Wildcard(wildcard) if wildcard.allows(&name) => {
+                            Some((leaf, Attribution::Wildcard(wildcard.process_contents)))
+                        }
+                        _ => None,
+                    })
+            });
+        let Some((leaf, attribution)) = attribution else {
+            let kind = ValidationErrorKind::UnexpectedElement {
+                name: element.name.to_string(),
+                expected: components.expected(&first),
+            };
+            self.error(element, path, kind);
+            return p;
+        };
+
+        let global = components.global_elements.get(&name).copied();
+        match attribution {
+            Attribution::Element(declaration) => self.element(id, Some(declaration), path),
+            Attribution::Wildcard(ProcessContents::Skip) => {}
+            Attribution::Wildcard(ProcessContents::Lax) => self.element(id, global, path),
+            Attribution::Wildcard(ProcessContents::Strict) => {
+                match (global, element.attribute(Some(XSI_NAMESPACE), "type")) {
+                    (None, None) => {
+                        let name = element.name.to_string();
+                        self.error(
+                            element,
+                            path,
+                            ValidationErrorKind::UndeclaredElement { name },
+                        );
+                    }
+                    _ => self.element(id, global, path),
+                }
+            }
+        }
+        self.terms.derive(p, leaf)
+    }
+
+    /// Assesses an element without a declaration or type: only the
+    /// attributes and descendants that have global declarations are
+    /// checked.
+    fn lax(&mut self, id: NodeId, element: &Element, path: &str) {
+        let document = self.document;
+        let namespaces = |prefix: Option<&str>| document.lookup_namespace_uri(id, prefix);
+        for attribute in &element.attributes {
+            let name = expanded(&attribute.name);
+            if let Some(declaration) = self.components.global_attributes.get(&name) {
+                let constraint = declaration.value_constraint.as_ref();
+                let display = Some(attribute.name.to_string());
+                if let Some(kind) = check_value(
+                    &declaration.datatype,
+                    constraint,
+                    &attribute.value,
+                    display,
+                    &namespaces,
+                ) {
+                    self.error(element, path, kind);
+                }
+            }
+        }
+        for (child, child_path) in child_paths(document, element, path) {
+            let Some(Node::Element(child_element)) = document.node(child) else {
+                continue;
+            };
+            let global = self
+                .components
+                .global_elements
+                .get(&expanded(&child_element.name))
+                .copied();
+            self.element(child, global, &child_path);
+        }
+    }
+}
+
+/// Checks a value against its type and fixed value, giving the error to
+/// report, if any.
+fn check_value(
+    datatype: &SimpleType,
+    value_constraint: Option<&ValueConstraint>,
+    value: &str,
+    attribute: Option<String>,
+    namespaces: &dyn Namespaces,
+) -> Option<ValidationErrorKind> {
+    let actual = match datatype.parse(value, namespaces) {
+        Ok(actual) => actual,
+        Err(e) => {
+            return Some(ValidationErrorKind::InvalidValue {
+                attribute,
+                value: value.to_owned(),
+                reason: e.to_string(),
+            })
+        }
+    };
+    match value_constraint {
+        Some(ValueConstraint::Fixed(fixed)) => match datatype.parse(fixed, namespaces) {
+            Ok(fixed) if fixed == actual => None,
+            _ => Some(ValidationErrorKind::NotFixedValue {
+                attribute,
+                value: value.to_owned(),
+                fixed: fixed.clone(),
+            }),
+        },
+        _ => None,
+    }
+}
+
+/// The child elements of `element` with their paths. A position is added
+/// to names that more than one child shares.
+fn child_paths(document: &Document, element: &Element, path: &str) -> Vec<(NodeId, String)> {
+    let children: Vec<_> = element
+        .children
+        .iter()
+        .filter_map(|&child| match document.node(child) {
+            Some(Node::Element(child_element)) => Some((child, child_element.name.to_string())),
+            _ => None,
+        })
+        .collect();
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for (_, name) in &children {
+        *counts.entry(name).or_default() += 1;
+    }
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    children
+        .iter()
+        .map(|(child, name)| {
+            let path = match counts[name.as_str()] {
+                1 => format!("{}/{}", path, name),
+                _ => {
+                    let position = positions.entry(name).or_default();
+                    *position += 1;
+                    format!("{}/{}[{}]", path, name, position)
+                }
+            };
+            (*child, path)
+        })
+        .collect()
+}
+
+fn expanded(name: &QName) -> ExpandedName {
+    ExpandedName::new(name.namespace.as_deref(), &name.local_name)
+}
+
+/// Whether the attribute is one of the `xsi` attributes every element
+/// may have.
+fn is_xsi_attribute(name: &ExpandedName) -> bool {
+    name.namespace.as_deref() == Some(XSI_NAMESPACE)
+        && matches!(
+            name.local_name.as_str(),
+            "type" | "nil" | "schemaLocation" | "noNamespaceSchemaLocation"
+        )
+}
+
+fn overlaps(a: &NamespaceConstraint, b: &NamespaceConstraint) -> bool {
+    match (a, b) {
+        (NamespaceConstraint::Any, _) | (_, NamespaceConstraint::Any) => true,
+        (NamespaceConstraint::Enumeration(a), NamespaceConstraint::Enumeration(b)) => {
+            a.iter().any(|namespace| b.contains(namespace))
+        }
+        (NamespaceConstraint::Enumeration(listed), NamespaceConstraint::Not(excluded))
+        | (NamespaceConstraint::Not(excluded), NamespaceConstraint::Enumeration(listed)) => {
+            listed.iter().any(|namespace| !excluded.contains(namespace))
+        }
+        (NamespaceConstraint::Not(_), NamespaceConstraint::Not(_)) => true,
+    }
+}
+
+/// Describes the elements a wildcard matches, `{ns}*` for each namespace
+/// it lists and `*` otherwise.
+fn describe_wildcard(wildcard: &Wildcard) -> String {
+    match &wildcard.namespace_constraint {
+        NamespaceConstraint::Enumeration(namespaces) => namespaces
+            .iter()
+            .map(|namespace| format!("{{{}}}*", namespace.as_deref().unwrap_or_default()))
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => "*".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use document::node::Document;
+    use document::ParseOptions;
+
+    use crate::components::SchemaSet;
+    use crate::elements::Schema;
+    use crate::error::{Error, ValidationErrorKind};
+    use crate::validator::Validator;
+
+    fn validator(documents: &[&str]) -> crate::Result<Validator> {
+        let documents: Vec<Schema> = documents
+            .iter()
+            .map(|data| data.parse().expect("a schema document"))
+            .collect();
+        Validator::new(&SchemaSet::new(&documents)?)
+    }
+
+    const ORDER: &str = r###"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:o="urn:order" targetNamespace="urn:order" elementFormDefault="qualified">
+  <xs:element name="order">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="customer" type="o:party"/>
+        <xs:element ref="o:item" maxOccurs="3"/>
+        <xs:choice minOccurs="0">
+          <xs:element name="note" type="xs:string"/>
+          <xs:element name="gift" type="xs:boolean"/>
+        </xs:choice>
+        <xs:element name="shipping" minOccurs="0">
+          <xs:complexType>
+            <xs:all>
+              <xs:element name="street" type="xs:string"/>
+              <xs:element name="city" type="xs:string"/>
+            </xs:all>
+          </xs:complexType>
+        </xs:element>
+        <xs:any namespace="##other" processContents="lax" minOccurs="0"/>
+      </xs:sequence>
+      <xs:attribute name="id" type="xs:positiveInteger" use="required"/>
+      <xs:attribute name="currency" type="xs:string" fixed="EUR"/>
+    </xs:complexType>
+  </xs:element>
+  <xs:element name="item" type="o:item" abstract="true"/>
+  <xs:element name="book" type="o:item" substitutionGroup="o:item"/>
+  <xs:element name="pen" substitutionGroup="o:item">
+    <xs:complexType>
+      <xs:simpleContent>
+        <xs:extension base="xs:decimal">
+          <xs:attribute name="colour" type="xs:string" default="blue"/>
+        </xs:extension>
+      </xs:simpleContent>
+    </xs:complexType>
+  </xs:element>
+  <xs:complexType name="item">
+    <xs:simpleContent>
+      <xs:extension base="xs:decimal"/>
+    </xs:simpleContent>
+  </xs:complexType>
+  <xs:complexType name="party">
+    <xs:sequence>
+      <xs:element name="name" type="xs:string"/>
+    </xs:sequence>
+  </xs:complexType>
+  <xs:complexType name="company">
+    <xs:complexContent>
+      <xs:extension base="o:party">
+        <xs:sequence>
+          <xs:element name="vat" type="o:vat"/>
+        </xs:sequence>
+      </xs:extension>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:complexType name="person" block="extension">
+    <xs:sequence>
+      <xs:element name="name" type="xs:string" nillable="true"/>
+    </xs:sequence>
+  </xs:complexType>
+  <xs:simpleType name="vat">
+    <xs:restriction base="xs:string">
+      <xs:pattern value="[A-Z]{2}\d{8}"/>
+    </xs:restriction>
+  </xs:simpleType>
+</xs:schema>"###;
+
+    #[test]
+    fn accepts_valid_documents() -> Result<(), anyhow::Error> {
+        let mut validator = validator(&[ORDER])?;
+        let valid = [
+            r#"<order xmlns="urn:order" id="1"><customer><name>Ann</name></customer><book>12.50</book></order>"#,
+            r#"<o:order xmlns:o="urn:order" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" id="2" currency="EUR">
+  <o:customer xsi:type="o:company"><o:name>Acme</o:name><o:vat>NL12345678</o:vat></o:customer>
+  <o:pen colour="red">1</o:pen><o:book>2</o:book><o:pen>3</o:pen>
+  <o:gift>true</o:gift>
+  <o:shipping><o:city>Delft</o:city><o:street>Markt 1</o:street></o:shipping>
+  <x:extra xmlns:x="urn:x"><anything/></x:extra>
+</o:order>"#,
+        ];
+        for xml in valid {
+            let document = Document::parse(xml)?;
+            assert_eq!(validator.validate(&document), Ok(()), "{}", xml);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn reports_errors_with_paths() -> Result<(), anyhow::Error> {
+        let mut validator = validator(&[ORDER])?;
+        let xml = r#"<order xmlns="urn:order" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" id="0" currency="USD" rush="yes">
+  <customer xsi:type="company"><name>Acme</name><vat>12</vat></customer>
+  <item>1</item><book>x</book><book>3</book><pen>4</pen>
+  <shipping><city>Delft</city></shipping>
+</order>"#;
+        let options = ParseOptions {
+            locations: true,
+            source_uri: None,
+            ..ParseOptions::default()
+        };
+        let document = Document::parse_with(xml, &options)?;
+        let errors = validator.validate(&document).expect_err("invalid");
+        let found: Vec<_> = errors
+            .iter()
+            .map(|error| (error.path.as_str(), &error.kind))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "/order",
+                    &ValidationErrorKind::InvalidValue {
+                        attribute: Some("id".to_owned()),
+                        value: "0".to_owned(),
+                        reason: "\"0\" is out of range for `positiveInteger`".to_owned(),
+                    }
+                ),
+                (
+                    "/order",
+                    &ValidationErrorKind::NotFixedValue {
+                        attribute: Some("currency".to_owned()),
+                        value: "USD".to_owned(),
+                        fixed: "EUR".to_owned(),
+                    }
+                ),
+                (
+                    "/order",
+                    &ValidationErrorKind::UnexpectedAttribute {
+                        name: "rush".to_owned()
+                    }
+                ),
+                (
+                    "/order/customer/vat",
+                    &ValidationErrorKind::InvalidValue {
+                        attribute: None,
+                        value: "12".to_owned(),
+                        reason: "\"12\" violates `pattern`: does not match [A-Z]{2}\\d{8}"
+                            .to_owned(),
+                    }
+                ),
+                (
+                    "/order/item",
+                    &ValidationErrorKind::Abstract {
+                        name: "{urn:order}item".to_owned()
+                    }
+                ),
+                (
+                    "/order/book[1]",
+                    &ValidationErrorKind::InvalidValue {
+                        attribute: None,
+                        value: "x".to_owned(),
+                        reason: "\"x\" is not a valid value of `decimal`".to_owned(),
+                    }
+                ),
+                (
+                    "/order/pen",
+                    &ValidationErrorKind::UnexpectedElement {
+                        name: "pen".to_owned(),
+                        expected: vec![
+                            "*".to_owned(),
+                            "{urn:order}gift".to_owned(),
+                            "{urn:order}note".to_owned(),
+                            "{urn:order}shipping".to_owned(),
+                        ],
+                    }
+                ),
+                (
+                    "/order/shipping",
+                    &ValidationErrorKind::IncompleteContent {
+                        expected: vec!["{urn:order}street".to_owned()],
+                    }
+                ),
+            ][..],
+        );
+        assert_eq!(
+            errors[3].to_string(),
+            "2:49: /order/customer/vat: invalid value \"12\": \"12\" violates `pattern`: does not match [A-Z]{2}\\d{8}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn checks_xsi_type_and_nil() -> Result<(), anyhow::Error> {
+        let schema = ORDER.replace(
+            r#"<xs:element name="item" "#,
+            r#"<xs:element name="buyer" type="o:person"/>
+  <xs:element name="client" type="o:party" block="restriction"/>
+  <xs:element name="item" "#,
+        );
+        let mut validator = validator(&[&schema])?;
+        let cases = [
+            (
+                r#"<buyer xmlns="urn:order" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"><name xsi:nil="true"/></buyer>"#,
+                vec![],
+            ),
+            (
+                r#"<buyer xmlns="urn:order" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"><name xsi:nil="1">Ann</name></buyer>"#,
+                vec!["/buyer/name: invalid xsi:nil: a nilled element must be empty"],
+            ),
+            (
+                r#"<client xmlns="urn:order" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:nil="true"/>"#,
+                vec!["/client: invalid xsi:nil: the element is not nillable"],
+            ),
+            (
+                r#"<client xmlns="urn:order" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="company"><name>Acme</name><vat>NL12345678</vat></client>"#,
+                vec![],
+            ),
+            (
+                r#"<client xmlns="urn:order" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="xsi:party"><name>Acme</name></client>"#,
+                vec!["/client: invalid xsi:type `xsi:party`: no type `{http://www.w3.org/2001/XMLSchema-instance}party` is defined"],
+            ),
+            (
+                r#"<client xmlns="urn:order" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="person"><name>Ann</name></client>"#,
+                vec!["/client: invalid xsi:type `person`: it does not derive from the declared type"],
+            ),
+            (
+                r#"<buyer xmlns="urn:order" xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="xs:anyType"><name>Ann</name></buyer>"#,
+                vec!["/buyer: invalid xsi:type `xs:anyType`: it does not derive from the declared type"],
+            ),
+            (
+                r#"<party xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:o="urn:order" xsi:type="o:vat">NL12345678</party>"#,
+                vec![],
+            ),
+        ];
+        for (xml, expected) in cases {
+            let document = Document::parse(xml)?;
+            let errors = match validator.validate(&document) {
+                Ok(()) => Vec::new(),
+                Err(errors) => errors.iter().map(ToString::to_string).collect(),
+            };
+            assert_eq!(errors, expected, "{}", xml);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_ambiguous_content_models() -> Result<(), anyhow::Error> {
+        let schema = |content: &str| {
+            format!(
+                r###"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="a" type="xs:string"/>
+  <xs:element name="b" type="xs:string" substitutionGroup="a"/>
+  <xs:complexType name="t">{}</xs:complexType>
+</xs:schema>"###,
+                content
+            )
+        };
+        let ambiguous = [
+            r#"<xs:sequence><xs:element ref="a" minOccurs="0"/><xs:element ref="a"/></xs:sequence>"#,
+            r#"<xs:sequence><xs:element ref="a" maxOccurs="2"/><xs:element name="b" type="xs:int"/></xs:sequence>"#,
+            r###"<xs:choice><xs:any namespace="##any"/><xs:any namespace="##local"/></xs:choice>"###,
+        ];
+        for content in ambiguous {
+            assert!(
+                matches!(validator(&[&schema(content)]), Err(Error::Ambiguous { .. })),
+                "{}",
+                content
+            );
+        }
+        let unambiguous = [
+            r#"<xs:sequence><xs:element ref="a" minOccurs="2" maxOccurs="3"/><xs:element name="c"/></xs:sequence>"#,
+            r###"<xs:sequence><xs:element ref="a" maxOccurs="unbounded"/><xs:any namespace="##any" minOccurs="0"/></xs:sequence>"###,
+            r#"<xs:sequence maxOccurs="2"><xs:element ref="a" maxOccurs="2"/></xs:sequence>"#,
+            // Derivatives of a repeated sequence that starts with optional
+            // particles pile up choices unless they are kept normalized.
+            r#"<xs:sequence maxOccurs="unbounded"><xs:element name="a" minOccurs="0" maxOccurs="2"/><xs:element name="b" minOccurs="0"/></xs:sequence>"#,
+        ];
+        for content in unambiguous {
+            validator(&[&schema(content)])?;
+        }
+
+        let large = r#"<xs:sequence><xs:element ref="a" maxOccurs="20000"/></xs:sequence>"#;
+        assert!(matches!(
+            validator(&[&schema(large)]),
+            Err(Error::TooComplex { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn validates_the_schema_for_schemas_against_itself() -> Result<(), anyhow::Error> {
+        let data = read_to_string("../../schema/XMLSchema.xsd")?;
+        let xml = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="http://www.w3.org/XML/1998/namespace">
+  <xs:attribute name="lang" type="xs:language"/>
+</xs:schema>"#;
+        let mut validator = validator(&[&data, xml])?;
+        assert_eq!(validator.validate(&Document::parse(&data)?), Ok(()));
+        let document = Document::parse(
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"><xs:element name="a" minOccurs="1"/></xs:schema>"#,
+        )?;
+        let errors = validator.validate(&document).expect_err("invalid");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "/xs:schema/xs:element");
+        Ok(())
+    }
+}
@@ -1,1107 +1,398 @@
-use serde::{Deserialize, Serialize};
-
-use crate::groups::SimpleRestrictionModel;
-
-// OpenAttrs is This type is extended by almost all schema types
-//        to allow attributes from other namespaces to be
-//        added to user schemas.
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct OpenAttrs {}
-
-// Annotated is This type is extended by all types which allow annotation
-//        other than <schema> itself
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Annotated {
-    #[serde(rename = "id")]
-    pub id: Option<String>,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-    #[serde(flatten)]
-    pub open_attrs: OpenAttrs,
-}
-
-// Composition ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Composition {
-    #[serde(rename = "xs:include")]
-    pub xs_include: Include,
-    #[serde(rename = "xs:import")]
-    pub xs_import: Import,
-    #[serde(rename = "xs:redefine")]
-    pub xs_redefine: Redefine,
-    #[serde(rename = "xs:override")]
-    pub xs_override: Override,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// SchemaTop ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct SchemaTop {
-    #[serde(rename = "xs:element")]
-    pub xs_element: TopLevelElement,
-    #[serde(rename = "xs:attribute")]
-    pub xs_attribute: Attribute,
-    #[serde(rename = "xs:notation")]
-    pub xs_notation: Notation,
-    #[serde(rename = "xs:redefinable")]
-    pub xs_redefinable: Redefinable,
-}
-
-// Redefinable ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Redefinable {
-    #[serde(rename = "xs:simpleType")]
-    pub xs_simple_type: SimpleType,
-    #[serde(rename = "xs:complexType")]
-    pub xs_complex_type: TopLevelComplexType,
-    #[serde(rename = "xs:group")]
-    pub xs_group: Group,
-    #[serde(rename = "xs:attributeGroup")]
-    pub xs_attribute_group: AttributeGroup,
-}
-
-// FormChoice is A utility type, not for public use
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct FormChoice {
-    #[serde(rename = "formChoice")]
-    pub form_choice: String,
-}
-
-// ReducedDerivationControl is A utility type, not for public use
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct ReducedDerivationControl {
-    #[serde(rename = "reducedDerivationControl")]
-    pub reduced_derivation_control: String,
-}
-
-// DerivationSet is #all or (possibly empty) subset of {extension, restriction}
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct DerivationSet {
-    #[serde(rename = "derivationSet")]
-    pub derivation_set: Vec<String>,
-}
-
-// TypeDerivationControl is A utility type, not for public use
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct TypeDerivationControl {
-    #[serde(rename = "typeDerivationControl")]
-    pub type_derivation_control: String,
-}
-
-// FullDerivationSet is #all or (possibly empty) subset of {extension, restriction, list, union}
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct FullDerivationSet {
-    #[serde(rename = "fullDerivationSet")]
-    pub full_derivation_set: Vec<String>,
-}
-
-// Schema ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Schema {
-    #[serde(rename = "targetNamespace")]
-    pub target_namespace: Option<String>,
-    #[serde(rename = "version")]
-    pub version: Option<String>,
-    #[serde(rename = "finalDefault")]
-    pub final_default: Option<FullDerivationSet>,
-    #[serde(rename = "blockDefault")]
-    pub block_default: Option<BlockSet>,
-    #[serde(rename = "attributeFormDefault")]
-    pub attribute_form_default: Option<String>,
-    #[serde(rename = "elementFormDefault")]
-    pub element_form_default: Option<String>,
-    #[serde(rename = "defaultAttributes")]
-    pub default_attributes: Option<String>,
-    #[serde(rename = "xpathDefaultNamespace")]
-    pub xpath_default_namespace: Option<XpathDefaultNamespace>,
-    #[serde(rename = "id")]
-    pub id: Option<String>,
-    #[serde(rename = "xml:lang")]
-    pub xml_lang: Option<Lang>,
-    #[serde(rename = "xs:composition")]
-    pub xs_composition: Vec<Composition>,
-    #[serde(rename = "xs:schemaTop")]
-    pub xs_schema_top: SchemaTop,
-    #[serde(rename = "xs:defaultOpenContent")]
-    pub xs_default_open_content: String,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Vec<Annotation>,
-    #[serde(flatten)]
-    pub open_attrs: OpenAttrs,
-}
-
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct AllNNI {
-    #[serde(rename = "allNNI")]
-    pub non_negative_integer: u32,
-}
-
-// Occurs is for all particles
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Occurs {
-    #[serde(rename = "minOccurs")]
-    pub min_occurs: Option<u32>,
-    #[serde(rename = "maxOccurs")]
-    pub max_occurs: Option<AllNNI>,
-}
-
-// DefRef is for element, group and attributeGroup,
-//    which both define and reference
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct DefRef {
-    #[serde(rename = "name")]
-    pub name: Option<String>,
-    #[serde(rename = "ref")]
-    pub ref_attr: Option<String>,
-}
-
-// TypeDefParticle ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct TypeDefParticle {
-    #[serde(rename = "group")]
-    pub group: GroupRef,
-    #[serde(rename = "xs:all")]
-    pub xs_all: All,
-    #[serde(rename = "xs:choice")]
-    pub xs_choice: Choice,
-    #[serde(rename = "xs:sequence")]
-    pub xs_sequence: Sequence,
-}
-
-// NestedParticle ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct NestedParticle {
-    #[serde(rename = "element")]
-    pub element: LocalElement,
-    #[serde(rename = "group")]
-    pub group: GroupRef,
-    #[serde(rename = "xs:choice")]
-    pub xs_choice: Choice,
-    #[serde(rename = "xs:sequence")]
-    pub xs_sequence: Sequence,
-    #[serde(rename = "xs:any")]
-    pub xs_any: Any,
-}
-
-// Particle ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Particle {
-    #[serde(rename = "element")]
-    pub element: LocalElement,
-    #[serde(rename = "group")]
-    pub group: GroupRef,
-    #[serde(rename = "xs:all")]
-    pub xs_all: All,
-    #[serde(rename = "xs:choice")]
-    pub xs_choice: Choice,
-    #[serde(rename = "xs:sequence")]
-    pub xs_sequence: Sequence,
-    #[serde(rename = "xs:any")]
-    pub xs_any: Any,
-}
-
-// Attribute ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Attribute {
-    #[serde(rename = "xs:defRef")]
-    pub xs_def_ref: Vec<DefRef>,
-    #[serde(rename = "type")]
-    pub type_attr: Option<String>,
-    #[serde(rename = "use")]
-    pub use_attr: Option<String>,
-    #[serde(rename = "default")]
-    pub default: Option<String>,
-    #[serde(rename = "fixed")]
-    pub fixed: Option<String>,
-    #[serde(rename = "form")]
-    pub form: Option<String>,
-    #[serde(rename = "targetNamespace")]
-    pub target_namespace: Option<String>,
-    #[serde(rename = "inheritable")]
-    pub inheritable: Option<bool>,
-    #[serde(rename = "simpleType")]
-    pub simple_type: LocalSimpleType,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// TopLevelAttribute ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct TopLevelAttribute {
-    #[serde(rename = "ref")]
-    pub ref_attr: Option<char>,
-    #[serde(rename = "form")]
-    pub form: Option<char>,
-    #[serde(rename = "use")]
-    pub use_attr: Option<char>,
-    #[serde(rename = "targetNamespace")]
-    pub target_namespace: Option<char>,
-    #[serde(rename = "name")]
-    pub name: String,
-    #[serde(rename = "inheritable")]
-    pub inheritable: Option<bool>,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-    #[serde(rename = "simpleType")]
-    pub simple_type: LocalSimpleType,
-}
-
-// AttrDecls ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct AttrDecls {
-    #[serde(rename = "attribute")]
-    pub attribute: Attribute,
-    #[serde(rename = "attributeGroup")]
-    pub attribute_group: AttributeGroupRef,
-    #[serde(rename = "xs:anyAttribute")]
-    pub xs_any_attribute: AnyAttribute,
-}
-
-// AnyAttribute ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct AnyAttribute {
-    #[serde(rename = "notQName")]
-    pub not_q_name: Option<QnameListA>,
-    #[serde(flatten)]
-    pub wildcard: Wildcard,
-}
-
-// Assertions ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Assertions {
-    #[serde(rename = "assert")]
-    pub assert: Assertion,
-}
-
-// Assertion ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Assertion {
-    #[serde(rename = "test")]
-    pub test: Option<String>,
-    #[serde(rename = "xpathDefaultNamespace")]
-    pub xpath_default_namespace: Option<XpathDefaultNamespace>,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// ComplexTypeModel ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct ComplexTypeModel {
-    #[serde(rename = "xs:simpleContent")]
-    pub xs_simple_content: SimpleContent,
-    #[serde(rename = "xs:complexContent")]
-    pub xs_complex_content: ComplexContent,
-    #[serde(rename = "xs:openContent")]
-    pub xs_open_content: String,
-    #[serde(rename = "xs:typeDefParticle")]
-    pub xs_type_def_particle: TypeDefParticle,
-    #[serde(rename = "xs:attrDecls")]
-    pub xs_attr_decls: AttrDecls,
-    #[serde(rename = "xs:assertions")]
-    pub xs_assertions: Assertions,
-}
-
-// ComplexType ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct ComplexType {
-    #[serde(rename = "name")]
-    pub name: Option<String>,
-    #[serde(rename = "mixed")]
-    pub mixed: Option<bool>,
-    #[serde(rename = "abstract")]
-    pub abstract_attr: Option<bool>,
-    #[serde(rename = "final")]
-    pub final_attr: Option<DerivationSet>,
-    #[serde(rename = "block")]
-    pub block: Option<DerivationSet>,
-    #[serde(rename = "defaultAttributesApply")]
-    pub default_attributes_apply: Option<bool>,
-    #[serde(rename = "xs:complexTypeModel")]
-    pub xs_complex_type_model: ComplexTypeModel,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// TopLevelComplexType ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct TopLevelComplexType {
-    #[serde(rename = "name")]
-    pub name: String,
-    #[serde(rename = "xs:complexTypeModel")]
-    pub xs_complex_type_model: ComplexTypeModel,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// LocalComplexType ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct LocalComplexType {
-    #[serde(rename = "name")]
-    pub name: Option<char>,
-    #[serde(rename = "abstract")]
-    pub abstract_attr: Option<char>,
-    #[serde(rename = "final")]
-    pub final_attr: Option<char>,
-    #[serde(rename = "block")]
-    pub block: Option<char>,
-    #[serde(rename = "xs:complexTypeModel")]
-    pub xs_complex_type_model: ComplexTypeModel,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// RestrictionType ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct RestrictionType {
-    #[serde(rename = "base")]
-    pub base: String,
-    #[serde(rename = "xs:typeDefParticle")]
-    pub xs_type_def_particle: TypeDefParticle,
-    #[serde(rename = "xs:simpleRestrictionModel")]
-    pub xs_simple_restriction_model: SimpleRestrictionModel,
-    #[serde(rename = "xs:attrDecls")]
-    pub xs_attr_decls: AttrDecls,
-    #[serde(rename = "xs:assertions")]
-    pub xs_assertions: Assertions,
-    #[serde(rename = "xs:openContent")]
-    pub xs_open_content: String,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// ComplexRestrictionType is This choice is added simply to
-//                    make this a valid restriction per the REC
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct ComplexRestrictionType {
-    #[serde(rename = "xs:typeDefParticle")]
-    pub xs_type_def_particle: TypeDefParticle,
-    #[serde(rename = "xs:attrDecls")]
-    pub xs_attr_decls: AttrDecls,
-    #[serde(rename = "xs:assertions")]
-    pub xs_assertions: Assertions,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-    #[serde(rename = "xs:openContent")]
-    pub xs_open_content: String,
-}
-
-// ExtensionType ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct ExtensionType {
-    #[serde(rename = "base")]
-    pub base: String,
-    #[serde(rename = "xs:typeDefParticle")]
-    pub xs_type_def_particle: TypeDefParticle,
-    #[serde(rename = "xs:attrDecls")]
-    pub xs_attr_decls: AttrDecls,
-    #[serde(rename = "xs:assertions")]
-    pub xs_assertions: Assertions,
-    #[serde(rename = "xs:openContent")]
-    pub xs_open_content: String,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// ComplexContent ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct ComplexContent {
-    #[serde(rename = "mixed")]
-    pub mixed: Option<bool>,
-    #[serde(rename = "restriction")]
-    pub restriction: ComplexRestrictionType,
-    #[serde(rename = "extension")]
-    pub extension: ExtensionType,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// OpenContent ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct OpenContent {
-    #[serde(rename = "mode")]
-    pub mode: Option<String>,
-    #[serde(rename = "openContent")]
-    pub open_content: String,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// DefaultOpenContent ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct DefaultOpenContent {
-    #[serde(rename = "appliesToEmpty")]
-    pub applies_to_empty: Option<bool>,
-    #[serde(rename = "mode")]
-    pub mode: Option<String>,
-    #[serde(rename = "defaultOpenContent")]
-    pub default_open_content: String,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// SimpleRestrictionType is This choice is added simply to
-//                    make this a valid restriction per the REC
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct SimpleRestrictionType {
-    #[serde(rename = "xs:simpleRestrictionModel")]
-    pub xs_simple_restriction_model: SimpleRestrictionModel,
-    #[serde(rename = "xs:attrDecls")]
-    pub xs_attr_decls: AttrDecls,
-    #[serde(rename = "xs:assertions")]
-    pub xs_assertions: Assertions,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// SimpleExtensionType is No typeDefParticle group reference
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct SimpleExtensionType {
-    #[serde(rename = "xs:attrDecls")]
-    pub xs_attr_decls: AttrDecls,
-    #[serde(rename = "xs:assertions")]
-    pub xs_assertions: Assertions,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// SimpleContent ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct SimpleContent {
-    #[serde(rename = "restriction")]
-    pub restriction: SimpleRestrictionType,
-    #[serde(rename = "extension")]
-    pub extension: SimpleExtensionType,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// BlockSet is #all or (possibly empty) subset of {substitution, extension,
-//     restriction}
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct BlockSet {
-    #[serde(rename = "blockSet")]
-    pub block_set: Vec<String>,
-}
-
-// Element is The element element can be used either
-//    at the top level to define an element-type binding globally,
-//    or within a content model to either reference a globally-defined
-//    element or type or declare an element-type binding locally.
-//    The ref form is not allowed at the top level.
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Element {
-    #[serde(rename = "xs:defRef")]
-    pub xs_def_ref: Vec<DefRef>,
-    #[serde(rename = "xs:occurs")]
-    pub xs_occurs: Vec<Occurs>,
-    #[serde(rename = "type")]
-    pub type_attr: Option<String>,
-    #[serde(rename = "substitutionGroup")]
-    pub substitution_group: Option<String>,
-    #[serde(rename = "default")]
-    pub default: Option<String>,
-    #[serde(rename = "fixed")]
-    pub fixed: Option<String>,
-    #[serde(rename = "nillable")]
-    pub nillable: Option<bool>,
-    #[serde(rename = "abstract")]
-    pub abstract_attr: Option<bool>,
-    #[serde(rename = "final")]
-    pub final_attr: Option<DerivationSet>,
-    #[serde(rename = "block")]
-    pub block: Option<BlockSet>,
-    #[serde(rename = "form")]
-    pub form: Option<String>,
-    #[serde(rename = "targetNamespace")]
-    pub target_namespace: Option<String>,
-    #[serde(rename = "xs:identityConstraint")]
-    pub xs_identity_constraint: Vec<IdentityConstraint>,
-    #[serde(rename = "simpleType")]
-    pub simple_type: LocalSimpleType,
-    #[serde(rename = "complexType")]
-    pub complex_type: LocalComplexType,
-    #[serde(rename = "alternative")]
-    pub alternative: Vec<AltType>,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// TopLevelElement ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct TopLevelElement {
-    #[serde(rename = "ref")]
-    pub ref_attr: Option<char>,
-    #[serde(rename = "form")]
-    pub form: Option<char>,
-    #[serde(rename = "targetNamespace")]
-    pub target_namespace: Option<char>,
-    #[serde(rename = "minOccurs")]
-    pub min_occurs: Option<char>,
-    #[serde(rename = "maxOccurs")]
-    pub max_occurs: Option<char>,
-    #[serde(rename = "name")]
-    pub name: String,
-    #[serde(rename = "xs:identityConstraint")]
-    pub xs_identity_constraint: Vec<IdentityConstraint>,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-    #[serde(rename = "simpleType")]
-    pub simple_type: LocalSimpleType,
-    #[serde(rename = "complexType")]
-    pub complex_type: LocalComplexType,
-    #[serde(rename = "alternative")]
-    pub alternative: Vec<AltType>,
-}
-
-// LocalElement ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct LocalElement {
-    #[serde(rename = "substitutionGroup")]
-    pub substitution_group: Option<char>,
-    #[serde(rename = "final")]
-    pub final_attr: Option<char>,
-    #[serde(rename = "abstract")]
-    pub abstract_attr: Option<char>,
-    #[serde(rename = "xs:identityConstraint")]
-    pub xs_identity_constraint: Vec<IdentityConstraint>,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-    #[serde(rename = "simpleType")]
-    pub simple_type: LocalSimpleType,
-    #[serde(rename = "complexType")]
-    pub complex_type: LocalComplexType,
-    #[serde(rename = "alternative")]
-    pub alternative: Vec<AltType>,
-}
-
-// AltType is This type is used for 'alternative' elements.
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct AltType {
-    #[serde(rename = "test")]
-    pub test: Option<String>,
-    #[serde(rename = "type")]
-    pub type_attr: Option<String>,
-    #[serde(rename = "xpathDefaultNamespace")]
-    pub xpath_default_namespace: Option<XpathDefaultNamespace>,
-    #[serde(rename = "simpleType")]
-    pub simple_type: LocalSimpleType,
-    #[serde(rename = "complexType")]
-    pub complex_type: LocalComplexType,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// Group is group type for explicit groups, named top-level groups and
-//    group references
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Group {
-    #[serde(rename = "xs:defRef")]
-    pub xs_def_ref: Vec<DefRef>,
-    #[serde(rename = "xs:occurs")]
-    pub xs_occurs: Vec<Occurs>,
-    #[serde(rename = "xs:particle")]
-    pub xs_particle: Vec<Particle>,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// RealGroup ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct RealGroup {
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-    #[serde(rename = "xs:all")]
-    pub xs_all: All,
-    #[serde(rename = "xs:choice")]
-    pub xs_choice: Choice,
-    #[serde(rename = "xs:sequence")]
-    pub xs_sequence: Sequence,
-}
-
-// All ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct All {
-    #[serde(rename = "minOccurs")]
-    pub min_occurs: Option<char>,
-    #[serde(rename = "maxOccurs")]
-    pub max_occurs: Option<char>,
-    #[serde(rename = "xs:allModel")]
-    pub xs_all_model: AllModel,
-}
-
-// NamedGroup ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct NamedGroup {
-    #[serde(rename = "name")]
-    pub name: String,
-    #[serde(rename = "ref")]
-    pub ref_attr: Option<char>,
-    #[serde(rename = "minOccurs")]
-    pub min_occurs: Option<char>,
-    #[serde(rename = "maxOccurs")]
-    pub max_occurs: Option<char>,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-    #[serde(rename = "all")]
-    pub all: All,
-    #[serde(rename = "choice")]
-    pub choice: SimpleExplicitGroup,
-    #[serde(rename = "sequence")]
-    pub sequence: SimpleExplicitGroup,
-}
-
-// GroupRef ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct GroupRef {
-    #[serde(rename = "ref")]
-    pub ref_attr: String,
-    #[serde(rename = "name")]
-    pub name: Option<char>,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// ExplicitGroup is group type for the three kinds of group
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct ExplicitGroup {
-    #[serde(rename = "name")]
-    pub name: Option<char>,
-    #[serde(rename = "ref")]
-    pub ref_attr: Option<char>,
-    #[serde(rename = "xs:nestedParticle")]
-    pub xs_nested_particle: Vec<NestedParticle>,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// SimpleExplicitGroup ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct SimpleExplicitGroup {
-    #[serde(rename = "minOccurs")]
-    pub min_occurs: Option<char>,
-    #[serde(rename = "maxOccurs")]
-    pub max_occurs: Option<char>,
-    #[serde(rename = "xs:nestedParticle")]
-    pub xs_nested_particle: Vec<NestedParticle>,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// AnyAttrGroup ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct AnyAttrGroup {
-    #[serde(rename = "namespace")]
-    pub namespace: Option<NamespaceList>,
-    #[serde(rename = "notNamespace")]
-    pub not_namespace: Option<BasicNamespaceList>,
-    #[serde(rename = "processContents")]
-    pub process_contents: Option<String>,
-}
-
-// Wildcard ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Wildcard {
-    #[serde(rename = "xs:anyAttrGroup")]
-    pub xs_any_attr_group: Vec<AnyAttrGroup>,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// Any ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Any {
-    #[serde(rename = "xs:occurs")]
-    pub xs_occurs: Vec<Occurs>,
-    #[serde(rename = "notQName")]
-    pub not_q_name: Option<QnameList>,
-    #[serde(flatten)]
-    pub wildcard: Wildcard,
-}
-
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct NamespaceList {
-    #[serde(rename = "namespaceList")]
-    pub basic_namespace_list: BasicNamespaceList,
-    #[serde(rename = "namespaceList")]
-    pub special_namespace_list: SpecialNamespaceList,
-}
-
-// BasicNamespaceList is A utility type, not for public use
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct BasicNamespaceList {
-    #[serde(rename = "basicNamespaceList")]
-    pub basic_namespace_list: Vec<String>,
-}
-
-// SpecialNamespaceList is A utility type, not for public use
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct SpecialNamespaceList {
-    #[serde(rename = "specialNamespaceList")]
-    pub special_namespace_list: String,
-}
-
-// QnameList is A utility type, not for public use
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct QnameList {
-    #[serde(rename = "qnameList")]
-    pub qname_list: Vec<String>,
-}
-
-// QnameListA is A utility type, not for public use
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct QnameListA {
-    #[serde(rename = "qnameListA")]
-    pub qname_list_a: Vec<String>,
-}
-
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct XpathDefaultNamespace {
-    #[serde(rename = "xpathDefaultNamespace")]
-    pub any_uri: String,
-}
-
-// AttributeGroup ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct AttributeGroup {
-    #[serde(rename = "xs:defRef")]
-    pub xs_def_ref: Vec<DefRef>,
-    #[serde(rename = "xs:attrDecls")]
-    pub xs_attr_decls: AttrDecls,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// NamedAttributeGroup ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct NamedAttributeGroup {
-    #[serde(rename = "name")]
-    pub name: String,
-    #[serde(rename = "ref")]
-    pub ref_attr: Option<char>,
-    #[serde(rename = "xs:attrDecls")]
-    pub xs_attr_decls: AttrDecls,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// AttributeGroupRef ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct AttributeGroupRef {
-    #[serde(rename = "ref")]
-    pub ref_attr: String,
-    #[serde(rename = "name")]
-    pub name: Option<char>,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// Include ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Include {
-    #[serde(rename = "schemaLocation")]
-    pub schema_location: String,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// Redefine ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Redefine {
-    #[serde(rename = "schemaLocation")]
-    pub schema_location: String,
-    #[serde(rename = "id")]
-    pub id: Option<String>,
-    #[serde(rename = "xs:redefinable")]
-    pub xs_redefinable: Vec<Redefinable>,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Vec<Annotation>,
-    #[serde(flatten)]
-    pub open_attrs: OpenAttrs,
-}
-
-// Override ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Override {
-    #[serde(rename = "schemaLocation")]
-    pub schema_location: String,
-    #[serde(rename = "id")]
-    pub id: Option<String>,
-    #[serde(rename = "xs:schemaTop")]
-    pub xs_schema_top: Vec<SchemaTop>,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-    #[serde(flatten)]
-    pub open_attrs: OpenAttrs,
-}
-
-// Import ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Import {
-    #[serde(rename = "namespace")]
-    pub namespace: Option<String>,
-    #[serde(rename = "schemaLocation")]
-    pub schema_location: Option<String>,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// Selector ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Selector {
-    #[serde(rename = "xpath")]
-    pub xpath: String,
-    #[serde(rename = "xpathDefaultNamespace")]
-    pub xpath_default_namespace: Option<XpathDefaultNamespace>,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// Field ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Field {
-    #[serde(rename = "xpath")]
-    pub xpath: String,
-    #[serde(rename = "xpathDefaultNamespace")]
-    pub xpath_default_namespace: Option<XpathDefaultNamespace>,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// Keybase ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Keybase {
-    #[serde(rename = "name")]
-    pub name: Option<String>,
-    #[serde(rename = "ref")]
-    pub ref_attr: Option<String>,
-    #[serde(rename = "xs:selector")]
-    pub xs_selector: Selector,
-    #[serde(rename = "xs:field")]
-    pub xs_field: Vec<Field>,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// Keyref ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Keyref {
-    #[serde(rename = "refer")]
-    pub refer: Option<String>,
-    #[serde(flatten)]
-    pub keybase: Keybase,
-}
-
-// Notation ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Notation {
-    #[serde(rename = "name")]
-    pub name: String,
-    #[serde(rename = "public")]
-    pub public: Option<String>,
-    #[serde(rename = "system")]
-    pub system: Option<String>,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// Public is A public identifier, per ISO 8879
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Public {
-    #[serde(rename = "public")]
-    pub public: String,
-}
-
-// Appinfo ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Appinfo {
-    #[serde(rename = "source")]
-    pub source: Option<String>,
-}
-
-// Documentation ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Documentation {
-    #[serde(rename = "source")]
-    pub source: Option<String>,
-    #[serde(rename = "xml:lang")]
-    pub xml_lang: Option<Lang>,
-}
-
-// Annotation ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Annotation {
-    #[serde(rename = "id")]
-    pub id: Option<String>,
-    #[serde(rename = "xs:appinfo")]
-    pub xs_appinfo: Vec<Appinfo>,
-    #[serde(rename = "xs:documentation")]
-    pub xs_documentation: Vec<Documentation>,
-    #[serde(flatten)]
-    pub open_attrs: OpenAttrs,
-}
-
-// AnyType is Not the real urType, but as close an approximation as we can
-//    get in the XML representation
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct AnyType {}
-
-// DerivationControl is A utility type, not for public use
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct DerivationControl {
-    #[serde(rename = "derivationControl")]
-    pub derivation_control: String,
-}
-
-// SimpleDerivationSet is A utility type, not for public use
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct SimpleDerivationSet {
-    #[serde(rename = "simpleDerivationSet")]
-    pub simple_derivation_set: Vec<String>,
-}
-
-// SimpleType ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct SimpleType {
-    #[serde(rename = "final")]
-    pub final_attr: Option<SimpleDerivationSet>,
-    #[serde(rename = "name")]
-    pub name: Option<String>,
-    #[serde(rename = "xs:simpleDerivation")]
-    pub xs_simple_derivation: SimpleDerivation,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// TopLevelSimpleType ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct TopLevelSimpleType {
-    #[serde(rename = "name")]
-    pub name: String,
-    #[serde(rename = "xs:simpleDerivation")]
-    pub xs_simple_derivation: SimpleDerivation,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// LocalSimpleType ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct LocalSimpleType {
-    #[serde(rename = "name")]
-    pub name: Option<char>,
-    #[serde(rename = "final")]
-    pub final_attr: Option<char>,
-    #[serde(rename = "xs:simpleDerivation")]
-    pub xs_simple_derivation: SimpleDerivation,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// facet is An abstract element, representing facets in general.
-//         The facets defined by this spec are substitutable for
-//         this element, and implementation-defined facets should
-//         also name this as a substitution-group head.
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct facet {
-    #[serde(rename = "facet")]
-    pub facet: Facet,
-}
-
-// Restriction is base attribute and simpleType child are mutually
-//           exclusive, but one or other is required
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Restriction {
-    #[serde(rename = "base")]
-    pub base: Option<String>,
-    #[serde(rename = "xs:simpleRestrictionModel")]
-    pub xs_simple_restriction_model: SimpleRestrictionModel,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// List is itemType attribute and simpleType child are mutually
-//           exclusive, but one or other is required
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct List {
-    #[serde(rename = "itemType")]
-    pub item_type: Option<String>,
-    #[serde(rename = "simpleType")]
-    pub simple_type: LocalSimpleType,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// Union is memberTypes attribute must be non-empty or there must be
-//           at least one simpleType child
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Union {
-    #[serde(rename = "memberTypes")]
-    pub member_types: Option<String>,
-    #[serde(rename = "simpleType")]
-    pub simple_type: Vec<LocalSimpleType>,
-    #[serde(flatten)]
-    pub annotated: Annotated,
-}
-
-// NoFixedFacet ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct NoFixedFacet {
-    #[serde(rename = "fixed")]
-    pub fixed: Option<char>,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// NumFacet ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct NumFacet {
-    #[serde(rename = "value")]
-    pub value: u32,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// IntFacet ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct IntFacet {
-    #[serde(rename = "value")]
-    pub value: i32,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// TotalDigits ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct TotalDigits {
-    #[serde(rename = "value")]
-    pub value: u32,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// WhiteSpace ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct WhiteSpace {
-    #[serde(rename = "value")]
-    pub value: String,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// Pattern ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct Pattern {
-    #[serde(rename = "value")]
-    pub value: String,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
-}
-
-// ExplicitTimezone ...
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub struct ExplicitTimezone {
-    #[serde(rename = "value")]
-    pub value: String,
-    #[serde(rename = "xs:annotation")]
-    pub xs_annotation: Annotation,
+use std::io::BufRead;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use document::namespace::Namespace;
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+use quick_xml::name::{PrefixDeclaration, ResolveResult};
+use quick_xml::writer::Writer;
+use quick_xml::{DeError, NsReader, Reader};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::elements::{OpenAttrs, Schema};
+
+/// The XML Schema namespace, bound to the `xs` prefix when serializing.
+pub const XS_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema";
+
+impl Schema {
+    /// Reads a schema document.
+    pub fn from_reader<R: BufRead>(mut reader: R) -> Result<Schema, DeError> {
+        let mut xml = String::new();
+        reader
+            .read_to_string(&mut xml)
+            .map_err(|e| DeError::from(quick_xml::Error::Io(Arc::new(e))))?;
+        xml.parse()
+    }
+
+    /// Writes the schema as a document with the XML Schema namespace bound
+    /// to `xs`.
+    pub fn to_xml(&self) -> Result<String, DeError> {
+        let xml = quick_xml::se::to_string_with_root("xs:schema", self)?;
+        let mut reader = Reader::from_str(&xml);
+        let mut writer = Writer::new(Vec::new());
+        let mut root = true;
+        loop {
+            let event = match reader.read_event()? {
+                Event::Eof => break,
+                Event::Start(start) => Event::Start(expand_open_attrs(&start, root)?),
+                Event::Empty(start) => Event::Empty(expand_open_attrs(&start, root)?),
+                event => event,
+            };
+            root = false;
+            writer.write_event(event)?;
+        }
+        into_string(writer)
+    }
+}
+
+impl FromStr for Schema {
+    type Err = DeError;
+
+    fn from_str(xml: &str) -> Result<Self, Self::Err> {
+        quick_xml::de::from_str(&normalize(xml)?)
+    }
+}
+
+/// The attribute that carries [`OpenAttrs`] through serde, which knows
+/// attributes by local name only. Attributes of schema elements are never
+/// in a namespace and foreign ones always are, so no real one is named so.
+const OPEN_ATTRS: &str = "open-attrs";
+
+impl OpenAttrs {
+    /// Writes the declarations and attributes as `name=value` pairs, with
+    /// `%` and whitespace in values percent-encoded.
+    fn encode(&self) -> String {
+        let declarations = self.namespaces.iter().map(|namespace| {
+            let name = match &namespace.prefix {
+                Some(prefix) => format!("xmlns:{prefix}"),
+                None => "xmlns".to_owned(),
+            };
+            (name, namespace.uri.as_str())
+        });
+        let attributes = self
+            .attributes
+            .iter()
+            .map(|(name, value)| (name.clone(), value.as_str()));
+        let mut encoded = String::new();
+        for (name, value) in declarations.chain(attributes) {
+            if !encoded.is_empty() {
+                encoded.push(' ');
+            }
+            encoded.push_str(&name);
+            encoded.push('=');
+            for c in value.chars() {
+                match c == '%' || c.is_ascii_whitespace() {
+                    true => encoded.push_str(&format!("%{:02X}", c as u32)),
+                    false => encoded.push(c),
+                }
+            }
+        }
+        encoded
+    }
+
+    fn decode(encoded: &str) -> OpenAttrs {
+        let mut open_attrs = OpenAttrs::default();
+        for pair in encoded.split_ascii_whitespace() {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let mut decoded = String::new();
+            let mut rest = value;
+            while let Some(at) = rest.find('%') {
+                decoded.push_str(&rest[..at]);
+                let code = rest
+                    .get(at + 1..at + 3)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match code {
+                    Some(code) => {
+                        decoded.push(char::from(code));
+                        rest = &rest[at + 3..];
+                    }
+                    None => {
+                        decoded.push('%');
+                        rest = &rest[at + 1..];
+                    }
+                }
+            }
+            decoded.push_str(rest);
+            let prefix = match name.strip_prefix("xmlns") {
+                Some("") => None,
+                Some(name) => match name.strip_prefix(':') {
+                    Some(prefix) => Some(prefix.to_owned()),
+                    None => {
+                        open_attrs
+                            .attributes
+                            .push((format!("xmlns{name}"), decoded));
+                        continue;
+                    }
+                },
+                None => {
+                    open_attrs.attributes.push((name.to_owned(), decoded));
+                    continue;
+                }
+            };
+            open_attrs.namespaces.push(Namespace {
+                prefix,
+                uri: decoded,
+            });
+        }
+        open_attrs
+    }
+
+    pub(crate) fn to_attribute(&self) -> Option<(&'static str, String)> {
+        (!self.is_empty()).then(|| (OPEN_ATTRS, self.encode()))
+    }
+
+    pub(crate) fn from_attribute(name: &str, value: &str) -> Option<OpenAttrs> {
+        (name == OPEN_ATTRS).then(|| OpenAttrs::decode(value))
+    }
+}
+
+impl Serialize for OpenAttrs {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.encode())
+    }
+}
+
+impl<'de> Deserialize<'de> for OpenAttrs {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(OpenAttrs::decode(&String::deserialize(deserializer)?))
+    }
+}
+
+/// Strips the prefix from elements in the XML Schema namespace, since the
+/// deserializer tells fields apart by qualified name, and gathers their
+/// namespace declarations and foreign attributes into [`OPEN_ATTRS`].
+fn normalize(xml: &str) -> Result<String, DeError> {
+    let mut reader = NsReader::from_str(xml);
+    let mut writer = Writer::new(Vec::new());
+    loop {
+        let (resolved, event) = reader.read_resolved_event()?;
+        let in_xs = matches!(resolved, ResolveResult::Bound(namespace) if namespace.as_ref() == XS_NAMESPACE.as_bytes());
+        let event = match event {
+            Event::Eof => break,
+            Event::Start(start) if in_xs => Event::Start(gather_open_attrs(&start)?),
+            Event::Empty(start) if in_xs => Event::Empty(gather_open_attrs(&start)?),
+            Event::End(end) if in_xs => {
+                let local_name = String::from_utf8_lossy(end.local_name().into_inner());
+                Event::End(BytesEnd::new(local_name.into_owned()))
+            }
+            event => event,
+        };
+        writer.write_event(event)?;
+    }
+    into_string(writer)
+}
+
+/// Renames the start tag of a schema element to its local name, moving its
+/// declarations and foreign attributes into one [`OPEN_ATTRS`] attribute.
+fn gather_open_attrs(start: &BytesStart) -> Result<BytesStart<'static>, DeError> {
+    let local_name = String::from_utf8_lossy(start.local_name().into_inner()).into_owned();
+    // `xml:lang` is an attribute of these two, not a foreign one.
+    let has_lang = matches!(local_name.as_str(), "schema" | "documentation");
+    let mut renamed = BytesStart::new(local_name);
+    let mut open_attrs = OpenAttrs::default();
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        let key = attribute.key;
+        if let Some(binding) = key.as_namespace_binding() {
+            let prefix = match binding {
+                PrefixDeclaration::Default => None,
+                PrefixDeclaration::Named(prefix) => {
+                    Some(String::from_utf8_lossy(prefix).into_owned())
+                }
+            };
+            let uri = attribute.unescape_value()?.into_owned();
+            open_attrs.namespaces.push(Namespace { prefix, uri });
+        } else if key.as_ref() == OPEN_ATTRS.as_bytes() {
+            continue;
+        } else if key.prefix().is_some() && !(has_lang && key.as_ref() == b"xml:lang") {
+            let name = String::from_utf8_lossy(key.as_ref()).into_owned();
+            let value = attribute.unescape_value()?.into_owned();
+            open_attrs.attributes.push((name, value));
+        } else {
+            renamed.push_attribute(attribute);
+        }
+    }
+    if let Some((name, value)) = open_attrs.to_attribute() {
+        renamed.push_attribute((name, value.as_str()));
+    }
+    Ok(renamed)
+}
+
+/// Writes the [`OPEN_ATTRS`] of a start tag back as attributes, with the
+/// declarations first and the XML Schema namespace bound to `xs` on the
+/// document element.
+fn expand_open_attrs(start: &BytesStart, root: bool) -> Result<BytesStart<'static>, DeError> {
+    let mut expanded = BytesStart::new(String::from_utf8_lossy(start.name().as_ref()).into_owned());
+    if root {
+        expanded.push_attribute(("xmlns:xs", XS_NAMESPACE));
+    }
+    let mut open_attrs = OpenAttrs::default();
+    let mut attributes = Vec::new();
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        let name = String::from_utf8_lossy(attribute.key.as_ref());
+        match OpenAttrs::from_attribute(&name, &attribute.unescape_value()?) {
+            Some(value) => open_attrs = value,
+            None => attributes.push(attribute),
+        }
+    }
+    for namespace in &open_attrs.namespaces {
+        let name = match &namespace.prefix {
+            // Rebinding `xs` would take the schema elements out of the
+            // XML Schema namespace.
+            Some(prefix) if prefix == "xs" => continue,
+            Some(prefix) => format!("xmlns:{prefix}"),
+            None => "xmlns".to_owned(),
+        };
+        expanded.push_attribute((name.as_str(), namespace.uri.as_str()));
+    }
+    expanded.extend_attributes(attributes);
+    for (name, value) in &open_attrs.attributes {
+        expanded.push_attribute((name.as_str(), value.as_str()));
+    }
+    Ok(expanded)
+}
+
+fn into_string(writer: Writer<Vec<u8>>) -> Result<String, DeError> {
+    String::from_utf8(writer.into_inner())
+        .map_err(|e| DeError::from(quick_xml::Error::NonDecodable(Some(e.utf8_error()))))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use crate::elements::Schema;
+    use crate::groups::{
+        AnnotationContent, AttrDecl, Composition, SchemaTop, SimpleDerivation, TypeDefParticle,
+    };
+    use crate::simple_types::{AllNNI, DerivationSet, FormChoice};
+    use crate::xml_schema::XS_NAMESPACE;
+
+    #[test]
+    fn parses_and_round_trips_the_schema_for_schemas() -> Result<(), anyhow::Error> {
+        let data = read_to_string("../../schema/XMLSchema.xsd")?;
+        let schema: Schema = data.parse()?;
+
+        assert_eq!(schema.target_namespace.as_deref(), Some(XS_NAMESPACE));
+        assert_eq!(schema.element_form_default, Some(FormChoice::Qualified));
+        assert_eq!(schema.xml_lang.as_deref(), Some("EN"));
+        assert!(matches!(schema.composition[3], Composition::Import(_)));
+
+        let schema_element = schema
+            .schema_top
+            .iter()
+            .find_map(|top| match top {
+                SchemaTop::Element(element) if element.name.as_deref() == Some("schema") => {
+                    Some(element)
+                }
+                _ => None,
+            })
+            .expect("schema element");
+        assert_eq!(schema_element.identity_constraint.len(), 7);
+
+        let written = schema.to_xml()?;
+        let reread: Schema = written.parse()?;
+        assert_eq!(reread, schema);
+        Ok(())
+    }
+
+    #[test]
+    fn reads_any_prefix_and_mixed_documentation() -> Result<(), anyhow::Error> {
+        let data = r###"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema" xmlns="urn:t" targetNamespace="urn:t">
+  <xsd:annotation><xsd:documentation xml:lang="en">Some <b class="x">bold</b> text</xsd:documentation></xsd:annotation>
+  <xsd:complexType name="t" mixed="true">
+    <xsd:sequence minOccurs="0" maxOccurs="unbounded">
+      <xsd:element name="a" type="xsd:string"/>
+      <xsd:any namespace="##other" processContents="lax"/>
+    </xsd:sequence>
+    <xsd:attribute name="x" use="required"/>
+    <xsd:attributeGroup ref="g"/>
+    <xsd:attribute name="y"/>
+  </xsd:complexType>
+  <xsd:simpleType name="s" final="#all">
+    <xsd:restriction base="xsd:string"><xsd:maxLength value="3"/><xsd:pattern value="[a-z]*"/></xsd:restriction>
+  </xsd:simpleType>
+</xsd:schema>"###;
+        let schema = Schema::from_reader(data.as_bytes())?;
+
+        assert_eq!(schema.namespaces.len(), 2);
+        let Composition::Annotation(annotation) = &schema.composition[0] else {
+            panic!("expected an annotation");
+        };
+        let AnnotationContent::Documentation(documentation) = &annotation.content[0] else {
+            panic!("expected documentation");
+        };
+        assert_eq!(documentation.xml_lang.as_deref(), Some("en"));
+        assert_eq!(documentation.content.len(), 3);
+
+        let SchemaTop::ComplexType(complex_type) = &schema.schema_top[0] else {
+            panic!("expected a complex type");
+        };
+        let Some(TypeDefParticle::Sequence(sequence)) = &complex_type.particle else {
+            panic!("expected a sequence");
+        };
+        assert_eq!(sequence.max_occurs, Some(AllNNI::Unbounded));
+        assert_eq!(sequence.particles.len(), 2);
+        assert_eq!(complex_type.attributes.len(), 3);
+        assert!(matches!(
+            complex_type.attributes[1],
+            AttrDecl::AttributeGroup(_)
+        ));
+
+        let SchemaTop::SimpleType(simple_type) = &schema.schema_top[1] else {
+            panic!("expected a simple type");
+        };
+        assert_eq!(simple_type.final_attr, Some(DerivationSet::All));
+        let SimpleDerivation::Restriction(restriction) = &simple_type.derivation else {
+            panic!("expected a restriction");
+        };
+        assert_eq!(restriction.facets.len(), 2);
+
+        let written = schema.to_xml()?;
+        assert!(written
+            .starts_with(r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:xsd="#));
+        let mut reread: Schema = written.parse()?;
+        assert_eq!(reread.namespaces.len(), 3);
+        reread.namespaces = schema.namespaces.clone();
+        assert_eq!(reread, schema);
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_declarations_and_foreign_attributes() -> Result<(), anyhow::Error> {
+        let data = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:vc="http://www.w3.org/2007/XMLSchema-versioning" vc:minVersion="1.1">
+  <xs:element name="a" xmlns:p="urn:t" xmlns:jaxb="urn:jaxb" type="p:t" jaxb:class="A b%c"/>
+  <xs:simpleType name="t">
+    <xs:annotation><xs:documentation xmlns:d="urn:d" d:kind="note">Text</xs:documentation></xs:annotation>
+    <xs:restriction base="xs:string"/>
+  </xs:simpleType>
+</xs:schema>"#;
+        let schema: Schema = data.parse()?;
+
+        assert_eq!(schema.namespaces.len(), 2);
+        assert_eq!(
+            schema.open_attrs.attributes,
+            [("vc:minVersion".to_owned(), "1.1".to_owned())]
+        );
+        let SchemaTop::Element(element) = &schema.schema_top[0] else {
+            panic!("expected an element");
+        };
+        assert_eq!(element.type_attr.as_deref(), Some("p:t"));
+        assert_eq!(element.open_attrs.namespaces.len(), 2);
+        assert_eq!(
+            element.open_attrs.attributes,
+            [("jaxb:class".to_owned(), "A b%c".to_owned())]
+        );
+
+        let written = schema.to_xml()?;
+        assert!(written.contains(r#"vc:minVersion="1.1""#));
+        assert!(written.contains(
+            r#"<xs:element xmlns:p="urn:t" xmlns:jaxb="urn:jaxb" name="a" type="p:t" jaxb:class="A b%c"/>"#
+        ));
+        assert!(written.contains(r#"<xs:documentation xmlns:d="urn:d" d:kind="note">"#));
+        let reread: Schema = written.parse()?;
+        assert_eq!(reread, schema);
+        Ok(())
+    }
 }
@@ -0,0 +1,101 @@
+use std::cell::OnceCell;
+use std::collections::HashMap;
+
+use document::node::{Document, NodeId};
+
+use crate::error::{Error, Result};
+use crate::lexer::Name;
+use crate::node::NodeRef;
+use crate::value::Value;
+
+/// The static context of an evaluation: the document, namespace bindings for
+/// prefixes used in expressions, and variable bindings.
+pub struct Context<'a> {
+    document: &'a Document,
+    namespaces: HashMap<String, String>,
+    variables: HashMap<(Option<String>, String), Value>,
+    /// Position of every node in document order, built on first use.
+    order: OnceCell<HashMap<NodeId, usize>>,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(document: &'a Document) -> Self {
+        Context {
+            document,
+            namespaces: HashMap::new(),
+            variables: HashMap::new(),
+            order: OnceCell::new(),
+        }
+    }
+
+    pub fn document(&self) -> &'a Document {
+        self.document
+    }
+
+    /// Binds `prefix` for use in name tests, variable names and function
+    /// names.
+    pub fn set_namespace(&mut self, prefix: &str, uri: &str) {
+        self.namespaces.insert(prefix.to_owned(), uri.to_owned());
+    }
+
+    /// Binds the variable with the given expanded name.
+    pub fn set_variable(&mut self, namespace: Option<&str>, local_name: &str, value: Value) {
+        self.variables
+            .insert((namespace.map(str::to_owned), local_name.to_owned()), value);
+    }
+
+    pub(crate) fn resolve_prefix(&self, prefix: &str) -> Result<&str> {
+        if prefix == "xml" {
+            return Ok(document::namespace::XML_NAMESPACE);
+        }
+        self.namespaces
+            .get(prefix)
+            .map(String::as_str)
+            .ok_or_else(|| Error::UndeclaredPrefix(prefix.to_owned()))
+    }
+
+    /// Resolves the namespace of a name in an expression. Unprefixed names
+    /// are in no namespace.
+    pub(crate) fn resolve_name(&self, name: &Name) -> Result<Option<&str>> {
+        name.prefix
+            .as_deref()
+            .map(|prefix| self.resolve_prefix(prefix))
+            .transpose()
+    }
+
+    pub(crate) fn variable(&self, name: &Name) -> Result<Value> {
+        let namespace = self.resolve_name(name)?.map(str::to_owned);
+        self.variables
+            .get(&(namespace, name.local_name.clone()))
+            .cloned()
+            .ok_or_else(|| Error::UnknownVariable(name.local_name.clone()))
+    }
+
+    /// Sorts `nodes` into document order and removes duplicates.
+    pub fn sort(&self, nodes: &mut Vec<NodeRef>) {
+        nodes.sort_by_cached_key(|node| self.order_key(node));
+        nodes.dedup();
+    }
+
+    fn order_key(&self, node: &NodeRef) -> (usize, NodeId, u8, usize) {
+        let order = self.order.get_or_init(|| {
+            let mut order = HashMap::new();
+            for &top in &self.document.children {
+                order.insert(top, order.len());
+                for (id, _) in self.document.descendants(top) {
+                    order.insert(id, order.len());
+                }
+            }
+            order
+        });
+        // Detached nodes sort after the tree, by id.
+        let position = |id: &NodeId| order.get(id).map_or(usize::MAX, |position| position + 1);
+
+        match node {
+            NodeRef::Root => (0, 0, 0, 0),
+            NodeRef::Node(id) => (position(id), *id, 0, 0),
+            NodeRef::Namespace(id, index) => (position(id), *id, 1, *index),
+            NodeRef::Attribute(id, index) => (position(id), *id, 2, *index),
+        }
+    }
+}
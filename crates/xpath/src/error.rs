@@ -0,0 +1,51 @@
+use std::fmt::{Display, Formatter};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The expression does not match the XPath grammar. `offset` is the byte
+    /// offset in the expression where the problem was found.
+    Syntax { offset: usize, message: String },
+    /// A QName in the expression uses a prefix the context does not declare.
+    UndeclaredPrefix(String),
+    /// A variable reference has no binding in the context.
+    UnknownVariable(String),
+    /// A function call names a function outside the core library.
+    UnknownFunction(String),
+    /// A function was called with the wrong number of arguments.
+    Arity {
+        function: String,
+        expected: &'static str,
+        found: usize,
+    },
+    /// A node-set was required but the expression produced another type.
+    NotANodeSet,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Syntax { offset, message } => {
+                write!(f, "syntax error at offset {}: {}", offset, message)
+            }
+            Error::UndeclaredPrefix(prefix) => {
+                write!(f, "namespace prefix `{}` is not declared", prefix)
+            }
+            Error::UnknownVariable(name) => write!(f, "variable `${}` is not bound", name),
+            Error::UnknownFunction(name) => write!(f, "unknown function `{}()`", name),
+            Error::Arity {
+                function,
+                expected,
+                found,
+            } => write!(
+                f,
+                "`{}()` takes {} arguments but {} were given",
+                function, expected, found
+            ),
+            Error::NotANodeSet => f.write_str("expression does not evaluate to a node-set"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,420 @@
+use crate::ast::{Axis, BinaryOperator, Expr, NodeTest, PathStart, Step};
+use crate::context::Context;
+use crate::error::{Error, Result};
+use crate::functions;
+use crate::node::{NodeKind, NodeRef};
+use crate::value::Value;
+
+/// The dynamic context: the context node with its position and the size of
+/// the node list it belongs to.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Focus {
+    pub node: NodeRef,
+    pub position: usize,
+    pub size: usize,
+}
+
+impl Context<'_> {
+    pub(crate) fn evaluate(&self, expr: &Expr, focus: &Focus) -> Result<Value> {
+        match expr {
+            Expr::Literal(value) => Ok(Value::String(value.clone())),
+            Expr::Number(value) => Ok(Value::Number(*value)),
+            Expr::Variable(name) => self.variable(name),
+            Expr::Negate(operand) => {
+                let value = self.evaluate(operand, focus)?;
+                Ok(Value::Number(-value.to_number(self.document())))
+            }
+            Expr::FunctionCall(name, arguments) => functions::call(self, name, arguments, focus),
+            Expr::Binary(operator, left, right) => self.binary(*operator, left, right, focus),
+            Expr::Filter(primary, predicates) => {
+                let nodes = self.node_set(primary, focus)?;
+                // Filter predicates number nodes in document order.
+                let nodes = self.apply_predicates(nodes, predicates)?;
+                Ok(Value::NodeSet(nodes))
+            }
+            Expr::Path(start, steps) => {
+                let mut nodes = match start {
+                    PathStart::Root => vec![NodeRef::Root],
+                    PathStart::Context => vec![focus.node],
+                    PathStart::Expr(expr) => self.node_set(expr, focus)?,
+                };
+                for step in steps {
+                    let mut selected = Vec::new();
+                    for node in nodes {
+                        selected.extend(self.step(step, node)?);
+                    }
+                    self.sort(&mut selected);
+                    nodes = selected;
+                }
+                Ok(Value::NodeSet(nodes))
+            }
+        }
+    }
+
+    pub(crate) fn node_set(&self, expr: &Expr, focus: &Focus) -> Result<Vec<NodeRef>> {
+        match self.evaluate(expr, focus)? {
+            Value::NodeSet(nodes) => Ok(nodes),
+            _ => Err(Error::NotANodeSet),
+        }
+    }
+
+    fn binary(
+        &self,
+        operator: BinaryOperator,
+        left: &Expr,
+        right: &Expr,
+        focus: &Focus,
+    ) -> Result<Value> {
+        let document = self.document();
+        match operator {
+            BinaryOperator::Or => Ok(Value::Boolean(
+                self.evaluate(left, focus)?.to_boolean()
+                    || self.evaluate(right, focus)?.to_boolean(),
+            )),
+            BinaryOperator::And => Ok(Value::Boolean(
+                self.evaluate(left, focus)?.to_boolean()
+                    && self.evaluate(right, focus)?.to_boolean(),
+            )),
+            BinaryOperator::Union => {
+                let mut nodes = self.node_set(left, focus)?;
+                nodes.extend(self.node_set(right, focus)?);
+                self.sort(&mut nodes);
+                Ok(Value::NodeSet(nodes))
+            }
+            BinaryOperator::Equal
+            | BinaryOperator::NotEqual
+            | BinaryOperator::Less
+            | BinaryOperator::LessEqual
+            | BinaryOperator::Greater
+            | BinaryOperator::GreaterEqual => {
+                let left = self.evaluate(left, focus)?;
+                let right = self.evaluate(right, focus)?;
+                Ok(Value::Boolean(self.compare(operator, &left, &right)))
+            }
+            _ => {
+                let left = self.evaluate(left, focus)?.to_number(document);
+                let right = self.evaluate(right, focus)?.to_number(document);
+                Ok(Value::Number(match operator {
+                    BinaryOperator::Add => left + right,
+                    BinaryOperator::Subtract => left - right,
+                    BinaryOperator::Multiply => left * right,
+                    BinaryOperator::Divide => left / right,
+                    // `%` truncates like the XPath `mod` operator.
+                    _ => left % right,
+                }))
+            }
+        }
+    }
+
+    /// Compares two values per XPath 1.0 section 3.4, including the
+    /// existential semantics of node-sets.
+    fn compare(&self, operator: BinaryOperator, left: &Value, right: &Value) -> bool {
+        let document = self.document();
+        match (left, right) {
+            (Value::NodeSet(left), Value::NodeSet(right)) => {
+                let right: Vec<_> = right
+                    .iter()
+                    .map(|node| node.string_value(document))
+                    .collect();
+                left.iter().any(|node| {
+                    let left = Value::String(node.string_value(document));
+                    right.iter().any(|right| {
+                        self.compare_atomic(operator, &left, &Value::String(right.clone()))
+                    })
+                })
+            }
+            (Value::NodeSet(nodes), other) => {
+                if let Value::Boolean(_) = other {
+                    return self.compare_atomic(
+                        operator,
+                        &Value::Boolean(!nodes.is_empty()),
+                        other,
+                    );
+                }
+                nodes.iter().any(|node| {
+                    let value = Value::String(node.string_value(document));
+                    self.compare_atomic(operator, &value, other)
+                })
+            }
+            (other, Value::NodeSet(nodes)) => {
+                if let Value::Boolean(_) = other {
+                    return self.compare_atomic(
+                        operator,
+                        other,
+                        &Value::Boolean(!nodes.is_empty()),
+                    );
+                }
+                nodes.iter().any(|node| {
+                    let value = Value::String(node.string_value(document));
+                    self.compare_atomic(operator, other, &value)
+                })
+            }
+            _ => self.compare_atomic(operator, left, right),
+        }
+    }
+
+    fn compare_atomic(&self, operator: BinaryOperator, left: &Value, right: &Value) -> bool {
+        let document = self.document();
+        match operator {
+            BinaryOperator::Equal | BinaryOperator::NotEqual => {
+                let equal = match (left, right) {
+                    (Value::Boolean(_), _) | (_, Value::Boolean(_)) => {
+                        left.to_boolean() == right.to_boolean()
+                    }
+                    (Value::Number(_), _) | (_, Value::Number(_)) => {
+                        left.to_number(document) == right.to_number(document)
+                    }
+                    _ => left.to_string(document) == right.to_string(document),
+                };
+                equal == (operator == BinaryOperator::Equal)
+            }
+            _ => {
+                let left = left.to_number(document);
+                let right = right.to_number(document);
+                match operator {
+                    BinaryOperator::Less => left < right,
+                    BinaryOperator::LessEqual => left <= right,
+                    BinaryOperator::Greater => left > right,
+                    _ => left >= right,
+                }
+            }
+        }
+    }
+
+    /// Keeps the nodes for which every predicate holds, numbering them in
+    /// the order given.
+    fn apply_predicates(
+        &self,
+        mut nodes: Vec<NodeRef>,
+        predicates: &[Expr],
+    ) -> Result<Vec<NodeRef>> {
+        for predicate in predicates {
+            let size = nodes.len();
+            let mut kept = Vec::new();
+            for (index, node) in nodes.into_iter().enumerate() {
+                let focus = Focus {
+                    node,
+                    position: index + 1,
+                    size,
+                };
+                let matches = match self.evaluate(predicate, &focus)? {
+                    Value::Number(position) => position == focus.position as f64,
+                    value => value.to_boolean(),
+                };
+                if matches {
+                    kept.push(node);
+                }
+            }
+            nodes = kept;
+        }
+        Ok(nodes)
+    }
+
+    /// Selects the nodes of one step from a single context node, in
+    /// document order.
+    fn step(&self, step: &Step, node: NodeRef) -> Result<Vec<NodeRef>> {
+        let mut selected = Vec::new();
+        for candidate in self.axis(step.axis, node) {
+            if self.test(&step.test, step.axis, candidate)? {
+                selected.push(candidate);
+            }
+        }
+        let mut selected = self.apply_predicates(selected, &step.predicates)?;
+        if step.axis.is_reverse() {
+            selected.reverse();
+        }
+        Ok(selected)
+    }
+
+    /// The nodes on `axis` from `node`, nearest first.
+    fn axis(&self, axis: Axis, node: NodeRef) -> Vec<NodeRef> {
+        let document = self.document();
+        match axis {
+            Axis::SelfAxis => vec![node],
+            Axis::Child => node.children(document),
+            Axis::Attribute => node.attributes(document),
+            Axis::Namespace => node.namespace_nodes(document),
+            Axis::Parent => node.parent(document).into_iter().collect(),
+            Axis::Descendant => {
+                let mut nodes = Vec::new();
+                self.push_descendants(node, &mut nodes);
+                nodes
+            }
+            Axis::DescendantOrSelf => {
+                let mut nodes = vec![node];
+                self.push_descendants(node, &mut nodes);
+                nodes
+            }
+            Axis::Ancestor => self.ancestors(node),
+            Axis::AncestorOrSelf => {
+                let mut nodes = vec![node];
+                nodes.extend(self.ancestors(node));
+                nodes
+            }
+            Axis::FollowingSibling | Axis::PrecedingSibling => {
+                if matches!(node, NodeRef::Attribute(..) | NodeRef::Namespace(..)) {
+                    return Vec::new();
+                }
+                let siblings = node
+                    .parent(document)
+                    .map_or(Vec::new(), |parent| parent.children(document));
+                let index = siblings.iter().position(|&sibling| sibling == node);
+                match (axis, index) {
+                    (Axis::FollowingSibling, Some(index)) => siblings[index + 1..].to_vec(),
+                    (_, Some(index)) => siblings[..index].iter().rev().copied().collect(),
+                    _ => Vec::new(),
+                }
+            }
+            Axis::Following => {
+                let mut nodes = Vec::new();
+                let mut current = node;
+                if let NodeRef::Attribute(id, _) | NodeRef::Namespace(id, _) = node {
+                    current = NodeRef::Node(id);
+                    self.push_descendants(current, &mut nodes);
+                }
+                for ancestor in std::iter::once(current).chain(self.ancestors(current)) {
+                    for sibling in self.axis(Axis::FollowingSibling, ancestor) {
+                        nodes.push(sibling);
+                        self.push_descendants(sibling, &mut nodes);
+                    }
+                }
+                nodes
+            }
+            Axis::Preceding => {
+                let mut nodes = Vec::new();
+                let current = match node {
+                    NodeRef::Attribute(id, _) | NodeRef::Namespace(id, _) => NodeRef::Node(id),
+                    node => node,
+                };
+                for ancestor in std::iter::once(current).chain(self.ancestors(current)) {
+                    for sibling in self.axis(Axis::PrecedingSibling, ancestor) {
+                        let mut subtree = vec![sibling];
+                        self.push_descendants(sibling, &mut subtree);
+                        nodes.extend(subtree.into_iter().rev());
+                    }
+                }
+                nodes
+            }
+        }
+    }
+
+    fn ancestors(&self, node: NodeRef) -> Vec<NodeRef> {
+        let document = self.document();
+        let mut nodes = Vec::new();
+        let mut current = node.parent(document);
+        while let Some(parent) = current {
+            nodes.push(parent);
+            current = parent.parent(document);
+        }
+        nodes
+    }
+
+    fn push_descendants(&self, node: NodeRef, nodes: &mut Vec<NodeRef>) {
+        for child in node.children(self.document()) {
+            nodes.push(child);
+            self.push_descendants(child, nodes);
+        }
+    }
+
+    fn test(&self, test: &NodeTest, axis: Axis, node: NodeRef) -> Result<bool> {
+        let document = self.document();
+        let kind = node.kind(document);
+        let principal = match axis {
+            Axis::Attribute => NodeKind::Attribute,
+            Axis::Namespace => NodeKind::Namespace,
+            _ => NodeKind::Element,
+        };
+
+        Ok(match test {
+            NodeTest::Node => true,
+            NodeTest::Text => kind == NodeKind::Text,
+            NodeTest::Comment => kind == NodeKind::Comment,
+            NodeTest::ProcessingInstruction(target) => {
+                kind == NodeKind::ProcessingInstruction
+                    && target.as_ref().is_none_or(|target| {
+                        node.expanded_name(document)
+                            .is_some_and(|(_, name)| &name == target)
+                    })
+            }
+            NodeTest::Any => kind == principal,
+            NodeTest::AnyLocalName(prefix) => {
+                let namespace = self.resolve_prefix(prefix)?;
+                kind == principal
+                    && node
+                        .expanded_name(document)
+                        .is_some_and(|(uri, _)| uri.as_deref() == Some(namespace))
+            }
+            NodeTest::Name(name) => {
+                let namespace = self.resolve_name(name)?;
+                kind == principal
+                    && node
+                        .expanded_name(document)
+                        .is_some_and(|(uri, local_name)| {
+                            uri.as_deref() == namespace && local_name == name.local_name
+                        })
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use document::node::Document;
+
+    use crate::{Context, NodeRef, XPath};
+
+    const XML: &str = r#"<r xmlns:p="urn:p"><a x="1" y="2"><b/><c><d/></c><e/></a><f><g/></f></r>"#;
+
+    /// The names of the nodes `expression` selects, in the order returned.
+    fn names(document: &Document, expression: &str) -> String {
+        let context = Context::new(document);
+        let nodes = XPath::compile(expression)
+            .unwrap()
+            .select(&context, NodeRef::Root)
+            .unwrap();
+        nodes
+            .iter()
+            .map(|node| node.qualified_name(document))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn follows_each_axis() {
+        let document = Document::parse(XML).unwrap();
+
+        assert_eq!(names(&document, "//c/self::*"), "c");
+        assert_eq!(names(&document, "//a/child::*"), "b c e");
+        assert_eq!(names(&document, "/r/descendant::*"), "a b c d e f g");
+        assert_eq!(names(&document, "//c/descendant-or-self::*"), "c d");
+        assert_eq!(names(&document, "//d/parent::*"), "c");
+        assert_eq!(names(&document, "//d/ancestor::*"), "r a c");
+        assert_eq!(names(&document, "//d/ancestor-or-self::*"), "r a c d");
+        assert_eq!(names(&document, "//c/following-sibling::*"), "e");
+        assert_eq!(names(&document, "//e/preceding-sibling::*"), "b c");
+        assert_eq!(names(&document, "//c/following::*"), "e f g");
+        assert_eq!(names(&document, "//f/preceding::*"), "a b c d e");
+        assert_eq!(names(&document, "//a/attribute::*"), "x y");
+        assert_eq!(names(&document, "//g/namespace::*"), "p xml");
+        assert_eq!(names(&document, "//@x/parent::*"), "a");
+        assert_eq!(names(&document, "//@x/following::*"), "b c d e f g");
+        assert_eq!(names(&document, "//@x/preceding::*"), "");
+        assert_eq!(names(&document, "//@x/following-sibling::node()"), "");
+        assert_eq!(names(&document, "/parent::node()"), "");
+    }
+
+    #[test]
+    fn numbers_reverse_axes_nearest_first() {
+        let document = Document::parse(XML).unwrap();
+
+        assert_eq!(names(&document, "//d/ancestor::*[1]"), "c");
+        assert_eq!(names(&document, "//d/ancestor-or-self::*[2]"), "c");
+        assert_eq!(names(&document, "//d/ancestor::*[last()]"), "r");
+        assert_eq!(names(&document, "//e/preceding-sibling::*[1]"), "c");
+        assert_eq!(names(&document, "//f/preceding::*[1]"), "e");
+        assert_eq!(names(&document, "//f/preceding::*[position() < 3]"), "d e");
+        // A parenthesized step is a plain node-set in document order.
+        assert_eq!(names(&document, "(//d/ancestor::*)[1]"), "r");
+        assert_eq!(names(&document, "//c/following::*[1]"), "e");
+    }
+}
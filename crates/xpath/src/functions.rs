@@ -0,0 +1,406 @@
+use document::chars::is_whitespace;
+use document::dtd::AttributeType;
+use document::namespace::XML_NAMESPACE;
+use document::node::Element;
+
+use crate::ast::Expr;
+use crate::context::Context;
+use crate::error::{Error, Result};
+use crate::eval::Focus;
+use crate::lexer::Name;
+use crate::node::{NodeKind, NodeRef};
+use crate::value::Value;
+
+/// Calls a function from the XPath 1.0 core function library.
+pub(crate) fn call(
+    context: &Context,
+    name: &Name,
+    arguments: &[Expr],
+    focus: &Focus,
+) -> Result<Value> {
+    if name.prefix.is_some() {
+        return Err(Error::UnknownFunction(format!(
+            "{}:{}",
+            name.prefix.as_deref().unwrap_or_default(),
+            name.local_name
+        )));
+    }
+
+    let document = context.document();
+    let function = name.local_name.as_str();
+    let arity = |expected: &'static str, valid: bool| {
+        if valid {
+            Ok(())
+        } else {
+            Err(Error::Arity {
+                function: function.to_owned(),
+                expected,
+                found: arguments.len(),
+            })
+        }
+    };
+    let count = arguments.len();
+    let string = |index: usize| -> Result<String> {
+        match arguments.get(index) {
+            Some(argument) => Ok(context.evaluate(argument, focus)?.to_string(document)),
+            None => Ok(focus.node.string_value(document)),
+        }
+    };
+    let number = |index: usize| -> Result<f64> {
+        match arguments.get(index) {
+            Some(argument) => Ok(context.evaluate(argument, focus)?.to_number(document)),
+            None => Ok(Value::NodeSet(vec![focus.node]).to_number(document)),
+        }
+    };
+    // The first node of an optional node-set argument, defaulting to the
+    // context node.
+    let first_node = || -> Result<Option<NodeRef>> {
+        match arguments.first() {
+            Some(argument) => Ok(context.node_set(argument, focus)?.first().copied()),
+            None => Ok(Some(focus.node)),
+        }
+    };
+
+    let value = match function {
+        "last" => {
+            arity("0", count == 0)?;
+            Value::Number(focus.size as f64)
+        }
+        "position" => {
+            arity("0", count == 0)?;
+            Value::Number(focus.position as f64)
+        }
+        "count" => {
+            arity("1", count == 1)?;
+            Value::Number(context.node_set(&arguments[0], focus)?.len() as f64)
+        }
+        "id" => {
+            arity("1", count == 1)?;
+            let ids = match context.evaluate(&arguments[0], focus)? {
+                Value::NodeSet(nodes) => nodes
+                    .iter()
+                    .map(|node| node.string_value(document))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                value => value.to_string(document),
+            };
+            Value::NodeSet(elements_by_id(context, &ids))
+        }
+        "local-name" | "namespace-uri" | "name" => {
+            arity("0 or 1", count <= 1)?;
+            let node = first_node()?;
+            let value = match (function, node) {
+                (_, None) => String::new(),
+                ("name", Some(node)) => node.qualified_name(document),
+                ("local-name", Some(node)) => node
+                    .expanded_name(document)
+                    .map(|(_, local_name)| local_name)
+                    .unwrap_or_default(),
+                (_, Some(node)) => node
+                    .expanded_name(document)
+                    .and_then(|(uri, _)| uri)
+                    .unwrap_or_default(),
+            };
+            Value::String(value)
+        }
+        "string" => {
+            arity("0 or 1", count <= 1)?;
+            Value::String(string(0)?)
+        }
+        "concat" => {
+            arity("2 or more", count >= 2)?;
+            let mut value = String::new();
+            for index in 0..count {
+                value.push_str(&string(index)?);
+            }
+            Value::String(value)
+        }
+        "starts-with" => {
+            arity("2", count == 2)?;
+            Value::Boolean(string(0)?.starts_with(&string(1)?))
+        }
+        "contains" => {
+            arity("2", count == 2)?;
+            Value::Boolean(string(0)?.contains(&string(1)?))
+        }
+        "substring-before" => {
+            arity("2", count == 2)?;
+            let value = string(0)?;
+            let value = value
+                .split_once(&string(1)?)
+                .map_or("", |(before, _)| before);
+            Value::String(value.to_owned())
+        }
+        "substring-after" => {
+            arity("2", count == 2)?;
+            let value = string(0)?;
+            let value = value.split_once(&string(1)?).map_or("", |(_, after)| after);
+            Value::String(value.to_owned())
+        }
+        "substring" => {
+            arity("2 or 3", count == 2 || count == 3)?;
+            let value = string(0)?;
+            let start = round(number(1)?);
+            let end = if count == 3 {
+                start + round(number(2)?)
+            } else {
+                f64::INFINITY
+            };
+            let value = value
+                .chars()
+                .enumerate()
+                .filter(|(index, _)| {
+                    let position = (*index + 1) as f64;
+                    position >= start && position < end
+                })
+                .map(|(_, c)| c)
+                .collect();
+            Value::String(value)
+        }
+        "string-length" => {
+            arity("0 or 1", count <= 1)?;
+            Value::Number(string(0)?.chars().count() as f64)
+        }
+        "normalize-space" => {
+            arity("0 or 1", count <= 1)?;
+            let value = string(0)?;
+            let words: Vec<_> = value
+                .split(is_whitespace)
+                .filter(|word| !word.is_empty())
+                .collect();
+            Value::String(words.join(" "))
+        }
+        "translate" => {
+            arity("3", count == 3)?;
+            let from: Vec<char> = string(1)?.chars().collect();
+            let to: Vec<char> = string(2)?.chars().collect();
+            let value = string(0)?
+                .chars()
+                .filter_map(|c| match from.iter().position(|&f| f == c) {
+                    Some(index) => to.get(index).copied(),
+                    None => Some(c),
+                })
+                .collect();
+            Value::String(value)
+        }
+        "boolean" => {
+            arity("1", count == 1)?;
+            Value::Boolean(context.evaluate(&arguments[0], focus)?.to_boolean())
+        }
+        "not" => {
+            arity("1", count == 1)?;
+            Value::Boolean(!context.evaluate(&arguments[0], focus)?.to_boolean())
+        }
+        "true" => {
+            arity("0", count == 0)?;
+            Value::Boolean(true)
+        }
+        "false" => {
+            arity("0", count == 0)?;
+            Value::Boolean(false)
+        }
+        "lang" => {
+            arity("1", count == 1)?;
+            Value::Boolean(lang(context, focus.node, &string(0)?))
+        }
+        "number" => {
+            arity("0 or 1", count <= 1)?;
+            Value::Number(number(0)?)
+        }
+        "sum" => {
+            arity("1", count == 1)?;
+            let nodes = context.node_set(&arguments[0], focus)?;
+            Value::Number(
+                nodes
+                    .iter()
+                    .map(|node| Value::NodeSet(vec![*node]).to_number(document))
+                    .sum(),
+            )
+        }
+        "floor" => {
+            arity("1", count == 1)?;
+            Value::Number(number(0)?.floor())
+        }
+        "ceiling" => {
+            arity("1", count == 1)?;
+            Value::Number(number(0)?.ceil())
+        }
+        "round" => {
+            arity("1", count == 1)?;
+            Value::Number(round(number(0)?))
+        }
+        _ => return Err(Error::UnknownFunction(name.local_name.clone())),
+    };
+
+    Ok(value)
+}
+
+/// Rounds half up, keeping negative zero for values in `[-0.5, 0)`.
+fn round(value: f64) -> f64 {
+    if value.is_nan() || value.is_infinite() {
+        value
+    } else if (-0.5..0.0).contains(&value) {
+        -0.0
+    } else {
+        // Adding 0.5 before flooring would round 0.49999999999999994 and
+        // odd integers above 2^52 up.
+        let floor = value.floor();
+        if value - floor >= 0.5 {
+            floor + 1.0
+        } else {
+            floor
+        }
+    }
+}
+
+/// Finds the elements whose ID, given by `xml:id` or by an attribute the
+/// DTD declares `ID`, is one of the whitespace-separated tokens in `ids`.
+fn elements_by_id(context: &Context, ids: &str) -> Vec<NodeRef> {
+    let document = context.document();
+    let has_id = |element: &Element, id: &str| {
+        element.attribute(Some(XML_NAMESPACE), "id") == Some(id)
+            || document.dtd.as_ref().is_some_and(|dtd| {
+                let name = element.name.to_string();
+                element.attributes.iter().any(|attribute| {
+                    attribute.value == id
+                        && dtd
+                            .attribute(&name, &attribute.name.to_string())
+                            .is_some_and(|declaration| declaration.kind == AttributeType::Id)
+                })
+            })
+    };
+    let mut nodes = Vec::new();
+    for id in ids.split(is_whitespace).filter(|id| !id.is_empty()) {
+        let found = document.children.iter().find_map(|&top| {
+            std::iter::once(top)
+                .chain(document.descendants(top).map(|(id, _)| id))
+                .find(|&node| {
+                    document
+                        .element(node)
+                        .is_some_and(|element| has_id(element, id))
+                })
+        });
+        nodes.extend(found.map(NodeRef::Node));
+    }
+    context.sort(&mut nodes);
+    nodes
+}
+
+/// Tests the `xml:lang` in effect on `node` against `language`, ignoring
+/// case and allowing a more specific subtag.
+fn lang(context: &Context, node: NodeRef, language: &str) -> bool {
+    let document = context.document();
+    let mut current = Some(node);
+    while let Some(node) = current {
+        if node.kind(document) == NodeKind::Element {
+            if let NodeRef::Node(id) = node {
+                let value = document
+                    .element(id)
+                    .and_then(|element| element.attribute(Some(XML_NAMESPACE), "lang"));
+                if let Some(value) = value {
+                    let value = value.to_ascii_lowercase();
+                    let language = language.to_ascii_lowercase();
+                    return value == language
+                        || value
+                            .strip_prefix(&language)
+                            .is_some_and(|rest| rest.starts_with('-'));
+                }
+            }
+        }
+        current = node.parent(document);
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use document::node::Document;
+
+    use crate::functions::round;
+    use crate::{Context, NodeRef, Value, XPath};
+
+    fn eval(expression: &str) -> Value {
+        let document = Document::parse("<r/>").unwrap();
+        let context = Context::new(&document);
+        XPath::compile(expression)
+            .unwrap()
+            .evaluate(&context, NodeRef::Root)
+            .unwrap()
+    }
+
+    fn string(expression: &str) -> String {
+        let document = Document::parse("<r/>").unwrap();
+        eval(expression).to_string(&document)
+    }
+
+    #[test]
+    fn rounds_half_up() {
+        assert_eq!(round(2.5), 3.0);
+        assert_eq!(round(-2.5), -2.0);
+        assert_eq!(round(-2.6), -3.0);
+        assert_eq!(round(0.49999999999999994), 0.0);
+        assert_eq!(round(4503599627370497.0), 4503599627370497.0);
+        assert!(round(-0.5).is_sign_negative());
+        assert!(round(-0.0).is_sign_negative());
+        assert!(round(0.2).is_sign_positive());
+        assert!(round(f64::NAN).is_nan());
+        assert_eq!(round(f64::INFINITY), f64::INFINITY);
+        assert_eq!(round(f64::NEG_INFINITY), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn converts_between_numbers_and_strings() {
+        assert_eq!(string("0 div 0"), "NaN");
+        assert_eq!(string("1 div 0"), "Infinity");
+        assert_eq!(string("-1 div 0"), "-Infinity");
+        assert_eq!(string("-0"), "0");
+        assert_eq!(string("1 div -0"), "-Infinity");
+        assert_eq!(string("round(-0.4)"), "0");
+        assert_eq!(string("1.50"), "1.5");
+        assert_eq!(string("100000000000000000000"), "100000000000000000000");
+        assert_eq!(string("0.000001"), "0.000001");
+
+        assert_eq!(eval("number(' -1.5 ')"), Value::Number(-1.5));
+        assert_eq!(eval("number('.5')"), Value::Number(0.5));
+        assert_eq!(eval("number('5.')"), Value::Number(5.0));
+        for input in ["''", "'1e3'", "'+1'", "'- 1'", "'Infinity'", "'0x10'"] {
+            assert_eq!(
+                string(&format!("number({})", input)),
+                "NaN",
+                "number({})",
+                input
+            );
+        }
+
+        assert_eq!(eval("boolean(0 div 0)"), Value::Boolean(false));
+        assert_eq!(eval("boolean(-0)"), Value::Boolean(false));
+        assert_eq!(eval("boolean(1 div 0)"), Value::Boolean(true));
+        assert_eq!(eval("boolean('false')"), Value::Boolean(true));
+        assert_eq!(eval("string(true())"), Value::String("true".to_owned()));
+        assert_eq!(eval("number(true())"), Value::Number(1.0));
+        assert_eq!(eval("0 div 0 = 0 div 0"), Value::Boolean(false));
+        assert_eq!(eval("0 div 0 != 0 div 0"), Value::Boolean(true));
+    }
+
+    #[test]
+    fn finds_elements_by_declared_id() {
+        let document = Document::parse(
+            r#"<!DOCTYPE r [<!ATTLIST e key ID #IMPLIED note CDATA #IMPLIED>]>
+<r><e key="a" note="b"/><e key="b"/><f key="c"/><g xml:id="c"/></r>"#,
+        )
+        .unwrap();
+        let context = Context::new(&document);
+        let string = |expression: &str| {
+            XPath::compile(expression)
+                .unwrap()
+                .evaluate(&context, NodeRef::Root)
+                .unwrap()
+                .to_string(&document)
+        };
+
+        assert_eq!(string("count(id('b a a'))"), "2");
+        assert_eq!(string("id('b a a')[1]/@note"), "b");
+        assert_eq!(string("name(id('c'))"), "g");
+        assert_eq!(string("count(id('note'))"), "0");
+    }
+}
@@ -0,0 +1,377 @@
+use document::chars::{is_ncname_char, is_ncname_start_char, is_whitespace};
+
+use crate::error::{Error, Result};
+
+/// A lexical name: an optional prefix and a local part.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Name {
+    pub prefix: Option<String>,
+    pub local_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    Dot,
+    DotDot,
+    At,
+    Comma,
+    ColonColon,
+    Slash,
+    DoubleSlash,
+    Pipe,
+    Plus,
+    Minus,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Multiply,
+    And,
+    Or,
+    Mod,
+    Div,
+    /// `*`, `prefix:*` or a QName in a step.
+    NameTest(NameTest),
+    /// `comment`, `text`, `processing-instruction` or `node` before `(`.
+    NodeType(String),
+    FunctionName(Name),
+    AxisName(String),
+    Literal(String),
+    Number(f64),
+    Variable(Name),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NameTest {
+    Any,
+    AnyLocalName(String),
+    Name(Name),
+}
+
+/// Splits an expression into tokens, applying the disambiguation rules of
+/// XPath 1.0 section 3.7. Each token is paired with its byte offset.
+pub fn tokenize(input: &str) -> Result<Vec<(usize, Token)>> {
+    let mut lexer = Lexer {
+        input,
+        offset: 0,
+        tokens: Vec::new(),
+    };
+    lexer.run()?;
+    Ok(lexer.tokens)
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    offset: usize,
+    tokens: Vec<(usize, Token)>,
+}
+
+impl Lexer<'_> {
+    fn run(&mut self) -> Result<()> {
+        loop {
+            self.skip_whitespace();
+            let start = self.offset;
+            let Some(c) = self.peek() else {
+                return Ok(());
+            };
+
+            let token = match c {
+                '(' => self.single(Token::LeftParen),
+                ')' => self.single(Token::RightParen),
+                '[' => self.single(Token::LeftBracket),
+                ']' => self.single(Token::RightBracket),
+                '@' => self.single(Token::At),
+                ',' => self.single(Token::Comma),
+                '|' => self.single(Token::Pipe),
+                '+' => self.single(Token::Plus),
+                '-' => self.single(Token::Minus),
+                '=' => self.single(Token::Equal),
+                ':' if self.rest().starts_with("::") => self.pair(Token::ColonColon),
+                '/' if self.rest().starts_with("//") => self.pair(Token::DoubleSlash),
+                '/' => self.single(Token::Slash),
+                '!' if self.rest().starts_with("!=") => self.pair(Token::NotEqual),
+                '<' if self.rest().starts_with("<=") => self.pair(Token::LessEqual),
+                '<' => self.single(Token::Less),
+                '>' if self.rest().starts_with(">=") => self.pair(Token::GreaterEqual),
+                '>' => self.single(Token::Greater),
+                '.' if self.rest().starts_with("..") => self.pair(Token::DotDot),
+                '.' if !self.rest()[1..].starts_with(|c: char| c.is_ascii_digit()) => {
+                    self.single(Token::Dot)
+                }
+                '.' | '0'..='9' => self.number(),
+                '"' | '\'' => self.literal(c)?,
+                '$' => {
+                    self.offset += 1;
+                    let name = self.qname()?;
+                    Token::Variable(name)
+                }
+                '*' if self.operator_expected() => self.single(Token::Multiply),
+                '*' => self.single(Token::NameTest(NameTest::Any)),
+                c if is_ncname_start_char(c) => self.name()?,
+                c => return Err(self.error(format!("unexpected character `{}`", c))),
+            };
+            self.tokens.push((start, token));
+        }
+    }
+
+    fn rest(&self) -> &str {
+        &self.input[self.offset..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn single(&mut self, token: Token) -> Token {
+        self.offset += 1;
+        token
+    }
+
+    fn pair(&mut self, token: Token) -> Token {
+        self.offset += 2;
+        token
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start_matches(is_whitespace).len();
+    }
+
+    fn error(&self, message: String) -> Error {
+        Error::Syntax {
+            offset: self.offset,
+            message,
+        }
+    }
+
+    /// Whether the previous token leaves us in operator position, where `*`
+    /// multiplies and names are operator names.
+    fn operator_expected(&self) -> bool {
+        match self.tokens.last() {
+            None => false,
+            Some((_, token)) => !matches!(
+                token,
+                Token::At
+                    | Token::ColonColon
+                    | Token::LeftParen
+                    | Token::LeftBracket
+                    | Token::Comma
+                    | Token::And
+                    | Token::Or
+                    | Token::Mod
+                    | Token::Div
+                    | Token::Multiply
+                    | Token::Slash
+                    | Token::DoubleSlash
+                    | Token::Pipe
+                    | Token::Plus
+                    | Token::Minus
+                    | Token::Equal
+                    | Token::NotEqual
+                    | Token::Less
+                    | Token::LessEqual
+                    | Token::Greater
+                    | Token::GreaterEqual
+            ),
+        }
+    }
+
+    fn number(&mut self) -> Token {
+        let rest = self.rest();
+        let mut length = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if rest[length..].starts_with('.') {
+            length += 1;
+            let fraction = &rest[length..];
+            length += fraction.len()
+                - fraction
+                    .trim_start_matches(|c: char| c.is_ascii_digit())
+                    .len();
+        }
+        let value = rest[..length].parse().unwrap_or(f64::NAN);
+        self.offset += length;
+        Token::Number(value)
+    }
+
+    fn literal(&mut self, quote: char) -> Result<Token> {
+        let body = &self.rest()[1..];
+        let Some(end) = body.find(quote) else {
+            return Err(self.error("unterminated string literal".to_owned()));
+        };
+        let value = body[..end].to_owned();
+        self.offset += end + 2;
+        Ok(Token::Literal(value))
+    }
+
+    fn ncname(&mut self) -> Result<String> {
+        let rest = self.rest();
+        if !rest.starts_with(is_ncname_start_char) {
+            return Err(self.error("expected a name".to_owned()));
+        }
+        let length = rest.len() - rest.trim_start_matches(is_ncname_char).len();
+        let name = rest[..length].to_owned();
+        self.offset += length;
+        Ok(name)
+    }
+
+    fn qname(&mut self) -> Result<Name> {
+        let first = self.ncname()?;
+        if self.rest().starts_with(':') && !self.rest().starts_with("::") {
+            self.offset += 1;
+            let local_name = self.ncname()?;
+            return Ok(Name {
+                prefix: Some(first),
+                local_name,
+            });
+        }
+        Ok(Name {
+            prefix: None,
+            local_name: first,
+        })
+    }
+
+    fn name(&mut self) -> Result<Token> {
+        let operator_expected = self.operator_expected();
+        let first = self.ncname()?;
+
+        if operator_expected {
+            return match first.as_str() {
+                "and" => Ok(Token::And),
+                "or" => Ok(Token::Or),
+                "mod" => Ok(Token::Mod),
+                "div" => Ok(Token::Div),
+                _ => Err(self.error(format!("expected an operator, found `{}`", first))),
+            };
+        }
+
+        // `prefix:*` and `prefix:local` are single tokens.
+        let mut name = Name {
+            prefix: None,
+            local_name: first,
+        };
+        if self.rest().starts_with(':') && !self.rest().starts_with("::") {
+            self.offset += 1;
+            if self.rest().starts_with('*') {
+                self.offset += 1;
+                return Ok(Token::NameTest(NameTest::AnyLocalName(name.local_name)));
+            }
+            name.prefix = Some(name.local_name);
+            name.local_name = self.ncname()?;
+        }
+
+        let after = self.rest().trim_start_matches(is_whitespace);
+        if name.prefix.is_none() && after.starts_with("::") {
+            return Ok(Token::AxisName(name.local_name));
+        }
+        if after.starts_with('(') {
+            if name.prefix.is_none()
+                && matches!(
+                    name.local_name.as_str(),
+                    "comment" | "text" | "processing-instruction" | "node"
+                )
+            {
+                return Ok(Token::NodeType(name.local_name));
+            }
+            return Ok(Token::FunctionName(name));
+        }
+
+        Ok(Token::NameTest(NameTest::Name(name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::lexer::{tokenize, Name, NameTest, Token};
+
+    fn tokens(input: &str) -> Vec<Token> {
+        tokenize(input)
+            .unwrap()
+            .into_iter()
+            .map(|(_, token)| token)
+            .collect()
+    }
+
+    fn name(prefix: Option<&str>, local_name: &str) -> Name {
+        Name {
+            prefix: prefix.map(str::to_owned),
+            local_name: local_name.to_owned(),
+        }
+    }
+
+    #[test]
+    fn disambiguates_operators_and_names() {
+        // `*` and names are operators only after an operand.
+        assert_eq!(
+            tokens("* * div div *"),
+            [
+                Token::NameTest(NameTest::Any),
+                Token::Multiply,
+                Token::NameTest(NameTest::Name(name(None, "div"))),
+                Token::Div,
+                Token::NameTest(NameTest::Any),
+            ]
+        );
+        assert_eq!(
+            tokens("child :: p:* | text() | p:f( ) | $v:x"),
+            [
+                Token::AxisName("child".to_owned()),
+                Token::ColonColon,
+                Token::NameTest(NameTest::AnyLocalName("p".to_owned())),
+                Token::Pipe,
+                Token::NodeType("text".to_owned()),
+                Token::LeftParen,
+                Token::RightParen,
+                Token::Pipe,
+                Token::FunctionName(name(Some("p"), "f")),
+                Token::LeftParen,
+                Token::RightParen,
+                Token::Pipe,
+                Token::Variable(name(Some("v"), "x")),
+            ]
+        );
+        assert_eq!(
+            tokens(".5 + 1. - .././/@a!=\"it's\""),
+            [
+                Token::Number(0.5),
+                Token::Plus,
+                Token::Number(1.0),
+                Token::Minus,
+                Token::DotDot,
+                Token::Slash,
+                Token::Dot,
+                Token::DoubleSlash,
+                Token::At,
+                Token::NameTest(NameTest::Name(name(None, "a"))),
+                Token::NotEqual,
+                Token::Literal("it's".to_owned()),
+            ]
+        );
+        assert_eq!(
+            tokenize("a  <= 2").unwrap(),
+            [
+                (0, Token::NameTest(NameTest::Name(name(None, "a")))),
+                (3, Token::LessEqual),
+                (6, Token::Number(2.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_tokens() {
+        let offset = |input: &str| match tokenize(input) {
+            Err(Error::Syntax { offset, .. }) => Some(offset),
+            _ => None,
+        };
+        assert_eq!(offset("'open"), Some(0));
+        assert_eq!(offset("a # b"), Some(2));
+        assert_eq!(offset("1 foo"), Some(5));
+        assert_eq!(offset("$ x"), Some(1));
+        assert_eq!(offset("p:"), Some(2));
+    }
+}
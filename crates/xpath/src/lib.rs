@@ -0,0 +1,198 @@
+pub use context::Context;
+pub use error::{Error, Result};
+pub use node::{NodeKind, NodeRef};
+pub use value::Value;
+
+use crate::ast::Expr;
+use crate::eval::Focus;
+
+pub mod ast;
+mod context;
+mod error;
+mod eval;
+mod functions;
+pub mod lexer;
+pub mod node;
+pub mod parser;
+pub mod value;
+
+/// A compiled XPath 1.0 expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XPath {
+    pub expr: Expr,
+}
+
+impl XPath {
+    pub fn compile(expression: &str) -> Result<XPath> {
+        Ok(XPath {
+            expr: parser::parse(expression)?,
+        })
+    }
+
+    /// Evaluates the expression with `node` as the context node.
+    pub fn evaluate(&self, context: &Context, node: NodeRef) -> Result<Value> {
+        let focus = Focus {
+            node,
+            position: 1,
+            size: 1,
+        };
+        context.evaluate(&self.expr, &focus)
+    }
+
+    /// Evaluates an expression that must return a node-set.
+    pub fn select(&self, context: &Context, node: NodeRef) -> Result<Vec<NodeRef>> {
+        match self.evaluate(context, node)? {
+            Value::NodeSet(nodes) => Ok(nodes),
+            _ => Err(Error::NotANodeSet),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use document::node::Document;
+
+    use crate::{Context, Error, NodeRef, Value, XPath};
+
+    const XML: &str = r#"<library xmlns:b="urn:books" xml:lang="en">
+  <b:book id="1" year="1990"><title>Alpha</title><price>10</price></b:book>
+  <b:book id="2" year="2005"><title>Beta</title><price>25.5</price></b:book>
+  <!-- note -->
+  <magazine xml:id="m1" xml:lang="de-CH"><title>Gamma</title>text<![CDATA[ more]]></magazine>
+  <?sort by-title?>
+</library>"#;
+
+    fn eval(document: &Document, expression: &str) -> Result<Value, Error> {
+        let mut context = Context::new(document);
+        context.set_namespace("bk", "urn:books");
+        context.set_variable(None, "limit", Value::Number(20.0));
+        XPath::compile(expression)?.evaluate(&context, NodeRef::Root)
+    }
+
+    fn string(document: &Document, expression: &str) -> String {
+        eval(document, expression).unwrap().to_string(document)
+    }
+
+    fn number(document: &Document, expression: &str) -> f64 {
+        eval(document, expression).unwrap().to_number(document)
+    }
+
+    #[test]
+    fn selects_along_axes() -> Result<(), Error> {
+        let document = Document::parse(XML).unwrap();
+
+        assert_eq!(number(&document, "count(//bk:book)"), 2.0);
+        assert_eq!(number(&document, "count(/library/*)"), 3.0);
+        assert_eq!(number(&document, "count(//title/ancestor::*)"), 4.0);
+        assert_eq!(number(&document, "count(//@*)"), 7.0);
+        assert_eq!(number(&document, "count(/library/namespace::*)"), 2.0);
+        assert_eq!(
+            string(&document, "//bk:book[2]/preceding-sibling::*/title"),
+            "Alpha"
+        );
+        assert_eq!(
+            string(&document, "//bk:book[1]/following-sibling::*[last()]/title"),
+            "Gamma"
+        );
+        assert_eq!(
+            string(&document, "//price[. > 20]/preceding::title[1]"),
+            "Beta"
+        );
+        assert_eq!(string(&document, "(//title)[last()]"), "Gamma");
+        assert_eq!(number(&document, "count(//title[1]/following::*)"), 6.0);
+        assert_eq!(string(&document, "//title[.='Beta']/../@id"), "2");
+        assert_eq!(string(&document, "//comment()"), " note ");
+        assert_eq!(
+            string(&document, "name(//processing-instruction('sort'))"),
+            "sort"
+        );
+        assert_eq!(string(&document, "//magazine/text()"), "text more");
+        assert_eq!(number(&document, "count(//magazine/node())"), 2.0);
+
+        let context = Context::new(&document);
+        let books =
+            XPath::compile("//*[@year < 2000] | //magazine")?.select(&context, NodeRef::Root)?;
+        assert_eq!(books.len(), 2);
+        assert_eq!(string(&document, "local-name(//*[@year < 2000])"), "book");
+        assert_eq!(string(&document, "name(//*[@year < 2000])"), "b:book");
+        assert_eq!(
+            string(&document, "namespace-uri(//*[@year < 2000])"),
+            "urn:books"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn evaluates_core_functions() {
+        let document = Document::parse(XML).unwrap();
+
+        assert_eq!(number(&document, "sum(//price)"), 35.5);
+        assert_eq!(number(&document, "count(//price[. < $limit])"), 1.0);
+        assert_eq!(string(&document, "concat('a', 1, true())"), "a1true");
+        assert_eq!(string(&document, "substring('12345', 1.5, 2.6)"), "234");
+        assert_eq!(string(&document, "substring('12345', 0, 3)"), "12");
+        assert_eq!(
+            string(&document, "substring-before('1999/04/01', '/')"),
+            "1999"
+        );
+        assert_eq!(
+            string(&document, "substring-after('1999/04/01', '/')"),
+            "04/01"
+        );
+        assert_eq!(string(&document, "normalize-space('  a \n b ')"), "a b");
+        assert_eq!(
+            string(&document, "translate('--aaa--', 'abc-', 'ABC')"),
+            "AAA"
+        );
+        assert_eq!(number(&document, "string-length('héllo')"), 5.0);
+        assert_eq!(
+            number(&document, "round(2.5) + floor(-1.5) + ceiling(1.2)"),
+            3.0
+        );
+        assert!(number(&document, "round(-0.2)").is_sign_negative());
+        assert_eq!(string(&document, "1 div 0"), "Infinity");
+        assert_eq!(string(&document, "number('abc')"), "NaN");
+        assert_eq!(number(&document, "-7 mod 3"), -1.0);
+        assert_eq!(string(&document, "id('m1')/title"), "Gamma");
+        assert_eq!(number(&document, "count(//title[lang('de')])"), 1.0);
+        assert_eq!(number(&document, "count(//title[lang('en')])"), 2.0);
+        assert_eq!(eval(&document, "//price = 25.5"), Ok(Value::Boolean(true)));
+        assert_eq!(eval(&document, "//price != 10"), Ok(Value::Boolean(true)));
+        assert_eq!(
+            eval(&document, "not(//nothing) and //title = 'Beta'"),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            eval(&document, "starts-with(//magazine, 'Gam')"),
+            Ok(Value::Boolean(true))
+        );
+    }
+
+    #[test]
+    fn reports_errors() {
+        let document = Document::parse(XML).unwrap();
+
+        assert!(matches!(
+            XPath::compile("//book["),
+            Err(Error::Syntax { .. })
+        ));
+        assert!(matches!(XPath::compile("1 +"), Err(Error::Syntax { .. })));
+        assert!(matches!(
+            eval(&document, "//x:book"),
+            Err(Error::UndeclaredPrefix(_))
+        ));
+        assert!(matches!(
+            eval(&document, "$missing"),
+            Err(Error::UnknownVariable(_))
+        ));
+        assert!(matches!(
+            eval(&document, "frobnicate()"),
+            Err(Error::UnknownFunction(_))
+        ));
+        assert!(matches!(
+            eval(&document, "count()"),
+            Err(Error::Arity { .. })
+        ));
+        assert!(matches!(eval(&document, "'a'/b"), Err(Error::NotANodeSet)));
+    }
+}
@@ -0,0 +1,233 @@
+use document::node::{Document, Node, NodeId};
+
+/// A node of the XPath data model.
+///
+/// Attribute and namespace nodes are not stored in the [`Document`]; they are
+/// addressed through their element. Adjacent text and CDATA nodes form a
+/// single XPath text node, addressed by the first of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum NodeRef {
+    Root,
+    Node(NodeId),
+    /// An element and the index of one of its attributes.
+    Attribute(NodeId, usize),
+    /// An element and the index of one of its in-scope namespaces, as
+    /// returned by [`namespaces`].
+    Namespace(NodeId, usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Root,
+    Element,
+    Attribute,
+    Namespace,
+    Text,
+    Comment,
+    ProcessingInstruction,
+}
+
+impl NodeRef {
+    pub fn kind(&self, document: &Document) -> NodeKind {
+        match self {
+            NodeRef::Root => NodeKind::Root,
+            NodeRef::Attribute(..) => NodeKind::Attribute,
+            NodeRef::Namespace(..) => NodeKind::Namespace,
+            NodeRef::Node(id) => match document.node(*id) {
+                Some(Node::Element(_)) | None => NodeKind::Element,
+                Some(Node::Text(_) | Node::CData(_)) => NodeKind::Text,
+                Some(Node::Comment(_)) => NodeKind::Comment,
+                Some(Node::ProcessingInstruction(_)) => NodeKind::ProcessingInstruction,
+            },
+        }
+    }
+
+    pub fn parent(&self, document: &Document) -> Option<NodeRef> {
+        match self {
+            NodeRef::Root => None,
+            NodeRef::Attribute(id, _) | NodeRef::Namespace(id, _) => Some(NodeRef::Node(*id)),
+            NodeRef::Node(id) => match document.node(*id)?.parent() {
+                Some(parent) => Some(NodeRef::Node(parent)),
+                None if document.children.contains(id) => Some(NodeRef::Root),
+                None => None,
+            },
+        }
+    }
+
+    /// The children of the root or an element, with text runs collapsed.
+    pub fn children(&self, document: &Document) -> Vec<NodeRef> {
+        let ids = match self {
+            NodeRef::Root => &document.children[..],
+            NodeRef::Node(id) => document.node(*id).map_or(&[][..], Node::children),
+            _ => &[],
+        };
+        collapse_text(document, ids)
+    }
+
+    pub fn attributes(&self, document: &Document) -> Vec<NodeRef> {
+        match self {
+            NodeRef::Node(id) => document.element(*id).map_or(Vec::new(), |element| {
+                (0..element.attributes.len())
+                    .map(|index| NodeRef::Attribute(*id, index))
+                    .collect()
+            }),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn namespace_nodes(&self, document: &Document) -> Vec<NodeRef> {
+        match self {
+            NodeRef::Node(id) if document.element(*id).is_some() => (0..namespaces(document, *id)
+                .len())
+                .map(|index| NodeRef::Namespace(*id, index))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The expanded name as a namespace URI and local part. Namespace nodes
+    /// have their prefix as local part; processing instructions their target.
+    pub fn expanded_name(&self, document: &Document) -> Option<(Option<String>, String)> {
+        match self {
+            NodeRef::Node(id) => match document.node(*id)? {
+                Node::Element(element) => Some((
+                    element.name.namespace.clone(),
+                    element.name.local_name.clone(),
+                )),
+                Node::ProcessingInstruction(pi) => Some((None, split_pi(&pi.data).0.to_owned())),
+                _ => None,
+            },
+            NodeRef::Attribute(id, index) => {
+                let attribute = document.element(*id)?.attributes.get(*index)?;
+                Some((
+                    attribute.name.namespace.clone(),
+                    attribute.name.local_name.clone(),
+                ))
+            }
+            NodeRef::Namespace(id, index) => {
+                let (prefix, _) = namespaces(document, *id).into_iter().nth(*index)?;
+                Some((None, prefix.unwrap_or_default()))
+            }
+            NodeRef::Root => None,
+        }
+    }
+
+    /// The name as written in the document, for the `name()` function.
+    pub fn qualified_name(&self, document: &Document) -> String {
+        match self {
+            NodeRef::Node(id) => match document.node(*id) {
+                Some(Node::Element(element)) => element.name.to_string(),
+                Some(Node::ProcessingInstruction(pi)) => split_pi(&pi.data).0.to_owned(),
+                _ => String::new(),
+            },
+            NodeRef::Attribute(id, index) => document
+                .element(*id)
+                .and_then(|element| element.attributes.get(*index))
+                .map_or(String::new(), |attribute| attribute.name.to_string()),
+            _ => self
+                .expanded_name(document)
+                .map_or(String::new(), |(_, local_name)| local_name),
+        }
+    }
+
+    pub fn string_value(&self, document: &Document) -> String {
+        match self {
+            NodeRef::Root => {
+                let mut value = String::new();
+                for &child in &document.children {
+                    NodeRef::Node(child).push_text(document, &mut value);
+                }
+                value
+            }
+            NodeRef::Attribute(id, index) => document
+                .element(*id)
+                .and_then(|element| element.attributes.get(*index))
+                .map_or(String::new(), |attribute| attribute.value.clone()),
+            NodeRef::Namespace(id, index) => namespaces(document, *id)
+                .into_iter()
+                .nth(*index)
+                .map_or(String::new(), |(_, uri)| uri),
+            NodeRef::Node(id) => match document.node(*id) {
+                Some(Node::Element(_)) => {
+                    let mut value = String::new();
+                    self.push_text(document, &mut value);
+                    value
+                }
+                Some(Node::Text(_) | Node::CData(_)) => text_run(document, *id)
+                    .iter()
+                    .filter_map(|&id| text_data(document, id))
+                    .collect(),
+                Some(Node::Comment(comment)) => comment.data.clone(),
+                Some(Node::ProcessingInstruction(pi)) => split_pi(&pi.data).1.to_owned(),
+                None => String::new(),
+            },
+        }
+    }
+
+    fn push_text(&self, document: &Document, value: &mut String) {
+        let NodeRef::Node(id) = self else {
+            return;
+        };
+        match document.node(*id) {
+            Some(Node::Element(element)) => {
+                for &child in &element.children {
+                    NodeRef::Node(child).push_text(document, value);
+                }
+            }
+            Some(Node::Text(text)) => value.push_str(&text.data),
+            Some(Node::CData(cdata)) => value.push_str(&cdata.data),
+            _ => {}
+        }
+    }
+}
+
+/// The namespaces in scope on an element, in a stable order.
+pub fn namespaces(document: &Document, id: NodeId) -> Vec<(Option<String>, String)> {
+    document.in_scope_namespaces(id).into_iter().collect()
+}
+
+fn is_text(document: &Document, id: NodeId) -> bool {
+    matches!(document.node(id), Some(Node::Text(_) | Node::CData(_)))
+}
+
+fn text_data(document: &Document, id: NodeId) -> Option<&str> {
+    match document.node(id)? {
+        Node::Text(text) => Some(&text.data),
+        Node::CData(cdata) => Some(&cdata.data),
+        _ => None,
+    }
+}
+
+/// Keeps only the first node of every run of adjacent text nodes.
+fn collapse_text(document: &Document, ids: &[NodeId]) -> Vec<NodeRef> {
+    let mut previous_is_text = false;
+    let mut nodes = Vec::with_capacity(ids.len());
+    for &id in ids {
+        let text = is_text(document, id);
+        if !(text && previous_is_text) {
+            nodes.push(NodeRef::Node(id));
+        }
+        previous_is_text = text;
+    }
+    nodes
+}
+
+/// The text node `id` and the text nodes directly following it.
+fn text_run(document: &Document, id: NodeId) -> Vec<NodeId> {
+    std::iter::once(id)
+        .chain(
+            document
+                .following_siblings(id)
+                .map(|(id, _)| id)
+                .take_while(|&id| is_text(document, id)),
+        )
+        .collect()
+}
+
+/// Splits processing instruction data into its target and content.
+fn split_pi(data: &str) -> (&str, &str) {
+    match data.find(|c: char| c.is_ascii_whitespace()) {
+        Some(index) => (&data[..index], data[index..].trim_start()),
+        None => (data, ""),
+    }
+}
@@ -0,0 +1,483 @@
+use crate::ast::{Axis, BinaryOperator, Expr, NodeTest, PathStart, Step};
+use crate::error::{Error, Result};
+use crate::lexer::{tokenize, NameTest, Token};
+
+/// Parses a complete XPath 1.0 expression.
+pub fn parse(input: &str) -> Result<Expr> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        index: 0,
+        end: input.len(),
+    };
+    let expr = parser.or_expr()?;
+    match parser.tokens.get(parser.index) {
+        None => Ok(expr),
+        Some((offset, token)) => Err(Error::Syntax {
+            offset: *offset,
+            message: format!("unexpected {:?}", token),
+        }),
+    }
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.index).map(|(_, token)| token.clone());
+        self.index += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<()> {
+        if self.eat(&token) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {:?}", token)))
+        }
+    }
+
+    fn error(&self, message: String) -> Error {
+        let offset = self
+            .tokens
+            .get(self.index)
+            .map_or(self.end, |(offset, _)| *offset);
+        Error::Syntax { offset, message }
+    }
+
+    /// Parses a left-associative chain of binary operators.
+    fn binary(
+        &mut self,
+        operand: fn(&mut Self) -> Result<Expr>,
+        operator: fn(&Token) -> Option<BinaryOperator>,
+    ) -> Result<Expr> {
+        let mut left = operand(self)?;
+        while let Some(op) = self.peek().and_then(operator) {
+            self.index += 1;
+            let right = operand(self)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn or_expr(&mut self) -> Result<Expr> {
+        self.binary(Self::and_expr, |token| match token {
+            Token::Or => Some(BinaryOperator::Or),
+            _ => None,
+        })
+    }
+
+    fn and_expr(&mut self) -> Result<Expr> {
+        self.binary(Self::equality_expr, |token| match token {
+            Token::And => Some(BinaryOperator::And),
+            _ => None,
+        })
+    }
+
+    fn equality_expr(&mut self) -> Result<Expr> {
+        self.binary(Self::relational_expr, |token| match token {
+            Token::Equal => Some(BinaryOperator::Equal),
+            Token::NotEqual => Some(BinaryOperator::NotEqual),
+            _ => None,
+        })
+    }
+
+    fn relational_expr(&mut self) -> Result<Expr> {
+        self.binary(Self::additive_expr, |token| match token {
+            Token::Less => Some(BinaryOperator::Less),
+            Token::LessEqual => Some(BinaryOperator::LessEqual),
+            Token::Greater => Some(BinaryOperator::Greater),
+            Token::GreaterEqual => Some(BinaryOperator::GreaterEqual),
+            _ => None,
+        })
+    }
+
+    fn additive_expr(&mut self) -> Result<Expr> {
+        self.binary(Self::multiplicative_expr, |token| match token {
+            Token::Plus => Some(BinaryOperator::Add),
+            Token::Minus => Some(BinaryOperator::Subtract),
+            _ => None,
+        })
+    }
+
+    fn multiplicative_expr(&mut self) -> Result<Expr> {
+        self.binary(Self::unary_expr, |token| match token {
+            Token::Multiply => Some(BinaryOperator::Multiply),
+            Token::Div => Some(BinaryOperator::Divide),
+            Token::Mod => Some(BinaryOperator::Modulo),
+            _ => None,
+        })
+    }
+
+    fn unary_expr(&mut self) -> Result<Expr> {
+        if self.eat(&Token::Minus) {
+            return Ok(Expr::Negate(Box::new(self.unary_expr()?)));
+        }
+        self.union_expr()
+    }
+
+    fn union_expr(&mut self) -> Result<Expr> {
+        self.binary(Self::path_expr, |token| match token {
+            Token::Pipe => Some(BinaryOperator::Union),
+            _ => None,
+        })
+    }
+
+    fn path_expr(&mut self) -> Result<Expr> {
+        match self.peek() {
+            Some(
+                Token::Variable(_)
+                | Token::LeftParen
+                | Token::Literal(_)
+                | Token::Number(_)
+                | Token::FunctionName(_),
+            ) => {}
+            _ => return self.location_path(),
+        }
+
+        let primary = self.primary_expr()?;
+        let predicates = self.predicates()?;
+        let filter = if predicates.is_empty() {
+            primary
+        } else {
+            Expr::Filter(Box::new(primary), predicates)
+        };
+
+        let mut steps = Vec::new();
+        match self.peek() {
+            Some(Token::Slash) => {
+                self.index += 1;
+            }
+            Some(Token::DoubleSlash) => {
+                self.index += 1;
+                steps.push(descendant_or_self());
+            }
+            _ => return Ok(filter),
+        }
+        self.relative_location_path(&mut steps)?;
+        Ok(Expr::Path(PathStart::Expr(Box::new(filter)), steps))
+    }
+
+    fn primary_expr(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some(Token::Variable(name)) => Ok(Expr::Variable(name)),
+            Some(Token::Literal(value)) => Ok(Expr::Literal(value)),
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::LeftParen) => {
+                let expr = self.or_expr()?;
+                self.expect(Token::RightParen)?;
+                Ok(expr)
+            }
+            Some(Token::FunctionName(name)) => {
+                self.expect(Token::LeftParen)?;
+                let mut arguments = Vec::new();
+                if !self.eat(&Token::RightParen) {
+                    loop {
+                        arguments.push(self.or_expr()?);
+                        if self.eat(&Token::RightParen) {
+                            break;
+                        }
+                        self.expect(Token::Comma)?;
+                    }
+                }
+                Ok(Expr::FunctionCall(name, arguments))
+            }
+            _ => {
+                self.index -= 1;
+                Err(self.error("expected an expression".to_owned()))
+            }
+        }
+    }
+
+    fn predicates(&mut self) -> Result<Vec<Expr>> {
+        let mut predicates = Vec::new();
+        while self.eat(&Token::LeftBracket) {
+            predicates.push(self.or_expr()?);
+            self.expect(Token::RightBracket)?;
+        }
+        Ok(predicates)
+    }
+
+    fn location_path(&mut self) -> Result<Expr> {
+        let mut steps = Vec::new();
+        let start = match self.peek() {
+            Some(Token::Slash) => {
+                self.index += 1;
+                if !self.at_step() {
+                    return Ok(Expr::Path(PathStart::Root, steps));
+                }
+                PathStart::Root
+            }
+            Some(Token::DoubleSlash) => {
+                self.index += 1;
+                steps.push(descendant_or_self());
+                PathStart::Root
+            }
+            _ => PathStart::Context,
+        };
+
+        self.relative_location_path(&mut steps)?;
+        Ok(Expr::Path(start, steps))
+    }
+
+    fn at_step(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(
+                Token::NameTest(_)
+                    | Token::NodeType(_)
+                    | Token::AxisName(_)
+                    | Token::At
+                    | Token::Dot
+                    | Token::DotDot
+            )
+        )
+    }
+
+    fn relative_location_path(&mut self, steps: &mut Vec<Step>) -> Result<()> {
+        loop {
+            steps.push(self.step()?);
+            match self.peek() {
+                Some(Token::Slash) => {
+                    self.index += 1;
+                }
+                Some(Token::DoubleSlash) => {
+                    self.index += 1;
+                    steps.push(descendant_or_self());
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn step(&mut self) -> Result<Step> {
+        if self.eat(&Token::Dot) {
+            return Ok(Step {
+                axis: Axis::SelfAxis,
+                test: NodeTest::Node,
+                predicates: Vec::new(),
+            });
+        }
+        if self.eat(&Token::DotDot) {
+            return Ok(Step {
+                axis: Axis::Parent,
+                test: NodeTest::Node,
+                predicates: Vec::new(),
+            });
+        }
+
+        let axis = match self.peek() {
+            Some(Token::At) => {
+                self.index += 1;
+                Axis::Attribute
+            }
+            Some(Token::AxisName(name)) => {
+                let axis = Axis::from_name(name)
+                    .ok_or_else(|| self.error(format!("unknown axis `{}`", name)))?;
+                self.index += 1;
+                self.expect(Token::ColonColon)?;
+                axis
+            }
+            _ => Axis::Child,
+        };
+
+        let test = match self.next() {
+            Some(Token::NameTest(NameTest::Any)) => NodeTest::Any,
+            Some(Token::NameTest(NameTest::AnyLocalName(prefix))) => NodeTest::AnyLocalName(prefix),
+            Some(Token::NameTest(NameTest::Name(name))) => NodeTest::Name(name),
+            Some(Token::NodeType(node_type)) => {
+                self.expect(Token::LeftParen)?;
+                let test = match node_type.as_str() {
+                    "node" => NodeTest::Node,
+                    "text" => NodeTest::Text,
+                    "comment" => NodeTest::Comment,
+                    _ => match self.peek() {
+                        Some(Token::Literal(target)) => {
+                            let target = target.clone();
+                            self.index += 1;
+                            NodeTest::ProcessingInstruction(Some(target))
+                        }
+                        _ => NodeTest::ProcessingInstruction(None),
+                    },
+                };
+                self.expect(Token::RightParen)?;
+                test
+            }
+            _ => {
+                self.index -= 1;
+                return Err(self.error("expected a node test".to_owned()));
+            }
+        };
+
+        Ok(Step {
+            axis,
+            test,
+            predicates: self.predicates()?,
+        })
+    }
+}
+
+/// The step `//` abbreviates.
+fn descendant_or_self() -> Step {
+    Step {
+        axis: Axis::DescendantOrSelf,
+        test: NodeTest::Node,
+        predicates: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::{Axis, BinaryOperator, Expr, NodeTest, PathStart, Step};
+    use crate::error::Error;
+    use crate::lexer::Name;
+    use crate::parser::parse;
+
+    fn step(axis: Axis, test: NodeTest) -> Step {
+        Step {
+            axis,
+            test,
+            predicates: Vec::new(),
+        }
+    }
+
+    fn named(local_name: &str) -> NodeTest {
+        NodeTest::Name(Name {
+            prefix: None,
+            local_name: local_name.to_owned(),
+        })
+    }
+
+    fn binary(op: BinaryOperator, left: Expr, right: Expr) -> Expr {
+        Expr::Binary(op, Box::new(left), Box::new(right))
+    }
+
+    #[test]
+    fn parses_operator_precedence() {
+        use BinaryOperator::*;
+        assert_eq!(
+            parse("1 + 2 * 3 = 7 or 1 and 0"),
+            Ok(binary(
+                Or,
+                binary(
+                    Equal,
+                    binary(
+                        Add,
+                        Expr::Number(1.0),
+                        binary(Multiply, Expr::Number(2.0), Expr::Number(3.0))
+                    ),
+                    Expr::Number(7.0)
+                ),
+                binary(And, Expr::Number(1.0), Expr::Number(0.0))
+            ))
+        );
+        // Subtraction is left-associative, and `|` binds tighter than
+        // negation.
+        assert_eq!(
+            parse("5 - 2 - -1"),
+            Ok(binary(
+                Subtract,
+                binary(Subtract, Expr::Number(5.0), Expr::Number(2.0)),
+                Expr::Negate(Box::new(Expr::Number(1.0)))
+            ))
+        );
+        assert_eq!(
+            parse("-a | b"),
+            Ok(Expr::Negate(Box::new(binary(
+                Union,
+                Expr::Path(PathStart::Context, vec![step(Axis::Child, named("a"))]),
+                Expr::Path(PathStart::Context, vec![step(Axis::Child, named("b"))]),
+            ))))
+        );
+    }
+
+    #[test]
+    fn expands_abbreviations() {
+        assert_eq!(parse("/"), Ok(Expr::Path(PathStart::Root, Vec::new())));
+        assert_eq!(
+            parse("//a/../@b"),
+            Ok(Expr::Path(
+                PathStart::Root,
+                vec![
+                    step(Axis::DescendantOrSelf, NodeTest::Node),
+                    step(Axis::Child, named("a")),
+                    step(Axis::Parent, NodeTest::Node),
+                    step(Axis::Attribute, named("b")),
+                ]
+            ))
+        );
+        assert_eq!(
+            parse("$v[1]//processing-instruction('t')"),
+            Ok(Expr::Path(
+                PathStart::Expr(Box::new(Expr::Filter(
+                    Box::new(Expr::Variable(Name {
+                        prefix: None,
+                        local_name: "v".to_owned(),
+                    })),
+                    vec![Expr::Number(1.0)]
+                ))),
+                vec![
+                    step(Axis::DescendantOrSelf, NodeTest::Node),
+                    step(
+                        Axis::Child,
+                        NodeTest::ProcessingInstruction(Some("t".to_owned()))
+                    ),
+                ]
+            ))
+        );
+        assert_eq!(
+            parse("preceding-sibling::node()[2][. = 'x']"),
+            Ok(Expr::Path(
+                PathStart::Context,
+                vec![Step {
+                    axis: Axis::PrecedingSibling,
+                    test: NodeTest::Node,
+                    predicates: vec![
+                        Expr::Number(2.0),
+                        binary(
+                            BinaryOperator::Equal,
+                            Expr::Path(
+                                PathStart::Context,
+                                vec![step(Axis::SelfAxis, NodeTest::Node)]
+                            ),
+                            Expr::Literal("x".to_owned())
+                        ),
+                    ],
+                }]
+            ))
+        );
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        let offset = |input: &str| match parse(input) {
+            Err(Error::Syntax { offset, .. }) => Some(offset),
+            other => panic!("{} parsed as {:?}", input, other),
+        };
+        assert_eq!(offset("//book["), Some(7));
+        assert_eq!(offset("1 +"), Some(3));
+        assert_eq!(offset("child::"), Some(7));
+        assert_eq!(offset("sideways::a"), Some(0));
+        assert_eq!(offset("(1"), Some(2));
+        assert_eq!(offset("f(1,)"), Some(4));
+        assert_eq!(offset("a/"), Some(2));
+        assert_eq!(offset("1 2"), Some(2));
+        assert_eq!(offset("comment(1)"), Some(8));
+    }
+}
@@ -0,0 +1,105 @@
+use document::chars::is_whitespace;
+use document::node::Document;
+
+use crate::node::NodeRef;
+
+/// The result of evaluating an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// Nodes in document order, without duplicates.
+    NodeSet(Vec<NodeRef>),
+    Boolean(bool),
+    Number(f64),
+    String(String),
+}
+
+impl Value {
+    /// The `boolean()` conversion.
+    pub fn to_boolean(&self) -> bool {
+        match self {
+            Value::NodeSet(nodes) => !nodes.is_empty(),
+            Value::Boolean(value) => *value,
+            Value::Number(value) => *value != 0.0 && !value.is_nan(),
+            Value::String(value) => !value.is_empty(),
+        }
+    }
+
+    /// The `number()` conversion.
+    pub fn to_number(&self, document: &Document) -> f64 {
+        match self {
+            Value::NodeSet(_) => string_to_number(&self.to_string(document)),
+            Value::Boolean(value) => f64::from(u8::from(*value)),
+            Value::Number(value) => *value,
+            Value::String(value) => string_to_number(value),
+        }
+    }
+
+    /// The `string()` conversion. A node-set converts to the string-value of
+    /// its first node.
+    pub fn to_string(&self, document: &Document) -> String {
+        match self {
+            Value::NodeSet(nodes) => nodes
+                .first()
+                .map_or(String::new(), |node| node.string_value(document)),
+            Value::Boolean(value) => value.to_string(),
+            Value::Number(value) => number_to_string(*value),
+            Value::String(value) => value.clone(),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Boolean(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Number(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_owned())
+    }
+}
+
+/// Converts a string to a number per XPath 1.0: optional whitespace, an
+/// optional minus sign, digits with an optional decimal point. Anything
+/// else is NaN.
+pub fn string_to_number(value: &str) -> f64 {
+    let trimmed = value.trim_matches(is_whitespace);
+    let digits = trimmed.strip_prefix('-').unwrap_or(trimmed);
+    let valid = !digits.is_empty()
+        && digits != "."
+        && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && digits.matches('.').count() <= 1;
+    if valid {
+        trimmed.parse().unwrap_or(f64::NAN)
+    } else {
+        f64::NAN
+    }
+}
+
+/// Formats a number per XPath 1.0: integers without a decimal point, no
+/// exponent notation, and `NaN`/`Infinity` spelled out.
+pub fn number_to_string(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_owned()
+    } else if value.is_infinite() {
+        if value > 0.0 { "Infinity" } else { "-Infinity" }.to_owned()
+    } else if value == 0.0 {
+        "0".to_owned()
+    } else {
+        // Rust never uses exponent notation when displaying floats.
+        value.to_string()
+    }
+}
@@ -0,0 +1,441 @@
+use document::chars::{is_name, is_name_char, is_ncname, is_xml_char};
+use document::namespace::XML_NAMESPACE;
+use document::node::QName;
+
+use crate::decimal::{Decimal, ParseError};
+use crate::error::{Error, Result};
+use crate::facets::WhiteSpace;
+use crate::temporal::{DateTime, DateTimeKind, Duration, DurationKind};
+use crate::value::{decode_base64, decode_hex, Value};
+
+/// The namespace of the built-in datatypes.
+pub const XSD_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema";
+
+/// Resolves the prefixes of `xs:QName` and `xs:NOTATION` values, or the
+/// default namespace for `None`.
+pub trait Namespaces {
+    fn namespace_uri(&self, prefix: Option<&str>) -> Option<String>;
+}
+
+impl<F> Namespaces for F
+where
+    F: Fn(Option<&str>) -> Option<String>,
+{
+    fn namespace_uri(&self, prefix: Option<&str>) -> Option<String> {
+        self(prefix)
+    }
+}
+
+/// The built-in simple types of XSD 1.1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Builtin {
+    AnySimpleType,
+    AnyAtomicType,
+    String,
+    NormalizedString,
+    Token,
+    Language,
+    NmToken,
+    NmTokens,
+    Name,
+    NcName,
+    Id,
+    IdRef,
+    IdRefs,
+    Entity,
+    Entities,
+    Boolean,
+    Decimal,
+    Integer,
+    NonPositiveInteger,
+    NegativeInteger,
+    Long,
+    Int,
+    Short,
+    Byte,
+    NonNegativeInteger,
+    UnsignedLong,
+    UnsignedInt,
+    UnsignedShort,
+    UnsignedByte,
+    PositiveInteger,
+    Float,
+    Double,
+    Duration,
+    YearMonthDuration,
+    DayTimeDuration,
+    DateTime,
+    DateTimeStamp,
+    Time,
+    Date,
+    GYearMonth,
+    GYear,
+    GMonthDay,
+    GDay,
+    GMonth,
+    HexBinary,
+    Base64Binary,
+    AnyUri,
+    QName,
+    Notation,
+}
+
+impl Builtin {
+    pub const ALL: [Builtin; 49] = [
+        Builtin::AnySimpleType,
+        Builtin::AnyAtomicType,
+        Builtin::String,
+        Builtin::NormalizedString,
+        Builtin::Token,
+        Builtin::Language,
+        Builtin::NmToken,
+        Builtin::NmTokens,
+        Builtin::Name,
+        Builtin::NcName,
+        Builtin::Id,
+        Builtin::IdRef,
+        Builtin::IdRefs,
+        Builtin::Entity,
+        Builtin::Entities,
+        Builtin::Boolean,
+        Builtin::Decimal,
+        Builtin::Integer,
+        Builtin::NonPositiveInteger,
+        Builtin::NegativeInteger,
+        Builtin::Long,
+        Builtin::Int,
+        Builtin::Short,
+        Builtin::Byte,
+        Builtin::NonNegativeInteger,
+        Builtin::UnsignedLong,
+        Builtin::UnsignedInt,
+        Builtin::UnsignedShort,
+        Builtin::UnsignedByte,
+        Builtin::PositiveInteger,
+        Builtin::Float,
+        Builtin::Double,
+        Builtin::Duration,
+        Builtin::YearMonthDuration,
+        Builtin::DayTimeDuration,
+        Builtin::DateTime,
+        Builtin::DateTimeStamp,
+        Builtin::Time,
+        Builtin::Date,
+        Builtin::GYearMonth,
+        Builtin::GYear,
+        Builtin::GMonthDay,
+        Builtin::GDay,
+        Builtin::GMonth,
+        Builtin::HexBinary,
+        Builtin::Base64Binary,
+        Builtin::AnyUri,
+        Builtin::QName,
+        Builtin::Notation,
+    ];
+
+    /// The local name of the datatype in [`XSD_NAMESPACE`].
+    pub fn name(self) -> &'static str {
+        match self {
+            Builtin::AnySimpleType => "anySimpleType",
+            Builtin::AnyAtomicType => "anyAtomicType",
+            Builtin::String => "string",
+            Builtin::NormalizedString => "normalizedString",
+            Builtin::Token => "token",
+            Builtin::Language => "language",
+            Builtin::NmToken => "NMTOKEN",
+            Builtin::NmTokens => "NMTOKENS",
+            Builtin::Name => "Name",
+            Builtin::NcName => "NCName",
+            Builtin::Id => "ID",
+            Builtin::IdRef => "IDREF",
+            Builtin::IdRefs => "IDREFS",
+            Builtin::Entity => "ENTITY",
+            Builtin::Entities => "ENTITIES",
+            Builtin::Boolean => "boolean",
+            Builtin::Decimal => "decimal",
+            Builtin::Integer => "integer",
+            Builtin::NonPositiveInteger => "nonPositiveInteger",
+            Builtin::NegativeInteger => "negativeInteger",
+            Builtin::Long => "long",
+            Builtin::Int => "int",
+            Builtin::Short => "short",
+            Builtin::Byte => "byte",
+            Builtin::NonNegativeInteger => "nonNegativeInteger",
+            Builtin::UnsignedLong => "unsignedLong",
+            Builtin::UnsignedInt => "unsignedInt",
+            Builtin::UnsignedShort => "unsignedShort",
+            Builtin::UnsignedByte => "unsignedByte",
+            Builtin::PositiveInteger => "positiveInteger",
+            Builtin::Float => "float",
+            Builtin::Double => "double",
+            Builtin::Duration => "duration",
+            Builtin::YearMonthDuration => "yearMonthDuration",
+            Builtin::DayTimeDuration => "dayTimeDuration",
+            Builtin::DateTime => "dateTime",
+            Builtin::DateTimeStamp => "dateTimeStamp",
+            Builtin::Time => "time",
+            Builtin::Date => "date",
+            Builtin::GYearMonth => "gYearMonth",
+            Builtin::GYear => "gYear",
+            Builtin::GMonthDay => "gMonthDay",
+            Builtin::GDay => "gDay",
+            Builtin::GMonth => "gMonth",
+            Builtin::HexBinary => "hexBinary",
+            Builtin::Base64Binary => "base64Binary",
+            Builtin::AnyUri => "anyURI",
+            Builtin::QName => "QName",
+            Builtin::Notation => "NOTATION",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Builtin> {
+        Builtin::ALL
+            .into_iter()
+            .find(|builtin| builtin.name() == name)
+    }
+
+    /// The type this one is derived from, `None` for `anySimpleType`.
+    pub fn base(self) -> Option<Builtin> {
+        let base = match self {
+            Builtin::AnySimpleType => return None,
+            Builtin::AnyAtomicType | Builtin::NmTokens | Builtin::IdRefs | Builtin::Entities => {
+                Builtin::AnySimpleType
+            }
+            Builtin::NormalizedString => Builtin::String,
+            Builtin::Token => Builtin::NormalizedString,
+            Builtin::Language | Builtin::NmToken | Builtin::Name => Builtin::Token,
+            Builtin::NcName => Builtin::Name,
+            Builtin::Id | Builtin::IdRef | Builtin::Entity => Builtin::NcName,
+            Builtin::Integer => Builtin::Decimal,
+            Builtin::NonPositiveInteger | Builtin::Long | Builtin::NonNegativeInteger => {
+                Builtin::Integer
+            }
+            Builtin::NegativeInteger => Builtin::NonPositiveInteger,
+            Builtin::Int => Builtin::Long,
+            Builtin::Short => Builtin::Int,
+            Builtin::Byte => Builtin::Short,
+            Builtin::UnsignedLong | Builtin::PositiveInteger => Builtin::NonNegativeInteger,
+            Builtin::UnsignedInt => Builtin::UnsignedLong,
+            Builtin::UnsignedShort => Builtin::UnsignedInt,
+            Builtin::UnsignedByte => Builtin::UnsignedShort,
+            Builtin::YearMonthDuration | Builtin::DayTimeDuration => Builtin::Duration,
+            Builtin::DateTimeStamp => Builtin::DateTime,
+            _ => Builtin::AnyAtomicType,
+        };
+        Some(base)
+    }
+
+    /// The primitive type this one is derived from, or the type itself for
+    /// the list types and the two special types.
+    pub fn primitive(self) -> Builtin {
+        match self.base() {
+            None | Some(Builtin::AnySimpleType | Builtin::AnyAtomicType) => self,
+            Some(base) => base.primitive(),
+        }
+    }
+
+    /// Whether this type is `other` or derived from it.
+    pub fn is_derived_from(self, other: Builtin) -> bool {
+        self == other || self.base().is_some_and(|base| base.is_derived_from(other))
+    }
+
+    /// The item type of `NMTOKENS`, `IDREFS` and `ENTITIES`.
+    pub fn item_type(self) -> Option<Builtin> {
+        match self {
+            Builtin::NmTokens => Some(Builtin::NmToken),
+            Builtin::IdRefs => Some(Builtin::IdRef),
+            Builtin::Entities => Some(Builtin::Entity),
+            _ => None,
+        }
+    }
+
+    pub fn white_space(self) -> WhiteSpace {
+        match self {
+            Builtin::AnySimpleType | Builtin::AnyAtomicType | Builtin::String => {
+                WhiteSpace::Preserve
+            }
+            Builtin::NormalizedString => WhiteSpace::Replace,
+            _ => WhiteSpace::Collapse,
+        }
+    }
+
+    /// The bounds of the integer types.
+    fn range(self) -> (Option<i128>, Option<i128>) {
+        match self {
+            Builtin::NonPositiveInteger => (None, Some(0)),
+            Builtin::NegativeInteger => (None, Some(-1)),
+            Builtin::Long => (Some(i64::MIN.into()), Some(i64::MAX.into())),
+            Builtin::Int => (Some(i32::MIN.into()), Some(i32::MAX.into())),
+            Builtin::Short => (Some(i16::MIN.into()), Some(i16::MAX.into())),
+            Builtin::Byte => (Some(i8::MIN.into()), Some(i8::MAX.into())),
+            Builtin::NonNegativeInteger => (Some(0), None),
+            Builtin::UnsignedLong => (Some(0), Some(u64::MAX.into())),
+            Builtin::UnsignedInt => (Some(0), Some(u32::MAX.into())),
+            Builtin::UnsignedShort => (Some(0), Some(u16::MAX.into())),
+            Builtin::UnsignedByte => (Some(0), Some(u8::MAX.into())),
+            Builtin::PositiveInteger => (Some(1), None),
+            _ => (None, None),
+        }
+    }
+
+    /// Reads a lexical form of an atomic type, whitespace already
+    /// normalized as [`Builtin::white_space`] says.
+    pub(crate) fn parse(self, lexical: &str, namespaces: &dyn Namespaces) -> Result<Value> {
+        let invalid = || Error::Lexical {
+            datatype: self.name().to_owned(),
+            value: lexical.to_owned(),
+        };
+        let numeric = |error| match error {
+            ParseError::Invalid => invalid(),
+            ParseError::Overflow => Error::Overflow {
+                datatype: self.name().to_owned(),
+                value: lexical.to_owned(),
+            },
+        };
+        let date_time = |kind| {
+            DateTime::parse(kind, lexical)
+                .map(Value::DateTime)
+                .ok_or_else(invalid)
+        };
+        let duration = |kind| {
+            Duration::parse(kind, lexical)
+                .map(Value::Duration)
+                .map_err(numeric)
+        };
+        let string = |valid: bool| match valid {
+            true => Ok(Value::String(lexical.to_owned())),
+            false => Err(invalid()),
+        };
+
+        if !lexical.chars().all(is_xml_char) {
+            return Err(invalid());
+        }
+        match self {
+            Builtin::AnySimpleType | Builtin::AnyAtomicType | Builtin::String => string(true),
+            Builtin::NormalizedString => string(!lexical.contains(['\t', '\n', '\r'])),
+            Builtin::Token => string(is_token(lexical)),
+            Builtin::Language => string(is_language(lexical)),
+            Builtin::NmToken => string(!lexical.is_empty() && lexical.chars().all(is_name_char)),
+            Builtin::Name => string(is_name(lexical)),
+            Builtin::NcName | Builtin::Id | Builtin::IdRef | Builtin::Entity => {
+                string(is_ncname(lexical))
+            }
+            Builtin::NmTokens | Builtin::IdRefs | Builtin::Entities => {
+                unreachable!("list types are parsed item by item")
+            }
+            Builtin::Boolean => match lexical {
+                "true" | "1" => Ok(Value::Boolean(true)),
+                "false" | "0" => Ok(Value::Boolean(false)),
+                _ => Err(invalid()),
+            },
+            Builtin::Decimal => Decimal::parse(lexical).map(Value::Decimal).map_err(numeric),
+            Builtin::Float if is_floating(lexical) => Ok(Value::Float(lexical.parse().unwrap())),
+            Builtin::Double if is_floating(lexical) => Ok(Value::Double(lexical.parse().unwrap())),
+            Builtin::Float | Builtin::Double => Err(invalid()),
+            Builtin::Duration => duration(DurationKind::Duration),
+            Builtin::YearMonthDuration => duration(DurationKind::YearMonth),
+            Builtin::DayTimeDuration => duration(DurationKind::DayTime),
+            Builtin::DateTime => date_time(DateTimeKind::DateTime),
+            Builtin::DateTimeStamp => match date_time(DateTimeKind::DateTime)? {
+                Value::DateTime(value) if value.timezone.is_none() => Err(invalid()),
+                value => Ok(value),
+            },
+            Builtin::Time => date_time(DateTimeKind::Time),
+            Builtin::Date => date_time(DateTimeKind::Date),
+            Builtin::GYearMonth => date_time(DateTimeKind::GYearMonth),
+            Builtin::GYear => date_time(DateTimeKind::GYear),
+            Builtin::GMonthDay => date_time(DateTimeKind::GMonthDay),
+            Builtin::GDay => date_time(DateTimeKind::GDay),
+            Builtin::GMonth => date_time(DateTimeKind::GMonth),
+            Builtin::HexBinary => decode_hex(lexical)
+                .map(Value::HexBinary)
+                .ok_or_else(invalid),
+            Builtin::Base64Binary => {
+                let compact: String = lexical.split(' ').collect();
+                decode_base64(&compact)
+                    .map(Value::Base64Binary)
+                    .ok_or_else(invalid)
+            }
+            Builtin::AnyUri => Ok(Value::AnyUri(lexical.to_owned())),
+            Builtin::QName => parse_qname(lexical, namespaces).map(Value::QName),
+            Builtin::Notation => parse_qname(lexical, namespaces).map(Value::Notation),
+            integer => {
+                let value = Decimal::parse_integer(lexical).map_err(numeric)?;
+                let (min, max) = integer.range();
+                let n = value.mantissa();
+                if min.is_some_and(|min| n < min) || max.is_some_and(|max| n > max) {
+                    return Err(numeric(ParseError::Overflow));
+                }
+                Ok(Value::Decimal(value))
+            }
+        }
+    }
+}
+
+/// No leading, trailing or doubled spaces, and no other whitespace.
+fn is_token(s: &str) -> bool {
+    !s.starts_with(' ') && !s.ends_with(' ') && !s.contains("  ") && !s.contains(['\t', '\n', '\r'])
+}
+
+/// `[a-zA-Z]{1,8}(-[a-zA-Z0-9]{1,8})*`.
+fn is_language(s: &str) -> bool {
+    s.split('-').enumerate().all(|(i, subtag)| {
+        (1..=8).contains(&subtag.len())
+            && subtag
+                .bytes()
+                .all(|b| b.is_ascii_alphabetic() || (i > 0 && b.is_ascii_digit()))
+    })
+}
+
+/// Whether `s` is in the lexical space shared by `xs:float` and
+/// `xs:double`, which unlike Rust's has no `inf` or `infinity` and spells
+/// out `INF` and `NaN`.
+fn is_floating(s: &str) -> bool {
+    let unsigned = s.strip_prefix(['+', '-']).unwrap_or(s);
+    match unsigned {
+        "INF" => return true,
+        "NaN" => return unsigned.len() == s.len(),
+        _ => {}
+    }
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (unsigned, None),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    let exponent_is_valid = exponent.is_none_or(|exponent| {
+        let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        !digits.is_empty() && is_digits(digits)
+    });
+    integer.len() + fraction.len() > 0
+        && is_digits(integer)
+        && is_digits(fraction)
+        && exponent_is_valid
+}
+
+fn parse_qname(lexical: &str, namespaces: &dyn Namespaces) -> Result<QName> {
+    let (prefix, local_name) = match lexical.split_once(':') {
+        Some((prefix, local_name)) => (Some(prefix), local_name),
+        None => (None, lexical),
+    };
+    if !is_ncname(local_name) || !prefix.is_none_or(is_ncname) {
+        return Err(Error::Lexical {
+            datatype: "QName".to_owned(),
+            value: lexical.to_owned(),
+        });
+    }
+    let namespace = match prefix {
+        Some("xml") => Some(XML_NAMESPACE.to_owned()),
+        Some(prefix) => Some(
+            namespaces
+                .namespace_uri(Some(prefix))
+                .ok_or_else(|| Error::UndeclaredPrefix(prefix.to_owned()))?,
+        ),
+        None => namespaces.namespace_uri(None),
+    };
+    Ok(QName {
+        prefix: prefix.map(str::to_owned),
+        local_name: local_name.to_owned(),
+        namespace: namespace.filter(|namespace| !namespace.is_empty()),
+    })
+}
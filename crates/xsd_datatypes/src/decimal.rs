@@ -0,0 +1,232 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+/// The most fractional digits a [`Decimal`] holds, and the most digits in
+/// all, which is what fits in an `i128`.
+pub const MAX_DIGITS: u32 = 38;
+
+/// Why a string could not be read as a [`Decimal`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// The string is not a decimal number.
+    Invalid,
+    /// The number has more than [`MAX_DIGITS`] significant digits.
+    Overflow,
+}
+
+/// An `xs:decimal` value, `mantissa × 10^-scale`. Trailing zeros are never
+/// kept in the fraction, so equal values have equal fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    pub const ZERO: Decimal = Decimal {
+        mantissa: 0,
+        scale: 0,
+    };
+
+    /// The value `mantissa × 10^-scale`, if the scale is at most
+    /// [`MAX_DIGITS`].
+    pub fn new(mantissa: i128, scale: u32) -> Option<Decimal> {
+        if scale > MAX_DIGITS {
+            return None;
+        }
+        let mut decimal = Decimal { mantissa, scale };
+        while decimal.scale > 0 && decimal.mantissa % 10 == 0 {
+            decimal.mantissa /= 10;
+            decimal.scale -= 1;
+        }
+        Some(decimal)
+    }
+
+    /// Reads the lexical form of `xs:decimal`: an optional sign, then
+    /// digits with an optional decimal point.
+    pub fn parse(s: &str) -> Result<Decimal, ParseError> {
+        let (negative, unsigned) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if integer.len() + fraction.len() == 0 || !is_digits(integer) || !is_digits(fraction) {
+            return Err(ParseError::Invalid);
+        }
+
+        let integer = integer.trim_start_matches('0');
+        let fraction = fraction.trim_end_matches('0');
+        if integer.len() + fraction.len() > MAX_DIGITS as usize {
+            return Err(ParseError::Overflow);
+        }
+        let mantissa = integer
+            .bytes()
+            .chain(fraction.bytes())
+            .fold(0i128, |mantissa, b| mantissa * 10 + i128::from(b - b'0'));
+        Ok(Decimal {
+            mantissa: if negative { -mantissa } else { mantissa },
+            scale: fraction.len() as u32,
+        })
+    }
+
+    /// Reads the lexical form of `xs:integer`, digits with an optional
+    /// sign.
+    pub fn parse_integer(s: &str) -> Result<Decimal, ParseError> {
+        if s.contains('.') {
+            return Err(ParseError::Invalid);
+        }
+        Decimal::parse(s)
+    }
+
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.scale == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.mantissa < 0
+    }
+
+    /// The digits the value needs, as counted by `xs:totalDigits`: those of
+    /// the mantissa, or of the fraction if it has leading zeros.
+    pub fn total_digits(&self) -> u32 {
+        let digits = self
+            .mantissa
+            .unsigned_abs()
+            .checked_ilog10()
+            .map_or(1, |log| log + 1);
+        digits.max(self.scale)
+    }
+
+    /// The digits after the decimal point, as counted by
+    /// `xs:fractionDigits`.
+    pub fn fraction_digits(&self) -> u32 {
+        self.scale
+    }
+
+    /// The largest integer not above the value.
+    pub fn floor(&self) -> i128 {
+        self.mantissa.div_euclid(pow10(self.scale))
+    }
+
+    pub fn checked_add(&self, other: &Decimal) -> Option<Decimal> {
+        let scale = self.scale.max(other.scale);
+        let a = self.mantissa.checked_mul(pow10(scale - self.scale))?;
+        let b = other.mantissa.checked_mul(pow10(scale - other.scale))?;
+        Decimal::new(a.checked_add(b)?, scale)
+    }
+
+    pub fn checked_sub(&self, other: &Decimal) -> Option<Decimal> {
+        self.checked_add(&-*other)
+    }
+}
+
+impl From<i128> for Decimal {
+    fn from(value: i128) -> Self {
+        Decimal {
+            mantissa: value,
+            scale: 0,
+        }
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Self {
+        Decimal::from(i128::from(value))
+    }
+}
+
+impl std::ops::Neg for Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal {
+            mantissa: -self.mantissa,
+            scale: self.scale,
+        }
+    }
+}
+
+impl Ord for Decimal {
+    /// Compares the integer parts, then the fractions scaled to the same
+    /// number of digits, which cannot overflow as aligning the whole
+    /// mantissas could.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        let (a, b) = (pow10(self.scale), pow10(other.scale));
+        (self.mantissa / a)
+            .cmp(&(other.mantissa / b))
+            .then_with(|| {
+                let x = (self.mantissa % a) * pow10(scale - self.scale);
+                let y = (other.mantissa % b) * pow10(scale - other.scale);
+                x.cmp(&y)
+            })
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Writes the canonical form: no decimal point for integers, otherwise at
+/// least one digit on each side of it.
+impl Display for Decimal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.scale == 0 {
+            return write!(f, "{}", self.mantissa);
+        }
+        let digits = format!(
+            "{:0width$}",
+            self.mantissa.unsigned_abs(),
+            width = self.scale as usize + 1
+        );
+        let (integer, fraction) = digits.split_at(digits.len() - self.scale as usize);
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        write!(f, "{}{}.{}", sign, integer, fraction)
+    }
+}
+
+fn pow10(exponent: u32) -> i128 {
+    10i128.pow(exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decimal::{Decimal, ParseError};
+
+    #[test]
+    fn reads_compares_and_writes_decimals() {
+        let parse = |s: &str| Decimal::parse(s).unwrap();
+        assert_eq!(parse("+007.50").to_string(), "7.5");
+        assert_eq!(parse("-.5").to_string(), "-0.5");
+        assert_eq!(parse("12.").to_string(), "12");
+        assert_eq!(parse("-0.000").to_string(), "0");
+        assert_eq!(parse("0.001").total_digits(), 3);
+        assert_eq!(parse("120.25").total_digits(), 5);
+        assert_eq!(Decimal::parse("."), Err(ParseError::Invalid));
+        assert_eq!(Decimal::parse("1e3"), Err(ParseError::Invalid));
+        assert_eq!(Decimal::parse_integer("1.0"), Err(ParseError::Invalid));
+        assert_eq!(Decimal::parse(&"9".repeat(39)), Err(ParseError::Overflow));
+
+        assert!(parse("-0.5") < parse("0.3"));
+        assert!(parse("-1.5") < parse("-1.25"));
+        assert!(parse("99999999999999999999999999999999999999") > parse("0.1"));
+        assert!(parse(&format!("1.{}1", "0".repeat(36))) > parse("1"));
+        assert_eq!(parse("-1.5").floor(), -2);
+        assert_eq!(
+            parse("0.25").checked_add(&parse("-1.75")),
+            Some(parse("-1.5"))
+        );
+    }
+}
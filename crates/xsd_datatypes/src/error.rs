@@ -0,0 +1,60 @@
+use std::fmt::{Display, Formatter};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// A string that is not in the lexical space of the datatype.
+    Lexical { datatype: String, value: String },
+    /// A lexical form whose value is too large or too precise to be held.
+    Overflow { datatype: String, value: String },
+    /// A QName or NOTATION value uses a prefix that is not declared.
+    UndeclaredPrefix(String),
+    /// A value that a constraining facet rules out. `facet` is the name of
+    /// the facet as written in a schema.
+    Facet {
+        facet: &'static str,
+        value: String,
+        message: String,
+    },
+    /// A facet the datatype does not take, or a facet value it cannot
+    /// hold.
+    InvalidFacet { facet: String, message: String },
+    /// A `pattern` facet that is not a regular expression of XML Schema.
+    Regex { pattern: String, message: String },
+    /// A facet this library does not evaluate.
+    UnsupportedFacet(String),
+    /// A name that is not a built-in datatype.
+    UnknownDatatype(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Lexical { datatype, value } => {
+                write!(f, "\"{}\" is not a valid value of `{}`", value, datatype)
+            }
+            Error::Overflow { datatype, value } => {
+                write!(f, "\"{}\" is out of range for `{}`", value, datatype)
+            }
+            Error::UndeclaredPrefix(prefix) => {
+                write!(f, "namespace prefix `{}` is not declared", prefix)
+            }
+            Error::Facet {
+                facet,
+                value,
+                message,
+            } => write!(f, "\"{}\" violates `{}`: {}", value, facet, message),
+            Error::InvalidFacet { facet, message } => {
+                write!(f, "invalid `{}` facet: {}", facet, message)
+            }
+            Error::Regex { pattern, message } => {
+                write!(f, "invalid regular expression \"{}\": {}", pattern, message)
+            }
+            Error::UnsupportedFacet(facet) => write!(f, "the `{}` facet is not supported", facet),
+            Error::UnknownDatatype(name) => write!(f, "`{}` is not a built-in datatype", name),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
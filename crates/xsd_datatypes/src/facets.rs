@@ -0,0 +1,233 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+use document::chars::is_whitespace;
+
+use crate::error::{Error, Result};
+use crate::regex::Regex;
+use crate::value::Value;
+
+/// How whitespace in a lexical form is normalized before it is read. Later
+/// variants are stricter, and a restriction may only make it stricter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WhiteSpace {
+    Preserve,
+    /// Tabs, line feeds and carriage returns become spaces.
+    Replace,
+    /// As `Replace`, then runs of spaces become one and leading and
+    /// trailing spaces are removed.
+    Collapse,
+}
+
+impl WhiteSpace {
+    pub fn from_name(name: &str) -> Option<WhiteSpace> {
+        match name {
+            "preserve" => Some(WhiteSpace::Preserve),
+            "replace" => Some(WhiteSpace::Replace),
+            "collapse" => Some(WhiteSpace::Collapse),
+            _ => None,
+        }
+    }
+
+    pub fn normalize<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        match self {
+            WhiteSpace::Preserve => Cow::Borrowed(s),
+            WhiteSpace::Replace if !s.contains(['\t', '\n', '\r']) => Cow::Borrowed(s),
+            WhiteSpace::Replace => Cow::Owned(s.replace(['\t', '\n', '\r'], " ")),
+            WhiteSpace::Collapse => {
+                let collapsed = s
+                    .split(is_whitespace)
+                    .filter(|token| !token.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                match collapsed == s {
+                    true => Cow::Borrowed(s),
+                    false => Cow::Owned(collapsed),
+                }
+            }
+        }
+    }
+}
+
+/// Whether date and time values must have a timezone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExplicitTimezone {
+    Required,
+    Prohibited,
+    Optional,
+}
+
+impl ExplicitTimezone {
+    pub fn from_name(name: &str) -> Option<ExplicitTimezone> {
+        match name {
+            "required" => Some(ExplicitTimezone::Required),
+            "prohibited" => Some(ExplicitTimezone::Prohibited),
+            "optional" => Some(ExplicitTimezone::Optional),
+            _ => None,
+        }
+    }
+}
+
+/// The constraining facets in effect for a simple type, those of its base
+/// types included.
+#[derive(Debug, Clone, Default)]
+pub struct Facets {
+    pub length: Option<u64>,
+    pub min_length: Option<u64>,
+    pub max_length: Option<u64>,
+    pub enumeration: Option<Vec<Value>>,
+    /// The `pattern` facets of each derivation step. A lexical form must
+    /// match one pattern of every step.
+    pub patterns: Vec<Vec<Regex>>,
+    /// `None` for unions, whose members each normalize their own way.
+    pub white_space: Option<WhiteSpace>,
+    pub max_inclusive: Option<Value>,
+    pub max_exclusive: Option<Value>,
+    pub min_inclusive: Option<Value>,
+    pub min_exclusive: Option<Value>,
+    pub total_digits: Option<u32>,
+    pub fraction_digits: Option<u32>,
+    pub explicit_timezone: Option<ExplicitTimezone>,
+}
+
+impl Facets {
+    /// Checks a lexical form, its whitespace already normalized, against
+    /// the `pattern` facets.
+    pub fn check_patterns(&self, lexical: &str) -> Result<()> {
+        for step in &self.patterns {
+            if !step.iter().any(|regex| regex.is_match(lexical)) {
+                let patterns: Vec<_> = step.iter().map(Regex::as_str).collect();
+                return Err(Error::Facet {
+                    facet: "pattern",
+                    value: lexical.to_owned(),
+                    message: format!("does not match {}", patterns.join(" | ")),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks `value` against every facet that applies to it.
+    pub fn check(&self, value: &Value) -> Result<()> {
+        let violation = |facet, message: String| Error::Facet {
+            facet,
+            value: value.to_string(),
+            message,
+        };
+
+        if let Some(length) = length(value) {
+            if let Some(expected) = self.length.filter(|&expected| expected != length) {
+                return Err(violation(
+                    "length",
+                    format!("has length {} instead of {}", length, expected),
+                ));
+            }
+            if let Some(min) = self.min_length.filter(|&min| length < min) {
+                return Err(violation(
+                    "minLength",
+                    format!("has length {}, less than {}", length, min),
+                ));
+            }
+            if let Some(max) = self.max_length.filter(|&max| length > max) {
+                return Err(violation(
+                    "maxLength",
+                    format!("has length {}, more than {}", length, max),
+                ));
+            }
+        }
+
+        if let Some(enumeration) = &self.enumeration {
+            if !enumeration
+                .iter()
+                .any(|allowed| allowed == value || allowed.identical(value))
+            {
+                return Err(violation(
+                    "enumeration",
+                    "is not one of the enumerated values".to_owned(),
+                ));
+            }
+        }
+
+        let bounds = [
+            (
+                "maxInclusive",
+                &self.max_inclusive,
+                [Ordering::Less, Ordering::Equal],
+            ),
+            (
+                "maxExclusive",
+                &self.max_exclusive,
+                [Ordering::Less, Ordering::Less],
+            ),
+            (
+                "minInclusive",
+                &self.min_inclusive,
+                [Ordering::Greater, Ordering::Equal],
+            ),
+            (
+                "minExclusive",
+                &self.min_exclusive,
+                [Ordering::Greater, Ordering::Greater],
+            ),
+        ];
+        for (facet, bound, allowed) in bounds {
+            let Some(bound) = bound else { continue };
+            if !value
+                .partial_cmp(bound)
+                .is_some_and(|ordering| allowed.contains(&ordering))
+            {
+                return Err(violation(
+                    facet,
+                    format!("is not within the bound {}", bound),
+                ));
+            }
+        }
+
+        if let Value::Decimal(decimal) = value {
+            if let Some(total) = self
+                .total_digits
+                .filter(|&total| decimal.total_digits() > total)
+            {
+                return Err(violation(
+                    "totalDigits",
+                    format!("has more than {} digits", total),
+                ));
+            }
+            if let Some(fraction) = self
+                .fraction_digits
+                .filter(|&fraction| decimal.fraction_digits() > fraction)
+            {
+                return Err(violation(
+                    "fractionDigits",
+                    format!("has more than {} fractional digits", fraction),
+                ));
+            }
+        }
+
+        if let Value::DateTime(date_time) = value {
+            match (self.explicit_timezone, date_time.timezone) {
+                (Some(ExplicitTimezone::Required), None) => {
+                    return Err(violation("explicitTimezone", "has no timezone".to_owned()));
+                }
+                (Some(ExplicitTimezone::Prohibited), Some(_)) => {
+                    return Err(violation("explicitTimezone", "has a timezone".to_owned()));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The length the length facets constrain: characters, octets or list
+/// items. QName and NOTATION values have none, as XSD 1.1 lets any length
+/// facet on them pass.
+fn length(value: &Value) -> Option<u64> {
+    let length = match value {
+        Value::String(s) | Value::AnyUri(s) => s.chars().count(),
+        Value::HexBinary(octets) | Value::Base64Binary(octets) => octets.len(),
+        Value::List(items) => items.len(),
+        _ => return None,
+    };
+    Some(length as u64)
+}
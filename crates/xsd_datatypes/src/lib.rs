@@ -0,0 +1,230 @@
+//! The built-in datatypes of XML Schema 1.1: their lexical and value
+//! spaces, canonical representations, equality and order, and the facets
+//! that restrict them.
+
+pub use builtin::{Builtin, Namespaces, XSD_NAMESPACE};
+pub use decimal::Decimal;
+pub use error::{Error, Result};
+pub use facets::{ExplicitTimezone, Facets, WhiteSpace};
+pub use regex::Regex;
+pub use simple_type::{SimpleType, Variety};
+pub use temporal::{DateTime, DateTimeKind, Duration, DurationKind};
+pub use value::Value;
+
+mod builtin;
+pub mod decimal;
+mod error;
+mod facets;
+pub mod regex;
+mod simple_type;
+pub mod temporal;
+pub mod value;
+
+/// The URI RELAX NG grammars use to refer to this library.
+pub const DATATYPE_LIBRARY: &str = "http://www.w3.org/2001/XMLSchema-datatypes";
+
+#[cfg(test)]
+mod tests {
+    use crate::{Builtin, Error, Namespaces, SimpleType, Value};
+
+    fn example_namespaces() -> impl Namespaces {
+        |prefix: Option<&str>| prefix.filter(|&p| p == "ex").map(|_| "urn:ex".to_owned())
+    }
+
+    fn facets(facets: &[(&str, &str)]) -> Vec<(String, String)> {
+        facets
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn reads_built_in_types() -> Result<(), anyhow::Error> {
+        let namespaces = example_namespaces();
+        let parse =
+            |name: &str, lexical: &str| SimpleType::from_name(name)?.parse(lexical, &namespaces);
+
+        assert_eq!(
+            parse("token", "  a \n b ")?,
+            Value::String("a b".to_owned())
+        );
+        assert_eq!(
+            parse("normalizedString", "a\tb")?,
+            Value::String("a b".to_owned())
+        );
+        assert_eq!(parse("boolean", "1")?, Value::Boolean(true));
+        assert_eq!(parse("int", " -12 ")?.to_string(), "-12");
+        assert!(matches!(parse("byte", "128"), Err(Error::Overflow { .. })));
+        assert!(matches!(
+            parse("positiveInteger", "0"),
+            Err(Error::Overflow { .. })
+        ));
+        assert!(matches!(
+            parse("integer", "1.0"),
+            Err(Error::Lexical { .. })
+        ));
+        assert_eq!(parse("double", "1e2")?.to_string(), "1.0E2");
+        assert!(parse("double", "inf").is_err());
+        assert!(parse("float", "-NaN").is_err());
+        assert_eq!(parse("language", "en-GB")?.to_string(), "en-GB");
+        assert!(parse("language", "en-").is_err());
+        assert!(parse("NCName", "a:b").is_err());
+        assert_eq!(
+            parse("NMTOKENS", " a  b ")?,
+            Value::List(vec![
+                Value::String("a".to_owned()),
+                Value::String("b".to_owned())
+            ])
+        );
+        assert!(parse("IDREFS", " ").is_err());
+        assert_eq!(parse("hexBinary", "0fb7")?.to_string(), "0FB7");
+        assert_eq!(parse("base64Binary", "TW Fu")?.to_string(), "TWFu");
+        assert_eq!(
+            parse("dateTimeStamp", "2000-01-01T00:00:00+01:00")?.to_string(),
+            "2000-01-01T00:00:00+01:00"
+        );
+        assert!(parse("dateTimeStamp", "2000-01-01T00:00:00").is_err());
+        assert!(parse("yearMonthDuration", "P1D").is_err());
+
+        let Value::QName(name) = parse("QName", "ex:item")? else {
+            panic!("expected a QName");
+        };
+        assert!(name.is(Some("urn:ex"), "item"));
+        assert_eq!(parse("QName", "item")?, parse("QName", "item")?);
+        assert_eq!(
+            parse("QName", "other:item"),
+            Err(Error::UndeclaredPrefix("other".to_owned()))
+        );
+        assert!(matches!(
+            SimpleType::from_name("date-time"),
+            Err(Error::UnknownDatatype(_))
+        ));
+
+        assert_eq!(Builtin::UnsignedByte.primitive(), Builtin::Decimal);
+        assert!(Builtin::Id.is_derived_from(Builtin::Token));
+        assert_eq!(Builtin::from_name("NOTATION"), Some(Builtin::Notation));
+        Ok(())
+    }
+
+    #[test]
+    fn restricts_with_facets() -> Result<(), anyhow::Error> {
+        let namespaces = example_namespaces();
+        let restrict = |name: &str, list: &[(&str, &str)]| {
+            SimpleType::from_name(name)?.restrict(&facets(list), &namespaces)
+        };
+
+        let percent = restrict(
+            "decimal",
+            &[
+                ("minInclusive", "0"),
+                ("maxExclusive", "100"),
+                ("fractionDigits", "1"),
+            ],
+        )?;
+        assert!(percent.parse("99.5", &namespaces).is_ok());
+        assert!(matches!(
+            percent.parse("100", &namespaces),
+            Err(Error::Facet {
+                facet: "maxExclusive",
+                ..
+            })
+        ));
+        assert!(matches!(
+            percent.parse("0.25", &namespaces),
+            Err(Error::Facet {
+                facet: "fractionDigits",
+                ..
+            })
+        ));
+        let digits = percent.restrict(&facets(&[("totalDigits", "2")]), &namespaces)?;
+        assert!(matches!(
+            digits.parse("10.5", &namespaces),
+            Err(Error::Facet {
+                facet: "totalDigits",
+                ..
+            })
+        ));
+
+        let code = restrict("token", &[("minLength", "2"), ("maxLength", "3")])?;
+        assert!(code.parse(" ab ", &namespaces).is_ok());
+        assert!(code.parse("abcd", &namespaces).is_err());
+        let sizes = restrict("token", &[("enumeration", "S"), ("enumeration", "M")])?;
+        assert!(sizes.parse(" M ", &namespaces).is_ok());
+        assert!(matches!(
+            sizes.parse("L", &namespaces),
+            Err(Error::Facet {
+                facet: "enumeration",
+                ..
+            })
+        ));
+        assert!(sizes
+            .restrict(&facets(&[("enumeration", "L")]), &namespaces)
+            .is_err());
+
+        let year = restrict(
+            "gYear",
+            &[("explicitTimezone", "prohibited"), ("minInclusive", "2000")],
+        )?;
+        assert!(year.parse("2001", &namespaces).is_ok());
+        assert!(year.parse("2001Z", &namespaces).is_err());
+        assert!(year.parse("1999", &namespaces).is_err());
+
+        let list = SimpleType::list(SimpleType::from_name("int")?)
+            .restrict(&facets(&[("length", "2")]), &namespaces)?;
+        assert!(list.parse("1 2", &namespaces).is_ok());
+        assert!(list.parse("1 2 3", &namespaces).is_err());
+        let union = SimpleType::union(vec![
+            SimpleType::from_name("int")?,
+            SimpleType::from_name("date")?,
+        ]);
+        assert_eq!(
+            union.parse("2000-01-01", &namespaces)?.to_string(),
+            "2000-01-01"
+        );
+        assert!(union.parse("x", &namespaces).is_err());
+
+        assert!(matches!(
+            restrict("boolean", &[("length", "1")]),
+            Err(Error::InvalidFacet { .. })
+        ));
+        assert!(matches!(
+            restrict("token", &[("whiteSpace", "preserve")]),
+            Err(Error::InvalidFacet { .. })
+        ));
+        assert!(matches!(
+            restrict("int", &[("maxInclusive", "x")]),
+            Err(Error::InvalidFacet { .. })
+        ));
+        assert_eq!(
+            restrict("string", &[("assertion", "$value != ''")]).err(),
+            Some(Error::UnsupportedFacet("assertion".to_owned()))
+        );
+        assert!(matches!(
+            restrict("string", &[("pattern", "[a-z")]),
+            Err(Error::Regex { .. })
+        ));
+
+        // Patterns of one step are alternatives; those of each step apply.
+        let letters = restrict("token", &[("pattern", "[a-z]+"), ("pattern", "[A-Z]+")])?;
+        assert!(letters.parse(" abc ", &namespaces).is_ok());
+        assert!(letters.parse("ABC", &namespaces).is_ok());
+        assert!(matches!(
+            letters.parse("aBC", &namespaces),
+            Err(Error::Facet {
+                facet: "pattern",
+                ..
+            })
+        ));
+        let short = letters.restrict(&facets(&[("pattern", ".{1,2}")]), &namespaces)?;
+        assert!(short.parse("AB", &namespaces).is_ok());
+        assert!(short.parse("ABC", &namespaces).is_err());
+        let codes = SimpleType::list(SimpleType::from_name("int")?)
+            .restrict(&facets(&[("pattern", "\\d+( \\d+)*")]), &namespaces)?;
+        assert!(codes.parse(" 1\n 22 ", &namespaces).is_ok());
+        assert!(codes.parse("1 -2", &namespaces).is_err());
+
+        let none = SimpleType::from_name("yearMonthDuration")?;
+        assert_eq!(none.canonical(&none.parse("P0Y", &namespaces)?), "P0M");
+        Ok(())
+    }
+}
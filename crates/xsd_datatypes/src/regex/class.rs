@@ -0,0 +1,164 @@
+use std::sync::OnceLock;
+
+use document::chars::{is_name_char, is_name_start_char};
+use unicode_blocks::{find_unicode_block, UnicodeBlock};
+use unicode_general_category::get_general_category;
+
+/// The general categories `\p{..}` may name, one letter for all the
+/// categories starting with it.
+const CATEGORIES: [&str; 36] = [
+    "L", "Lu", "Ll", "Lt", "Lm", "Lo", "M", "Mn", "Mc", "Me", "N", "Nd", "Nl", "No", "P", "Pc",
+    "Pd", "Ps", "Pe", "Pi", "Pf", "Po", "Z", "Zs", "Zl", "Zp", "S", "Sm", "Sc", "Sk", "So", "C",
+    "Cc", "Cf", "Co", "Cn",
+];
+
+/// Block names of XML Schema 1.0 that Unicode has since renamed or split.
+const BLOCK_ALIASES: [(&str, &[&str]); 4] = [
+    ("Greek", &["GreekandCoptic"]),
+    (
+        "CombiningMarksforSymbols",
+        &["CombiningDiacriticalMarksforSymbols"],
+    ),
+    ("CyrillicSupplementary", &["CyrillicSupplement"]),
+    (
+        "PrivateUse",
+        &[
+            "PrivateUseArea",
+            "SupplementaryPrivateUseArea-A",
+            "SupplementaryPrivateUseArea-B",
+        ],
+    ),
+];
+
+/// Blocks made of surrogate code points, which no character is in.
+const SURROGATE_BLOCKS: [&str; 3] = [
+    "HighSurrogates",
+    "HighPrivateUseSurrogates",
+    "LowSurrogates",
+];
+
+/// The characters one position of a regular expression matches.
+#[derive(Debug, Clone)]
+pub(crate) enum CharClass {
+    Ranges(Vec<(char, char)>),
+    /// A general category, or all of those starting with a letter such as
+    /// `L`.
+    Category(&'static str),
+    /// `\i`.
+    NameStart,
+    /// `\c`.
+    Name,
+    Union(Vec<CharClass>),
+    Complement(Box<CharClass>),
+    Subtraction(Box<CharClass>, Box<CharClass>),
+}
+
+impl CharClass {
+    pub(crate) fn char(c: char) -> CharClass {
+        CharClass::Ranges(vec![(c, c)])
+    }
+
+    /// The character the class is made of, if it is a single one.
+    pub(crate) fn as_char(&self) -> Option<char> {
+        match self {
+            CharClass::Ranges(ranges) => match ranges[..] {
+                [(start, end)] if start == end => Some(start),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// `.`: anything but a line feed or carriage return.
+    pub(crate) fn wildcard() -> CharClass {
+        CharClass::Complement(Box::new(CharClass::Ranges(vec![
+            ('\n', '\n'),
+            ('\r', '\r'),
+        ])))
+    }
+
+    /// The class of a multi-character escape such as `\d`, named by the
+    /// letter after the backslash.
+    pub(crate) fn escape(letter: char) -> Option<CharClass> {
+        let class = match letter.to_ascii_lowercase() {
+            's' => CharClass::Ranges(vec![(' ', ' '), ('\t', '\t'), ('\n', '\n'), ('\r', '\r')]),
+            'i' => CharClass::NameStart,
+            'c' => CharClass::Name,
+            'd' => CharClass::Category("Nd"),
+            'w' => CharClass::Complement(Box::new(CharClass::Union(vec![
+                CharClass::Category("P"),
+                CharClass::Category("Z"),
+                CharClass::Category("C"),
+            ]))),
+            _ => return None,
+        };
+        match letter.is_ascii_uppercase() {
+            true => Some(CharClass::Complement(Box::new(class))),
+            false => Some(class),
+        }
+    }
+
+    /// The class `\p{name}` stands for: a general category, or a block
+    /// such as `IsBasicLatin`.
+    pub(crate) fn property(name: &str) -> Option<CharClass> {
+        let Some(block) = name.strip_prefix("Is") else {
+            return CATEGORIES
+                .iter()
+                .find(|&&category| category == name)
+                .map(|&category| CharClass::Category(category));
+        };
+        if SURROGATE_BLOCKS.contains(&block) {
+            return Some(CharClass::Ranges(Vec::new()));
+        }
+        let block = [block];
+        let names = BLOCK_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == block[0])
+            .map_or(&block[..], |(_, names)| names);
+        let ranges: Vec<_> = blocks()
+            .iter()
+            .filter(|b| names.contains(&b.name().replace(' ', "").as_str()))
+            .filter_map(|b| Some((char::from_u32(b.start())?, char::from_u32(b.end())?)))
+            .collect();
+        (!ranges.is_empty()).then_some(CharClass::Ranges(ranges))
+    }
+
+    pub(crate) fn contains(&self, c: char) -> bool {
+        match self {
+            CharClass::Ranges(ranges) => ranges.iter().any(|&(start, end)| start <= c && c <= end),
+            CharClass::Category(name) => {
+                let category = get_general_category(c).abbreviation();
+                match name.len() {
+                    1 => category.starts_with(name),
+                    _ => category == *name,
+                }
+            }
+            CharClass::NameStart => is_name_start_char(c),
+            CharClass::Name => is_name_char(c),
+            CharClass::Union(classes) => classes.iter().any(|class| class.contains(c)),
+            CharClass::Complement(class) => !class.contains(c),
+            CharClass::Subtraction(class, subtracted) => {
+                class.contains(c) && !subtracted.contains(c)
+            }
+        }
+    }
+}
+
+/// Every Unicode block, found once by walking the code points.
+fn blocks() -> &'static [UnicodeBlock] {
+    static BLOCKS: OnceLock<Vec<UnicodeBlock>> = OnceLock::new();
+    BLOCKS.get_or_init(|| {
+        let mut blocks = Vec::new();
+        let mut code_point = 0;
+        while code_point <= u32::from(char::MAX) {
+            match char::from_u32(code_point).and_then(find_unicode_block) {
+                Some(block) => {
+                    blocks.push(block);
+                    code_point = block.end() + 1;
+                }
+                None => code_point += 1,
+            }
+        }
+        blocks
+    })
+}
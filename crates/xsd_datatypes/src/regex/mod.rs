@@ -0,0 +1,136 @@
+//! The regular expressions of the `pattern` facet. They differ from those
+//! of Perl and its descendants: an expression always matches the whole
+//! string, `^` and `$` are ordinary characters, `\i` and `\c` match the
+//! characters of XML names, `\p{IsBasicLatin}` names a Unicode block and
+//! `[a-z-[aeiou]]` subtracts one class from another.
+
+use std::fmt::{Display, Formatter};
+
+use crate::error::{Error, Result};
+
+mod class;
+mod parser;
+mod program;
+
+/// A compiled regular expression of XML Schema.
+#[derive(Debug, Clone)]
+pub struct Regex {
+    pattern: String,
+    program: program::Program,
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex> {
+        let invalid = |message| Error::Regex {
+            pattern: pattern.to_owned(),
+            message,
+        };
+        let (node, classes) = parser::parse(pattern).map_err(invalid)?;
+        let program = program::Program::compile(&node, classes).map_err(invalid)?;
+        Ok(Regex {
+            pattern: pattern.to_owned(),
+            program,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Whether the expression matches all of `s`.
+    pub fn is_match(&self, s: &str) -> bool {
+        self.program.is_match(s)
+    }
+}
+
+impl Display for Regex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::Regex;
+    use crate::Error;
+
+    fn matches(pattern: &str, s: &str) -> bool {
+        Regex::new(pattern)
+            .unwrap_or_else(|e| panic!("{}", e))
+            .is_match(s)
+    }
+
+    #[test]
+    fn matches_whole_strings() {
+        assert!(matches("[a-z]+", "abc"));
+        assert!(!matches("[a-z]+", "abc1"));
+        assert!(!matches("b", "abc"));
+        assert!(matches("^a$", "^a$"));
+        assert!(matches("", ""));
+        assert!(matches("a|", ""));
+        assert!(matches("(ab|c)*d?", "abcab"));
+        assert!(matches("\\d{3}-\\d{2,}", "123-4567"));
+        assert!(!matches("\\d{3}-\\d{2,4}", "123-45678"));
+        assert!(matches("a{0}", ""));
+        assert!(matches(".+", "a b"));
+        assert!(!matches(".", "\n"));
+        assert!(matches(
+            "(a*)*b",
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaab"
+        ));
+        assert!(!matches("(a*)*b", &"a".repeat(10_000)));
+    }
+
+    #[test]
+    fn matches_classes_and_escapes() {
+        assert!(matches("[a-z-[aeiou]]+", "xyz"));
+        assert!(!matches("[a-z-[aeiou]]+", "xaz"));
+        assert!(matches("[^a-c-[x]]", "d"));
+        assert!(!matches("[^a-c-[x]]", "x"));
+        assert!(matches("[-a]+", "-a"));
+        assert!(matches("[a-]+", "-a"));
+        assert!(matches("[\\-\\[\\]]+", "-[]"));
+        assert!(matches("\\i\\c*", "_x.1-y:z"));
+        assert!(!matches("\\i\\c*", "1x"));
+        assert!(matches("\\s\\S\\w\\W", " a\u{e9}!"));
+        assert!(matches("\\p{Lu}\\p{Ll}+\\P{L}", "Ab\u{e9}5"));
+        assert!(matches("\\p{IsBasicLatin}+", "abc"));
+        assert!(!matches("\\p{IsBasicLatin}", "\u{e9}"));
+        assert!(matches(
+            "\\p{IsLatin-1Supplement}\\p{IsGreek}",
+            "\u{e9}\u{3b1}"
+        ));
+        assert!(matches("\\d", "\u{0663}"));
+        assert!(matches("\\n\\t\\|", "\n\t|"));
+    }
+
+    #[test]
+    fn rejects_what_xml_schema_does_not_allow() {
+        for pattern in [
+            "a**",
+            "(a",
+            "a)",
+            "[a",
+            "[]",
+            "[a-]b]",
+            "[z-a]",
+            "a{2,1}",
+            "a{,2}",
+            "\\q",
+            "\\p{Foo}",
+            "\\p{IsFoo}",
+            "[a-z-[aeiou]x]",
+            "[a[b]",
+            "[a-c-e]",
+            "(?:a)",
+            "{",
+            "]",
+        ] {
+            assert!(
+                matches!(Regex::new(pattern), Err(Error::Regex { .. })),
+                "{}",
+                pattern
+            );
+        }
+    }
+}
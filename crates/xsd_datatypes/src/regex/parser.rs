@@ -0,0 +1,286 @@
+use crate::regex::class::CharClass;
+
+/// A parsed regular expression. Character classes are kept apart and
+/// referred to by index, so repeating a class does not copy it.
+#[derive(Debug)]
+pub(crate) enum Node {
+    Empty,
+    Class(usize),
+    Concatenation(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repetition {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+    },
+}
+
+/// Reads a regular expression, giving its tree and the character classes
+/// the tree refers to, or what is wrong with it.
+pub(crate) fn parse(pattern: &str) -> Result<(Node, Vec<CharClass>), String> {
+    let mut parser = Parser {
+        chars: pattern.chars().collect(),
+        position: 0,
+        classes: Vec::new(),
+    };
+    let node = parser.regex()?;
+    match parser.next() {
+        None => Ok((node, parser.classes)),
+        Some(c) => Err(format!(
+            "unexpected `{}` at offset {}",
+            c,
+            parser.position - 1
+        )),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    classes: Vec<CharClass>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.position + offset).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn class(&mut self, class: CharClass) -> Node {
+        self.classes.push(class);
+        Node::Class(self.classes.len() - 1)
+    }
+
+    /// `branch ('|' branch)*`.
+    fn regex(&mut self) -> Result<Node, String> {
+        let mut branches = vec![self.branch()?];
+        while self.eat('|') {
+            branches.push(self.branch()?);
+        }
+        Ok(match branches.len() {
+            1 => branches.remove(0),
+            _ => Node::Alternation(branches),
+        })
+    }
+
+    /// `piece*`, up to the end of the branch or group.
+    fn branch(&mut self) -> Result<Node, String> {
+        let mut pieces = Vec::new();
+        while !matches!(self.peek(), None | Some('|' | ')')) {
+            pieces.push(self.piece()?);
+        }
+        Ok(match pieces.len() {
+            0 => Node::Empty,
+            1 => pieces.remove(0),
+            _ => Node::Concatenation(pieces),
+        })
+    }
+
+    /// An atom and its quantifier, if any.
+    fn piece(&mut self) -> Result<Node, String> {
+        let atom = self.atom()?;
+        let (min, max) = if self.eat('?') {
+            (0, Some(1))
+        } else if self.eat('*') {
+            (0, None)
+        } else if self.eat('+') {
+            (1, None)
+        } else if self.eat('{') {
+            self.quantity()?
+        } else {
+            return Ok(atom);
+        };
+        Ok(Node::Repetition {
+            node: Box::new(atom),
+            min,
+            max,
+        })
+    }
+
+    /// `{n}`, `{n,}` or `{n,m}`, after the brace.
+    fn quantity(&mut self) -> Result<(u32, Option<u32>), String> {
+        let min = self
+            .number()?
+            .ok_or_else(|| "expected a number after `{`".to_owned())?;
+        let max = match self.eat(',') {
+            true => self.number()?,
+            false => Some(min),
+        };
+        if !self.eat('}') {
+            return Err("expected `}` to close the quantifier".to_owned());
+        }
+        if max.is_some_and(|max| max < min) {
+            return Err(format!(
+                "the quantifier {{{},{}}} is out of order",
+                min,
+                max.unwrap_or(0)
+            ));
+        }
+        Ok((min, max))
+    }
+
+    fn number(&mut self) -> Result<Option<u32>, String> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Ok(None);
+        }
+        let digits: String = self.chars[start..self.position].iter().collect();
+        digits
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("the quantity {} is too large", digits))
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        let position = self.position;
+        match self.next().expect("an atom") {
+            '(' => {
+                let node = self.regex()?;
+                match self.eat(')') {
+                    true => Ok(node),
+                    false => Err(format!("the group at offset {} is not closed", position)),
+                }
+            }
+            '[' => {
+                let class = self.class_expression()?;
+                Ok(self.class(class))
+            }
+            '.' => Ok(self.class(CharClass::wildcard())),
+            '\\' => {
+                let class = self.escape()?;
+                Ok(self.class(class))
+            }
+            c @ ('?' | '*' | '+' | '{' | '}' | ']') => {
+                Err(format!("unexpected `{}` at offset {}", c, position))
+            }
+            c => Ok(self.class(CharClass::char(c))),
+        }
+    }
+
+    /// What follows a backslash: a single character escape such as `\n`
+    /// or `\|`, a multi-character escape such as `\d`, or a category or
+    /// block escape such as `\p{Lu}`.
+    fn escape(&mut self) -> Result<CharClass, String> {
+        let c = self
+            .next()
+            .ok_or_else(|| "the expression ends with a backslash".to_owned())?;
+        match c {
+            'n' => Ok(CharClass::char('\n')),
+            'r' => Ok(CharClass::char('\r')),
+            't' => Ok(CharClass::char('\t')),
+            '\\' | '|' | '.' | '-' | '^' | '?' | '*' | '+' | '{' | '}' | '(' | ')' | '[' | ']' => {
+                Ok(CharClass::char(c))
+            }
+            'p' | 'P' => {
+                if !self.eat('{') {
+                    return Err(format!("expected `{{` after `\\{}`", c));
+                }
+                let start = self.position;
+                while self.peek().is_some_and(|c| c != '}') {
+                    self.position += 1;
+                }
+                let name: String = self.chars[start..self.position].iter().collect();
+                if !self.eat('}') {
+                    return Err(format!("`\\{}{{{}` is not closed", c, name));
+                }
+                let class = CharClass::property(&name)
+                    .ok_or_else(|| format!("unknown category or block `{}`", name))?;
+                Ok(match c {
+                    'P' => CharClass::Complement(Box::new(class)),
+                    _ => class,
+                })
+            }
+            _ => CharClass::escape(c).ok_or_else(|| format!("unknown escape `\\{}`", c)),
+        }
+    }
+
+    /// A character class expression, after its `[`: a group of characters,
+    /// ranges and escapes, negated by a leading `^`, from which another
+    /// class expression may be subtracted.
+    fn class_expression(&mut self) -> Result<CharClass, String> {
+        let negated = self.eat('^');
+        let mut items = Vec::new();
+        let subtracted = loop {
+            match (self.peek(), self.peek_at(1)) {
+                (None, _) => return Err("a character class is not closed".to_owned()),
+                (Some(']'), _) if !items.is_empty() => {
+                    self.position += 1;
+                    break None;
+                }
+                (Some(']'), _) => return Err("empty character class".to_owned()),
+                (Some('-'), Some('[')) if !items.is_empty() => {
+                    self.position += 2;
+                    let subtracted = self.class_expression()?;
+                    if !self.eat(']') {
+                        return Err("expected `]` after a class subtraction".to_owned());
+                    }
+                    break Some(subtracted);
+                }
+                _ => {}
+            }
+
+            let start = self.class_char(items.is_empty())?;
+            let range_start = start.as_char().filter(|_| {
+                self.peek() == Some('-') && !matches!(self.peek_at(1), Some(']' | '['))
+            });
+            let Some(first) = range_start else {
+                items.push(start);
+                continue;
+            };
+            self.position += 1;
+            let last = self
+                .class_char(false)?
+                .as_char()
+                .ok_or_else(|| format!("a range from `{}` must end with a character", first))?;
+            if last < first {
+                return Err(format!("the range `{}-{}` is out of order", first, last));
+            }
+            items.push(CharClass::Ranges(vec![(first, last)]));
+        };
+
+        let mut class = match items.len() {
+            1 => items.remove(0),
+            _ => CharClass::Union(items),
+        };
+        if negated {
+            class = CharClass::Complement(Box::new(class));
+        }
+        Ok(match subtracted {
+            Some(subtracted) => CharClass::Subtraction(Box::new(class), Box::new(subtracted)),
+            None => class,
+        })
+    }
+
+    /// A character or escape in a class. `-` stands for itself only first
+    /// or last in the group.
+    fn class_char(&mut self, first: bool) -> Result<CharClass, String> {
+        match self.next() {
+            None => Err("a character class is not closed".to_owned()),
+            Some('\\') => self.escape(),
+            Some('[') => Err("`[` must be escaped in a character class".to_owned()),
+            Some('-') if first || self.peek() == Some(']') => Ok(CharClass::char('-')),
+            Some('-') => Err("`-` must be escaped inside a character class".to_owned()),
+            Some(c) => Ok(CharClass::char(c)),
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::regex::class::CharClass;
+use crate::regex::parser::Node;
+
+/// The most instructions a compiled expression may have, which bounded
+/// quantifiers such as `{1000}` multiply.
+const MAX_INSTRUCTIONS: usize = 100_000;
+
+#[derive(Debug, Clone, Copy)]
+enum Instruction {
+    /// Consumes a character of the class with this index.
+    Class(usize),
+    /// Continues at both targets.
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+/// A regular expression compiled to a nondeterministic automaton, which
+/// is run over the input in one pass, following every path at once, so
+/// matching takes time linear in the input whatever the expression.
+#[derive(Debug, Clone)]
+pub(crate) struct Program {
+    instructions: Vec<Instruction>,
+    classes: Vec<CharClass>,
+}
+
+impl Program {
+    pub(crate) fn compile(node: &Node, classes: Vec<CharClass>) -> Result<Program, String> {
+        let mut program = Program {
+            instructions: Vec::new(),
+            classes,
+        };
+        program.emit(node)?;
+        program.push(Instruction::Match)?;
+        Ok(program)
+    }
+
+    fn push(&mut self, instruction: Instruction) -> Result<usize, String> {
+        if self.instructions.len() == MAX_INSTRUCTIONS {
+            return Err("the expression is too large".to_owned());
+        }
+        self.instructions.push(instruction);
+        Ok(self.instructions.len() - 1)
+    }
+
+    fn emit(&mut self, node: &Node) -> Result<(), String> {
+        match node {
+            Node::Empty => {}
+            Node::Class(class) => {
+                self.push(Instruction::Class(*class))?;
+            }
+            Node::Concatenation(nodes) => {
+                for node in nodes {
+                    self.emit(node)?;
+                }
+            }
+            Node::Alternation(branches) => {
+                let mut jumps = Vec::new();
+                let (last, rest) = branches.split_last().expect("two branches");
+                for branch in rest {
+                    let split = self.push(Instruction::Split(0, 0))?;
+                    self.emit(branch)?;
+                    jumps.push(self.push(Instruction::Jump(0))?);
+                    self.instructions[split] =
+                        Instruction::Split(split + 1, self.instructions.len());
+                }
+                self.emit(last)?;
+                let end = self.instructions.len();
+                for jump in jumps {
+                    self.instructions[jump] = Instruction::Jump(end);
+                }
+            }
+            Node::Repetition { node, .. } if matches!(**node, Node::Empty) => {}
+            Node::Repetition { node, min, max } => {
+                for _ in 0..*min {
+                    self.emit(node)?;
+                }
+                let mut splits = Vec::new();
+                match max {
+                    None => {
+                        let split = self.push(Instruction::Split(0, 0))?;
+                        self.emit(node)?;
+                        self.push(Instruction::Jump(split))?;
+                        splits.push(split);
+                    }
+                    Some(max) => {
+                        for _ in *min..*max {
+                            splits.push(self.push(Instruction::Split(0, 0))?);
+                            self.emit(node)?;
+                        }
+                    }
+                }
+                let end = self.instructions.len();
+                for split in splits {
+                    self.instructions[split] = Instruction::Split(split + 1, end);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the automaton accepts the whole of `s`.
+    pub(crate) fn is_match(&self, s: &str) -> bool {
+        let mut current = Threads::new(self.instructions.len());
+        let mut next = Threads::new(self.instructions.len());
+        self.add(&mut current, 0);
+        for c in s.chars() {
+            if current.list.is_empty() {
+                return false;
+            }
+            next.clear();
+            for &pc in &current.list {
+                if let Instruction::Class(class) = self.instructions[pc] {
+                    if self.classes[class].contains(c) {
+                        self.add(&mut next, pc + 1);
+                    }
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+        }
+        current
+            .list
+            .iter()
+            .any(|&pc| matches!(self.instructions[pc], Instruction::Match))
+    }
+
+    /// Adds the thread at `pc` and every thread reachable from it without
+    /// consuming a character.
+    fn add(&self, threads: &mut Threads, pc: usize) {
+        let mut stack = vec![pc];
+        while let Some(pc) = stack.pop() {
+            if threads.seen[pc] {
+                continue;
+            }
+            threads.seen[pc] = true;
+            match self.instructions[pc] {
+                Instruction::Jump(target) => stack.push(target),
+                Instruction::Split(first, second) => {
+                    stack.push(second);
+                    stack.push(first);
+                }
+                Instruction::Class(_) | Instruction::Match => threads.list.push(pc),
+            }
+        }
+    }
+}
+
+/// The instructions live at one position of the input.
+struct Threads {
+    list: Vec<usize>,
+    seen: Vec<bool>,
+}
+
+impl Threads {
+    fn new(size: usize) -> Threads {
+        Threads {
+            list: Vec::with_capacity(size),
+            seen: vec![false; size],
+        }
+    }
+
+    fn clear(&mut self) {
+        self.list.clear();
+        self.seen.fill(false);
+    }
+}
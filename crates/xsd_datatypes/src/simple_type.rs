@@ -0,0 +1,244 @@
+use std::borrow::Cow;
+
+use crate::builtin::{Builtin, Namespaces};
+use crate::error::{Error, Result};
+use crate::facets::{ExplicitTimezone, Facets, WhiteSpace};
+use crate::regex::Regex;
+use crate::value::Value;
+
+/// How the values of a simple type are made up.
+#[derive(Debug, Clone)]
+pub enum Variety {
+    Atomic(Builtin),
+    /// Whitespace-separated items of the item type.
+    List(Box<SimpleType>),
+    /// A value of the first member type that accepts the lexical form.
+    Union(Vec<SimpleType>),
+}
+
+/// A simple type: a built-in type, or a list, union or restriction made
+/// from them, with the facets that constrain it.
+#[derive(Debug, Clone)]
+pub struct SimpleType {
+    pub variety: Variety,
+    pub facets: Facets,
+}
+
+impl SimpleType {
+    pub fn builtin(builtin: Builtin) -> SimpleType {
+        match builtin.item_type() {
+            Some(item_type) => {
+                let mut list = SimpleType::list(SimpleType::builtin(item_type));
+                list.facets.min_length = Some(1);
+                list
+            }
+            None => SimpleType {
+                variety: Variety::Atomic(builtin),
+                facets: Facets {
+                    white_space: Some(builtin.white_space()),
+                    ..Facets::default()
+                },
+            },
+        }
+    }
+
+    /// The built-in type with the local name `name`.
+    pub fn from_name(name: &str) -> Result<SimpleType> {
+        Builtin::from_name(name)
+            .map(SimpleType::builtin)
+            .ok_or_else(|| Error::UnknownDatatype(name.to_owned()))
+    }
+
+    pub fn list(item_type: SimpleType) -> SimpleType {
+        SimpleType {
+            variety: Variety::List(Box::new(item_type)),
+            facets: Facets {
+                white_space: Some(WhiteSpace::Collapse),
+                ..Facets::default()
+            },
+        }
+    }
+
+    pub fn union(member_types: Vec<SimpleType>) -> SimpleType {
+        SimpleType {
+            variety: Variety::Union(member_types),
+            facets: Facets::default(),
+        }
+    }
+
+    /// Derives a type by restriction, with the facets given by name and
+    /// lexical value as in a schema. The enumerations of one restriction
+    /// together make up its value space; bounds and enumerated values are
+    /// read as values of this type.
+    pub fn restrict(
+        &self,
+        facets: &[(String, String)],
+        namespaces: &dyn Namespaces,
+    ) -> Result<SimpleType> {
+        let mut restricted = self.clone();
+        let mut enumeration = Vec::new();
+        let mut patterns = Vec::new();
+        for (name, value) in facets {
+            let invalid = |message: String| Error::InvalidFacet {
+                facet: name.clone(),
+                message,
+            };
+            if !self.takes_facet(name) {
+                return Err(invalid("the base type does not take it".to_owned()));
+            }
+            let count = || {
+                value
+                    .parse::<u64>()
+                    .map_err(|_| invalid(format!("\"{}\" is not a non-negative integer", value)))
+            };
+            let digits =
+                || u32::try_from(count()?).map_err(|_| invalid(format!("{} is too large", value)));
+            let bound = || {
+                self.value(value, namespaces)
+                    .map(Some)
+                    .map_err(|error| invalid(error.to_string()))
+            };
+
+            let facets = &mut restricted.facets;
+            match name.as_str() {
+                "length" => facets.length = Some(count()?),
+                "minLength" => facets.min_length = Some(count()?),
+                "maxLength" => facets.max_length = Some(count()?),
+                "pattern" => patterns.push(Regex::new(value)?),
+                "enumeration" => enumeration.push(
+                    self.parse(value, namespaces)
+                        .map_err(|error| invalid(error.to_string()))?,
+                ),
+                "whiteSpace" => {
+                    let white_space = WhiteSpace::from_name(value)
+                        .ok_or_else(|| invalid(format!("unknown value \"{}\"", value)))?;
+                    if self.facets.white_space > Some(white_space) {
+                        return Err(invalid(format!(
+                            "the base type does not allow \"{}\"",
+                            value
+                        )));
+                    }
+                    facets.white_space = Some(white_space);
+                }
+                "maxInclusive" => facets.max_inclusive = bound()?,
+                "maxExclusive" => facets.max_exclusive = bound()?,
+                "minInclusive" => facets.min_inclusive = bound()?,
+                "minExclusive" => facets.min_exclusive = bound()?,
+                "totalDigits" => match digits()? {
+                    0 => return Err(invalid("must be positive".to_owned())),
+                    total => facets.total_digits = Some(total),
+                },
+                "fractionDigits" => facets.fraction_digits = Some(digits()?),
+                "explicitTimezone" => {
+                    facets.explicit_timezone = Some(
+                        ExplicitTimezone::from_name(value)
+                            .ok_or_else(|| invalid(format!("unknown value \"{}\"", value)))?,
+                    )
+                }
+                _ => return Err(Error::UnsupportedFacet(name.clone())),
+            }
+        }
+        if !enumeration.is_empty() {
+            restricted.facets.enumeration = Some(enumeration);
+        }
+        if !patterns.is_empty() {
+            restricted.facets.patterns.push(patterns);
+        }
+        if let (Some(min), Some(max)) = (restricted.facets.min_length, restricted.facets.max_length)
+        {
+            if min > max {
+                return Err(Error::InvalidFacet {
+                    facet: "minLength".to_owned(),
+                    message: format!("{} is more than the maxLength {}", min, max),
+                });
+            }
+        }
+        Ok(restricted)
+    }
+
+    /// Whether a restriction of this type may constrain it with the facet
+    /// `name`.
+    fn takes_facet(&self, name: &str) -> bool {
+        let length = matches!(name, "length" | "minLength" | "maxLength");
+        let common = matches!(name, "pattern" | "enumeration" | "whiteSpace" | "assertion");
+        let bounds = matches!(
+            name,
+            "maxInclusive" | "maxExclusive" | "minInclusive" | "minExclusive"
+        );
+        match &self.variety {
+            Variety::Union(_) => matches!(name, "pattern" | "enumeration" | "assertion"),
+            Variety::List(_) => length || common,
+            Variety::Atomic(builtin) => match builtin.primitive() {
+                Builtin::AnySimpleType | Builtin::AnyAtomicType => false,
+                Builtin::String
+                | Builtin::AnyUri
+                | Builtin::HexBinary
+                | Builtin::Base64Binary
+                | Builtin::QName
+                | Builtin::Notation => length || common,
+                Builtin::Boolean => matches!(name, "pattern" | "whiteSpace" | "assertion"),
+                Builtin::Decimal => {
+                    common || bounds || matches!(name, "totalDigits" | "fractionDigits")
+                }
+                Builtin::Float | Builtin::Double | Builtin::Duration => common || bounds,
+                _ => common || bounds || name == "explicitTimezone",
+            },
+        }
+    }
+
+    /// Reads a lexical form, normalizing its whitespace first, and checks
+    /// it and its value against the facets.
+    pub fn parse(&self, lexical: &str, namespaces: &dyn Namespaces) -> Result<Value> {
+        let normalized = self.normalize(lexical);
+        self.facets.check_patterns(&normalized)?;
+        let value = self.value(&normalized, namespaces)?;
+        self.facets.check(&value)?;
+        Ok(value)
+    }
+
+    fn normalize<'a>(&self, lexical: &'a str) -> Cow<'a, str> {
+        match self.facets.white_space {
+            Some(white_space) => white_space.normalize(lexical),
+            None => Cow::Borrowed(lexical),
+        }
+    }
+
+    /// Reads a lexical form without checking the facets of this type.
+    fn value(&self, lexical: &str, namespaces: &dyn Namespaces) -> Result<Value> {
+        let normalized = self.normalize(lexical);
+        match &self.variety {
+            Variety::Atomic(builtin) => builtin.parse(&normalized, namespaces),
+            Variety::List(item_type) => normalized
+                .split(' ')
+                .filter(|item| !item.is_empty())
+                .map(|item| item_type.parse(item, namespaces))
+                .collect::<Result<_>>()
+                .map(Value::List),
+            Variety::Union(member_types) => {
+                let mut error = None;
+                for member_type in member_types {
+                    match member_type.parse(&normalized, namespaces) {
+                        Ok(value) => return Ok(value),
+                        Err(e) => error = Some(e),
+                    }
+                }
+                Err(error.unwrap_or_else(|| Error::Lexical {
+                    datatype: "union".to_owned(),
+                    value: lexical.to_owned(),
+                }))
+            }
+        }
+    }
+
+    /// The canonical representation of a value of this type.
+    pub fn canonical(&self, value: &Value) -> String {
+        match (&self.variety, value) {
+            (Variety::Atomic(builtin), Value::Duration(duration))
+                if builtin.is_derived_from(Builtin::YearMonthDuration) && duration.is_zero() =>
+            {
+                "P0M".to_owned()
+            }
+            _ => value.to_string(),
+        }
+    }
+}
@@ -0,0 +1,643 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+use crate::decimal::{Decimal, ParseError};
+
+const SECONDS_PER_DAY: i128 = 86_400;
+
+/// How far a timezone may be from UTC, in seconds.
+const MAX_TIMEZONE_SECONDS: i128 = 14 * 3_600;
+
+/// The date and time datatypes, which each have their own value space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DateTimeKind {
+    DateTime,
+    Time,
+    Date,
+    GYearMonth,
+    GYear,
+    GMonthDay,
+    GDay,
+    GMonth,
+}
+
+/// A value of one of the date and time datatypes, in the seven-property
+/// model of XSD 1.1: the properties a datatype lacks are `None`. Years are
+/// numbered astronomically, so year 0 is 1 BCE. The derived `PartialEq`
+/// tells identical values apart; [`DateTime::partial_cmp`] compares them
+/// on the timeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DateTime {
+    pub kind: DateTimeKind,
+    pub year: Option<i64>,
+    pub month: Option<u8>,
+    pub day: Option<u8>,
+    pub hour: Option<u8>,
+    pub minute: Option<u8>,
+    pub second: Option<Decimal>,
+    /// The offset from UTC in minutes, if the value has a timezone.
+    pub timezone: Option<i16>,
+}
+
+/// A value of `xs:duration`: months and seconds, which have the same sign.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Duration {
+    pub months: i64,
+    pub seconds: Decimal,
+}
+
+/// Which designators a duration may use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationKind {
+    Duration,
+    YearMonth,
+    DayTime,
+}
+
+impl DateTime {
+    /// Reads the lexical form of the datatype `kind`. Gives `None` unless
+    /// the whole string matches it and names a day that exists.
+    pub fn parse(kind: DateTimeKind, s: &str) -> Option<DateTime> {
+        let mut cursor = Cursor {
+            input: s,
+            offset: 0,
+        };
+        let mut value = DateTime {
+            kind,
+            year: None,
+            month: None,
+            day: None,
+            hour: None,
+            minute: None,
+            second: None,
+            timezone: None,
+        };
+        use DateTimeKind::{Date, GDay, GMonth, GMonthDay, GYear, GYearMonth, Time};
+        match kind {
+            DateTimeKind::DateTime | Date | GYearMonth | GYear => {
+                value.year = Some(cursor.year()?);
+                if kind != GYear {
+                    cursor.expect("-")?;
+                    value.month = Some(cursor.number(2, 1, 12)?);
+                }
+                if kind == DateTimeKind::DateTime || kind == Date {
+                    cursor.expect("-")?;
+                    value.day = Some(cursor.number(2, 1, 31)?);
+                }
+            }
+            GMonthDay | GMonth => {
+                cursor.expect("--")?;
+                value.month = Some(cursor.number(2, 1, 12)?);
+                if kind == GMonthDay {
+                    cursor.expect("-")?;
+                    value.day = Some(cursor.number(2, 1, 31)?);
+                }
+            }
+            GDay => {
+                cursor.expect("---")?;
+                value.day = Some(cursor.number(2, 1, 31)?);
+            }
+            Time => {}
+        }
+        if kind == DateTimeKind::DateTime {
+            cursor.expect("T")?;
+        }
+        if kind == DateTimeKind::DateTime || kind == Time {
+            value.hour = Some(cursor.number(2, 0, 24)?);
+            cursor.expect(":")?;
+            value.minute = Some(cursor.number(2, 0, 59)?);
+            cursor.expect(":")?;
+            value.second = Some(cursor.seconds()?);
+        }
+        value.timezone = cursor.timezone()?;
+        if !cursor.rest().is_empty() {
+            return None;
+        }
+
+        if let (Some(month), Some(day)) = (value.month, value.day) {
+            // A leap year, for the datatypes without one.
+            if day > days_in_month(value.year.unwrap_or(2000), month) {
+                return None;
+            }
+        }
+        if value.hour == Some(24) {
+            if value.minute != Some(0) || value.second != Some(Decimal::ZERO) {
+                return None;
+            }
+            value.hour = Some(0);
+            if kind == DateTimeKind::DateTime {
+                value.next_day()?;
+            }
+        }
+        Some(value)
+    }
+
+    /// The seconds from 0001-01-01T00:00:00Z, with the properties the
+    /// value lacks taken from 1972-12-31T00:00:00 and a missing timezone
+    /// taken as UTC.
+    fn timeline(&self) -> Decimal {
+        let days = days_from_civil(
+            self.year.unwrap_or(1972),
+            self.month.unwrap_or(12),
+            self.day.unwrap_or(31),
+        );
+        let seconds = days * SECONDS_PER_DAY
+            + i128::from(self.hour.unwrap_or(0)) * 3_600
+            + i128::from(self.minute.unwrap_or(0)) * 60
+            - i128::from(self.timezone.unwrap_or(0)) * 60;
+        Decimal::from(seconds)
+            .checked_add(&self.second.unwrap_or(Decimal::ZERO))
+            .expect("seconds within range")
+    }
+
+    /// Orders values of the same datatype on the timeline. A value without
+    /// a timezone could be anywhere within 14 hours of its UTC reading, so
+    /// it is only ordered against a value with one outside that window.
+    pub fn partial_cmp(&self, other: &DateTime) -> Option<Ordering> {
+        if self.kind != other.kind {
+            return None;
+        }
+        let (a, b) = (self.timeline(), other.timeline());
+        match (self.timezone, other.timezone) {
+            (Some(_), Some(_)) | (None, None) => Some(a.cmp(&b)),
+            (Some(_), None) => compare_with_floating(a, b),
+            (None, Some(_)) => compare_with_floating(b, a).map(Ordering::reverse),
+        }
+    }
+
+    /// Whether the values stand for the same point on the timeline. Values
+    /// with a timezone never equal values without one.
+    pub fn equal(&self, other: &DateTime) -> bool {
+        self.kind == other.kind
+            && self.timezone.is_some() == other.timezone.is_some()
+            && self.timeline() == other.timeline()
+    }
+
+    fn next_day(&mut self) -> Option<()> {
+        let (year, month, day) = (self.year?, self.month?, self.day?);
+        let (year, month, day) = civil_from_days(days_from_civil(year, month, day) + 1);
+        self.year = Some(i64::try_from(year).ok()?);
+        self.month = Some(month);
+        self.day = Some(day);
+        Some(())
+    }
+}
+
+/// Writes the canonical form, with `Z` for UTC and no trailing zeros in
+/// the seconds.
+impl Display for DateTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            DateTimeKind::GMonthDay | DateTimeKind::GMonth => f.write_str("--")?,
+            DateTimeKind::GDay => f.write_str("---")?,
+            _ => {}
+        }
+        if let Some(year) = self.year {
+            let sign = if year < 0 { "-" } else { "" };
+            write!(f, "{}{:04}", sign, year.unsigned_abs())?;
+        }
+        if let Some(month) = self.month {
+            if self.year.is_some() {
+                f.write_str("-")?;
+            }
+            write!(f, "{:02}", month)?;
+        }
+        if let Some(day) = self.day {
+            if self.month.is_some() {
+                f.write_str("-")?;
+            }
+            write!(f, "{:02}", day)?;
+        }
+        if let (Some(hour), Some(minute), Some(second)) = (self.hour, self.minute, self.second) {
+            if self.kind == DateTimeKind::DateTime {
+                f.write_str("T")?;
+            }
+            let padding = if second < Decimal::from(10i64) {
+                "0"
+            } else {
+                ""
+            };
+            write!(f, "{:02}:{:02}:{}{}", hour, minute, padding, second)?;
+        }
+        match self.timezone {
+            None => Ok(()),
+            Some(0) => f.write_str("Z"),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                write!(f, "{}{:02}:{:02}", sign, offset / 60, offset % 60)
+            }
+        }
+    }
+}
+
+fn compare_with_floating(fixed: Decimal, floating: Decimal) -> Option<Ordering> {
+    let window = Decimal::from(MAX_TIMEZONE_SECONDS);
+    if fixed < floating.checked_sub(&window)? {
+        Some(Ordering::Less)
+    } else if fixed > floating.checked_add(&window)? {
+        Some(Ordering::Greater)
+    } else {
+        None
+    }
+}
+
+impl Duration {
+    /// Reads the lexical form of a duration that may use the designators
+    /// of `kind`.
+    pub fn parse(kind: DurationKind, s: &str) -> Result<Duration, ParseError> {
+        let (negative, rest) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let rest = rest.strip_prefix('P').ok_or(ParseError::Invalid)?;
+        let (date, time) = match rest.split_once('T') {
+            Some((_, "")) => return Err(ParseError::Invalid),
+            Some((date, time)) => (date, Some(time)),
+            None => (rest, None),
+        };
+        if date.is_empty() && time.is_none() {
+            return Err(ParseError::Invalid);
+        }
+
+        let mut months = 0i64;
+        let mut seconds = Decimal::ZERO;
+        let overflow = || ParseError::Overflow;
+        for (number, designator) in components(date, "YMD")? {
+            let number = number.parse::<i64>().map_err(|_| overflow())?;
+            match designator {
+                'Y' if kind != DurationKind::DayTime => {
+                    let years = number.checked_mul(12).ok_or_else(overflow)?;
+                    months = months.checked_add(years).ok_or_else(overflow)?;
+                }
+                'M' if kind != DurationKind::DayTime => {
+                    months = months.checked_add(number).ok_or_else(overflow)?;
+                }
+                'D' if kind != DurationKind::YearMonth => {
+                    let days = Decimal::from(i128::from(number) * SECONDS_PER_DAY);
+                    seconds = seconds.checked_add(&days).ok_or_else(overflow)?;
+                }
+                _ => return Err(ParseError::Invalid),
+            }
+        }
+        if let Some(time) = time {
+            if kind == DurationKind::YearMonth {
+                return Err(ParseError::Invalid);
+            }
+            for (number, designator) in components(time, "HMS")? {
+                let number = match designator {
+                    'S' => Decimal::parse(number)?,
+                    _ if number.contains('.') => return Err(ParseError::Invalid),
+                    _ => Decimal::parse_integer(number)?,
+                };
+                let factor = match designator {
+                    'H' => 3_600,
+                    'M' => 60,
+                    _ => 1,
+                };
+                let part = Decimal::new(
+                    number.mantissa().checked_mul(factor).ok_or_else(overflow)?,
+                    number.scale(),
+                )
+                .ok_or_else(overflow)?;
+                seconds = seconds.checked_add(&part).ok_or_else(overflow)?;
+            }
+        }
+        if negative {
+            months = -months;
+            seconds = -seconds;
+        }
+        Ok(Duration { months, seconds })
+    }
+
+    /// Orders durations by adding them to four dateTimes chosen to show
+    /// every way months can differ in length. Durations that order those
+    /// differently, such as `P1M` and `P30D`, are incomparable.
+    pub fn partial_cmp(&self, other: &Duration) -> Option<Ordering> {
+        const REFERENCES: [(i64, u8, u8); 4] =
+            [(1696, 9, 1), (1697, 2, 1), (1903, 3, 1), (1903, 7, 1)];
+        let mut orderings = REFERENCES.iter().map(|&reference| {
+            let a = self.added_to(reference)?;
+            let b = other.added_to(reference)?;
+            Some(a.cmp(&b))
+        });
+        let first = orderings.next()??;
+        for ordering in orderings {
+            if ordering? != first {
+                return None;
+            }
+        }
+        Some(first)
+    }
+
+    /// The timeline of `year-month-day` plus the duration, with the day
+    /// pinned to the end of shorter months.
+    fn added_to(&self, (year, month, day): (i64, u8, u8)) -> Option<Decimal> {
+        let months = i128::from(month - 1) + i128::from(self.months);
+        let year = i64::try_from(i128::from(year) + months.div_euclid(12)).ok()?;
+        let month = months.rem_euclid(12) as u8 + 1;
+        let day = day.min(days_in_month(year, month));
+        Decimal::from(days_from_civil(year, month, day) * SECONDS_PER_DAY)
+            .checked_add(&self.seconds)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.months == 0 && self.seconds == Decimal::ZERO
+    }
+}
+
+/// Writes the canonical form, which leaves out zero components and gives
+/// the zero duration as `PT0S`.
+impl Display for Duration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return f.write_str("PT0S");
+        }
+        if self.months < 0 || self.seconds.is_negative() {
+            f.write_str("-")?;
+        }
+        f.write_str("P")?;
+        let months = self.months.unsigned_abs();
+        if months >= 12 {
+            write!(f, "{}Y", months / 12)?;
+        }
+        if !months.is_multiple_of(12) {
+            write!(f, "{}M", months % 12)?;
+        }
+
+        let seconds = if self.seconds.is_negative() {
+            -self.seconds
+        } else {
+            self.seconds
+        };
+        let whole = seconds.floor();
+        let fraction = seconds
+            .checked_sub(&Decimal::from(whole))
+            .expect("fraction within range");
+        let days = whole / SECONDS_PER_DAY;
+        if days > 0 {
+            write!(f, "{}D", days)?;
+        }
+        let (hours, minutes) = (whole % SECONDS_PER_DAY / 3_600, whole % 3_600 / 60);
+        let rest = Decimal::from(whole % 60)
+            .checked_add(&fraction)
+            .expect("seconds within range");
+        if hours > 0 || minutes > 0 || rest != Decimal::ZERO {
+            f.write_str("T")?;
+        }
+        if hours > 0 {
+            write!(f, "{}H", hours)?;
+        }
+        if minutes > 0 {
+            write!(f, "{}M", minutes)?;
+        }
+        if rest != Decimal::ZERO {
+            write!(f, "{}S", rest)?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits duration components such as `1Y2M` into their numbers and
+/// designators, which must come in the order of `designators`, each at
+/// most once.
+fn components<'a>(s: &'a str, designators: &str) -> Result<Vec<(&'a str, char)>, ParseError> {
+    let mut components = Vec::new();
+    let mut allowed = designators;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .ok_or(ParseError::Invalid)?;
+        let designator = rest[end..].chars().next().expect("a designator");
+        let position = allowed.find(designator).ok_or(ParseError::Invalid)?;
+        if end == 0 {
+            return Err(ParseError::Invalid);
+        }
+        components.push((&rest[..end], designator));
+        allowed = &allowed[position + 1..];
+        rest = &rest[end + 1..];
+    }
+    Ok(components)
+}
+
+/// Reads date and time fields, keeping track of the offset.
+struct Cursor<'a> {
+    input: &'a str,
+    offset: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.offset..]
+    }
+
+    fn expect(&mut self, s: &str) -> Option<()> {
+        self.rest().starts_with(s).then(|| self.offset += s.len())
+    }
+
+    fn digits(&mut self) -> &'a str {
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        self.offset += end;
+        &rest[..end]
+    }
+
+    /// Exactly `width` digits, between `min` and `max`.
+    fn number(&mut self, width: usize, min: u8, max: u8) -> Option<u8> {
+        let digits = self.rest().get(..width)?;
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        self.offset += width;
+        let number = digits.parse().ok()?;
+        (min..=max).contains(&number).then_some(number)
+    }
+
+    /// At least four digits, without leading zeros beyond that.
+    fn year(&mut self) -> Option<i64> {
+        let negative = self.expect("-").is_some();
+        let digits = self.digits();
+        if digits.len() < 4 || (digits.len() > 4 && digits.starts_with('0')) {
+            return None;
+        }
+        let year = digits.parse::<i64>().ok()?;
+        Some(if negative { -year } else { year })
+    }
+
+    fn seconds(&mut self) -> Option<Decimal> {
+        let whole = self.number(2, 0, 59)?;
+        let mut seconds = Decimal::from(i64::from(whole));
+        if self.expect(".").is_some() {
+            let fraction = self.digits();
+            if fraction.is_empty() {
+                return None;
+            }
+            let fraction = Decimal::parse(&format!("0.{}", fraction)).ok()?;
+            seconds = seconds.checked_add(&fraction)?;
+        }
+        Some(seconds)
+    }
+
+    /// An optional timezone, `Z` or `±hh:mm` within 14 hours of UTC. The
+    /// outer `None` means a malformed one.
+    fn timezone(&mut self) -> Option<Option<i16>> {
+        if self.rest().is_empty() {
+            return Some(None);
+        }
+        if self.expect("Z").is_some() {
+            return Some(Some(0));
+        }
+        let negative = match self.rest().as_bytes()[0] {
+            b'+' => false,
+            b'-' => true,
+            _ => return None,
+        };
+        self.offset += 1;
+        let hours = self.number(2, 0, 14)?;
+        self.expect(":")?;
+        let minutes = self.number(2, 0, 59)?;
+        if hours == 14 && minutes > 0 {
+            return None;
+        }
+        let offset = i16::from(hours) * 60 + i16::from(minutes);
+        Some(Some(if negative { -offset } else { offset }))
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// The days from 1970-01-01 in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: u8, day: u8) -> i128 {
+    let year = i128::from(year) - i128::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i128::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i128::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The inverse of [`days_from_civil`].
+fn civil_from_days(days: i128) -> (i128, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u8;
+    let year = year_of_era + era * 400 + i128::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use crate::temporal::{DateTime, DateTimeKind, Duration, DurationKind};
+
+    #[test]
+    fn reads_and_orders_dates_and_times() {
+        let parse = |kind, s| DateTime::parse(kind, s).unwrap_or_else(|| panic!("{}", s));
+        let date_time = |s| parse(DateTimeKind::DateTime, s);
+
+        assert_eq!(
+            date_time("2002-10-10T12:00:00.500-05:00").to_string(),
+            "2002-10-10T12:00:00.5-05:00"
+        );
+        assert_eq!(
+            date_time("1999-12-31T24:00:00Z").to_string(),
+            "2000-01-01T00:00:00Z"
+        );
+        assert_eq!(
+            date_time("-0044-03-15T00:00:00").to_string(),
+            "-0044-03-15T00:00:00"
+        );
+        assert_eq!(
+            parse(DateTimeKind::GMonthDay, "--02-29").to_string(),
+            "--02-29"
+        );
+        assert_eq!(
+            parse(DateTimeKind::Time, "24:00:00").to_string(),
+            "00:00:00"
+        );
+        for (kind, invalid) in [
+            (DateTimeKind::DateTime, "2001-02-29T00:00:00"),
+            (DateTimeKind::DateTime, "2001-01-01T00:00"),
+            (DateTimeKind::DateTime, "01-01-01T00:00:00"),
+            (DateTimeKind::Date, "02001-01-01"),
+            (DateTimeKind::Date, "2001-01-01+14:30"),
+            (DateTimeKind::Time, "24:00:01"),
+            (DateTimeKind::GYear, "2001-01"),
+            (DateTimeKind::GDay, "--01"),
+        ] {
+            assert_eq!(DateTime::parse(kind, invalid), None, "{}", invalid);
+        }
+
+        let a = date_time("2002-10-10T12:00:00-05:00");
+        assert!(a.equal(&date_time("2002-10-10T17:00:00Z")));
+        assert!(!a.equal(&date_time("2002-10-10T17:00:00")));
+        assert_eq!(
+            a.partial_cmp(&date_time("2002-10-10T12:00:01-05:00")),
+            Some(Ordering::Less)
+        );
+        assert_eq!(a.partial_cmp(&date_time("2002-10-11T00:00:00")), None);
+        assert_eq!(
+            a.partial_cmp(&date_time("2002-10-11T08:00:00")),
+            Some(Ordering::Less)
+        );
+    }
+
+    #[test]
+    fn reads_and_orders_durations() {
+        let parse = |s| Duration::parse(DurationKind::Duration, s).unwrap();
+        assert_eq!(parse("P1Y14M").to_string(), "P2Y2M");
+        assert_eq!(parse("PT36H").to_string(), "P1DT12H");
+        assert_eq!(parse("-PT0.50S").to_string(), "-PT0.5S");
+        assert_eq!(parse("P0D").to_string(), "PT0S");
+        for invalid in ["P", "PT", "P1S", "P1M1Y", "1Y", "PT1.5M", "P-1D"] {
+            assert!(
+                Duration::parse(DurationKind::Duration, invalid).is_err(),
+                "{}",
+                invalid
+            );
+        }
+        assert!(Duration::parse(DurationKind::YearMonth, "P1D").is_err());
+        assert!(Duration::parse(DurationKind::DayTime, "P1Y").is_err());
+
+        assert_eq!(parse("P1M").partial_cmp(&parse("P30D")), None);
+        assert_eq!(
+            parse("P1M").partial_cmp(&parse("P32D")),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            parse("P1Y").partial_cmp(&parse("P12M")),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            parse("PT24H").partial_cmp(&parse("P1D")),
+            Some(Ordering::Equal)
+        );
+    }
+}
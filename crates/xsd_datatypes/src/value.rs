@@ -0,0 +1,232 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+use document::node::QName;
+
+use crate::decimal::Decimal;
+use crate::temporal::{DateTime, Duration};
+
+/// A value in the value space of a built-in datatype. Types derived from
+/// the same primitive share a variant: `xs:int` values are decimals, and
+/// `xs:token` values strings.
+///
+/// `==` and `partial_cmp` follow XSD equality and order: `0.0E0` equals
+/// `-0.0E0`, `NaN` equals nothing, and values whose order XSD leaves open
+/// are incomparable. [`Value::identical`] is the stricter relation that
+/// `enumeration` uses.
+#[derive(Debug, Clone)]
+pub enum Value {
+    String(String),
+    Boolean(bool),
+    Decimal(Decimal),
+    Float(f32),
+    Double(f64),
+    Duration(Duration),
+    DateTime(DateTime),
+    HexBinary(Vec<u8>),
+    Base64Binary(Vec<u8>),
+    AnyUri(String),
+    QName(QName),
+    Notation(QName),
+    List(Vec<Value>),
+}
+
+impl Value {
+    /// Whether the values are the same, which unlike equality tells `0.0E0`
+    /// from `-0.0E0`, makes `NaN` identical to itself and tells dateTimes
+    /// in different timezones apart.
+    pub fn identical(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Float(a), Value::Float(b)) => {
+                a.to_bits() == b.to_bits() || a.is_nan() && b.is_nan()
+            }
+            (Value::Double(a), Value::Double(b)) => {
+                a.to_bits() == b.to_bits() || a.is_nan() && b.is_nan()
+            }
+            (Value::DateTime(a), Value::DateTime(b)) => a == b,
+            (Value::List(a), Value::List(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.identical(b))
+            }
+            _ => self == other,
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::String(a), Value::String(b)) | (Value::AnyUri(a), Value::AnyUri(b)) => a == b,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Decimal(a), Value::Decimal(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::Double(a), Value::Double(b)) => a == b,
+            (Value::Duration(a), Value::Duration(b)) => a == b,
+            (Value::DateTime(a), Value::DateTime(b)) => a.equal(b),
+            (Value::HexBinary(a), Value::HexBinary(b))
+            | (Value::Base64Binary(a), Value::Base64Binary(b)) => a == b,
+            (Value::QName(a), Value::QName(b)) | (Value::Notation(a), Value::Notation(b)) => {
+                a.namespace == b.namespace && a.local_name == b.local_name
+            }
+            (Value::List(a), Value::List(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    /// Orders the numeric, duration and date and time values. Values of
+    /// the other datatypes are only ever equal or incomparable.
+    fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Decimal(a), Value::Decimal(b)) => Some(a.cmp(b)),
+            (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+            (Value::Double(a), Value::Double(b)) => a.partial_cmp(b),
+            (Value::Duration(a), Value::Duration(b)) => a.partial_cmp(b),
+            (Value::DateTime(a), Value::DateTime(b)) => a.partial_cmp(b),
+            _ => (self == other).then_some(Ordering::Equal),
+        }
+    }
+}
+
+/// Writes the canonical representation of the value. QNames keep the
+/// prefix they were written with.
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::String(s) | Value::AnyUri(s) => f.write_str(s),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Decimal(d) => write!(f, "{}", d),
+            Value::Float(x) => write_floating(f, f64::from(*x), format!("{:E}", x)),
+            Value::Double(x) => write_floating(f, *x, format!("{:E}", x)),
+            Value::Duration(d) => write!(f, "{}", d),
+            Value::DateTime(d) => write!(f, "{}", d),
+            Value::HexBinary(octets) => {
+                for octet in octets {
+                    write!(f, "{:02X}", octet)?;
+                }
+                Ok(())
+            }
+            Value::Base64Binary(octets) => f.write_str(&encode_base64(octets)),
+            Value::QName(name) | Value::Notation(name) => write!(f, "{}", name),
+            Value::List(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Writes `INF`, `-INF`, `NaN`, or scientific notation with at least one
+/// digit after the point, such as `1.0E0`.
+fn write_floating(f: &mut Formatter<'_>, x: f64, scientific: String) -> std::fmt::Result {
+    if x.is_nan() {
+        return f.write_str("NaN");
+    }
+    if x.is_infinite() {
+        return f.write_str(if x < 0.0 { "-INF" } else { "INF" });
+    }
+    let (mantissa, exponent) = scientific.split_once('E').expect("an exponent");
+    if mantissa.contains('.') {
+        write!(f, "{}E{}", mantissa, exponent)
+    } else {
+        write!(f, "{}.0E{}", mantissa, exponent)
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn encode_base64(octets: &[u8]) -> String {
+    let mut encoded = String::with_capacity(octets.len().div_ceil(3) * 4);
+    for chunk in octets.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &octet)| {
+            bits | u32::from(octet) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (bits >> (18 - 6 * i)) & 0x3F;
+                encoded.push(char::from(BASE64_ALPHABET[index as usize]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Reads base64 with whitespace already removed, rejecting padding that
+/// leaves bits unused.
+pub(crate) fn decode_base64(s: &str) -> Option<Vec<u8>> {
+    let bytes = s.as_bytes();
+    if !bytes.len().is_multiple_of(4) {
+        return None;
+    }
+    let mut octets = Vec::with_capacity(bytes.len() / 4 * 3);
+    for (n, chunk) in bytes.chunks(4).enumerate() {
+        let last = n == bytes.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|&&b| b == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return None;
+        }
+        let mut bits = 0u32;
+        for &b in &chunk[..4 - padding] {
+            let index = BASE64_ALPHABET.iter().position(|&a| a == b)?;
+            bits = bits << 6 | index as u32;
+        }
+        bits <<= 6 * padding as u32;
+        let unused = (1u32 << (8 * padding as u32)) - 1;
+        if bits & unused != 0 {
+            return None;
+        }
+        octets.extend_from_slice(&bits.to_be_bytes()[1..4 - padding]);
+    }
+    Some(octets)
+}
+
+pub(crate) fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) || !s.is_ascii() {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::value::{decode_base64, Value};
+
+    #[test]
+    fn writes_canonical_values() {
+        assert_eq!(Value::Double(1.0).to_string(), "1.0E0");
+        assert_eq!(Value::Double(-1250.5).to_string(), "-1.2505E3");
+        assert_eq!(Value::Float(0.001).to_string(), "1.0E-3");
+        assert_eq!(Value::Double(f64::NEG_INFINITY).to_string(), "-INF");
+        assert_eq!(Value::HexBinary(vec![0x0F, 0xB7]).to_string(), "0FB7");
+        for (octets, encoded) in [(&b"Man"[..], "TWFu"), (b"Ma", "TWE="), (b"M", "TQ==")] {
+            assert_eq!(Value::Base64Binary(octets.to_vec()).to_string(), encoded);
+            assert_eq!(decode_base64(encoded).as_deref(), Some(octets));
+        }
+        assert_eq!(decode_base64("TWF="), None);
+        assert_eq!(decode_base64("TQ==TQ=="), None);
+    }
+
+    #[test]
+    fn compares_and_identifies_values() {
+        assert_eq!(Value::Double(0.0), Value::Double(-0.0));
+        assert!(!Value::Double(0.0).identical(&Value::Double(-0.0)));
+        assert_ne!(Value::Double(f64::NAN), Value::Double(f64::NAN));
+        assert!(Value::Double(f64::NAN).identical(&Value::Double(f64::NAN)));
+        assert_eq!(Value::Float(1.0).partial_cmp(&Value::Double(1.0)), None);
+        assert_eq!(
+            Value::String("a".to_owned()).partial_cmp(&Value::String("b".to_owned())),
+            None
+        );
+    }
+}